@@ -0,0 +1,273 @@
+//! Check a patch against the current file contents without writing anything,
+//! reporting every hunk that would not apply instead of stopping at the first.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use similar::TextDiff;
+
+use crate::ChunkMismatch;
+use crate::Hunk;
+use crate::ParseError;
+use crate::locate_chunk;
+use crate::parse_patch;
+use crate::read_original_lines;
+
+/// Regions less similar than this to the expected lines are not suggested.
+const MIN_SUGGESTION_SIMILARITY: f32 = 0.6;
+
+/// Result of [`dry_run_hunks`]: the conflicts found, empty if the patch
+/// applies cleanly.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct DryRunReport {
+    pub conflicts: Vec<PatchConflict>,
+}
+
+impl DryRunReport {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// A hunk, or a chunk of an update hunk, that does not match the file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PatchConflict {
+    /// Path as written in the patch.
+    pub path: PathBuf,
+    /// 1-based index of the `@@` chunk within the file's update hunk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<usize>,
+    pub reason: ConflictReason,
+    /// The lines the patch expected to find.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub expected_lines: Vec<String>,
+    /// The most similar region of the file, if any is close enough.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<FuzzyMatch>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    /// The file to update or delete does not exist or cannot be read.
+    FileNotFound,
+    /// The file is outside the directories a dry run may read.
+    OutsideReadableRoots,
+    /// The `@@` context line of a chunk was not found.
+    ContextNotFound,
+    /// The lines a chunk replaces were not found.
+    LinesNotFound,
+}
+
+/// A region of the file that resembles the expected lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FuzzyMatch {
+    /// 1-based line number where the region starts.
+    pub line: usize,
+    pub lines: Vec<String>,
+    /// Similarity to the expected lines, from 0 to 1.
+    pub similarity: f32,
+}
+
+/// If `argv` is `["apply_patch", "--dry-run", patch]`, parse the patch.
+pub fn maybe_parse_apply_patch_dry_run(argv: &[String]) -> Option<Result<Vec<Hunk>, ParseError>> {
+    match argv {
+        [cmd, flag, body] if cmd == "apply_patch" && flag == "--dry-run" => Some(parse_patch(body)),
+        _ => None,
+    }
+}
+
+/// Check every hunk against the files under `cwd`. A dry run needs no
+/// approval, so only files inside `readable_roots` are read; the others are
+/// reported as [`ConflictReason::OutsideReadableRoots`].
+pub fn dry_run_hunks(hunks: &[Hunk], cwd: &Path, readable_roots: &[PathBuf]) -> DryRunReport {
+    let mut conflicts = Vec::new();
+    for hunk in hunks {
+        let resolved = hunk.resolve_path(cwd);
+        let path = match hunk {
+            Hunk::AddFile { .. } => continue,
+            Hunk::DeleteFile { path } | Hunk::UpdateFile { path, .. } => path,
+        };
+        if let Err(reason) = check_readable(&resolved, readable_roots) {
+            conflicts.push(file_conflict(path, reason));
+            continue;
+        }
+        match hunk {
+            Hunk::AddFile { .. } => {}
+            Hunk::DeleteFile { path } => {
+                if !resolved.is_file() {
+                    conflicts.push(file_conflict(path, ConflictReason::FileNotFound));
+                }
+            }
+            Hunk::UpdateFile { path, chunks, .. } => {
+                let Ok((_, original_lines)) = read_original_lines(&resolved) else {
+                    conflicts.push(file_conflict(path, ConflictReason::FileNotFound));
+                    continue;
+                };
+                let mut line_index = 0;
+                for (index, chunk) in chunks.iter().enumerate() {
+                    let (reason, expected_lines) =
+                        match locate_chunk(&original_lines, chunk, line_index) {
+                            Ok(located) => {
+                                line_index = located.next_line_index;
+                                continue;
+                            }
+                            Err(ChunkMismatch::Context) => (
+                                ConflictReason::ContextNotFound,
+                                chunk.change_context.iter().cloned().collect::<Vec<_>>(),
+                            ),
+                            Err(ChunkMismatch::Lines) => {
+                                let mut expected = chunk.old_lines.clone();
+                                if expected.last().is_some_and(|line| line.is_empty()) {
+                                    expected.pop();
+                                }
+                                (ConflictReason::LinesNotFound, expected)
+                            }
+                        };
+                    conflicts.push(PatchConflict {
+                        path: path.clone(),
+                        chunk: Some(index + 1),
+                        reason,
+                        suggestion: closest_region(&original_lines, &expected_lines),
+                        expected_lines,
+                    });
+                }
+            }
+        }
+    }
+    DryRunReport { conflicts }
+}
+
+/// Whether `path` exists and, with symlinks and `..` resolved, is inside one
+/// of `roots`.
+fn check_readable(path: &Path, roots: &[PathBuf]) -> Result<(), ConflictReason> {
+    let path = path
+        .canonicalize()
+        .map_err(|_| ConflictReason::FileNotFound)?;
+    if roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| path.starts_with(root))
+    {
+        Ok(())
+    } else {
+        Err(ConflictReason::OutsideReadableRoots)
+    }
+}
+
+fn file_conflict(path: &Path, reason: ConflictReason) -> PatchConflict {
+    PatchConflict {
+        path: path.to_path_buf(),
+        chunk: None,
+        reason,
+        expected_lines: Vec::new(),
+        suggestion: None,
+    }
+}
+
+/// The region of `lines` with as many lines as `expected` that is most
+/// similar to it.
+fn closest_region(lines: &[String], expected: &[String]) -> Option<FuzzyMatch> {
+    if expected.is_empty() || expected.len() > lines.len() {
+        return None;
+    }
+    let expected_text = expected.join("\n");
+    let (start, similarity) = (0..=lines.len() - expected.len())
+        .map(|start| {
+            let candidate = lines[start..start + expected.len()].join("\n");
+            let similarity =
+                TextDiff::from_chars(expected_text.as_str(), candidate.as_str()).ratio();
+            (start, similarity)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    (similarity >= MIN_SUGGESTION_SIMILARITY).then(|| FuzzyMatch {
+        line: start + 1,
+        lines: lines[start..start + expected.len()].to_vec(),
+        similarity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::tempdir;
+
+    fn dry_run(patch: &str, cwd: &Path) -> DryRunReport {
+        let argv = vec![
+            "apply_patch".to_string(),
+            "--dry-run".to_string(),
+            patch.to_string(),
+        ];
+        let hunks = maybe_parse_apply_patch_dry_run(&argv).unwrap().unwrap();
+        dry_run_hunks(&hunks, cwd, &[cwd.to_path_buf()])
+    }
+
+    #[test]
+    fn clean_patch_has_no_conflicts() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        let report = dry_run(
+            "*** Begin Patch\n*** Update File: a.txt\n@@\n-two\n+2\n*** End Patch",
+            dir.path(),
+        );
+        assert!(report.is_clean());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+    }
+
+    #[test]
+    fn reports_every_conflict_with_suggestions() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.rs"),
+            "fn main() {\n    println!(\"hello\");\n}\n",
+        )
+        .unwrap();
+        let report = dry_run(
+            "*** Begin Patch\n*** Update File: a.rs\n@@\n-    println!(\"helo\");\n+    println!(\"bye\");\n@@ fn other()\n-x\n+y\n*** Delete File: missing.rs\n*** End Patch",
+            dir.path(),
+        );
+        assert_eq!(report.conflicts.len(), 3);
+
+        let lines = &report.conflicts[0];
+        assert_eq!(lines.reason, ConflictReason::LinesNotFound);
+        assert_eq!(lines.chunk, Some(1));
+        let suggestion = lines.suggestion.as_ref().unwrap();
+        assert_eq!(suggestion.line, 2);
+        assert_eq!(
+            suggestion.lines,
+            vec!["    println!(\"hello\");".to_string()]
+        );
+
+        let context = &report.conflicts[1];
+        assert_eq!(context.reason, ConflictReason::ContextNotFound);
+        assert_eq!(context.expected_lines, vec!["fn other()".to_string()]);
+
+        assert_eq!(report.conflicts[2].reason, ConflictReason::FileNotFound);
+        assert_eq!(report.conflicts[2].path, PathBuf::from("missing.rs"));
+    }
+
+    #[test]
+    fn files_outside_the_readable_roots_are_not_read() {
+        let root = tempdir().unwrap();
+        let cwd = root.path().join("repo");
+        std::fs::create_dir(&cwd).unwrap();
+        let secret = root.path().join("secret.txt");
+        std::fs::write(&secret, "token = abc\n").unwrap();
+        let patch = format!(
+            "*** Begin Patch\n*** Update File: {}\n@@\n-token = abd\n+x\n*** Update File: ../secret.txt\n@@\n-token = abd\n+x\n*** End Patch",
+            secret.display()
+        );
+        let report = dry_run(&patch, &cwd);
+        assert_eq!(report.conflicts.len(), 2);
+        for conflict in &report.conflicts {
+            assert_eq!(conflict.reason, ConflictReason::OutsideReadableRoots);
+            assert_eq!(conflict.suggestion, None);
+        }
+    }
+}
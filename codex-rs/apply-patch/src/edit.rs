@@ -0,0 +1,187 @@
+//! Find/replace edits of a single file: a lighter-weight alternative to a
+//! full patch for small tweaks, producing the same [`ApplyPatchAction`] so the
+//! edit is approved and applied like any other patch.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use regex_lite::Regex;
+use serde::Deserialize;
+use similar::TextDiff;
+use thiserror::Error;
+
+use crate::ApplyPatchAction;
+use crate::ApplyPatchFileChange;
+
+/// Arguments of the `edit_file` tool.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EditFileArgs {
+    /// File to edit, relative to the working directory.
+    pub path: PathBuf,
+    /// Text to find, or a regular expression if `regex` is set.
+    pub old_string: String,
+    /// Replacement text. With `regex`, `$1`/`${name}` refer to capture groups.
+    pub new_string: String,
+    #[serde(default)]
+    pub regex: bool,
+    /// How many matches the file must contain; every one of them is replaced.
+    #[serde(default = "default_expected_occurrences")]
+    pub expected_occurrences: usize,
+}
+
+fn default_expected_occurrences() -> usize {
+    1
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum EditError {
+    #[error("failed to read {}: {message}", path.display())]
+    ReadFile { path: PathBuf, message: String },
+    #[error("invalid regex: {0}")]
+    InvalidRegex(String),
+    #[error("old_string must not be empty")]
+    EmptyPattern,
+    #[error("expected {expected} occurrence(s) of old_string in {}, found {found}", path.display())]
+    OccurrenceMismatch {
+        path: PathBuf,
+        expected: usize,
+        found: usize,
+    },
+    #[error("the edit does not change {}", path.display())]
+    Unchanged { path: PathBuf },
+}
+
+/// The result of [`plan_edit`], not yet written to disk.
+#[derive(Debug, PartialEq)]
+pub struct PlannedEdit {
+    pub action: ApplyPatchAction,
+    pub unified_diff: String,
+    pub occurrences: usize,
+}
+
+/// Compute the new contents of the file named by `args`, resolved against
+/// `cwd`, checking that `old_string` matches exactly `expected_occurrences`
+/// times.
+pub fn plan_edit(args: &EditFileArgs, cwd: &Path) -> Result<PlannedEdit, EditError> {
+    if args.old_string.is_empty() {
+        return Err(EditError::EmptyPattern);
+    }
+    let path = cwd.join(&args.path);
+    let original = std::fs::read_to_string(&path).map_err(|e| EditError::ReadFile {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+
+    let (occurrences, new_content) = if args.regex {
+        let regex =
+            Regex::new(&args.old_string).map_err(|e| EditError::InvalidRegex(e.to_string()))?;
+        let occurrences = regex.find_iter(&original).count();
+        let new_content = regex
+            .replace_all(&original, args.new_string.as_str())
+            .into_owned();
+        (occurrences, new_content)
+    } else {
+        let occurrences = original.matches(args.old_string.as_str()).count();
+        let new_content = original.replace(&args.old_string, &args.new_string);
+        (occurrences, new_content)
+    };
+
+    if occurrences != args.expected_occurrences {
+        return Err(EditError::OccurrenceMismatch {
+            path,
+            expected: args.expected_occurrences,
+            found: occurrences,
+        });
+    }
+    if new_content == original {
+        return Err(EditError::Unchanged { path });
+    }
+
+    let unified_diff = TextDiff::from_lines(&original, &new_content)
+        .unified_diff()
+        .context_radius(1)
+        .to_string();
+    let change = ApplyPatchFileChange::Update {
+        unified_diff: unified_diff.clone(),
+        move_path: None,
+        new_content,
+    };
+    Ok(PlannedEdit {
+        action: ApplyPatchAction {
+            changes: HashMap::from([(path, change)]),
+        },
+        unified_diff,
+        occurrences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    fn args(old: &str, new: &str, regex: bool, expected_occurrences: usize) -> EditFileArgs {
+        EditFileArgs {
+            path: PathBuf::from("a.txt"),
+            old_string: old.to_string(),
+            new_string: new.to_string(),
+            regex,
+            expected_occurrences,
+        }
+    }
+
+    #[test]
+    fn replaces_exact_and_regex_matches() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "let x = 1;\nlet y = 2;\n").unwrap();
+
+        let planned = plan_edit(&args("x = 1", "x = 10", false, 1), dir.path()).unwrap();
+        assert_eq!(planned.occurrences, 1);
+        assert_eq!(
+            planned.unified_diff,
+            "@@ -1,2 +1,2 @@\n-let x = 1;\n+let x = 10;\n let y = 2;\n"
+        );
+        // Nothing is written until the action is applied.
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "let x = 1;\nlet y = 2;\n"
+        );
+
+        let planned = plan_edit(&args(r"let (\w)", "const $1", true, 2), dir.path()).unwrap();
+        let (_, change) = planned.action.changes().iter().next().unwrap();
+        let ApplyPatchFileChange::Update { new_content, .. } = change else {
+            panic!("expected an update, got {change:?}");
+        };
+        assert_eq!(new_content, "const x = 1;\nconst y = 2;\n");
+    }
+
+    #[test]
+    fn rejects_unexpected_occurrences() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a a a\n").unwrap();
+
+        let err = plan_edit(&args("a", "b", false, 1), dir.path()).unwrap_err();
+        assert_eq!(
+            err,
+            EditError::OccurrenceMismatch {
+                path: dir.path().join("a.txt"),
+                expected: 1,
+                found: 3,
+            }
+        );
+        assert_eq!(
+            plan_edit(&args("a", "a", false, 3), dir.path()).unwrap_err(),
+            EditError::Unchanged {
+                path: dir.path().join("a.txt"),
+            }
+        );
+        assert!(matches!(
+            plan_edit(&args("(", "b", true, 1), dir.path()).unwrap_err(),
+            EditError::InvalidRegex(_)
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+mod dry_run;
+mod edit;
 mod parser;
 mod seek_sequence;
 
@@ -8,6 +10,16 @@ use std::str::Utf8Error;
 
 use anyhow::Context;
 use anyhow::Result;
+pub use dry_run::ConflictReason;
+pub use dry_run::DryRunReport;
+pub use dry_run::FuzzyMatch;
+pub use dry_run::PatchConflict;
+pub use dry_run::dry_run_hunks;
+pub use dry_run::maybe_parse_apply_patch_dry_run;
+pub use edit::EditError;
+pub use edit::EditFileArgs;
+pub use edit::PlannedEdit;
+pub use edit::plan_edit;
 pub use parser::Hunk;
 pub use parser::ParseError;
 use parser::ParseError::*;
@@ -422,6 +434,23 @@ fn derive_new_contents_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
 ) -> std::result::Result<AppliedPatch, ApplyPatchError> {
+    let (original_contents, original_lines) = read_original_lines(path)?;
+
+    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    let new_lines = apply_replacements(original_lines, &replacements);
+    let mut new_lines = new_lines;
+    if !new_lines.last().is_some_and(|s| s.is_empty()) {
+        new_lines.push(String::new());
+    }
+    let new_contents = new_lines.join("\n");
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
+
+/// Read the file at `path`, returning its contents and its lines.
+fn read_original_lines(path: &Path) -> std::result::Result<(String, Vec<String>), ApplyPatchError> {
     let original_contents = match std::fs::read_to_string(path) {
         Ok(contents) => contents,
         Err(err) => {
@@ -442,18 +471,7 @@ fn derive_new_contents_from_chunks(
     if original_lines.last().is_some_and(|s| s.is_empty()) {
         original_lines.pop();
     }
-
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
-    let new_lines = apply_replacements(original_lines, &replacements);
-    let mut new_lines = new_lines;
-    if !new_lines.last().is_some_and(|s| s.is_empty()) {
-        new_lines.push(String::new());
-    }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    Ok((original_contents, original_lines))
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -468,80 +486,113 @@ fn compute_replacements(
     let mut line_index: usize = 0;
 
     for chunk in chunks {
-        // If a chunk has a `change_context`, we use seek_sequence to find it, then
-        // adjust our `line_index` to continue from there.
-        if let Some(ctx_line) = &chunk.change_context {
-            if let Some(idx) =
-                seek_sequence::seek_sequence(original_lines, &[ctx_line.clone()], line_index, false)
-            {
-                line_index = idx + 1;
-            } else {
+        match locate_chunk(original_lines, chunk, line_index) {
+            Ok(located) => {
+                replacements.push(located.replacement);
+                line_index = located.next_line_index;
+            }
+            Err(ChunkMismatch::Context) => {
                 return Err(ApplyPatchError::ComputeReplacements(format!(
                     "Failed to find context '{}' in {}",
-                    ctx_line,
+                    chunk.change_context.as_deref().unwrap_or_default(),
+                    path.display()
+                )));
+            }
+            Err(ChunkMismatch::Lines) => {
+                return Err(ApplyPatchError::ComputeReplacements(format!(
+                    "Failed to find expected lines {:?} in {}",
+                    chunk.old_lines,
                     path.display()
                 )));
             }
         }
+    }
 
-        if chunk.old_lines.is_empty() {
-            // Pure addition (no old lines). We'll add them at the end or just
-            // before the final empty line if one exists.
-            let insertion_idx = if original_lines.last().is_some_and(|s| s.is_empty()) {
-                original_lines.len() - 1
-            } else {
-                original_lines.len()
-            };
-            replacements.push((insertion_idx, 0, chunk.new_lines.clone()));
-            continue;
-        }
-
-        // Otherwise, try to match the existing lines in the file with the old lines
-        // from the chunk. If found, schedule that region for replacement.
-        // Attempt to locate the `old_lines` verbatim within the file.  In many
-        // real‑world diffs the last element of `old_lines` is an *empty* string
-        // representing the terminating newline of the region being replaced.
-        // This sentinel is not present in `original_lines` because we strip the
-        // trailing empty slice emitted by `split('\n')`.  If a direct search
-        // fails and the pattern ends with an empty string, retry without that
-        // final element so that modifications touching the end‑of‑file can be
-        // located reliably.
-
-        let mut pattern: &[String] = &chunk.old_lines;
-        let mut found =
-            seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
+    Ok(replacements)
+}
 
-        let mut new_slice: &[String] = &chunk.new_lines;
+/// Why [`locate_chunk`] could not place a chunk in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkMismatch {
+    /// The chunk's `@@` context line was not found.
+    Context,
+    /// The chunk's old lines were not found.
+    Lines,
+}
 
-        if found.is_none() && pattern.last().is_some_and(|s| s.is_empty()) {
-            // Retry without the trailing empty line which represents the final
-            // newline in the file.
-            pattern = &pattern[..pattern.len() - 1];
-            if new_slice.last().is_some_and(|s| s.is_empty()) {
-                new_slice = &new_slice[..new_slice.len() - 1];
-            }
+/// Where [`locate_chunk`] placed a chunk in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LocatedChunk {
+    /// The `(start_index, old_len, new_lines)` replacement.
+    replacement: (usize, usize, Vec<String>),
+    /// The line index to search the next chunk from.
+    next_line_index: usize,
+}
 
-            found = seek_sequence::seek_sequence(
-                original_lines,
-                pattern,
-                line_index,
-                chunk.is_end_of_file,
-            );
-        }
+/// Find where `chunk` applies in `original_lines`, searching from
+/// `line_index`.
+fn locate_chunk(
+    original_lines: &[String],
+    chunk: &UpdateFileChunk,
+    mut line_index: usize,
+) -> std::result::Result<LocatedChunk, ChunkMismatch> {
+    // If a chunk has a `change_context`, we use seek_sequence to find it, then
+    // adjust our `line_index` to continue from there.
+    if let Some(ctx_line) = &chunk.change_context {
+        let idx =
+            seek_sequence::seek_sequence(original_lines, &[ctx_line.clone()], line_index, false)
+                .ok_or(ChunkMismatch::Context)?;
+        line_index = idx + 1;
+    }
 
-        if let Some(start_idx) = found {
-            replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
-            line_index = start_idx + pattern.len();
+    if chunk.old_lines.is_empty() {
+        // Pure addition (no old lines). We'll add them at the end or just
+        // before the final empty line if one exists.
+        let insertion_idx = if original_lines.last().is_some_and(|s| s.is_empty()) {
+            original_lines.len() - 1
         } else {
-            return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines {:?} in {}",
-                chunk.old_lines,
-                path.display()
-            )));
+            original_lines.len()
+        };
+        return Ok(LocatedChunk {
+            replacement: (insertion_idx, 0, chunk.new_lines.clone()),
+            next_line_index: line_index,
+        });
+    }
+
+    // Otherwise, try to match the existing lines in the file with the old lines
+    // from the chunk. If found, schedule that region for replacement.
+    // Attempt to locate the `old_lines` verbatim within the file.  In many
+    // real‑world diffs the last element of `old_lines` is an *empty* string
+    // representing the terminating newline of the region being replaced.
+    // This sentinel is not present in `original_lines` because we strip the
+    // trailing empty slice emitted by `split('\n')`.  If a direct search
+    // fails and the pattern ends with an empty string, retry without that
+    // final element so that modifications touching the end‑of‑file can be
+    // located reliably.
+
+    let mut pattern: &[String] = &chunk.old_lines;
+    let mut found =
+        seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
+
+    let mut new_slice: &[String] = &chunk.new_lines;
+
+    if found.is_none() && pattern.last().is_some_and(|s| s.is_empty()) {
+        // Retry without the trailing empty line which represents the final
+        // newline in the file.
+        pattern = &pattern[..pattern.len() - 1];
+        if new_slice.last().is_some_and(|s| s.is_empty()) {
+            new_slice = &new_slice[..new_slice.len() - 1];
         }
+
+        found =
+            seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
     }
 
-    Ok(replacements)
+    let start_idx = found.ok_or(ChunkMismatch::Lines)?;
+    Ok(LocatedChunk {
+        replacement: (start_idx, pattern.len(), new_slice.to_vec()),
+        next_line_index: start_idx + pattern.len(),
+    })
 }
 
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
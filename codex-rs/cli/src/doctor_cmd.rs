@@ -0,0 +1,484 @@
+//! `codex doctor` – check that the environment is ready for Codex (CODEX_HOME,
+//! config.toml, credentials, MCP servers, the sandbox and the terminal) and
+//! print how to fix whatever is not.
+
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config::find_codex_home;
+use codex_core::exec::StdioPolicy;
+use codex_core::exec::spawn_command_under_linux_sandbox;
+use codex_core::exec::spawn_command_under_seatbelt;
+use codex_core::inspect_mcp_server;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::auth_file_path;
+use codex_login::read_account_info;
+use codex_login::try_read_openai_api_key;
+
+/// How long an MCP server may take to start and list its tools.
+const MCP_SERVER_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Parser)]
+pub struct DoctorCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Codex works, but not as well as it could.
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every check, print the results and exit with status 1 if any failed.
+pub async fn run_doctor(
+    doctor_cli: DoctorCli,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut checks = Vec::new();
+
+    match find_codex_home() {
+        Ok(codex_home) => checks.extend(check_codex_home(&codex_home)),
+        Err(err) => checks.push(Check::fail(
+            "codex home",
+            format!("cannot locate CODEX_HOME: {err}"),
+            "set $CODEX_HOME to an existing directory, or $HOME so that ~/.codex can be used",
+        )),
+    }
+
+    let cli_overrides = doctor_cli
+        .config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(
+        cli_overrides,
+        ConfigOverrides {
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        },
+    );
+    match &config {
+        Ok(config) => {
+            checks.push(Check::pass(
+                "config",
+                format!("{} loaded", config.codex_home.join("config.toml").display()),
+            ));
+            checks.push(check_auth(config).await);
+            checks.extend(check_mcp_servers(config).await);
+            checks.push(check_sandbox(config).await);
+        }
+        Err(err) => checks.push(Check::fail(
+            "config",
+            format!("config.toml could not be loaded: {err}"),
+            "fix the reported error with `codex config edit`; auth, MCP and sandbox checks were skipped",
+        )),
+    }
+
+    checks.push(check_truecolor(std::env::var("COLORTERM").ok().as_deref()));
+    checks.push(check_keyboard_enhancement());
+
+    print_checks(&checks);
+    if checks.iter().any(|check| check.status == CheckStatus::Fail) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// CODEX_HOME must be a writable directory; credentials in it should only be
+/// readable by the user.
+fn check_codex_home(codex_home: &Path) -> Vec<Check> {
+    const NAME: &str = "codex home";
+    if !codex_home.exists() {
+        return vec![Check::warn(
+            NAME,
+            format!("{} does not exist", codex_home.display()),
+            format!(
+                "create it with `mkdir -p {}`; sessions, history and logins are stored there",
+                codex_home.display()
+            ),
+        )];
+    }
+    if !codex_home.is_dir() {
+        return vec![Check::fail(
+            NAME,
+            format!("{} is not a directory", codex_home.display()),
+            "move the file out of the way or point $CODEX_HOME at a directory",
+        )];
+    }
+    let probe = codex_home.join(format!(".doctor-{}", std::process::id()));
+    if let Err(err) = std::fs::write(&probe, b"") {
+        return vec![Check::fail(
+            NAME,
+            format!("{} is not writable: {err}", codex_home.display()),
+            format!(
+                "make it writable by your user, e.g. `chmod u+rwx {}`",
+                codex_home.display()
+            ),
+        )];
+    }
+    let _ = std::fs::remove_file(&probe);
+
+    let mut checks = vec![Check::pass(NAME, codex_home.display().to_string())];
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let auth_path = codex_home.join("auth.json");
+        if let Ok(metadata) = std::fs::metadata(&auth_path) {
+            let mode = metadata.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                checks.push(Check::warn(
+                    "credentials",
+                    format!(
+                        "{} is readable by other users (mode {mode:o})",
+                        auth_path.display()
+                    ),
+                    format!("run `chmod 600 {}`", auth_path.display()),
+                ));
+            }
+        }
+    }
+    checks
+}
+
+async fn check_auth(config: &Config) -> Check {
+    const NAME: &str = "auth";
+    let Some(env_key) = &config.model_provider.env_key else {
+        return Check::pass(
+            NAME,
+            format!(
+                "provider `{}` does not need an API key",
+                config.model_provider_id
+            ),
+        );
+    };
+    let account = config.account.as_deref();
+    if account.is_none()
+        && config
+            .model_provider
+            .api_key()
+            .is_ok_and(|key| key.is_some())
+    {
+        return Check::pass(NAME, format!("API key from ${env_key}"));
+    }
+    let (login, login_cmd) = match account {
+        Some(account) => (
+            format!("the login of account `{account}`"),
+            format!("codex login --profile {account}"),
+        ),
+        None => ("the ChatGPT login".to_string(), "codex login".to_string()),
+    };
+    if !auth_file_path(&config.codex_home, account).is_ok_and(|path| path.is_file()) {
+        return Check::fail(
+            NAME,
+            format!("no credentials found for {login}"),
+            match account {
+                Some(_) => format!("run `{login_cmd}`"),
+                None => format!("run `{login_cmd}` or export ${env_key}"),
+            },
+        );
+    }
+    // Reading the key refreshes an expired login, so this also tells whether
+    // the refresh token is still accepted.
+    match try_read_openai_api_key(&config.codex_home, account).await {
+        Ok(key) if !key.trim().is_empty() => {
+            let info = read_account_info(&config.codex_home, account).unwrap_or_default();
+            match config
+                .org_id
+                .as_deref()
+                .map(|org_id| info.check_org(org_id))
+            {
+                Some(Err(e)) => Check::fail(
+                    NAME,
+                    format!("{login} is valid, but {e}"),
+                    "fix `org_id` in config.toml",
+                ),
+                _ => Check::pass(NAME, format!("{login} is valid")),
+            }
+        }
+        Ok(_) => Check::fail(
+            NAME,
+            format!("{login} has no API key"),
+            format!("run `{login_cmd}` again"),
+        ),
+        Err(err) => Check::fail(
+            NAME,
+            format!("{login} could not be read or refreshed: {err}"),
+            format!("run `{login_cmd}` again"),
+        ),
+    }
+}
+
+/// Start every enabled MCP server and list its tools, as a session would.
+async fn check_mcp_servers(config: &Config) -> Vec<Check> {
+    let mut servers: Vec<_> = config
+        .mcp_servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .collect();
+    servers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut join_set = tokio::task::JoinSet::new();
+    for (index, (server_name, server)) in servers.into_iter().enumerate() {
+        let server_name = server_name.clone();
+        let server = server.clone();
+        let codex_home = config.codex_home.clone();
+        join_set.spawn(async move {
+            let name = format!("mcp `{server_name}`");
+            let inventory = tokio::time::timeout(
+                MCP_SERVER_TIMEOUT,
+                inspect_mcp_server(&server_name, server, &codex_home),
+            )
+            .await;
+            let check = match inventory {
+                Ok(Ok(inventory)) => {
+                    Check::pass(name, format!("{} tools", inventory.tools.len()))
+                }
+                Ok(Err(err)) => Check::fail(
+                    name,
+                    format!("{err:#}"),
+                    format!(
+                        "check the server with `codex mcp get {server_name}`, or turn it off with `codex mcp disable {server_name}`"
+                    ),
+                ),
+                Err(_) => Check::fail(
+                    name,
+                    format!(
+                        "did not respond within {}s",
+                        MCP_SERVER_TIMEOUT.as_secs()
+                    ),
+                    format!("check that `codex mcp get {server_name}` starts the server"),
+                ),
+            };
+            (index, check)
+        });
+    }
+
+    let mut checks = Vec::new();
+    while let Some(res) = join_set.join_next().await {
+        if let Ok(check) = res {
+            checks.push(check);
+        }
+    }
+    checks.sort_by_key(|(index, _)| *index);
+    checks.into_iter().map(|(_, check)| check).collect()
+}
+
+/// Run `true` under the platform's sandbox with a read-only policy.
+async fn check_sandbox(config: &Config) -> Check {
+    let policy = SandboxPolicy::new_read_only_policy();
+    let command = vec!["true".to_string()];
+    let env = HashMap::new();
+    let (name, child) = if cfg!(target_os = "macos") {
+        (
+            "sandbox (seatbelt)",
+            spawn_command_under_seatbelt(
+                command,
+                &policy,
+                config.cwd.clone(),
+                StdioPolicy::RedirectForShellTool,
+                env,
+            )
+            .await,
+        )
+    } else if cfg!(target_os = "linux") {
+        let Some(exe) = &config.codex_linux_sandbox_exe else {
+            return Check::fail(
+                "sandbox (landlock)",
+                "the codex-linux-sandbox helper was not found",
+                "run doctor through the `codex` binary, which bundles the helper",
+            );
+        };
+        (
+            "sandbox (landlock)",
+            spawn_command_under_linux_sandbox(
+                exe,
+                command,
+                &policy,
+                config.cwd.clone(),
+                StdioPolicy::RedirectForShellTool,
+                env,
+            )
+            .await,
+        )
+    } else {
+        return Check::warn(
+            "sandbox",
+            "no sandbox is available on this platform",
+            "run Codex in a container, or set `sandbox_backend = \"container\"` in config.toml",
+        );
+    };
+
+    let hint = if cfg!(target_os = "macos") {
+        "`/usr/bin/sandbox-exec` must be usable; inside another sandbox, use `sandbox_backend = \"container\"`"
+    } else {
+        "Landlock needs Linux 5.13+ with `landlock` listed in /sys/kernel/security/lsm; in a container, use `sandbox_backend = \"container\"` on the host or `--sandbox danger-full-access`"
+    };
+    let output = match child {
+        Ok(child) => child.wait_with_output().await,
+        Err(err) => return Check::fail(name, format!("could not start: {err}"), hint),
+    };
+    match output {
+        Ok(output) if output.status.success() => {
+            Check::pass(name, "commands run with a read-only policy")
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().last().unwrap_or("no output");
+            Check::fail(name, format!("{}: {reason}", output.status), hint)
+        }
+        Err(err) => Check::fail(name, err.to_string(), hint),
+    }
+}
+
+/// `colorterm` is the value of `$COLORTERM`, which terminals with 24-bit
+/// color set to `truecolor` or `24bit`.
+fn check_truecolor(colorterm: Option<&str>) -> Check {
+    match colorterm {
+        Some("truecolor" | "24bit") => Check::pass("truecolor", "supported"),
+        _ => Check::warn(
+            "truecolor",
+            "$COLORTERM does not announce 24-bit color; syntax highlighting uses approximate colors",
+            "if your terminal supports it, `export COLORTERM=truecolor`",
+        ),
+    }
+}
+
+fn check_keyboard_enhancement() -> Check {
+    const NAME: &str = "keyboard";
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Check::warn(
+            NAME,
+            "not running in a terminal; skipped",
+            "run `codex doctor` directly in the terminal you use Codex in",
+        );
+    }
+    match crossterm::terminal::supports_keyboard_enhancement() {
+        Ok(true) => Check::pass(NAME, "the terminal reports modified keys (kitty protocol)"),
+        Ok(false) => Check::warn(
+            NAME,
+            "the terminal does not report modified keys, so e.g. Shift+Enter is seen as Enter",
+            "use a terminal with the kitty keyboard protocol (kitty, WezTerm, foot, Ghostty, iTerm2)",
+        ),
+        Err(err) => Check::warn(
+            NAME,
+            format!("could not query the terminal: {err}"),
+            "use a terminal with the kitty keyboard protocol (kitty, WezTerm, foot, Ghostty, iTerm2)",
+        ),
+    }
+}
+
+fn print_checks(checks: &[Check]) {
+    let name_width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or_default();
+    for check in checks {
+        let status = match check.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!("{status}  {:<name_width$}  {}", check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            println!("      {:<name_width$}  hint: {hint}", "");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn codex_home_must_be_a_writable_directory() {
+        let dir = TempDir::new().unwrap();
+        let checks = check_codex_home(dir.path());
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, CheckStatus::Pass);
+
+        let missing = dir.path().join("missing");
+        assert_eq!(check_codex_home(&missing)[0].status, CheckStatus::Warn);
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(check_codex_home(&file)[0].status, CheckStatus::Fail);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn warns_about_world_readable_credentials() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let auth_path = dir.path().join("auth.json");
+        std::fs::write(&auth_path, "{}").unwrap();
+        std::fs::set_permissions(&auth_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let checks = check_codex_home(dir.path());
+        assert_eq!(checks[1].name, "credentials");
+        assert_eq!(checks[1].status, CheckStatus::Warn);
+        assert_eq!(
+            checks[1].hint,
+            Some(format!("run `chmod 600 {}`", auth_path.display()))
+        );
+    }
+
+    #[test]
+    fn truecolor_follows_colorterm() {
+        assert_eq!(check_truecolor(Some("truecolor")).status, CheckStatus::Pass);
+        assert_eq!(check_truecolor(Some("24bit")).status, CheckStatus::Pass);
+        assert_eq!(check_truecolor(None).status, CheckStatus::Warn);
+    }
+}
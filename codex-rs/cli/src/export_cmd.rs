@@ -0,0 +1,89 @@
+//! `codex export` – write a saved session to a Markdown or HTML file for
+//! sharing.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use clap::ValueEnum;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::session_store::latest_session_id;
+use codex_core::session_store::load_session;
+use codex_core::transcript_export::ExportFormat;
+use codex_core::transcript_export::default_export_file_name;
+use codex_core::transcript_export::render_transcript;
+use uuid::Uuid;
+
+#[derive(Debug, Parser)]
+pub struct ExportCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Session to export. Defaults to the most recently updated session.
+    #[arg(long = "session", value_name = "SESSION_ID")]
+    pub session_id: Option<Uuid>,
+
+    /// File to write. Defaults to `codex-session-<id>.<ext>` in the current
+    /// directory; `-` writes to stdout.
+    #[arg(long, short = 'o', value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Output format. Defaults to the extension of `--output`, or Markdown.
+    #[arg(long, value_enum)]
+    pub format: Option<ExportFormatArg>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormatArg {
+    #[value(name = "md", alias = "markdown")]
+    Markdown,
+    Html,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(arg: ExportFormatArg) -> Self {
+        match arg {
+            ExportFormatArg::Markdown => ExportFormat::Markdown,
+            ExportFormatArg::Html => ExportFormat::Html,
+        }
+    }
+}
+
+pub fn run_export(export_cli: ExportCli) -> anyhow::Result<()> {
+    let cli_overrides = export_cli
+        .config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(cli_overrides, ConfigOverrides::default())?;
+
+    let session_id = match export_cli.session_id {
+        Some(id) => id,
+        None => latest_session_id(&config.codex_home)?
+            .ok_or_else(|| anyhow::anyhow!("no saved sessions to export"))?,
+    };
+    let session = load_session(&config.codex_home, session_id)?;
+
+    let format = export_cli
+        .format
+        .map(ExportFormat::from)
+        .unwrap_or_else(|| {
+            export_cli
+                .output
+                .as_deref()
+                .map(ExportFormat::from_path)
+                .unwrap_or(ExportFormat::Markdown)
+        });
+    let rendered = render_transcript(session_id, &session, format);
+
+    match export_cli.output {
+        Some(path) if path.as_os_str() == "-" => print!("{rendered}"),
+        output => {
+            let path = output
+                .unwrap_or_else(|| PathBuf::from(default_export_file_name(session_id, format)));
+            std::fs::write(&path, rendered)?;
+            eprintln!("Exported session {session_id} to {}", path.display());
+        }
+    }
+    Ok(())
+}
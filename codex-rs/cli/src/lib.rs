@@ -1,7 +1,15 @@
 pub mod debug_sandbox;
+pub mod doctor_cmd;
 mod exit_status;
+pub mod export_cmd;
 pub mod login;
+pub mod mcp_cmd;
 pub mod proto;
+pub mod review_cmd;
+pub mod rules_cmd;
+mod single_turn;
+pub mod status_cmd;
+pub mod worktree_cmd;
 
 use clap::Parser;
 use codex_common::CliConfigOverrides;
@@ -1,34 +1,114 @@
 use codex_common::CliConfigOverrides;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_login::DEFAULT_ACCOUNT;
+use codex_login::complete_device_code_login;
+use codex_login::is_valid_account_name;
 use codex_login::login_with_chatgpt;
+use codex_login::read_account_info;
+use codex_login::request_device_code;
 
-pub async fn run_login_with_chatgpt(cli_config_overrides: CliConfigOverrides) -> ! {
-    let cli_overrides = match cli_config_overrides.parse_overrides() {
-        Ok(v) => v,
+/// Log in with ChatGPT in the browser and store the login as `account`, or
+/// as the default `auth.json` when `None`.
+pub async fn run_login_with_chatgpt(
+    cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+    exit_if_invalid_account(account.as_deref());
+
+    let capture_output = false;
+    match login_with_chatgpt(&config.codex_home, account.as_deref(), capture_output).await {
+        Ok(_) => finish_login(&config, account.as_deref()),
         Err(e) => {
-            eprintln!("Error parsing -c overrides: {e}");
+            eprintln!("Error logging in: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Log in from a machine without a browser: print a URL and a code to enter
+/// on another device, then wait until the login is approved.
+pub async fn run_login_with_device_code(
+    cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+    exit_if_invalid_account(account.as_deref());
+
+    let device_code = match request_device_code().await {
+        Ok(device_code) => device_code,
+        Err(e) => {
+            eprintln!("Error starting the device login: {e}");
             std::process::exit(1);
         }
     };
+    eprintln!(
+        "To log in, open {} on any device and enter the code:\n\n    {}\n",
+        device_code.verification_uri, device_code.user_code
+    );
+    if let Some(url) = &device_code.verification_uri_complete {
+        eprintln!("Or open {url} to skip entering the code.\n");
+    }
+    eprintln!(
+        "Waiting for the login to be approved (the code expires in {} minutes)...",
+        device_code.expires_in.div_ceil(60)
+    );
 
-    let config_overrides = ConfigOverrides::default();
-    let config = match Config::load_with_cli_overrides(cli_overrides, config_overrides) {
-        Ok(config) => config,
+    match complete_device_code_login(&config.codex_home, account.as_deref(), &device_code).await {
+        Ok(_) => finish_login(&config, account.as_deref()),
         Err(e) => {
-            eprintln!("Error loading configuration: {e}");
+            eprintln!("Error logging in: {e}");
             std::process::exit(1);
         }
+    }
+}
+
+fn exit_if_invalid_account(account: Option<&str>) {
+    if let Some(account) = account.filter(|account| !is_valid_account_name(account)) {
+        eprintln!(
+            "Invalid account name `{account}`: use letters, digits, `-`, `_` and `.`, and not `{DEFAULT_ACCOUNT}`"
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Report the stored login and check that it may use the configured
+/// `org_id`; exits non-zero if it may not.
+fn finish_login(config: &Config, account: Option<&str>) -> ! {
+    match account {
+        Some(account) => eprintln!(
+            "Successfully logged in as account `{account}`. Use it with `account = \"{account}\"` in config.toml or `/account {account}` in the TUI."
+        ),
+        None => eprintln!("Successfully logged in"),
+    }
+    let Some(org_id) = &config.org_id else {
+        std::process::exit(0);
     };
+    let info = read_account_info(&config.codex_home, account).unwrap_or_default();
+    match info.check_org(org_id) {
+        Ok(()) => std::process::exit(0),
+        Err(e) => {
+            eprintln!("Error: {e}. Fix `org_id` in config.toml.");
+            std::process::exit(1);
+        }
+    }
+}
 
-    let capture_output = false;
-    match login_with_chatgpt(&config.codex_home, capture_output).await {
-        Ok(_) => {
-            eprintln!("Successfully logged in");
-            std::process::exit(0);
+fn load_config_or_exit(cli_config_overrides: CliConfigOverrides) -> Config {
+    let cli_overrides = match cli_config_overrides.parse_overrides() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error parsing -c overrides: {e}");
+            std::process::exit(1);
         }
+    };
+
+    let config_overrides = ConfigOverrides::default();
+    match Config::load_with_cli_overrides(cli_overrides, config_overrides) {
+        Ok(config) => config,
         Err(e) => {
-            eprintln!("Error logging in: {e}");
+            eprintln!("Error loading configuration: {e}");
             std::process::exit(1);
         }
     }
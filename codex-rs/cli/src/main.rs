@@ -1,8 +1,30 @@
 use clap::Parser;
 use codex_cli::LandlockCommand;
 use codex_cli::SeatbeltCommand;
+use codex_cli::doctor_cmd::DoctorCli;
+use codex_cli::doctor_cmd::run_doctor;
+use codex_cli::export_cmd::ExportCli;
+use codex_cli::export_cmd::run_export;
 use codex_cli::login::run_login_with_chatgpt;
+use codex_cli::login::run_login_with_device_code;
+use codex_cli::mcp_cmd::McpCli;
+use codex_cli::mcp_cmd::McpSubcommand;
+use codex_cli::mcp_cmd::run_mcp_get;
+use codex_cli::mcp_cmd::run_mcp_list;
+use codex_cli::mcp_cmd::run_mcp_list_tools;
+use codex_cli::mcp_cmd::run_mcp_login;
+use codex_cli::mcp_cmd::set_mcp_server_enabled;
 use codex_cli::proto;
+use codex_cli::review_cmd::ReviewCli;
+use codex_cli::review_cmd::run_review;
+use codex_cli::rules_cmd::RulesCli;
+use codex_cli::rules_cmd::RulesSubcommand;
+use codex_cli::rules_cmd::run_rules_check;
+use codex_cli::status_cmd::StatusCli;
+use codex_cli::status_cmd::run_status;
+use codex_cli::worktree_cmd::WorktreeCli;
+use codex_cli::worktree_cmd::WorktreeSubcommand;
+use codex_cli::worktree_cmd::run_worktrees;
 use codex_common::CliConfigOverrides;
 use codex_core::config::find_codex_home;
 use codex_exec::Cli as ExecCli;
@@ -64,10 +86,16 @@ enum ConfigCmd {
 
 #[derive(Debug, clap::Subcommand)]
 enum Subcommand {
-    /// Resume an existing TUI session by UUID.
-    Session {
-        /// UUID of the session to resume
-        session_id: Uuid,
+    /// Resume a previous interactive session.
+    #[clap(alias = "session")]
+    Resume {
+        /// UUID of the session to resume. When omitted, a picker lists saved
+        /// sessions.
+        session_id: Option<Uuid>,
+
+        /// Resume the most recently updated session without prompting.
+        #[arg(long, conflicts_with = "session_id")]
+        last: bool,
     },
     /// Inspect or modify the CLI configuration file.
     #[command(subcommand)]
@@ -79,13 +107,33 @@ enum Subcommand {
     /// Login with ChatGPT.
     Login(LoginCommand),
 
-    /// Experimental: run Codex as an MCP server.
-    Mcp,
+    /// Experimental: run Codex as an MCP server, or manage MCP servers.
+    Mcp(McpCli),
 
     /// Run the Protocol stream via stdin/stdout
     #[clap(visible_alias = "p")]
     Proto(ProtoCli),
 
+    /// Inspect the approval rules from config.toml.
+    Rules(RulesCli),
+
+    /// Review the changes on the current branch and print the findings as
+    /// JSON.
+    Review(ReviewCli),
+
+    /// Run the same prompt in several isolated git worktrees in parallel.
+    Worktree(WorktreeCli),
+
+    /// Show the model, sandbox, account and latest session token usage.
+    Status(StatusCli),
+
+    /// Export a saved session as a Markdown or HTML transcript.
+    Export(ExportCli),
+
+    /// Check credentials, MCP servers, the sandbox and the terminal, and
+    /// print how to fix any problem found.
+    Doctor(DoctorCli),
+
     /// Internal debugging commands.
     Debug(DebugArgs),
 }
@@ -109,6 +157,16 @@ enum DebugCommand {
 struct LoginCommand {
     #[clap(skip)]
     config_overrides: CliConfigOverrides,
+
+    /// Log in with a code entered on another device instead of opening a
+    /// browser, e.g. over SSH.
+    #[arg(long)]
+    device: bool,
+
+    /// Store the login as a named account in `CODEX_HOME/auth/<ACCOUNT>.json`
+    /// instead of `auth.json`.
+    #[arg(long = "profile", value_name = "ACCOUNT")]
+    account: Option<String>,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -127,9 +185,13 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut tui_cli.config_overrides, cli.config_overrides);
             codex_tui::run_main(tui_cli, codex_linux_sandbox_exe)?;
         }
-        Some(Subcommand::Session { session_id }) => {
+        Some(Subcommand::Resume { session_id, last }) => {
             let mut tui_cli = cli.interactive;
-            tui_cli.session = Some(session_id);
+            match session_id {
+                Some(id) => tui_cli.session = Some(id),
+                None if last => tui_cli.resume_last = true,
+                None => tui_cli.resume_picker = true,
+            }
             prepend_config_flags(&mut tui_cli.config_overrides, cli.config_overrides);
             codex_tui::run_main(tui_cli, codex_linux_sandbox_exe)?;
         }
@@ -173,14 +235,75 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
         }
         Some(Subcommand::Exec(mut exec_cli)) => {
             prepend_config_flags(&mut exec_cli.config_overrides, cli.config_overrides);
-            codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
+            let outcome = codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
+            std::process::exit(outcome.exit_code());
+        }
+        Some(Subcommand::Mcp(mut mcp_cli)) => {
+            prepend_config_flags(&mut mcp_cli.config_overrides, cli.config_overrides);
+            match mcp_cli.cmd {
+                None | Some(McpSubcommand::Serve) => {
+                    codex_mcp_server::run_main(codex_linux_sandbox_exe).await?;
+                }
+                Some(McpSubcommand::Login { server_name }) => {
+                    run_mcp_login(mcp_cli.config_overrides, server_name).await;
+                }
+                Some(McpSubcommand::List { tools: false, .. }) => {
+                    run_mcp_list(mcp_cli.config_overrides)?;
+                }
+                Some(McpSubcommand::List { tools: true, json }) => {
+                    run_mcp_list_tools(mcp_cli.config_overrides, json).await?;
+                }
+                Some(McpSubcommand::Get { server_name }) => {
+                    run_mcp_get(mcp_cli.config_overrides, server_name).await?;
+                }
+                Some(McpSubcommand::Enable { server_name }) => {
+                    set_mcp_server_enabled(&find_codex_home()?, &server_name, true)?;
+                }
+                Some(McpSubcommand::Disable { server_name }) => {
+                    set_mcp_server_enabled(&find_codex_home()?, &server_name, false)?;
+                }
+            }
+        }
+        Some(Subcommand::Rules(mut rules_cli)) => {
+            prepend_config_flags(&mut rules_cli.config_overrides, cli.config_overrides);
+            match rules_cli.cmd {
+                RulesSubcommand::Check { command } => {
+                    run_rules_check(rules_cli.config_overrides, command);
+                }
+            }
+        }
+        Some(Subcommand::Review(mut review_cli)) => {
+            prepend_config_flags(&mut review_cli.config_overrides, cli.config_overrides);
+            run_review(review_cli, codex_linux_sandbox_exe).await?;
+        }
+        Some(Subcommand::Worktree(mut worktree_cli)) => {
+            prepend_config_flags(&mut worktree_cli.config_overrides, cli.config_overrides);
+            match worktree_cli.cmd {
+                WorktreeSubcommand::Run(args) => {
+                    run_worktrees(worktree_cli.config_overrides, args, codex_linux_sandbox_exe)
+                        .await?;
+                }
+            }
         }
-        Some(Subcommand::Mcp) => {
-            codex_mcp_server::run_main(codex_linux_sandbox_exe).await?;
+        Some(Subcommand::Status(mut status_cli)) => {
+            prepend_config_flags(&mut status_cli.config_overrides, cli.config_overrides);
+            run_status(status_cli)?;
+        }
+        Some(Subcommand::Export(mut export_cli)) => {
+            prepend_config_flags(&mut export_cli.config_overrides, cli.config_overrides);
+            run_export(export_cli)?;
+        }
+        Some(Subcommand::Doctor(mut doctor_cli)) => {
+            prepend_config_flags(&mut doctor_cli.config_overrides, cli.config_overrides);
+            run_doctor(doctor_cli, codex_linux_sandbox_exe).await?;
         }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(&mut login_cli.config_overrides, cli.config_overrides);
-            run_login_with_chatgpt(login_cli.config_overrides).await;
+            if login_cli.device {
+                run_login_with_device_code(login_cli.config_overrides, login_cli.account).await;
+            } else {
+                run_login_with_chatgpt(login_cli.config_overrides, login_cli.account).await;
+            }
         }
         Some(Subcommand::Proto(mut proto_cli)) => {
             prepend_config_flags(&mut proto_cli.config_overrides, cli.config_overrides);
@@ -278,4 +401,166 @@ mod tests {
         assert!(help.contains("edit"), "help missing 'edit': {}", help);
         assert!(help.contains("set"), "help missing 'set': {}", help);
     }
+
+    #[test]
+    fn mcp_subcommands_help() {
+        let mut cmd = MultitoolCli::command();
+        let mcp = cmd
+            .find_subcommand_mut("mcp")
+            .expect("mcp subcommand not found");
+        let mut buf = Vec::new();
+        mcp.write_long_help(&mut buf).unwrap();
+        let help = String::from_utf8(buf).unwrap();
+        assert!(help.contains("serve"), "help missing 'serve': {}", help);
+        assert!(help.contains("login"), "help missing 'login': {}", help);
+        assert!(help.contains("enable"), "help missing 'enable': {}", help);
+        assert!(help.contains("disable"), "help missing 'disable': {}", help);
+    }
+
+    #[test]
+    fn rules_check_parses_command() {
+        use super::Subcommand;
+        use clap::Parser;
+        use codex_cli::rules_cmd::RulesSubcommand;
+
+        let cli =
+            MultitoolCli::try_parse_from(["codex", "rules", "check", "cargo test --all"]).unwrap();
+        let Some(Subcommand::Rules(rules_cli)) = cli.subcommand else {
+            panic!("expected rules subcommand");
+        };
+        let RulesSubcommand::Check { command } = rules_cli.cmd;
+        assert_eq!(command, "cargo test --all");
+    }
+
+    #[test]
+    fn review_requires_base() {
+        use super::Subcommand;
+        use clap::Parser;
+
+        assert!(MultitoolCli::try_parse_from(["codex", "review"]).is_err());
+        let cli =
+            MultitoolCli::try_parse_from(["codex", "review", "--base", "origin/main"]).unwrap();
+        let Some(Subcommand::Review(review_cli)) = cli.subcommand else {
+            panic!("expected review subcommand");
+        };
+        assert_eq!(review_cli.base, "origin/main");
+    }
+
+    #[test]
+    fn login_parses_device_and_profile_flags() {
+        use super::Subcommand;
+        use clap::Parser;
+
+        let cli = MultitoolCli::try_parse_from(["codex", "login", "--device", "--profile", "work"])
+            .unwrap();
+        let Some(Subcommand::Login(login_cli)) = cli.subcommand else {
+            panic!("expected login subcommand");
+        };
+        assert!(login_cli.device);
+        assert_eq!(login_cli.account.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn status_parses_json_flag() {
+        use super::Subcommand;
+        use clap::Parser;
+
+        let cli = MultitoolCli::try_parse_from(["codex", "status", "--json"]).unwrap();
+        let Some(Subcommand::Status(status_cli)) = cli.subcommand else {
+            panic!("expected status subcommand");
+        };
+        assert!(status_cli.json);
+    }
+
+    #[test]
+    fn mcp_list_json_requires_tools() {
+        use super::Subcommand;
+        use clap::Parser;
+        use codex_cli::mcp_cmd::McpSubcommand;
+
+        assert!(MultitoolCli::try_parse_from(["codex", "mcp", "list", "--json"]).is_err());
+        let cli =
+            MultitoolCli::try_parse_from(["codex", "mcp", "list", "--tools", "--json"]).unwrap();
+        let Some(Subcommand::Mcp(mcp_cli)) = cli.subcommand else {
+            panic!("expected mcp subcommand");
+        };
+        assert!(matches!(
+            mcp_cli.cmd,
+            Some(McpSubcommand::List {
+                tools: true,
+                json: true
+            })
+        ));
+    }
+
+    #[test]
+    fn export_parses_session_and_format() {
+        use super::Subcommand;
+        use clap::Parser;
+        use codex_cli::export_cmd::ExportFormatArg;
+
+        let session_id = uuid::Uuid::nil().to_string();
+        let cli = MultitoolCli::try_parse_from([
+            "codex",
+            "export",
+            "--session",
+            &session_id,
+            "--format",
+            "html",
+        ])
+        .unwrap();
+        let Some(Subcommand::Export(export_cli)) = cli.subcommand else {
+            panic!("expected export subcommand");
+        };
+        assert_eq!(export_cli.session_id, Some(uuid::Uuid::nil()));
+        assert_eq!(export_cli.format, Some(ExportFormatArg::Html));
+        assert_eq!(export_cli.output, None);
+    }
+
+    #[test]
+    fn worktree_run_parses_count_and_prompt() {
+        use super::Subcommand;
+        use clap::Parser;
+
+        let cli = MultitoolCli::try_parse_from([
+            "codex",
+            "worktree",
+            "run",
+            "-n",
+            "2",
+            "--test",
+            "cargo test",
+            "fix the bug",
+        ])
+        .unwrap();
+        let Some(Subcommand::Worktree(worktree_cli)) = cli.subcommand else {
+            panic!("expected worktree subcommand");
+        };
+        let WorktreeSubcommand::Run(args) = worktree_cli.cmd;
+        assert_eq!(args.count, 2);
+        assert_eq!(args.test.as_deref(), Some("cargo test"));
+        assert_eq!(args.prompt, "fix the bug");
+    }
+
+    #[test]
+    fn resume_accepts_last_and_session_alias() {
+        use super::Subcommand;
+        use clap::Parser;
+
+        let cli = MultitoolCli::try_parse_from(["codex", "resume", "--last"]).unwrap();
+        assert!(matches!(
+            cli.subcommand,
+            Some(Subcommand::Resume {
+                session_id: None,
+                last: true
+            })
+        ));
+
+        let id = uuid::Uuid::new_v4();
+        let cli = MultitoolCli::try_parse_from(["codex", "session", &id.to_string()]).unwrap();
+        assert!(matches!(
+            cli.subcommand,
+            Some(Subcommand::Resume { session_id: Some(s), last: false }) if s == id
+        ));
+    }
 }
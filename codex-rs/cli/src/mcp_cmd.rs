@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context;
+use anyhow::anyhow;
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::inspect_mcp_server;
+use codex_core::mcp_auth::begin_mcp_login;
+use codex_core::mcp_tool_names::qualify_tool_names;
+use mcp_types::Tool;
+use mcp_types::ToolInputSchema;
+use serde::Serialize;
+
+/// `codex mcp` – run Codex as an MCP server or manage configured MCP servers.
+#[derive(Debug, Parser)]
+pub struct McpCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// When omitted, Codex runs as an MCP server over stdio.
+    #[command(subcommand)]
+    pub cmd: Option<McpSubcommand>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum McpSubcommand {
+    /// Experimental: run Codex as an MCP server (the default).
+    Serve,
+
+    /// Authorize Codex against an OAuth-protected `streamable_http` server.
+    Login {
+        /// Name of the server as configured under `[mcp_servers]`.
+        server_name: String,
+    },
+
+    /// List configured MCP servers and whether they are enabled.
+    List {
+        /// Start every enabled server and list the tools the model is
+        /// offered, under the names it sees them by.
+        #[arg(long)]
+        tools: bool,
+
+        /// With `--tools`, print the tools as a JSON array.
+        #[arg(long, requires = "tools")]
+        json: bool,
+    },
+
+    /// Start a configured server and show its tools and resources.
+    Get {
+        /// Name of the server as configured under `[mcp_servers]`.
+        server_name: String,
+    },
+
+    /// Start the server again in future sessions.
+    Enable {
+        /// Name of the server as configured under `[mcp_servers]`.
+        server_name: String,
+    },
+
+    /// Keep the server in config.toml but do not start it.
+    Disable {
+        /// Name of the server as configured under `[mcp_servers]`.
+        server_name: String,
+    },
+}
+
+pub fn run_mcp_list(cli_config_overrides: CliConfigOverrides) -> anyhow::Result<()> {
+    let config = load_config_or_exit(cli_config_overrides);
+
+    let mut servers: Vec<_> = config.mcp_servers.iter().collect();
+    if servers.is_empty() {
+        println!("No MCP servers configured.");
+        return Ok(());
+    }
+    servers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let name_width = servers
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default()
+        .max("NAME".len());
+    println!("{:<name_width$}  {:<8}  COMMAND", "NAME", "STATUS");
+    for (name, server) in servers {
+        let status = if server.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        let target = match &server.url {
+            Some(url) if server.command.is_empty() => url.clone(),
+            _ => std::iter::once(server.command.as_str())
+                .chain(server.args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        println!("{name:<name_width$}  {status:<8}  {target}");
+    }
+    Ok(())
+}
+
+pub async fn run_mcp_get(
+    cli_config_overrides: CliConfigOverrides,
+    server_name: String,
+) -> anyhow::Result<()> {
+    let config = load_config_or_exit(cli_config_overrides);
+    let server = config
+        .mcp_servers
+        .get(&server_name)
+        .cloned()
+        .ok_or_else(|| anyhow!("MCP server `{server_name}` is not configured"))?;
+
+    println!("{server_name}");
+    println!(
+        "  status: {}",
+        if server.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    if !server.command.is_empty() {
+        let command = std::iter::once(server.command.as_str())
+            .chain(server.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("  command: {command}");
+    }
+    if let Some(url) = &server.url {
+        println!("  url: {url}");
+    }
+
+    let inventory = inspect_mcp_server(&server_name, server, &config.codex_home)
+        .await
+        .with_context(|| format!("failed to query MCP server `{server_name}`"))?;
+    println!("\nTools ({}):", inventory.tools.len());
+    for tool in &inventory.tools {
+        match &tool.description {
+            Some(description) => println!("  {} - {}", tool.name, first_line(description)),
+            None => println!("  {}", tool.name),
+        }
+    }
+    match &inventory.resources {
+        Some(resources) => {
+            println!("\nResources ({}):", resources.len());
+            for resource in resources {
+                let mime_type = resource
+                    .mime_type
+                    .as_deref()
+                    .map(|mime_type| format!(" [{mime_type}]"))
+                    .unwrap_or_default();
+                println!("  {} ({}){mime_type}", resource.uri, resource.name);
+            }
+        }
+        None => println!("\nResources: not supported by this server"),
+    }
+    Ok(())
+}
+
+/// A tool as listed by `codex mcp list --tools --json`.
+#[derive(Debug, Serialize)]
+struct ListedTool {
+    /// The name the model calls the tool by.
+    name: String,
+    server: String,
+    tool: String,
+    description: Option<String>,
+    input_schema: ToolInputSchema,
+}
+
+pub async fn run_mcp_list_tools(
+    cli_config_overrides: CliConfigOverrides,
+    json: bool,
+) -> anyhow::Result<()> {
+    let config = load_config_or_exit(cli_config_overrides);
+
+    let mut join_set = tokio::task::JoinSet::new();
+    for (server_name, server) in &config.mcp_servers {
+        if !server.enabled {
+            continue;
+        }
+        let server_name = server_name.clone();
+        let server = server.clone();
+        let codex_home = config.codex_home.clone();
+        join_set.spawn(async move {
+            let inventory = inspect_mcp_server(&server_name, server, &codex_home).await;
+            (server_name, inventory)
+        });
+    }
+
+    let mut tools: HashMap<(String, String), Tool> = HashMap::new();
+    while let Some(res) = join_set.join_next().await {
+        let (server_name, inventory) = res?;
+        match inventory {
+            Ok(inventory) => {
+                for tool in inventory.tools {
+                    if config.tools.is_mcp_tool_allowed(&server_name, &tool.name) {
+                        tools.insert((server_name.clone(), tool.name.clone()), tool);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to query MCP server `{server_name}`: {e:#}"),
+        }
+    }
+
+    let pairs: Vec<(String, String)> = tools.keys().cloned().collect();
+    let listed: Vec<ListedTool> = qualify_tool_names(&pairs, &config.mcp_servers)
+        .into_iter()
+        .filter_map(|(name, key)| {
+            let tool = tools.remove(&key)?;
+            Some(ListedTool {
+                name,
+                server: key.0,
+                tool: key.1,
+                description: tool.description,
+                input_schema: tool.input_schema,
+            })
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&listed)?);
+        return Ok(());
+    }
+    if listed.is_empty() {
+        println!("No MCP tools available.");
+        return Ok(());
+    }
+    let name_width = listed
+        .iter()
+        .map(|tool| tool.name.len())
+        .max()
+        .unwrap_or_default()
+        .max("NAME".len());
+    println!("{:<name_width$}  ARGUMENTS / DESCRIPTION", "NAME");
+    for tool in &listed {
+        println!(
+            "{:<name_width$}  ({})",
+            tool.name,
+            summarize_input_schema(&tool.input_schema)
+        );
+        if let Some(description) = &tool.description {
+            println!("{:<name_width$}  {}", "", first_line(description));
+        }
+    }
+    Ok(())
+}
+
+/// The arguments of a tool as `name: type`, sorted by name (the schema's
+/// own order depends on serde_json's `preserve_order`), required ones marked
+/// with `*`.
+fn summarize_input_schema(schema: &ToolInputSchema) -> String {
+    let Some(properties) = schema.properties.as_ref().and_then(|p| p.as_object()) else {
+        return String::new();
+    };
+    let required = schema.required.as_deref().unwrap_or_default();
+    let mut properties: Vec<_> = properties.iter().collect();
+    properties.sort_by(|(a, _), (b, _)| a.cmp(b));
+    properties
+        .into_iter()
+        .map(|(name, property)| {
+            let marker = if required.contains(name) { "*" } else { "" };
+            match property.get("type").and_then(|t| t.as_str()) {
+                Some(r#type) => format!("{name}{marker}: {type}"),
+                None => format!("{name}{marker}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim()
+}
+
+/// Flip `mcp_servers.<name>.enabled` in config.toml, leaving the rest of the
+/// server entry untouched.
+pub fn set_mcp_server_enabled(
+    codex_home: &Path,
+    server_name: &str,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    let config_path = codex_home.join("config.toml");
+    let contents = std::fs::read_to_string(&config_path)
+        .with_context(|| format!("failed to read {}", config_path.display()))?;
+    let mut doc: toml::Value = toml::from_str(&contents)?;
+
+    let server = doc
+        .get_mut("mcp_servers")
+        .and_then(|servers| servers.get_mut(server_name))
+        .and_then(toml::Value::as_table_mut)
+        .ok_or_else(|| anyhow!("MCP server `{server_name}` is not configured"))?;
+    server.insert("enabled".to_string(), toml::Value::Boolean(enabled));
+
+    std::fs::write(&config_path, toml::to_string_pretty(&doc)?)?;
+    Ok(())
+}
+
+pub async fn run_mcp_login(cli_config_overrides: CliConfigOverrides, server_name: String) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+
+    let Some(server_url) = config
+        .mcp_servers
+        .get(&server_name)
+        .and_then(|server| server.url.clone())
+    else {
+        eprintln!("MCP server `{server_name}` is not configured with a `url`");
+        std::process::exit(1);
+    };
+
+    let pending = match begin_mcp_login(&server_name, &server_url).await {
+        Ok(pending) => pending,
+        Err(e) => {
+            eprintln!("Error starting OAuth flow for `{server_name}`: {e:#}");
+            std::process::exit(1);
+        }
+    };
+
+    eprintln!("Open the following URL in your browser to authorize Codex:\n");
+    eprintln!("    {}\n", pending.authorize_url);
+
+    match pending.complete(&config.codex_home).await {
+        Ok(()) => {
+            eprintln!("Successfully logged in to `{server_name}`");
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Error logging in to `{server_name}`: {e:#}");
+            std::process::exit(1);
+        }
+    }
+}
+
+pub(crate) fn load_config_or_exit(cli_config_overrides: CliConfigOverrides) -> Config {
+    let cli_overrides = match cli_config_overrides.parse_overrides() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error parsing -c overrides: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    match Config::load_with_cli_overrides(cli_overrides, ConfigOverrides::default()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error loading configuration: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn summarizes_input_schema() {
+        let schema = ToolInputSchema {
+            properties: Some(json!({
+                "path": { "type": "string" },
+                "limit": { "type": "integer" },
+                "filter": {},
+            })),
+            required: Some(vec!["path".to_string()]),
+            r#type: "object".to_string(),
+        };
+        assert_eq!(
+            summarize_input_schema(&schema),
+            "filter, limit: integer, path*: string"
+        );
+    }
+}
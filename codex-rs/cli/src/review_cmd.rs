@@ -0,0 +1,172 @@
+//! `codex review` – review the changes of a branch non-interactively and
+//! report the findings as JSON, e.g. to post them as PR review comments from a
+//! GitHub Action.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context;
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::SandboxPolicy;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::single_turn::run_single_turn;
+
+#[derive(Debug, Parser)]
+pub struct ReviewCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Git ref to review against, e.g. `origin/main`. The diff covers the
+    /// commits on `HEAD` since it branched off this ref.
+    #[arg(long, value_name = "REF")]
+    pub base: String,
+
+    /// Model the agent should use.
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    /// Write the findings to this file instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// A single review comment, anchored to a line of the new version of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewFinding {
+    /// Path relative to the repository root.
+    pub file: String,
+    pub line: u32,
+    pub severity: ReviewSeverity,
+    pub comment: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+const REVIEW_PROMPT: &str = r#"You are reviewing a pull request. The diff of its changes is below.
+
+Look for bugs, security issues, missing error handling and confusing code in
+the changed lines. You may read other files in the repository for context, but
+do not modify anything.
+
+Reply with only a JSON array of findings, without any other text. Each finding
+is an object with these keys:
+- "file": the path of the changed file, relative to the repository root
+- "line": the line number in the new version of the file
+- "severity": "error", "warning" or "info"
+- "comment": what is wrong and how to fix it
+Reply with [] if there is nothing worth commenting on.
+
+"#;
+
+pub async fn run_review(
+    review_cli: ReviewCli,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let ReviewCli {
+        config_overrides,
+        base,
+        model,
+        output,
+    } = review_cli;
+    let cli_overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    // Reviews only read the repository, and nobody is around to approve
+    // anything.
+    let overrides = ConfigOverrides {
+        model,
+        approval_policy: Some(AskForApproval::Never),
+        sandbox_policy: Some(SandboxPolicy::new_read_only_policy()),
+        codex_linux_sandbox_exe,
+        ..Default::default()
+    };
+    let config = Config::load_with_cli_overrides(cli_overrides, overrides)?;
+
+    let diff = git_diff_against(&config, &base)?;
+    let findings = if diff.trim().is_empty() {
+        Vec::new()
+    } else {
+        let prompt = format!("{REVIEW_PROMPT}```diff\n{diff}```\n");
+        let reply = run_single_turn(config, prompt)
+            .await
+            .context("review failed")?;
+        parse_findings(&reply)?
+    };
+
+    let json = serde_json::to_string_pretty(&findings)?;
+    match output {
+        Some(path) => std::fs::write(&path, format!("{json}\n"))
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+/// The diff of `HEAD` against its merge base with `base`.
+fn git_diff_against(config: &Config, base: &str) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        // `--end-of-options` keeps a base such as `--output=x` from being
+        // read as an option.
+        .args(["diff", "--no-color", "--no-ext-diff", "--end-of-options"])
+        .arg(format!("{base}...HEAD"))
+        .current_dir(&config.cwd)
+        .output()
+        .context("failed to run git diff")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git diff against {base} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse the findings from the agent's reply, tolerating text or a code fence
+/// around the JSON array.
+pub fn parse_findings(reply: &str) -> anyhow::Result<Vec<ReviewFinding>> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => anyhow::bail!("the review reply does not contain a JSON array: {reply}"),
+    };
+    serde_json::from_str(json).context("the review reply is not a valid list of findings")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn parses_findings_inside_code_fence() {
+        let reply = "Here you go:\n```json\n[{\"file\": \"src/lib.rs\", \"line\": 3, \"severity\": \"warning\", \"comment\": \"Unchecked [index].\"}]\n```";
+        assert_eq!(
+            parse_findings(reply).unwrap(),
+            vec![ReviewFinding {
+                file: "src/lib.rs".to_string(),
+                line: 3,
+                severity: ReviewSeverity::Warning,
+                comment: "Unchecked [index].".to_string(),
+            }]
+        );
+        assert_eq!(parse_findings("[]").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn rejects_replies_without_findings() {
+        assert!(parse_findings("Looks good to me!").is_err());
+        assert!(parse_findings("[{\"file\": \"a.rs\"}]").is_err());
+    }
+}
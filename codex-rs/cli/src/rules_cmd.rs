@@ -0,0 +1,41 @@
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::approval_rules::ApprovalRuleAction;
+use codex_core::approval_rules::evaluate_approval_rules;
+
+use crate::mcp_cmd::load_config_or_exit;
+
+/// `codex rules` – inspect the `[[approval_rules]]` from config.toml.
+#[derive(Debug, Parser)]
+pub struct RulesCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub cmd: RulesSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum RulesSubcommand {
+    /// Show which approval rule, if any, decides a shell command.
+    Check {
+        /// The command as the model would run it, e.g. `cargo test --all`.
+        command: String,
+    },
+}
+
+pub fn run_rules_check(cli_config_overrides: CliConfigOverrides, command: String) {
+    let config = load_config_or_exit(cli_config_overrides);
+    let command = vec!["bash".to_string(), "-lc".to_string(), command];
+
+    match evaluate_approval_rules(&command, &config.approval_rules) {
+        Some(rule) => {
+            let decision = match rule.action {
+                ApprovalRuleAction::Allow => "allow",
+                ApprovalRuleAction::Deny => "deny",
+            };
+            println!("{decision} (matched {})", rule.describe());
+        }
+        None => println!("no matching rule; the approval policy decides"),
+    }
+}
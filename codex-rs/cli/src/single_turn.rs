@@ -0,0 +1,31 @@
+use anyhow::Context;
+use codex_core::codex_wrapper;
+use codex_core::config::Config;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::TaskCompleteEvent;
+
+/// Start a session with `config`, run a single turn with `prompt` and return
+/// the agent's last message. Fails if the turn ends with an error.
+pub(crate) async fn run_single_turn(config: Config, prompt: String) -> anyhow::Result<String> {
+    let (codex, _session_configured, _ctrl_c) = codex_wrapper::init_codex(config).await?;
+    let task_id = codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text { text: prompt }],
+        })
+        .await?;
+    loop {
+        let event = codex.next_event().await?;
+        if event.id != task_id {
+            continue;
+        }
+        match event.msg {
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                return last_agent_message.context("the turn ended without a reply");
+            }
+            EventMsg::Error(error) => anyhow::bail!("{}", error.message),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! `codex status` – print the effective configuration, how Codex would
+//! authenticate, and the token usage of the most recent session, e.g. for
+//! shell prompts and dashboards.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config_types::ReasoningEffort;
+use codex_core::config_types::ReasoningSummary;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TokenUsage;
+use codex_core::session_store::latest_session_id;
+use codex_core::session_store::load_session;
+use codex_login::auth_file_path;
+use codex_login::read_account_info;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Parser)]
+pub struct StatusCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Print the status as a JSON object.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub model: String,
+    pub provider: String,
+    pub cwd: PathBuf,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    pub reasoning_effort: ReasoningEffort,
+    pub reasoning_summary: ReasoningSummary,
+    pub account: AccountStatus,
+    /// Usage of the most recently updated saved session, if any.
+    pub last_session: Option<SessionUsage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountStatus {
+    pub auth: AuthSource,
+    /// Environment variable the provider reads its API key from.
+    pub env_key: Option<String>,
+    /// Named account (`account` in config.toml) whose login is used.
+    pub name: Option<String>,
+    /// Email of the ChatGPT login.
+    pub email: Option<String>,
+    /// ChatGPT plan of the login, e.g. `plus`.
+    pub plan: Option<String>,
+    /// Organization billed: `org_id` from config.toml, else the login's
+    /// default organization.
+    pub org_id: Option<String>,
+    /// Project billed within the organization (`workspace_id`).
+    pub workspace_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthSource {
+    /// The provider's API key is set in the environment.
+    EnvironmentVariable,
+    /// Credentials from `codex login` are stored in `CODEX_HOME/auth.json`,
+    /// or in `CODEX_HOME/auth/<account>.json` for a named account.
+    ChatgptLogin,
+    /// The provider does not need an API key.
+    NotRequired,
+    None,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionUsage {
+    pub session_id: Uuid,
+    pub token_usage: TokenUsage,
+}
+
+pub fn run_status(status_cli: StatusCli) -> anyhow::Result<()> {
+    let cli_overrides = status_cli
+        .config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(cli_overrides, ConfigOverrides::default())?;
+    let report = build_status_report(&config)?;
+    if status_cli.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_status_report(&report);
+    }
+    Ok(())
+}
+
+fn build_status_report(config: &Config) -> anyhow::Result<StatusReport> {
+    let env_key = config.model_provider.env_key.clone();
+    let account = config.account.as_deref();
+    // A configured account is used even if the API key is in the environment.
+    let auth = if env_key.is_none() {
+        AuthSource::NotRequired
+    } else if account.is_none()
+        && config
+            .model_provider
+            .api_key()
+            .is_ok_and(|key| key.is_some())
+    {
+        AuthSource::EnvironmentVariable
+    } else if auth_file_path(&config.codex_home, account).is_ok_and(|path| path.is_file()) {
+        AuthSource::ChatgptLogin
+    } else {
+        AuthSource::None
+    };
+    let info = match auth {
+        AuthSource::ChatgptLogin => read_account_info(&config.codex_home, account).ok(),
+        _ => None,
+    }
+    .unwrap_or_default();
+
+    // An unreadable session should not hide the rest of the status.
+    let last_session = last_session_usage(config).unwrap_or_else(|e| {
+        eprintln!("warning: failed to read the last session: {e}");
+        None
+    });
+
+    Ok(StatusReport {
+        model: config.model.clone(),
+        provider: config.model_provider_id.clone(),
+        cwd: config.cwd.clone(),
+        approval_policy: config.approval_policy,
+        sandbox_policy: config.sandbox_policy.clone(),
+        reasoning_effort: config.model_reasoning_effort,
+        reasoning_summary: config.model_reasoning_summary,
+        account: AccountStatus {
+            auth,
+            env_key,
+            name: config.account.clone(),
+            email: info.email,
+            plan: info.plan,
+            org_id: config.org_id.clone().or(info.org_id),
+            workspace_id: config.workspace_id.clone(),
+        },
+        last_session,
+    })
+}
+
+fn last_session_usage(config: &Config) -> std::io::Result<Option<SessionUsage>> {
+    let Some(session_id) = latest_session_id(&config.codex_home)? else {
+        return Ok(None);
+    };
+    Ok(Some(SessionUsage {
+        session_id,
+        token_usage: load_session(&config.codex_home, session_id)?.token_usage,
+    }))
+}
+
+fn print_status_report(report: &StatusReport) {
+    let billing: Vec<String> = [
+        report
+            .account
+            .org_id
+            .as_ref()
+            .map(|org| format!("org {org}")),
+        report
+            .account
+            .workspace_id
+            .as_ref()
+            .map(|workspace| format!("workspace {workspace}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let auth = match report.account.auth {
+        AuthSource::EnvironmentVariable => {
+            let mut key = format!(
+                "API key from ${}",
+                report.account.env_key.as_deref().unwrap_or_default()
+            );
+            if !billing.is_empty() {
+                key.push_str(&format!(" ({})", billing.join(", ")));
+            }
+            key
+        }
+        AuthSource::ChatgptLogin => {
+            let details: Vec<&str> = [&report.account.email, &report.account.plan]
+                .into_iter()
+                .flatten()
+                .chain(&billing)
+                .map(String::as_str)
+                .collect();
+            let mut login = match &report.account.name {
+                Some(name) => format!("ChatGPT login `{name}`"),
+                None => "ChatGPT login".to_string(),
+            };
+            if !details.is_empty() {
+                login.push_str(&format!(" ({})", details.join(", ")));
+            }
+            login
+        }
+        AuthSource::NotRequired => "not required".to_string(),
+        AuthSource::None => match &report.account.name {
+            Some(name) => format!("not logged in as account `{name}`"),
+            None => "not logged in".to_string(),
+        },
+    };
+    let mut entries = vec![
+        ("workdir", report.cwd.display().to_string()),
+        ("model", report.model.clone()),
+        ("provider", report.provider.clone()),
+        ("approval", format!("{:?}", report.approval_policy)),
+        ("sandbox", format!("{:?}", report.sandbox_policy)),
+        ("account", auth),
+    ];
+    if let Some(session) = &report.last_session {
+        let usage = &session.token_usage;
+        entries.push(("last session", session.session_id.to_string()));
+        entries.push((
+            "tokens",
+            format!(
+                "{} total ({} input, {} output)",
+                usage.total_tokens, usage.input_tokens, usage.output_tokens
+            ),
+        ));
+    }
+    for (key, value) in entries {
+        println!("{key}: {value}");
+    }
+}
@@ -0,0 +1,301 @@
+//! `codex worktree run` – run the same prompt in several isolated git
+//! worktrees at once and compare the candidate changes.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context;
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::SandboxPolicy;
+
+use crate::single_turn::run_single_turn;
+
+#[derive(Debug, Parser)]
+pub struct WorktreeCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub cmd: WorktreeSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum WorktreeSubcommand {
+    /// Run the prompt in N new worktrees in parallel and summarize the results.
+    Run(WorktreeRunArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct WorktreeRunArgs {
+    /// Number of worktrees (and independent conversations) to start.
+    #[arg(long, short = 'n', default_value_t = 3)]
+    pub count: usize,
+
+    /// Git ref each worktree starts from.
+    #[arg(long, value_name = "REF", default_value = "HEAD")]
+    pub base: String,
+
+    /// Shell command run in each worktree afterwards to check the result,
+    /// e.g. `cargo test`.
+    #[arg(long, value_name = "COMMAND")]
+    pub test: Option<String>,
+
+    /// Model the agents should use.
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    /// Instructions given to every agent.
+    pub prompt: String,
+}
+
+/// Changes of a worktree relative to the base commit, as reported by
+/// `git diff --shortstat`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiffStat {
+    pub files: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+struct WorktreeOutcome {
+    branch: String,
+    path: PathBuf,
+    /// Why the conversation failed, if it did.
+    error: Option<String>,
+    diff: DiffStat,
+    tests_passed: Option<bool>,
+}
+
+pub async fn run_worktrees(
+    config_overrides: CliConfigOverrides,
+    args: WorktreeRunArgs,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let WorktreeRunArgs {
+        count,
+        base,
+        test,
+        model,
+        prompt,
+    } = args;
+    if count == 0 {
+        anyhow::bail!("--count must be at least 1");
+    }
+    let cli_overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let cwd = std::env::current_dir()?;
+    let repo_root = PathBuf::from(git(&cwd, &["rev-parse", "--show-toplevel"])?);
+    let base_commit = git(
+        &repo_root,
+        &["rev-parse", "--verify", &format!("{base}^{{commit}}")],
+    )?;
+    let worktrees_dir = worktrees_dir(&repo_root)?;
+    let run_id = uuid::Uuid::new_v4().simple().to_string();
+    let run_id = &run_id[..8];
+
+    let mut tasks = Vec::with_capacity(count);
+    for index in 1..=count {
+        let branch = format!("codex/{run_id}-{index}");
+        let path = worktrees_dir.join(format!("{run_id}-{index}"));
+        git(
+            &repo_root,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                &branch,
+                &path.to_string_lossy(),
+                &base_commit,
+            ],
+        )?;
+
+        // Each agent may only write inside its own worktree and is never
+        // asked for approval, so the conversations run unattended.
+        let overrides = ConfigOverrides {
+            model: model.clone(),
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_policy: Some(SandboxPolicy::new_full_auto_policy()),
+            cwd: Some(path.clone()),
+            codex_linux_sandbox_exe: codex_linux_sandbox_exe.clone(),
+            ..Default::default()
+        };
+        let config = Config::load_with_cli_overrides(cli_overrides.clone(), overrides)?;
+        let prompt = prompt.clone();
+        eprintln!("Starting agent {index} in {}", path.display());
+        tasks.push((
+            branch,
+            path,
+            tokio::spawn(async move { run_single_turn(config, prompt).await }),
+        ));
+    }
+
+    let mut outcomes = Vec::with_capacity(count);
+    for (branch, path, task) in tasks {
+        let error = match task.await {
+            Ok(Ok(_)) => None,
+            Ok(Err(e)) => Some(format!("{e:#}")),
+            Err(e) => Some(e.to_string()),
+        };
+        let diff = worktree_diff_stat(&path, &base_commit).unwrap_or_default();
+        let tests_passed = test.as_deref().map(|test| run_test(&path, test));
+        outcomes.push(WorktreeOutcome {
+            branch,
+            path,
+            error,
+            diff,
+            tests_passed,
+        });
+    }
+
+    print_summary(&outcomes);
+    Ok(())
+}
+
+/// Directory for the worktrees of the repository at `repo_root`, inside its
+/// git directory rather than the temp dir, which may be cleaned up while the
+/// worktrees are still in use.
+fn worktrees_dir(repo_root: &Path) -> anyhow::Result<PathBuf> {
+    let git_dir = PathBuf::from(git(repo_root, &["rev-parse", "--git-common-dir"])?);
+    Ok(repo_root.join(git_dir).join("codex-worktrees"))
+}
+
+/// Run `git` in `dir` and return its trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Stage everything in the worktree (so new files are counted) and compare
+/// it with `base_commit`.
+fn worktree_diff_stat(path: &Path, base_commit: &str) -> anyhow::Result<DiffStat> {
+    git(path, &["add", "-A"])?;
+    let shortstat = git(path, &["diff", "--cached", "--shortstat", base_commit])?;
+    Ok(parse_shortstat(&shortstat))
+}
+
+/// Parse `git diff --shortstat` output such as
+/// ` 3 files changed, 10 insertions(+), 2 deletions(-)`.
+pub fn parse_shortstat(shortstat: &str) -> DiffStat {
+    let mut stat = DiffStat::default();
+    for part in shortstat.split(',') {
+        let mut words = part.split_whitespace();
+        let (Some(count), Some(kind)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let Ok(count) = count.parse() else {
+            continue;
+        };
+        if kind.starts_with("file") {
+            stat.files = count;
+        } else if kind.starts_with("insertion") {
+            stat.insertions = count;
+        } else if kind.starts_with("deletion") {
+            stat.deletions = count;
+        }
+    }
+    stat
+}
+
+fn run_test(path: &Path, test: &str) -> bool {
+    Command::new("sh")
+        .args(["-c", test])
+        .current_dir(path)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn print_summary(outcomes: &[WorktreeOutcome]) {
+    let branch_width = outcomes
+        .iter()
+        .map(|outcome| outcome.branch.len())
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{:<3} {:<branch_width$} {:<6} {:>5} {:>12} {:<5} path",
+        "#", "branch", "agent", "files", "+/-", "tests"
+    );
+    for (index, outcome) in outcomes.iter().enumerate() {
+        let agent = if outcome.error.is_some() {
+            "failed"
+        } else {
+            "done"
+        };
+        let lines = format!("+{}/-{}", outcome.diff.insertions, outcome.diff.deletions);
+        let tests = match outcome.tests_passed {
+            Some(true) => "pass",
+            Some(false) => "fail",
+            None => "-",
+        };
+        println!(
+            "{:<3} {:<branch_width$} {:<6} {:>5} {:>12} {:<5} {}",
+            index + 1,
+            outcome.branch,
+            agent,
+            outcome.diff.files,
+            lines,
+            tests,
+            outcome.path.display()
+        );
+    }
+    for (index, outcome) in outcomes.iter().enumerate() {
+        if let Some(error) = &outcome.error {
+            eprintln!("agent {} failed: {error}", index + 1);
+        }
+    }
+    eprintln!("Remove a worktree with `git worktree remove <path>` once you are done with it.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_shortstat() {
+        assert_eq!(
+            parse_shortstat(" 3 files changed, 10 insertions(+), 2 deletions(-)"),
+            DiffStat {
+                files: 3,
+                insertions: 10,
+                deletions: 2,
+            }
+        );
+        assert_eq!(
+            parse_shortstat(" 1 file changed, 1 deletion(-)"),
+            DiffStat {
+                files: 1,
+                insertions: 0,
+                deletions: 1,
+            }
+        );
+        assert_eq!(parse_shortstat(""), DiffStat::default());
+    }
+
+    #[test]
+    fn worktrees_live_in_the_git_dir() -> anyhow::Result<()> {
+        let repo = tempfile::tempdir()?;
+        git(repo.path(), &["init", "-q"])?;
+        let repo_root = repo.path().canonicalize()?;
+        assert_eq!(
+            worktrees_dir(&repo_root)?,
+            repo_root.join(".git").join("codex-worktrees")
+        );
+        Ok(())
+    }
+}
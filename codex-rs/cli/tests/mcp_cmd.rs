@@ -0,0 +1,71 @@
+/// Integration tests for the `codex mcp` subcommands that edit config.toml.
+/// This uses `CARGO_BIN_EXE_codex` to locate the compiled binary.
+#[cfg(test)]
+mod cli_mcp {
+    use std::fs;
+    use std::process::Command;
+    use tempfile;
+    use toml;
+
+    #[test]
+    fn mcp_disable_and_enable_flip_flag() {
+        let exe = env!("CARGO_BIN_EXE_codex");
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let cfg_path = tmp.path().join("config.toml");
+        fs::write(
+            &cfg_path,
+            "[mcp_servers.docs]\ncommand = \"docs-server\"\nargs = [\"--stdio\"]\n",
+        )
+        .expect("write config");
+
+        let status = Command::new(exe)
+            .env("CODEX_HOME", tmp.path())
+            .args(["mcp", "disable", "docs"])
+            .status()
+            .expect("failed to run codex mcp disable");
+        assert!(status.success());
+        let contents = fs::read_to_string(&cfg_path).expect("read config");
+        let doc: toml::Value = toml::from_str(&contents).expect("parse config.toml");
+        assert_eq!(doc["mcp_servers"]["docs"]["enabled"].as_bool(), Some(false));
+        assert_eq!(
+            doc["mcp_servers"]["docs"]["command"].as_str(),
+            Some("docs-server")
+        );
+
+        let output = Command::new(exe)
+            .env("CODEX_HOME", tmp.path())
+            .args(["mcp", "list"])
+            .output()
+            .expect("failed to run codex mcp list");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("disabled"),
+            "list missing status: {}",
+            stdout
+        );
+
+        let status = Command::new(exe)
+            .env("CODEX_HOME", tmp.path())
+            .args(["mcp", "enable", "docs"])
+            .status()
+            .expect("failed to run codex mcp enable");
+        assert!(status.success());
+        let contents = fs::read_to_string(&cfg_path).expect("read config");
+        let doc: toml::Value = toml::from_str(&contents).expect("parse config.toml");
+        assert_eq!(doc["mcp_servers"]["docs"]["enabled"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn mcp_disable_unknown_server_fails() {
+        let exe = env!("CARGO_BIN_EXE_codex");
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::write(tmp.path().join("config.toml"), "").expect("write config");
+        let status = Command::new(exe)
+            .env("CODEX_HOME", tmp.path())
+            .args(["mcp", "disable", "missing"])
+            .status()
+            .expect("failed to run codex mcp disable");
+        assert!(!status.success());
+    }
+}
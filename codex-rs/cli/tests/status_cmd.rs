@@ -0,0 +1,36 @@
+/// Integration test for the `codex status` subcommand.
+/// This uses `CARGO_BIN_EXE_codex` to locate the compiled binary.
+#[cfg(test)]
+mod cli_status {
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn unreadable_session_is_reported_as_missing() {
+        let exe = env!("CARGO_BIN_EXE_codex");
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let sessions = tmp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("create sessions dir");
+        fs::write(
+            sessions.join("67e55044-10b1-426f-9247-bb680e5fe0c8.jsonl"),
+            b"\xff\xfe not utf-8\n",
+        )
+        .expect("write session");
+
+        let output = Command::new(exe)
+            .env("CODEX_HOME", tmp.path())
+            .arg("status")
+            .arg("--json")
+            .output()
+            .expect("failed to run codex status");
+        assert!(output.status.success(), "Exited with {:?}", output.status);
+        let report: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("parse status report");
+        assert_eq!(report["last_session"], serde_json::Value::Null);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("warning: failed to read the last session"),
+            "missing warning: {stderr}"
+        );
+    }
+}
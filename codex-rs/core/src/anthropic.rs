@@ -0,0 +1,657 @@
+//! Native backend for Anthropic's Messages API (`POST {base_url}/messages`).
+//!
+//! The conversation is translated into Anthropic's content-block format on
+//! the way out, and the streamed events are mapped onto Codex's internal
+//! [`ResponseEvent`] on the way back, so the rest of the pipeline stays
+//! agnostic of the provider.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use bytes::Bytes;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+use crate::ModelProviderInfo;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::config_types::PromptCacheTtl;
+use crate::config_types::PromptCaching;
+use crate::error::CodexErr;
+use crate::error::EnvVarError;
+use crate::error::Result;
+use crate::flags::OPENAI_REQUEST_MAX_RETRIES;
+use crate::flags::OPENAI_STREAM_IDLE_TIMEOUT_MS;
+use crate::models::ContentItem;
+use crate::models::ResponseItem;
+use crate::openai_tools::create_tools_json_for_responses_api;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+
+/// Version of the Messages API the requests are written against.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// The Messages API requires an explicit output budget per request.
+const MAX_OUTPUT_TOKENS: u64 = 8192;
+
+/// Beta that enables the one-hour cache TTL.
+const EXTENDED_CACHE_TTL_BETA: &str = "extended-cache-ttl-2025-04-11";
+
+/// Implementation for Anthropic's Messages API.
+pub(crate) async fn stream_anthropic_messages(
+    prompt: &Prompt,
+    model: &str,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    prompt_caching: PromptCaching,
+) -> Result<ResponseStream> {
+    let mut system = vec![json!({"type": "text", "text": prompt.get_full_instructions(model)})];
+    let mut tools_json = create_tools_json_for_anthropic(prompt, model)?;
+    let mut messages = messages_for_prompt(&prompt.input);
+    if prompt_caching.enabled {
+        add_cache_breakpoints(&mut system, &mut tools_json, &mut messages, prompt_caching);
+    }
+    let payload = json!({
+        "model": model,
+        "max_tokens": MAX_OUTPUT_TOKENS,
+        "system": system,
+        "messages": messages,
+        "tools": tools_json,
+        "stream": true,
+    });
+
+    let base_url = provider.base_url.trim_end_matches('/');
+    let url = format!("{}/messages", base_url);
+
+    debug!(
+        "POST to {url}: {}",
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let api_key = provider.api_key()?.ok_or_else(|| {
+        CodexErr::EnvVar(EnvVarError {
+            var: provider.env_key.clone().unwrap_or_default(),
+            instructions: provider.env_key_instructions.clone(),
+        })
+    })?;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+
+        let mut req_builder = client
+            .post(&url)
+            .header("x-api-key", &api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION);
+        if prompt_caching.enabled && prompt_caching.ttl == PromptCacheTtl::OneHour {
+            req_builder = req_builder.header("anthropic-beta", EXTENDED_CACHE_TTL_BETA);
+        }
+        let res = req_builder
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload)
+            .send()
+            .await;
+
+        match res {
+            Ok(resp) if resp.status().is_success() => {
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(16);
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                tokio::spawn(process_anthropic_sse(stream, tx_event));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                // 529 means "overloaded" and is worth retrying like a 5xx.
+                if !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                    let body = (res.text().await).unwrap_or_default();
+                    return Err(CodexErr::UnexpectedStatus(status, body));
+                }
+
+                if attempt > *OPENAI_REQUEST_MAX_RETRIES {
+                    return Err(CodexErr::RetryLimit(status));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+
+                let delay = retry_after_secs
+                    .map(|s| Duration::from_millis(s * 1_000))
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > *OPENAI_REQUEST_MAX_RETRIES {
+                    return Err(e.into());
+                }
+                let delay = backoff(attempt);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Rewrite the Responses API function tools into Anthropic's
+/// `{name, description, input_schema}` shape. Built-in tools that only exist
+/// on the Responses API (e.g. `local_shell`) are dropped.
+fn create_tools_json_for_anthropic(
+    prompt: &Prompt,
+    model: &str,
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    let tools_json = create_tools_json_for_responses_api(prompt, model)?
+        .into_iter()
+        .filter(|tool| tool.get("type").and_then(|t| t.as_str()) == Some("function"))
+        .map(|tool| {
+            json!({
+                "name": tool.get("name"),
+                "description": tool.get("description"),
+                "input_schema": tool.get("parameters"),
+            })
+        })
+        .collect();
+    Ok(tools_json)
+}
+
+/// Place `cache_control` breakpoints so that the system prompt, the tool
+/// definitions and, if `prompt_caching.conversation` is set, the conversation
+/// up to the latest message are cached and reused by the next request. The
+/// API caches the whole prefix up to each marked block.
+fn add_cache_breakpoints(
+    system: &mut [serde_json::Value],
+    tools: &mut [serde_json::Value],
+    messages: &mut [serde_json::Value],
+    prompt_caching: PromptCaching,
+) {
+    let cache_control = match prompt_caching.ttl {
+        PromptCacheTtl::FiveMinutes => json!({"type": "ephemeral"}),
+        PromptCacheTtl::OneHour => json!({"type": "ephemeral", "ttl": "1h"}),
+    };
+    let mark = |block: Option<&mut serde_json::Value>| {
+        if let Some(serde_json::Value::Object(block)) = block {
+            block.insert("cache_control".to_string(), cache_control.clone());
+        }
+    };
+    mark(system.last_mut());
+    mark(tools.last_mut());
+    if prompt_caching.conversation {
+        mark(
+            messages
+                .last_mut()
+                .and_then(|message| message["content"].as_array_mut())
+                .and_then(|content| content.last_mut()),
+        );
+    }
+}
+
+/// Translate the conversation into Messages API `messages`. Tool calls become
+/// `tool_use` blocks in assistant turns and their outputs `tool_result`
+/// blocks in user turns; consecutive blocks of the same role are merged into
+/// one message as the API expects strictly alternating roles.
+fn messages_for_prompt(input: &[ResponseItem]) -> Vec<serde_json::Value> {
+    let answered: HashSet<&str> = input
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCallOutput { call_id, .. } => Some(call_id.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut messages = Vec::<serde_json::Value>::new();
+    let mut push_block = |role: &str, block: serde_json::Value| {
+        if let Some(last) = messages.last_mut() {
+            if last["role"] == role {
+                if let Some(content) = last["content"].as_array_mut() {
+                    content.push(block);
+                    return;
+                }
+            }
+        }
+        messages.push(json!({"role": role, "content": [block]}));
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                for c in content {
+                    match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            if !text.is_empty() {
+                                push_block(role, json!({"type": "text", "text": text}));
+                            }
+                        }
+                        ContentItem::InputImage { image_url } => {
+                            if let Some(block) = image_block(image_url) {
+                                push_block(role, block);
+                            }
+                        }
+                    }
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+            } => {
+                let input = serde_json::from_str::<serde_json::Value>(arguments)
+                    .ok()
+                    .filter(|v| v.is_object())
+                    .unwrap_or_else(|| json!({}));
+                push_block(
+                    "assistant",
+                    json!({"type": "tool_use", "id": call_id, "name": name, "input": input}),
+                );
+                // Every `tool_use` needs a `tool_result` in the next user
+                // turn, so stand in for calls that were cancelled.
+                if !answered.contains(call_id.as_str()) {
+                    push_block(
+                        "user",
+                        json!({
+                            "type": "tool_result",
+                            "tool_use_id": call_id,
+                            "content": "Tool cancelled",
+                            "is_error": true,
+                        }),
+                    );
+                }
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                push_block(
+                    "user",
+                    json!({
+                        "type": "tool_result",
+                        "tool_use_id": call_id,
+                        "content": output.content,
+                        "is_error": output.success == Some(false),
+                    }),
+                );
+            }
+            ResponseItem::LocalShellCall { .. }
+            | ResponseItem::Reasoning { .. }
+            | ResponseItem::Other => {
+                // Not produced by this backend; skip.
+                continue;
+            }
+        }
+    }
+    messages
+}
+
+/// An `image` block for a `data:<media type>;base64,<data>` URL. Remote URLs
+/// are not fetched.
+fn image_block(image_url: &str) -> Option<serde_json::Value> {
+    let (media_type, data) = image_url.strip_prefix("data:")?.split_once(";base64,")?;
+    Some(json!({
+        "type": "image",
+        "source": {"type": "base64", "media_type": media_type, "data": data},
+    }))
+}
+
+/// SSE processor for the Messages API streaming format. Text deltas are
+/// forwarded as they arrive (to be aggregated like Chat Completions output),
+/// and each `tool_use` block becomes a `ResponseItem::FunctionCall` once its
+/// `input` JSON has been streamed in full.
+async fn process_anthropic_sse<S>(stream: S, tx_event: mpsc::Sender<Result<ResponseEvent>>)
+where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+
+    let idle_timeout = *OPENAI_STREAM_IDLE_TIMEOUT_MS;
+
+    let mut tool_use: Option<ToolUseState> = None;
+    let mut response_id = String::new();
+    let mut usage = UsageState::default();
+
+    loop {
+        let sse = match timeout(idle_timeout, stream.next()).await {
+            Ok(Some(Ok(ev))) => ev,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event.send(Err(CodexErr::Stream(e.to_string()))).await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream("idle timeout waiting for SSE".into())))
+                    .await;
+                return;
+            }
+        };
+
+        let event: serde_json::Value = match serde_json::from_str(&sse.data) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        trace!("anthropic received SSE event: {event:?}");
+
+        match event
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+        {
+            "message_start" => {
+                if let Some(id) = event.pointer("/message/id").and_then(|v| v.as_str()) {
+                    response_id = id.to_string();
+                }
+                if let Some(message_usage) = event.pointer("/message/usage") {
+                    usage.apply(message_usage);
+                }
+            }
+            "content_block_start" => {
+                let block = &event["content_block"];
+                if block["type"] == "tool_use" {
+                    tool_use = Some(ToolUseState {
+                        id: block["id"].as_str().unwrap_or_default().to_string(),
+                        name: block["name"].as_str().unwrap_or_default().to_string(),
+                        input_json: String::new(),
+                    });
+                }
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str().unwrap_or_default() {
+                    "text_delta" => {
+                        if let Some(text) = delta["text"].as_str().filter(|t| !t.is_empty()) {
+                            let item = ResponseItem::Message {
+                                role: "assistant".to_string(),
+                                content: vec![ContentItem::OutputText {
+                                    text: text.to_string(),
+                                }],
+                            };
+                            let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+                        }
+                    }
+                    "input_json_delta" => {
+                        if let (Some(state), Some(partial)) =
+                            (tool_use.as_mut(), delta["partial_json"].as_str())
+                        {
+                            state.input_json.push_str(partial);
+                        }
+                    }
+                    // Thinking and signature deltas are not surfaced.
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                if let Some(state) = tool_use.take() {
+                    let _ = tx_event
+                        .send(Ok(ResponseEvent::OutputItemDone(state.into_item())))
+                        .await;
+                }
+            }
+            "message_delta" => {
+                if let Some(delta_usage) = event.get("usage") {
+                    usage.apply(delta_usage);
+                }
+            }
+            "message_stop" => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage: usage.into_token_usage(),
+                    }))
+                    .await;
+                return;
+            }
+            "error" => {
+                let message = event
+                    .pointer("/error/message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("unknown error");
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(format!("Anthropic error: {message}"))))
+                    .await;
+                return;
+            }
+            // `ping` and future event types.
+            _ => {}
+        }
+    }
+}
+
+struct ToolUseState {
+    id: String,
+    name: String,
+    input_json: String,
+}
+
+impl ToolUseState {
+    fn into_item(self) -> ResponseItem {
+        ResponseItem::FunctionCall {
+            name: self.name,
+            // A tool called without arguments streams no input at all.
+            arguments: if self.input_json.trim().is_empty() {
+                "{}".to_string()
+            } else {
+                self.input_json
+            },
+            call_id: self.id,
+        }
+    }
+}
+
+/// Token counts as reported across `message_start` (input) and
+/// `message_delta` (cumulative output).
+#[derive(Default)]
+struct UsageState {
+    input_tokens: Option<u64>,
+    cache_read_input_tokens: Option<u64>,
+    cache_creation_input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+}
+
+impl UsageState {
+    fn apply(&mut self, usage: &serde_json::Value) {
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64());
+        self.input_tokens = count("input_tokens").or(self.input_tokens);
+        self.cache_read_input_tokens =
+            count("cache_read_input_tokens").or(self.cache_read_input_tokens);
+        self.cache_creation_input_tokens =
+            count("cache_creation_input_tokens").or(self.cache_creation_input_tokens);
+        self.output_tokens = count("output_tokens").or(self.output_tokens);
+    }
+
+    /// Anthropic counts cached prompt tokens separately from `input_tokens`;
+    /// Codex's `input_tokens` includes them, with the cache reads broken out
+    /// in `cached_input_tokens`.
+    fn into_token_usage(self) -> Option<TokenUsage> {
+        let input_tokens = self.input_tokens?
+            + self.cache_read_input_tokens.unwrap_or(0)
+            + self.cache_creation_input_tokens.unwrap_or(0);
+        let output_tokens = self.output_tokens.unwrap_or(0);
+        Some(TokenUsage {
+            input_tokens,
+            cached_input_tokens: self.cache_read_input_tokens,
+            output_tokens,
+            reasoning_output_tokens: None,
+            total_tokens: input_tokens + output_tokens,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::models::FunctionCallOutputPayload;
+
+    async fn process_events(events: &[&str]) -> Vec<Result<ResponseEvent>> {
+        let body: String = events.iter().map(|e| format!("data: {e}\n\n")).collect();
+        let stream = futures::stream::iter(vec![Ok::<_, CodexErr>(Bytes::from(body))]);
+        let (tx_event, mut rx_event) = mpsc::channel(16);
+        process_anthropic_sse(stream, tx_event).await;
+        let mut out = Vec::new();
+        while let Some(event) = rx_event.recv().await {
+            out.push(event);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn maps_text_tool_use_and_usage() {
+        let events = process_events(&[
+            r#"{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":20,"cache_read_input_tokens":80,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Listing."}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"shell","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"[\"ls\"]}"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":15}}"#,
+            r#"{"type":"message_stop"}"#,
+        ])
+        .await;
+
+        let events: Vec<ResponseEvent> = events.into_iter().map(|e| e.unwrap()).collect();
+        let [
+            ResponseEvent::OutputItemDone(ResponseItem::Message { content, .. }),
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+            }),
+            ResponseEvent::Completed {
+                response_id,
+                token_usage,
+            },
+        ] = events.as_slice()
+        else {
+            panic!("unexpected events: {events:?}");
+        };
+        assert!(matches!(&content[..], [ContentItem::OutputText { text }] if text == "Listing."));
+        assert_eq!(name, "shell");
+        assert_eq!(arguments, r#"{"command":["ls"]}"#);
+        assert_eq!(call_id, "toolu_1");
+        assert_eq!(response_id, "msg_1");
+        let usage = token_usage.unwrap();
+        assert_eq!(
+            (
+                usage.input_tokens,
+                usage.cached_input_tokens,
+                usage.output_tokens,
+                usage.total_tokens
+            ),
+            (100, Some(80), 15, 115)
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_without_message_stop_is_an_error() {
+        let events = process_events(&[
+            r#"{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":1}}}"#,
+        ])
+        .await;
+        assert!(matches!(events.last(), Some(Err(CodexErr::Stream(_)))));
+    }
+
+    #[test]
+    fn cache_breakpoints_mark_last_system_tool_and_message_blocks() {
+        let mut system = vec![json!({"type": "text", "text": "be helpful"})];
+        let mut tools = vec![json!({"name": "shell"}), json!({"name": "edit_file"})];
+        let mut messages = vec![
+            json!({"role": "user", "content": [{"type": "text", "text": "hi"}]}),
+            json!({"role": "assistant", "content": [{"type": "text", "text": "hello"}]}),
+            json!({"role": "user", "content": [{"type": "text", "text": "a"}, {"type": "text", "text": "b"}]}),
+        ];
+        let prompt_caching = PromptCaching {
+            ttl: PromptCacheTtl::OneHour,
+            ..PromptCaching::default()
+        };
+        add_cache_breakpoints(&mut system, &mut tools, &mut messages, prompt_caching);
+
+        let marker = json!({"type": "ephemeral", "ttl": "1h"});
+        assert_eq!(system[0]["cache_control"], marker);
+        assert!(tools[0].get("cache_control").is_none());
+        assert_eq!(tools[1]["cache_control"], marker);
+        assert!(messages[2]["content"][0].get("cache_control").is_none());
+        assert_eq!(messages[2]["content"][1]["cache_control"], marker);
+
+        let mut messages =
+            vec![json!({"role": "user", "content": [{"type": "text", "text": "hi"}]})];
+        let prompt_caching = PromptCaching {
+            conversation: false,
+            ..PromptCaching::default()
+        };
+        add_cache_breakpoints(&mut system, &mut tools, &mut messages, prompt_caching);
+        assert!(messages[0]["content"][0].get("cache_control").is_none());
+        assert_eq!(system[0]["cache_control"], json!({"type": "ephemeral"}));
+    }
+
+    #[test]
+    fn tool_calls_map_to_alternating_content_blocks() {
+        let input = vec![
+            ResponseItem::Message {
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::FunctionCall {
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "README.md".to_string(),
+                    success: Some(true),
+                },
+            },
+            ResponseItem::FunctionCall {
+                name: "shell".to_string(),
+                arguments: r#"{"command":["rm","-rf","/"]}"#.to_string(),
+                call_id: "toolu_2".to_string(),
+            },
+            ResponseItem::Message {
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "never mind".to_string(),
+                }],
+            },
+        ];
+
+        let messages = messages_for_prompt(&input);
+        let roles: Vec<&str> = messages
+            .iter()
+            .map(|m| m["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            roles,
+            vec!["user", "assistant", "user", "assistant", "user"]
+        );
+        assert_eq!(
+            messages[1]["content"][0]["input"],
+            json!({"command": ["ls"]})
+        );
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+        // The cancelled call is answered, followed by the next user message.
+        assert_eq!(messages[4]["content"][0]["tool_use_id"], "toolu_2");
+        assert_eq!(messages[4]["content"][0]["is_error"], true);
+        assert_eq!(messages[4]["content"][1]["text"], "never mind");
+    }
+}
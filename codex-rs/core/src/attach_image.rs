@@ -0,0 +1,124 @@
+//! Support for the `attach_image` tool: load an image from disk, downsize it
+//! if it is larger than the model needs, and encode it as a `data:` URL that
+//! can be sent as an `input_image` content item.
+
+use std::io::Cursor;
+use std::path::Path;
+
+use base64::Engine;
+use image::GenericImageView;
+use image::ImageFormat;
+use serde::Deserialize;
+
+/// Longest edge (in pixels) of images sent to the model. Larger images are
+/// scaled down, preserving the aspect ratio.
+const MAX_IMAGE_DIMENSION: u32 = 2048;
+
+/// Refuse to even decode files larger than this.
+const MAX_IMAGE_FILE_BYTES: u64 = 20 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AttachImageArgs {
+    pub path: String,
+}
+
+/// An image ready to be attached to the conversation.
+#[derive(Debug)]
+pub(crate) struct LoadedImage {
+    pub data_url: String,
+    pub width: u32,
+    pub height: u32,
+    /// `true` if the image was scaled down to fit [`MAX_IMAGE_DIMENSION`].
+    pub resized: bool,
+}
+
+/// Read the PNG or JPEG at `path`. Errors are returned as messages suitable
+/// for the model.
+pub(crate) fn load_image(path: &Path) -> Result<LoadedImage, String> {
+    let display = path.display();
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("failed to read {display}: {e}"))?
+        .len();
+    if size > MAX_IMAGE_FILE_BYTES {
+        return Err(format!(
+            "{display} is {size} bytes; images larger than {MAX_IMAGE_FILE_BYTES} bytes are not supported"
+        ));
+    }
+    let bytes = std::fs::read(path).map_err(|e| format!("failed to read {display}: {e}"))?;
+
+    let format = match image::guess_format(&bytes) {
+        Ok(format @ (ImageFormat::Png | ImageFormat::Jpeg)) => format,
+        _ => return Err(format!("{display} is not a PNG or JPEG image")),
+    };
+
+    let img = image::load_from_memory_with_format(&bytes, format)
+        .map_err(|e| format!("failed to decode {display}: {e}"))?;
+    let (width, height) = img.dimensions();
+    if width <= MAX_IMAGE_DIMENSION && height <= MAX_IMAGE_DIMENSION {
+        return Ok(LoadedImage {
+            data_url: data_url(format, &bytes),
+            width,
+            height,
+            resized: false,
+        });
+    }
+
+    let resized = img.thumbnail(MAX_IMAGE_DIMENSION, MAX_IMAGE_DIMENSION);
+    let mut encoded = Vec::new();
+    resized
+        .write_to(&mut Cursor::new(&mut encoded), format)
+        .map_err(|e| format!("failed to re-encode {display}: {e}"))?;
+    let (width, height) = resized.dimensions();
+    Ok(LoadedImage {
+        data_url: data_url(format, &encoded),
+        width,
+        height,
+        resized: true,
+    })
+}
+
+fn data_url(format: ImageFormat, bytes: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    format!("data:{};base64,{encoded}", format.to_mime_type())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use image::RgbImage;
+    use tempfile::TempDir;
+
+    fn write_png(dir: &TempDir, width: u32, height: u32) -> std::path::PathBuf {
+        let path = dir.path().join("test.png");
+        RgbImage::new(width, height).save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn small_png_is_sent_as_is() {
+        let dir = TempDir::new().unwrap();
+        let path = write_png(&dir, 4, 3);
+        let img = load_image(&path).unwrap();
+        assert_eq!((img.width, img.height, img.resized), (4, 3, false));
+        assert!(img.data_url.starts_with("data:image/png;base64,"));
+    }
+
+    #[test]
+    fn large_png_is_downsized() {
+        let dir = TempDir::new().unwrap();
+        let path = write_png(&dir, 4096, 1024);
+        let img = load_image(&path).unwrap();
+        assert_eq!((img.width, img.height, img.resized), (2048, 512, true));
+    }
+
+    #[test]
+    fn non_image_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let err = load_image(&path).unwrap_err();
+        assert!(err.contains("not a PNG or JPEG"), "{err}");
+    }
+}
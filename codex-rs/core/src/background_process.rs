@@ -0,0 +1,262 @@
+//! Long-running commands started with the `exec_background` tool, e.g. dev
+//! servers and file watchers. Their output is collected while they run and
+//! handed to the model when it polls with `process_output`.
+//!
+//! Each background process leads its own process group, which is killed when
+//! the session that started it goes away, so commands the process started
+//! do not outlive the session either.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::process::Child;
+
+use crate::exec::kill_process_group;
+use crate::protocol::BackgroundProcessInfo;
+
+/// Output kept per process between polls. Older output is dropped first.
+const MAX_UNREAD_OUTPUT: usize = 64 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ExecBackgroundArgs {
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub workdir: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProcessIdArgs {
+    pub id: u32,
+}
+
+#[derive(Default)]
+struct OutputBuffer {
+    unread: String,
+    /// Bytes discarded because the model did not poll often enough.
+    dropped: usize,
+}
+
+struct BackgroundProcess {
+    command: Vec<String>,
+    child: Child,
+    output: Arc<Mutex<OutputBuffer>>,
+}
+
+#[derive(Default)]
+pub(crate) struct BackgroundProcesses {
+    next_id: u32,
+    processes: BTreeMap<u32, BackgroundProcess>,
+}
+
+impl BackgroundProcesses {
+    /// Track `child`, whose stdout and stderr must be piped, and return its id.
+    pub(crate) fn start(&mut self, command: Vec<String>, mut child: Child) -> u32 {
+        let output = Arc::new(Mutex::new(OutputBuffer::default()));
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(collect_output(stdout, Arc::clone(&output)));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(collect_output(stderr, Arc::clone(&output)));
+        }
+
+        self.next_id += 1;
+        let id = self.next_id;
+        self.processes.insert(
+            id,
+            BackgroundProcess {
+                command,
+                child,
+                output,
+            },
+        );
+        id
+    }
+
+    /// Output produced by process `id` since the last call, followed by its
+    /// exit status once it has finished. Finished processes are forgotten
+    /// after their remaining output has been returned.
+    pub(crate) fn read_output(&mut self, id: u32) -> Result<String, String> {
+        let process = self
+            .processes
+            .get_mut(&id)
+            .ok_or_else(|| format!("no background process with id {id}"))?;
+        let exit_status = process
+            .child
+            .try_wait()
+            .map_err(|e| format!("failed to check process {id}: {e}"))?;
+        // Only report the exit once the readers have drained the pipes (they
+        // hold the other references to `output`), so no output is lost.
+        let exit_status = exit_status.filter(|_| Arc::strong_count(&process.output) == 1);
+
+        let mut report = String::new();
+        {
+            #[expect(clippy::unwrap_used)]
+            let mut output = process.output.lock().unwrap();
+            if output.dropped > 0 {
+                report.push_str(&format!("[{} earlier bytes dropped]\n", output.dropped));
+                output.dropped = 0;
+            }
+            report.push_str(&std::mem::take(&mut output.unread));
+        }
+        match exit_status {
+            Some(status) => {
+                let code = status
+                    .code()
+                    .map_or_else(|| "none".to_string(), |c| c.to_string());
+                report.push_str(&format!("\n[process {id} exited with code {code}]"));
+                self.processes.remove(&id);
+            }
+            None => report.push_str(&format!("\n[process {id} is still running]")),
+        }
+        Ok(report)
+    }
+
+    pub(crate) fn kill(&mut self, id: u32) -> Result<(), String> {
+        let mut process = self
+            .processes
+            .remove(&id)
+            .ok_or_else(|| format!("no background process with id {id}"))?;
+        if let Some(pid) = process.child.id() {
+            kill_process_group(pid);
+        }
+        process
+            .child
+            .start_kill()
+            .map_err(|e| format!("failed to kill process {id}: {e}"))
+    }
+
+    pub(crate) fn list(&self) -> Vec<BackgroundProcessInfo> {
+        self.processes
+            .iter()
+            .map(|(id, process)| BackgroundProcessInfo {
+                id: *id,
+                command: process.command.clone(),
+            })
+            .collect()
+    }
+}
+
+impl Drop for BackgroundProcesses {
+    fn drop(&mut self) {
+        // `kill_on_drop` only reaches the child itself.
+        for process in self.processes.values() {
+            if let Some(pid) = process.child.id() {
+                kill_process_group(pid);
+            }
+        }
+    }
+}
+
+async fn collect_output<R: AsyncRead + Unpin>(mut reader: R, output: Arc<Mutex<OutputBuffer>>) {
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        #[expect(clippy::unwrap_used)]
+        let mut output = output.lock().unwrap();
+        output.unread.push_str(&String::from_utf8_lossy(&buf[..n]));
+        let excess = output.unread.len().saturating_sub(MAX_UNREAD_OUTPUT);
+        if excess > 0 {
+            let mut cut = excess;
+            while !output.unread.is_char_boundary(cut) {
+                cut += 1;
+            }
+            output.unread.drain(..cut);
+            output.dropped += cut;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::process::Stdio;
+    use tokio::process::Command;
+
+    fn spawn(script: &str) -> Child {
+        Command::new("bash")
+            .args(["-c", script])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn output_is_collected_until_exit() {
+        let mut processes = BackgroundProcesses::default();
+        let id = processes.start(vec!["echo".to_string()], spawn("echo ready"));
+        assert_eq!(processes.list().len(), 1);
+
+        let mut report = String::new();
+        for _ in 0..100 {
+            report.push_str(&processes.read_output(id).unwrap());
+            if processes.list().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        assert!(report.contains("ready\n"), "{report}");
+        assert!(report.ends_with(&format!("[process {id} exited with code 0]")));
+        assert!(processes.list().is_empty());
+        assert!(processes.read_output(id).is_err());
+    }
+
+    #[tokio::test]
+    async fn kill_removes_the_process() {
+        let mut processes = BackgroundProcesses::default();
+        let id = processes.start(vec!["sleep".to_string()], spawn("sleep 30"));
+        let report = processes.read_output(id).unwrap();
+        assert!(report.ends_with(&format!("[process {id} is still running]")));
+
+        processes.kill(id).unwrap();
+        assert!(processes.list().is_empty());
+        assert!(processes.kill(id).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dropping_kills_what_the_processes_started() {
+        let mut child = Command::new("bash")
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        let mut buf = [0u8; 32];
+        let n = stdout.read(&mut buf).await.unwrap();
+        let grandchild: libc::pid_t = String::from_utf8_lossy(&buf[..n]).trim().parse().unwrap();
+
+        let mut processes = BackgroundProcesses::default();
+        processes.start(vec!["bash".to_string()], child);
+        drop(processes);
+
+        // SAFETY: signal 0 only checks whether the process exists. A killed
+        // process can linger as a zombie until it is reaped.
+        let alive = || {
+            (unsafe { libc::kill(grandchild, 0) }) == 0
+                && !std::fs::read_to_string(format!("/proc/{grandchild}/stat"))
+                    .is_ok_and(|stat| stat.contains(") Z"))
+        };
+        for _ in 0..100 {
+            if !alive() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(!alive(), "sleep {grandchild} survived");
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use bytes::Bytes;
@@ -14,6 +15,7 @@ use tokio::sync::mpsc;
 use tokio::time::timeout;
 use tracing::debug;
 use tracing::trace;
+use uuid::Uuid;
 
 use crate::ModelProviderInfo;
 use crate::client_common::Prompt;
@@ -26,6 +28,7 @@ use crate::flags::OPENAI_STREAM_IDLE_TIMEOUT_MS;
 use crate::models::ContentItem;
 use crate::models::ResponseItem;
 use crate::openai_tools::create_tools_json_for_chat_completions_api;
+use crate::protocol::TokenUsage;
 use crate::util::backoff;
 
 /// Implementation for the classic Chat Completions API.
@@ -208,6 +211,11 @@ pub(crate) async fn stream_chat_completions(
 /// Lightweight SSE processor for the Chat Completions streaming format. The
 /// output is mapped onto Codex's internal [`ResponseEvent`] so that the rest
 /// of the pipeline can stay agnostic of the underlying wire format.
+///
+/// OpenAI-compatible servers (Ollama, vLLM, LM Studio, ...) differ from OpenAI
+/// in the details, so the processor also accepts tool calls without an `id`
+/// or `index`, arguments sent as a JSON object instead of a string, and tool
+/// calls that end with `finish_reason: "stop"` or no finish reason at all.
 async fn process_chat_sse<S>(stream: S, tx_event: mpsc::Sender<Result<ResponseEvent>>)
 where
     S: Stream<Item = Result<Bytes>> + Unpin,
@@ -216,20 +224,12 @@ where
 
     let idle_timeout = *OPENAI_STREAM_IDLE_TIMEOUT_MS;
 
-    // State to accumulate a function call across streaming chunks.
-    // OpenAI may split the `arguments` string over multiple `delta` events
-    // until the chunk whose `finish_reason` is `tool_calls` is emitted. We
-    // keep collecting the pieces here and forward a single
-    // `ResponseItem::FunctionCall` once the call is complete.
-    #[derive(Default)]
-    struct FunctionCallState {
-        name: Option<String>,
-        arguments: String,
-        call_id: Option<String>,
-        active: bool,
-    }
-
-    let mut fn_call_state = FunctionCallState::default();
+    // Tool calls being streamed, keyed by their `index`. OpenAI may split the
+    // `arguments` string of each call over multiple `delta` events; we collect
+    // the pieces here and forward one `ResponseItem::FunctionCall` per call
+    // once the turn finishes.
+    let mut fn_calls: BTreeMap<u64, FunctionCallState> = BTreeMap::new();
+    let mut token_usage: Option<TokenUsage> = None;
 
     loop {
         let sse = match timeout(idle_timeout, stream.next()).await {
@@ -240,11 +240,7 @@ where
             }
             Ok(None) => {
                 // Stream closed gracefully – emit Completed with dummy id.
-                let _ = tx_event
-                    .send(Ok(ResponseEvent::Completed {
-                        response_id: String::new(),
-                    }))
-                    .await;
+                finish_turn(&tx_event, &mut fn_calls, token_usage).await;
                 return;
             }
             Err(_) => {
@@ -257,11 +253,7 @@ where
 
         // OpenAI Chat streaming sends a literal string "[DONE]" when finished.
         if sse.data.trim() == "[DONE]" {
-            let _ = tx_event
-                .send(Ok(ResponseEvent::Completed {
-                    response_id: String::new(),
-                }))
-                .await;
+            finish_turn(&tx_event, &mut fn_calls, token_usage).await;
             return;
         }
 
@@ -272,6 +264,10 @@ where
         };
         trace!("chat_completions received SSE chunk: {chunk:?}");
 
+        if let Some(usage) = chunk.get("usage").and_then(parse_usage) {
+            token_usage = Some(usage);
+        }
+
         let choice_opt = chunk.get("choices").and_then(|c| c.get(0));
 
         if let Some(choice) = choice_opt {
@@ -280,6 +276,7 @@ where
                 .get("delta")
                 .and_then(|d| d.get("content"))
                 .and_then(|c| c.as_str())
+                .filter(|c| !c.is_empty())
             {
                 let item = ResponseItem::Message {
                     role: "assistant".to_string(),
@@ -297,66 +294,103 @@ where
                 .and_then(|d| d.get("tool_calls"))
                 .and_then(|tc| tc.as_array())
             {
-                if let Some(tool_call) = tool_calls.first() {
-                    // Mark that we have an active function call in progress.
-                    fn_call_state.active = true;
-
-                    // Extract call_id if present.
-                    if let Some(id) = tool_call.get("id").and_then(|v| v.as_str()) {
-                        fn_call_state.call_id.get_or_insert_with(|| id.to_string());
-                    }
-
-                    // Extract function details if present.
-                    if let Some(function) = tool_call.get("function") {
-                        if let Some(name) = function.get("name").and_then(|n| n.as_str()) {
-                            fn_call_state.name.get_or_insert_with(|| name.to_string());
-                        }
-
-                        if let Some(args_fragment) =
-                            function.get("arguments").and_then(|a| a.as_str())
-                        {
-                            fn_call_state.arguments.push_str(args_fragment);
-                        }
-                    }
+                for (position, tool_call) in tool_calls.iter().enumerate() {
+                    // Servers that send every call whole may omit `index`.
+                    let index = tool_call
+                        .get("index")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(position as u64);
+                    fn_calls.entry(index).or_default().apply_delta(tool_call);
                 }
             }
 
-            // Emit end-of-turn when finish_reason signals completion.
-            if let Some(finish_reason) = choice.get("finish_reason").and_then(|v| v.as_str()) {
-                match finish_reason {
-                    "tool_calls" if fn_call_state.active => {
-                        // Build the FunctionCall response item.
-                        let item = ResponseItem::FunctionCall {
-                            name: fn_call_state.name.clone().unwrap_or_else(|| "".to_string()),
-                            arguments: fn_call_state.arguments.clone(),
-                            call_id: fn_call_state.call_id.clone().unwrap_or_else(String::new),
-                        };
-
-                        // Emit it downstream.
-                        let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
-                    }
-                    "stop" => {
-                        // Regular turn without tool-call.
-                    }
-                    _ => {}
-                }
-
-                // Emit Completed regardless of reason so the agent can advance.
-                let _ = tx_event
-                    .send(Ok(ResponseEvent::Completed {
-                        response_id: String::new(),
-                    }))
-                    .await;
+            // Emit end-of-turn when finish_reason signals completion. Ollama
+            // reports "stop" even when the turn ends with tool calls, so any
+            // collected calls are emitted regardless of the reason.
+            if choice.get("finish_reason").is_some_and(|v| v.is_string()) {
+                finish_turn(&tx_event, &mut fn_calls, token_usage).await;
+                return; // End processing for this SSE stream.
+            }
+        }
+    }
+}
 
-                // Prepare for potential next turn (should not happen in same stream).
-                // fn_call_state = FunctionCallState::default();
+#[derive(Default)]
+struct FunctionCallState {
+    name: Option<String>,
+    arguments: String,
+    call_id: Option<String>,
+}
 
-                return; // End processing for this SSE stream.
+impl FunctionCallState {
+    fn apply_delta(&mut self, tool_call: &serde_json::Value) {
+        if let Some(id) = tool_call.get("id").and_then(|v| v.as_str()) {
+            self.call_id.get_or_insert_with(|| id.to_string());
+        }
+        let Some(function) = tool_call.get("function") else {
+            return;
+        };
+        if let Some(name) = function.get("name").and_then(|n| n.as_str()) {
+            self.name.get_or_insert_with(|| name.to_string());
+        }
+        match function.get("arguments") {
+            Some(serde_json::Value::String(fragment)) => self.arguments.push_str(fragment),
+            // Some servers send the arguments already parsed.
+            Some(arguments @ serde_json::Value::Object(_)) => {
+                self.arguments = arguments.to_string();
             }
+            _ => {}
+        }
+    }
+
+    fn into_item(self) -> ResponseItem {
+        ResponseItem::FunctionCall {
+            name: self.name.unwrap_or_default(),
+            arguments: self.arguments,
+            // The call id pairs the tool output with the call in the next
+            // request, so make one up if the server did not send it.
+            call_id: self
+                .call_id
+                .filter(|id| !id.is_empty())
+                .unwrap_or_else(|| format!("call_{}", Uuid::new_v4().simple())),
         }
     }
 }
 
+/// Emit the collected tool calls followed by `Completed`.
+async fn finish_turn(
+    tx_event: &mpsc::Sender<Result<ResponseEvent>>,
+    fn_calls: &mut BTreeMap<u64, FunctionCallState>,
+    token_usage: Option<TokenUsage>,
+) {
+    for (_, call) in std::mem::take(fn_calls) {
+        let _ = tx_event
+            .send(Ok(ResponseEvent::OutputItemDone(call.into_item())))
+            .await;
+    }
+    let _ = tx_event
+        .send(Ok(ResponseEvent::Completed {
+            response_id: String::new(),
+            token_usage,
+        }))
+        .await;
+}
+
+/// Token usage of a Chat Completions `usage` object, as sent in the last
+/// chunk by servers that report it while streaming.
+fn parse_usage(usage: &serde_json::Value) -> Option<TokenUsage> {
+    let count = |pointer: &str| usage.pointer(pointer).and_then(|v| v.as_u64());
+    let input_tokens = count("/prompt_tokens")?;
+    let output_tokens = count("/completion_tokens")?;
+    Some(TokenUsage {
+        input_tokens,
+        cached_input_tokens: count("/prompt_tokens_details/cached_tokens"),
+        output_tokens,
+        reasoning_output_tokens: count("/completion_tokens_details/reasoning_tokens"),
+        total_tokens: count("/total_tokens").unwrap_or(input_tokens + output_tokens),
+    })
+}
+
 /// Optional client-side aggregation helper
 ///
 /// Stream adapter that merges the incremental `OutputItemDone` chunks coming from
@@ -422,7 +456,10 @@ where
                     // Not an assistant message – forward immediately.
                     return Poll::Ready(Some(Ok(ResponseEvent::OutputItemDone(item))));
                 }
-                Poll::Ready(Some(Ok(ResponseEvent::Completed { response_id }))) => {
+                Poll::Ready(Some(Ok(ResponseEvent::Completed {
+                    response_id,
+                    token_usage,
+                }))) => {
                     if !this.cumulative.is_empty() {
                         let aggregated_item = crate::models::ResponseItem::Message {
                             role: "assistant".to_string(),
@@ -432,7 +469,10 @@ where
                         };
 
                         // Buffer Completed so it is returned *after* the aggregated message.
-                        this.pending_completed = Some(ResponseEvent::Completed { response_id });
+                        this.pending_completed = Some(ResponseEvent::Completed {
+                            response_id,
+                            token_usage,
+                        });
 
                         return Poll::Ready(Some(Ok(ResponseEvent::OutputItemDone(
                             aggregated_item,
@@ -440,7 +480,10 @@ where
                     }
 
                     // Nothing aggregated – forward Completed directly.
-                    return Poll::Ready(Some(Ok(ResponseEvent::Completed { response_id })));
+                    return Poll::Ready(Some(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage,
+                    })));
                 } // No other `Ok` variants exist at the moment, continue polling.
             }
         }
@@ -478,3 +521,84 @@ pub(crate) trait AggregateStreamExt: Stream<Item = Result<ResponseEvent>> + Size
 }
 
 impl<T> AggregateStreamExt for T where T: Stream<Item = Result<ResponseEvent>> + Sized {}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    async fn process_chunks(chunks: &[&str]) -> Vec<ResponseEvent> {
+        let body: String = chunks.iter().map(|c| format!("data: {c}\n\n")).collect();
+        let stream = futures::stream::iter(vec![Ok::<_, CodexErr>(Bytes::from(body))]);
+        let (tx_event, mut rx_event) = mpsc::channel(16);
+        process_chat_sse(stream, tx_event).await;
+        let mut events = Vec::new();
+        while let Some(event) = rx_event.recv().await {
+            events.push(event.unwrap());
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn ollama_tool_call_without_id_ends_with_stop() {
+        let events = process_chunks(&[
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"shell","arguments":{"command":["ls"]}}}]},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":""},"finish_reason":"stop"}],"usage":{"prompt_tokens":10,"completion_tokens":5,"total_tokens":15}}"#,
+        ])
+        .await;
+
+        let [
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+            }),
+            ResponseEvent::Completed { token_usage, .. },
+        ] = events.as_slice()
+        else {
+            panic!("unexpected events: {events:?}");
+        };
+        assert_eq!(name, "shell");
+        assert_eq!(arguments, r#"{"command":["ls"]}"#);
+        assert!(call_id.starts_with("call_"));
+        let usage = token_usage.unwrap();
+        assert_eq!(
+            (usage.input_tokens, usage.output_tokens, usage.total_tokens),
+            (10, 5, 15)
+        );
+    }
+
+    #[tokio::test]
+    async fn parallel_tool_calls_are_split_by_index() {
+        let events = process_chunks(&[
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_a","function":{"name":"shell","arguments":"{\"command\":"}},{"index":1,"id":"call_b","function":{"name":"shell","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":1,"function":{"arguments":"{\"command\":[\"pwd\"]}"}},{"index":0,"function":{"arguments":"[\"ls\"]}"}}]}}]}"#,
+            "[DONE]",
+        ])
+        .await;
+
+        let calls: Vec<(String, String)> = events
+            .iter()
+            .filter_map(|event| match event {
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                    arguments,
+                    call_id,
+                    ..
+                }) => Some((call_id.clone(), arguments.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("call_a".to_string(), r#"{"command":["ls"]}"#.to_string()),
+                ("call_b".to_string(), r#"{"command":["pwd"]}"#.to_string()),
+            ]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(ResponseEvent::Completed { .. })
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+//! Snapshots of the files each turn's patches touched, so that a turn's file
+//! changes can be rolled back with [`crate::protocol::Op::RevertTurn`].
+
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Only this many of the most recent turns can be reverted.
+const MAX_CHECKPOINTS: usize = 20;
+
+/// Contents of each file a turn changed, as they were before the turn's first
+/// change to it. `None` means the file did not exist.
+struct TurnCheckpoint {
+    sub_id: String,
+    files: BTreeMap<PathBuf, Option<Vec<u8>>>,
+}
+
+#[derive(Default)]
+pub(crate) struct Checkpoints {
+    turns: VecDeque<TurnCheckpoint>,
+}
+
+impl Checkpoints {
+    /// Record the current contents of `paths` for the turn `sub_id`. Files
+    /// already recorded for this turn keep their earlier snapshot.
+    pub(crate) fn snapshot<'a>(
+        &mut self,
+        sub_id: &str,
+        paths: impl IntoIterator<Item = &'a Path>,
+    ) -> std::io::Result<()> {
+        if self.turns.back().is_none_or(|turn| turn.sub_id != sub_id) {
+            if self.turns.len() == MAX_CHECKPOINTS {
+                self.turns.pop_front();
+            }
+            self.turns.push_back(TurnCheckpoint {
+                sub_id: sub_id.to_string(),
+                files: BTreeMap::new(),
+            });
+        }
+        let Some(turn) = self.turns.back_mut() else {
+            return Ok(());
+        };
+        for path in paths {
+            if turn.files.contains_key(path) {
+                continue;
+            }
+            let contents = match std::fs::read(path) {
+                Ok(contents) => Some(contents),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e),
+            };
+            turn.files.insert(path.to_path_buf(), contents);
+        }
+        Ok(())
+    }
+
+    /// Restore the files of the most recent turn that changed any, returning
+    /// the reverted paths, or `None` if there is nothing to revert. If
+    /// restoring fails, the checkpoint is kept so the revert can be retried.
+    pub(crate) fn revert_last(&mut self) -> Option<std::io::Result<Vec<PathBuf>>> {
+        let result = restore(self.turns.back()?);
+        if result.is_ok() {
+            self.turns.pop_back();
+        }
+        Some(result)
+    }
+}
+
+fn restore(turn: &TurnCheckpoint) -> std::io::Result<Vec<PathBuf>> {
+    let mut reverted = Vec::with_capacity(turn.files.len());
+    for (path, contents) in &turn.files {
+        match contents {
+            Some(contents) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, contents)?;
+            }
+            None => match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            },
+        }
+        reverted.push(path.clone());
+    }
+    Ok(reverted)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reverts_the_last_turn_only() {
+        let dir = tempdir().unwrap();
+        let edited = dir.path().join("edited.txt");
+        let added = dir.path().join("added.txt");
+        std::fs::write(&edited, "original").unwrap();
+
+        let mut checkpoints = Checkpoints::default();
+        checkpoints.snapshot("1", [edited.as_path()]).unwrap();
+        std::fs::write(&edited, "turn 1").unwrap();
+
+        checkpoints
+            .snapshot("2", [edited.as_path(), added.as_path()])
+            .unwrap();
+        std::fs::write(&edited, "turn 2").unwrap();
+        std::fs::write(&added, "new").unwrap();
+        // A later patch in the same turn keeps the first snapshot.
+        checkpoints.snapshot("2", [edited.as_path()]).unwrap();
+        std::fs::write(&edited, "turn 2, again").unwrap();
+
+        let reverted = checkpoints.revert_last().unwrap().unwrap();
+        assert_eq!(reverted, vec![added.clone(), edited.clone()]);
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "turn 1");
+        assert!(!added.exists());
+
+        checkpoints.revert_last().unwrap().unwrap();
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "original");
+        assert!(checkpoints.revert_last().is_none());
+    }
+
+    #[test]
+    fn a_failed_revert_can_be_retried() {
+        let dir = tempdir().unwrap();
+        let added = dir.path().join("added");
+
+        let mut checkpoints = Checkpoints::default();
+        checkpoints.snapshot("1", [added.as_path()]).unwrap();
+        // A directory where the turn added a file cannot be removed as one.
+        std::fs::create_dir(&added).unwrap();
+        assert!(checkpoints.revert_last().unwrap().is_err());
+
+        std::fs::remove_dir(&added).unwrap();
+        std::fs::write(&added, "new").unwrap();
+        assert_eq!(
+            checkpoints.revert_last().unwrap().unwrap(),
+            vec![added.clone()]
+        );
+        assert!(!added.exists());
+        assert!(checkpoints.revert_last().is_none());
+    }
+}
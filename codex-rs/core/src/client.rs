@@ -6,6 +6,8 @@ use bytes::Bytes;
 use eventsource_stream::Eventsource;
 use futures::prelude::*;
 use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderValue;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
@@ -16,6 +18,7 @@ use tracing::debug;
 use tracing::trace;
 use tracing::warn;
 
+use crate::anthropic::stream_anthropic_messages;
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
 use crate::client_common::Prompt;
@@ -23,6 +26,7 @@ use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
 use crate::client_common::ResponsesApiRequest;
 use crate::client_common::create_reasoning_param_for_request;
+use crate::config_types::PromptCaching;
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use crate::error::CodexErr;
@@ -35,6 +39,7 @@ use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
 use crate::models::ResponseItem;
 use crate::openai_tools::create_tools_json_for_responses_api;
+use crate::protocol::TokenUsage;
 use crate::util::backoff;
 
 #[derive(Clone)]
@@ -44,6 +49,7 @@ pub struct ModelClient {
     provider: ModelProviderInfo,
     effort: ReasoningEffortConfig,
     summary: ReasoningSummaryConfig,
+    prompt_caching: PromptCaching,
 }
 
 impl ModelClient {
@@ -52,6 +58,7 @@ impl ModelClient {
         provider: ModelProviderInfo,
         effort: ReasoningEffortConfig,
         summary: ReasoningSummaryConfig,
+        prompt_caching: PromptCaching,
     ) -> Self {
         Self {
             model: model.to_string(),
@@ -59,9 +66,57 @@ impl ModelClient {
             provider,
             effort,
             summary,
+            prompt_caching,
         }
     }
 
+    /// The same client for another model of the provider.
+    pub fn with_model(&self, model: impl ToString) -> Self {
+        Self {
+            model: model.to_string(),
+            ..self.clone()
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn provider(&self) -> &ModelProviderInfo {
+        &self.provider
+    }
+
+    /// Bill the requests to `org_id` and the project `workspace_id` instead
+    /// of the login's defaults. Anthropic has no such headers.
+    pub fn with_organization(mut self, org_id: Option<&str>, workspace_id: Option<&str>) -> Self {
+        if self.provider.wire_api == WireApi::Anthropic {
+            return self;
+        }
+        let mut headers = HeaderMap::new();
+        for (name, value) in [
+            ("OpenAI-Organization", org_id),
+            ("OpenAI-Project", workspace_id),
+        ] {
+            let Some(value) = value else {
+                continue;
+            };
+            match HeaderValue::from_str(value) {
+                Ok(value) => {
+                    headers.insert(name, value);
+                }
+                Err(e) => warn!("ignoring invalid {name} `{value}`: {e}"),
+            }
+        }
+        if headers.is_empty() {
+            return self;
+        }
+        match reqwest::Client::builder().default_headers(headers).build() {
+            Ok(client) => self.client = client,
+            Err(e) => warn!("failed to set the organization headers: {e}"),
+        }
+        self
+    }
+
     /// Dispatches to either the Responses or Chat implementation depending on
     /// the provider config.  Public callers always invoke `stream()` – the
     /// specialised helpers are private to avoid accidental misuse.
@@ -69,31 +124,21 @@ impl ModelClient {
         match self.provider.wire_api {
             WireApi::Responses => self.stream_responses(prompt).await,
             WireApi::Chat => {
-                // Create the raw streaming connection first.
                 let response_stream =
                     stream_chat_completions(prompt, &self.model, &self.client, &self.provider)
                         .await?;
-
-                // Wrap it with the aggregation adapter so callers see *only*
-                // the final assistant message per turn (matching the
-                // behaviour of the Responses API).
-                let mut aggregated = response_stream.aggregate();
-
-                // Bridge the aggregated stream back into a standard
-                // `ResponseStream` by forwarding events through a channel.
-                let (tx, rx) = mpsc::channel::<Result<ResponseEvent>>(16);
-
-                tokio::spawn(async move {
-                    use futures::StreamExt;
-                    while let Some(ev) = aggregated.next().await {
-                        // Exit early if receiver hung up.
-                        if tx.send(ev).await.is_err() {
-                            break;
-                        }
-                    }
-                });
-
-                Ok(ResponseStream { rx_event: rx })
+                Ok(aggregated(response_stream))
+            }
+            WireApi::Anthropic => {
+                let response_stream = stream_anthropic_messages(
+                    prompt,
+                    &self.model,
+                    &self.client,
+                    &self.provider,
+                    self.prompt_caching,
+                )
+                .await?;
+                Ok(aggregated(response_stream))
             }
         }
     }
@@ -108,7 +153,12 @@ impl ModelClient {
 
         let full_instructions = prompt.get_full_instructions(&self.model);
         let tools_json = create_tools_json_for_responses_api(prompt, &self.model)?;
-        let reasoning = create_reasoning_param_for_request(&self.model, self.effort, self.summary);
+        let reasoning = create_reasoning_param_for_request(
+            &self.provider,
+            &self.model,
+            self.effort,
+            self.summary,
+        );
         let payload = ResponsesApiRequest {
             model: &self.model,
             instructions: &full_instructions,
@@ -210,6 +260,59 @@ struct SseEvent {
 #[derive(Debug, Deserialize)]
 struct ResponseCompleted {
     id: String,
+    usage: Option<ResponseCompletedUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseCompletedUsage {
+    input_tokens: u64,
+    input_tokens_details: Option<ResponseCompletedInputTokensDetails>,
+    output_tokens: u64,
+    output_tokens_details: Option<ResponseCompletedOutputTokensDetails>,
+    total_tokens: u64,
+}
+
+impl From<ResponseCompletedUsage> for TokenUsage {
+    fn from(val: ResponseCompletedUsage) -> Self {
+        TokenUsage {
+            input_tokens: val.input_tokens,
+            cached_input_tokens: val.input_tokens_details.map(|d| d.cached_tokens),
+            output_tokens: val.output_tokens,
+            reasoning_output_tokens: val.output_tokens_details.map(|d| d.reasoning_tokens),
+            total_tokens: val.total_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseCompletedInputTokensDetails {
+    cached_tokens: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseCompletedOutputTokensDetails {
+    reasoning_tokens: u64,
+}
+
+/// Wrap a delta-emitting stream (Chat Completions, Anthropic Messages) with
+/// the aggregation adapter so callers see *only* the final assistant message
+/// per turn (matching the behaviour of the Responses API), and bridge it back
+/// into a standard `ResponseStream` by forwarding events through a channel.
+fn aggregated(response_stream: ResponseStream) -> ResponseStream {
+    let mut aggregated = response_stream.aggregate();
+    let (tx, rx) = mpsc::channel::<Result<ResponseEvent>>(16);
+
+    tokio::spawn(async move {
+        use futures::StreamExt;
+        while let Some(ev) = aggregated.next().await {
+            // Exit early if receiver hung up.
+            if tx.send(ev).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    ResponseStream { rx_event: rx }
 }
 
 async fn process_sse<S>(stream: S, tx_event: mpsc::Sender<Result<ResponseEvent>>)
@@ -220,8 +323,8 @@ where
 
     // If the stream stays completely silent for an extended period treat it as disconnected.
     let idle_timeout = *OPENAI_STREAM_IDLE_TIMEOUT_MS;
-    // The response id returned from the "complete" message.
-    let mut response_id = None;
+    // The response id and token usage returned from the "complete" message.
+    let mut response_completed: Option<ResponseCompleted> = None;
 
     loop {
         let sse = match timeout(idle_timeout, stream.next()).await {
@@ -233,9 +336,12 @@ where
                 return;
             }
             Ok(None) => {
-                match response_id {
-                    Some(response_id) => {
-                        let event = ResponseEvent::Completed { response_id };
+                match response_completed {
+                    Some(ResponseCompleted { id, usage }) => {
+                        let event = ResponseEvent::Completed {
+                            response_id: id,
+                            token_usage: usage.map(Into::into),
+                        };
                         let _ = tx_event.send(Ok(event)).await;
                     }
                     None => {
@@ -301,7 +407,7 @@ where
                 if let Some(resp_val) = event.response {
                     match serde_json::from_value::<ResponseCompleted>(resp_val) {
                         Ok(r) => {
-                            response_id = Some(r.id);
+                            response_completed = Some(r);
                         }
                         Err(e) => {
                             debug!("failed to parse ResponseCompleted: {e}");
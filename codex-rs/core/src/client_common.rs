@@ -1,12 +1,15 @@
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use crate::error::Result;
+use crate::model_provider_info::ModelProviderInfo;
 use crate::models::ResponseItem;
+use crate::protocol::TokenUsage;
 use codex_apply_patch::APPLY_PATCH_TOOL_INSTRUCTIONS;
 use futures::Stream;
 use serde::Serialize;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
@@ -23,6 +26,9 @@ pub struct Prompt {
     pub input: Vec<ResponseItem>,
     /// Optional previous response ID (when storage is enabled).
     pub prev_id: Option<String>,
+    /// Replaces the built-in agent instructions for this request, e.g. with
+    /// the review prompt of `Op::StartReview`.
+    pub base_instructions_override: Option<String>,
     /// Optional instructions from the user to amend to the built-in agent
     /// instructions.
     pub user_instructions: Option<String>,
@@ -33,6 +39,16 @@ pub struct Prompt {
     /// the "fully qualified" tool name (i.e., prefixed with the server name),
     /// which should be reported to the model in place of Tool::name.
     pub extra_tools: HashMap<String, mcp_types::Tool>,
+
+    /// Names of the `extra_tools` to offer in strict mode.
+    pub strict_tools: HashSet<String>,
+
+    /// Names of built-in tools that must not be offered to the model.
+    pub disabled_tools: Vec<String>,
+
+    /// Offer the provider's hosted web search (Responses API only), for the
+    /// native backend of the `web_search` tool.
+    pub hosted_web_search: bool,
 }
 
 impl Prompt {
@@ -40,27 +56,32 @@ impl Prompt {
     /// 1. Base instructions (built-in prompt.md), unless disabled via
     ///    the CODEX_DISABLE_BASE_INSTRUCTIONS env var.
     /// 2. Or, if CODEX_BASE_INSTRUCTIONS_FILE is set, load that file instead of the built-in prompt.
-    /// 3. User instructions (e.g. from instructions.md and AGENTS.md), if any.
-    /// 4. Apply-patch tool instructions when using GPT-4.1 models.
+    /// 3. Or the prompt's `base_instructions_override`, which wins over both.
+    /// 4. User instructions (e.g. from instructions.md and AGENTS.md), if any.
+    /// 5. Apply-patch tool instructions when using GPT-4.1 models.
     pub(crate) fn get_full_instructions(&self, model: &str) -> Cow<str> {
         // Determine base instructions or override/disable via CODEX_BASE_INSTRUCTIONS_FILE
         let mut sections = Vec::new();
-        match std::env::var("CODEX_BASE_INSTRUCTIONS_FILE") {
-            Ok(ref path) if !path.is_empty() && path != "-" => {
-                // Override built-in prompt: read file or abort
-                let contents = std::fs::read_to_string(path).unwrap_or_else(|e| {
-                    panic!(
-                        "failed to read CODEX_BASE_INSTRUCTIONS_FILE '{}': {e}",
-                        path
-                    )
-                });
-                sections.push(contents);
-            }
-            Ok(_) => {
-                // Explicitly disabled (empty or "-"): skip base instructions
-            }
-            Err(_) => {
-                sections.push(BASE_INSTRUCTIONS.to_string());
+        if let Some(ref base) = self.base_instructions_override {
+            sections.push(base.clone());
+        } else {
+            match std::env::var("CODEX_BASE_INSTRUCTIONS_FILE") {
+                Ok(ref path) if !path.is_empty() && path != "-" => {
+                    // Override built-in prompt: read file or abort
+                    let contents = std::fs::read_to_string(path).unwrap_or_else(|e| {
+                        panic!(
+                            "failed to read CODEX_BASE_INSTRUCTIONS_FILE '{}': {e}",
+                            path
+                        )
+                    });
+                    sections.push(contents);
+                }
+                Ok(_) => {
+                    // Explicitly disabled (empty or "-"): skip base instructions
+                }
+                Err(_) => {
+                    sections.push(BASE_INSTRUCTIONS.to_string());
+                }
             }
         }
         if let Some(ref user) = self.user_instructions {
@@ -76,7 +97,10 @@ impl Prompt {
 #[derive(Debug)]
 pub enum ResponseEvent {
     OutputItemDone(ResponseItem),
-    Completed { response_id: String },
+    Completed {
+        response_id: String,
+        token_usage: Option<TokenUsage>,
+    },
 }
 
 #[derive(Debug, Serialize)]
@@ -152,6 +176,7 @@ pub(crate) struct ResponsesApiRequest<'a> {
 }
 
 pub(crate) fn create_reasoning_param_for_request(
+    provider: &ModelProviderInfo,
     model: &str,
     effort: ReasoningEffortConfig,
     summary: ReasoningSummaryConfig,
@@ -159,7 +184,7 @@ pub(crate) fn create_reasoning_param_for_request(
     let effort: Option<OpenAiReasoningEffort> = effort.into();
     let effort = effort?;
 
-    if model_supports_reasoning_summaries(model) {
+    if provider.supports_reasoning_summaries(model) {
         Some(Reasoning {
             effort,
             summary: summary.into(),
@@ -181,7 +206,8 @@ pub fn model_supports_reasoning_summaries(model: &str) -> bool {
     //
     // Ultimately, this should also be configurable in config.toml, but we
     // need to have defaults that "just work." Perhaps we could have a
-    // "reasoning models pattern" as part of ModelProviderInfo?
+    // "reasoning models pattern" as part of ModelProviderInfo? For now a
+    // provider can override this guess with `reasoning_summaries`.
     model.starts_with("o") || model.starts_with("codex")
 }
 
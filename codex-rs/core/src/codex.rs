@@ -5,10 +5,15 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::Weak;
+use std::sync::atomic::AtomicU16;
+use std::sync::atomic::AtomicU32;
 use std::sync::atomic::AtomicU64;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use async_channel::Receiver;
@@ -16,16 +21,24 @@ use async_channel::Sender;
 use codex_apply_patch::AffectedPaths;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
+use codex_apply_patch::EditFileArgs;
 use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::PlannedEdit;
+use codex_apply_patch::dry_run_hunks;
+use codex_apply_patch::maybe_parse_apply_patch_dry_run;
 use codex_apply_patch::maybe_parse_apply_patch_verified;
+use codex_apply_patch::plan_edit;
 use codex_apply_patch::print_summary;
 use futures::prelude::*;
 use mcp_types::CallToolResult;
+use mcp_types::ProgressNotificationParams;
 use serde::Serialize;
 use serde_json;
 use tokio::sync::Notify;
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::task::AbortHandle;
+use tracing::Instrument;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -34,24 +47,62 @@ use tracing::warn;
 use uuid::Uuid;
 
 use crate::WireApi;
+use crate::approval_rules::ApprovalRule;
+use crate::approval_rules::ApprovalRuleAction;
+use crate::approval_rules::evaluate_approval_rules;
+use crate::attach_image::AttachImageArgs;
+use crate::attach_image::load_image;
+use crate::background_process::BackgroundProcesses;
+use crate::background_process::ExecBackgroundArgs;
+use crate::background_process::ProcessIdArgs;
+use crate::checkpoints::Checkpoints;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
+use crate::compact::SUMMARIZATION_PROMPT;
+use crate::compact::model_context_window;
+use crate::compact::should_auto_compact;
+use crate::compact::summary_input_item;
 use crate::config::{AutoAllowPredicate, Config};
+use crate::config_types::ContainerSandboxConfig;
+use crate::config_types::SandboxBackendKind;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::config_types::WebSearchBackend;
+use crate::config_types::WebSearchConfig;
 use crate::conversation_history::ConversationHistory;
+use crate::custom_prompts::PromptPart;
+use crate::custom_prompts::discover_prompts;
+use crate::custom_prompts::expand_prompt_parts;
+use crate::custom_prompts::prompt_dirs;
+use crate::custom_prompts::prompt_text;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::error::SandboxErr;
 use crate::exec::ExecParams;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
+use crate::exec::StdoutStream;
 use crate::exec::process_exec_tool_call;
+use crate::exec::spawn_exec_child;
+use crate::exec::tty_available;
 use crate::exec_env::create_env;
+use crate::fetch_url::FETCH_URL_TOOL_NAME;
+use crate::fetch_url::FetchUrlArgs;
+use crate::fetch_url::fetch_url;
+use crate::fetch_url::format_page;
+use crate::file_references::expand_file_references;
+use crate::file_watcher::FileWatcher;
 use crate::flags::OPENAI_STREAM_MAX_RETRIES;
 use crate::mcp_connection_manager::McpConnectionManager;
-use crate::mcp_connection_manager::try_parse_fully_qualified_tool_name;
+use crate::mcp_prompts::render_prompt;
+use crate::mcp_resources::LIST_MCP_RESOURCES_TOOL_NAME;
+use crate::mcp_resources::ListMcpResourcesArgs;
+use crate::mcp_resources::READ_MCP_RESOURCE_TOOL_NAME;
+use crate::mcp_resources::ReadMcpResourceArgs;
+use crate::mcp_resources::format_resource_contents;
+use crate::mcp_resources::format_resource_list;
 use crate::mcp_tool_call::handle_mcp_tool_call;
+use crate::model_pricing::ModelPricing;
 use crate::models::ContentItem;
 use crate::models::FunctionCallOutputPayload;
 use crate::models::LocalShellAction;
@@ -59,12 +110,28 @@ use crate::models::ReasoningItemReasoningSummary;
 use crate::models::ResponseInputItem;
 use crate::models::ResponseItem;
 use crate::models::ShellToolCallParams;
+use crate::openai_tools::builtin_tool_names;
+use crate::plan_tool::latest_plan;
+use crate::plan_tool::parse_plan_update;
+use crate::plan_tool::plan_summary;
+use crate::project_doc::ProjectDocs;
+use crate::project_doc::combine_instructions;
+use crate::project_doc::format_edited_dirs_docs;
 use crate::project_doc::get_user_instructions;
+use crate::project_doc::load_docs_in;
+use crate::project_init::INIT_INSTRUCTIONS;
+use crate::project_init::InitContext;
+use crate::project_init::PROJECT_DOC_FILE;
+use crate::project_init::doc_from_reply;
 use crate::protocol::AgentMessageEvent;
 use crate::protocol::AgentReasoningEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::BackgroundProcessesUpdatedEvent;
+use crate::protocol::ContextCompactedEvent;
+use crate::protocol::CustomPrompt;
+use crate::protocol::CustomPromptsUpdatedEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
@@ -73,22 +140,89 @@ use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::FileChange;
 use crate::protocol::InputItem;
+use crate::protocol::McpPromptEvent;
+use crate::protocol::McpStartupEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::PlanUpdateEvent;
+use crate::protocol::ProjectDocProposalEvent;
+use crate::protocol::ProjectDocsLoadedEvent;
+use crate::protocol::PromptScope;
 use crate::protocol::ReviewDecision;
+use crate::protocol::ReviewFinding;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::SessionConfiguredEvent;
+use crate::protocol::SubAgentBeginEvent;
+use crate::protocol::SubAgentEndEvent;
+use crate::protocol::SubAgentMessageEvent;
 use crate::protocol::Submission;
 use crate::protocol::TaskCompleteEvent;
-use crate::rollout::RolloutRecorder;
+use crate::protocol::TokenCountEvent;
+use crate::protocol::TokenUsage;
+use crate::protocol::TurnRevertedEvent;
+use crate::protocol::UserGuidanceDeliveredEvent;
+use crate::redaction::Redactor;
+use crate::review::REVIEW_INSTRUCTIONS;
+use crate::review::git_diff;
+use crate::review::parse_findings;
+use crate::review::review_request;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
 use crate::safety::{
     AutoAllowVote, assess_command_safety, evaluate_auto_allow_predicates, get_platform_sandbox,
+    session_prefix_for_command,
 };
+use crate::session_store::SessionStore;
+use crate::session_store::TurnContext;
+use crate::session_store::load_session;
+use crate::sub_agent::SUB_AGENT_INSTRUCTIONS;
+use crate::sub_agent::SpawnAgentArgs;
+use crate::telemetry::model_request_span;
+use crate::telemetry::record_approval;
+use crate::telemetry::record_failure;
+use crate::telemetry::record_token_usage;
+use crate::telemetry::record_tool_call_output;
+use crate::telemetry::tool_call_span;
+use crate::telemetry::turn_span;
+use crate::token_budget::BudgetStatus;
+use crate::token_budget::TokenBudget;
+use crate::tool_audit::ToolAuditLog;
+use crate::tool_middleware::AuditLogMiddleware;
+use crate::tool_middleware::EnvSecretMaskingMiddleware;
+use crate::tool_middleware::ToolCall;
+use crate::tool_middleware::ToolMiddleware;
+use crate::tool_middleware::ToolMiddlewareStack;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
+use crate::web_search::NATIVE_SEARCH_INSTRUCTIONS;
+use crate::web_search::SearchResult;
+use crate::web_search::WEB_SEARCH_TOOL_NAME;
+use crate::web_search::WebSearchArgs;
+use crate::web_search::format_results;
+use crate::web_search::max_results;
+use crate::web_search::parse_native_results;
+use crate::web_search::search_endpoint;
+use crate::web_search::web_search_unavailable;
+
+/// The `ConfigureSession` op for `config`. Submitting it to a running
+/// session, e.g. with a different `model`, reconfigures the session and keeps
+/// its conversation.
+pub async fn configure_session_op(config: &Config) -> Op {
+    Op::ConfigureSession {
+        provider: config.model_provider.clone(),
+        model: config.model.clone(),
+        model_reasoning_effort: config.model_reasoning_effort,
+        model_reasoning_summary: config.model_reasoning_summary,
+        prompt_caching: config.prompt_caching,
+        instructions: get_user_instructions(config).await,
+        approval_policy: config.approval_policy,
+        sandbox_policy: config.sandbox_policy.clone(),
+        disable_response_storage: config.disable_response_storage,
+        notify: config.notify.clone(),
+        cwd: config.cwd.clone(),
+    }
+}
 
 /// The high-level interface to the Codex system.
 /// It operates as a queue pair where you send submissions and receive events.
@@ -103,25 +237,26 @@ impl Codex {
     /// of `Codex` and the ID of the `SessionInitialized` event that was
     /// submitted to start the session.
     pub async fn spawn(config: Config, ctrl_c: Arc<Notify>) -> CodexResult<(Codex, String)> {
+        Self::spawn_with_middleware(config, ctrl_c, Vec::new()).await
+    }
+
+    /// Like [`Codex::spawn`], with `middleware` hooked around every tool
+    /// call (after the secret masking and before the audit log of the
+    /// config).
+    pub async fn spawn_with_middleware(
+        config: Config,
+        ctrl_c: Arc<Notify>,
+        middleware: Vec<Arc<dyn ToolMiddleware>>,
+    ) -> CodexResult<(Codex, String)> {
         let (tx_sub, rx_sub) = async_channel::bounded(64);
         let (tx_event, rx_event) = async_channel::bounded(64);
 
-        let instructions = get_user_instructions(&config).await;
-        let configure_session = Op::ConfigureSession {
-            provider: config.model_provider.clone(),
-            model: config.model.clone(),
-            model_reasoning_effort: config.model_reasoning_effort,
-            model_reasoning_summary: config.model_reasoning_summary,
-            instructions,
-            approval_policy: config.approval_policy,
-            sandbox_policy: config.sandbox_policy.clone(),
-            disable_response_storage: config.disable_response_storage,
-            notify: config.notify.clone(),
-            cwd: config.cwd.clone(),
-        };
+        let configure_session = configure_session_op(&config).await;
 
         let config = Arc::new(config);
-        tokio::spawn(submission_loop(config, rx_sub, tx_event, ctrl_c));
+        tokio::spawn(submission_loop(
+            config, rx_sub, tx_event, ctrl_c, middleware,
+        ));
         let codex = Codex {
             next_id: AtomicU64::new(0),
             tx_sub,
@@ -163,6 +298,10 @@ impl Codex {
     }
 }
 
+/// PTY width used until the client reports its terminal size with
+/// [`Op::SetTerminalWidth`].
+const DEFAULT_TERMINAL_COLUMNS: u16 = 80;
+
 /// Context for an initialized model agent
 ///
 /// A session has at most 1 running task at a time, and can be interrupted by user input.
@@ -175,26 +314,72 @@ pub(crate) struct Session {
     /// the model as well as sandbox policies are resolved against this path
     /// instead of `std::env::current_dir()`.
     cwd: PathBuf,
-    instructions: Option<String>,
+    /// Updated when the AGENTS.md files change.
+    instructions: Mutex<Option<String>>,
+    /// `instructions` of the config, which the AGENTS.md files are appended
+    /// to when they are reloaded.
+    config_instructions: Option<String>,
+    /// The AGENTS.md files loaded for `cwd` and for the directories the
+    /// agent edited files in.
+    project_docs: Mutex<ProjectDocs>,
+    /// Watches the AGENTS.md files to reload them on change.
+    project_doc_watcher: Mutex<Option<FileWatcher>>,
+    /// Prompts of the project and `$CODEX_HOME`, for
+    /// [`Op::RunCustomPrompt`]. Updated when the prompt files change.
+    custom_prompts: Mutex<Vec<CustomPrompt>>,
+    /// Watches the prompt directories to rediscover the prompts on change.
+    prompt_watcher: Mutex<Option<FileWatcher>>,
+    /// Whether the command blocks of the project's prompts may run.
+    allow_project_prompt_shell: bool,
     approval_policy: AskForApproval,
     /// External predicate scripts for auto-approval or rejection of shell commands.
     pub auto_allow: Vec<AutoAllowPredicate>,
+    /// Glob/regex rules checked before the auto-approval predicates.
+    approval_rules: Vec<ApprovalRule>,
     sandbox_policy: SandboxPolicy,
     shell_environment_policy: ShellEnvironmentPolicy,
     writable_roots: Mutex<Vec<PathBuf>>,
+    /// Processes started with the `exec_background` tool.
+    background_processes: Mutex<BackgroundProcesses>,
+    /// Width of the client's terminal, used for commands run on a PTY.
+    terminal_columns: AtomicU16,
+    /// Pre-turn snapshots of the files changed by patches, for
+    /// [`Op::RevertTurn`].
+    checkpoints: Mutex<Checkpoints>,
 
     /// Manager for external MCP servers/tools.
     mcp_connection_manager: McpConnectionManager,
+    /// Built-in tools disabled by the `[tools]` config, plus `web_search`
+    /// unless it is enabled and allowed.
+    disabled_tools: Vec<String>,
+    /// Backend of the `web_search` tool.
+    web_search: WebSearchConfig,
+    /// Hooks around tool calls, including the audit log and secret masking
+    /// of the config.
+    tool_middleware: ToolMiddlewareStack,
+    /// Masks secrets in the items recorded for the conversation, if
+    /// `[redaction]` is enabled.
+    redactor: Option<Arc<Redactor>>,
 
     /// External notifier command (will be passed as args to exec()). When
     /// `None` this feature is disabled.
     notify: Option<Vec<String>>,
 
-    /// Optional rollout recorder for persisting the conversation transcript so
-    /// sessions can be replayed or inspected later.
-    rollout: Mutex<Option<crate::rollout::RolloutRecorder>>,
+    /// Optional session store for persisting the conversation so sessions can
+    /// be resumed or inspected later.
+    session_store: Mutex<Option<SessionStore>>,
     state: Mutex<State>,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    sandbox_backend: SandboxBackendKind,
+    container_sandbox: ContainerSandboxConfig,
+    /// Default timeout for shell commands, from `exec_timeout_sec`.
+    exec_timeout_sec: Option<u64>,
+    /// Per-stream output cap for shell commands, from `max_output_bytes`.
+    max_output_bytes: Option<usize>,
+    /// Context window of the model, used to decide when to compact.
+    model_context_window: Option<u64>,
+    /// Prices of the session's model, for [`TokenCountEvent::estimated_cost_usd`].
+    model_pricing: Option<ModelPricing>,
 }
 
 impl Session {
@@ -203,25 +388,103 @@ impl Session {
             .map(PathBuf::from)
             .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
     }
+
+    async fn notify_background_processes(&self, sub_id: &str) {
+        let processes = self.background_processes.lock().unwrap().list();
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::BackgroundProcessesUpdated(BackgroundProcessesUpdatedEvent {
+                processes,
+            }),
+        };
+        let _ = self.tx_event.send(event).await;
+    }
+
+    fn terminal_columns(&self) -> u16 {
+        self.terminal_columns
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Streams output of the exec call `call_id` to the client as it arrives.
+    fn stdout_stream(&self, sub_id: &str, call_id: &str) -> StdoutStream {
+        StdoutStream {
+            sub_id: sub_id.to_string(),
+            call_id: call_id.to_string(),
+            tx_event: self.tx_event.clone(),
+        }
+    }
+
+    /// Commands the safety checks decided to sandbox run under the configured
+    /// backend instead of the platform sandbox.
+    fn sandbox_type_for_backend(&self, sandbox_type: SandboxType) -> SandboxType {
+        match (self.sandbox_backend, sandbox_type) {
+            (_, SandboxType::None) | (SandboxBackendKind::Platform, _) => sandbox_type,
+            (SandboxBackendKind::Container, _) => SandboxType::Container,
+        }
+    }
 }
 
 /// Mutable state of the agent
 #[derive(Default)]
 struct State {
     approved_commands: HashSet<Vec<String>>,
+    /// Word-level command prefixes approved for the rest of the session.
+    approved_prefixes: HashSet<Vec<String>>,
     current_task: Option<AgentTask>,
     previous_response_id: Option<String>,
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
     pending_input: Vec<ResponseInputItem>,
+    /// Corrections sent with `Op::InjectUserGuidance`, for the running task's
+    /// next request to the model.
+    pending_guidance: Vec<String>,
+    /// Submission id of the turn that takes guidance, until it stops
+    /// following up on tool calls.
+    guidance_turn: Option<String>,
     zdr_transcript: Option<ConversationHistory>,
+    token_budget: TokenBudget,
+    /// Prompt size (input tokens) reported for the most recent response.
+    last_input_tokens: Option<u64>,
+    /// Summary to prepend to the next turn after compacting a conversation
+    /// whose history lives server-side (previous_response_id).
+    pending_summary: Option<ResponseItem>,
+    /// Latest `update_plan` of the conversation.
+    plan: PlanUpdateEvent,
+    /// Settings the current task runs with instead of the session's.
+    task_overrides: TaskOverrides,
+}
+
+/// Settings of the session that a task runs with instead, e.g. those of a
+/// custom prompt.
+#[derive(Debug, Clone, Default)]
+struct TaskOverrides {
+    model: Option<String>,
+    approval_policy: Option<AskForApproval>,
+}
+
+impl State {
+    /// Stop taking guidance for the turn `sub_id`, returning what it did not
+    /// get to read.
+    fn close_guidance(&mut self, sub_id: &str) -> Vec<String> {
+        if self.guidance_turn.as_deref() == Some(sub_id) {
+            self.guidance_turn = None;
+            std::mem::take(&mut self.pending_guidance)
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 impl Session {
     pub fn set_task(&self, task: AgentTask) {
         let mut state = self.state.lock().unwrap();
         if let Some(current_task) = state.current_task.take() {
+            let undelivered = state.close_guidance(&current_task.sub_id);
+            self.report_undelivered_guidance(&current_task.sub_id, undelivered);
             current_task.abort();
         }
+        if task.takes_guidance {
+            state.guidance_turn = Some(task.sub_id.clone());
+        }
         state.current_task = Some(task);
     }
 
@@ -242,6 +505,126 @@ impl Session {
         }
     }
 
+    /// Load the AGENTS.md files of the directories of the files changed by
+    /// `action` that were not looked for yet, and report all loaded docs.
+    /// Returns the instructions of the new docs, for the result of the edit.
+    async fn load_edited_dirs_project_docs(
+        &self,
+        sub_id: &str,
+        action: &ApplyPatchAction,
+    ) -> Option<String> {
+        let paths: Vec<PathBuf> = action
+            .changes()
+            .iter()
+            .flat_map(|(path, change)| match change {
+                ApplyPatchFileChange::Update {
+                    move_path: Some(move_path),
+                    ..
+                } => vec![path.clone(), move_path.clone()],
+                _ => vec![path.clone()],
+            })
+            .collect();
+        let (dirs, max_bytes) = {
+            let mut project_docs = self.project_docs.lock().unwrap();
+            (
+                project_docs.take_unseen_dirs(&paths),
+                project_docs.max_bytes(),
+            )
+        };
+        if dirs.is_empty() {
+            return None;
+        }
+        if let Some(watcher) = self.project_doc_watcher.lock().unwrap().as_mut() {
+            let candidates = dirs.iter().map(|dir| dir.join("AGENTS.md"));
+            if let Err(e) = watcher.watch(candidates) {
+                warn!("failed to watch AGENTS.md files: {e}");
+            }
+        }
+        let new_docs = load_docs_in(&dirs, max_bytes).await;
+        if new_docs.is_empty() {
+            return None;
+        }
+        let docs = {
+            let mut project_docs = self.project_docs.lock().unwrap();
+            project_docs.extend(new_docs.iter().cloned());
+            project_docs.docs().to_vec()
+        };
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::ProjectDocsLoaded(ProjectDocsLoadedEvent { docs }),
+        })
+        .await;
+        Some(format_edited_dirs_docs(&new_docs))
+    }
+
+    /// The approval policy of the current task.
+    fn approval_policy(&self) -> AskForApproval {
+        let state = self.state.lock().unwrap();
+        state
+            .task_overrides
+            .approval_policy
+            .unwrap_or(self.approval_policy)
+    }
+
+    /// The client for the model of the current task.
+    fn client(&self) -> ModelClient {
+        let model = self.state.lock().unwrap().task_overrides.model.clone();
+        match model {
+            Some(model) => self.client.with_model(model),
+            None => self.client.clone(),
+        }
+    }
+
+    fn set_task_overrides(&self, overrides: TaskOverrides) {
+        self.state.lock().unwrap().task_overrides = overrides;
+    }
+
+    fn instructions(&self) -> Option<String> {
+        self.instructions.lock().unwrap().clone()
+    }
+
+    /// Reload the AGENTS.md files after `changed` did, and tell the client.
+    async fn reload_project_docs(&self, sub_id: &str, changed: &[PathBuf]) {
+        let current = self.project_docs.lock().unwrap().clone();
+        let reloaded = current.reload(&self.cwd).await;
+        let docs = reloaded.docs().to_vec();
+        *self.instructions.lock().unwrap() =
+            combine_instructions(self.config_instructions.as_deref(), &docs);
+        *self.project_docs.lock().unwrap() = reloaded;
+
+        let changed: Vec<String> = changed
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        self.notify_background_event(
+            sub_id,
+            format!("project docs reloaded: {} changed", changed.join(", ")),
+        )
+        .await;
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::ProjectDocsLoaded(ProjectDocsLoadedEvent { docs }),
+        })
+        .await;
+    }
+
+    /// Discover the custom prompts again after a prompt file changed, and
+    /// tell the client.
+    async fn reload_custom_prompts(&self, sub_id: &str, codex_home: &Path) {
+        if let Some(watcher) = self.prompt_watcher.lock().unwrap().as_mut() {
+            if let Err(e) = watch_prompt_dirs_in(watcher, codex_home, &self.cwd) {
+                warn!("failed to watch the custom prompt directories: {e}");
+            }
+        }
+        let custom_prompts = discover_prompts(codex_home, &self.cwd);
+        *self.custom_prompts.lock().unwrap() = custom_prompts.clone();
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::CustomPromptsUpdated(CustomPromptsUpdatedEvent { custom_prompts }),
+        })
+        .await;
+    }
+
     pub async fn request_command_approval(
         &self,
         sub_id: String,
@@ -250,12 +633,14 @@ impl Session {
         reason: Option<String>,
     ) -> oneshot::Receiver<ReviewDecision> {
         let (tx_approve, rx_approve) = oneshot::channel();
+        let session_prefix = session_prefix_for_command(&command);
         let event = Event {
             id: sub_id.clone(),
             msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
                 command,
                 cwd,
                 reason,
+                session_prefix,
             }),
         };
         let _ = self.tx_event.send(event).await;
@@ -302,34 +687,134 @@ impl Session {
         state.approved_commands.insert(cmd);
     }
 
-    /// Records items to both the rollout and the chat completions/ZDR
+    /// Approve `cmd` and, if it has one, its session prefix (see
+    /// [`session_prefix_for_command`]) for the rest of the session.
+    pub fn add_approved_prefix_for(&self, cmd: Vec<String>) {
+        let mut state = self.state.lock().unwrap();
+        match session_prefix_for_command(&cmd) {
+            Some(prefix) => state.approved_prefixes.insert(prefix),
+            None => state.approved_commands.insert(cmd),
+        };
+    }
+
+    /// Records items to both the session store and the chat completions/ZDR
     /// transcript, if enabled.
     async fn record_conversation_items(&self, items: &[ResponseItem]) {
+        let redacted: Vec<ResponseItem>;
+        let items = match &self.redactor {
+            Some(redactor) => {
+                redacted = items
+                    .iter()
+                    .map(|item| redactor.redact_item(item))
+                    .collect();
+                &redacted[..]
+            }
+            None => items,
+        };
         debug!("Recording items for conversation: {items:?}");
-        self.record_rollout_items(items).await;
+        self.record_session_items(items).await;
 
         if let Some(transcript) = self.state.lock().unwrap().zdr_transcript.as_mut() {
             transcript.record_items(items);
         }
     }
 
-    /// Append the given items to the session's rollout transcript (if enabled)
-    /// and persist them to disk.
-    async fn record_rollout_items(&self, items: &[ResponseItem]) {
-        // Clone the recorder outside of the mutex so we don’t hold the lock
-        // across an await point (MutexGuard is not Send).
-        let recorder = {
-            let guard = self.rollout.lock().unwrap();
-            guard.as_ref().cloned()
-        };
+    /// Clone the store outside of the mutex so we don’t hold the lock across
+    /// an await point (MutexGuard is not Send).
+    fn session_store(&self) -> Option<SessionStore> {
+        self.session_store.lock().unwrap().as_ref().cloned()
+    }
+
+    /// Append the given items to the session store (if enabled) and persist
+    /// them to disk.
+    async fn record_session_items(&self, items: &[ResponseItem]) {
+        if let Some(store) = self.session_store() {
+            if let Err(e) = store.record_items(items).await {
+                error!("failed to record session items: {e:#}");
+            }
+        }
+    }
 
-        if let Some(rec) = recorder {
-            if let Err(e) = rec.record_items(items).await {
-                error!("failed to record rollout items: {e:#}");
+    /// Persist the response id and token usage reported at the end of a turn.
+    async fn record_turn_completed(&self, response_id: String, token_usage: Option<TokenUsage>) {
+        if let Some(store) = self.session_store() {
+            let response_id = (!response_id.is_empty()).then_some(response_id);
+            if let Err(e) = store.record_turn_completed(response_id, token_usage).await {
+                error!("failed to record turn completion: {e:#}");
             }
         }
     }
 
+    /// Add the usage reported for a completed response to the session
+    /// budget and report the running total (plus a one-time warning at 80%).
+    async fn record_token_usage(&self, sub_id: &str, usage: TokenUsage) {
+        self.state.lock().unwrap().last_input_tokens = Some(usage.input_tokens);
+        self.charge_token_usage(sub_id, usage, true).await;
+    }
+
+    /// Like [`Self::record_token_usage`], but `in_conversation` is false for
+    /// requests made outside of the conversation, whose prompt size says
+    /// nothing about the context left.
+    async fn charge_token_usage(&self, sub_id: &str, usage: TokenUsage, in_conversation: bool) {
+        record_token_usage(self.client().model(), &usage);
+        let (status, total, max_session_tokens) = {
+            let mut state = self.state.lock().unwrap();
+            let status = state.token_budget.record(&usage);
+            (
+                status,
+                state.token_budget.total(),
+                state.token_budget.max_tokens(),
+            )
+        };
+        let mut events = vec![EventMsg::TokenCount(TokenCountEvent {
+            last: usage,
+            total,
+            max_session_tokens,
+            estimated_cost_usd: self.model_pricing.map(|pricing| pricing.cost_usd(&total)),
+            model_context_window: self.model_context_window.filter(|_| in_conversation),
+        })];
+        if let (BudgetStatus::Warn, Some(max)) = (status, max_session_tokens) {
+            events.push(EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: format!(
+                    "Warning: this session has used {} of its {max} token budget \
+                     (`max_session_tokens`).",
+                    total.total_tokens
+                ),
+            }));
+        }
+        for msg in events {
+            let event = Event {
+                id: sub_id.to_string(),
+                msg,
+            };
+            self.send_event(event).await;
+        }
+    }
+
+    /// Whether the prompt of the last model request came close enough to the
+    /// context window that the conversation should be compacted first.
+    fn needs_auto_compaction(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        should_auto_compact(
+            state.last_input_tokens.unwrap_or_default(),
+            self.model_context_window,
+        )
+    }
+
+    /// Returns the error to report when the session's token budget has been
+    /// used up, or `None` if more turns are allowed.
+    fn token_budget_exhausted_message(&self) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        if !state.token_budget.is_exhausted() {
+            return None;
+        }
+        Some(format!(
+            "Session token budget exhausted: {} of {} tokens used. Start a new session or raise `max_session_tokens`.",
+            state.token_budget.total().total_tokens,
+            state.token_budget.max_tokens().unwrap_or_default()
+        ))
+    }
+
     async fn notify_exec_command_begin(&self, sub_id: &str, call_id: &str, params: &ExecParams) {
         let event = Event {
             id: sub_id.to_string(),
@@ -389,6 +874,62 @@ impl Session {
         }
     }
 
+    /// Queue `text` for the model's next request in the running turn, i.e.
+    /// once the tool calls in flight have returned. Returns the text back
+    /// when no turn takes guidance.
+    pub fn inject_guidance(&self, text: String) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if state.guidance_turn.is_some() {
+            state.pending_guidance.push(text);
+            Ok(())
+        } else {
+            Err(text)
+        }
+    }
+
+    fn take_pending_guidance(&self) -> Vec<String> {
+        std::mem::take(&mut self.state.lock().unwrap().pending_guidance)
+    }
+
+    /// Whether the turn `sub_id` may end: no guidance is waiting for an
+    /// answer. Guidance sent from then on starts a turn of its own.
+    fn end_guidance(&self, sub_id: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.pending_guidance.is_empty() {
+            return false;
+        }
+        state.close_guidance(sub_id);
+        true
+    }
+
+    /// Stop taking guidance for the turn `sub_id`, which ended early, and
+    /// report what it did not read.
+    fn close_guidance(&self, sub_id: &str) {
+        let undelivered = self.state.lock().unwrap().close_guidance(sub_id);
+        self.report_undelivered_guidance(sub_id, undelivered);
+    }
+
+    fn report_undelivered_guidance(&self, sub_id: &str, undelivered: Vec<String>) {
+        if undelivered.is_empty() {
+            return;
+        }
+        let events: Vec<Event> = undelivered
+            .into_iter()
+            .map(|text| Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                    message: format!("Guidance not delivered, the turn ended first: {text}"),
+                }),
+            })
+            .collect();
+        let tx_event = self.tx_event.clone();
+        tokio::spawn(async move {
+            for event in events {
+                tx_event.send(event).await.ok();
+            }
+        });
+    }
+
     pub fn get_pending_input(&self) -> Vec<ResponseInputItem> {
         let mut state = self.state.lock().unwrap();
         if state.pending_input.is_empty() {
@@ -406,9 +947,10 @@ impl Session {
         tool: &str,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        progress_tx: Option<mpsc::Sender<ProgressNotificationParams>>,
     ) -> anyhow::Result<CallToolResult> {
         self.mcp_connection_manager
-            .call_tool(server, tool, arguments, timeout)
+            .call_tool(server, tool, arguments, timeout, progress_tx)
             .await
     }
 
@@ -418,6 +960,8 @@ impl Session {
         state.pending_approvals.clear();
         state.pending_input.clear();
         if let Some(task) = state.current_task.take() {
+            let undelivered = state.close_guidance(&task.sub_id);
+            self.report_undelivered_guidance(&task.sub_id, undelivered);
             task.abort();
         }
     }
@@ -462,12 +1006,20 @@ impl State {
     pub fn partial_clone(&self, retain_zdr_transcript: bool) -> Self {
         Self {
             approved_commands: self.approved_commands.clone(),
+            approved_prefixes: self.approved_prefixes.clone(),
             previous_response_id: self.previous_response_id.clone(),
             zdr_transcript: if retain_zdr_transcript {
                 self.zdr_transcript.clone()
             } else {
                 None
             },
+            token_budget: TokenBudget::new(
+                self.token_budget.max_tokens(),
+                self.token_budget.total(),
+            ),
+            last_input_tokens: self.last_input_tokens,
+            pending_summary: self.pending_summary.clone(),
+            plan: self.plan.clone(),
             ..Default::default()
         }
     }
@@ -478,19 +1030,78 @@ pub(crate) struct AgentTask {
     sess: Arc<Session>,
     sub_id: String,
     handle: AbortHandle,
+    /// `true` for tasks started by [`Op::Compact`], which take no user input.
+    is_compaction: bool,
+    /// Whether the task runs a turn of the conversation, which
+    /// `Op::InjectUserGuidance` can steer.
+    takes_guidance: bool,
 }
 
 impl AgentTask {
     fn spawn(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) -> Self {
+        Self::spawn_with_overrides(sess, sub_id, input, TaskOverrides::default())
+    }
+
+    fn spawn_with_overrides(
+        sess: Arc<Session>,
+        sub_id: String,
+        input: Vec<InputItem>,
+        overrides: TaskOverrides,
+    ) -> Self {
+        sess.set_task_overrides(overrides);
+        let handle =
+            tokio::spawn(run_task(Arc::clone(&sess), sub_id.clone(), input, false)).abort_handle();
+        Self {
+            sess,
+            sub_id,
+            handle,
+            is_compaction: false,
+            takes_guidance: true,
+        }
+    }
+
+    fn compact(sess: Arc<Session>, sub_id: String) -> Self {
+        sess.set_task_overrides(TaskOverrides::default());
+        let handle =
+            tokio::spawn(run_compact_task(Arc::clone(&sess), sub_id.clone())).abort_handle();
+        Self {
+            sess,
+            sub_id,
+            handle,
+            is_compaction: true,
+            takes_guidance: false,
+        }
+    }
+
+    fn review(sess: Arc<Session>, sub_id: String, base: Option<String>) -> Self {
+        sess.set_task_overrides(TaskOverrides::default());
         let handle =
-            tokio::spawn(run_task(Arc::clone(&sess), sub_id.clone(), input)).abort_handle();
+            tokio::spawn(run_review_task(Arc::clone(&sess), sub_id.clone(), base)).abort_handle();
+        Self {
+            sess,
+            sub_id,
+            handle,
+            is_compaction: false,
+            takes_guidance: true,
+        }
+    }
+
+    fn init_project_doc(sess: Arc<Session>, sub_id: String) -> Self {
+        sess.set_task_overrides(TaskOverrides::default());
+        let handle = tokio::spawn(run_init_task(Arc::clone(&sess), sub_id.clone())).abort_handle();
         Self {
             sess,
             sub_id,
             handle,
+            is_compaction: false,
+            takes_guidance: false,
         }
     }
 
+    fn is_running(&self) -> bool {
+        !self.handle.is_finished()
+    }
+
     fn abort(self) {
         if !self.handle.is_finished() {
             self.handle.abort();
@@ -513,9 +1124,11 @@ async fn submission_loop(
     rx_sub: Receiver<Submission>,
     tx_event: Sender<Event>,
     ctrl_c: Arc<Notify>,
+    middleware: Vec<Arc<dyn ToolMiddleware>>,
 ) {
-    // Generate a unique ID for the lifetime of this Codex session.
-    let session_id = Uuid::new_v4();
+    // Generate a unique ID for the lifetime of this Codex session, or reuse
+    // the ID of the session being resumed so it keeps appending to its file.
+    let session_id = config.resume_session_id.unwrap_or_else(Uuid::new_v4);
 
     let mut sess: Option<Arc<Session>> = None;
     // shorthand - send an event when there is no active session
@@ -562,6 +1175,7 @@ async fn submission_loop(
                 model,
                 model_reasoning_effort,
                 model_reasoning_summary,
+                prompt_caching,
                 instructions,
                 approval_policy,
                 sandbox_policy,
@@ -583,17 +1197,31 @@ async fn submission_loop(
                     return;
                 }
 
+                let mut disabled_tools = config.tools.disabled.clone();
+                if let Some(reason) =
+                    web_search_unavailable(&config.web_search, &sandbox_policy, provider.wire_api)
+                {
+                    debug!("web_search is not offered: {reason}");
+                    disabled_tools.push(WEB_SEARCH_TOOL_NAME.to_string());
+                }
+                if !sandbox_policy.has_full_network_access() {
+                    disabled_tools.push(FETCH_URL_TOOL_NAME.to_string());
+                }
+
                 let client = ModelClient::new(
                     model.clone(),
                     provider.clone(),
                     model_reasoning_effort,
                     model_reasoning_summary,
-                );
+                    prompt_caching,
+                )
+                .with_organization(config.org_id.as_deref(), config.workspace_id.as_deref());
 
                 // abort any current running session and clone its state
                 let retain_zdr_transcript =
                     record_conversation_history(disable_response_storage, provider.wire_api);
-                let state = match sess.take() {
+                let is_first_configure = sess.is_none();
+                let mut state = match sess.take() {
                     Some(sess) => {
                         sess.abort();
                         sess.state
@@ -612,46 +1240,140 @@ async fn submission_loop(
                 };
 
                 let writable_roots = Mutex::new(get_writable_roots(&cwd));
-
-                // Error messages to dispatch after SessionConfigured is sent.
-                let mut mcp_connection_errors = Vec::<Event>::new();
-                let (mcp_connection_manager, failed_clients) =
-                    match McpConnectionManager::new(config.mcp_servers.clone()).await {
-                        Ok((mgr, failures)) => (mgr, failures),
+                let project_docs = ProjectDocs::load(&cwd, config.project_doc_max_bytes).await;
+                let loaded_docs = project_docs.docs().to_vec();
+                let (project_doc_watcher, project_doc_changes) =
+                    match FileWatcher::new(project_docs.candidate_paths()) {
+                        Ok((watcher, changes)) => (Some(watcher), Some(changes)),
                         Err(e) => {
-                            let message = format!("Failed to create MCP connection manager: {e:#}");
-                            error!("{message}");
-                            mcp_connection_errors.push(Event {
-                                id: sub.id.clone(),
-                                msg: EventMsg::Error(ErrorEvent { message }),
-                            });
-                            (McpConnectionManager::default(), Default::default())
+                            warn!("failed to watch AGENTS.md files: {e}");
+                            (None, None)
                         }
                     };
 
-                // Surface individual client start-up failures to the user.
-                if !failed_clients.is_empty() {
-                    for (server_name, err) in failed_clients {
-                        let message =
-                            format!("MCP client for `{server_name}` failed to start: {err:#}");
+                // Events to dispatch after SessionConfigured is sent.
+                let mut mcp_connection_errors = Vec::<Event>::new();
+                let (mcp_connection_manager, mcp_statuses) = match McpConnectionManager::new(
+                    config.mcp_servers.clone(),
+                    &config.tools,
+                    &config.codex_home,
+                    tx_event.clone(),
+                    &sub.id,
+                )
+                .await
+                {
+                    Ok((mgr, statuses)) => (mgr, statuses),
+                    Err(e) => {
+                        let message = format!("Failed to create MCP connection manager: {e:#}");
                         error!("{message}");
                         mcp_connection_errors.push(Event {
                             id: sub.id.clone(),
                             msg: EventMsg::Error(ErrorEvent { message }),
                         });
+                        (McpConnectionManager::default(), Default::default())
+                    }
+                };
+
+                // Report which servers came up. Reconfiguring restarts the
+                // servers too, but only failures are worth repeating then.
+                for status in &mcp_statuses {
+                    if let Err(err) = &status.result {
+                        error!("MCP client for `{}` failed to start: {err}", status.server);
                     }
                 }
+                if (is_first_configure && !mcp_statuses.is_empty())
+                    || mcp_statuses.iter().any(|status| status.result.is_err())
+                {
+                    mcp_connection_errors.push(Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::McpStartup(McpStartupEvent {
+                            servers: mcp_statuses,
+                        }),
+                    });
+                }
+                let mcp_prompts = mcp_connection_manager.list_prompts();
+                let custom_prompts = discover_prompts(&config.codex_home, &cwd);
+                // The prompt directories go through the same watcher as the
+                // project docs above; which directories those are depends on
+                // the project prompt lookup in `custom_prompts`.
+                let (prompt_watcher, prompt_changes) =
+                    match watch_prompt_dirs(&config.codex_home, &cwd) {
+                        Ok((watcher, changes)) => (Some(watcher), Some(changes)),
+                        Err(e) => {
+                            warn!("failed to watch the custom prompt directories: {e}");
+                            (None, None)
+                        }
+                    };
+                if !mcp_connection_manager.has_resources() {
+                    disabled_tools.push(LIST_MCP_RESOURCES_TOOL_NAME.to_string());
+                    disabled_tools.push(READ_MCP_RESOURCE_TOOL_NAME.to_string());
+                }
 
-                // Attempt to create a RolloutRecorder *before* moving the
-                // `instructions` value into the Session struct.
-                // TODO: if ConfigureSession is sent twice, we will create an
-                // overlapping rollout file. Consider passing RolloutRecorder
-                // from above.
-                let rollout_recorder =
-                    match RolloutRecorder::new(&config, session_id, instructions.clone()).await {
-                        Ok(r) => Some(r),
+                // When resuming, seed the conversation state from the saved
+                // session before any new items are appended to it.
+                let mut tokens_used = state.token_budget.total();
+                if let Some(resume_id) = config.resume_session_id.filter(|_| is_first_configure) {
+                    match load_session(&config.codex_home, resume_id) {
+                        Ok(saved) => {
+                            tokens_used.accumulate(&saved.token_usage);
+                            state.plan = latest_plan(&saved.items).unwrap_or_default();
+                            match state.zdr_transcript.as_mut() {
+                                Some(transcript) => transcript.record_items(&saved.items),
+                                None => state.previous_response_id = saved.previous_response_id,
+                            }
+                        }
+                        Err(e) => warn!("failed to load session {resume_id}: {e}"),
+                    }
+                }
+                state.token_budget = TokenBudget::new(config.max_session_tokens, tokens_used);
+                let plan = state.plan.clone();
+
+                let redactor = config
+                    .redaction
+                    .enabled
+                    .then(|| Arc::new(Redactor::new(&config.redaction)));
+                let mut tool_middleware: Vec<Arc<dyn ToolMiddleware>> = Vec::new();
+                if let Some(redactor) = &redactor {
+                    tool_middleware.push(redactor.clone());
+                }
+                if config.mask_env_secrets {
+                    tool_middleware.push(Arc::new(EnvSecretMaskingMiddleware::from_env()));
+                }
+                tool_middleware.extend(middleware.iter().cloned());
+                if config.tool_audit_log {
+                    tool_middleware.push(Arc::new(AuditLogMiddleware::new(ToolAuditLog::new(
+                        &config.codex_home,
+                        session_id,
+                    ))));
+                }
+                let tool_middleware = ToolMiddlewareStack::new(tool_middleware);
+
+                // Attempt to open the SessionStore *before* moving the
+                // `instructions` value into the Session struct. Reconfiguring
+                // the session reopens the same file in append mode.
+                let session_store =
+                    match SessionStore::new(&config.codex_home, session_id, instructions.clone())
+                        .await
+                    {
+                        Ok(store) => {
+                            let context = TurnContext {
+                                cwd: cwd.clone(),
+                                model: model.clone(),
+                                model_provider: config
+                                    .model_providers
+                                    .iter()
+                                    .find(|(_, info)| **info == provider)
+                                    .map(|(id, _)| id.clone()),
+                                approval_policy,
+                                sandbox_policy: sandbox_policy.clone(),
+                            };
+                            if let Err(e) = store.record_turn_context(context).await {
+                                warn!("failed to record turn context: {e}");
+                            }
+                            Some(store)
+                        }
                         Err(e) => {
-                            tracing::warn!("failed to initialise rollout recorder: {e}");
+                            warn!("failed to initialise session store: {e}");
                             None
                         }
                     };
@@ -660,20 +1382,54 @@ async fn submission_loop(
                     client,
                     tx_event: tx_event.clone(),
                     ctrl_c: Arc::clone(&ctrl_c),
-                    instructions,
+                    instructions: Mutex::new(instructions),
+                    config_instructions: config.instructions.clone(),
+                    project_docs: Mutex::new(project_docs),
+                    project_doc_watcher: Mutex::new(project_doc_watcher),
+                    custom_prompts: Mutex::new(custom_prompts.clone()),
+                    prompt_watcher: Mutex::new(prompt_watcher),
+                    allow_project_prompt_shell: config.allow_project_prompt_shell,
                     approval_policy,
                     auto_allow: config.auto_allow.clone(),
+                    approval_rules: config.approval_rules.clone(),
                     sandbox_policy,
                     shell_environment_policy: config.shell_environment_policy.clone(),
                     cwd,
                     writable_roots,
+                    background_processes: Mutex::new(BackgroundProcesses::default()),
+                    terminal_columns: AtomicU16::new(DEFAULT_TERMINAL_COLUMNS),
+                    checkpoints: Mutex::new(Checkpoints::default()),
                     mcp_connection_manager,
+                    disabled_tools,
+                    web_search: config.web_search.clone(),
+                    tool_middleware,
+                    redactor,
                     notify,
                     state: Mutex::new(state),
-                    rollout: Mutex::new(rollout_recorder),
+                    session_store: Mutex::new(session_store),
                     codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+                    sandbox_backend: config.sandbox_backend,
+                    container_sandbox: config.container_sandbox.clone(),
+                    exec_timeout_sec: config.exec_timeout_sec,
+                    max_output_bytes: config.max_output_bytes,
+                    model_context_window: config
+                        .model_context_window
+                        .or_else(|| model_context_window(&model)),
+                    model_pricing: config.model_pricing_for(&model),
                 }));
 
+                if let (Some(sess), Some(changes)) = (&sess, project_doc_changes) {
+                    spawn_project_doc_reloader(Arc::downgrade(sess), sub.id.clone(), changes);
+                }
+                if let (Some(sess), Some(changes)) = (&sess, prompt_changes) {
+                    spawn_prompt_reloader(
+                        Arc::downgrade(sess),
+                        sub.id.clone(),
+                        config.codex_home.clone(),
+                        changes,
+                    );
+                }
+
                 // Gather history metadata for SessionConfiguredEvent.
                 let (history_log_id, history_entry_count) =
                     crate::message_history::history_metadata(&config).await;
@@ -686,9 +1442,20 @@ async fn submission_loop(
                         model,
                         history_log_id,
                         history_entry_count,
+                        mcp_prompts,
+                        custom_prompts,
                     }),
                 })
-                .chain(mcp_connection_errors.into_iter());
+                .chain(mcp_connection_errors.into_iter())
+                .chain((!loaded_docs.is_empty()).then(|| Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::ProjectDocsLoaded(ProjectDocsLoadedEvent { docs: loaded_docs }),
+                }))
+                // Show the plan of a resumed or reconfigured conversation.
+                .chain((!plan.plan.is_empty()).then(|| Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::PlanUpdate(plan),
+                }));
                 for event in events {
                     if let Err(e) = tx_event.send(event).await {
                         error!("failed to send event: {e:?}");
@@ -704,6 +1471,42 @@ async fn submission_loop(
                     }
                 };
 
+                let compacting = sess
+                    .state
+                    .lock()
+                    .unwrap()
+                    .current_task
+                    .as_ref()
+                    .is_some_and(|task| task.is_compaction && task.is_running());
+                let refusal = if compacting {
+                    Some(
+                        "Compaction in progress; send your message again once it finishes."
+                            .to_string(),
+                    )
+                } else {
+                    sess.token_budget_exhausted_message()
+                };
+                if let Some(message) = refusal {
+                    let event = Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    };
+                    if let Err(e) = tx_event.send(event).await {
+                        error!("failed to send event: {e:?}");
+                    }
+                    continue;
+                }
+
+                let items = items
+                    .into_iter()
+                    .map(|item| match item {
+                        InputItem::Text { text } => InputItem::Text {
+                            text: expand_file_references(&text, &sess.cwd),
+                        },
+                        item => item,
+                    })
+                    .collect();
+
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
                     // no current task, spawn a new one
@@ -711,6 +1514,50 @@ async fn submission_loop(
                     sess.set_task(task);
                 }
             }
+            Op::InjectUserGuidance { text } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                // Compaction and `/init` run no turn that could read it.
+                let refusal = sess
+                    .state
+                    .lock()
+                    .unwrap()
+                    .current_task
+                    .as_ref()
+                    .filter(|task| !task.takes_guidance && task.is_running())
+                    .map(|task| {
+                        if task.is_compaction {
+                            "Compaction in progress; send your guidance again once it finishes."
+                        } else {
+                            "The running task takes no guidance; send it again once it finishes."
+                        }
+                    });
+                if let Some(message) = refusal {
+                    let event = Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent {
+                            message: message.to_string(),
+                        }),
+                    };
+                    if let Err(e) = tx_event.send(event).await {
+                        error!("failed to send event: {e:?}");
+                    }
+                    continue;
+                }
+                if let Err(text) = sess.inject_guidance(text) {
+                    // Nothing to steer: the guidance starts a task of its own.
+                    let items = vec![InputItem::Text {
+                        text: expand_file_references(&text, &sess.cwd),
+                    }];
+                    let task = AgentTask::spawn(Arc::clone(sess), sub.id, items);
+                    sess.set_task(task);
+                }
+            }
             Op::ExecApproval { id, decision } => {
                 let sess = match sess.as_ref() {
                     Some(sess) => sess,
@@ -719,6 +1566,7 @@ async fn submission_loop(
                         continue;
                     }
                 };
+                record_approval("exec", decision);
                 match decision {
                     ReviewDecision::Abort => {
                         sess.abort();
@@ -734,6 +1582,7 @@ async fn submission_loop(
                         continue;
                     }
                 };
+                record_approval("patch", decision);
                 match decision {
                     ReviewDecision::Abort => {
                         sess.abort();
@@ -741,6 +1590,17 @@ async fn submission_loop(
                     other => sess.notify_approval(&id, other),
                 }
             }
+            Op::SetTerminalWidth { columns } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                sess.terminal_columns
+                    .store(columns, std::sync::atomic::Ordering::Relaxed);
+            }
             Op::AddToHistory { text } => {
                 let id = session_id;
                 let config = config.clone();
@@ -752,18 +1612,269 @@ async fn submission_loop(
                 });
             }
 
-            Op::GetHistoryEntryRequest { offset, log_id } => {
-                let config = config.clone();
-                let tx_event = tx_event.clone();
-                let sub_id = sub.id.clone();
-
-                tokio::spawn(async move {
-                    // Run lookup in blocking thread because it does file IO + locking.
-                    let entry_opt = tokio::task::spawn_blocking(move || {
-                        crate::message_history::lookup(log_id, offset, &config)
-                    })
-                    .await
-                    .unwrap_or(None);
+            Op::Compact => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+
+                let task_running = sess
+                    .state
+                    .lock()
+                    .unwrap()
+                    .current_task
+                    .as_ref()
+                    .is_some_and(AgentTask::is_running);
+                if task_running {
+                    let event = Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent {
+                            message: "Cannot compact while a task is running".to_string(),
+                        }),
+                    };
+                    if let Err(e) = tx_event.send(event).await {
+                        error!("failed to send event: {e:?}");
+                    }
+                    continue;
+                }
+
+                let task = AgentTask::compact(Arc::clone(sess), sub.id);
+                sess.set_task(task);
+            }
+            Op::StartReview { base } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+
+                let task_running = sess
+                    .state
+                    .lock()
+                    .unwrap()
+                    .current_task
+                    .as_ref()
+                    .is_some_and(AgentTask::is_running);
+                let refusal = if task_running {
+                    Some("Cannot start a review while a task is running".to_string())
+                } else {
+                    sess.token_budget_exhausted_message()
+                };
+                if let Some(message) = refusal {
+                    let event = Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    };
+                    if let Err(e) = tx_event.send(event).await {
+                        error!("failed to send event: {e:?}");
+                    }
+                    continue;
+                }
+
+                let task = AgentTask::review(Arc::clone(sess), sub.id, base);
+                sess.set_task(task);
+            }
+            Op::InitProjectDoc => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+
+                let task_running = sess
+                    .state
+                    .lock()
+                    .unwrap()
+                    .current_task
+                    .as_ref()
+                    .is_some_and(AgentTask::is_running);
+                let refusal = if task_running {
+                    Some(format!(
+                        "Cannot draft {PROJECT_DOC_FILE} while a task is running"
+                    ))
+                } else {
+                    sess.token_budget_exhausted_message()
+                };
+                if let Some(message) = refusal {
+                    let event = Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    };
+                    if let Err(e) = tx_event.send(event).await {
+                        error!("failed to send event: {e:?}");
+                    }
+                    continue;
+                }
+
+                let task = AgentTask::init_project_doc(Arc::clone(sess), sub.id);
+                sess.set_task(task);
+            }
+            Op::RevertTurn => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+
+                let task_running = sess
+                    .state
+                    .lock()
+                    .unwrap()
+                    .current_task
+                    .as_ref()
+                    .is_some_and(AgentTask::is_running);
+                let result = if task_running {
+                    Err("Cannot undo while a task is running".to_string())
+                } else {
+                    match sess.checkpoints.lock().unwrap().revert_last() {
+                        Some(Ok(files)) => Ok(files),
+                        Some(Err(e)) => Err(format!("Failed to revert file changes: {e}")),
+                        None => Err("No file changes to undo".to_string()),
+                    }
+                };
+                let msg = match result {
+                    Ok(files) => {
+                        // Tell the model at the start of its next turn.
+                        let list = files
+                            .iter()
+                            .map(|path| format!("- {}", path.display()))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        sess.state
+                            .lock()
+                            .unwrap()
+                            .pending_input
+                            .push(ResponseInputItem::Message {
+                                role: "user".to_string(),
+                                content: vec![ContentItem::InputText {
+                                    text: format!(
+                                        "I reverted the file changes of your last turn. These files are back to their previous contents:\n{list}"
+                                    ),
+                                }],
+                            });
+                        EventMsg::TurnReverted(TurnRevertedEvent { files })
+                    }
+                    Err(message) => EventMsg::Error(ErrorEvent { message }),
+                };
+                if let Err(e) = tx_event.send(Event { id: sub.id, msg }).await {
+                    error!("failed to send event: {e:?}");
+                }
+            }
+            Op::GetMcpPrompt {
+                server,
+                name,
+                arguments,
+            } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let tx_event = tx_event.clone();
+                let sub_id = sub.id.clone();
+
+                // Servers can take a while to render a prompt.
+                tokio::spawn(async move {
+                    let msg = match sess
+                        .mcp_connection_manager
+                        .get_prompt(&server, &name, arguments)
+                        .await
+                    {
+                        Ok(result) => EventMsg::McpPrompt(McpPromptEvent {
+                            server,
+                            name,
+                            text: render_prompt(result),
+                        }),
+                        Err(e) => EventMsg::Error(ErrorEvent {
+                            message: format!("{e:#}"),
+                        }),
+                    };
+                    if let Err(e) = tx_event.send(Event { id: sub_id, msg }).await {
+                        error!("failed to send event: {e:?}");
+                    }
+                });
+            }
+            Op::RunCustomPrompt { name, arguments } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let prompt = sess
+                    .custom_prompts
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|p| p.name == name)
+                    .cloned()
+                    .map(|mut prompt| {
+                        // A repository's prompts only run commands if the
+                        // user opted in.
+                        if prompt.scope == PromptScope::Project && !sess.allow_project_prompt_shell
+                        {
+                            prompt.allow_shell = false;
+                        }
+                        prompt
+                    });
+                let expanded = match prompt {
+                    Some(prompt) => {
+                        expand_prompt_parts(&prompt, &arguments).map(|parts| (prompt, parts))
+                    }
+                    None => Err(format!("no custom prompt named `{name}`")),
+                };
+                let (prompt, parts) = match expanded {
+                    Ok(expanded) => expanded,
+                    Err(message) => {
+                        let event = Event {
+                            id: sub.id,
+                            msg: EventMsg::Error(ErrorEvent { message }),
+                        };
+                        if let Err(e) = tx_event.send(event).await {
+                            error!("failed to send event: {e:?}");
+                        }
+                        continue;
+                    }
+                };
+                if parts
+                    .iter()
+                    .any(|part| matches!(part, PromptPart::Command(_)))
+                {
+                    // The commands may need approval, which arrives through
+                    // this loop.
+                    let sess = Arc::clone(sess);
+                    tokio::spawn(async move {
+                        let text = run_prompt_commands(&sess, &sub.id, parts).await;
+                        start_custom_prompt(&sess, sub.id, &prompt, text);
+                    });
+                } else {
+                    start_custom_prompt(sess, sub.id, &prompt, prompt_text(&parts));
+                }
+            }
+            Op::GetHistoryEntryRequest { offset, log_id } => {
+                let config = config.clone();
+                let tx_event = tx_event.clone();
+                let sub_id = sub.id.clone();
+
+                tokio::spawn(async move {
+                    // Run lookup in blocking thread because it does file IO + locking.
+                    let entry_opt = tokio::task::spawn_blocking(move || {
+                        crate::message_history::lookup(log_id, offset, &config)
+                    })
+                    .await
+                    .unwrap_or(None);
 
                     let event = Event {
                         id: sub_id,
@@ -781,6 +1892,41 @@ async fn submission_loop(
                     }
                 });
             }
+            Op::SearchHistoryRequest {
+                query,
+                before,
+                log_id,
+            } => {
+                let config = config.clone();
+                let tx_event = tx_event.clone();
+                let sub_id = sub.id.clone();
+
+                tokio::spawn(async move {
+                    let search_query = query.clone();
+                    let found = tokio::task::spawn_blocking(move || {
+                        crate::message_history::search(log_id, &search_query, before, &config)
+                    })
+                    .await
+                    .unwrap_or(None);
+
+                    let (offset, entry) = found.unzip();
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::SearchHistoryResponse(
+                            crate::protocol::SearchHistoryResponseEvent {
+                                query,
+                                log_id,
+                                offset,
+                                entry,
+                            },
+                        ),
+                    };
+
+                    if let Err(e) = tx_event.send(event).await {
+                        tracing::warn!("failed to send SearchHistoryResponse event: {e}");
+                    }
+                });
+            }
         }
     }
     debug!("Agent loop exited");
@@ -799,7 +1945,10 @@ async fn submission_loop(
 ///   back to the model in the next turn.
 /// - If the model sends only an assistant message, we record it in the
 ///   conversation history and consider the task complete.
-async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
+///
+/// A `review` task runs under the review system prompt and reports the
+/// findings in its final message before completing.
+async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>, review: bool) {
     if input.is_empty() {
         return;
     }
@@ -811,11 +1960,18 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
         return;
     }
 
+    // Compact before the new input is recorded so the summary covers whole
+    // turns and never splits a function call from its output.
+    if sess.needs_auto_compaction() {
+        run_compaction(&sess, &sub_id, &[], false).await;
+    }
+
     let initial_input_for_turn = ResponseInputItem::from(input);
     sess.record_conversation_items(&[initial_input_for_turn.clone().into()])
         .await;
 
     let mut input_for_next_turn: Vec<ResponseInputItem> = vec![initial_input_for_turn];
+    let base_instructions = review.then(|| REVIEW_INSTRUCTIONS.to_string());
     let last_agent_message: Option<String>;
     loop {
         let mut net_new_turn_input = input_for_next_turn
@@ -826,16 +1982,24 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
-        let pending_input = sess
+        let mut pending_input = sess
             .get_pending_input()
             .into_iter()
             .map(ResponseItem::from)
             .collect::<Vec<ResponseItem>>();
+        for text in sess.take_pending_guidance() {
+            pending_input.push(ResponseItem::from(guidance_message(&text)));
+            let event = Event {
+                id: sub_id.clone(),
+                msg: EventMsg::UserGuidanceDelivered(UserGuidanceDeliveredEvent { text }),
+            };
+            sess.tx_event.send(event).await.ok();
+        }
         sess.record_conversation_items(&pending_input).await;
 
         // Construct the input that we will send to the model. When using the
         // Chat completions API (or ZDR clients), the model needs the full
-        // conversation history on each turn. The rollout file, however, should
+        // conversation history on each turn. The session file, however, should
         // only record the new items that originated in this turn so that it
         // represents an append-only log without duplicates.
         let turn_input: Vec<ResponseItem> =
@@ -858,6 +2022,12 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                 // - Outputs for function calls requested by the model
                 net_new_turn_input.extend(pending_input);
 
+                // After compaction the server-side history was dropped, so the
+                // summary has to lead the next request.
+                if let Some(summary) = sess.state.lock().unwrap().pending_summary.take() {
+                    net_new_turn_input.insert(0, summary);
+                }
+
                 // Responses API path – we can just send the new items and
                 // record the same.
                 net_new_turn_input
@@ -876,7 +2046,14 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                 })
             })
             .collect();
-        match run_turn(&sess, sub_id.clone(), turn_input).await {
+        let span = turn_span(&sub_id);
+        let turn_result = run_turn(&sess, sub_id.clone(), turn_input, base_instructions.clone())
+            .instrument(span.clone())
+            .await;
+        if turn_result.is_err() {
+            record_failure(&span);
+        }
+        match turn_result {
             Ok(turn_output) => {
                 let mut items_to_record_in_conversation_history = Vec::<ResponseItem>::new();
                 let mut responses = Vec::<ResponseInputItem>::new();
@@ -917,8 +2094,18 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                         ) => {
                             items_to_record_in_conversation_history.push(item);
                             let (content, success): (String, Option<bool>) = match result {
-                                Ok(CallToolResult { content, is_error }) => {
-                                    match serde_json::to_string(content) {
+                                Ok(CallToolResult {
+                                    content,
+                                    is_error,
+                                    structured_content,
+                                }) => {
+                                    // Tools with an output schema answer
+                                    // with validated JSON; pass that on.
+                                    let output = match structured_content {
+                                        Some(structured) => serde_json::to_string(structured),
+                                        None => serde_json::to_string(content),
+                                    };
+                                    match output {
                                         Ok(content) => (content, *is_error),
                                         Err(e) => {
                                             warn!("Failed to serialize MCP tool call output: {e}");
@@ -953,7 +2140,9 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                         .await;
                 }
 
-                if responses.is_empty() {
+                // Guidance that arrived during the last request still gets
+                // an answer before the task ends.
+                if responses.is_empty() && sess.end_guidance(&sub_id) {
                     debug!("Turn completed");
                     last_agent_message = get_last_assistant_message_from_turn(
                         &items_to_record_in_conversation_history,
@@ -966,6 +2155,25 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                     break;
                 }
 
+                // Stop following up on tool calls once the budget is gone.
+                if let Some(message) = sess.token_budget_exhausted_message() {
+                    let event = Event {
+                        id: sub_id.clone(),
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    };
+                    sess.tx_event.send(event).await.ok();
+                    last_agent_message = None;
+                    break;
+                }
+
+                // Many tool calls can fill the window before the task ends, so
+                // compact between follow-up requests as well. The summary
+                // covers the tool outputs, so they are not sent again.
+                if sess.needs_auto_compaction()
+                    && run_compaction(&sess, &sub_id, &responses, true).await
+                {
+                    responses.clear();
+                }
                 input_for_next_turn = responses;
             }
             Err(e) => {
@@ -977,10 +2185,15 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                     }),
                 };
                 sess.tx_event.send(event).await.ok();
+                sess.close_guidance(&sub_id);
                 return;
             }
         }
     }
+    sess.close_guidance(&sub_id);
+    if review {
+        send_review_findings(&sess, &sub_id, last_agent_message.as_deref()).await;
+    }
     sess.remove_task(&sub_id);
     let event = Event {
         id: sub_id,
@@ -989,103 +2202,462 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
     sess.tx_event.send(event).await.ok();
 }
 
-async fn run_turn(
-    sess: &Session,
-    sub_id: String,
-    input: Vec<ResponseItem>,
-) -> CodexResult<Vec<ProcessedResponseItem>> {
-    // Decide whether to use server-side storage (previous_response_id) or disable it
-    let (prev_id, store) = {
-        let state = sess.state.lock().unwrap();
-        let store = state.zdr_transcript.is_none();
-        let prev_id = if store {
-            state.previous_response_id.clone()
-        } else {
-            // When using ZDR, the Responses API may send previous_response_id
-            // back, but trying to use it results in a 400.
-            None
-        };
-        (prev_id, store)
+/// Handle [`Op::StartReview`]: review the diff against `base` (or the
+/// uncommitted changes) as a task under the review system prompt.
+async fn run_review_task(sess: Arc<Session>, sub_id: String, base: Option<String>) {
+    let diff = match git_diff(&sess.cwd, base.as_deref()).await {
+        Ok(diff) if diff.trim().is_empty() => Err("There are no changes to review".to_string()),
+        diff => diff,
     };
+    match diff {
+        Ok(diff) => {
+            let input = vec![InputItem::Text {
+                text: review_request(&diff, base.as_deref()),
+            }];
+            run_task(sess, sub_id, input, true).await;
+        }
+        Err(message) => {
+            sess.close_guidance(&sub_id);
+            sess.remove_task(&sub_id);
+            sess.send_event(Event {
+                id: sub_id,
+                msg: EventMsg::Error(ErrorEvent { message }),
+            })
+            .await;
+        }
+    }
+}
 
-    let extra_tools = sess.mcp_connection_manager.list_all_tools();
-    let prompt = Prompt {
-        input,
-        prev_id,
-        user_instructions: sess.instructions.clone(),
-        store,
-        extra_tools,
+/// Report the findings in the final message of a review, one event each.
+async fn send_review_findings(sess: &Session, sub_id: &str, reply: Option<&str>) {
+    let findings: Result<Vec<ReviewFinding>, String> = match reply {
+        Some(reply) => parse_findings(reply),
+        None => Err("the review finished without a reply".to_string()),
     };
-
-    let mut retries = 0;
-    loop {
-        match try_run_turn(sess, &sub_id, &prompt).await {
-            Ok(output) => return Ok(output),
-            Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
-            Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
-            Err(e) => {
-                if retries < *OPENAI_STREAM_MAX_RETRIES {
-                    retries += 1;
-                    let delay = backoff(retries);
-                    warn!(
-                        "stream disconnected - retrying turn ({retries}/{} in {delay:?})...",
-                        *OPENAI_STREAM_MAX_RETRIES
-                    );
-
-                    // Surface retry information to any UI/front‑end so the
-                    // user understands what is happening instead of staring
-                    // at a seemingly frozen screen.
-                    sess.notify_background_event(
-                        &sub_id,
-                        format!(
-                            "stream error: {e}; retrying {retries}/{} in {:?}…",
-                            *OPENAI_STREAM_MAX_RETRIES, delay
-                        ),
-                    )
-                    .await;
-
-                    tokio::time::sleep(delay).await;
-                } else {
-                    return Err(e);
-                }
+    match findings {
+        Ok(findings) if findings.is_empty() => {
+            sess.notify_background_event(sub_id, "review: no findings")
+                .await;
+        }
+        Ok(findings) => {
+            for finding in findings {
+                sess.send_event(Event {
+                    id: sub_id.to_string(),
+                    msg: EventMsg::ReviewFinding(finding),
+                })
+                .await;
             }
         }
+        Err(e) => {
+            warn!("review findings not reported: {e}");
+            sess.notify_background_event(sub_id, format!("review findings not reported: {e}"))
+                .await;
+        }
     }
 }
 
-/// When the model is prompted, it returns a stream of events. Some of these
-/// events map to a `ResponseItem`. A `ResponseItem` may need to be
-/// "handled" such that it produces a `ResponseInputItem` that needs to be
-/// sent back to the model on the next turn.
-struct ProcessedResponseItem {
-    item: ResponseItem,
-    response: Option<ResponseInputItem>,
+/// The user message that carries guidance sent while the model works.
+fn guidance_message(text: &str) -> ResponseInputItem {
+    ResponseInputItem::Message {
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!(
+                "While you were working I sent this correction. Take it into account and continue from where you are:\n{text}"
+            ),
+        }],
+    }
 }
 
-async fn try_run_turn(
-    sess: &Session,
-    sub_id: &str,
-    prompt: &Prompt,
-) -> CodexResult<Vec<ProcessedResponseItem>> {
-    let mut stream = sess.client.clone().stream(prompt).await?;
-
-    // Buffer all the incoming messages from the stream first, then execute them.
-    // If we execute a function call in the middle of handling the stream, it can time out.
-    let mut input = Vec::new();
-    while let Some(event) = stream.next().await {
-        input.push(event?);
+/// Handle [`Op::Compact`]: compact the conversation as a standalone task.
+async fn run_compact_task(sess: Arc<Session>, sub_id: String) {
+    let event = Event {
+        id: sub_id.clone(),
+        msg: EventMsg::TaskStarted,
+    };
+    if sess.tx_event.send(event).await.is_err() {
+        return;
     }
 
-    let mut output = Vec::new();
-    for event in input {
-        match event {
-            ResponseEvent::OutputItemDone(item) => {
-                let response = handle_response_item(sess, sub_id, item.clone()).await?;
+    run_compaction(&sess, &sub_id, &[], false).await;
+
+    sess.remove_task(&sub_id);
+    let event = Event {
+        id: sub_id,
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+        }),
+    };
+    sess.tx_event.send(event).await.ok();
+}
+
+/// Handle [`Op::InitProjectDoc`]: draft an AGENTS.md in a single request
+/// outside of the conversation and propose it to the client.
+async fn run_init_task(sess: Arc<Session>, sub_id: String) {
+    let event = Event {
+        id: sub_id.clone(),
+        msg: EventMsg::TaskStarted,
+    };
+    if sess.tx_event.send(event).await.is_err() {
+        return;
+    }
+
+    let msg = match draft_project_doc(&sess, &sub_id).await {
+        Ok(event) => EventMsg::ProjectDocProposal(event),
+        Err(message) => EventMsg::Error(ErrorEvent {
+            message: format!("Failed to draft {PROJECT_DOC_FILE}: {message}"),
+        }),
+    };
+    sess.send_event(Event {
+        id: sub_id.clone(),
+        msg,
+    })
+    .await;
+
+    sess.remove_task(&sub_id);
+    let event = Event {
+        id: sub_id,
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+        }),
+    };
+    sess.tx_event.send(event).await.ok();
+}
+
+async fn draft_project_doc(
+    sess: &Session,
+    sub_id: &str,
+) -> Result<ProjectDocProposalEvent, String> {
+    let cwd = sess.cwd.clone();
+    let context = tokio::task::spawn_blocking(move || InitContext::gather(&cwd))
+        .await
+        .map_err(|e| e.to_string())?;
+    let prompt = Prompt {
+        input: vec![ResponseItem::from(ResponseInputItem::Message {
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: context.request(),
+            }],
+        })],
+        prev_id: None,
+        base_instructions_override: Some(INIT_INSTRUCTIONS.to_string()),
+        user_instructions: None,
+        store: false,
+        extra_tools: HashMap::new(),
+        strict_tools: HashSet::new(),
+        disabled_tools: builtin_tool_names().map(str::to_string).collect(),
+        hosted_web_search: false,
+    };
+
+    let mut stream = sess
+        .client()
+        .stream(&prompt)
+        .await
+        .map_err(|e| format!("model request failed: {e}"))?;
+    let mut reply = String::new();
+    while let Some(event) = stream.next().await {
+        match event.map_err(|e| format!("model request failed: {e}"))? {
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, content })
+                if role == "assistant" =>
+            {
+                for item in content {
+                    if let ContentItem::OutputText { text } = item {
+                        reply.push_str(&text);
+                    }
+                }
+            }
+            ResponseEvent::OutputItemDone(_) => {}
+            ResponseEvent::Completed { token_usage, .. } => {
+                if let Some(usage) = token_usage {
+                    sess.charge_token_usage(sub_id, usage, false).await;
+                }
+                break;
+            }
+        }
+    }
+    if reply.trim().is_empty() {
+        return Err("the model returned an empty reply".to_string());
+    }
+    Ok(ProjectDocProposalEvent {
+        path: sess.cwd.join(PROJECT_DOC_FILE),
+        existing: context.existing,
+        content: doc_from_reply(&reply),
+    })
+}
+
+/// Summarize the conversation and report the result to the client. Failures
+/// are surfaced as background events; the conversation is left untouched.
+/// `tool_outputs` are the outputs of the last request's tool calls, which
+/// have not been sent yet. `mid_task` is set between the requests of a task,
+/// which then goes on from the summary. Returns whether the conversation was
+/// compacted.
+async fn run_compaction(
+    sess: &Session,
+    sub_id: &str,
+    tool_outputs: &[ResponseInputItem],
+    mid_task: bool,
+) -> bool {
+    match compact_conversation(sess, sub_id, tool_outputs, mid_task).await {
+        Ok(Some(event)) => {
+            sess.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::ContextCompacted(event),
+            })
+            .await;
+            true
+        }
+        Ok(None) => {
+            sess.notify_background_event(
+                sub_id,
+                "compaction skipped: the model returned no summary",
+            )
+            .await;
+            false
+        }
+        Err(e) => {
+            warn!("compaction failed: {e:#}");
+            sess.notify_background_event(sub_id, format!("compaction failed: {e}"))
+                .await;
+            false
+        }
+    }
+}
+
+/// Ask the model to summarize the conversation so far and replace the
+/// history sent with future turns by that summary. Returns `None` if the
+/// model produced no summary.
+async fn compact_conversation(
+    sess: &Session,
+    sub_id: &str,
+    tool_outputs: &[ResponseInputItem],
+    mid_task: bool,
+) -> CodexResult<Option<ContextCompactedEvent>> {
+    let (mut input, prev_id, store, tokens_before) = {
+        let state = sess.state.lock().unwrap();
+        let store = state.zdr_transcript.is_none();
+        let history = state
+            .zdr_transcript
+            .as_ref()
+            .map(ConversationHistory::contents)
+            .unwrap_or_default();
+        // Carry over a summary from an earlier compaction that has not been
+        // sent yet, otherwise it would be lost.
+        let history = state
+            .pending_summary
+            .iter()
+            .cloned()
+            .chain(history)
+            .collect::<Vec<_>>();
+        let prev_id = if store {
+            state.previous_response_id.clone()
+        } else {
+            None
+        };
+        (
+            history,
+            prev_id,
+            store,
+            state.last_input_tokens.unwrap_or_default(),
+        )
+    };
+    // The stored response ends in the tool calls, which the server expects
+    // to be answered first. The ZDR transcript already contains the outputs.
+    if store {
+        input.extend(tool_outputs.iter().cloned().map(ResponseItem::from));
+    }
+    input.push(ResponseItem::from(ResponseInputItem::Message {
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: SUMMARIZATION_PROMPT.to_string(),
+        }],
+    }));
+    let prompt = Prompt {
+        input,
+        prev_id,
+        base_instructions_override: None,
+        user_instructions: sess.instructions(),
+        store,
+        extra_tools: HashMap::new(),
+        strict_tools: HashSet::new(),
+        disabled_tools: sess.disabled_tools.clone(),
+        hosted_web_search: false,
+    };
+
+    let mut stream = sess.client().stream(&prompt).await?;
+    let mut summary = String::new();
+    let mut token_usage = None;
+    while let Some(event) = stream.next().await {
+        match event? {
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, content })
+                if role == "assistant" =>
+            {
+                for item in content {
+                    if let ContentItem::OutputText { text } = item {
+                        summary.push_str(&text);
+                    }
+                }
+            }
+            ResponseEvent::OutputItemDone(_) => {}
+            ResponseEvent::Completed {
+                token_usage: usage, ..
+            } => {
+                token_usage = usage;
+                break;
+            }
+        }
+    }
+    if let Some(usage) = token_usage {
+        sess.record_token_usage(sub_id, usage).await;
+    }
+
+    let summary = summary.trim().to_string();
+    if summary.is_empty() {
+        return Ok(None);
+    }
+    let tokens_after = token_usage
+        .map(|usage| usage.output_tokens)
+        .unwrap_or_else(|| (summary.len() as u64).div_ceil(4));
+
+    let summary_item = ResponseItem::from(summary_input_item(&summary, mid_task));
+    {
+        let mut state = sess.state.lock().unwrap();
+        state.previous_response_id = None;
+        state.last_input_tokens = Some(tokens_after);
+        match state.zdr_transcript.as_mut() {
+            Some(transcript) => {
+                *transcript = ConversationHistory::new();
+                transcript.record_items(&[summary_item]);
+                state.pending_summary = None;
+            }
+            None => state.pending_summary = Some(summary_item),
+        }
+    }
+
+    Ok(Some(ContextCompactedEvent {
+        summary,
+        tokens_before,
+        tokens_after,
+    }))
+}
+
+async fn run_turn(
+    sess: &Session,
+    sub_id: String,
+    input: Vec<ResponseItem>,
+    base_instructions_override: Option<String>,
+) -> CodexResult<Vec<ProcessedResponseItem>> {
+    // Decide whether to use server-side storage (previous_response_id) or disable it
+    let (prev_id, store) = {
+        let state = sess.state.lock().unwrap();
+        let store = state.zdr_transcript.is_none();
+        let prev_id = if store {
+            state.previous_response_id.clone()
+        } else {
+            // When using ZDR, the Responses API may send previous_response_id
+            // back, but trying to use it results in a 400.
+            None
+        };
+        (prev_id, store)
+    };
+
+    let extra_tools = sess.mcp_connection_manager.list_all_tools();
+    let strict_tools = sess.mcp_connection_manager.strict_tool_names();
+    let prompt = Prompt {
+        input,
+        prev_id,
+        base_instructions_override,
+        user_instructions: sess.instructions(),
+        store,
+        extra_tools,
+        strict_tools,
+        disabled_tools: sess.disabled_tools.clone(),
+        hosted_web_search: false,
+    };
+
+    let mut retries = 0;
+    loop {
+        match try_run_turn(sess, &sub_id, &prompt).await {
+            Ok(output) => return Ok(output),
+            Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
+            Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
+            Err(e) => {
+                if retries < *OPENAI_STREAM_MAX_RETRIES {
+                    retries += 1;
+                    let delay = backoff(retries);
+                    warn!(
+                        "stream disconnected - retrying turn ({retries}/{} in {delay:?})...",
+                        *OPENAI_STREAM_MAX_RETRIES
+                    );
+
+                    // Surface retry information to any UI/front‑end so the
+                    // user understands what is happening instead of staring
+                    // at a seemingly frozen screen.
+                    sess.notify_background_event(
+                        &sub_id,
+                        format!(
+                            "stream error: {e}; retrying {retries}/{} in {:?}…",
+                            *OPENAI_STREAM_MAX_RETRIES, delay
+                        ),
+                    )
+                    .await;
+
+                    tokio::time::sleep(delay).await;
+                } else {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// When the model is prompted, it returns a stream of events. Some of these
+/// events map to a `ResponseItem`. A `ResponseItem` may need to be
+/// "handled" such that it produces a `ResponseInputItem` that needs to be
+/// sent back to the model on the next turn.
+struct ProcessedResponseItem {
+    item: ResponseItem,
+    response: Option<ResponseInputItem>,
+}
+
+async fn try_run_turn(
+    sess: &Session,
+    sub_id: &str,
+    prompt: &Prompt,
+) -> CodexResult<Vec<ProcessedResponseItem>> {
+    let client = sess.client();
+    let span = model_request_span(client.model(), &client.provider().name);
+    // Buffer all the incoming messages from the stream first, then execute them.
+    // If we execute a function call in the middle of handling the stream, it can time out.
+    let input = async {
+        let mut stream = client.stream(prompt).await?;
+        let mut input = Vec::new();
+        while let Some(event) = stream.next().await {
+            input.push(event?);
+        }
+        Ok::<_, CodexErr>(input)
+    }
+    .instrument(span.clone())
+    .await;
+    if input.is_err() {
+        record_failure(&span);
+    }
+    let input = input?;
+
+    let mut output = Vec::new();
+    for event in input {
+        match event {
+            ResponseEvent::OutputItemDone(item) => {
+                let response = handle_response_item(sess, sub_id, item.clone()).await?;
                 output.push(ProcessedResponseItem { item, response });
             }
-            ResponseEvent::Completed { response_id } => {
-                let mut state = sess.state.lock().unwrap();
-                state.previous_response_id = Some(response_id);
+            ResponseEvent::Completed {
+                response_id,
+                token_usage,
+            } => {
+                sess.state.lock().unwrap().previous_response_id = Some(response_id.clone());
+                sess.record_turn_completed(response_id, token_usage).await;
+                if let Some(token_usage) = token_usage {
+                    sess.record_token_usage(sub_id, token_usage).await;
+                }
                 break;
             }
         }
@@ -1131,7 +2703,28 @@ async fn handle_response_item(
             call_id,
         } => {
             tracing::info!("FunctionCall: {arguments}");
-            Some(handle_function_call(sess, sub_id.to_string(), name, arguments, call_id).await)
+            let mut call = ToolCall {
+                call_id,
+                tool: name,
+                arguments,
+                started: Instant::now(),
+            };
+            if let Err(output) = sess.tool_middleware.pre_call(&mut call) {
+                return Ok(Some(output));
+            }
+            let span = tool_call_span(&call.tool, &call.call_id);
+            let mut output = handle_function_call(
+                sess,
+                sub_id.to_string(),
+                call.tool.clone(),
+                call.arguments.clone(),
+                call.call_id.clone(),
+            )
+            .instrument(span.clone())
+            .await;
+            record_tool_call_output(&span, &output);
+            sess.tool_middleware.post_call(&call, &mut output);
+            Some(output)
         }
         ResponseItem::LocalShellCall {
             id,
@@ -1145,6 +2738,7 @@ async fn handle_response_item(
                 command: action.command,
                 workdir: action.working_directory,
                 timeout_ms: action.timeout_ms,
+                tty: false,
             };
             let effective_call_id = match (call_id, id) {
                 (Some(call_id), _) => call_id,
@@ -1160,17 +2754,41 @@ async fn handle_response_item(
                     }));
                 }
             };
+            if is_tool_disabled(sess, "local_shell") {
+                return Ok(Some(function_call_output(
+                    effective_call_id,
+                    Err("tool `shell` is disabled".to_string()),
+                )));
+            }
 
+            let arguments = serde_json::json!({
+                "command": &params.command,
+                "workdir": &params.workdir,
+                "timeout_ms": params.timeout_ms,
+            })
+            .to_string();
+            let mut call = ToolCall {
+                call_id: effective_call_id,
+                tool: "local_shell".to_string(),
+                arguments,
+                started: Instant::now(),
+            };
+            if let Err(output) = sess.tool_middleware.pre_call(&mut call) {
+                return Ok(Some(output));
+            }
             let exec_params = to_exec_params(params, sess);
-            Some(
-                handle_container_exec_with_params(
-                    exec_params,
-                    sess,
-                    sub_id.to_string(),
-                    effective_call_id,
-                )
-                .await,
+            let span = tool_call_span(&call.tool, &call.call_id);
+            let mut output = handle_container_exec_with_params(
+                exec_params,
+                sess,
+                sub_id.to_string(),
+                call.call_id.clone(),
             )
+            .instrument(span.clone())
+            .await;
+            record_tool_call_output(&span, &output);
+            sess.tool_middleware.post_call(&call, &mut output);
+            Some(output)
         }
         ResponseItem::FunctionCallOutput { .. } => {
             debug!("unexpected FunctionCallOutput from stream");
@@ -1178,48 +2796,639 @@ async fn handle_response_item(
         }
         ResponseItem::Other => None,
     };
-    Ok(output)
+    Ok(output)
+}
+
+async fn handle_function_call(
+    sess: &Session,
+    sub_id: String,
+    name: String,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    if is_tool_disabled(sess, &name) {
+        return function_call_output(
+            call_id,
+            Err(format!("tool `{}` is disabled", config_tool_name(&name))),
+        );
+    }
+    match name.as_str() {
+        "container.exec" | "shell" => {
+            let params = match parse_container_exec_arguments(arguments, sess, &call_id) {
+                Ok(params) => params,
+                Err(output) => {
+                    return output;
+                }
+            };
+            handle_container_exec_with_params(params, sess, sub_id, call_id).await
+        }
+        "attach_image" => handle_attach_image(sess, arguments, call_id),
+        "edit_file" => handle_edit_file(sess, sub_id, arguments, call_id).await,
+        "exec_background" => handle_exec_background(sess, sub_id, arguments, call_id).await,
+        "process_output" => handle_process_output(sess, sub_id, arguments, call_id).await,
+        "process_kill" => handle_process_kill(sess, sub_id, arguments, call_id).await,
+        "spawn_agent" => handle_spawn_agent(sess, sub_id, arguments, call_id).await,
+        "update_plan" => handle_update_plan(sess, sub_id, arguments, call_id).await,
+        "web_search" => handle_web_search(sess, sub_id, arguments, call_id).await,
+        "fetch_url" => handle_fetch_url(sess, sub_id, arguments, call_id).await,
+        "list_mcp_resources" => handle_list_mcp_resources(sess, arguments, call_id).await,
+        "read_mcp_resource" => handle_read_mcp_resource(sess, arguments, call_id).await,
+        _ => {
+            match sess.mcp_connection_manager.resolve_tool(&name) {
+                Some((server, tool_name)) => {
+                    // TODO(mbolin): Determine appropriate timeout for tool call.
+                    let timeout = None;
+                    handle_mcp_tool_call(
+                        sess, &sub_id, call_id, server, tool_name, arguments, timeout,
+                    )
+                    .await
+                }
+                None => {
+                    // Unknown function: reply with structured failure so the model can adapt.
+                    ResponseInputItem::FunctionCallOutput {
+                        call_id,
+                        output: crate::models::FunctionCallOutputPayload {
+                            content: format!("unsupported call: {}", name),
+                            success: None,
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Images cannot be returned in a function call output, so the image is
+/// queued as user input for the follow-up turn and the output only describes
+/// what was attached.
+fn handle_attach_image(sess: &Session, arguments: String, call_id: String) -> ResponseInputItem {
+    let result = serde_json::from_str::<AttachImageArgs>(&arguments)
+        .map_err(|e| format!("failed to parse function arguments: {e}"))
+        .and_then(|args| {
+            let path = sess.resolve_path(Some(args.path));
+            let image = load_image(&path)?;
+            let label = format!("Image attached from {}:", path.display());
+            sess.inject_input(vec![
+                InputItem::Text { text: label },
+                InputItem::Image {
+                    image_url: image.data_url,
+                },
+            ])
+            .map_err(|_| "no running task to attach the image to".to_string())?;
+            let resized = if image.resized { " (downsized)" } else { "" };
+            Ok(format!(
+                "Attached {} as a {}x{} image{resized}; it follows in the next message.",
+                path.display(),
+                image.width,
+                image.height
+            ))
+        });
+    function_call_output(call_id, result)
+}
+
+/// The replacement is turned into a patch, so it goes through the same
+/// approval, writable-root checks and undo snapshots as `apply_patch`. On
+/// success the model gets the diff back instead of the patch summary.
+async fn handle_edit_file(
+    sess: &Session,
+    sub_id: String,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let planned = serde_json::from_str::<EditFileArgs>(&arguments)
+        .map_err(|e| format!("failed to parse function arguments: {e}"))
+        .and_then(|args| plan_edit(&args, &sess.cwd).map_err(|e| e.to_string()));
+    let PlannedEdit {
+        action,
+        unified_diff,
+        occurrences,
+    } = match planned {
+        Ok(planned) => planned,
+        Err(e) => return function_call_output(call_id, Err(e)),
+    };
+    match apply_patch(sess, sub_id, call_id, action).await {
+        ResponseInputItem::FunctionCallOutput { call_id, output }
+            if output.success == Some(true) =>
+        {
+            function_call_output(
+                call_id,
+                Ok(format!(
+                    "Replaced {occurrences} occurrence(s):\n{unified_diff}"
+                )),
+            )
+        }
+        other => other,
+    }
+}
+
+/// Starts a command that keeps running after the tool call returns. It is
+/// approved and sandboxed like `shell`, but has no timeout.
+async fn handle_exec_background(
+    sess: &Session,
+    sub_id: String,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let result = async {
+        let args = serde_json::from_str::<ExecBackgroundArgs>(&arguments)
+            .map_err(|e| format!("failed to parse function arguments: {e}"))?;
+        let params = ExecParams {
+            command: args.command,
+            cwd: sess.resolve_path(args.workdir),
+            timeout_ms: None,
+            env: create_env(&sess.shell_environment_policy),
+            tty_columns: None,
+            max_output_bytes: None,
+        };
+        let sandbox_type = approve_exec(sess, &sub_id, &params).await?;
+        let child = spawn_exec_child(
+            params.command.clone(),
+            params.cwd,
+            params.env,
+            sess.sandbox_type_for_backend(sandbox_type),
+            &sess.sandbox_policy,
+            &sess.codex_linux_sandbox_exe,
+            &sess.container_sandbox,
+            None,
+        )
+        .await
+        .map_err(|e| format!("failed to start background process: {e}"))?;
+        let id = sess
+            .background_processes
+            .lock()
+            .unwrap()
+            .start(params.command, child);
+        sess.notify_background_processes(&sub_id).await;
+        Ok(format!(
+            "started background process {id}; poll it with process_output"
+        ))
+    }
+    .await;
+    function_call_output(call_id, result)
+}
+
+async fn handle_process_output(
+    sess: &Session,
+    sub_id: String,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let result = serde_json::from_str::<ProcessIdArgs>(&arguments)
+        .map_err(|e| format!("failed to parse function arguments: {e}"))
+        .and_then(|args| {
+            sess.background_processes
+                .lock()
+                .unwrap()
+                .read_output(args.id)
+        });
+    // Reading the output of a finished process forgets it.
+    sess.notify_background_processes(&sub_id).await;
+    function_call_output(call_id, result)
+}
+
+async fn handle_process_kill(
+    sess: &Session,
+    sub_id: String,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let result = serde_json::from_str::<ProcessIdArgs>(&arguments)
+        .map_err(|e| format!("failed to parse function arguments: {e}"))
+        .and_then(|args| {
+            sess.background_processes.lock().unwrap().kill(args.id)?;
+            Ok(format!("killed background process {}", args.id))
+        });
+    sess.notify_background_processes(&sub_id).await;
+    function_call_output(call_id, result)
+}
+
+async fn handle_update_plan(
+    sess: &Session,
+    sub_id: String,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let update = match parse_plan_update(&arguments) {
+        Ok(update) => update,
+        Err(e) => return function_call_output(call_id, Err(e)),
+    };
+    let summary = plan_summary(&update);
+    sess.state.lock().unwrap().plan = update.clone();
+    sess.send_event(Event {
+        id: sub_id,
+        msg: EventMsg::PlanUpdate(update),
+    })
+    .await;
+    function_call_output(call_id, Ok(summary))
+}
+
+/// Runs a `spawn_agent` sub-task to completion in a child conversation and
+/// returns its final message.
+async fn handle_spawn_agent(
+    sess: &Session,
+    sub_id: String,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let args = match serde_json::from_str::<SpawnAgentArgs>(&arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return function_call_output(
+                call_id,
+                Err(format!("failed to parse function arguments: {e}")),
+            );
+        }
+    };
+    let max_turns = args.max_turns();
+    sess.send_event(Event {
+        id: sub_id.clone(),
+        msg: EventMsg::SubAgentBegin(SubAgentBeginEvent {
+            call_id: call_id.clone(),
+            prompt: args.prompt.clone(),
+            max_turns,
+        }),
+    })
+    .await;
+
+    let mut end = SubAgentEndGuard {
+        tx_event: sess.tx_event.clone(),
+        sub_id: sub_id.clone(),
+        call_id: call_id.clone(),
+        turns: AtomicU32::new(0),
+        sent: false,
+    };
+
+    // Auto-compaction goes by the size of the parent's prompt, not the
+    // child's.
+    let parent_input_tokens = sess.state.lock().unwrap().last_input_tokens;
+    let (turns, result) =
+        run_sub_agent(sess, &sub_id, &call_id, &args, max_turns, &end.turns).await;
+    sess.state.lock().unwrap().last_input_tokens = parent_input_tokens;
+
+    end.sent = true;
+    sess.send_event(Event {
+        id: sub_id,
+        msg: EventMsg::SubAgentEnd(SubAgentEndEvent {
+            call_id: call_id.clone(),
+            turns,
+            result: result.clone(),
+        }),
+    })
+    .await;
+    function_call_output(call_id, result)
+}
+
+/// Sends the `SubAgentEnd` of a sub-agent whose turn is aborted before it
+/// finishes, so clients do not show it running forever.
+struct SubAgentEndGuard {
+    tx_event: Sender<Event>,
+    sub_id: String,
+    call_id: String,
+    /// Turns the sub-agent has started.
+    turns: AtomicU32,
+    sent: bool,
+}
+
+impl Drop for SubAgentEndGuard {
+    fn drop(&mut self) {
+        if self.sent {
+            return;
+        }
+        let event = Event {
+            id: self.sub_id.clone(),
+            msg: EventMsg::SubAgentEnd(SubAgentEndEvent {
+                call_id: self.call_id.clone(),
+                turns: self.turns.load(std::sync::atomic::Ordering::Relaxed),
+                result: Err("the sub-agent was interrupted".to_string()),
+            }),
+        };
+        let tx_event = self.tx_event.clone();
+        tokio::spawn(async move {
+            tx_event.send(event).await.ok();
+        });
+    }
+}
+
+/// The child conversation of `spawn_agent`. It is not stored server-side, so
+/// every request carries the child's whole transcript. Tool calls go through
+/// the same handlers (approvals, sandbox, audit log) as the parent's. Returns
+/// the number of turns taken and the final message; `turns_started` tracks
+/// the turns for a sub-agent that is interrupted.
+async fn run_sub_agent(
+    sess: &Session,
+    sub_id: &str,
+    call_id: &str,
+    args: &SpawnAgentArgs,
+    max_turns: u32,
+    turns_started: &AtomicU32,
+) -> (u32, Result<String, String>) {
+    let instructions = match &sess.instructions() {
+        Some(instructions) => format!("{instructions}\n\n{SUB_AGENT_INSTRUCTIONS}"),
+        None => SUB_AGENT_INSTRUCTIONS.to_string(),
+    };
+    let disabled_tools = args.disabled_tools(&sess.disabled_tools);
+    let extra_tools = args.mcp_tools(sess.mcp_connection_manager.list_all_tools());
+    let strict_tools = sess.mcp_connection_manager.strict_tool_names();
+    let mut transcript = vec![ResponseItem::Message {
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: args.prompt.clone(),
+        }],
+    }];
+    let mut last_message: Option<String> = None;
+
+    for turn in 1..=max_turns {
+        turns_started.store(turn, std::sync::atomic::Ordering::Relaxed);
+        let prompt = Prompt {
+            input: transcript.clone(),
+            prev_id: None,
+            base_instructions_override: None,
+            user_instructions: Some(instructions.clone()),
+            store: false,
+            extra_tools: extra_tools.clone(),
+            strict_tools: strict_tools.clone(),
+            disabled_tools: disabled_tools.clone(),
+            hosted_web_search: false,
+        };
+        let items = match run_sub_agent_turn(sess, sub_id, &prompt).await {
+            Ok(items) => items,
+            Err(e) => return (turn, Err(format!("sub-agent failed: {e}"))),
+        };
+
+        let mut responses = Vec::new();
+        for item in items {
+            let tool_call = match &item {
+                ResponseItem::Message { role, content } if role == "assistant" => {
+                    let text: String = content
+                        .iter()
+                        .filter_map(|content| match content {
+                            ContentItem::OutputText { text } => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    if !text.is_empty() {
+                        sess.send_event(Event {
+                            id: sub_id.to_string(),
+                            msg: EventMsg::SubAgentMessage(SubAgentMessageEvent {
+                                call_id: call_id.to_string(),
+                                message: text.clone(),
+                            }),
+                        })
+                        .await;
+                        last_message = Some(text);
+                    }
+                    transcript.push(item);
+                    continue;
+                }
+                ResponseItem::FunctionCall { name, call_id, .. } => {
+                    (name.as_str(), call_id.clone())
+                }
+                ResponseItem::LocalShellCall { id, call_id, .. } => (
+                    "local_shell",
+                    call_id.clone().or_else(|| id.clone()).unwrap_or_default(),
+                ),
+                // Reasoning can only be replayed from server-side storage.
+                _ => continue,
+            };
+
+            let (name, tool_call_id) = tool_call;
+            let response = if args.allows(name) {
+                match handle_sub_agent_item(sess, sub_id, item.clone()).await {
+                    Ok(response) => response,
+                    Err(e) => return (turn, Err(format!("sub-agent failed: {e}"))),
+                }
+            } else {
+                Some(function_call_output(
+                    tool_call_id,
+                    Err(format!("tool `{name}` is not available to this sub-agent")),
+                ))
+            };
+            if let Some(response) = response {
+                transcript.push(item);
+                responses.push(response);
+            }
+        }
+
+        if responses.is_empty() {
+            let result =
+                last_message.ok_or_else(|| "the sub-agent finished without a message".to_string());
+            return (turn, result);
+        }
+        if let Some(message) = sess.token_budget_exhausted_message() {
+            return (turn, Err(message));
+        }
+        transcript.extend(responses.into_iter().map(ResponseItem::from));
+    }
+
+    let last_message = last_message
+        .map(|message| format!("; its last message was:\n{message}"))
+        .unwrap_or_default();
+    (
+        max_turns,
+        Err(format!(
+            "the sub-agent did not finish within {max_turns} turns{last_message}"
+        )),
+    )
+}
+
+/// [`handle_response_item`] for a sub-agent's tool call, boxed because the
+/// call handlers can lead back to [`handle_spawn_agent`].
+fn handle_sub_agent_item<'a>(
+    sess: &'a Session,
+    sub_id: &'a str,
+    item: ResponseItem,
+) -> Pin<Box<dyn Future<Output = CodexResult<Option<ResponseInputItem>>> + Send + 'a>> {
+    Box::pin(handle_response_item(sess, sub_id, item))
+}
+
+/// One model request outside the conversation (a sub-agent's turn, a native
+/// web search). Unlike [`try_run_turn`], this leaves the session's
+/// `previous_response_id` and session log alone.
+async fn run_sub_agent_turn(
+    sess: &Session,
+    sub_id: &str,
+    prompt: &Prompt,
+) -> CodexResult<Vec<ResponseItem>> {
+    let mut stream = sess.client().stream(prompt).await?;
+    let mut items = Vec::new();
+    while let Some(event) = stream.next().await {
+        match event? {
+            ResponseEvent::OutputItemDone(item) => items.push(item),
+            ResponseEvent::Completed { token_usage, .. } => {
+                if let Some(token_usage) = token_usage {
+                    sess.record_token_usage(sub_id, token_usage).await;
+                }
+                break;
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// Searches the web with the configured backend and returns the results as
+/// numbered title/URL/snippet entries. The query shows up in the client's
+/// transcript as a background event.
+async fn handle_web_search(
+    sess: &Session,
+    sub_id: &str,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let args = match serde_json::from_str::<WebSearchArgs>(&arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return function_call_output(
+                call_id,
+                Err(format!("failed to parse function arguments: {e}")),
+            );
+        }
+    };
+    sess.notify_background_event(sub_id, format!("web search: {}", args.query))
+        .await;
+    let max_results = max_results(&sess.web_search, &args);
+    let results = match sess.web_search.backend {
+        WebSearchBackend::Native => native_web_search(sess, sub_id, &args.query, max_results).await,
+        _ => search_endpoint(&sess.web_search, &args.query, max_results).await,
+    };
+    function_call_output(
+        call_id,
+        results.map(|results| format_results(&args.query, &results)),
+    )
+}
+
+/// A one-off request with the provider's hosted search tool, outside the
+/// conversation, whose reply lists the results as JSON.
+async fn native_web_search(
+    sess: &Session,
+    sub_id: &str,
+    query: &str,
+    max_results: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let prompt = Prompt {
+        input: vec![ResponseItem::Message {
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: format!("{query}\n\n(Return at most {max_results} results.)"),
+            }],
+        }],
+        prev_id: None,
+        base_instructions_override: Some(NATIVE_SEARCH_INSTRUCTIONS.to_string()),
+        user_instructions: None,
+        store: false,
+        extra_tools: HashMap::new(),
+        strict_tools: HashSet::new(),
+        disabled_tools: builtin_tool_names().map(str::to_string).collect(),
+        hosted_web_search: true,
+    };
+    let items = run_sub_agent_turn(sess, sub_id, &prompt)
+        .await
+        .map_err(|e| format!("web search failed: {e}"))?;
+    let reply: String = items
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::Message { role, content } if role == "assistant" => Some(content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|content| match content {
+            ContentItem::OutputText { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    parse_native_results(&reply, max_results)
+}
+
+/// Downloads a page and returns its readable text with the final URL.
+async fn handle_fetch_url(
+    sess: &Session,
+    sub_id: &str,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let args = match serde_json::from_str::<FetchUrlArgs>(&arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return function_call_output(
+                call_id,
+                Err(format!("failed to parse function arguments: {e}")),
+            );
+        }
+    };
+    sess.notify_background_event(sub_id, format!("fetch: {}", args.url))
+        .await;
+    let page = fetch_url(&args.url).await;
+    function_call_output(call_id, page.map(|page| format_page(&page)))
 }
 
-async fn handle_function_call(
+async fn handle_list_mcp_resources(
     sess: &Session,
-    sub_id: String,
-    name: String,
     arguments: String,
     call_id: String,
 ) -> ResponseInputItem {
-    match name.as_str() {
-        "container.exec" | "shell" => {
-            let params = match parse_container_exec_arguments(arguments, sess, &call_id) {
-                Ok(params) => params,
-                Err(output) => {
-                    return output;
-                }
-            };
-            handle_container_exec_with_params(params, sess, sub_id, call_id).await
+    let args = match serde_json::from_str::<ListMcpResourcesArgs>(&arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return function_call_output(
+                call_id,
+                Err(format!("failed to parse function arguments: {e}")),
+            );
         }
-        _ => {
-            match try_parse_fully_qualified_tool_name(&name) {
-                Some((server, tool_name)) => {
-                    // TODO(mbolin): Determine appropriate timeout for tool call.
-                    let timeout = None;
-                    handle_mcp_tool_call(
-                        sess, &sub_id, call_id, server, tool_name, arguments, timeout,
-                    )
-                    .await
-                }
-                None => {
-                    // Unknown function: reply with structured failure so the model can adapt.
-                    ResponseInputItem::FunctionCallOutput {
-                        call_id,
-                        output: crate::models::FunctionCallOutputPayload {
-                            content: format!("unsupported call: {}", name),
-                            success: None,
-                        },
-                    }
-                }
-            }
+    };
+    let resources = sess
+        .mcp_connection_manager
+        .list_resources(args.server.as_deref())
+        .await
+        .map_err(|e| format!("{e:#}"));
+    function_call_output(
+        call_id,
+        resources.map(|resources| format_resource_list(&resources)),
+    )
+}
+
+async fn handle_read_mcp_resource(
+    sess: &Session,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let args = match serde_json::from_str::<ReadMcpResourceArgs>(&arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return function_call_output(
+                call_id,
+                Err(format!("failed to parse function arguments: {e}")),
+            );
         }
+    };
+    let contents = sess
+        .mcp_connection_manager
+        .read_resource(&args.server, &args.uri)
+        .await
+        .map_err(|e| format!("{e:#}"));
+    function_call_output(call_id, contents.map(format_resource_contents))
+}
+
+/// The name the `[tools]` config disables the tool `name` by:
+/// `container.exec` and `local_shell` are the `shell` tool under other names.
+fn config_tool_name(name: &str) -> &str {
+    match name {
+        "container.exec" | "local_shell" => "shell",
+        name => name,
+    }
+}
+
+fn is_tool_disabled(sess: &Session, name: &str) -> bool {
+    let name = config_tool_name(name);
+    sess.disabled_tools.iter().any(|tool| tool == name)
+}
+
+fn function_call_output(call_id: String, result: Result<String, String>) -> ResponseInputItem {
+    let (content, success) = match result {
+        Ok(content) => (content, Some(true)),
+        Err(content) => (content, Some(false)),
+    };
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload { content, success },
     }
 }
 
@@ -1227,8 +3436,12 @@ fn to_exec_params(params: ShellToolCallParams, sess: &Session) -> ExecParams {
     ExecParams {
         command: params.command,
         cwd: sess.resolve_path(params.workdir.clone()),
-        timeout_ms: params.timeout_ms,
+        timeout_ms: params
+            .timeout_ms
+            .or_else(|| sess.exec_timeout_sec.map(|secs| secs * 1000)),
         env: create_env(&sess.shell_environment_policy),
+        tty_columns: params.tty.then(|| sess.terminal_columns()),
+        max_output_bytes: sess.max_output_bytes,
     }
 }
 
@@ -1254,45 +3467,30 @@ fn parse_container_exec_arguments(
     }
 }
 
-async fn handle_container_exec_with_params(
-    params: ExecParams,
+/// Runs the approval rules, auto-approval predicates and safety checks for
+/// `params`, asking the user if needed. Returns the sandbox to run the command
+/// in, or the message to send back to the model if it must not run.
+async fn approve_exec(
     sess: &Session,
-    sub_id: String,
-    call_id: String,
-) -> ResponseInputItem {
-    // check if this was a patch, and apply it if so
-    match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
-        MaybeApplyPatchVerified::Body(changes) => {
-            return apply_patch(sess, sub_id, call_id, changes).await;
-        }
-        MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
-            // It looks like an invocation of `apply_patch`, but we
-            // could not resolve it into a patch that would apply
-            // cleanly. Return to model for resample.
-            return ResponseInputItem::FunctionCallOutput {
-                call_id,
-                output: FunctionCallOutputPayload {
-                    content: format!("error: {parse_error:#}"),
-                    success: None,
-                },
-            };
-        }
-        MaybeApplyPatchVerified::ShellParseError(error) => {
-            trace!("Failed to parse shell command, {error:?}");
-        }
-        MaybeApplyPatchVerified::NotApplyPatch => (),
-    }
-
-    // safety checks with auto-approval predicates
-    let safety = match evaluate_auto_allow_predicates(&params.command, &sess.auto_allow) {
+    sub_id: &str,
+    params: &ExecParams,
+) -> Result<SandboxType, String> {
+    // safety checks with approval rules, then auto-approval predicates
+    let vote = match evaluate_approval_rules(&params.command, &sess.approval_rules) {
+        Some(rule) => match rule.action {
+            ApprovalRuleAction::Allow => AutoAllowVote::Allow,
+            ApprovalRuleAction::Deny => {
+                return Err(format!(
+                    "exec command denied by approval rule ({})",
+                    rule.describe()
+                ));
+            }
+        },
+        None => evaluate_auto_allow_predicates(&params.command, &sess.auto_allow),
+    };
+    let safety = match vote {
         AutoAllowVote::Deny => {
-            return ResponseInputItem::FunctionCallOutput {
-                call_id,
-                output: crate::models::FunctionCallOutputPayload {
-                    content: "exec command denied by auto-approval predicate".to_string(),
-                    success: None,
-                },
-            };
+            return Err("exec command denied by auto-approval predicate".to_string());
         }
         AutoAllowVote::Allow => {
             let sandbox_type = if sess.sandbox_policy.is_unrestricted() {
@@ -1303,12 +3501,14 @@ async fn handle_container_exec_with_params(
             SafetyCheck::AutoApprove { sandbox_type }
         }
         AutoAllowVote::NoOpinion => {
+            let approval_policy = sess.approval_policy();
             let state = sess.state.lock().unwrap();
             assess_command_safety(
                 &params.command,
-                sess.approval_policy,
+                approval_policy,
                 &sess.sandbox_policy,
                 &state.approved_commands,
+                &state.approved_prefixes,
             )
         }
     };
@@ -1317,7 +3517,7 @@ async fn handle_container_exec_with_params(
         SafetyCheck::AskUser => {
             let rx_approve = sess
                 .request_command_approval(
-                    sub_id.clone(),
+                    sub_id.to_string(),
                     params.command.clone(),
                     params.cwd.clone(),
                     None,
@@ -1328,14 +3528,11 @@ async fn handle_container_exec_with_params(
                 ReviewDecision::ApprovedForSession => {
                     sess.add_approved_command(params.command.clone());
                 }
+                ReviewDecision::ApprovedPrefixForSession => {
+                    sess.add_approved_prefix_for(params.command.clone());
+                }
                 ReviewDecision::Denied | ReviewDecision::Abort => {
-                    return ResponseInputItem::FunctionCallOutput {
-                        call_id,
-                        output: crate::models::FunctionCallOutputPayload {
-                            content: "exec command rejected by user".to_string(),
-                            success: None,
-                        },
-                    };
+                    return Err("exec command rejected by user".to_string());
                 }
             }
             // No sandboxing is applied because the user has given
@@ -1345,10 +3542,150 @@ async fn handle_container_exec_with_params(
             SandboxType::None
         }
         SafetyCheck::Reject { reason } => {
+            return Err(format!("exec command rejected: {reason}"));
+        }
+    };
+    Ok(sandbox_type)
+}
+
+/// Sends the expanded custom prompt `text` to the running task, or starts
+/// one with the prompt's overrides. `@path` references are inlined last, so
+/// `` !`command` `` blocks in the referenced files are not run.
+fn start_custom_prompt(sess: &Arc<Session>, sub_id: String, prompt: &CustomPrompt, text: String) {
+    let text = expand_file_references(&text, &sess.cwd);
+    let items = vec![InputItem::Text { text }];
+    if let Err(items) = sess.inject_input(items) {
+        let overrides = TaskOverrides {
+            model: prompt.model.clone(),
+            approval_policy: prompt.approval,
+        };
+        let task = AgentTask::spawn_with_overrides(Arc::clone(sess), sub_id, items, overrides);
+        sess.set_task(task);
+    }
+}
+
+/// The text of the expanded custom prompt `parts`, with its command blocks
+/// replaced by the output of their commands. Each runs like a `shell` tool
+/// call, after the approval checks and in the sandbox; a command that may
+/// not run or fails to start is replaced with the reason.
+async fn run_prompt_commands(sess: &Session, sub_id: &str, parts: Vec<PromptPart>) -> String {
+    let mut expanded = String::new();
+    let mut commands = 0;
+    for part in parts {
+        match part {
+            PromptPart::Text(text) => expanded.push_str(&text),
+            PromptPart::Command(command) => {
+                let call_id = format!("custom-prompt-{sub_id}-{commands}");
+                commands += 1;
+                let output = run_prompt_command(sess, sub_id, &call_id, &command).await;
+                expanded.push_str(&output);
+            }
+        }
+    }
+    expanded
+}
+
+async fn run_prompt_command(sess: &Session, sub_id: &str, call_id: &str, command: &str) -> String {
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-lc".to_string(), command.to_string()],
+        cwd: sess.cwd.clone(),
+        timeout_ms: sess.exec_timeout_sec.map(|secs| secs * 1000),
+        env: create_env(&sess.shell_environment_policy),
+        tty_columns: None,
+        max_output_bytes: sess.max_output_bytes,
+    };
+    let sandbox_type = match approve_exec(sess, sub_id, &params).await {
+        Ok(sandbox_type) => sandbox_type,
+        Err(reason) => return format!("[`{command}` was not run: {reason}]"),
+    };
+    sess.notify_exec_command_begin(sub_id, call_id, &params)
+        .await;
+    let result = process_exec_tool_call(
+        params,
+        sess.sandbox_type_for_backend(sandbox_type),
+        sess.ctrl_c.clone(),
+        &sess.sandbox_policy,
+        &sess.codex_linux_sandbox_exe,
+        &sess.container_sandbox,
+        None,
+    )
+    .await;
+    match result {
+        Ok(output) => {
+            sess.notify_exec_command_end(
+                sub_id,
+                call_id,
+                &output.stdout,
+                &output.stderr,
+                output.exit_code,
+            )
+            .await;
+            let mut text = output.stdout;
+            text.push_str(&output.stderr);
+            text.trim_end().to_string()
+        }
+        Err(e) => format!("[`{command}` failed: {e}]"),
+    }
+}
+
+async fn handle_container_exec_with_params(
+    params: ExecParams,
+    sess: &Session,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    // `apply_patch --dry-run` only reads files, so it needs no approval, but
+    // it may only read the session's cwd and the sandbox's writable roots.
+    if let Some(parsed) = maybe_parse_apply_patch_dry_run(&params.command) {
+        let output = match parsed {
+            Ok(hunks) => {
+                let mut readable_roots = sess.sandbox_policy.get_writable_roots_with_cwd(&sess.cwd);
+                readable_roots.push(sess.cwd.clone());
+                let report = dry_run_hunks(&hunks, &params.cwd, &readable_roots);
+                FunctionCallOutputPayload {
+                    content: serde_json::to_string(&report)
+                        .unwrap_or_else(|e| format!("error: {e}")),
+                    success: Some(report.is_clean()),
+                }
+            }
+            Err(parse_error) => FunctionCallOutputPayload {
+                content: format!("error: {parse_error:#}"),
+                success: Some(false),
+            },
+        };
+        return ResponseInputItem::FunctionCallOutput { call_id, output };
+    }
+
+    // check if this was a patch, and apply it if so
+    match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
+        MaybeApplyPatchVerified::Body(changes) => {
+            return apply_patch(sess, sub_id, call_id, changes).await;
+        }
+        MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
+            // It looks like an invocation of `apply_patch`, but we
+            // could not resolve it into a patch that would apply
+            // cleanly. Return to model for resample.
             return ResponseInputItem::FunctionCallOutput {
                 call_id,
-                output: crate::models::FunctionCallOutputPayload {
-                    content: format!("exec command rejected: {reason}"),
+                output: FunctionCallOutputPayload {
+                    content: format!("error: {parse_error:#}"),
+                    success: None,
+                },
+            };
+        }
+        MaybeApplyPatchVerified::ShellParseError(error) => {
+            trace!("Failed to parse shell command, {error:?}");
+        }
+        MaybeApplyPatchVerified::NotApplyPatch => (),
+    }
+
+    let sandbox_type = match approve_exec(sess, &sub_id, &params).await {
+        Ok(sandbox_type) => sandbox_type,
+        Err(content) => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content,
                     success: None,
                 },
             };
@@ -1358,41 +3695,23 @@ async fn handle_container_exec_with_params(
     sess.notify_exec_command_begin(&sub_id, &call_id, &params)
         .await;
 
+    let sandbox_type = sess.sandbox_type_for_backend(sandbox_type);
+    let without_tty = params.tty_columns.is_some() && !tty_available(sandbox_type);
     let output_result = process_exec_tool_call(
         params.clone(),
         sandbox_type,
         sess.ctrl_c.clone(),
         &sess.sandbox_policy,
         &sess.codex_linux_sandbox_exe,
+        &sess.container_sandbox,
+        Some(sess.stdout_stream(&sub_id, &call_id)),
     )
     .await;
 
     match output_result {
         Ok(output) => {
-            let ExecToolCallOutput {
-                exit_code,
-                stdout,
-                stderr,
-                duration,
-            } = output;
-
-            sess.notify_exec_command_end(&sub_id, &call_id, &stdout, &stderr, exit_code)
-                .await;
-
-            let is_success = exit_code == 0;
-            let content = format_exec_output(
-                if is_success { &stdout } else { &stderr },
-                exit_code,
-                duration,
-            );
-
-            ResponseInputItem::FunctionCallOutput {
-                call_id,
-                output: FunctionCallOutputPayload {
-                    content,
-                    success: Some(is_success),
-                },
-            }
+            let output = report_exec_output(sess, &sub_id, &call_id, output, without_tty).await;
+            ResponseInputItem::FunctionCallOutput { call_id, output }
         }
         Err(CodexErr::Sandbox(error)) => {
             handle_sanbox_error(error, sandbox_type, params, sess, sub_id, call_id).await
@@ -1410,6 +3729,71 @@ async fn handle_container_exec_with_params(
     }
 }
 
+/// Tells the client how the exec call `call_id` ended, including why it
+/// ended early or lost output, and builds the function call output for it.
+/// `without_tty` is set when the command asked for a terminal the sandbox
+/// could not give it.
+async fn report_exec_output(
+    sess: &Session,
+    sub_id: &str,
+    call_id: &str,
+    output: ExecToolCallOutput,
+    without_tty: bool,
+) -> FunctionCallOutputPayload {
+    let ExecToolCallOutput {
+        exit_code,
+        stdout,
+        stderr,
+        duration,
+        timed_out,
+        truncated_bytes,
+    } = output;
+
+    sess.notify_exec_command_end(sub_id, call_id, &stdout, &stderr, exit_code)
+        .await;
+    let timeout_message = format!(
+        "command timed out after {:.1}s and was killed",
+        duration.as_secs_f32()
+    );
+    if timed_out {
+        sess.notify_background_event(sub_id, timeout_message.clone())
+            .await;
+    }
+    if truncated_bytes > 0 {
+        sess.notify_background_event(
+            sub_id,
+            format!("command output truncated: {truncated_bytes} bytes omitted"),
+        )
+        .await;
+    }
+    let tty_message =
+        "`tty` is not available in the Linux sandbox; the command ran without a terminal";
+    if without_tty {
+        sess.notify_background_event(sub_id, tty_message.to_string())
+            .await;
+    }
+
+    let is_success = exit_code == 0;
+    let output = if timed_out {
+        // Everything printed before the kill helps the model decide what to
+        // do next, whichever stream it went to.
+        format!("{stdout}{stderr}\n[{timeout_message}]")
+    } else if is_success {
+        stdout
+    } else {
+        stderr
+    };
+    let output = if without_tty {
+        format!("[{tty_message}]\n{output}")
+    } else {
+        output
+    };
+    FunctionCallOutputPayload {
+        content: format_exec_output(&output, exit_code, duration),
+        success: Some(is_success),
+    }
+}
+
 async fn handle_sanbox_error(
     error: SandboxErr,
     sandbox_type: SandboxType,
@@ -1419,7 +3803,7 @@ async fn handle_sanbox_error(
     call_id: String,
 ) -> ResponseInputItem {
     // Early out if the user never wants to be asked for approval; just return to the model immediately
-    if sess.approval_policy == AskForApproval::Never {
+    if sess.approval_policy() == AskForApproval::Never {
         return ResponseInputItem::FunctionCallOutput {
             call_id,
             output: FunctionCallOutputPayload {
@@ -1446,12 +3830,18 @@ async fn handle_sanbox_error(
         .await;
 
     match rx_approve.await.unwrap_or_default() {
-        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+        decision @ (ReviewDecision::Approved
+        | ReviewDecision::ApprovedForSession
+        | ReviewDecision::ApprovedPrefixForSession) => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future
             // executions skip the sandbox directly.
             // TODO(ragona): Isn't this a bug? It always saves the command in an | fork?
-            sess.add_approved_command(params.command.clone());
+            if decision == ReviewDecision::ApprovedPrefixForSession {
+                sess.add_approved_prefix_for(params.command.clone());
+            } else {
+                sess.add_approved_command(params.command.clone());
+            }
             // Inform UI we are retrying without sandbox.
             sess.notify_background_event(&sub_id, "retrying command without sandbox")
                 .await;
@@ -1469,41 +3859,17 @@ async fn handle_sanbox_error(
                 sess.ctrl_c.clone(),
                 &sess.sandbox_policy,
                 &sess.codex_linux_sandbox_exe,
+                &sess.container_sandbox,
+                Some(sess.stdout_stream(&sub_id, &retry_call_id)),
             )
             .await;
 
             match retry_output_result {
                 Ok(retry_output) => {
-                    let ExecToolCallOutput {
-                        exit_code,
-                        stdout,
-                        stderr,
-                        duration,
-                    } = retry_output;
-
-                    sess.notify_exec_command_end(
-                        &sub_id,
-                        &retry_call_id,
-                        &stdout,
-                        &stderr,
-                        exit_code,
-                    )
-                    .await;
-
-                    let is_success = exit_code == 0;
-                    let content = format_exec_output(
-                        if is_success { &stdout } else { &stderr },
-                        exit_code,
-                        duration,
-                    );
-
-                    ResponseInputItem::FunctionCallOutput {
-                        call_id,
-                        output: FunctionCallOutputPayload {
-                            content,
-                            success: Some(is_success),
-                        },
-                    }
+                    let output =
+                        report_exec_output(sess, &sub_id, &retry_call_id, retry_output, false)
+                            .await;
+                    ResponseInputItem::FunctionCallOutput { call_id, output }
                 }
                 Err(e) => {
                     // Handle retry failure
@@ -1530,6 +3896,69 @@ async fn handle_sanbox_error(
     }
 }
 
+/// Reload the AGENTS.md files whenever they change, for as long as the session
+/// lives.
+fn spawn_project_doc_reloader(
+    sess: Weak<Session>,
+    sub_id: String,
+    mut changes: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+) {
+    tokio::spawn(async move {
+        while let Some(changed) = changes.recv().await {
+            let Some(sess) = sess.upgrade() else {
+                break;
+            };
+            sess.reload_project_docs(&sub_id, &changed).await;
+        }
+    });
+}
+
+/// Watches the prompt directories, reporting changes to any file in them.
+fn watch_prompt_dirs(
+    codex_home: &Path,
+    cwd: &Path,
+) -> notify::Result<(FileWatcher, mpsc::UnboundedReceiver<Vec<PathBuf>>)> {
+    let (mut watcher, changes) = FileWatcher::new(Vec::new())?;
+    watch_prompt_dirs_in(&mut watcher, codex_home, cwd)?;
+    Ok((watcher, changes))
+}
+
+/// Has `watcher` watch the files in the prompt directories that exist. One
+/// that does not exist yet is watched like a file, so that its creation is
+/// reported and the reload that follows starts watching its files.
+fn watch_prompt_dirs_in(
+    watcher: &mut FileWatcher,
+    codex_home: &Path,
+    cwd: &Path,
+) -> notify::Result<()> {
+    let (existing, missing): (Vec<PathBuf>, Vec<PathBuf>) = prompt_dirs(codex_home, cwd)
+        .into_iter()
+        .map(|(dir, _)| dir)
+        .partition(|dir| dir.is_dir());
+    watcher.watch_dirs(existing)?;
+    watcher.watch(
+        missing
+            .into_iter()
+            .filter(|dir| dir.parent().is_some_and(Path::is_dir)),
+    )
+}
+
+fn spawn_prompt_reloader(
+    sess: Weak<Session>,
+    sub_id: String,
+    codex_home: PathBuf,
+    mut changes: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+) {
+    tokio::spawn(async move {
+        while changes.recv().await.is_some() {
+            let Some(sess) = sess.upgrade() else {
+                break;
+            };
+            sess.reload_custom_prompts(&sub_id, &codex_home).await;
+        }
+    });
+}
+
 async fn apply_patch(
     sess: &Session,
     sub_id: String,
@@ -1543,7 +3972,7 @@ async fn apply_patch(
 
     let auto_approved = match assess_patch_safety(
         &action,
-        sess.approval_policy,
+        sess.approval_policy(),
         &writable_roots_snapshot,
         &sess.cwd,
     ) {
@@ -1555,7 +3984,9 @@ async fn apply_patch(
                 .request_patch_approval(sub_id.clone(), &action, None, None)
                 .await;
             match rx_approve.await.unwrap_or_default() {
-                ReviewDecision::Approved | ReviewDecision::ApprovedForSession => false,
+                ReviewDecision::Approved
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedPrefixForSession => false,
                 ReviewDecision::Denied | ReviewDecision::Abort => {
                     return ResponseInputItem::FunctionCallOutput {
                         call_id,
@@ -1622,6 +4053,27 @@ async fn apply_patch(
         })
         .await;
 
+    // Remember what the files looked like before this turn first changed
+    // them, so the turn can be reverted.
+    let touched_paths = action
+        .changes()
+        .iter()
+        .flat_map(|(path, change)| match change {
+            ApplyPatchFileChange::Update {
+                move_path: Some(dest),
+                ..
+            } => vec![path.as_path(), dest.as_path()],
+            _ => vec![path.as_path()],
+        });
+    if let Err(e) = sess
+        .checkpoints
+        .lock()
+        .unwrap()
+        .snapshot(&sub_id, touched_paths)
+    {
+        warn!("failed to snapshot files before applying patch: {e}");
+    }
+
     let mut stdout = Vec::new();
     let mut stderr = Vec::new();
     // Enforce writable roots. If a write is blocked, collect offending root
@@ -1713,13 +4165,19 @@ async fn apply_patch(
         .await;
 
     match result {
-        Ok(_) => ResponseInputItem::FunctionCallOutput {
-            call_id,
-            output: FunctionCallOutputPayload {
-                content: String::from_utf8_lossy(&stdout).to_string(),
-                success: None,
-            },
-        },
+        Ok(_) => {
+            let mut content = String::from_utf8_lossy(&stdout).to_string();
+            if let Some(instructions) = sess.load_edited_dirs_project_docs(&sub_id, &action).await {
+                content.push_str(&instructions);
+            }
+            ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(true),
+                },
+            }
+        }
         Err(e) => ResponseInputItem::FunctionCallOutput {
             call_id,
             output: FunctionCallOutputPayload {
@@ -1958,6 +4416,6 @@ fn record_conversation_history(disable_response_storage: bool, wire_api: WireApi
 
     match wire_api {
         WireApi::Responses => false,
-        WireApi::Chat => true,
+        WireApi::Chat | WireApi::Anthropic => true,
     }
 }
@@ -0,0 +1,134 @@
+//! Recognizes shell commands that change directory before doing their actual
+//! work, e.g. `bash -lc 'cd src && cargo test'`, so they can be shown and
+//! checked as the command that really runs, in the directory it runs in.
+
+use std::path::PathBuf;
+
+use tree_sitter::Node;
+
+use crate::is_safe_command::parse_word_only_command_node;
+use crate::is_safe_command::try_parse_bash;
+
+/// A shell script with its leading `cd <dir>` split off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedCommand {
+    /// Directory the script changes into first. Relative paths are relative
+    /// to the working directory of the command.
+    pub cd: PathBuf,
+    /// The rest of the script, which runs in `cd`.
+    pub script: String,
+}
+
+/// If `command` is a `bash`, `sh` or `zsh` `-c`/`-lc` script that starts
+/// with `cd <dir> &&` or `cd <dir>;` (or `pushd`), split off the change of
+/// directory. Returns `None` if the directory is not a plain word or string
+/// (e.g. `cd $HOME`, `cd ~`, `cd -`).
+pub fn normalize_wrapped_command(command: &[String]) -> Option<NormalizedCommand> {
+    let script = shell_script(command)?;
+    let tree = try_parse_bash(script)?;
+    let root = tree.root_node();
+    if root.has_error() {
+        return None;
+    }
+
+    // Find the first command of the script; `a && b && c` nests to the left.
+    let mut first = root.named_child(0)?;
+    while first.kind() == "list" {
+        first = first.named_child(0)?;
+    }
+    let cd = cd_target(first, script)?;
+
+    let operator = first.next_sibling()?;
+    if !matches!(operator.kind(), "&&" | ";" | "\n") {
+        return None;
+    }
+    let rest = script[operator.end_byte()..].trim();
+    if rest.is_empty() {
+        return None;
+    }
+    Some(NormalizedCommand {
+        cd,
+        script: rest.to_string(),
+    })
+}
+
+fn shell_script(command: &[String]) -> Option<&str> {
+    match command {
+        [shell, flag, script]
+            if matches!(shell.rsplit('/').next(), Some("bash" | "sh" | "zsh"))
+                && matches!(flag.as_str(), "-c" | "-lc") =>
+        {
+            Some(script)
+        }
+        _ => None,
+    }
+}
+
+/// The directory `node` changes into if it is a plain `cd`/`pushd <dir>`.
+fn cd_target(node: Node<'_>, src: &str) -> Option<PathBuf> {
+    let command = parse_word_only_command_node(node, src)?;
+    if !command.assignments.is_empty() {
+        return None;
+    }
+    let (program, args) = command.words.split_first()?;
+    if !matches!(program.as_str(), "cd" | "pushd") {
+        return None;
+    }
+    // `-q` (zsh: stay quiet) and `-L`/`-P` (symlink handling) do not change
+    // which directory is entered.
+    let args: Vec<&String> = args
+        .iter()
+        .skip_while(|arg| matches!(arg.as_str(), "-q" | "-L" | "-P" | "--"))
+        .collect();
+    match args.as_slice() {
+        [dir] if !dir.is_empty() && !dir.starts_with(['-', '+', '~']) => Some(PathBuf::from(dir)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(shell: &str, script: &str) -> Option<(String, String)> {
+        let command = [shell, "-lc", script].map(String::from);
+        normalize_wrapped_command(&command)
+            .map(|normalized| (normalized.cd.display().to_string(), normalized.script))
+    }
+
+    fn pair(cd: &str, script: &str) -> Option<(String, String)> {
+        Some((cd.to_string(), script.to_string()))
+    }
+
+    #[test]
+    fn strips_cd_wrappers() {
+        assert_eq!(
+            normalize("bash", "cd src && cargo test"),
+            pair("src", "cargo test")
+        );
+        assert_eq!(
+            normalize("bash", "cd \"my dir\"; ls -l && wc -l x"),
+            pair("my dir", "ls -l && wc -l x")
+        );
+        assert_eq!(
+            normalize("/bin/zsh", "pushd -q '/tmp/a b' && make"),
+            pair("/tmp/a b", "make")
+        );
+        assert_eq!(
+            normalize("sh", "cd a && cd b && ls"),
+            pair("a", "cd b && ls")
+        );
+    }
+
+    #[test]
+    fn keeps_other_commands() {
+        assert_eq!(normalize("bash", "cargo test"), None);
+        assert_eq!(normalize("bash", "cd src"), None);
+        assert_eq!(normalize("bash", "cd src || exit 1"), None);
+        assert_eq!(normalize("bash", "cd $HOME && ls"), None);
+        assert_eq!(normalize("bash", "cd ~/src && ls"), None);
+        assert_eq!(normalize("bash", "cd - && ls"), None);
+        assert_eq!(normalize("bash", "cd src | ls"), None);
+        assert_eq!(normalize("python3", "cd src && ls"), None);
+    }
+}
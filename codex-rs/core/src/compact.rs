@@ -0,0 +1,96 @@
+//! Context compaction: when a conversation approaches the model's context
+//! window, older turns are replaced by a model-written summary so the session
+//! can continue instead of failing with a context-length error.
+
+use crate::models::ContentItem;
+use crate::models::ResponseInputItem;
+
+/// Compact once the prompt of the last turn used this fraction of the window.
+const AUTO_COMPACT_THRESHOLD: f64 = 0.9;
+
+/// Instructions sent to the model to produce the summary.
+pub(crate) const SUMMARIZATION_PROMPT: &str = "\
+Summarize the conversation so far so that it can replace the full transcript. \
+Include the user's goals, decisions made, the current state of the work \
+(files touched, commands run and their outcomes), and any open questions or \
+next steps. Be concise but do not omit details needed to continue the task. \
+Do not call any tools; reply with the summary only.";
+
+/// Known context window sizes (in tokens) for built-in models. Models not in
+/// this list can be configured via `model_context_window`.
+pub(crate) fn model_context_window(model: &str) -> Option<u64> {
+    let model = model.to_ascii_lowercase();
+    let window = match model.as_str() {
+        m if m.starts_with("o3") || m.starts_with("o4-mini") => 200_000,
+        m if m.starts_with("codex-mini") => 200_000,
+        m if m.starts_with("gpt-4.1") => 1_047_576,
+        m if m.starts_with("gpt-4o") => 128_000,
+        m if m.starts_with("gpt-3.5-turbo") => 16_385,
+        m if m.starts_with("claude-") => 200_000,
+        _ => return None,
+    };
+    Some(window)
+}
+
+/// Whether a prompt of `input_tokens` is close enough to `context_window` that
+/// the conversation should be compacted before the next turn.
+pub(crate) fn should_auto_compact(input_tokens: u64, context_window: Option<u64>) -> bool {
+    match context_window {
+        Some(window) if window > 0 => input_tokens as f64 >= window as f64 * AUTO_COMPACT_THRESHOLD,
+        _ => false,
+    }
+}
+
+/// Appended to the summary when compacting in the middle of a task: the
+/// next request carries no new user message, so without it the model tends
+/// to stop after acknowledging the summary.
+const CONTINUE_TASK_INSTRUCTION: &str =
+    "Continue working on the current task from where the summary leaves off.";
+
+/// The item that stands in for the compacted turns in subsequent prompts.
+/// `mid_task` adds an instruction to carry on with the task in progress.
+pub(crate) fn summary_input_item(summary: &str, mid_task: bool) -> ResponseInputItem {
+    let mut text = format!(
+        "The earlier part of this conversation was compacted. Summary of the conversation so far:\n\n{summary}"
+    );
+    if mid_task {
+        text.push_str("\n\n");
+        text.push_str(CONTINUE_TASK_INSTRUCTION);
+    }
+    ResponseInputItem::Message {
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText { text }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_models_have_context_windows() {
+        assert_eq!(model_context_window("o3"), Some(200_000));
+        assert_eq!(model_context_window("codex-mini-latest"), Some(200_000));
+        assert_eq!(model_context_window("my-local-model"), None);
+    }
+
+    #[test]
+    fn compacts_near_the_window() {
+        assert!(!should_auto_compact(100_000, Some(200_000)));
+        assert!(should_auto_compact(180_000, Some(200_000)));
+        assert!(!should_auto_compact(u64::MAX, None));
+    }
+
+    #[test]
+    fn mid_task_summary_asks_to_continue() {
+        let text = |mid_task| match summary_input_item("did X", mid_task) {
+            ResponseInputItem::Message { content, .. } => match &content[..] {
+                [ContentItem::InputText { text }] => text.clone(),
+                other => panic!("unexpected content: {other:?}"),
+            },
+            other => panic!("unexpected item: {other:?}"),
+        };
+        assert!(!text(false).contains(CONTINUE_TASK_INSTRUCTION));
+        assert!(text(true).ends_with(&format!("did X\n\n{CONTINUE_TASK_INSTRUCTION}")));
+    }
+}
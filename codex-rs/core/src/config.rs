@@ -1,24 +1,42 @@
+use crate::approval_rules::ApprovalRule;
 use crate::config_profile::ConfigProfile;
+use crate::config_types::ContainerSandboxConfig;
 use crate::config_types::History;
+use crate::config_types::KeyBinding;
+use crate::config_types::McpSamplingConfig;
 use crate::config_types::McpServerConfig;
+use crate::config_types::OtelConfig;
+use crate::config_types::PromptCaching;
 use crate::config_types::ReasoningEffort;
 use crate::config_types::ReasoningSummary;
+use crate::config_types::RedactionConfig;
+use crate::config_types::SandboxBackendKind;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
+use crate::config_types::ToolsConfig;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
+use crate::config_types::WebSearchConfig;
 use crate::flags::OPENAI_DEFAULT_MODEL;
+use crate::model_pricing::ModelPricing;
+use crate::model_pricing::builtin_model_pricing;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPermission;
 use crate::protocol::SandboxPolicy;
+use crate::tool_restrictions::find_project_config;
+use crate::tool_restrictions::load_project_tools_config;
+use codex_login::DEFAULT_ACCOUNT;
+use codex_login::is_valid_account_name;
+use codex_login::list_accounts;
 use dirs::home_dir;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use toml::Value as TomlValue;
+use uuid::Uuid;
 
 /// Maximum number of bytes of the documentation that will be embedded. Larger
 /// files are *silently truncated* to this size so we do not take up too much of
@@ -32,6 +50,14 @@ pub struct AutoAllowPredicate {
     pub script: String,
 }
 
+/// Settings of a [`Config`] that no flag, `-c` override, profile or
+/// `config.toml` set.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DefaultedSettings {
+    pub model: bool,
+    pub model_provider: bool,
+}
+
 /// Application configuration loaded from disk and merged with overrides.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Config {
@@ -46,9 +72,18 @@ pub struct Config {
 
     /// Approval policy for executing commands.
     pub approval_policy: AskForApproval,
+
+    /// Settings left at their built-in default, which a resumed session may
+    /// take from where it left off.
+    pub defaulted: DefaultedSettings,
+
     /// Auto-approval predicate scripts that cast votes on each shell command.
     pub auto_allow: Vec<AutoAllowPredicate>,
 
+    /// Glob/regex rules that pre-approve or reject shell commands. Checked
+    /// before `auto_allow` predicates and before the user is asked.
+    pub approval_rules: Vec<ApprovalRule>,
+
     pub sandbox_policy: SandboxPolicy,
 
     pub shell_environment_policy: ShellEnvironmentPolicy,
@@ -96,12 +131,72 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Tools hidden from the model, combining the user config with the
+    /// project's `.codex/config.toml`.
+    pub tools: ToolsConfig,
+
+    /// When `true`, every tool call is appended to
+    /// `~/.codex/logs/tool_audit.jsonl`.
+    pub tool_audit_log: bool,
+
+    /// When `true`, the values of secret-looking environment variables
+    /// (`*KEY*`, `*TOKEN*`, `*SECRET*`, `*PASSWORD*`) are masked in tool
+    /// output before the model sees it.
+    pub mask_env_secrets: bool,
+
+    /// Prices from `[model_pricing]` by model name, used to estimate what a
+    /// session costs. See [`Config::model_pricing_for`].
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// TUI key bindings from the `[keys]` table: action name -> key(s).
+    pub keys: HashMap<String, KeyBinding>,
+
+    /// Models offered by `/model` in addition to those the provider lists,
+    /// e.g. local models the provider cannot enumerate.
+    pub models: Vec<String>,
+
+    /// Where to place prompt-caching breakpoints for providers that need them.
+    pub prompt_caching: PromptCaching,
+
+    /// Backend of the `web_search` tool and whether it is offered.
+    pub web_search: WebSearchConfig,
+
+    /// Whether MCP clients of `codex mcp serve` may sample the model.
+    pub mcp_sampling: McpSamplingConfig,
+
+    /// Masking of secrets in tool output and messages.
+    pub redaction: RedactionConfig,
+
+    /// OpenTelemetry collector to export traces and metrics to, if any.
+    pub otel: Option<OtelConfig>,
+
+    /// Name of the `[profiles]` entry in effect, if any.
+    pub active_profile: Option<String>,
+
+    /// Profiles defined under `[profiles]`, for switching with `/profile`.
+    pub profiles: HashMap<String, ConfigProfile>,
+
+    /// Named login to use (`CODEX_HOME/auth/<account>.json`, written by
+    /// `codex login --profile <account>`) instead of `CODEX_HOME/auth.json`.
+    pub account: Option<String>,
+
+    /// OpenAI organization to bill (`OpenAI-Organization` header) instead of
+    /// the default one of the login.
+    pub org_id: Option<String>,
+
+    /// Project within the organization to bill (`OpenAI-Project` header).
+    pub workspace_id: Option<String>,
+
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: usize,
 
+    /// Whether the `allow_shell` of a project's custom prompts is honored,
+    /// letting prompts that come with a repository run commands.
+    pub allow_project_prompt_shell: bool,
+
     /// Directory containing all Codex state (defaults to `~/.codex` but can be
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
@@ -116,6 +211,21 @@ pub struct Config {
     /// Collection of settings that are specific to the TUI.
     pub tui: Tui,
 
+    /// Mechanism used to isolate sandboxed shell commands.
+    pub sandbox_backend: SandboxBackendKind,
+
+    /// Engine and image used when `sandbox_backend` is `container`.
+    pub container_sandbox: ContainerSandboxConfig,
+
+    /// Timeout for shell commands that do not specify one. When `None`, 10
+    /// seconds.
+    pub exec_timeout_sec: Option<u64>,
+
+    /// Bytes of stdout and of stderr kept per shell command. Longer output
+    /// keeps its head and tail around a `[truncated N bytes]` marker. When
+    /// `None`, 10 KiB.
+    pub max_output_bytes: Option<usize>,
+
     /// Path to the `codex-linux-sandbox` executable. This must be set if
     /// [`crate::exec::SandboxType::LinuxSeccomp`] is used. Note that this
     /// cannot be set in the config file: it must be set in code via
@@ -131,6 +241,20 @@ pub struct Config {
     /// If not "none", the value to use for `reasoning.summary` when making a
     /// request using the Responses API.
     pub model_reasoning_summary: ReasoningSummary,
+
+    /// When set, the session with this id is loaded from
+    /// `~/.codex/sessions` and continued instead of starting a new one. This
+    /// cannot be set in the config file: it is set by `codex resume`.
+    pub resume_session_id: Option<Uuid>,
+
+    /// Maximum number of tokens (input + output, as reported by the provider)
+    /// a single session may consume. Codex warns at 80% of the budget and
+    /// refuses further turns once it is exhausted. `None` means unlimited.
+    pub max_session_tokens: Option<u64>,
+
+    /// Size of the model's context window in tokens. Used to decide when to
+    /// compact the conversation; defaults to the known window of `model`.
+    pub model_context_window: Option<u64>,
 }
 
 impl Config {
@@ -169,6 +293,33 @@ impl Config {
     }
 }
 
+/// The `account` of the nearest project `.codex/config.toml` (see
+/// [`find_project_config`]), so that a repository is always worked on with
+/// the same one of the user's logins. The project is not trusted to point
+/// at credentials, so only an account the user has already logged in to is
+/// taken; any other name is ignored.
+fn load_project_account(cwd: &Path, codex_home: &Path) -> std::io::Result<Option<String>> {
+    let Some(path) = find_project_config(cwd, codex_home) else {
+        return Ok(None);
+    };
+    let contents = std::fs::read_to_string(&path)?;
+    let value = toml::from_str::<TomlValue>(&contents).map_err(|e| {
+        tracing::error!("Failed to parse {}: {e}", path.display());
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    })?;
+    let Some(account) = value.get("account").and_then(TomlValue::as_str) else {
+        return Ok(None);
+    };
+    if !list_accounts(codex_home).iter().any(|a| a == account) {
+        tracing::warn!(
+            "Ignoring account `{account}` of {}: not a stored login",
+            path.display()
+        );
+        return Ok(None);
+    }
+    Ok(Some(account.to_string()))
+}
+
 /// Read `CODEX_HOME/config.toml` and return it as a generic TOML value. Returns
 /// an empty TOML table when the file does not exist.
 fn load_config_as_toml(codex_home: &Path) -> std::io::Result<TomlValue> {
@@ -251,6 +402,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub auto_allow: Vec<AutoAllowPredicate>,
 
+    /// Glob/regex rules that pre-approve or reject shell commands.
+    #[serde(default)]
+    pub approval_rules: Vec<ApprovalRule>,
+
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
@@ -276,6 +431,16 @@ pub struct ConfigToml {
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Built-in tools to disable and MCP servers to limit to some tools.
+    pub tools: Option<ToolsConfig>,
+
+    /// Record every tool call in `~/.codex/logs/tool_audit.jsonl`.
+    pub tool_audit_log: Option<bool>,
+
+    /// Mask the values of secret-looking environment variables in tool
+    /// output.
+    pub mask_env_secrets: Option<bool>,
+
     /// User-defined provider entries that extend/override the built-in list.
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
@@ -283,6 +448,10 @@ pub struct ConfigToml {
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
+    /// When `true`, the `allow_shell` of a project's custom prompts is
+    /// honored. Defaults to `false`.
+    pub allow_project_prompt_shell: Option<bool>,
+
     /// Profile to use from the `profiles` map.
     pub profile: Option<String>,
 
@@ -290,6 +459,16 @@ pub struct ConfigToml {
     #[serde(default)]
     pub profiles: HashMap<String, ConfigProfile>,
 
+    /// Named login to use instead of `auth.json`; a project's
+    /// `.codex/config.toml` may set it too.
+    pub account: Option<String>,
+
+    /// OpenAI organization (`org-…`) to bill, for logins in several.
+    pub org_id: Option<String>,
+
+    /// OpenAI project (`proj_…`) within `org_id` to bill.
+    pub workspace_id: Option<String>,
+
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     #[serde(default)]
     pub history: Option<History>,
@@ -301,12 +480,62 @@ pub struct ConfigToml {
     /// Collection of settings that are specific to the TUI.
     pub tui: Option<Tui>,
 
+    /// Mechanism used to isolate sandboxed shell commands.
+    pub sandbox_backend: Option<SandboxBackendKind>,
+
+    /// Settings for the container sandbox backend.
+    pub container_sandbox: Option<ContainerSandboxConfig>,
+
+    /// Timeout in seconds for shell commands that do not specify one.
+    pub exec_timeout_sec: Option<u64>,
+
+    /// Bytes of stdout and of stderr kept per shell command.
+    pub max_output_bytes: Option<usize>,
+
     /// When set to `true`, `AgentReasoning` events will be hidden from the
     /// UI/output. Defaults to `false`.
     pub hide_agent_reasoning: Option<bool>,
 
     pub model_reasoning_effort: Option<ReasoningEffort>,
     pub model_reasoning_summary: Option<ReasoningSummary>,
+
+    /// Hard limit on the tokens a single session may consume.
+    pub max_session_tokens: Option<u64>,
+
+    /// Override the context window size of the configured model.
+    pub model_context_window: Option<u64>,
+
+    /// USD prices per million tokens by model name, for models without a
+    /// built-in price or to override it.
+    #[serde(default)]
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// Remapped TUI key bindings: action name -> key(s).
+    #[serde(default)]
+    pub keys: HashMap<String, KeyBinding>,
+
+    /// Extra models to offer in the TUI's `/model` picker.
+    #[serde(default)]
+    pub models: Vec<String>,
+
+    /// `cache_control` breakpoints for providers with explicit caching.
+    #[serde(default)]
+    pub prompt_caching: PromptCaching,
+
+    /// The `web_search` tool.
+    #[serde(default)]
+    pub web_search: WebSearchConfig,
+
+    /// `sampling/createMessage` requests to `codex mcp serve`.
+    #[serde(default)]
+    pub mcp_sampling: McpSamplingConfig,
+
+    /// Masking of secrets in tool output and messages.
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    /// OpenTelemetry collector to export traces and metrics to.
+    pub otel: Option<OtelConfig>,
 }
 
 fn deserialize_sandbox_permissions<'de, D>(
@@ -368,10 +597,11 @@ impl Config {
             codex_linux_sandbox_exe,
         } = overrides;
 
-        let config_profile = match config_profile_key.or(cfg.profile) {
+        let active_profile = config_profile_key.or(cfg.profile);
+        let config_profile = match &active_profile {
             Some(key) => cfg
                 .profiles
-                .get(&key)
+                .get(key)
                 .ok_or_else(|| {
                     std::io::Error::new(
                         std::io::ErrorKind::NotFound,
@@ -403,6 +633,13 @@ impl Config {
             model_providers.entry(key).or_insert(provider);
         }
 
+        let defaulted = DefaultedSettings {
+            model: model.is_none() && config_profile.model.is_none() && cfg.model.is_none(),
+            model_provider: model_provider.is_none()
+                && config_profile.model_provider.is_none()
+                && cfg.model_provider.is_none(),
+        };
+
         let model_provider_id = model_provider
             .or(config_profile.model_provider)
             .or(cfg.model_provider)
@@ -440,11 +677,33 @@ impl Config {
 
         let history = cfg.history.unwrap_or_default();
 
+        let mut tools = cfg.tools.unwrap_or_default();
+        if let Some(project_tools) = load_project_tools_config(&resolved_cwd, &codex_home)? {
+            tools.restrict(project_tools);
+        }
+
+        let account = match config_profile.account {
+            Some(account) => Some(account),
+            None => load_project_account(&resolved_cwd, &codex_home)?.or(cfg.account),
+        };
+        if let Some(account) = account
+            .as_deref()
+            .filter(|account| !is_valid_account_name(account))
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "invalid account `{account}`: use letters, digits, `-`, `_` and `.`, and not `{DEFAULT_ACCOUNT}`"
+                ),
+            ));
+        }
+
+        let model = model
+            .or(config_profile.model)
+            .or(cfg.model)
+            .unwrap_or_else(default_model);
         let config = Self {
-            model: model
-                .or(config_profile.model)
-                .or(cfg.model)
-                .unwrap_or_else(default_model),
+            model,
             model_provider_id,
             model_provider,
             cwd: resolved_cwd,
@@ -452,7 +711,9 @@ impl Config {
                 .or(config_profile.approval_policy)
                 .or(cfg.approval_policy)
                 .unwrap_or_else(AskForApproval::default),
+            defaulted,
             auto_allow: config_profile.auto_allow.unwrap_or(cfg.auto_allow),
+            approval_rules: cfg.approval_rules,
             sandbox_policy,
             shell_environment_policy,
             disable_response_storage: config_profile
@@ -462,21 +723,54 @@ impl Config {
             notify: cfg.notify,
             instructions,
             mcp_servers: cfg.mcp_servers,
+            tools,
+            tool_audit_log: cfg.tool_audit_log.unwrap_or(false),
+            mask_env_secrets: cfg.mask_env_secrets.unwrap_or(false),
+            model_pricing: cfg.model_pricing,
+            keys: cfg.keys,
+            models: cfg.models,
+            prompt_caching: cfg.prompt_caching,
+            web_search: cfg.web_search,
+            mcp_sampling: cfg.mcp_sampling,
+            redaction: cfg.redaction,
+            otel: cfg.otel,
+            active_profile,
+            profiles: cfg.profiles,
+            account,
+            org_id: config_profile.org_id.or(cfg.org_id),
+            workspace_id: config_profile.workspace_id.or(cfg.workspace_id),
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
+            allow_project_prompt_shell: cfg.allow_project_prompt_shell.unwrap_or(false),
             codex_home,
             history,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             tui: cfg.tui.unwrap_or_default(),
+            sandbox_backend: cfg.sandbox_backend.unwrap_or_default(),
+            container_sandbox: cfg.container_sandbox.unwrap_or_default(),
+            exec_timeout_sec: cfg.exec_timeout_sec,
+            max_output_bytes: cfg.max_output_bytes,
             codex_linux_sandbox_exe,
 
             hide_agent_reasoning: cfg.hide_agent_reasoning.unwrap_or(false),
             model_reasoning_effort: cfg.model_reasoning_effort.unwrap_or_default(),
             model_reasoning_summary: cfg.model_reasoning_summary.unwrap_or_default(),
+            resume_session_id: None,
+            max_session_tokens: config_profile.max_session_tokens.or(cfg.max_session_tokens),
+            model_context_window: cfg.model_context_window,
         };
         Ok(config)
     }
 
+    /// Prices of `model`: the `[model_pricing]` entry if there is one,
+    /// otherwise the built-in price. `None` if the model has no known price.
+    pub fn model_pricing_for(&self, model: &str) -> Option<ModelPricing> {
+        self.model_pricing
+            .get(model)
+            .copied()
+            .or_else(|| builtin_model_pricing(model))
+    }
+
     fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
         let mut p = match codex_dir {
             Some(p) => p.to_path_buf(),
@@ -530,7 +824,7 @@ pub fn find_codex_home() -> std::io::Result<PathBuf> {
 /// that the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
     let mut p = cfg.codex_home.clone();
-    p.push("log");
+    p.push("logs");
     Ok(p)
 }
 
@@ -657,6 +951,26 @@ persistence = "none"
         );
     }
 
+    #[test]
+    fn otel_table_defaults_the_service_name() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[otel]
+endpoint = "http://localhost:4318"
+headers = { "x-api-key" = "k" }
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        assert_eq!(
+            cfg.otel,
+            Some(OtelConfig {
+                endpoint: "http://localhost:4318".to_string(),
+                headers: HashMap::from([("x-api-key".to_string(), "k".to_string())]),
+                service_name: "codex".to_string(),
+            })
+        );
+    }
+
     /// Deserializing a TOML string containing an *invalid* permission should
     /// fail with a helpful error rather than silently defaulting or
     /// succeeding.
@@ -673,6 +987,117 @@ persistence = "none"
         assert!(msg.contains("not-a-real-permission"));
     }
 
+    #[test]
+    fn project_config_picks_the_account() {
+        let repo = TempDir::new().unwrap();
+        let codex_home = TempDir::new().unwrap();
+        std::fs::create_dir(repo.path().join(".git")).unwrap();
+        assert_eq!(
+            load_project_account(repo.path(), codex_home.path()).unwrap(),
+            None
+        );
+
+        std::fs::create_dir(repo.path().join(".codex")).unwrap();
+        std::fs::write(
+            repo.path().join(".codex").join("config.toml"),
+            "account = \"work\"\n",
+        )
+        .unwrap();
+        // A project can only pick a login the user already has.
+        assert_eq!(
+            load_project_account(repo.path(), codex_home.path()).unwrap(),
+            None
+        );
+        std::fs::create_dir(codex_home.path().join("auth")).unwrap();
+        std::fs::write(codex_home.path().join("auth").join("work.json"), "{}").unwrap();
+        assert_eq!(
+            load_project_account(repo.path(), codex_home.path()).unwrap(),
+            Some("work".to_string())
+        );
+
+        std::fs::write(
+            repo.path().join(".codex").join("config.toml"),
+            "account = \"/repo/creds\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_project_account(repo.path(), codex_home.path()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_account_is_rejected() {
+        let codex_home = TempDir::new().unwrap();
+        let cfg = ConfigToml {
+            account: Some("../auth".to_string()),
+            ..Default::default()
+        };
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(codex_home.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn defaulted_settings_are_the_unset_ones() {
+        let codex_home = TempDir::new().unwrap();
+        let cfg = ConfigToml {
+            model_provider: Some("openai".to_string()),
+            ..Default::default()
+        };
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(codex_home.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.defaulted,
+            DefaultedSettings {
+                model: true,
+                model_provider: false,
+            }
+        );
+    }
+
+    #[test]
+    fn pricing_is_looked_up_for_any_model() {
+        let codex_home = TempDir::new().unwrap();
+        let local = ModelPricing {
+            input: 0.5,
+            cached_input: None,
+            output: 1.0,
+        };
+        let cfg = ConfigToml {
+            model: Some("o3".to_string()),
+            model_pricing: HashMap::from([("my-local-model".to_string(), local)]),
+            ..Default::default()
+        };
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(codex_home.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )
+        .unwrap();
+        // A session may run another model than the configured one.
+        assert_eq!(config.model_pricing_for("my-local-model"), Some(local));
+        assert_eq!(config.model_pricing_for("o3"), builtin_model_pricing("o3"));
+        assert_eq!(config.model_pricing_for("unknown-model"), None);
+    }
+
     struct PrecedenceTestFixture {
         cwd: TempDir,
         codex_home: TempDir,
@@ -742,6 +1167,7 @@ disable_response_storage = true
             base_url: "https://api.openai.com/v1".to_string(),
             env_key: Some("OPENAI_API_KEY".to_string()),
             wire_api: crate::WireApi::Chat,
+            reasoning_summaries: None,
             env_key_instructions: None,
         };
         let model_provider_map = {
@@ -800,7 +1226,9 @@ disable_response_storage = true
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
                 approval_policy: AskForApproval::Never,
+                defaulted: DefaultedSettings::default(),
                 auto_allow: Vec::new(),
+                approval_rules: Vec::new(),
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 disable_response_storage: false,
@@ -808,16 +1236,40 @@ disable_response_storage = true
                 notify: None,
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
+                tools: ToolsConfig::default(),
+                tool_audit_log: false,
+                mask_env_secrets: false,
+                model_pricing: HashMap::new(),
+                keys: HashMap::new(),
+                models: Vec::new(),
+                prompt_caching: PromptCaching::default(),
+                web_search: WebSearchConfig::default(),
+                mcp_sampling: McpSamplingConfig::default(),
+                redaction: RedactionConfig::default(),
+                otel: None,
+                active_profile: Some("o3".to_string()),
+                profiles: fixture.cfg.profiles.clone(),
+                account: None,
+                org_id: None,
+                workspace_id: None,
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+                allow_project_prompt_shell: false,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 tui: Tui::default(),
+                sandbox_backend: SandboxBackendKind::default(),
+                container_sandbox: ContainerSandboxConfig::default(),
+                exec_timeout_sec: None,
+                max_output_bytes: None,
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
                 model_reasoning_effort: ReasoningEffort::default(),
                 model_reasoning_summary: ReasoningSummary::default(),
+                resume_session_id: None,
+                max_session_tokens: None,
+                model_context_window: None,
             },
             o3_profile_config
         );
@@ -843,7 +1295,9 @@ disable_response_storage = true
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
             approval_policy: AskForApproval::UnlessAllowListed,
+            defaulted: DefaultedSettings::default(),
             auto_allow: Vec::new(),
+            approval_rules: Vec::new(),
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             disable_response_storage: false,
@@ -851,16 +1305,40 @@ disable_response_storage = true
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            tools: ToolsConfig::default(),
+            tool_audit_log: false,
+            mask_env_secrets: false,
+            model_pricing: HashMap::new(),
+            keys: HashMap::new(),
+            models: Vec::new(),
+            prompt_caching: PromptCaching::default(),
+            web_search: WebSearchConfig::default(),
+            mcp_sampling: McpSamplingConfig::default(),
+            redaction: RedactionConfig::default(),
+            otel: None,
+            active_profile: Some("gpt3".to_string()),
+            profiles: fixture.cfg.profiles.clone(),
+            account: None,
+            org_id: None,
+            workspace_id: None,
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            allow_project_prompt_shell: false,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            sandbox_backend: SandboxBackendKind::default(),
+            container_sandbox: ContainerSandboxConfig::default(),
+            exec_timeout_sec: None,
+            max_output_bytes: None,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             model_reasoning_effort: ReasoningEffort::default(),
             model_reasoning_summary: ReasoningSummary::default(),
+            resume_session_id: None,
+            max_session_tokens: None,
+            model_context_window: None,
         };
 
         assert_eq!(expected_gpt3_profile_config, gpt3_profile_config);
@@ -901,7 +1379,9 @@ disable_response_storage = true
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
+            defaulted: DefaultedSettings::default(),
             auto_allow: Vec::new(),
+            approval_rules: Vec::new(),
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             disable_response_storage: true,
@@ -909,16 +1389,40 @@ disable_response_storage = true
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            tools: ToolsConfig::default(),
+            tool_audit_log: false,
+            mask_env_secrets: false,
+            model_pricing: HashMap::new(),
+            keys: HashMap::new(),
+            models: Vec::new(),
+            prompt_caching: PromptCaching::default(),
+            web_search: WebSearchConfig::default(),
+            mcp_sampling: McpSamplingConfig::default(),
+            redaction: RedactionConfig::default(),
+            otel: None,
+            active_profile: Some("zdr".to_string()),
+            profiles: fixture.cfg.profiles.clone(),
+            account: None,
+            org_id: None,
+            workspace_id: None,
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            allow_project_prompt_shell: false,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            sandbox_backend: SandboxBackendKind::default(),
+            container_sandbox: ContainerSandboxConfig::default(),
+            exec_timeout_sec: None,
+            max_output_bytes: None,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             model_reasoning_effort: ReasoningEffort::default(),
             model_reasoning_summary: ReasoningSummary::default(),
+            resume_session_id: None,
+            max_session_tokens: None,
+            model_context_window: None,
         };
 
         assert_eq!(expected_zdr_profile_config, zdr_profile_config);
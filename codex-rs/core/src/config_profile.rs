@@ -15,4 +15,12 @@ pub struct ConfigProfile {
     pub disable_response_storage: Option<bool>,
     /// External predicate scripts for auto-approval or rejection of shell commands.
     pub auto_allow: Option<Vec<AutoAllowPredicate>>,
+    /// Hard limit on the tokens a single session may consume.
+    pub max_session_tokens: Option<u64>,
+    /// Named login to use instead of `auth.json`.
+    pub account: Option<String>,
+    /// OpenAI organization to bill.
+    pub org_id: Option<String>,
+    /// OpenAI project within `org_id` to bill.
+    pub workspace_id: Option<String>,
 }
@@ -5,6 +5,7 @@
 
 use std::collections::HashMap;
 use strum_macros::Display;
+use strum_macros::EnumString;
 use wildmatch::WildMatchPattern;
 
 use serde::Deserialize;
@@ -12,6 +13,7 @@ use serde::Serialize;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
+    #[serde(default)]
     pub command: String,
 
     #[serde(default)]
@@ -19,6 +21,46 @@ pub struct McpServerConfig {
 
     #[serde(default)]
     pub env: Option<HashMap<String, String>>,
+
+    /// Endpoint of a `streamable_http` server. Credentials obtained with
+    /// `codex mcp login` for this server are handed to `command` (typically a
+    /// stdio bridge) through the `MCP_ACCESS_TOKEN` environment variable.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// When false the server is kept in config.toml but not started.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Seconds the server gets to start and list its tools before the
+    /// session goes on without it (default 30).
+    #[serde(default)]
+    pub startup_timeout_sec: Option<u64>,
+
+    /// Times the server is restarted when it exits mid-session (default 3;
+    /// 0 disables restarts).
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+
+    /// Prefix of the names under which the server's tools are offered to the
+    /// model (`<prefix>__<tool>`) instead of the server name. Empty offers
+    /// the tools under their own names.
+    #[serde(default)]
+    pub tool_prefix: Option<String>,
+
+    /// Tool name -> the full name to offer it under instead.
+    #[serde(default)]
+    pub tool_aliases: HashMap<String, String>,
+
+    /// Offer the server's tools in strict mode, so the model's arguments
+    /// always match their input schemas. Tools with optional arguments are
+    /// offered normally.
+    #[serde(default)]
+    pub strict_tools: bool,
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -58,8 +100,8 @@ pub struct History {
     /// If true, history entries will not be written to disk.
     pub persistence: HistoryPersistence,
 
-    /// If set, the maximum size of the history file in bytes.
-    /// TODO(mbolin): Not currently honored.
+    /// If set, the maximum size of the history file in bytes. The oldest
+    /// entries are dropped once an append would exceed it.
     pub max_bytes: Option<usize>,
 }
 
@@ -73,6 +115,185 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Which mechanism isolates sandboxed shell commands.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxBackendKind {
+    /// Seatbelt on macOS, Landlock+seccomp on Linux.
+    #[default]
+    Platform,
+    /// Run commands in a throwaway Docker/Podman container with only the
+    /// writable roots mounted. Useful where the platform sandbox is not
+    /// available (e.g. kernels without Landlock).
+    Container,
+}
+
+/// Settings for `sandbox_backend = "container"`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ContainerSandboxConfig {
+    /// Container engine executable, e.g. `docker` or `podman`.
+    #[serde(default = "default_container_engine")]
+    pub engine: String,
+
+    /// Image the commands run in. It must contain the tools the model is
+    /// expected to use (shell, compilers, ...).
+    #[serde(default = "default_container_image")]
+    pub image: String,
+}
+
+fn default_container_engine() -> String {
+    "docker".to_string()
+}
+
+fn default_container_image() -> String {
+    "ubuntu:24.04".to_string()
+}
+
+impl Default for ContainerSandboxConfig {
+    fn default() -> Self {
+        Self {
+            engine: default_container_engine(),
+            image: default_container_image(),
+        }
+    }
+}
+
+/// Restrictions on the tools offered to the model, from the `[tools]` table
+/// of `~/.codex/config.toml` or of a project's `.codex/config.toml`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ToolsConfig {
+    /// Built-in tools that are not offered, e.g. `["exec_background"]`.
+    #[serde(default)]
+    pub disabled: Vec<String>,
+
+    /// MCP server name -> the only tools of that server that are offered.
+    /// Servers not listed here offer all of their tools.
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, Vec<String>>,
+}
+
+/// The `web_search` tool, from the `[web_search]` table of config.toml. The
+/// tool is only offered when enabled here and the sandbox policy allows
+/// network access.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WebSearchConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub backend: WebSearchBackend,
+
+    /// Endpoint of the `searxng` (e.g. `https://searx.example.org`) or
+    /// `bing` backend. Bing defaults to the public Web Search API.
+    pub url: Option<String>,
+
+    /// Environment variable with the Bing subscription key
+    /// (default `BING_SEARCH_API_KEY`).
+    pub api_key_env: Option<String>,
+
+    /// Results returned per query (default 5).
+    pub max_results: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebSearchBackend {
+    /// The provider's own search, through the Responses API.
+    #[default]
+    Native,
+    /// A SearxNG instance with the JSON output format enabled.
+    Searxng,
+    /// The Bing Web Search API.
+    Bing,
+}
+
+/// `sampling/createMessage` requests to `codex mcp serve`, from the
+/// `[mcp_sampling]` table of config.toml. MCP clients may only use the
+/// configured model once sampling is enabled here.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct McpSamplingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Names of the clients (their `clientInfo.name`) allowed to sample.
+    /// Empty allows any client.
+    #[serde(default)]
+    pub clients: Vec<String>,
+}
+
+/// Secret redaction, from the `[redaction]` table of config.toml. When
+/// enabled, AWS keys, GitHub tokens, private keys and similar credentials are
+/// masked in tool output and messages before they reach the model or the
+/// session file.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RedactionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Additional regular expressions whose matches are masked.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// OpenTelemetry export, from the `[otel]` table of config.toml: spans of
+/// turns, model requests and tool calls, and token and approval counters are
+/// sent to an OTLP/HTTP collector. Only builds with the `otel` feature export.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct OtelConfig {
+    /// Base URL of the collector, e.g. `http://localhost:4318`; traces go to
+    /// `/v1/traces` and metrics to `/v1/metrics` below it.
+    pub endpoint: String,
+
+    /// Headers sent with every export, e.g. the API key of the backend.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// `service.name` of the exported resource.
+    #[serde(default = "default_otel_service_name")]
+    pub service_name: String,
+}
+
+fn default_otel_service_name() -> String {
+    "codex".to_string()
+}
+
+/// Prompt caching, from the `[prompt_caching]` table of config.toml. OpenAI
+/// caches prompt prefixes automatically; providers with explicit caching
+/// (Anthropic) need `cache_control` breakpoints, which these settings place.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PromptCaching {
+    /// Mark the system prompt and tool definitions as cacheable.
+    pub enabled: bool,
+    /// Also mark the conversation so far, so that each turn reuses the
+    /// previous turn's prefix.
+    pub conversation: bool,
+    /// How long cached prefixes live.
+    pub ttl: PromptCacheTtl,
+}
+
+impl Default for PromptCaching {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            conversation: true,
+            ttl: PromptCacheTtl::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Display)]
+pub enum PromptCacheTtl {
+    #[default]
+    #[serde(rename = "5m")]
+    #[strum(serialize = "5m")]
+    FiveMinutes,
+    /// Costs more to write; worth it when turns are far apart.
+    #[serde(rename = "1h")]
+    #[strum(serialize = "1h")]
+    OneHour,
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct Tui {
@@ -115,6 +336,37 @@ pub struct Tui {
     /// Defaults to the `VISUAL` or `EDITOR` environment variable, falling back to `nvim`.
     #[serde(default = "default_editor")]
     pub editor: String,
+    /// When `true`, images in agent messages that refer to local files are
+    /// displayed inline using the terminal's graphics protocol (Kitty, iTerm2
+    /// or Sixel). Otherwise only an `[image: alt] (url)` placeholder is shown.
+    #[serde(default)]
+    pub inline_images: bool,
+
+    /// When `true`, a dim line with the turn's token usage and wall time is
+    /// added to the history after each turn.
+    #[serde(default)]
+    pub show_turn_stats: bool,
+
+    /// When `true` (the default), a line below the composer shows the
+    /// model, the sandbox, the time spent on the turn and how much of the
+    /// context window is left.
+    #[serde(default = "default_status_line")]
+    pub status_line: bool,
+
+    /// How the model's reasoning is shown in the history. Sessions start
+    /// with it hidden if the top-level `hide_agent_reasoning` is set.
+    #[serde(default)]
+    pub reasoning_display: ReasoningDisplay,
+
+    /// How to alert the user when an approval is required or a task
+    /// completes while the terminal window is not focused.
+    #[serde(default)]
+    pub notifications: NotificationMethod,
+    /// Command spawned for `notifications = "command"`. The notification text
+    /// is appended as the last argument.
+    #[serde(default)]
+    pub notify_command: Option<Vec<String>>,
+
     /// Require two consecutive Ctrl+D keystrokes to exit the TUI when enabled.
     #[serde(default)]
     pub require_double_ctrl_d: bool,
@@ -123,10 +375,61 @@ pub struct Tui {
     pub double_ctrl_d_timeout_secs: u64,
 }
 
+/// Key(s) bound to a TUI action in the `[keys]` table, e.g.
+/// `interrupt = "ctrl-c"` or `scroll_up = ["up", "k"]`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBinding {
+    pub fn keys(&self) -> &[String] {
+        match self {
+            KeyBinding::One(key) => std::slice::from_ref(key),
+            KeyBinding::Many(keys) => keys,
+        }
+    }
+}
+
+/// How the TUI shows the model's reasoning, also set with `/reasoning`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ReasoningDisplay {
+    /// The full text.
+    #[default]
+    Show,
+    /// Nothing.
+    Hide,
+    /// One line with the reasoning's heading, expanded with ctrl-o.
+    Summary,
+}
+
+/// How the TUI alerts an unfocused terminal.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationMethod {
+    #[default]
+    Off,
+    /// Desktop notification via the OSC 9 escape sequence (iTerm2, kitty,
+    /// WezTerm, Windows Terminal, ...).
+    Osc9,
+    /// The terminal bell.
+    Bell,
+    /// Spawn `tui.notify_command`.
+    Command,
+}
+
 fn default_composer_max_rows() -> usize {
     10
 }
 
+fn default_status_line() -> bool {
+    true
+}
+
 /// Default editor: `$VISUAL`, then `$EDITOR`, falling back to `nvim`.
 fn default_editor() -> String {
     std::env::var("VISUAL")
@@ -147,6 +450,12 @@ impl Default for Tui {
             header_compact: Default::default(),
             message_spacing: Default::default(),
             sender_break_line: Default::default(),
+            inline_images: Default::default(),
+            show_turn_stats: Default::default(),
+            status_line: default_status_line(),
+            reasoning_display: Default::default(),
+            notifications: Default::default(),
+            notify_command: None,
             composer_max_rows: default_composer_max_rows(),
             editor: default_editor(),
             require_double_ctrl_d: false,
@@ -0,0 +1,86 @@
+//! Crash reports: when Codex panics, what happened is written to
+//! `~/.codex/crash/` so it survives the terminal being reset and can be
+//! attached to a bug report.
+
+use std::backtrace::Backtrace;
+use std::path::Path;
+use std::path::PathBuf;
+
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+
+/// Directory in CODEX_HOME holding the crash reports.
+const CRASH_DIR: &str = "crash";
+
+/// Write a report of the panic described by `panic` (its message and
+/// location) on `thread` and return its path.
+pub fn write_crash_report(
+    codex_home: &Path,
+    version: &str,
+    thread: Option<&str>,
+    panic: &str,
+    backtrace: &Backtrace,
+) -> std::io::Result<PathBuf> {
+    let dir = codex_home.join(CRASH_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let now = OffsetDateTime::now_utc();
+    let stamp = now
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .unwrap_or_default();
+    // Several threads may panic within the same second.
+    let path = dir.join(format!("crash-{stamp}-{}.txt", std::process::id()));
+    let path = (1..)
+        .map(|n| match n {
+            1 => path.clone(),
+            n => path.with_extension(format!("{n}.txt")),
+        })
+        .find(|path| !path.exists())
+        .unwrap_or(path);
+
+    let report = format!(
+        "codex {version}\nos: {} {}\ntime: {}\nthread: {}\n\n{panic}\n\nbacktrace:\n{backtrace}\n",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        now.format(&Rfc3339).unwrap_or_default(),
+        thread.unwrap_or("<unnamed>"),
+    );
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reports_do_not_overwrite_each_other() {
+        let codex_home = TempDir::new().unwrap();
+        let backtrace = Backtrace::disabled();
+        let first = write_crash_report(
+            codex_home.path(),
+            "1.0.0",
+            Some("main"),
+            "panicked at src/app.rs:1:2:\nboom",
+            &backtrace,
+        )
+        .unwrap();
+        let second =
+            write_crash_report(codex_home.path(), "1.0.0", None, "boom", &backtrace).unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(
+            first.parent(),
+            Some(codex_home.path().join(CRASH_DIR).as_path())
+        );
+        let report = std::fs::read_to_string(first).unwrap();
+        assert!(report.starts_with("codex 1.0.0\n"));
+        assert!(report.contains("thread: main\n"));
+        assert!(report.contains("panicked at src/app.rs:1:2:\nboom\n"));
+    }
+}
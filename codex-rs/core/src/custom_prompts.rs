@@ -0,0 +1,707 @@
+//! Custom prompts: Markdown files in the `prompts` or `commands` directory
+//! of the project's `.codex` or of `$CODEX_HOME`, which the TUI offers as the
+//! slash command `/<file stem>` and `codex mcp serve` as MCP prompts. When
+//! several directories have a prompt of the same name, the user's prompts in
+//! `$CODEX_HOME` win over those of the project, and `prompts` over
+//! `commands`; clients give their built-in commands precedence over prompts.
+//! A prompt may start with a frontmatter block of `key: value` lines:
+//!
+//! ```markdown
+//! ---
+//! description: Review a pull request
+//! argument_hint: <pr-number>
+//! model: o3
+//! approval: on-failure
+//! ---
+//! Review pull request #$ARGUMENTS and list the problems you find.
+//! ```
+//!
+//! `model` and `approval` apply to the task the prompt starts only. A
+//! project's prompts come with the repository, so their `approval` is
+//! ignored, and their `allow_shell` only counts when the user opted in with
+//! `allow_project_prompt_shell`. With `allow_shell: true`, each `` !`command` `` in the prompt file is replaced
+//! with the output of the command, run like a `shell` tool call: subject to
+//! the approval policy and in the sandbox. Placeholders inside a command are
+//! not filled, and blocks in the arguments are not run, so what the user
+//! types never becomes part of a command. `@path` references are replaced
+//! with the contents of the files, as in messages typed by the user.
+//!
+//! The text typed after the command replaces `$ARGUMENTS`. It is also split
+//! into words with shell-style quoting: `name=value` words fill `${name}`
+//! and the others fill `$1` to `$9` in order. A prompt without placeholders
+//! gets the text appended.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::de::IntoDeserializer;
+use tracing::warn;
+
+use crate::protocol::AskForApproval;
+use crate::protocol::CustomPrompt;
+use crate::protocol::PromptScope;
+
+/// Directories of `codex_home` and of the project's `.codex` the prompts
+/// are read from, by precedence.
+const PROMPT_DIRS: [&str; 2] = ["prompts", "commands"];
+
+/// Replaced by the text typed after the command.
+const ARGUMENTS_PLACEHOLDER: &str = "$ARGUMENTS";
+
+/// Key of `$ARGUMENTS` in [`prompt_parameters`].
+const ARGUMENTS_KEY: &str = "ARGUMENTS";
+
+/// The prompts of the project of `cwd` and of `codex_home`, sorted by
+/// name. Unreadable files are skipped with a warning, and so are prompts
+/// shadowed by one of the same name in a directory that takes precedence.
+pub fn discover_prompts(codex_home: &Path, cwd: &Path) -> Vec<CustomPrompt> {
+    let mut prompts: Vec<CustomPrompt> = Vec::new();
+    for (dir, scope) in prompt_dirs(codex_home, cwd) {
+        for prompt in prompts_in(&dir, scope) {
+            match prompts.iter().find(|p| p.name == prompt.name) {
+                Some(shadowing) => warn!(
+                    "ignoring prompt {}, which is shadowed by {}",
+                    prompt.path.display(),
+                    shadowing.path.display()
+                ),
+                None => prompts.push(prompt),
+            }
+        }
+    }
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    prompts
+}
+
+/// The directories prompts are read from, by precedence, whether they
+/// exist or not.
+pub fn prompt_dirs(codex_home: &Path, cwd: &Path) -> Vec<(PathBuf, PromptScope)> {
+    let user = PROMPT_DIRS.map(|name| (codex_home.join(name), PromptScope::User));
+    let project = project_codex_dir(codex_home, cwd)
+        .into_iter()
+        .flat_map(|dir| PROMPT_DIRS.map(|name| (dir.join(name), PromptScope::Project)));
+    user.into_iter().chain(project).collect()
+}
+
+/// The nearest `.codex` directory between `cwd` and the root of its Git
+/// repository, other than `codex_home`.
+fn project_codex_dir(codex_home: &Path, cwd: &Path) -> Option<PathBuf> {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let codex_home = canonical(codex_home);
+    let mut dir = canonical(cwd);
+    loop {
+        let candidate = dir.join(".codex");
+        if candidate.is_dir() && canonical(&candidate) != codex_home {
+            return Some(candidate);
+        }
+        if dir.join(".git").exists() {
+            return None;
+        }
+        dir = dir.parent()?.to_path_buf();
+    }
+}
+
+/// The prompts in `dir`, which need not exist.
+fn prompts_in(dir: &Path, scope: PromptScope) -> Vec<CustomPrompt> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            warn!("failed to read {}: {e}", dir.display());
+            return Vec::new();
+        }
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    let mut prompt = CustomPrompt {
+                        scope,
+                        ..parse_prompt(name, &path, &text)
+                    };
+                    // A repository may not loosen the user's approval policy.
+                    if scope == PromptScope::Project && prompt.approval.take().is_some() {
+                        warn!("ignoring approval of project prompt {}", path.display());
+                    }
+                    Some(prompt)
+                }
+                Err(e) => {
+                    warn!("failed to read prompt {}: {e}", path.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// The prompt named `name` with the frontmatter of `text` applied.
+/// Unknown keys and invalid values are ignored with a warning.
+fn parse_prompt(name: String, path: &Path, text: &str) -> CustomPrompt {
+    let (frontmatter, content) = split_frontmatter(text);
+    let mut prompt = CustomPrompt {
+        name,
+        path: path.to_path_buf(),
+        scope: PromptScope::User,
+        content: content.to_string(),
+        description: None,
+        argument_hint: None,
+        model: None,
+        approval: None,
+        allow_shell: false,
+    };
+    for line in frontmatter.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            warn!("ignoring frontmatter line `{line}` of {}", path.display());
+            continue;
+        };
+        let value = unquote(value.trim()).to_string();
+        match key.trim() {
+            "description" => prompt.description = Some(value),
+            "argument_hint" | "argument-hint" => prompt.argument_hint = Some(value),
+            "model" => prompt.model = Some(value),
+            "approval" => {
+                let parsed: Result<AskForApproval, serde::de::value::Error> =
+                    AskForApproval::deserialize(value.as_str().into_deserializer());
+                match parsed {
+                    Ok(approval) => prompt.approval = Some(approval),
+                    Err(e) => warn!("ignoring approval of {}: {e}", path.display()),
+                }
+            }
+            "allow_shell" | "allow-shell" => match value.as_str() {
+                "true" => prompt.allow_shell = true,
+                "false" => prompt.allow_shell = false,
+                _ => warn!("ignoring allow_shell of {}: {value}", path.display()),
+            },
+            key => warn!(
+                "ignoring unknown frontmatter key `{key}` of {}",
+                path.display()
+            ),
+        }
+    }
+    prompt
+}
+
+/// (frontmatter, rest) of `text`; the frontmatter is empty unless the text
+/// starts with a `---` line that is closed by another.
+fn split_frontmatter(text: &str) -> (&str, &str) {
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return ("", text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (&rest[..offset], &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    ("", text)
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// A placeholder in the text of a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Placeholder {
+    /// `$ARGUMENTS`: the text typed after the command.
+    Arguments,
+    /// `$1` to `$9`.
+    Positional(usize),
+    /// `${name}`, given as `name=value`.
+    Named(String),
+}
+
+impl Placeholder {
+    /// The name of the placeholder's value in [`expand_prompt_with_values`].
+    fn key(&self) -> String {
+        match self {
+            Placeholder::Arguments => ARGUMENTS_KEY.to_string(),
+            Placeholder::Positional(n) => n.to_string(),
+            Placeholder::Named(name) => name.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Placeholder::Arguments => f.write_str(ARGUMENTS_PLACEHOLDER),
+            Placeholder::Positional(n) => write!(f, "${n}"),
+            Placeholder::Named(name) => write!(f, "${{{name}}}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(Placeholder),
+    /// The command of a `` !`command` `` block, taken verbatim.
+    Command(&'a str),
+}
+
+/// A piece of an expanded prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PromptPart {
+    Text(String),
+    /// A `` !`command` `` block of the prompt file, to be replaced with the
+    /// command's output.
+    Command(String),
+}
+
+/// The text of an expanded prompt, with its command blocks as written.
+pub(crate) fn prompt_text(parts: &[PromptPart]) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            PromptPart::Text(text) => text.clone(),
+            PromptPart::Command(command) => format!("!`{command}`"),
+        })
+        .collect()
+}
+
+/// Splits the content of `prompt` into text, placeholders and, for a prompt
+/// with `allow_shell`, command blocks. Blocks are found before anything is
+/// substituted, and placeholders inside them are left alone.
+fn parse_template(prompt: &CustomPrompt) -> Vec<Segment<'_>> {
+    let content = prompt.content.trim();
+    if !prompt.allow_shell {
+        return parse_placeholders(content);
+    }
+    let mut segments = Vec::new();
+    let mut last = 0;
+    for block in shell_blocks(content) {
+        segments.extend(parse_placeholders(&content[last..block.range.start]));
+        segments.push(Segment::Command(block.command));
+        last = block.range.end;
+    }
+    segments.extend(parse_placeholders(&content[last..]));
+    segments
+}
+
+/// Splits `content` into text and placeholders. A `$` that does not start
+/// a placeholder is kept as text.
+fn parse_placeholders(content: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut pos = 0;
+    while let Some(offset) = content[pos..].find('$') {
+        let dollar = pos + offset;
+        let rest = &content[dollar + 1..];
+        let parsed = if rest.starts_with(&ARGUMENTS_PLACEHOLDER[1..]) {
+            Some((Placeholder::Arguments, ARGUMENTS_PLACEHOLDER.len()))
+        } else if let Some(digit) = rest.chars().next().and_then(|c| c.to_digit(10)) {
+            (digit > 0).then_some((Placeholder::Positional(digit as usize), 2))
+        } else if let Some(inner) = rest.strip_prefix('{') {
+            inner
+                .find('}')
+                .map(|end| &inner[..end])
+                .filter(|name| is_placeholder_name(name))
+                .map(|name| (Placeholder::Named(name.to_string()), name.len() + 3))
+        } else {
+            None
+        };
+        match parsed {
+            Some((placeholder, len)) => {
+                if text_start < dollar {
+                    segments.push(Segment::Text(&content[text_start..dollar]));
+                }
+                segments.push(Segment::Placeholder(placeholder));
+                pos = dollar + len;
+                text_start = pos;
+            }
+            None => pos = dollar + 1,
+        }
+    }
+    if text_start < content.len() {
+        segments.push(Segment::Text(&content[text_start..]));
+    }
+    segments
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// The text of `prompt` with its placeholders filled from `arguments`, or
+/// with `arguments` appended if it has none. Fails with a message for the
+/// user if the quoting of `arguments` is unbalanced or a placeholder is
+/// left without a value.
+pub fn expand_prompt(prompt: &CustomPrompt, arguments: &str) -> Result<String, String> {
+    expand_prompt_parts(prompt, arguments).map(|parts| prompt_text(&parts))
+}
+
+/// Like [`expand_prompt`], keeping the command blocks of a prompt with
+/// `allow_shell` apart from the text.
+pub(crate) fn expand_prompt_parts(
+    prompt: &CustomPrompt,
+    arguments: &str,
+) -> Result<Vec<PromptPart>, String> {
+    let arguments = arguments.trim();
+    let segments = parse_template(prompt);
+    let mut positional = Vec::new();
+    let mut named = HashMap::new();
+    if segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Placeholder(p) if *p != Placeholder::Arguments))
+    {
+        let words = shlex::split(arguments).ok_or_else(|| {
+            format!(
+                "unbalanced quotes in the arguments of /{}: {arguments}",
+                prompt.name
+            )
+        })?;
+        for word in words {
+            // `key=value` only names an argument if the prompt has `${key}`.
+            let name = word
+                .split_once('=')
+                .map(|(key, _)| key)
+                .filter(|key| {
+                    segments.iter().any(|segment| {
+                        matches!(segment, Segment::Placeholder(Placeholder::Named(name)) if name == key)
+                    })
+                })
+                .map(str::to_string);
+            match name {
+                Some(name) => {
+                    let value = word[name.len() + 1..].to_string();
+                    named.insert(name, value);
+                }
+                None => positional.push(word),
+            }
+        }
+    }
+    render(
+        prompt,
+        &segments,
+        arguments,
+        |placeholder| match placeholder {
+            Placeholder::Arguments => Some(arguments),
+            Placeholder::Positional(n) => positional.get(n - 1).map(String::as_str),
+            Placeholder::Named(name) => named.get(name).map(String::as_str),
+        },
+    )
+}
+
+/// Like [`expand_prompt`], with the values given by the keys of
+/// [`prompt_parameters`], as clients that ask for each argument do.
+pub fn expand_prompt_with_values(
+    prompt: &CustomPrompt,
+    values: &HashMap<String, String>,
+) -> Result<String, String> {
+    let segments = parse_template(prompt);
+    let arguments = values
+        .get(ARGUMENTS_KEY)
+        .map(|arguments| arguments.trim())
+        .unwrap_or_default();
+    render(
+        prompt,
+        &segments,
+        arguments,
+        |placeholder| match placeholder {
+            Placeholder::Arguments => Some(arguments),
+            placeholder => values.get(&placeholder.key()).map(String::as_str),
+        },
+    )
+    .map(|parts| prompt_text(&parts))
+}
+
+/// The arguments `prompt` takes, as (key, required) in the order they
+/// appear: `ARGUMENTS` for `$ARGUMENTS` or a prompt without placeholders,
+/// `1` to `9` for `$1` to `$9`, and the name of `${name}`.
+pub fn prompt_parameters(prompt: &CustomPrompt) -> Vec<(String, bool)> {
+    let mut parameters: Vec<(String, bool)> = Vec::new();
+    for segment in parse_template(prompt) {
+        let Segment::Placeholder(placeholder) = segment else {
+            continue;
+        };
+        let key = placeholder.key();
+        if !parameters.iter().any(|(k, _)| *k == key) {
+            parameters.push((key, placeholder != Placeholder::Arguments));
+        }
+    }
+    if parameters.is_empty() {
+        parameters.push((ARGUMENTS_KEY.to_string(), false));
+    }
+    parameters
+}
+
+/// `segments` with each placeholder replaced by its `value`, or `arguments`
+/// appended if there are none.
+fn render<'a>(
+    prompt: &CustomPrompt,
+    segments: &[Segment<'a>],
+    arguments: &str,
+    value: impl Fn(&Placeholder) -> Option<&'a str>,
+) -> Result<Vec<PromptPart>, String> {
+    let has_placeholders = segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Placeholder(_)));
+
+    let mut missing: Vec<String> = Vec::new();
+    let mut parts = Vec::new();
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => push_text(&mut parts, text),
+            Segment::Command(command) => parts.push(PromptPart::Command(command.to_string())),
+            Segment::Placeholder(placeholder) => match value(placeholder) {
+                Some(value) => push_text(&mut parts, value),
+                None => {
+                    let placeholder = placeholder.to_string();
+                    if !missing.contains(&placeholder) {
+                        missing.push(placeholder);
+                    }
+                }
+            },
+        }
+    }
+    if !missing.is_empty() {
+        let mut message = format!(
+            "/{} is missing arguments for {}",
+            prompt.name,
+            missing.join(", ")
+        );
+        if let Some(hint) = &prompt.argument_hint {
+            message.push_str(&format!(" (usage: /{} {hint})", prompt.name));
+        }
+        return Err(message);
+    }
+    if !has_placeholders && !arguments.is_empty() {
+        push_text(&mut parts, &format!("\n\n{arguments}"));
+    }
+    Ok(parts)
+}
+
+fn push_text(parts: &mut Vec<PromptPart>, text: &str) {
+    match parts.last_mut() {
+        Some(PromptPart::Text(last)) => last.push_str(text),
+        _ => parts.push(PromptPart::Text(text.to_string())),
+    }
+}
+
+/// A `` !`command` `` block of a prompt file.
+#[derive(Debug, PartialEq, Eq)]
+struct ShellBlock<'a> {
+    /// Byte range of the whole block in the prompt.
+    range: Range<usize>,
+    command: &'a str,
+}
+
+/// The `` !`command` `` blocks of `text`, in order. Blocks with an empty
+/// command are left alone.
+fn shell_blocks(text: &str) -> Vec<ShellBlock<'_>> {
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find("!`") {
+        let start = pos + offset;
+        let command_start = start + 2;
+        let Some(len) = text[command_start..].find('`') else {
+            break;
+        };
+        let end = command_start + len + 1;
+        let command = text[command_start..end - 1].trim();
+        if !command.is_empty() {
+            blocks.push(ShellBlock {
+                range: start..end,
+                command,
+            });
+        }
+        pos = end;
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn parses_frontmatter() {
+        let text = "---\ndescription: Review a pull request\nargument-hint: \"<pr-number>\"\nmodel: o3\napproval: never\nunknown: 1\n---\nReview #$ARGUMENTS.\n";
+        let prompt = parse_prompt("review-pr".to_string(), Path::new("review-pr.md"), text);
+        assert_eq!(prompt.description.as_deref(), Some("Review a pull request"));
+        assert_eq!(prompt.argument_hint.as_deref(), Some("<pr-number>"));
+        assert_eq!(prompt.model.as_deref(), Some("o3"));
+        assert_eq!(prompt.approval, Some(AskForApproval::Never));
+        assert!(!prompt.allow_shell);
+        assert_eq!(prompt.content, "Review #$ARGUMENTS.\n");
+        assert_eq!(expand_prompt(&prompt, " 12 ").unwrap(), "Review #12.");
+    }
+
+    #[test]
+    fn prompts_without_frontmatter_are_used_verbatim() {
+        let text = "Explain this.\n---\nnot frontmatter\n";
+        let prompt = parse_prompt("explain".to_string(), Path::new("explain.md"), text);
+        assert_eq!(prompt.description, None);
+        assert_eq!(prompt.content, text);
+        assert_eq!(
+            expand_prompt(&prompt, "src/lib.rs").unwrap(),
+            "Explain this.\n---\nnot frontmatter\n\nsrc/lib.rs"
+        );
+    }
+
+    #[test]
+    fn fills_positional_and_named_placeholders() {
+        let text = "Compare $1 with ${base} ($2, $ 3, $x, $0): $ARGUMENTS";
+        let prompt = parse_prompt("compare".to_string(), Path::new("compare.md"), text);
+        assert_eq!(
+            expand_prompt(&prompt, r#"src/a.rs base=main "two words" x y z"#).unwrap(),
+            r#"Compare src/a.rs with main (two words, $ 3, $x, $0): src/a.rs base=main "two words" x y z"#
+        );
+    }
+
+    #[test]
+    fn reports_missing_placeholders() {
+        let text =
+            "---\nargument_hint: <file> [base=<ref>]\n---\nCompare $1 with ${base} and $1 again.";
+        let prompt = parse_prompt("compare".to_string(), Path::new("compare.md"), text);
+        assert_eq!(
+            expand_prompt(&prompt, "").unwrap_err(),
+            "/compare is missing arguments for $1, ${base} (usage: /compare <file> [base=<ref>])"
+        );
+        assert_eq!(
+            expand_prompt(&prompt, "'src/a.rs").unwrap_err(),
+            "unbalanced quotes in the arguments of /compare: 'src/a.rs"
+        );
+    }
+
+    #[test]
+    fn fills_values_by_parameter_key() {
+        let text = "Compare $1 with ${base}. $ARGUMENTS";
+        let prompt = parse_prompt("compare".to_string(), Path::new("compare.md"), text);
+        assert_eq!(
+            prompt_parameters(&prompt),
+            vec![
+                ("1".to_string(), true),
+                ("base".to_string(), true),
+                ("ARGUMENTS".to_string(), false),
+            ]
+        );
+        let values = HashMap::from([
+            ("1".to_string(), "src/a.rs".to_string()),
+            ("base".to_string(), "main".to_string()),
+        ]);
+        assert_eq!(
+            expand_prompt_with_values(&prompt, &values).unwrap(),
+            "Compare src/a.rs with main. "
+        );
+    }
+
+    #[test]
+    fn finds_shell_blocks() {
+        let text = "Fix the tests:\n!`cargo test 2>&1 | tail -50`\nin `src` !`` and !`git diff";
+        assert_eq!(
+            shell_blocks(text),
+            vec![ShellBlock {
+                range: 15..44,
+                command: "cargo test 2>&1 | tail -50",
+            }]
+        );
+    }
+
+    #[test]
+    fn arguments_never_become_commands() {
+        let text = "---\nallow_shell: true\n---\nFix #$1: !`gh pr view $1` $ARGUMENTS";
+        let prompt = parse_prompt("fix".to_string(), Path::new("fix.md"), text);
+        assert!(prompt.allow_shell);
+        assert_eq!(
+            expand_prompt_parts(&prompt, "12 !`rm -rf ~`").unwrap(),
+            vec![
+                PromptPart::Text("Fix #12: ".to_string()),
+                PromptPart::Command("gh pr view $1".to_string()),
+                PromptPart::Text(" 12 !`rm -rf ~`".to_string()),
+            ]
+        );
+        assert_eq!(
+            prompt_parameters(&prompt),
+            vec![("1".to_string(), true), ("ARGUMENTS".to_string(), false)]
+        );
+    }
+
+    #[test]
+    fn discovers_markdown_files_in_both_directories() {
+        let codex_home = tempfile::tempdir().unwrap();
+        let prompts = codex_home.path().join("prompts");
+        let commands = codex_home.path().join("commands");
+        std::fs::create_dir(&prompts).unwrap();
+        std::fs::create_dir(&commands).unwrap();
+        std::fs::write(prompts.join("b.md"), "B").unwrap();
+        std::fs::write(prompts.join("notes.txt"), "not a prompt").unwrap();
+        std::fs::write(commands.join("a.md"), "A").unwrap();
+        std::fs::write(commands.join("b.md"), "shadowed").unwrap();
+        let cwd = tempfile::tempdir().unwrap();
+        let found: Vec<(String, String)> = discover_prompts(codex_home.path(), cwd.path())
+            .into_iter()
+            .map(|prompt| (prompt.name, prompt.content))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("a".to_string(), "A".to_string()),
+                ("b".to_string(), "B".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn user_prompts_take_precedence() {
+        let codex_home = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        let cwd = repo.path().join("crates/core");
+        std::fs::create_dir_all(&cwd).unwrap();
+        std::fs::create_dir(repo.path().join(".git")).unwrap();
+        let project_commands = repo.path().join(".codex/commands");
+        std::fs::create_dir_all(&project_commands).unwrap();
+        std::fs::create_dir(codex_home.path().join("prompts")).unwrap();
+        std::fs::write(project_commands.join("lint.md"), "project lint").unwrap();
+        std::fs::write(
+            project_commands.join("deploy.md"),
+            "---\napproval: never\nallow_shell: true\n---\ndeploy",
+        )
+        .unwrap();
+        std::fs::write(codex_home.path().join("prompts/lint.md"), "user lint").unwrap();
+        let found = discover_prompts(codex_home.path(), &cwd);
+        let summary: Vec<(&str, PromptScope, &str)> = found
+            .iter()
+            .map(|prompt| (prompt.name.as_str(), prompt.scope, prompt.content.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("deploy", PromptScope::Project, "deploy"),
+                ("lint", PromptScope::User, "user lint"),
+            ]
+        );
+        // Project prompts cannot change the approval policy.
+        assert_eq!(found[0].approval, None);
+        assert!(found[0].allow_shell);
+    }
+}
@@ -0,0 +1,263 @@
+//! The TUI's debug log and the bundle `/debug export` writes for bug reports.
+//!
+//! Each TUI session logs to its own `~/.codex/logs/codex-tui-<time>-<pid>.log`,
+//! so several sessions running at once never write to (or rotate away) each
+//! other's log. At startup all but the newest [`KEPT_LOGS`] logs are deleted.
+//! A debug bundle is a zip of the current session's log, the config.toml
+//! with its secrets redacted and the version information.
+
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use time::OffsetDateTime;
+use time::macros::format_description;
+use toml::Value as TomlValue;
+use uuid::Uuid;
+use zip::CompressionMethod;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::config::Config;
+use crate::config_types::RedactionConfig;
+use crate::redaction::Redactor;
+
+/// Session logs are named `codex-tui-<time>-<pid>.log`.
+const LOG_PREFIX: &str = "codex-tui-";
+const LOG_EXTENSION: &str = ".log";
+
+/// Name of the session's log inside a debug bundle.
+const BUNDLE_LOG_FILE: &str = "codex-tui.log";
+
+/// Logs of earlier sessions kept next to the current one.
+const KEPT_LOGS: usize = 5;
+
+/// Replaces the values of secret config keys.
+const REDACTED: &str = "[REDACTED]";
+
+/// Path in `dir` for the log of a new session, named after the time and
+/// this process. Makes room for it by deleting all but the newest
+/// [`KEPT_LOGS`] logs of earlier sessions.
+pub fn new_log_path(dir: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    prune_logs(dir, KEPT_LOGS)?;
+    Ok(dir.join(log_file_name(OffsetDateTime::now_utc(), std::process::id())))
+}
+
+fn log_file_name(time: OffsetDateTime, pid: u32) -> String {
+    let stamp = time
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .unwrap_or_default();
+    format!("{LOG_PREFIX}{stamp}-{pid}{LOG_EXTENSION}")
+}
+
+/// Delete all but the newest `keep` session logs in `dir`. The names start
+/// with the time, so they sort oldest first.
+fn prune_logs(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut logs: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_PREFIX) && name.ends_with(LOG_EXTENSION))
+        })
+        .collect();
+    logs.sort();
+    let excess = logs.len().saturating_sub(keep);
+    for path in logs.into_iter().take(excess) {
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            // Another session starting at the same time pruned it first.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Keys whose values are redacted, along with everything nested below them:
+/// credentials, and the environment variables and headers handed to MCP
+/// servers and commands.
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    matches!(key.as_str(), "env" | "set" | "headers" | "http_headers")
+        || key.ends_with("api_key")
+        || key.split(['_', '-']).any(|word| {
+            matches!(
+                word,
+                "token" | "secret" | "password" | "authorization" | "credentials"
+            )
+        })
+}
+
+fn redact_secret_values(value: &mut TomlValue, is_secret: bool) {
+    match value {
+        TomlValue::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                redact_secret_values(value, is_secret || is_secret_key(key));
+            }
+        }
+        TomlValue::Array(items) => {
+            for item in items {
+                redact_secret_values(item, is_secret);
+            }
+        }
+        value if is_secret => *value = TomlValue::String(REDACTED.to_string()),
+        _ => {}
+    }
+}
+
+/// `CODEX_HOME/config.toml` with the values of secret-looking keys replaced,
+/// and any credential left in the rest masked by the `[redaction]` patterns.
+pub fn redacted_config(codex_home: &Path, redaction: &RedactionConfig) -> std::io::Result<String> {
+    let contents = match std::fs::read_to_string(codex_home.join("config.toml")) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => return Err(e),
+    };
+    let mut value = toml::from_str::<TomlValue>(&contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    redact_secret_values(&mut value, false);
+    let redacted = toml::to_string_pretty(&value).map_err(std::io::Error::other)?;
+    Ok(Redactor::new(redaction).redact(&redacted))
+}
+
+/// Default file name of a bundle written now.
+pub fn default_bundle_file_name() -> String {
+    let now = OffsetDateTime::now_utc()
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .unwrap_or_default();
+    format!("codex-debug-{now}.zip")
+}
+
+/// Write a zip to `dest` with the session's log (`log_path`, with secrets
+/// masked), the redacted config.toml and `version.txt`.
+pub fn write_debug_bundle(
+    config: &Config,
+    version: &str,
+    session_id: Option<Uuid>,
+    log_path: &Path,
+    dest: &Path,
+) -> std::io::Result<()> {
+    let redactor = Redactor::new(&config.redaction);
+    let log = match std::fs::read(log_path) {
+        Ok(log) => redactor.redact(&String::from_utf8_lossy(&log)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut info = format!(
+        "codex {version}\nos: {} {}\nmodel: {}\nprovider: {}\nsandbox: {:?}\n",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        config.model,
+        config.model_provider_id,
+        config.sandbox_policy,
+    );
+    if let Some(session_id) = session_id {
+        info.push_str(&format!("session: {session_id}\n"));
+    }
+    let entries = [
+        (BUNDLE_LOG_FILE, log),
+        (
+            "config.toml",
+            redacted_config(&config.codex_home, &config.redaction)?,
+        ),
+        ("version.txt", info),
+    ];
+
+    let mut zip = ZipWriter::new(std::fs::File::create(dest)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in entries {
+        zip.start_file(name, options)
+            .map_err(std::io::Error::other)?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    zip.finish().map_err(std::io::Error::other)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn each_session_gets_its_own_log_and_old_ones_are_pruned() {
+        let dir = TempDir::new().unwrap();
+        let start = OffsetDateTime::UNIX_EPOCH;
+        let earlier: Vec<PathBuf> = (0..KEPT_LOGS + 2)
+            .map(|n| {
+                let path = dir.path().join(log_file_name(
+                    start + time::Duration::minutes(n as i64),
+                    100 + n as u32,
+                ));
+                std::fs::write(&path, n.to_string()).unwrap();
+                path
+            })
+            .collect();
+        // Logs that are not session logs are left alone.
+        std::fs::write(dir.path().join("codex-tui.log"), "").unwrap();
+
+        let path = new_log_path(dir.path()).unwrap();
+
+        assert!(!path.exists());
+        assert!(
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .ends_with(&format!("-{}.log", std::process::id()))
+        );
+        assert!(!earlier[0].exists());
+        assert!(!earlier[1].exists());
+        assert!(earlier[2..].iter().all(|path| path.exists()));
+        assert!(dir.path().join("codex-tui.log").exists());
+    }
+
+    #[test]
+    fn config_secrets_are_redacted() {
+        let codex_home = TempDir::new().unwrap();
+        std::fs::write(
+            codex_home.path().join("config.toml"),
+            r#"
+model = "o3"
+max_session_tokens = 1000
+
+[model_providers.azure]
+name = "Azure"
+env_key = "AZURE_OPENAI_API_KEY"
+api_key = "plain"
+
+[mcp_servers.github]
+command = "gh-mcp"
+env = { GITHUB_TOKEN = "ghp_x" }
+
+[notes]
+text = "uses sk-abcdefghijklmnopqrstuvwx"
+"#,
+        )
+        .unwrap();
+
+        let config = redacted_config(codex_home.path(), &RedactionConfig::default()).unwrap();
+        let value: TomlValue = toml::from_str(&config).unwrap();
+        assert_eq!(value["model"].as_str(), Some("o3"));
+        assert_eq!(value["max_session_tokens"].as_integer(), Some(1000));
+        let azure = &value["model_providers"]["azure"];
+        assert_eq!(azure["env_key"].as_str(), Some("AZURE_OPENAI_API_KEY"));
+        assert_eq!(azure["api_key"].as_str(), Some(REDACTED));
+        assert_eq!(
+            value["mcp_servers"]["github"]["env"]["GITHUB_TOKEN"].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(
+            value["notes"]["text"].as_str(),
+            Some("uses [REDACTED:openai_api_key]")
+        );
+    }
+}
@@ -2,6 +2,7 @@
 use std::os::unix::process::ExitStatusExt;
 
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
@@ -11,26 +12,46 @@ use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 
+use async_channel::Sender;
+use portable_pty::ChildKiller;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 use tokio::process::Command;
 use tokio::sync::Notify;
+use uuid::Uuid;
 
+use crate::config_types::ContainerSandboxConfig;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::ExecCommandOutputDeltaEvent;
+use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::pty_exec::PtyChild;
+use crate::pty_exec::spawn_pty_child;
+use crate::sandbox_backend::ContainerBackend;
+use crate::sandbox_backend::LinuxSandboxBackend;
+use crate::sandbox_backend::SandboxBackend;
+use crate::sandbox_backend::SandboxedCommand;
+use crate::sandbox_backend::SeatbeltBackend;
 
-// Maximum we send for each stream, which is either:
-// - 10KiB OR
-// - 256 lines
-const MAX_STREAM_OUTPUT: usize = 10 * 1024;
-const MAX_STREAM_OUTPUT_LINES: usize = 256;
+// Maximum we send for each stream unless `max_output_bytes` says otherwise.
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 10 * 1024;
+
+/// Exit code reported for commands killed after their timeout, as used by
+/// `timeout(1)`.
+const TIMED_OUT_EXIT_CODE: i32 = 124;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 
+/// How often a PTY child is checked for exit; `portable_pty` only offers a
+/// blocking `wait`.
+const PTY_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
 // Hardcode these since it does not seem worth including the libc crate just
 // for these.
 const SIGKILL_CODE: i32 = 9;
@@ -42,7 +63,7 @@ const MACOS_SEATBELT_BASE_POLICY: &str = include_str!("seatbelt_base_policy.sbpl
 /// to defend against an attacker trying to inject a malicious version on the
 /// PATH. If /usr/bin/sandbox-exec has been tampered with, then the attacker
 /// already has root access.
-const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
+pub(crate) const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
 
 /// Experimental environment variable that will be set to some non-empty value
 /// if both of the following are true:
@@ -60,6 +81,11 @@ pub struct ExecParams {
     pub cwd: PathBuf,
     pub timeout_ms: Option<u64>,
     pub env: HashMap<String, String>,
+    /// Run the command on a pseudo-terminal this many columns wide instead of
+    /// with piped output. Ignored under the Linux sandbox.
+    pub tty_columns: Option<u16>,
+    /// Bytes of stdout and of stderr to keep. When `None`, 10 KiB.
+    pub max_output_bytes: Option<usize>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -71,6 +97,9 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Docker/Podman container, selected with `sandbox_backend = "container"`.
+    Container,
 }
 
 pub async fn process_exec_tool_call(
@@ -79,65 +108,89 @@ pub async fn process_exec_tool_call(
     ctrl_c: Arc<Notify>,
     sandbox_policy: &SandboxPolicy,
     codex_linux_sandbox_exe: &Option<PathBuf>,
+    container_sandbox: &ContainerSandboxConfig,
+    stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
 
-    let raw_output_result = match sandbox_type {
-        SandboxType::None => exec(params, sandbox_policy, ctrl_c).await,
-        SandboxType::MacosSeatbelt => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env,
-            } = params;
-            let child = spawn_command_under_seatbelt(
-                command,
-                sandbox_policy,
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                env,
-            )
-            .await?;
-            consume_truncated_output(child, ctrl_c, timeout_ms).await
-        }
-        SandboxType::LinuxSeccomp => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env,
-            } = params;
-
-            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
-                .as_ref()
-                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
-            let child = spawn_command_under_linux_sandbox(
-                codex_linux_sandbox_exe,
-                command,
-                sandbox_policy,
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                env,
-            )
-            .await?;
-
-            consume_truncated_output(child, ctrl_c, timeout_ms).await
+    let ExecParams {
+        command,
+        cwd,
+        timeout_ms,
+        env,
+        tty_columns,
+        max_output_bytes,
+    } = params;
+    let max_output_bytes = max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+    let mut container_removal =
+        (sandbox_type == SandboxType::Container).then(|| ContainerRemoval {
+            engine: container_sandbox.engine.clone(),
+            name: format!("codex-exec-{}", Uuid::new_v4()),
+            armed: true,
+        });
+    let container_name = container_removal
+        .as_ref()
+        .map(|removal| removal.name.as_str());
+    let raw_output_result = async {
+        match tty_columns {
+            Some(columns) if tty_available(sandbox_type) => {
+                let pty = spawn_exec_pty(
+                    command,
+                    cwd,
+                    env,
+                    columns,
+                    sandbox_type,
+                    sandbox_policy,
+                    container_sandbox,
+                    container_name,
+                )?;
+                consume_pty_output(pty, ctrl_c, timeout_ms, max_output_bytes, stdout_stream).await
+            }
+            _ => {
+                let child = spawn_exec_child(
+                    command,
+                    cwd,
+                    env,
+                    sandbox_type,
+                    sandbox_policy,
+                    codex_linux_sandbox_exe,
+                    container_sandbox,
+                    container_name,
+                )
+                .await?;
+                consume_truncated_output(child, ctrl_c, timeout_ms, max_output_bytes, stdout_stream)
+                    .await
+            }
         }
-    };
+    }
+    .await;
+    if let (Some(removal), Ok(raw_output)) = (&mut container_removal, &raw_output_result) {
+        // A container whose client exited by itself has ended, and `--rm`
+        // removed it.
+        removal.armed = raw_output.timed_out || raw_output.exit_status.code().is_none();
+    }
     let duration = start.elapsed();
     match raw_output_result {
         Ok(raw_output) => {
             let stdout = String::from_utf8_lossy(&raw_output.stdout).to_string();
             let stderr = String::from_utf8_lossy(&raw_output.stderr).to_string();
 
+            // Whatever the command printed before the timeout is still useful
+            // to the model, so this is not an error.
+            if raw_output.timed_out {
+                return Ok(ExecToolCallOutput {
+                    exit_code: TIMED_OUT_EXIT_CODE,
+                    stdout,
+                    stderr,
+                    duration,
+                    timed_out: true,
+                    truncated_bytes: raw_output.truncated_bytes,
+                });
+            }
+
             #[cfg(target_family = "unix")]
-            match raw_output.exit_status.signal() {
-                Some(TIMEOUT_CODE) => return Err(CodexErr::Sandbox(SandboxErr::Timeout)),
-                Some(signal) => {
-                    return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
-                }
-                None => {}
+            if let Some(signal) = raw_output.exit_status.signal() {
+                return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
             }
 
             let exit_code = raw_output.exit_status.code().unwrap_or(-1);
@@ -157,6 +210,8 @@ pub async fn process_exec_tool_call(
                 stdout,
                 stderr,
                 duration,
+                timed_out: false,
+                truncated_bytes: raw_output.truncated_bytes,
             })
         }
         Err(err) => {
@@ -166,21 +221,161 @@ pub async fn process_exec_tool_call(
     }
 }
 
-pub async fn spawn_command_under_seatbelt(
+/// Spawns `command` with its output piped, isolated as `sandbox_type`
+/// requires. `container_name` names the container of
+/// [`SandboxType::Container`] commands.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn spawn_exec_child(
+    command: Vec<String>,
+    cwd: PathBuf,
+    env: HashMap<String, String>,
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    container_sandbox: &ContainerSandboxConfig,
+    container_name: Option<&str>,
+) -> Result<Child> {
+    let SandboxedCommand {
+        program,
+        args,
+        arg0,
+    } = sandboxed_command(
+        command,
+        &cwd,
+        &env,
+        sandbox_type,
+        sandbox_policy,
+        codex_linux_sandbox_exe,
+        container_sandbox,
+        container_name,
+    )?;
+    let child = spawn_child_async(
+        program,
+        args,
+        arg0,
+        cwd,
+        sandbox_policy,
+        StdioPolicy::RedirectForShellTool,
+        env,
+    )
+    .await?;
+    Ok(child)
+}
+
+/// Spawns `command` on a pseudo-terminal `columns` wide, isolated as
+/// `sandbox_type` requires. Not available for [`SandboxType::LinuxSeccomp`].
+#[allow(clippy::too_many_arguments)]
+fn spawn_exec_pty(
+    command: Vec<String>,
+    cwd: PathBuf,
+    mut env: HashMap<String, String>,
+    columns: u16,
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    container_sandbox: &ContainerSandboxConfig,
+    container_name: Option<&str>,
+) -> Result<PtyChild> {
+    let SandboxedCommand { program, args, .. } = sandboxed_command(
+        command,
+        &cwd,
+        &env,
+        sandbox_type,
+        sandbox_policy,
+        &None,
+        container_sandbox,
+        container_name,
+    )?;
+    if !sandbox_policy.has_full_network_access() {
+        env.insert(
+            CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR.to_string(),
+            "1".to_string(),
+        );
+    }
+    Ok(spawn_pty_child(program, args, cwd, env, columns)?)
+}
+
+/// Whether commands run under `sandbox_type` can get a terminal. Where they
+/// cannot, `tty` is ignored and the command runs with pipes.
+pub fn tty_available(sandbox_type: SandboxType) -> bool {
+    // `codex-linux-sandbox` is selected through argv[0], which cannot be set
+    // for a PTY child.
+    sandbox_type != SandboxType::LinuxSeccomp
+}
+
+/// The program invocation that runs `command` under `sandbox_type`.
+#[allow(clippy::too_many_arguments)]
+fn sandboxed_command(
+    command: Vec<String>,
+    cwd: &Path,
+    env: &HashMap<String, String>,
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    container_sandbox: &ContainerSandboxConfig,
+    container_name: Option<&str>,
+) -> Result<SandboxedCommand> {
+    let sandboxed = match sandbox_type {
+        SandboxType::None => {
+            let (program, args) = command.split_first().ok_or_else(|| {
+                CodexErr::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "command args are empty",
+                ))
+            })?;
+            SandboxedCommand {
+                program: PathBuf::from(program),
+                args: args.into(),
+                arg0: None,
+            }
+        }
+        SandboxType::MacosSeatbelt => SeatbeltBackend.wrap_command(command, sandbox_policy, cwd),
+        SandboxType::LinuxSeccomp => {
+            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
+                .as_ref()
+                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            LinuxSandboxBackend {
+                codex_linux_sandbox_exe,
+            }
+            .wrap_command(command, sandbox_policy, cwd)
+        }
+        SandboxType::Container => ContainerBackend {
+            config: container_sandbox,
+            env_keys: env.keys().cloned().collect(),
+            name: container_name.map(String::from),
+        }
+        .wrap_command(command, sandbox_policy, cwd),
+    };
+    Ok(sandboxed)
+}
+
+async fn spawn_command_under_backend(
+    backend: &dyn SandboxBackend,
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: PathBuf,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
-    let args = create_seatbelt_command_args(command, sandbox_policy, &cwd);
-    let arg0 = None;
-    spawn_child_async(
-        PathBuf::from(MACOS_PATH_TO_SEATBELT_EXECUTABLE),
+    let SandboxedCommand {
+        program,
         args,
         arg0,
-        cwd,
+    } = backend.wrap_command(command, sandbox_policy, &cwd);
+    spawn_child_async(program, args, arg0, cwd, sandbox_policy, stdio_policy, env).await
+}
+
+pub async fn spawn_command_under_seatbelt(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: PathBuf,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+) -> std::io::Result<Child> {
+    spawn_command_under_backend(
+        &SeatbeltBackend,
+        command,
         sandbox_policy,
+        cwd,
         stdio_policy,
         env,
     )
@@ -205,22 +400,14 @@ pub async fn spawn_command_under_linux_sandbox<P>(
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd);
-    let arg0 = Some("codex-linux-sandbox");
-    spawn_child_async(
-        codex_linux_sandbox_exe.as_ref().to_path_buf(),
-        args,
-        arg0,
-        cwd,
-        sandbox_policy,
-        stdio_policy,
-        env,
-    )
-    .await
+    let backend = LinuxSandboxBackend {
+        codex_linux_sandbox_exe: codex_linux_sandbox_exe.as_ref(),
+    };
+    spawn_command_under_backend(&backend, command, sandbox_policy, cwd, stdio_policy, env).await
 }
 
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
-fn create_linux_sandbox_command_args(
+pub(crate) fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
@@ -267,7 +454,7 @@ fn create_linux_sandbox_command_args(
     linux_cmd
 }
 
-fn create_seatbelt_command_args(
+pub(crate) fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
@@ -326,11 +513,23 @@ fn create_seatbelt_command_args(
     seatbelt_args
 }
 
+/// Where to send [`ExecCommandOutputDeltaEvent`]s for a running command.
+#[derive(Clone)]
+pub struct StdoutStream {
+    pub sub_id: String,
+    pub call_id: String,
+    pub tx_event: Sender<Event>,
+}
+
 #[derive(Debug)]
 pub struct RawExecToolCallOutput {
     pub exit_status: ExitStatus,
     pub stdout: Vec<u8>,
     pub stderr: Vec<u8>,
+    /// The command was killed because it ran past its timeout.
+    pub timed_out: bool,
+    /// Bytes of output replaced by truncation markers, across both streams.
+    pub truncated_bytes: usize,
 }
 
 #[derive(Debug)]
@@ -339,36 +538,8 @@ pub struct ExecToolCallOutput {
     pub stdout: String,
     pub stderr: String,
     pub duration: Duration,
-}
-
-async fn exec(
-    ExecParams {
-        command,
-        cwd,
-        timeout_ms,
-        env,
-    }: ExecParams,
-    sandbox_policy: &SandboxPolicy,
-    ctrl_c: Arc<Notify>,
-) -> Result<RawExecToolCallOutput> {
-    let (program, args) = command.split_first().ok_or_else(|| {
-        CodexErr::Io(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "command args are empty",
-        ))
-    })?;
-    let arg0 = None;
-    let child = spawn_child_async(
-        PathBuf::from(program),
-        args.into(),
-        arg0,
-        cwd,
-        sandbox_policy,
-        StdioPolicy::RedirectForShellTool,
-        env,
-    )
-    .await?;
-    consume_truncated_output(child, ctrl_c, timeout_ms).await
+    pub timed_out: bool,
+    pub truncated_bytes: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -407,6 +578,11 @@ async fn spawn_child_async(
 
     match stdio_policy {
         StdioPolicy::RedirectForShellTool => {
+            // Lead a new process group so a timeout can kill everything the
+            // command started, not just the command itself.
+            #[cfg(unix)]
+            cmd.process_group(0);
+
             // Do not create a file descriptor for stdin because otherwise some
             // commands may hang forever waiting for input. For example, ripgrep has
             // a heuristic where it may try to read from stdin as explained here:
@@ -432,6 +608,8 @@ pub(crate) async fn consume_truncated_output(
     mut child: Child,
     ctrl_c: Arc<Notify>,
     timeout_ms: Option<u64>,
+    max_output_bytes: usize,
+    stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
     // Both stdout and stderr were configured with `Stdio::piped()`
     // above, therefore `take()` should normally return `Some`.  If it doesn't
@@ -450,17 +628,20 @@ pub(crate) async fn consume_truncated_output(
 
     let stdout_handle = tokio::spawn(read_capped(
         BufReader::new(stdout_reader),
-        MAX_STREAM_OUTPUT,
-        MAX_STREAM_OUTPUT_LINES,
+        max_output_bytes,
+        stdout_stream
+            .clone()
+            .map(|stream| (stream, ExecOutputStream::Stdout)),
     ));
     let stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
-        MAX_STREAM_OUTPUT,
-        MAX_STREAM_OUTPUT_LINES,
+        max_output_bytes,
+        stdout_stream.map(|stream| (stream, ExecOutputStream::Stderr)),
     ));
 
     let interrupted = ctrl_c.notified();
     let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let mut timed_out = false;
     let exit_status = tokio::select! {
         result = tokio::time::timeout(timeout, child.wait()) => {
             match result {
@@ -468,6 +649,10 @@ pub(crate) async fn consume_truncated_output(
                 Ok(e) => e?,
                 Err(_) => {
                     // timeout
+                    timed_out = true;
+                    if let Some(pid) = child.id() {
+                        kill_process_group(pid);
+                    }
                     child.start_kill()?;
                     // Debatable whether `child.wait().await` should be called here.
                     synthetic_exit_status(128 + TIMEOUT_CODE)
@@ -475,6 +660,9 @@ pub(crate) async fn consume_truncated_output(
             }
         }
         _ = interrupted => {
+            if let Some(pid) = child.id() {
+                kill_process_group(pid);
+            }
             child.start_kill()?;
             synthetic_exit_status(128 + SIGKILL_CODE)
         }
@@ -485,49 +673,244 @@ pub(crate) async fn consume_truncated_output(
 
     Ok(RawExecToolCallOutput {
         exit_status,
-        stdout,
-        stderr,
+        stdout: stdout.bytes,
+        stderr: stderr.bytes,
+        timed_out,
+        truncated_bytes: stdout.truncated_bytes + stderr.truncated_bytes,
+    })
+}
+
+/// Like [`consume_truncated_output`] for a command on a pseudo-terminal. The
+/// terminal merges stdout and stderr, so all output is reported as stdout.
+async fn consume_pty_output(
+    pty: PtyChild,
+    ctrl_c: Arc<Notify>,
+    timeout_ms: Option<u64>,
+    max_output_bytes: usize,
+    stdout_stream: Option<StdoutStream>,
+) -> Result<RawExecToolCallOutput> {
+    let PtyChild {
+        mut child,
+        output,
+        master,
+    } = pty;
+    let output_handle = tokio::spawn(read_capped(
+        BufReader::new(output),
+        max_output_bytes,
+        stdout_stream.map(|stream| (stream, ExecOutputStream::Stdout)),
+    ));
+
+    // The child leads its own session, so its pid is also its process group.
+    let pid = child.process_id();
+    let mut killer = child.clone_killer();
+    let mut kill = || {
+        if let Some(pid) = pid {
+            kill_process_group(pid);
+        }
+        killer.kill()
+    };
+    let wait = async {
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return io::Result::Ok(pty_exit_status(&status));
+            }
+            tokio::time::sleep(PTY_POLL_INTERVAL).await;
+        }
+    };
+
+    let interrupted = ctrl_c.notified();
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let mut timed_out = false;
+    let exit_status = tokio::select! {
+        result = tokio::time::timeout(timeout, wait) => {
+            match result {
+                Ok(exit_status) => exit_status?,
+                Err(_) => {
+                    timed_out = true;
+                    kill()?;
+                    synthetic_exit_status(128 + TIMEOUT_CODE)
+                }
+            }
+        }
+        _ = interrupted => {
+            kill()?;
+            synthetic_exit_status(128 + SIGKILL_CODE)
+        }
+    };
+
+    let stdout = output_handle.await??;
+    drop(master);
+
+    Ok(RawExecToolCallOutput {
+        exit_status,
+        stdout: stdout.bytes,
+        stderr: Vec::new(),
+        timed_out,
+        truncated_bytes: stdout.truncated_bytes,
     })
 }
 
+/// Output of one stream, cut down to at most `max_output` bytes plus a
+/// truncation marker.
+struct CappedOutput {
+    bytes: Vec<u8>,
+    truncated_bytes: usize,
+}
+
+/// Reads `reader` to EOF, keeping the first and last `max_output / 2` bytes.
+/// The bytes in between are replaced with a `[truncated N bytes]` marker so
+/// the model knows output is missing. Neither the kept bytes nor the streamed
+/// chunks split a UTF-8 character.
 async fn read_capped<R: AsyncRead + Unpin>(
     mut reader: R,
     max_output: usize,
-    max_lines: usize,
-) -> io::Result<Vec<u8>> {
-    let mut buf = Vec::with_capacity(max_output.min(8 * 1024));
+    stream: Option<(StdoutStream, ExecOutputStream)>,
+) -> io::Result<CappedOutput> {
+    let head_cap = max_output / 2;
+    let mut head = Vec::with_capacity(head_cap.min(8 * 1024));
+    let mut head_full = false;
+    let mut tail: VecDeque<u8> = VecDeque::new();
+    let mut total_bytes = 0;
     let mut tmp = [0u8; 8192];
-
-    let mut remaining_bytes = max_output;
-    let mut remaining_lines = max_lines;
+    // Bytes of a character the last read split, streamed with the next one.
+    let mut unstreamed = Vec::new();
 
     loop {
         let n = reader.read(&mut tmp).await?;
+        if let Some((stdout_stream, output_stream)) = &stream {
+            unstreamed.extend_from_slice(&tmp[..n]);
+            let complete = if n == 0 {
+                unstreamed.len()
+            } else {
+                complete_utf8_len(&unstreamed)
+            };
+            if complete > 0 {
+                let event = Event {
+                    id: stdout_stream.sub_id.clone(),
+                    msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                        call_id: stdout_stream.call_id.clone(),
+                        stream: *output_stream,
+                        chunk: String::from_utf8_lossy(&unstreamed[..complete]).into_owned(),
+                    }),
+                };
+                unstreamed.drain(..complete);
+                // The UI may have gone away; the output is still collected below.
+                let _ = stdout_stream.tx_event.send(event).await;
+            }
+        }
         if n == 0 {
             break;
         }
 
-        // Copy into the buffer only while we still have byte and line budget.
-        if remaining_bytes > 0 && remaining_lines > 0 {
-            let mut copy_len = 0;
-            for &b in &tmp[..n] {
-                if remaining_bytes == 0 || remaining_lines == 0 {
-                    break;
-                }
-                copy_len += 1;
-                remaining_bytes -= 1;
-                if b == b'\n' {
-                    remaining_lines -= 1;
-                }
+        total_bytes += n;
+        let mut chunk = &tmp[..n];
+        if !head_full {
+            let take = chunk.len().min(head_cap - head.len());
+            head.extend_from_slice(&chunk[..take]);
+            chunk = &chunk[take..];
+            if head.len() == head_cap {
+                head_full = true;
+                // A character cut by the cap continues in the tail.
+                let complete = complete_utf8_len(&head);
+                tail.extend(head.drain(complete..));
             }
-            buf.extend_from_slice(&tmp[..copy_len]);
         }
-        // Continue reading to EOF to avoid back-pressure, but discard once caps are hit.
+        tail.extend(chunk);
+        let tail_cap = max_output - head.len();
+        if tail.len() > tail_cap {
+            tail.drain(..tail.len() - tail_cap);
+            // Start at a whole character.
+            let split = tail
+                .iter()
+                .take(3)
+                .take_while(|b| is_utf8_continuation(**b))
+                .count();
+            tail.drain(..split);
+        }
+        // Continue reading to EOF to avoid back-pressure.
+    }
+
+    let truncated_bytes = total_bytes - head.len() - tail.len();
+    let mut bytes = head;
+    if truncated_bytes > 0 {
+        bytes.extend_from_slice(format!("\n[truncated {truncated_bytes} bytes]\n").as_bytes());
+    }
+    bytes.extend(tail);
+    Ok(CappedOutput {
+        bytes,
+        truncated_bytes,
+    })
+}
+
+/// Length of `bytes` without the start of a UTF-8 character at its end that
+/// is missing its last bytes.
+fn complete_utf8_len(bytes: &[u8]) -> usize {
+    let start = bytes.len().saturating_sub(3);
+    for i in (start..bytes.len()).rev() {
+        if is_utf8_continuation(bytes[i]) {
+            continue;
+        }
+        let needed = match bytes[i] {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        return if bytes.len() - i < needed {
+            i
+        } else {
+            bytes.len()
+        };
+    }
+    bytes.len()
+}
+
+fn is_utf8_continuation(byte: u8) -> bool {
+    byte & 0xc0 == 0x80
+}
+
+/// Removes the container a command runs in when dropped while armed. Killing
+/// the engine's client on a timeout or an interrupt, or dropping it when the
+/// turn is aborted, does not stop the container itself.
+struct ContainerRemoval {
+    engine: String,
+    name: String,
+    armed: bool,
+}
+
+impl Drop for ContainerRemoval {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let engine = std::mem::take(&mut self.engine);
+        let name = std::mem::take(&mut self.name);
+        // Waited for on another thread, so that dropping does not block.
+        std::thread::spawn(move || {
+            let _ = std::process::Command::new(engine)
+                .args(["rm", "-f", &name])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        });
     }
+}
 
-    Ok(buf)
+/// Kills the process group led by `pid`, taking down anything the command
+/// started along with it. Commands run by the shell tool lead their own group.
+#[cfg(unix)]
+pub(crate) fn kill_process_group(pid: u32) {
+    // SAFETY: `killpg` has no memory-safety preconditions; a stale pid at
+    // worst makes it fail with ESRCH.
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
 }
 
+#[cfg(not(unix))]
+pub(crate) fn kill_process_group(_pid: u32) {}
+
 #[cfg(unix)]
 fn synthetic_exit_status(code: i32) -> ExitStatus {
     use std::os::unix::process::ExitStatusExt;
@@ -540,3 +923,94 @@ fn synthetic_exit_status(code: i32) -> ExitStatus {
     #[expect(clippy::unwrap_used)]
     std::process::ExitStatus::from_raw(code.try_into().unwrap())
 }
+
+#[cfg(unix)]
+fn pty_exit_status(status: &portable_pty::ExitStatus) -> ExitStatus {
+    // The exit code is the second byte of a raw wait status.
+    synthetic_exit_status(((status.exit_code() & 0xff) as i32) << 8)
+}
+
+#[cfg(windows)]
+fn pty_exit_status(status: &portable_pty::ExitStatus) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    std::process::ExitStatus::from_raw(status.exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[tokio::test]
+    async fn read_capped_keeps_head_and_tail() {
+        let input = b"0123456789abcdefghij";
+        let output = read_capped(&input[..], 8, None).await.unwrap();
+        assert_eq!(output.truncated_bytes, 12);
+        assert_eq!(
+            String::from_utf8(output.bytes).unwrap(),
+            "0123\n[truncated 12 bytes]\nghij"
+        );
+
+        let output = read_capped(&input[..], 64, None).await.unwrap();
+        assert_eq!(output.truncated_bytes, 0);
+        assert_eq!(output.bytes, input);
+    }
+
+    #[tokio::test]
+    async fn read_capped_cuts_between_characters() {
+        // Three bytes each; the caps fall inside the second and last ones.
+        let input = "€€€€€".as_bytes();
+        let output = read_capped(input, 8, None).await.unwrap();
+        assert_eq!(
+            String::from_utf8(output.bytes).unwrap(),
+            "€\n[truncated 9 bytes]\n€"
+        );
+
+        // Output that fits is kept whole even when a cap falls in a character.
+        let output = read_capped(input, 15, None).await.unwrap();
+        assert_eq!(output.truncated_bytes, 0);
+        assert_eq!(output.bytes, input);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dropping_an_armed_container_removal_removes_the_container() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("engine.log");
+        let engine = dir.path().join("engine");
+        std::fs::write(
+            &engine,
+            format!("#!/bin/sh\necho \"$@\" >> '{}'\n", log.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&engine, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let removal = |name: &str, armed| ContainerRemoval {
+            engine: engine.to_string_lossy().into_owned(),
+            name: name.to_string(),
+            armed,
+        };
+
+        drop(removal("finished", false));
+        drop(removal("killed", true));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !log.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "rm -f killed\n");
+    }
+
+    #[test]
+    fn complete_utf8_len_leaves_out_a_split_character() {
+        let euro = "€".as_bytes();
+        assert_eq!(complete_utf8_len(b"abc"), 3);
+        assert_eq!(complete_utf8_len(&[b'a', euro[0]]), 1);
+        assert_eq!(complete_utf8_len(&[b'a', euro[0], euro[1]]), 1);
+        assert_eq!(complete_utf8_len(&[b'a', euro[0], euro[1], euro[2]]), 4);
+        // Bytes that are not UTF-8 at all are streamed as they are.
+        assert_eq!(complete_utf8_len(&[0xff, 0x80]), 2);
+    }
+}
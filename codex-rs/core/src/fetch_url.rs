@@ -0,0 +1,515 @@
+//! The `fetch_url` tool: the model downloads a page and gets back its
+//! readable text as markdown, with the final URL after redirects. The
+//! download is limited in size and time, and the tool is only offered when
+//! the sandbox policy allows network access.
+
+use std::time::Duration;
+
+use reqwest::Url;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+
+pub(crate) const FETCH_URL_TOOL_NAME: &str = "fetch_url";
+
+/// Bytes downloaded at most; the rest of the body is dropped.
+const MAX_DOWNLOAD_BYTES: usize = 2 * 1024 * 1024;
+
+/// Bytes of extracted text returned to the model.
+const MAX_OUTPUT_BYTES: usize = 100 * 1024;
+
+/// Time allowed for the whole download, redirects included.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const MAX_REDIRECTS: usize = 10;
+
+/// Elements dropped with everything inside them: scripts and styles, and
+/// the navigation and other boilerplate around the content of a page.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "aside", "button", "footer", "form", "head", "header", "iframe", "nav", "noscript", "script",
+    "select", "style", "svg", "template",
+];
+
+/// Elements that start a new paragraph.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "article",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figure",
+    "main",
+    "ol",
+    "p",
+    "section",
+    "table",
+    "ul",
+];
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct FetchUrlArgs {
+    pub url: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct FetchedPage {
+    /// The URL the page was served from, after redirects.
+    pub url: String,
+    pub title: Option<String>,
+    pub text: String,
+    /// Whether the download or the text was cut off.
+    pub truncated: bool,
+}
+
+/// Download `url` and extract its readable text. HTML is converted to
+/// markdown; other text types (plain text, JSON, XML...) are returned as is.
+pub(crate) async fn fetch_url(url: &str) -> Result<FetchedPage, String> {
+    let url = Url::parse(url).map_err(|e| format!("invalid URL {url}: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "only http and https URLs can be fetched, not {url}"
+        ));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+        .build()
+        .map_err(|e| format!("failed to create HTTP client: {e}"))?;
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {e}"))?;
+    let final_url = response.url().clone();
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("{final_url} returned {status}"));
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+    if !is_text(&content_type) {
+        return Err(format!(
+            "{final_url} is not a text page (content type {content_type})"
+        ));
+    }
+
+    let mut body = Vec::new();
+    let mut truncated = false;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("failed to read {final_url}: {e}"))?
+    {
+        let room = MAX_DOWNLOAD_BYTES - body.len();
+        if chunk.len() > room {
+            body.extend_from_slice(&chunk[..room]);
+            truncated = true;
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = String::from_utf8_lossy(&body);
+
+    let (title, mut text) = if content_type.contains("html") {
+        (html_title(&body), html_to_markdown(&body, Some(&final_url)))
+    } else {
+        (None, body.into_owned())
+    };
+    if text.len() > MAX_OUTPUT_BYTES {
+        let mut end = MAX_OUTPUT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        truncated = true;
+    }
+    Ok(FetchedPage {
+        url: final_url.to_string(),
+        title,
+        text,
+        truncated,
+    })
+}
+
+fn is_text(content_type: &str) -> bool {
+    content_type.starts_with("text/")
+        || ["html", "json", "xml", "javascript", "markdown"]
+            .iter()
+            .any(|kind| content_type.contains(kind))
+}
+
+/// The tool output: the final URL and title, then the text.
+pub(crate) fn format_page(page: &FetchedPage) -> String {
+    let mut output = format!("URL: {}\n", page.url);
+    if let Some(title) = &page.title {
+        output.push_str(&format!("Title: {title}\n"));
+    }
+    output.push('\n');
+    output.push_str(&page.text);
+    if page.truncated {
+        output.push_str("\n\n[The page was truncated.]");
+    }
+    output
+}
+
+fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = collapse_whitespace(&decode_entities(&html[start..end]));
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// The readable part of `html` as markdown: the page's `<main>` or
+/// `<article>` if it has one, without scripts, navigation and other
+/// boilerplate. Relative links are resolved against `base`.
+pub(crate) fn html_to_markdown(html: &str, base: Option<&Url>) -> String {
+    let mut out = String::new();
+    // Elements being skipped, innermost last.
+    let mut skipping: Vec<String> = Vec::new();
+    // Hrefs of the open links.
+    let mut links: Vec<Option<String>> = Vec::new();
+    let mut in_pre = false;
+
+    let mut rest = main_content(html);
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_text(&mut out, rest, &skipping, in_pre);
+            break;
+        };
+        push_text(&mut out, &rest[..lt], &skipping, in_pre);
+        rest = &rest[lt..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if !rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!') {
+            // A bare `<` in the text.
+            push_text(&mut out, "<", &skipping, in_pre);
+            rest = &rest[1..];
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            break;
+        };
+        let tag = Tag::parse(&rest[1..gt]);
+        rest = &rest[gt + 1..];
+        let Some(tag) = tag else {
+            continue;
+        };
+
+        if let Some(open) = skipping.last() {
+            if tag.closing && *open == tag.name {
+                skipping.pop();
+            } else if !tag.closing && *open == tag.name && !tag.self_closing {
+                skipping.push(tag.name);
+            }
+            continue;
+        }
+        if SKIPPED_ELEMENTS.contains(&tag.name.as_str()) {
+            if !tag.closing && !tag.self_closing {
+                skipping.push(tag.name);
+            }
+            continue;
+        }
+
+        let name = tag.name.as_str();
+        match (name, tag.closing) {
+            ("br", _) => out.push('\n'),
+            ("hr", _) => paragraph(&mut out, "---"),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                paragraph(&mut out, &format!("{} ", "#".repeat(level)));
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => paragraph(&mut out, ""),
+            ("li", false) => {
+                trim_trailing_spaces(&mut out);
+                out.push_str("\n- ");
+            }
+            ("tr", false) => out.push('\n'),
+            ("td" | "th", false) => {
+                if !out.ends_with('\n') {
+                    out.push_str(" | ");
+                }
+            }
+            ("pre", false) => {
+                paragraph(&mut out, "```\n");
+                in_pre = true;
+            }
+            ("pre", true) => {
+                trim_trailing_spaces(&mut out);
+                if !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str("```");
+                paragraph(&mut out, "");
+                in_pre = false;
+            }
+            ("code", _) if !in_pre => out.push('`'),
+            ("strong" | "b", _) => out.push_str("**"),
+            ("em" | "i", _) => out.push('_'),
+            ("a", false) => {
+                let href = tag
+                    .href
+                    .filter(|href| !href.starts_with('#') && !href.starts_with("javascript:"))
+                    .map(|href| match base.map(|base| base.join(&href)) {
+                        Some(Ok(url)) => url.to_string(),
+                        _ => href,
+                    });
+                if href.is_some() {
+                    out.push('[');
+                }
+                links.push(href);
+            }
+            ("a", true) => {
+                if let Some(Some(href)) = links.pop() {
+                    trim_trailing_spaces(&mut out);
+                    out.push_str(&format!("]({href})"));
+                }
+            }
+            (name, _) if BLOCK_ELEMENTS.contains(&name) => paragraph(&mut out, ""),
+            _ => {}
+        }
+    }
+    tidy(&out)
+}
+
+/// The `<main>` or `<article>` element of `html`, or all of it.
+fn main_content(html: &str) -> &str {
+    let lower = html.to_ascii_lowercase();
+    for name in ["main", "article"] {
+        let open = format!("<{name}");
+        let close = format!("</{name}>");
+        let start = lower.match_indices(&open).map(|(idx, _)| idx).find(|&idx| {
+            lower[idx + open.len()..].starts_with(|c: char| c == '>' || c.is_ascii_whitespace())
+        });
+        match (start, lower.rfind(&close)) {
+            (Some(start), Some(end)) if start < end => return &html[start..end + close.len()],
+            _ => {}
+        }
+    }
+    html
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    href: Option<String>,
+}
+
+impl Tag {
+    /// Parse the inside of `<...>`; `None` for doctypes and the like.
+    fn parse(inner: &str) -> Option<Tag> {
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let name: String = inner
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        Some(Tag {
+            self_closing: inner.ends_with('/') || matches!(name.as_str(), "br" | "hr" | "img"),
+            href: (name == "a").then(|| attribute(inner, "href")).flatten(),
+            name,
+            closing,
+        })
+    }
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower
+        .match_indices(name)
+        .map(|(idx, _)| idx + name.len())
+        .find(|&idx| lower[idx..].trim_start().starts_with('='))?;
+    let value = tag[start..].trim_start().strip_prefix('=')?.trim_start();
+    let value = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value.split(|c: char| c.is_ascii_whitespace()).next()?,
+    };
+    Some(decode_entities(value))
+}
+
+fn push_text(out: &mut String, text: &str, skipping: &[String], in_pre: bool) {
+    if !skipping.is_empty() || text.is_empty() {
+        return;
+    }
+    let text = decode_entities(text);
+    if in_pre {
+        out.push_str(&text);
+        return;
+    }
+    let text = collapse_whitespace(&text);
+    if text == " " && (out.is_empty() || out.ends_with([' ', '\n', '['])) {
+        return;
+    }
+    let text = if out.is_empty() || out.ends_with([' ', '\n', '[']) {
+        text.trim_start()
+    } else {
+        &text
+    };
+    out.push_str(text);
+}
+
+/// Start a new paragraph with `prefix`.
+fn paragraph(out: &mut String, prefix: &str) {
+    trim_trailing_spaces(out);
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(prefix);
+}
+
+fn trim_trailing_spaces(out: &mut String) {
+    let len = out.trim_end_matches(' ').len();
+    out.truncate(len);
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                collapsed.push(' ');
+            }
+            in_space = true;
+        } else {
+            collapsed.push(c);
+            in_space = false;
+        }
+    }
+    collapsed
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..=end])?, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "copy" => '©',
+        _ => return None,
+    })
+}
+
+/// Trim the lines and squeeze runs of blank lines outside code blocks.
+fn tidy(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        if line.starts_with("```") {
+            in_code = !in_code;
+        }
+        let line = if in_code {
+            line.trim_end()
+        } else {
+            line.trim()
+        };
+        if line.is_empty() && !in_code && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn extracts_the_main_content_as_markdown() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Guide &amp; Reference</title><style>p { color: red }</style></head>
+<body>
+<nav><a href="/">Home</a> <a href="/docs">Docs</a></nav>
+<main>
+  <h1>Getting   started</h1>
+  <!-- a comment -->
+  <p>Install the <code>cli</code> with <a href="install.html">the installer</a>.</p>
+  <script>track();</script>
+  <ul><li>Fast</li><li><strong>Safe</strong></li></ul>
+  <pre>cargo install codex
+  codex --help</pre>
+</main>
+<footer>Copyright</footer>
+</body></html>"#;
+        let base = Url::parse("https://example.org/docs/guide.html").unwrap();
+        assert_eq!(
+            html_to_markdown(html, Some(&base)),
+            "# Getting started\n\n\
+             Install the `cli` with [the installer](https://example.org/docs/install.html).\n\n\
+             - Fast\n\
+             - **Safe**\n\n\
+             ```\n\
+             cargo install codex\n  codex --help\n\
+             ```"
+        );
+        assert_eq!(html_title(html), Some("Guide & Reference".to_string()));
+    }
+
+    #[test]
+    fn falls_back_to_the_whole_body_without_boilerplate() {
+        let html = "<body><header>Menu</header><div>A &lt;b&gt; &#x41;&#66; < 3</div>\
+                    <div>Second<br>line</div><aside><p>Ads</p></aside></body>";
+        assert_eq!(html_to_markdown(html, None), "A <b> AB < 3\n\nSecond\nline");
+    }
+}
@@ -0,0 +1,234 @@
+//! `@path` references in user messages and custom prompts. A word that
+//! starts with `@` and names a file relative to the session's working
+//! directory is replaced with a fenced code block of the file's contents, so
+//! the user does not have to paste it. Words that do not name a readable
+//! text file, such as `@someone`, are left alone, and so are paths that lead
+//! out of the working directory, through `..` or a symlink.
+
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+
+use tracing::warn;
+
+/// Files longer than this are cut at the last line that fits.
+const MAX_FILE_BYTES: usize = 32 * 1024;
+
+/// Total contents inlined into one message; references past it are left as
+/// they were typed.
+const MAX_TOTAL_BYTES: usize = 128 * 1024;
+
+/// Characters that may follow a reference without being part of the path,
+/// as in "see @src/lib.rs.".
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '}', '"', '\''];
+
+/// `text` with each `@path` reference to a file under `cwd` replaced by the
+/// file's contents. A file referenced more than once is inlined the first
+/// time only; later references become the quoted path.
+pub(crate) fn expand_file_references(text: &str, cwd: &Path) -> String {
+    let Ok(cwd) = cwd.canonicalize() else {
+        return text.to_string();
+    };
+    let mut expanded = String::new();
+    let mut inlined: HashSet<String> = HashSet::new();
+    let mut total = 0;
+    let mut last = 0;
+    for (start, word) in reference_words(text) {
+        let Some((path, contents)) = resolve_reference(word, &cwd) else {
+            continue;
+        };
+        expanded.push_str(&text[last..start]);
+        last = start + 1 + path.len();
+        if inlined.contains(path) {
+            expanded.push_str(&format!("`{path}`"));
+            continue;
+        }
+        let contents = match contents {
+            Ok(contents) if total + contents.len() <= MAX_TOTAL_BYTES => contents,
+            Ok(_) => {
+                warn!("not inlining @{path}: the message already has {total} bytes of files");
+                expanded.push('@');
+                expanded.push_str(path);
+                continue;
+            }
+            Err(e) => {
+                warn!("not inlining @{path}: {e}");
+                expanded.push('@');
+                expanded.push_str(path);
+                continue;
+            }
+        };
+        total += contents.len();
+        inlined.insert(path.to_string());
+        expanded.truncate(expanded.trim_end_matches([' ', '\t']).len());
+        if !expanded.is_empty() && !expanded.ends_with('\n') {
+            expanded.push('\n');
+        }
+        expanded.push_str(&fenced_block(path, &contents));
+        if !text[last..].starts_with('\n') {
+            expanded.push('\n');
+        }
+    }
+    expanded.push_str(&text[last..]);
+    expanded
+}
+
+/// (offset of the `@`, word after it) for each `@` that starts a
+/// whitespace-separated word, so `user@example.com` is not a reference.
+fn reference_words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.char_indices().filter_map(move |(i, c)| {
+        if c != '@'
+            || text[..i]
+                .chars()
+                .next_back()
+                .is_some_and(|c| !c.is_whitespace())
+        {
+            return None;
+        }
+        let rest = &text[i + 1..];
+        let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        (len > 0).then(|| (i, &rest[..len]))
+    })
+}
+
+/// The longest prefix of `word`, less trailing punctuation, that names a
+/// file under `cwd`, with the file's contents. `cwd` must be canonical.
+fn resolve_reference<'a>(word: &'a str, cwd: &Path) -> Option<(&'a str, Result<String, String>)> {
+    let mut path = word;
+    loop {
+        let file = cwd
+            .join(path)
+            .canonicalize()
+            .ok()
+            .filter(|file| file.starts_with(cwd) && file.is_file());
+        if let Some(file) = file {
+            return Some((path, read_contents(&file)));
+        }
+        path = path.strip_suffix(TRAILING_PUNCTUATION)?;
+    }
+}
+
+/// The text of the file at `path`, cut to [`MAX_FILE_BYTES`] with a note
+/// saying so. Binary files are an error.
+fn read_contents(path: &Path) -> Result<String, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut bytes = Vec::new();
+    file.take(MAX_FILE_BYTES as u64)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    let truncated = size > bytes.len() as u64;
+    if let Some(end) = bytes
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .filter(|_| truncated)
+    {
+        bytes.truncate(end + 1);
+    }
+    if bytes.contains(&0) {
+        return Err("it is a binary file".to_string());
+    }
+    let mut contents = match String::from_utf8(bytes) {
+        Ok(contents) => contents,
+        // The cut may split a character of a file without newlines.
+        Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).map_err(|e| e.to_string())?
+        }
+        Err(_) => return Err("it is not UTF-8 text".to_string()),
+    };
+    if truncated {
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&format!(
+            "[... truncated: showing {} of {size} bytes]\n",
+            contents.len()
+        ));
+    }
+    Ok(contents)
+}
+
+/// `contents` under a line naming `path`, fenced with more backticks than
+/// any run in the contents and tagged with the file's extension.
+fn fenced_block(path: &str, contents: &str) -> String {
+    let longest_run = contents
+        .split(|c: char| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let language = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let mut block = format!("`{path}`:\n{fence}{language}\n{contents}");
+    if !contents.ends_with('\n') {
+        block.push('\n');
+    }
+    block.push_str(&fence);
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn inlines_referenced_files() {
+        let cwd = tempfile::tempdir().unwrap();
+        std::fs::create_dir(cwd.path().join("src")).unwrap();
+        std::fs::write(cwd.path().join("src/lib.rs"), "fn main() {}\n").unwrap();
+        let text =
+            "Explain @src/lib.rs, then mail user@example.com about @missing.rs and @src/lib.rs.";
+        assert_eq!(
+            expand_file_references(text, cwd.path()),
+            "Explain\n`src/lib.rs`:\n```rs\nfn main() {}\n```\n, then mail user@example.com about @missing.rs and `src/lib.rs`."
+        );
+    }
+
+    #[test]
+    fn leaves_files_outside_cwd_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().join("repo");
+        std::fs::create_dir(&cwd).unwrap();
+        std::fs::write(dir.path().join("secret"), "hunter2\n").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("secret"), cwd.join("link")).unwrap();
+        let absolute = dir.path().join("secret").display().to_string();
+        let text = format!("see @../secret @{absolute} @link");
+        assert_eq!(expand_file_references(&text, &cwd), text);
+    }
+
+    #[test]
+    fn fences_contents_with_backticks() {
+        assert_eq!(
+            fenced_block("README.md", "```sh\nls\n```"),
+            "`README.md`:\n````md\n```sh\nls\n```\n````"
+        );
+    }
+
+    #[test]
+    fn truncates_long_files_and_skips_binary_ones() {
+        let cwd = tempfile::tempdir().unwrap();
+        let line = "x".repeat(99) + "\n";
+        std::fs::write(cwd.path().join("big.log"), line.repeat(1000)).unwrap();
+        std::fs::write(cwd.path().join("blob.bin"), b"\0\x01\x02").unwrap();
+
+        let contents = read_contents(&cwd.path().join("big.log")).unwrap();
+        assert!(contents.starts_with(&line));
+        assert!(contents.ends_with(&format!(
+            "{line}[... truncated: showing {} of 100000 bytes]\n",
+            MAX_FILE_BYTES / 100 * 100
+        )));
+
+        assert_eq!(
+            expand_file_references("see @blob.bin", cwd.path()),
+            "see @blob.bin"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Watches the files that shape a running session, such as `AGENTS.md`,
+//! `config.toml` and custom prompts, so that edits take effect without
+//! restarting Codex.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher;
+use tokio::sync::mpsc;
+
+/// Editors often write a file in several steps; the changes within this
+/// window are reported together.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Reports changes to a set of files, including their creation and deletion.
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    files: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Directories all of whose files are watched.
+    whole_dirs: Arc<Mutex<HashSet<PathBuf>>>,
+    dirs: HashSet<PathBuf>,
+}
+
+impl FileWatcher {
+    /// Watch `files`. The changed ones are sent in batches on the returned
+    /// receiver, which is closed when the watcher is dropped. Must be called
+    /// from within a Tokio runtime.
+    pub fn new(
+        files: impl IntoIterator<Item = PathBuf>,
+    ) -> notify::Result<(Self, mpsc::UnboundedReceiver<Vec<PathBuf>>)> {
+        let watched: Arc<Mutex<HashSet<PathBuf>>> = Arc::default();
+        let whole_dirs: Arc<Mutex<HashSet<PathBuf>>> = Arc::default();
+        let (raw_tx, mut raw_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let filter = Arc::clone(&watched);
+        let dir_filter = Arc::clone(&whole_dirs);
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            let (Ok(files), Ok(dirs)) = (filter.lock(), dir_filter.lock()) else {
+                return;
+            };
+            for path in event.paths {
+                if files.contains(&path) || path.parent().is_some_and(|dir| dirs.contains(dir)) {
+                    let _ = raw_tx.send(path);
+                }
+            }
+        })?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(first) = raw_rx.recv().await {
+                tokio::time::sleep(DEBOUNCE).await;
+                let mut changed = vec![first];
+                while let Ok(path) = raw_rx.try_recv() {
+                    changed.push(path);
+                }
+                changed.sort();
+                changed.dedup();
+                if tx.send(changed).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut file_watcher = Self {
+            watcher,
+            files: watched,
+            whole_dirs,
+            dirs: HashSet::new(),
+        };
+        file_watcher.watch(files)?;
+        Ok((file_watcher, rx))
+    }
+
+    /// Also watch `files`, whose directories must exist. Changes are
+    /// reported by canonical path.
+    pub fn watch(&mut self, files: impl IntoIterator<Item = PathBuf>) -> notify::Result<()> {
+        for file in files {
+            // Editors save by renaming a new file over the old one, which a
+            // watch on the file itself would not survive.
+            let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+                continue;
+            };
+            // Events name the canonical path on some platforms.
+            let dir = dir.canonicalize()?;
+            if !self.dirs.contains(&dir) {
+                self.watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+                self.dirs.insert(dir.clone());
+            }
+            if let Ok(mut files) = self.files.lock() {
+                files.insert(dir.join(name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Also watch every file in `dirs`, which must exist. Changes are
+    /// reported by canonical path.
+    pub fn watch_dirs(&mut self, dirs: impl IntoIterator<Item = PathBuf>) -> notify::Result<()> {
+        for dir in dirs {
+            let dir = dir.canonicalize()?;
+            if !self.dirs.contains(&dir) {
+                self.watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+                self.dirs.insert(dir.clone());
+            }
+            if let Ok(mut whole_dirs) = self.whole_dirs.lock() {
+                whole_dirs.insert(dir);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_changes_to_watched_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let agents = root.join("AGENTS.md");
+        let (_watcher, mut rx) = FileWatcher::new([agents.clone()]).unwrap();
+
+        std::fs::write(root.join("notes.md"), "unrelated").unwrap();
+        std::fs::write(&agents, "new instructions").unwrap();
+
+        let changed = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed, vec![agents]);
+    }
+
+    #[tokio::test]
+    async fn reports_changes_to_any_file_in_watched_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let prompts = root.join("prompts");
+        std::fs::create_dir(&prompts).unwrap();
+        let (mut watcher, mut rx) = FileWatcher::new(Vec::new()).unwrap();
+        watcher.watch_dirs([prompts.clone()]).unwrap();
+
+        std::fs::write(root.join("notes.md"), "unrelated").unwrap();
+        std::fs::write(prompts.join("review.md"), "Review $ARGUMENTS").unwrap();
+
+        let changed = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed, vec![prompts.join("review.md")]);
+    }
+}
@@ -1,12 +1,85 @@
+use tree_sitter::Node;
 use tree_sitter::Parser;
 use tree_sitter::Tree;
 use tree_sitter_bash::LANGUAGE as BASH;
 
+use crate::windows_shell::parse_windows_word_only_commands_sequence;
+use crate::windows_shell::windows_shell_script;
+
+/// A command made only of plain words, together with the `NAME=value`
+/// assignments it runs with: either written before it (`FOO=bar ls`) or
+/// passed through `env` (`env FOO=bar ls`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordOnlyCommand {
+    pub assignments: Vec<(String, String)>,
+    /// The command that actually runs, with `env` and its assignments removed.
+    pub words: Vec<String>,
+}
+
+impl WordOnlyCommand {
+    /// Split `words` into the command that runs and the assignments it gets,
+    /// unwrapping a leading `env NAME=value ...`. `env` invocations with
+    /// options, or without a command to run, are kept as they are.
+    pub fn from_words(words: Vec<String>) -> Self {
+        Self::new(Vec::new(), words)
+    }
+
+    fn new(mut assignments: Vec<(String, String)>, words: Vec<String>) -> Self {
+        if words.first().map(String::as_str) == Some("env") {
+            let env_assignments: Vec<(String, String)> = words[1..]
+                .iter()
+                .map_while(|word| parse_assignment_word(word))
+                .collect();
+            let rest = &words[1 + env_assignments.len()..];
+            if rest
+                .first()
+                .is_some_and(|program| !program.starts_with('-'))
+            {
+                assignments.extend(env_assignments);
+                return Self {
+                    assignments,
+                    words: rest.to_vec(),
+                };
+            }
+        }
+        Self { assignments, words }
+    }
+
+    /// True if the assignments cannot make the command run other code
+    /// (unlike e.g. `LD_PRELOAD` or `GIT_PAGER`).
+    pub fn has_only_harmless_assignments(&self) -> bool {
+        self.assignments.iter().all(|(name, _)| {
+            matches!(
+                name.as_str(),
+                "LANG" | "LC_ALL" | "NO_COLOR" | "TERM" | "TZ" | "COLUMNS" | "RUST_BACKTRACE"
+            ) || name.starts_with("LC_")
+        })
+    }
+}
+
+/// Parse a `NAME=value` word as accepted by `env`.
+fn parse_assignment_word(word: &str) -> Option<(String, String)> {
+    let (name, value) = word.split_once('=')?;
+    let mut chars = name.chars();
+    let valid_name = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid_name.then(|| (name.to_string(), value.to_string()))
+}
+
 pub fn is_known_safe_command(command: &[String]) -> bool {
-    if is_safe_to_call_with_exec(command) {
+    if is_safe_word_only_command(&WordOnlyCommand::from_words(command.to_vec())) {
         return true;
     }
 
+    if let Some((shell, script)) = windows_shell_script(command) {
+        return parse_windows_word_only_commands_sequence(shell, script).is_some_and(|commands| {
+            matches!(commands.as_slice(), [only]
+                    if is_safe_to_call_with_exec(only) || is_safe_windows_command(only))
+        });
+    }
+
     // TODO(mbolin): Also support safe commands that are piped together such
     // as `cat foo | wc -l`.
     matches!(
@@ -14,11 +87,15 @@ pub fn is_known_safe_command(command: &[String]) -> bool {
         [bash, flag, script]
             if bash == "bash"
             && flag == "-lc"
-            && try_parse_bash(script).and_then(|tree|
-                try_parse_single_word_only_command(&tree, script)).is_some_and(|parsed_bash_command| is_safe_to_call_with_exec(&parsed_bash_command))
+            && parse_bash_word_only_commands_sequence(script).is_some_and(|commands|
+                matches!(commands.as_slice(), [only] if is_safe_word_only_command(only)))
     )
 }
 
+fn is_safe_word_only_command(command: &WordOnlyCommand) -> bool {
+    command.has_only_harmless_assignments() && is_safe_to_call_with_exec(&command.words)
+}
+
 fn is_safe_to_call_with_exec(command: &[String]) -> bool {
     let cmd0 = command.first().map(String::as_str);
 
@@ -72,7 +149,30 @@ fn is_safe_to_call_with_exec(command: &[String]) -> bool {
     }
 }
 
-fn try_parse_bash(bash_lc_arg: &str) -> Option<Tree> {
+/// Read-only `cmd` builtins and PowerShell cmdlets (and their aliases). Like
+/// both shells, the command name is matched case-insensitively.
+fn is_safe_windows_command(command: &[String]) -> bool {
+    let Some(cmd0) = command.first() else {
+        return false;
+    };
+    matches!(
+        cmd0.to_ascii_lowercase().as_str(),
+        "dir"
+            | "type"
+            | "findstr"
+            | "get-childitem"
+            | "gci"
+            | "get-content"
+            | "gc"
+            | "get-location"
+            | "select-string"
+            | "sls"
+            | "test-path"
+            | "get-item"
+    )
+}
+
+pub(crate) fn try_parse_bash(bash_lc_arg: &str) -> Option<Tree> {
     let lang = BASH.into();
     let mut parser = Parser::new();
     #[expect(clippy::expect_used)]
@@ -82,8 +182,59 @@ fn try_parse_bash(bash_lc_arg: &str) -> Option<Tree> {
     parser.parse(bash_lc_arg, old_tree)
 }
 
+/// Parse a `bash -lc` script into its words if it is a single command made
+/// only of plain words (no pipes, redirections, substitutions, ...).
+pub(crate) fn parse_bash_word_only_command(script: &str) -> Option<Vec<String>> {
+    try_parse_bash(script).and_then(|tree| try_parse_single_word_only_command(&tree, script))
+}
+
+/// Parse a `bash -lc` script into the words of each of its commands if it is
+/// a sequence of plain-word commands (see
+/// [`try_parse_word_only_commands_sequence`]).
+pub(crate) fn parse_bash_word_only_commands_sequence(script: &str) -> Option<Vec<WordOnlyCommand>> {
+    try_parse_bash(script).and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))
+}
+
+/// The words of every command in a `bash -lc` script, however the commands
+/// are combined or nested (lists, pipes, subshells, substitutions, ...).
+/// Words that are not plain are kept as written. Meant for matching scripts
+/// against deny rules, where finding more commands than run is harmless.
+pub(crate) fn parse_bash_all_commands(script: &str) -> Vec<Vec<String>> {
+    let Some(tree) = try_parse_bash(script) else {
+        return Vec::new();
+    };
+    let mut commands = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "command" {
+            let mut cursor = node.walk();
+            let words: Vec<String> = node
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() != "variable_assignment")
+                .filter_map(|child| {
+                    let word = match child.kind() {
+                        "command_name" => child.named_child(0)?,
+                        _ => child,
+                    };
+                    plain_word_text(word, script)
+                        .or_else(|| word.utf8_text(script.as_bytes()).ok().map(str::to_owned))
+                })
+                .collect();
+            if !words.is_empty() {
+                commands.push(words);
+            }
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node<'_>> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    commands
+}
+
 /// If `tree` represents a single Bash command whose name and every argument is
 /// an ordinary `word`, return those words in order; otherwise, return `None`.
+/// Commands with variable assignments are rejected; use
+/// [`try_parse_word_only_commands_sequence`] to inspect those.
 ///
 /// `src` must be the exact source string that was parsed into `tree`, so we can
 /// extract the text for every node.
@@ -100,10 +251,60 @@ pub fn try_parse_single_word_only_command(tree: &Tree, src: &str) -> Option<Vec<
     }
 
     let cmd = root.named_child(0)?; // (command …)
+    let WordOnlyCommand { assignments, words } = parse_word_only_command_node(cmd, src)?;
+    assignments.is_empty().then_some(words)
+}
+
+/// If `tree` represents one or more Bash commands made only of ordinary words
+/// and joined by `&&`, `||`, `;` or `|`, return each command in order;
+/// otherwise, return `None`. Commands may be preceded by plain `NAME=value`
+/// assignments, which are returned separately from the command words.
+pub fn try_parse_word_only_commands_sequence(
+    tree: &Tree,
+    src: &str,
+) -> Option<Vec<WordOnlyCommand>> {
+    if tree.root_node().has_error() {
+        return None;
+    }
+
+    let mut commands = Vec::new();
+    collect_word_only_commands(tree.root_node(), src, &mut commands)?;
+    if commands.is_empty() {
+        return None;
+    }
+    Some(commands)
+}
+
+fn collect_word_only_commands(
+    node: Node<'_>,
+    src: &str,
+    commands: &mut Vec<WordOnlyCommand>,
+) -> Option<()> {
+    match node.kind() {
+        "command" => {
+            commands.push(parse_word_only_command_node(node, src)?);
+        }
+        "program" | "list" | "pipeline" => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                if child.is_named() {
+                    collect_word_only_commands(child, src, commands)?;
+                } else if !matches!(child.kind(), "&&" | "||" | ";" | "|" | "\n") {
+                    return None;
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+pub(crate) fn parse_word_only_command_node(cmd: Node<'_>, src: &str) -> Option<WordOnlyCommand> {
     if cmd.kind() != "command" {
         return None;
     }
 
+    let mut assignments = Vec::new();
     let mut words = Vec::new();
     let mut cursor = cmd.walk();
 
@@ -117,51 +318,60 @@ pub fn try_parse_single_word_only_command(tree: &Tree, src: &str) -> Option<Vec<
                 }
                 words.push(word_node.utf8_text(src.as_bytes()).ok()?.to_owned());
             }
-            // Positional‑argument word (allowed).
-            "word" | "number" => {
-                words.push(child.utf8_text(src.as_bytes()).ok()?.to_owned());
-            }
-            "string" => {
-                if child.child_count() == 3
-                    && child.child(0)?.kind() == "\""
-                    && child.child(1)?.kind() == "string_content"
-                    && child.child(2)?.kind() == "\""
-                {
-                    words.push(child.child(1)?.utf8_text(src.as_bytes()).ok()?.to_owned());
-                } else {
-                    // Anything else means the command is *not* plain words.
+            // `NAME=value` before the command name.
+            "variable_assignment" => {
+                let name = child.child_by_field_name("name")?;
+                if name.kind() != "variable_name" {
                     return None;
                 }
+                let value = match child.child_by_field_name("value") {
+                    Some(value) => plain_word_text(value, src)?,
+                    None => String::new(),
+                };
+                assignments.push((name.utf8_text(src.as_bytes()).ok()?.to_owned(), value));
             }
-            "concatenation" => {
-                // TODO: Consider things like `'ab\'a'`.
-                return None;
-            }
-            "raw_string" => {
-                // Raw string is a single word, but we need to strip the quotes.
-                let raw_string = child.utf8_text(src.as_bytes()).ok()?;
-                let stripped = raw_string
-                    .strip_prefix('\'')
-                    .and_then(|s| s.strip_suffix('\''));
-                if let Some(stripped) = stripped {
-                    words.push(stripped.to_owned());
-                } else {
-                    return None;
-                }
-            }
-            // Anything else means the command is *not* plain words.
-            _ => return None,
+            _ => words.push(plain_word_text(child, src)?),
         }
     }
 
-    Some(words)
+    Some(WordOnlyCommand::new(assignments, words))
 }
 
-/* ----------------------------------------------------------
-Example
----------------------------------------------------------- */
+/// The text of `node` if it is a plain word or a quoted string without
+/// expansions; otherwise, `None`.
+fn plain_word_text(node: Node<'_>, src: &str) -> Option<String> {
+    match node.kind() {
+        // Positional‑argument word (allowed).
+        "word" | "number" => Some(node.utf8_text(src.as_bytes()).ok()?.to_owned()),
+        "string" => {
+            if node.child_count() == 3
+                && node.child(0)?.kind() == "\""
+                && node.child(1)?.kind() == "string_content"
+                && node.child(2)?.kind() == "\""
+            {
+                Some(node.child(1)?.utf8_text(src.as_bytes()).ok()?.to_owned())
+            } else {
+                // Anything else means the command is *not* plain words.
+                None
+            }
+        }
+        "concatenation" => {
+            // TODO: Consider things like `'ab\'a'`.
+            None
+        }
+        "raw_string" => {
+            // Raw string is a single word, but we need to strip the quotes.
+            let raw_string = node.utf8_text(src.as_bytes()).ok()?;
+            raw_string
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .map(str::to_owned)
+        }
+        // Anything else means the command is *not* plain words.
+        _ => None,
+    }
+}
 
-/// Returns true if `arg` matches /^(\d+,)?\d+p$/
 fn is_valid_sed_n_arg(arg: Option<&str>) -> bool {
     // unwrap or bail
     let s = match arg {
@@ -295,6 +505,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn windows_shell_examples() {
+        assert!(is_known_safe_command(&vec_str(&[
+            "powershell.exe",
+            "-Command",
+            "Get-ChildItem -Recurse src"
+        ])));
+        assert!(is_known_safe_command(&vec_str(&[
+            "pwsh",
+            "-NoProfile",
+            "-c",
+            "git status"
+        ])));
+        assert!(is_known_safe_command(&vec_str(&[
+            "cmd",
+            "/c",
+            "type README.md"
+        ])));
+
+        assert!(!is_known_safe_command(&vec_str(&[
+            "powershell",
+            "-Command",
+            "Remove-Item -Recurse src"
+        ])));
+        assert!(!is_known_safe_command(&vec_str(&[
+            "powershell",
+            "-Command",
+            "Get-Content $profile"
+        ])));
+        assert!(!is_known_safe_command(&vec_str(&[
+            "cmd",
+            "/c",
+            "type a.txt > b.txt"
+        ])));
+    }
+
     #[test]
     fn test_try_parse_single_word_only_command() {
         let script_with_single_quoted_string = "sed -n '1,5p' file.txt";
@@ -331,4 +577,78 @@ mod tests {
             .unwrap();
         assert_eq!(vec!["grep", "-R", "Cargo.toml", "-n"], parsed_words);
     }
+
+    #[test]
+    fn test_try_parse_word_only_commands_sequence() {
+        let parse = |script: &str| {
+            try_parse_bash(script)
+                .and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))
+                .map(|commands| commands.into_iter().map(|c| c.words).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            parse("cargo build && cargo test --all; git status | head -1"),
+            Some(vec![
+                vec_str(&["cargo", "build"]),
+                vec_str(&["cargo", "test", "--all"]),
+                vec_str(&["git", "status"]),
+                vec_str(&["head", "-1"]),
+            ])
+        );
+        assert_eq!(parse("ls"), Some(vec![vec_str(&["ls"])]));
+
+        assert_eq!(parse("cargo test > out.txt"), None);
+        assert_eq!(parse("cargo test && echo $(whoami)"), None);
+        assert_eq!(parse("sleep 1 &"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn assignments_are_returned_separately() {
+        let assignment = |name: &str, value: &str| (name.to_string(), value.to_string());
+
+        assert_eq!(
+            parse_bash_word_only_commands_sequence("RUST_LOG=debug FOO='a b' cargo test"),
+            Some(vec![WordOnlyCommand {
+                assignments: vec![assignment("RUST_LOG", "debug"), assignment("FOO", "a b")],
+                words: vec_str(&["cargo", "test"]),
+            }])
+        );
+        assert_eq!(
+            parse_bash_word_only_commands_sequence("env LANG=C ls -l"),
+            Some(vec![WordOnlyCommand {
+                assignments: vec![assignment("LANG", "C")],
+                words: vec_str(&["ls", "-l"]),
+            }])
+        );
+        assert_eq!(
+            WordOnlyCommand::from_words(vec_str(&["env", "-i", "ls"])).words,
+            vec_str(&["env", "-i", "ls"])
+        );
+        assert_eq!(parse_bash_word_only_commands_sequence("FOO=$(id) ls"), None);
+        assert_eq!(parse_bash_word_only_command("FOO=bar ls"), None);
+
+        assert!(is_known_safe_command(&vec_str(&[
+            "bash",
+            "-lc",
+            "LANG=C ls"
+        ])));
+        assert!(is_known_safe_command(&vec_str(&[
+            "env",
+            "NO_COLOR=1",
+            "git",
+            "status"
+        ])));
+        assert!(!is_known_safe_command(&vec_str(&[
+            "bash",
+            "-lc",
+            "LD_PRELOAD=/tmp/x.so ls"
+        ])));
+        assert!(!is_known_safe_command(&vec_str(&[
+            "env",
+            "GIT_PAGER=sh",
+            "git",
+            "log"
+        ])));
+    }
 }
@@ -5,24 +5,45 @@
 // the TUI or the tracing stack).
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+mod anthropic;
+pub mod approval_rules;
+mod attach_image;
+mod background_process;
 mod chat_completions;
+mod checkpoints;
 mod client;
 mod client_common;
 pub mod codex;
 pub use codex::Codex;
 pub mod codex_wrapper;
+pub mod command_wrapper;
+mod compact;
 pub mod config;
 pub mod config_profile;
 pub mod config_types;
 mod conversation_history;
+pub mod crash_report;
+pub mod custom_prompts;
+pub mod debug_bundle;
 pub mod error;
 pub mod exec;
 pub mod exec_env;
+mod fetch_url;
+mod file_references;
+pub mod file_watcher;
 mod flags;
 mod is_safe_command;
+pub mod mcp_auth;
 mod mcp_connection_manager;
+pub use mcp_connection_manager::McpServerInventory;
+pub use mcp_connection_manager::inspect_mcp_server;
+mod mcp_prompts;
+mod mcp_resources;
 mod mcp_tool_call;
+pub mod mcp_tool_names;
 mod message_history;
+pub mod model_catalog;
+pub mod model_pricing;
 mod model_provider_info;
 pub use model_provider_info::ModelProviderInfo;
 pub use model_provider_info::WireApi;
@@ -32,11 +53,29 @@ pub use models::{
 };
 pub mod openai_api_key;
 mod openai_tools;
+mod plan_tool;
 mod project_doc;
+mod project_init;
 pub mod protocol;
-mod rollout;
+mod pty_exec;
+mod redaction;
+pub mod review;
 mod safety;
+pub mod sampling;
+pub mod sandbox_backend;
+pub mod session_store;
+mod sub_agent;
+pub mod telemetry;
+mod token_budget;
+mod tool_audit;
+pub mod tool_middleware;
+mod tool_output;
+mod tool_restrictions;
+pub use tool_restrictions::find_project_config;
+pub mod transcript_export;
 mod user_notification;
 pub mod util;
+mod web_search;
+mod windows_shell;
 
 pub use client_common::{Prompt, model_supports_reasoning_summaries};
@@ -0,0 +1,467 @@
+//! OAuth credentials for MCP servers that are reached over `streamable_http`.
+//!
+//! `codex mcp login <server>` runs the MCP authorization-code flow (with PKCE
+//! and dynamic client registration) against the server's authorization
+//! server and persists the resulting tokens in `~/.codex/mcp_auth.json`. When
+//! the [`crate::mcp_connection_manager::McpConnectionManager`] later starts
+//! that server it looks up the stored credentials, refreshes the access token
+//! if it has expired, and injects it into the server process.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use base64::Engine;
+use rand::Rng;
+use reqwest::Url;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+/// Filename that stores MCP OAuth credentials inside `~/.codex`.
+const MCP_AUTH_FILENAME: &str = "mcp_auth.json";
+
+/// Environment variable used to hand the access token to the server process.
+pub const MCP_ACCESS_TOKEN_ENV_VAR: &str = "MCP_ACCESS_TOKEN";
+
+/// Refresh tokens slightly before they actually expire to avoid races.
+const EXPIRY_SKEW_SECS: u64 = 60;
+
+/// How long `codex mcp login` waits for the browser redirect.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Credentials for a single MCP server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct McpOAuthCredentials {
+    /// URL of the `streamable_http` endpoint the tokens were issued for.
+    pub server_url: String,
+    pub client_id: String,
+    pub token_endpoint: String,
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Unix timestamp (seconds) after which `access_token` is no longer valid.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+impl McpOAuthCredentials {
+    fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now_secs() + EXPIRY_SKEW_SECS >= expires_at,
+            None => false,
+        }
+    }
+}
+
+/// Contents of `~/.codex/mcp_auth.json`, keyed by server name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct McpAuthStore {
+    #[serde(default)]
+    pub servers: HashMap<String, McpOAuthCredentials>,
+}
+
+impl McpAuthStore {
+    pub fn path(codex_home: &Path) -> PathBuf {
+        codex_home.join(MCP_AUTH_FILENAME)
+    }
+
+    /// Load the store, returning an empty one if the file does not exist.
+    pub fn load(codex_home: &Path) -> Result<Self> {
+        let path = Self::path(codex_home);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Persist the store with owner-only permissions.
+    pub fn save(&self, codex_home: &Path) -> Result<()> {
+        std::fs::create_dir_all(codex_home)?;
+        let mut options = OpenOptions::new();
+        options.truncate(true).write(true).create(true);
+        #[cfg(unix)]
+        {
+            options.mode(0o600);
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        let mut file = options.open(Self::path(codex_home))?;
+        file.write_all(json.as_bytes())?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Return a valid access token for `server_name`, refreshing (and persisting)
+/// it first if it has expired. Returns `Ok(None)` when no credentials exist,
+/// and an error when they were issued for another URL than `server_url`, so
+/// that a token is never handed to a server it was not meant for.
+pub async fn access_token_for_server(
+    codex_home: &Path,
+    server_name: &str,
+    server_url: &str,
+) -> Result<Option<String>> {
+    let mut store = McpAuthStore::load(codex_home)?;
+    let Some(creds) = store.servers.get(server_name).cloned() else {
+        return Ok(None);
+    };
+    if creds.server_url != server_url {
+        return Err(anyhow!(
+            "credentials for `{server_name}` were issued for `{}`, not `{server_url}`; \
+             run `codex mcp login {server_name}`",
+            creds.server_url
+        ));
+    }
+    if !creds.is_expired() {
+        return Ok(Some(creds.access_token));
+    }
+
+    let refresh_token = creds.refresh_token.clone().ok_or_else(|| {
+        anyhow!("access token for `{server_name}` expired; run `codex mcp login {server_name}`")
+    })?;
+    let response = request_token(
+        &creds.token_endpoint,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+            ("client_id", &creds.client_id),
+        ],
+    )
+    .await?;
+    let refreshed = McpOAuthCredentials {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token.or(Some(refresh_token)),
+        expires_at: response.expires_in.map(|secs| now_secs() + secs),
+        ..creds
+    };
+    let token = refreshed.access_token.clone();
+    store.servers.insert(server_name.to_string(), refreshed);
+    store.save(codex_home)?;
+    Ok(Some(token))
+}
+
+/// An authorization-code flow that is waiting for the user to approve access
+/// in the browser.
+pub struct PendingMcpLogin {
+    server_name: String,
+    server_url: String,
+    client_id: String,
+    token_endpoint: String,
+    redirect_uri: String,
+    code_verifier: String,
+    state: String,
+    listener: TcpListener,
+    /// URL the user must open to authorize Codex.
+    pub authorize_url: String,
+}
+
+/// Discover the authorization server for `server_url`, register a client and
+/// bind the local redirect listener. The caller is responsible for showing
+/// [`PendingMcpLogin::authorize_url`] to the user before calling
+/// [`PendingMcpLogin::complete`].
+pub async fn begin_mcp_login(server_name: &str, server_url: &str) -> Result<PendingMcpLogin> {
+    let metadata = discover_metadata(server_url).await?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let redirect_uri = format!("http://127.0.0.1:{port}/callback");
+
+    let client_id = register_client(&metadata, &redirect_uri).await?;
+
+    let code_verifier = random_urlsafe_string(32);
+    let code_challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .encode(Sha256::digest(code_verifier.as_bytes()));
+    let state = random_urlsafe_string(16);
+
+    let mut authorize_url = Url::parse(&metadata.authorization_endpoint)?;
+    authorize_url
+        .query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &client_id)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("code_challenge", &code_challenge)
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("state", &state)
+        .append_pair("resource", server_url);
+
+    Ok(PendingMcpLogin {
+        server_name: server_name.to_string(),
+        server_url: server_url.to_string(),
+        client_id,
+        token_endpoint: metadata.token_endpoint,
+        redirect_uri,
+        code_verifier,
+        state,
+        listener,
+        authorize_url: authorize_url.to_string(),
+    })
+}
+
+impl PendingMcpLogin {
+    /// Wait for the browser redirect, exchange the authorization code and
+    /// store the resulting tokens under `codex_home`.
+    pub async fn complete(self, codex_home: &Path) -> Result<()> {
+        let code = tokio::time::timeout(LOGIN_TIMEOUT, self.wait_for_code())
+            .await
+            .map_err(|_| anyhow!("timed out waiting for the OAuth redirect"))??;
+
+        let response = request_token(
+            &self.token_endpoint,
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", &code),
+                ("redirect_uri", &self.redirect_uri),
+                ("client_id", &self.client_id),
+                ("code_verifier", &self.code_verifier),
+            ],
+        )
+        .await?;
+
+        let mut store = McpAuthStore::load(codex_home)?;
+        store.servers.insert(
+            self.server_name.clone(),
+            McpOAuthCredentials {
+                server_url: self.server_url.clone(),
+                client_id: self.client_id.clone(),
+                token_endpoint: self.token_endpoint.clone(),
+                access_token: response.access_token,
+                refresh_token: response.refresh_token,
+                expires_at: response.expires_in.map(|secs| now_secs() + secs),
+            },
+        );
+        store.save(codex_home)
+    }
+
+    /// Accept connections on the redirect listener until the `/callback`
+    /// request arrives and return the authorization code it carries.
+    async fn wait_for_code(&self) -> Result<String> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+            let mut buf = vec![0u8; 8192];
+            let n = stream.read(&mut buf).await?;
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let Some(target) = request
+                .lines()
+                .next()
+                .and_then(|line| line.split_whitespace().nth(1))
+            else {
+                continue;
+            };
+            let url = Url::parse(&format!("http://127.0.0.1{target}"))?;
+            if url.path() != "/callback" {
+                stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                    .await?;
+                continue;
+            }
+
+            let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+            let result = if let Some(error) = params.get("error") {
+                Err(anyhow!("authorization failed: {error}"))
+            } else if params.get("state") != Some(&self.state) {
+                Err(anyhow!("OAuth state mismatch"))
+            } else {
+                params
+                    .get("code")
+                    .cloned()
+                    .ok_or_else(|| anyhow!("redirect is missing the authorization code"))
+            };
+
+            let body = match &result {
+                Ok(_) => "Login successful. You can close this window and return to Codex.",
+                Err(_) => "Login failed. Check the terminal for details.",
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await?;
+            return result;
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct AuthorizationServerMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ClientRegistrationResponse {
+    client_id: String,
+}
+
+/// Fetch RFC 8414 metadata from the origin of `server_url`, falling back to
+/// the default endpoint layout described by the MCP authorization spec.
+async fn discover_metadata(server_url: &str) -> Result<AuthorizationServerMetadata> {
+    let base = Url::parse(server_url).with_context(|| format!("invalid URL `{server_url}`"))?;
+    let well_known = base.join("/.well-known/oauth-authorization-server")?;
+
+    let response = reqwest::Client::new()
+        .get(well_known.clone())
+        .header("MCP-Protocol-Version", mcp_types::MCP_SCHEMA_VERSION)
+        .send()
+        .await?;
+    if response.status().is_success() {
+        return Ok(response.json().await?);
+    }
+
+    Ok(AuthorizationServerMetadata {
+        authorization_endpoint: base.join("/authorize")?.to_string(),
+        token_endpoint: base.join("/token")?.to_string(),
+        registration_endpoint: Some(base.join("/register")?.to_string()),
+    })
+}
+
+/// Dynamically register Codex as a public OAuth client (RFC 7591).
+async fn register_client(
+    metadata: &AuthorizationServerMetadata,
+    redirect_uri: &str,
+) -> Result<String> {
+    let registration_endpoint = metadata.registration_endpoint.as_deref().ok_or_else(|| {
+        anyhow!("authorization server does not support dynamic client registration")
+    })?;
+    let response = reqwest::Client::new()
+        .post(registration_endpoint)
+        .json(&serde_json::json!({
+            "client_name": "Codex CLI",
+            "redirect_uris": [redirect_uri],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "response_types": ["code"],
+            "token_endpoint_auth_method": "none",
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("client registration failed: {}", response.status()));
+    }
+    let registration: ClientRegistrationResponse = response.json().await?;
+    Ok(registration.client_id)
+}
+
+async fn request_token(token_endpoint: &str, form: &[(&str, &str)]) -> Result<TokenResponse> {
+    let response = reqwest::Client::new()
+        .post(token_endpoint)
+        .form(form)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("token request failed: {}", response.status()));
+    }
+    Ok(response.json().await?)
+}
+
+fn random_urlsafe_string(num_bytes: usize) -> String {
+    let mut bytes = vec![0u8; num_bytes];
+    rand::rng().fill(bytes.as_mut_slice());
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::TempDir;
+
+    fn creds(expires_at: Option<u64>) -> McpOAuthCredentials {
+        McpOAuthCredentials {
+            server_url: "https://example.com/mcp".to_string(),
+            client_id: "client".to_string(),
+            token_endpoint: "https://example.com/token".to_string(),
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn store_round_trips_through_disk() {
+        let codex_home = TempDir::new().unwrap();
+        assert_eq!(
+            McpAuthStore::load(codex_home.path()).unwrap(),
+            McpAuthStore::default()
+        );
+
+        let mut store = McpAuthStore::default();
+        store.servers.insert("docs".to_string(), creds(None));
+        store.save(codex_home.path()).unwrap();
+
+        assert_eq!(McpAuthStore::load(codex_home.path()).unwrap(), store);
+    }
+
+    #[tokio::test]
+    async fn unexpired_token_is_returned_without_refresh() {
+        let codex_home = TempDir::new().unwrap();
+        let mut store = McpAuthStore::default();
+        store
+            .servers
+            .insert("docs".to_string(), creds(Some(now_secs() + 3600)));
+        store.save(codex_home.path()).unwrap();
+
+        let token = access_token_for_server(codex_home.path(), "docs", "https://example.com/mcp")
+            .await
+            .unwrap();
+        assert_eq!(token.as_deref(), Some("access"));
+        assert_eq!(
+            access_token_for_server(codex_home.path(), "other", "https://example.com/mcp")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn token_is_refused_for_another_url() {
+        let codex_home = TempDir::new().unwrap();
+        let mut store = McpAuthStore::default();
+        store
+            .servers
+            .insert("docs".to_string(), creds(Some(now_secs() + 3600)));
+        store.save(codex_home.path()).unwrap();
+
+        let err = access_token_for_server(codex_home.path(), "docs", "https://evil.example/mcp")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "credentials for `docs` were issued for `https://example.com/mcp`, not \
+             `https://evil.example/mcp`; run `codex mcp login docs`"
+        );
+    }
+}
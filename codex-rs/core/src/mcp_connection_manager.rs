@@ -3,61 +3,231 @@
 //! The [`McpConnectionManager`] owns one [`codex_mcp_client::McpClient`] per
 //! configured server (keyed by the *server name*). It offers convenience
 //! helpers to query the available tools across *all* servers and returns them
-//! in a single aggregated map using the fully-qualified tool name
-//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//! in a single aggregated map keyed by the qualified tool name (see
+//! [`crate::mcp_tool_names`]).
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::Weak;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use async_channel::Sender;
 use codex_mcp_client::McpClient;
 use mcp_types::ClientCapabilities;
+use mcp_types::GetPromptResult;
 use mcp_types::Implementation;
+use mcp_types::InitializeResult;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::Prompt;
+use mcp_types::ReadResourceResult;
+use mcp_types::Resource;
 use mcp_types::Tool;
+use tokio::sync::RwLock;
+use tokio::sync::mpsc;
 use tokio::task::JoinSet;
 use tracing::info;
+use tracing::warn;
 
 use crate::config_types::McpServerConfig;
+use crate::config_types::ToolsConfig;
+use crate::mcp_auth::MCP_ACCESS_TOKEN_ENV_VAR;
+use crate::mcp_auth::access_token_for_server;
+use crate::mcp_tool_names::qualify_tool_names;
+use crate::protocol::BackgroundEventEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::McpPromptInfo;
+use crate::protocol::McpServerStatus;
+use crate::protocol::McpStartupEvent;
+use crate::tool_output::validate_tool_output;
 
-/// Delimiter used to separate the server name from the tool name in a fully
-/// qualified tool name.
-///
-/// OpenAI requires tool names to conform to `^[a-zA-Z0-9_-]+$`, so we must
-/// choose a delimiter from this character set.
-const MCP_TOOL_NAME_DELIMITER: &str = "__OAI_CODEX_MCP__";
+/// Time a server gets to start, initialize and list its tools and prompts
+/// when its config has no `startup_timeout_sec`.
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time the session waits for the servers before it starts without the ones
+/// that are not ready yet.
+const STARTUP_WAIT: Duration = Duration::from_secs(2);
+
+/// Restarts of a server that exits mid-session when its config has no
+/// `max_restarts`.
+const DEFAULT_MAX_RESTARTS: u32 = 3;
+
+/// Upper bound of the delay before a restart, which doubles from one second.
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// Timeout for the `initialize` request.
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Timeout for the `tools/list` request.
 const LIST_TOOLS_TIMEOUT: Duration = Duration::from_secs(10);
 
-/// Map that holds a startup error for every MCP server that could **not** be
-/// spawned successfully.
-pub type ClientStartErrors = HashMap<String, anyhow::Error>;
+/// Timeout for the `resources/list` and `resources/read` requests.
+const RESOURCE_TIMEOUT: Duration = Duration::from_secs(30);
 
-fn fully_qualified_tool_name(server: &str, tool: &str) -> String {
-    format!("{server}{MCP_TOOL_NAME_DELIMITER}{tool}")
-}
+/// Timeout for the `prompts/list` and `prompts/get` requests.
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(10);
 
-pub(crate) fn try_parse_fully_qualified_tool_name(fq_name: &str) -> Option<(String, String)> {
-    let (server, tool) = fq_name.split_once(MCP_TOOL_NAME_DELIMITER)?;
-    if server.is_empty() || tool.is_empty() {
-        return None;
-    }
-    Some((server.to_string(), tool.to_string()))
+/// Pages of `resources/list` or `prompts/list` fetched at most from one
+/// server.
+const MAX_RESOURCE_PAGES: usize = 20;
+
+/// The client of a running server. Its supervisor swaps in a new client when
+/// the server is restarted.
+struct ServerHandle {
+    client: RwLock<Arc<McpClient>>,
 }
 
-/// A thin wrapper around a set of running [`McpClient`] instances.
+/// A thin wrapper around a set of running [`McpClient`] instances. Servers
+/// that are still starting when [`McpConnectionManager::new`] returns are
+/// added once they are ready.
 #[derive(Default)]
 pub(crate) struct McpConnectionManager {
+    servers: Arc<std::sync::RwLock<Servers>>,
+}
+
+#[derive(Default)]
+struct Servers {
     /// Server-name -> client instance.
     ///
     /// The server name originates from the keys of the `mcp_servers` map in
     /// the user configuration.
-    clients: HashMap<String, std::sync::Arc<McpClient>>,
+    clients: HashMap<String, Arc<ServerHandle>>,
+
+    /// Configs of the running servers, for qualifying tool names.
+    configs: HashMap<String, McpServerConfig>,
+
+    /// Tools offered to the model as (server, tool) pairs.
+    offered_tools: Vec<(String, Tool)>,
+
+    /// Qualified tool name -> server name and tool instance.
+    tools: HashMap<String, (String, Tool)>,
+
+    /// Servers whose tools are offered in strict mode (`strict_tools`).
+    strict_servers: HashSet<String>,
+
+    /// Servers that advertise the `resources` capability.
+    resource_servers: HashSet<String>,
+
+    /// Servers still starting after [`McpConnectionManager::new`] returned.
+    starting: HashSet<String>,
+
+    /// Prompt templates of the servers that advertise the `prompts`
+    /// capability, sorted by server and name.
+    prompts: Vec<McpPromptInfo>,
+}
+
+/// What the servers need to be added to the manager once they are ready.
+struct StartupContext {
+    tools_config: ToolsConfig,
+    codex_home: PathBuf,
+    tx_event: Sender<Event>,
+    sub_id: String,
+}
+
+/// Outcome of starting one server: its name, config, the time it took and
+/// the started server.
+type Startup = (String, McpServerConfig, Duration, Result<StartedServer>);
+
+impl Servers {
+    /// Add a started server, supervised from now on, and offer its tools.
+    /// Returns the number of tools offered to the model.
+    fn attach(
+        &mut self,
+        server_name: String,
+        cfg: McpServerConfig,
+        started: StartedServer,
+        ctx: &StartupContext,
+    ) -> usize {
+        let mut offered = 0;
+        for tool in started.tools {
+            if ctx
+                .tools_config
+                .is_mcp_tool_allowed(&server_name, &tool.name)
+            {
+                self.offered_tools.push((server_name.clone(), tool));
+                offered += 1;
+            }
+        }
+        if cfg.strict_tools {
+            self.strict_servers.insert(server_name.clone());
+        }
+        if started.resources {
+            self.resource_servers.insert(server_name.clone());
+        }
+        self.prompts
+            .extend(started.prompts.into_iter().map(|prompt| McpPromptInfo {
+                server: server_name.clone(),
+                prompt,
+            }));
+        // Servers finish in any order; keep prompts grouped by server.
+        self.prompts.sort_by(|a, b| a.server.cmp(&b.server));
+
+        let exited = Box::pin(started.client.wait_for_exit());
+        let handle = Arc::new(ServerHandle {
+            client: RwLock::new(Arc::new(started.client)),
+        });
+        tokio::spawn(supervise_server(
+            server_name.clone(),
+            cfg.clone(),
+            ctx.codex_home.clone(),
+            Arc::downgrade(&handle),
+            exited,
+            ctx.tx_event.clone(),
+            ctx.sub_id.clone(),
+        ));
+        self.clients.insert(server_name.clone(), handle);
+        self.configs.insert(server_name, cfg);
+        self.qualify_tools();
+        offered
+    }
 
-    /// Fully qualified tool name -> tool instance.
-    tools: HashMap<String, Tool>,
+    /// Record the outcome of starting a server as its status.
+    fn record(&mut self, startup: Startup, ctx: &StartupContext) -> McpServerStatus {
+        let (server_name, cfg, elapsed, started) = startup;
+        self.starting.remove(&server_name);
+        let result = match started {
+            Ok(started) => Ok(self.attach(server_name.clone(), cfg, started, ctx)),
+            Err(e) => Err(format!("{e:#}")),
+        };
+        McpServerStatus {
+            server: server_name,
+            startup_ms: elapsed.as_millis() as u64,
+            result,
+        }
+    }
+
+    /// Name the offered tools anew, as a server that starts late can clash
+    /// with the names of the others.
+    fn qualify_tools(&mut self) {
+        let tool_names: Vec<(String, String)> = self
+            .offered_tools
+            .iter()
+            .map(|(server, tool)| (server.clone(), tool.name.clone()))
+            .collect();
+        let mut offered_tools: HashMap<(String, String), Tool> = self
+            .offered_tools
+            .iter()
+            .map(|(server, tool)| ((server.clone(), tool.name.clone()), tool.clone()))
+            .collect();
+        self.tools.clear();
+        for (name, key) in qualify_tool_names(&tool_names, &self.configs) {
+            if let Some(tool) = offered_tools.remove(&key) {
+                self.tools.insert(name, (key.0, tool));
+            }
+        }
+    }
 }
 
 impl McpConnectionManager {
@@ -67,144 +237,528 @@ impl McpConnectionManager {
     ///   are human-readable server identifiers and *values* are the spawn
     ///   instructions.
     ///
-    /// Servers that fail to start are reported in `ClientStartErrors`: the
-    /// user should be informed about these errors.
+    /// Tools that `tools_config` restricts are not offered to the model.
+    ///
+    /// All servers start concurrently, each bounded by its
+    /// `startup_timeout_sec`. This waits at most [`STARTUP_WAIT`] for them:
+    /// servers that take longer are added when they are ready, and their
+    /// status is then reported in an `McpStartup` event with id `sub_id` on
+    /// `tx_event`. The returned statuses (sorted by server) say which of the
+    /// other servers are ready and why the others failed: the user should be
+    /// informed about these failures.
+    ///
+    /// A server that exits mid-session is restarted with backoff, up to its
+    /// `max_restarts`; each restart is reported as a background event with
+    /// id `sub_id` on `tx_event`.
+    ///
+    /// OAuth credentials stored under `codex_home` by `codex mcp login` are
+    /// injected into the environment of the corresponding server.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
-    ) -> Result<(Self, ClientStartErrors)> {
+        tools_config: &ToolsConfig,
+        codex_home: &Path,
+        tx_event: Sender<Event>,
+        sub_id: &str,
+    ) -> Result<(Self, Vec<McpServerStatus>)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
-            return Ok((Self::default(), ClientStartErrors::default()));
+            return Ok((Self::default(), Vec::new()));
         }
 
         // Launch all configured servers concurrently.
         let mut join_set = JoinSet::new();
+        let mut starting = HashSet::new();
 
-        for (server_name, cfg) in mcp_servers {
+        for (server_name, cfg) in mcp_servers.into_iter().filter(|(_, cfg)| cfg.enabled) {
             // TODO: Verify server name: require `^[a-zA-Z0-9_-]+$`?
+            let codex_home = codex_home.to_path_buf();
+            starting.insert(server_name.clone());
             join_set.spawn(async move {
-                let McpServerConfig { command, args, env } = cfg;
-                let client_res = McpClient::new_stdio_client(command, args, env).await;
-                match client_res {
-                    Ok(client) => {
-                        // Initialize the client.
-                        let params = mcp_types::InitializeRequestParams {
-                            capabilities: ClientCapabilities {
-                                experimental: None,
-                                roots: None,
-                                sampling: None,
-                            },
-                            client_info: Implementation {
-                                name: "codex-mcp-client".to_owned(),
-                                version: env!("CARGO_PKG_VERSION").to_owned(),
-                            },
-                            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                        };
-                        let initialize_notification_params = None;
-                        let timeout = Some(Duration::from_secs(10));
-                        match client
-                            .initialize(params, initialize_notification_params, timeout)
-                            .await
-                        {
-                            Ok(_response) => (server_name, Ok(client)),
-                            Err(e) => (server_name, Err(e)),
-                        }
-                    }
-                    Err(e) => (server_name, Err(e.into())),
-                }
+                let start = Instant::now();
+                let startup_timeout = cfg
+                    .startup_timeout_sec
+                    .map(Duration::from_secs)
+                    .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+                let started = match tokio::time::timeout(
+                    startup_timeout,
+                    start_server(&server_name, cfg.clone(), &codex_home),
+                )
+                .await
+                {
+                    Ok(started) => started,
+                    Err(_) => Err(anyhow!(
+                        "did not start within {}s (startup_timeout_sec)",
+                        startup_timeout.as_secs()
+                    )),
+                };
+                (server_name, cfg, start.elapsed(), started)
             });
         }
 
-        let mut clients: HashMap<String, std::sync::Arc<McpClient>> =
-            HashMap::with_capacity(join_set.len());
-        let mut errors = ClientStartErrors::new();
+        let manager = Self::default();
+        let ctx = StartupContext {
+            tools_config: tools_config.clone(),
+            codex_home: codex_home.to_path_buf(),
+            tx_event,
+            sub_id: sub_id.to_string(),
+        };
+        let mut statuses = Vec::with_capacity(join_set.len());
+        let wait = tokio::time::sleep(STARTUP_WAIT);
+        tokio::pin!(wait);
+        loop {
+            let startup = tokio::select! {
+                res = join_set.join_next() => match res {
+                    Some(res) => res?, // JoinError propagation
+                    None => break,
+                },
+                _ = &mut wait => break,
+            };
+            statuses.push(manager.write().record(startup, &ctx));
+        }
 
-        while let Some(res) = join_set.join_next().await {
-            let (server_name, client_res) = res?; // JoinError propagation
+        if !join_set.is_empty() {
+            starting.retain(|server| !statuses.iter().any(|status| status.server == *server));
+            info!("MCP servers still starting: {starting:?}");
+            manager.write().starting = starting;
+            tokio::spawn(add_late_servers(
+                join_set,
+                Arc::downgrade(&manager.servers),
+                ctx,
+            ));
+        }
 
-            match client_res {
-                Ok(client) => {
-                    clients.insert(server_name, std::sync::Arc::new(client));
-                }
-                Err(e) => {
-                    errors.insert(server_name, e);
-                }
-            }
+        statuses.sort_by(|a, b| a.server.cmp(&b.server));
+        {
+            let servers = manager.read();
+            info!(
+                "aggregated {} tools from {} servers",
+                servers.tools.len(),
+                servers.clients.len()
+            );
         }
 
-        let tools = list_all_tools(&clients).await?;
+        Ok((manager, statuses))
+    }
 
-        Ok((Self { clients, tools }, errors))
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Servers> {
+        self.servers.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Servers> {
+        self.servers.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The current client of `server`.
+    async fn client(&self, server: &str) -> Result<Arc<McpClient>> {
+        let handle = {
+            let servers = self.read();
+            match servers.clients.get(server) {
+                Some(handle) => handle.clone(),
+                None if servers.starting.contains(server) => {
+                    return Err(anyhow!("MCP server '{server}' is still starting"));
+                }
+                None => return Err(anyhow!("unknown MCP server '{server}'")),
+            }
+        };
+        Ok(handle.client.read().await.clone())
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
-    /// fully-qualified name for the tool.
+    /// qualified name of the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
-        self.tools.clone()
+        self.read()
+            .tools
+            .iter()
+            .map(|(name, (_, tool))| (name.clone(), tool.clone()))
+            .collect()
+    }
+
+    /// The server and tool name behind the qualified name of an MCP tool.
+    pub fn resolve_tool(&self, name: &str) -> Option<(String, String)> {
+        self.read()
+            .tools
+            .get(name)
+            .map(|(server, tool)| (server.clone(), tool.name.clone()))
+    }
+
+    /// Qualified names of the tools to offer in strict mode.
+    pub fn strict_tool_names(&self) -> HashSet<String> {
+        let servers = self.read();
+        servers
+            .tools
+            .iter()
+            .filter(|(_, (server, _))| servers.strict_servers.contains(server))
+            .map(|(name, _)| name.clone())
+            .collect()
     }
 
-    /// Invoke the tool indicated by the (server, tool) pair.
+    /// Invoke the tool indicated by the (server, tool) pair. Progress the
+    /// server reports for the call is sent to `progress_tx`. Results of tools
+    /// with an output schema are validated against it.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        progress_tx: Option<mpsc::Sender<ProgressNotificationParams>>,
     ) -> Result<mcp_types::CallToolResult> {
-        let client = self
-            .clients
-            .get(server)
-            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?
-            .clone();
+        // Also rejects tools hidden by the `[tools]` config.
+        let Some((_, offered)) = self
+            .read()
+            .tools
+            .values()
+            .find(|(tool_server, offered)| tool_server == server && offered.name == tool)
+            .cloned()
+        else {
+            return Err(anyhow!("tool `{server}/{tool}` is not available"));
+        };
+        let client = self.client(server).await?;
+
+        let result = client
+            .call_tool_with_progress(tool.to_string(), arguments, timeout, progress_tx)
+            .await
+            .with_context(|| format!("tool call failed for `{server}/{tool}`"))?;
+        if let Some(output_schema) = &offered.output_schema {
+            validate_tool_output(tool, output_schema, &result)?;
+        }
+        Ok(result)
+    }
+
+    /// The prompt templates of the servers that are ready.
+    pub fn list_prompts(&self) -> Vec<McpPromptInfo> {
+        self.read().prompts.clone()
+    }
 
+    /// Render the prompt `name` of `server` with `arguments`.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<GetPromptResult> {
+        let client = self.client(server).await?;
+        let arguments = (!arguments.is_empty()).then(|| serde_json::json!(arguments));
         client
-            .call_tool(tool.to_string(), arguments, timeout)
+            .get_prompt(name.to_string(), arguments, Some(PROMPT_TIMEOUT))
             .await
-            .with_context(|| format!("tool call failed for `{server}/{tool}`"))
+            .with_context(|| format!("prompts/get failed for `{server}/{name}`"))
     }
-}
 
-/// Query every server for its available tools and return a single map that
-/// contains **all** tools. Each key is the fully-qualified name for the tool.
-pub async fn list_all_tools(
-    clients: &HashMap<String, std::sync::Arc<McpClient>>,
-) -> Result<HashMap<String, Tool>> {
-    let mut join_set = JoinSet::new();
-
-    // Spawn one task per server so we can query them concurrently. This
-    // keeps the overall latency roughly at the slowest server instead of
-    // the cumulative latency.
-    for (server_name, client) in clients {
-        let server_name_cloned = server_name.clone();
-        let client_clone = client.clone();
-        join_set.spawn(async move {
-            let res = client_clone
-                .list_tools(None, Some(LIST_TOOLS_TIMEOUT))
-                .await;
-            (server_name_cloned, res)
-        });
+    /// Whether any server offers resources to read, or may once it has
+    /// started.
+    pub fn has_resources(&self) -> bool {
+        let servers = self.read();
+        !servers.resource_servers.is_empty() || !servers.starting.is_empty()
     }
 
-    let mut aggregated: HashMap<String, Tool> = HashMap::with_capacity(join_set.len());
+    /// The resources of `server`, or of every server that offers resources,
+    /// as (server, resource) pairs sorted by server.
+    pub async fn list_resources(&self, server: Option<&str>) -> Result<Vec<(String, Resource)>> {
+        let mut servers: Vec<String> = {
+            let servers = self.read();
+            match server {
+                Some(server) => vec![
+                    servers
+                        .resource_servers
+                        .get(server)
+                        .ok_or_else(|| anyhow!("MCP server `{server}` does not offer resources"))?
+                        .clone(),
+                ],
+                None => servers.resource_servers.iter().cloned().collect(),
+            }
+        };
+        servers.sort();
+
+        let mut resources = Vec::new();
+        for server in servers {
+            let client = self.client(&server).await?;
+            for resource in list_server_resources(&client)
+                .await
+                .with_context(|| format!("resources/list failed for `{server}`"))?
+            {
+                resources.push((server.clone(), resource));
+            }
+        }
+        Ok(resources)
+    }
+
+    /// Read the resource at `uri` from `server`.
+    pub async fn read_resource(&self, server: &str, uri: &str) -> Result<ReadResourceResult> {
+        if !self.read().resource_servers.contains(server) {
+            return Err(anyhow!("MCP server `{server}` does not offer resources"));
+        }
+        let client = self.client(server).await?;
+        client
+            .read_resource(uri.to_string(), Some(RESOURCE_TIMEOUT))
+            .await
+            .with_context(|| format!("resources/read failed for `{server}` {uri}"))
+    }
+}
+
+/// Add the servers that were still starting when the manager was created as
+/// they become ready, and report each one's status. Servers that finish after
+/// the manager is dropped are shut down.
+async fn add_late_servers(
+    mut join_set: JoinSet<Startup>,
+    servers: Weak<std::sync::RwLock<Servers>>,
+    ctx: StartupContext,
+) {
+    while let Some(res) = join_set.join_next().await {
+        let startup = match res {
+            Ok(startup) => startup,
+            Err(e) => {
+                warn!("MCP server startup task failed: {e}");
+                continue;
+            }
+        };
+        let Some(servers) = servers.upgrade() else {
+            return;
+        };
+        let status = servers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(startup, &ctx);
+        drop(servers);
+        if let Err(err) = &status.result {
+            warn!("MCP client for `{}` failed to start: {err}", status.server);
+        }
+        let event = Event {
+            id: ctx.sub_id.clone(),
+            msg: EventMsg::McpStartup(McpStartupEvent {
+                servers: vec![status],
+            }),
+        };
+        let _ = ctx.tx_event.send(event).await;
+    }
+}
 
-    while let Some(join_res) = join_set.join_next().await {
-        let (server_name, list_result) = join_res?;
-        let list_result = list_result?;
+/// Restart `server_name` whenever its process exits, waiting 1s, 2s, 4s...
+/// (at most [`MAX_RESTART_DELAY`]) before each attempt, until `max_restarts`
+/// attempts have been used up. Stops once the manager owning `handle` is
+/// dropped, which also kills the server.
+async fn supervise_server(
+    server_name: String,
+    cfg: McpServerConfig,
+    codex_home: PathBuf,
+    handle: Weak<ServerHandle>,
+    mut exited: Pin<Box<dyn Future<Output = ()> + Send>>,
+    tx_event: Sender<Event>,
+    sub_id: String,
+) {
+    let max_restarts = cfg.max_restarts.unwrap_or(DEFAULT_MAX_RESTARTS);
+    let notify = |message: String| {
+        let event = Event {
+            id: sub_id.clone(),
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent { message }),
+        };
+        let tx_event = tx_event.clone();
+        async move {
+            let _ = tx_event.send(event).await;
+        }
+    };
+    let mut attempts = 0;
+    loop {
+        (&mut exited).await;
+        if handle.strong_count() == 0 {
+            return;
+        }
+        warn!("MCP server `{server_name}` exited");
 
-        for tool in list_result.tools {
-            // TODO(mbolin): escape tool names that contain invalid characters.
-            let fq_name = fully_qualified_tool_name(&server_name, &tool.name);
-            if aggregated.insert(fq_name.clone(), tool).is_some() {
-                panic!("tool name collision for '{fq_name}': suspicious");
+        let mut restarted = None;
+        while restarted.is_none() && attempts < max_restarts {
+            attempts += 1;
+            let delay = Duration::from_secs(1 << (attempts - 1).min(5)).min(MAX_RESTART_DELAY);
+            tokio::time::sleep(delay).await;
+            if handle.strong_count() == 0 {
+                return;
+            }
+            match start_client(&server_name, cfg.clone(), &codex_home).await {
+                Ok((client, _)) => restarted = Some(client),
+                Err(e) => warn!("restarting MCP server `{server_name}` failed: {e:#}"),
             }
         }
+
+        let Some(server) = handle.upgrade() else {
+            return;
+        };
+        let Some(client) = restarted else {
+            notify(format!(
+                "MCP server `{server_name}` exited and could not be restarted \
+                 ({max_restarts} attempts); its tools are unavailable"
+            ))
+            .await;
+            return;
+        };
+        info!("MCP server `{server_name}` restarted");
+        exited = Box::pin(client.wait_for_exit());
+        *server.client.write().await = Arc::new(client);
+        drop(server);
+        notify(format!(
+            "MCP server `{server_name}` exited and was restarted"
+        ))
+        .await;
     }
+}
+
+/// What a server offers, for `codex mcp get`.
+#[derive(Debug, Default)]
+pub struct McpServerInventory {
+    pub tools: Vec<Tool>,
+    /// `None` if the server does not advertise the `resources` capability.
+    pub resources: Option<Vec<Resource>>,
+}
 
-    info!(
-        "aggregated {} tools from {} servers",
-        aggregated.len(),
-        clients.len()
-    );
+/// Start the server `server_name` on its own and list its tools and
+/// resources. The server is shut down when this returns.
+pub async fn inspect_mcp_server(
+    server_name: &str,
+    cfg: McpServerConfig,
+    codex_home: &Path,
+) -> Result<McpServerInventory> {
+    let (client, initialize_result) = start_client(server_name, cfg, codex_home).await?;
+    let tools = client
+        .list_tools(None, Some(LIST_TOOLS_TIMEOUT))
+        .await
+        .context("tools/list failed")?
+        .tools;
+    let resources = match initialize_result.capabilities.resources {
+        Some(_) => Some(
+            list_server_resources(&client)
+                .await
+                .context("resources/list failed")?,
+        ),
+        None => None,
+    };
+    Ok(McpServerInventory { tools, resources })
+}
+
+/// A server that is initialized and has listed what it offers.
+struct StartedServer {
+    client: McpClient,
+    tools: Vec<Tool>,
+    /// Whether the server advertises the `resources` capability.
+    resources: bool,
+    prompts: Vec<Prompt>,
+}
 
-    Ok(aggregated)
+/// Start the server and list its tools and, if it offers any, its prompts.
+async fn start_server(
+    server_name: &str,
+    cfg: McpServerConfig,
+    codex_home: &Path,
+) -> Result<StartedServer> {
+    let (client, initialize_result) = start_client(server_name, cfg, codex_home).await?;
+    let tools = client
+        .list_tools(None, Some(LIST_TOOLS_TIMEOUT))
+        .await
+        .context("tools/list failed")?
+        .tools;
+    let prompts = match initialize_result.capabilities.prompts {
+        Some(_) => list_server_prompts(&client)
+            .await
+            // A server without prompts is still useful for its tools.
+            .unwrap_or_else(|e| {
+                warn!("prompts/list failed for `{server_name}`: {e:#}");
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+    Ok(StartedServer {
+        client,
+        tools,
+        resources: initialize_result.capabilities.resources.is_some(),
+        prompts,
+    })
+}
+
+/// Spawn the server described by `cfg` and negotiate the initialization.
+async fn start_client(
+    server_name: &str,
+    cfg: McpServerConfig,
+    codex_home: &Path,
+) -> Result<(McpClient, InitializeResult)> {
+    let McpServerConfig {
+        command,
+        args,
+        mut env,
+        url,
+        enabled: _,
+        startup_timeout_sec: _,
+        max_restarts: _,
+        tool_prefix: _,
+        tool_aliases: _,
+        strict_tools: _,
+    } = cfg;
+    if let Some(url) = url {
+        if command.is_empty() {
+            return Err(anyhow!(
+                "streamable_http transport is not supported natively; \
+                 configure a stdio `command` that bridges to `url`"
+            ));
+        }
+        if let Some(token) = access_token_for_server(codex_home, server_name, &url).await? {
+            env.get_or_insert_with(HashMap::new)
+                .insert(MCP_ACCESS_TOKEN_ENV_VAR.to_string(), token);
+        }
+    }
+    let client = McpClient::new_stdio_client(command, args, env).await?;
+
+    // Initialize the client.
+    let params = mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+        },
+        client_info: Implementation {
+            name: "codex-mcp-client".to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+    };
+    let initialize_notification_params = None;
+    let timeout = Some(INITIALIZE_TIMEOUT);
+    let initialize_result = client
+        .initialize(params, initialize_notification_params, timeout)
+        .await?;
+    Ok((client, initialize_result))
+}
+
+/// All pages of `prompts/list`, up to [`MAX_RESOURCE_PAGES`].
+async fn list_server_prompts(client: &McpClient) -> Result<Vec<Prompt>> {
+    let mut prompts = Vec::new();
+    let mut cursor = None;
+    for _ in 0..MAX_RESOURCE_PAGES {
+        let params = cursor.take().map(|cursor| ListPromptsRequestParams {
+            cursor: Some(cursor),
+        });
+        let page = client.list_prompts(params, Some(PROMPT_TIMEOUT)).await?;
+        prompts.extend(page.prompts);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    Ok(prompts)
+}
+
+/// All pages of `resources/list`, up to [`MAX_RESOURCE_PAGES`].
+async fn list_server_resources(client: &McpClient) -> Result<Vec<Resource>> {
+    let mut resources = Vec::new();
+    let mut cursor = None;
+    for _ in 0..MAX_RESOURCE_PAGES {
+        let params = cursor.take().map(|cursor| ListResourcesRequestParams {
+            cursor: Some(cursor),
+        });
+        let page = client
+            .list_resources(params, Some(RESOURCE_TIMEOUT))
+            .await?;
+        resources.extend(page.resources);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    Ok(resources)
 }
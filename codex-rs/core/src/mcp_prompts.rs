@@ -0,0 +1,72 @@
+//! MCP prompt templates (`prompts/get`), which clients offer as slash
+//! commands. A rendered prompt is sent on as the text of a user message.
+
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::GetPromptResult;
+use mcp_types::PromptMessageContent;
+use mcp_types::Role;
+
+/// The text of the messages of a rendered prompt. Assistant messages are
+/// marked as such; images, audio and binary resources are only named.
+pub(crate) fn render_prompt(result: GetPromptResult) -> String {
+    result
+        .messages
+        .into_iter()
+        .map(|message| {
+            let text = match message.content {
+                PromptMessageContent::TextContent(content) => content.text,
+                PromptMessageContent::EmbeddedResource(content) => match content.resource {
+                    EmbeddedResourceResource::TextResourceContents(resource) => resource.text,
+                    EmbeddedResourceResource::BlobResourceContents(resource) => {
+                        format!("[binary resource {}]", resource.uri)
+                    }
+                },
+                PromptMessageContent::ImageContent(content) => {
+                    format!("[{} image]", content.mime_type)
+                }
+                PromptMessageContent::AudioContent(content) => {
+                    format!("[{} audio]", content.mime_type)
+                }
+            };
+            match message.role {
+                Role::User => text,
+                Role::Assistant => format!("Assistant: {text}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::PromptMessage;
+    use mcp_types::TextContent;
+
+    fn message(role: Role, text: &str) -> PromptMessage {
+        PromptMessage {
+            content: PromptMessageContent::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            }),
+            role,
+        }
+    }
+
+    #[test]
+    fn renders_the_messages_as_one_text() {
+        let result = GetPromptResult {
+            description: None,
+            messages: vec![
+                message(Role::User, "Summarize issue #12."),
+                message(Role::Assistant, "Which repository?"),
+                message(Role::User, "codex"),
+            ],
+        };
+        assert_eq!(
+            render_prompt(result),
+            "Summarize issue #12.\n\nAssistant: Which repository?\n\ncodex"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! The `list_mcp_resources` and `read_mcp_resource` tools: the model reads
+//! the resources (files, database rows, documents...) that MCP servers
+//! expose, not just call their tools. They are only offered when a server
+//! advertises the `resources` capability.
+
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::Resource;
+use serde::Deserialize;
+
+pub(crate) const LIST_MCP_RESOURCES_TOOL_NAME: &str = "list_mcp_resources";
+
+pub(crate) const READ_MCP_RESOURCE_TOOL_NAME: &str = "read_mcp_resource";
+
+/// Bytes of resource text returned to the model.
+const MAX_OUTPUT_BYTES: usize = 100 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListMcpResourcesArgs {
+    /// Only list the resources of this server.
+    #[serde(default)]
+    pub server: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReadMcpResourceArgs {
+    pub server: String,
+    pub uri: String,
+}
+
+/// One line per resource: server, URI, name, MIME type and description.
+pub(crate) fn format_resource_list(resources: &[(String, Resource)]) -> String {
+    if resources.is_empty() {
+        return "No resources.".to_string();
+    }
+    resources
+        .iter()
+        .map(|(server, resource)| {
+            let mut line = format!("{server}: {} ({})", resource.uri, resource.name);
+            if let Some(mime_type) = &resource.mime_type {
+                line.push_str(&format!(" [{mime_type}]"));
+            }
+            if let Some(description) = &resource.description {
+                line.push_str(&format!(" - {}", description.trim()));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The text of the contents of a resource. Binary contents are described
+/// rather than returned.
+pub(crate) fn format_resource_contents(result: ReadResourceResult) -> String {
+    let several = result.contents.len() > 1;
+    let mut output = result
+        .contents
+        .into_iter()
+        .map(|contents| match contents {
+            ReadResourceResultContents::TextResourceContents(contents) if several => {
+                format!("--- {} ---\n{}", contents.uri, contents.text)
+            }
+            ReadResourceResultContents::TextResourceContents(contents) => contents.text,
+            ReadResourceResultContents::BlobResourceContents(contents) => format!(
+                "[{}: binary content{}, {} bytes]",
+                contents.uri,
+                contents
+                    .mime_type
+                    .map(|mime_type| format!(" ({mime_type})"))
+                    .unwrap_or_default(),
+                contents.blob.len() / 4 * 3,
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if output.len() > MAX_OUTPUT_BYTES {
+        let mut end = MAX_OUTPUT_BYTES;
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        output.truncate(end);
+        output.push_str("\n\n[The resource was truncated.]");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::TextResourceContents;
+
+    #[test]
+    fn formats_resources_and_contents() {
+        let resources = vec![(
+            "docs".to_string(),
+            Resource {
+                annotations: None,
+                description: Some("The project README.".to_string()),
+                mime_type: Some("text/markdown".to_string()),
+                name: "README".to_string(),
+                size: None,
+                uri: "file:///README.md".to_string(),
+            },
+        )];
+        assert_eq!(
+            format_resource_list(&resources),
+            "docs: file:///README.md (README) [text/markdown] - The project README."
+        );
+
+        let result = ReadResourceResult {
+            contents: vec![
+                ReadResourceResultContents::TextResourceContents(TextResourceContents {
+                    mime_type: None,
+                    text: "# Hello".to_string(),
+                    uri: "file:///README.md".to_string(),
+                }),
+                ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                    blob: "iVBORw0K".to_string(),
+                    mime_type: Some("image/png".to_string()),
+                    uri: "file:///logo.png".to_string(),
+                }),
+            ],
+        };
+        assert_eq!(
+            format_resource_contents(result),
+            "--- file:///README.md ---\n# Hello\n\n\
+             [file:///logo.png: binary content (image/png), 6 bytes]"
+        );
+    }
+}
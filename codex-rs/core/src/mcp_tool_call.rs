@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use tokio::sync::mpsc;
 use tracing::error;
 
 use crate::codex::Session;
@@ -9,9 +10,15 @@ use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::McpToolCallProgressEvent;
+
+/// Progress updates buffered between the MCP client and the session; older
+/// updates are dropped when the client falls behind.
+const PROGRESS_CHANNEL_CAPACITY: usize = 16;
 
 /// Handles the specified tool call dispatches the appropriate
-/// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+/// `McpToolCallBegin`, `McpToolCallProgress` and `McpToolCallEnd` events to
+/// the `Session`.
 pub(crate) async fn handle_mcp_tool_call(
     sess: &Session,
     sub_id: &str,
@@ -49,11 +56,32 @@ pub(crate) async fn handle_mcp_tool_call(
     });
     notify_mcp_tool_call_event(sess, sub_id, tool_call_begin_event).await;
 
-    // Perform the tool call.
-    let result = sess
-        .call_tool(&server, &tool_name, arguments_value, timeout)
-        .await
-        .map_err(|e| format!("tool call error: {e}"));
+    // Perform the tool call, forwarding the server's progress updates while
+    // it runs.
+    let (progress_tx, mut progress_rx) = mpsc::channel(PROGRESS_CHANNEL_CAPACITY);
+    let call = sess.call_tool(
+        &server,
+        &tool_name,
+        arguments_value,
+        timeout,
+        Some(progress_tx),
+    );
+    tokio::pin!(call);
+    let result = loop {
+        tokio::select! {
+            result = &mut call => break result,
+            Some(progress) = progress_rx.recv() => {
+                let progress_event = EventMsg::McpToolCallProgress(McpToolCallProgressEvent {
+                    call_id: call_id.clone(),
+                    progress: progress.progress,
+                    total: progress.total,
+                    message: progress.message,
+                });
+                notify_mcp_tool_call_event(sess, sub_id, progress_event).await;
+            }
+        }
+    }
+    .map_err(|e| format!("tool call error: {e}"));
     let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
         call_id: call_id.clone(),
         result: result.clone(),
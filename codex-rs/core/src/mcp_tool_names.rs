@@ -0,0 +1,157 @@
+//! Names under which MCP tools are offered to the model. A tool is named
+//! `<server>__<tool>`, where `<server>` can be replaced by the server's
+//! `tool_prefix` and the whole name by an entry of its `tool_aliases`. Names
+//! are limited to the characters and length the model APIs accept, and clashes
+//! (with another MCP tool or a built-in tool) are resolved deterministically
+//! by appending `_2`, `_3`... in order of server and tool name.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::config_types::McpServerConfig;
+use crate::openai_tools::builtin_tool_names;
+
+/// Separates the prefix (by default the server name) from the tool name.
+pub const MCP_TOOL_NAME_DELIMITER: &str = "__";
+
+/// Longest function name the OpenAI APIs accept.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Qualified name -> (server, tool) for the `tools` of the configured
+/// `mcp_servers`.
+pub fn qualify_tool_names(
+    tools: &[(String, String)],
+    mcp_servers: &HashMap<String, McpServerConfig>,
+) -> BTreeMap<String, (String, String)> {
+    let mut sorted: Vec<&(String, String)> = tools.iter().collect();
+    sorted.sort();
+
+    let mut taken: HashSet<String> = builtin_tool_names().map(str::to_string).collect();
+    let mut names = BTreeMap::new();
+    for (server, tool) in sorted {
+        let cfg = mcp_servers.get(server);
+        let base = match cfg.and_then(|cfg| cfg.tool_aliases.get(tool)) {
+            Some(alias) => alias.clone(),
+            None => match cfg
+                .and_then(|cfg| cfg.tool_prefix.as_deref())
+                .unwrap_or(server)
+            {
+                "" => tool.clone(),
+                prefix => format!("{prefix}{MCP_TOOL_NAME_DELIMITER}{tool}"),
+            },
+        };
+        let base = limit_length(&sanitize(&base), MAX_TOOL_NAME_LEN);
+        let mut name = base.clone();
+        let mut n = 2;
+        while taken.contains(&name) {
+            let suffix = format!("_{n}");
+            name = format!(
+                "{}{suffix}",
+                limit_length(&base, MAX_TOOL_NAME_LEN - suffix.len())
+            );
+            n += 1;
+        }
+        taken.insert(name.clone());
+        names.insert(name, (server.clone(), tool.clone()));
+    }
+    names
+}
+
+/// Replace characters outside `[a-zA-Z0-9_-]` with `_`.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Shorten `name` (ASCII after [`sanitize`]) to `max_len`, keeping it unique
+/// with a hash of the full name.
+fn limit_length(name: &str, max_len: usize) -> String {
+    if name.len() <= max_len {
+        return name.to_string();
+    }
+    let hash = format!("{:x}", Sha256::digest(name.as_bytes()));
+    let hash = &hash[..8];
+    format!("{}_{hash}", &name[..max_len - hash.len() - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(prefix: Option<&str>, aliases: &[(&str, &str)]) -> McpServerConfig {
+        McpServerConfig {
+            command: "server".to_string(),
+            args: Vec::new(),
+            env: None,
+            url: None,
+            enabled: true,
+            startup_timeout_sec: None,
+            max_restarts: None,
+            tool_prefix: prefix.map(str::to_string),
+            tool_aliases: aliases
+                .iter()
+                .map(|(tool, alias)| (tool.to_string(), alias.to_string()))
+                .collect(),
+            strict_tools: false,
+        }
+    }
+
+    fn tool(server: &str, tool: &str) -> (String, String) {
+        (server.to_string(), tool.to_string())
+    }
+
+    #[test]
+    fn namespaces_by_server_prefix_and_alias() {
+        let servers = HashMap::from([
+            ("github".to_string(), server(Some("gh"), &[])),
+            ("gitlab".to_string(), server(None, &[("search", "gl_find")])),
+        ]);
+        let names = qualify_tool_names(
+            &[
+                tool("github", "search"),
+                tool("gitlab", "search"),
+                tool("gitlab", "get.issue"),
+                tool("docs", "search"),
+            ],
+            &servers,
+        );
+        let names: Vec<&str> = names.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            vec!["docs__search", "gh__search", "gitlab__get_issue", "gl_find"]
+        );
+    }
+
+    #[test]
+    fn resolves_clashes_in_order() {
+        let servers = HashMap::from([
+            ("a".to_string(), server(Some(""), &[])),
+            ("b".to_string(), server(Some(""), &[])),
+        ]);
+        let names = qualify_tool_names(
+            &[tool("b", "fetch"), tool("a", "fetch"), tool("a", "shell")],
+            &servers,
+        );
+        assert_eq!(names["fetch"], tool("a", "fetch"));
+        assert_eq!(names["fetch_2"], tool("b", "fetch"));
+        // Built-in tools keep their names.
+        assert_eq!(names["shell_2"], tool("a", "shell"));
+
+        let long = "x".repeat(100);
+        let names = qualify_tool_names(&[tool("srv", &long)], &HashMap::new());
+        let name = names.keys().next().map(String::as_str).unwrap_or_default();
+        assert_eq!(name.len(), MAX_TOOL_NAME_LEN);
+        assert!(name.starts_with("srv__xxx"));
+    }
+}
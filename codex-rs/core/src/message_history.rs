@@ -16,7 +16,10 @@
 
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
 use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
 use std::path::PathBuf;
 
@@ -111,9 +114,13 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
     // We use sync I/O with spawn_blocking() because we are using a
     // [`std::fs::File`] instead of a [`tokio::fs::File`] to leverage an
     // advisory file locking API that is not available in the async API.
+    let max_bytes = config.history.max_bytes;
     tokio::task::spawn_blocking(move || -> Result<()> {
         history_file.write_all(line.as_bytes())?;
         history_file.flush()?;
+        if let Some(max_bytes) = max_bytes {
+            trim_to_max_bytes(&mut history_file, max_bytes as u64)?;
+        }
         Ok(())
     })
     .await??;
@@ -121,6 +128,31 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
     Ok(())
 }
 
+/// Drop the oldest entries of the history `file` until it is at most
+/// `max_bytes` long. The file is rewritten in place so its identifier, and
+/// with it the `log_id` of running sessions, stays the same; their offsets
+/// shift, which at worst recalls a newer entry than expected. The caller must
+/// hold the exclusive lock.
+fn trim_to_max_bytes(file: &mut File, max_bytes: u64) -> Result<()> {
+    let len = file.metadata()?.len();
+    if len <= max_bytes {
+        return Ok(());
+    }
+    // Start one byte early to see whether the cut falls on a line boundary.
+    file.seek(SeekFrom::Start(len - max_bytes - 1))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    // Keep whole lines only.
+    let keep_from = tail
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(tail.len(), |i| i + 1);
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&tail[keep_from..])?;
+    file.flush()
+}
+
 /// Attempt to acquire an exclusive advisory lock on `file`, retrying up to 10
 /// times if the lock is currently held by another process. This prevents a
 /// potential indefinite wait while still giving other writers some time to
@@ -256,6 +288,77 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
     None
 }
 
+/// Search the history file identified by `log_id` for the newest entry
+/// before `before` whose text contains `query`, ignoring case. Returns the
+/// entry with its offset. As with [`lookup`], I/O errors are logged and
+/// result in `None`.
+#[cfg(unix)]
+pub(crate) fn search(
+    log_id: u64,
+    query: &str,
+    before: usize,
+    config: &Config,
+) -> Option<(usize, HistoryEntry)> {
+    use std::io::BufReader;
+    use std::os::unix::fs::MetadataExt;
+
+    let path = history_filepath(config);
+    let file = match OpenOptions::new().read(true).open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to open history file");
+            return None;
+        }
+    };
+    if file.metadata().ok()?.ino() != log_id {
+        return None;
+    }
+    if let Err(e) = acquire_shared_lock_with_retry(&file) {
+        tracing::warn!(error = %e, "failed to acquire shared lock on history file");
+        return None;
+    }
+    newest_match(BufReader::new(&file), query, before)
+}
+
+/// Fallback stub for non-Unix systems: currently always returns `None`.
+#[cfg(not(unix))]
+pub(crate) fn search(
+    log_id: u64,
+    query: &str,
+    before: usize,
+    config: &Config,
+) -> Option<(usize, HistoryEntry)> {
+    let _ = (log_id, query, before, config);
+    None
+}
+
+/// The last of the first `before` entries in `reader` that contains `query`.
+fn newest_match(
+    reader: impl std::io::BufRead,
+    query: &str,
+    before: usize,
+) -> Option<(usize, HistoryEntry)> {
+    let query = query.to_lowercase();
+    let mut found = None;
+    for (idx, line) in reader.lines().take(before).enumerate() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to read line from history file");
+                break;
+            }
+        };
+        // Unparseable lines are skipped rather than ending the search.
+        if let Some(entry) = serde_json::from_str::<HistoryEntry>(&line)
+            .ok()
+            .filter(|entry| entry.text.to_lowercase().contains(&query))
+        {
+            found = Some((idx, entry));
+        }
+    }
+    found
+}
+
 #[cfg(unix)]
 fn acquire_shared_lock_with_retry(file: &File) -> Result<()> {
     for _ in 0..MAX_RETRIES {
@@ -295,3 +398,50 @@ async fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
     // For now, on non-Unix, simply succeed.
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn entry_line(text: &str) -> String {
+        format!("{{\"session_id\":\"s\",\"ts\":0,\"text\":\"{text}\"}}\n")
+    }
+
+    #[test]
+    fn trims_oldest_entries_to_max_bytes() {
+        let mut file = tempfile::tempfile().unwrap();
+        let lines = ["one", "two", "three"].map(entry_line);
+        file.write_all(lines.concat().as_bytes()).unwrap();
+
+        // The cut falls exactly on the start of the last two entries.
+        let max_bytes = (lines[1].len() + lines[2].len()) as u64;
+        trim_to_max_bytes(&mut file, max_bytes).unwrap();
+        let mut contents = String::new();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, lines[1..].concat());
+
+        // One byte less drops the entry that no longer fits whole.
+        trim_to_max_bytes(&mut file, max_bytes - 1).unwrap();
+        contents.clear();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, lines[2]);
+    }
+
+    #[test]
+    fn finds_newest_match_before_offset() {
+        let history = ["cargo test", "git status", "Cargo build", "ls"]
+            .map(entry_line)
+            .concat();
+        let search = |query: &str, before: usize| {
+            newest_match(history.as_bytes(), query, before).map(|(idx, e)| (idx, e.text))
+        };
+        assert_eq!(search("cargo", 4), Some((2, "Cargo build".to_string())));
+        assert_eq!(search("cargo", 2), Some((0, "cargo test".to_string())));
+        assert_eq!(search("cargo", 0), None);
+        assert_eq!(search("rustc", 4), None);
+    }
+}
@@ -0,0 +1,141 @@
+//! Models the user can switch to with the TUI's `/model` picker: the ones the
+//! provider lists at `GET {base_url}/models`, the ones configured under
+//! `models` in config.toml, and the current model.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::compact::model_context_window;
+use crate::config::Config;
+use crate::model_pricing::ModelPricing;
+use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::WireApi;
+
+/// Listing the provider's models must not hold up the picker for long.
+const LIST_MODELS_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Context window in tokens, if known.
+    pub context_window: Option<u64>,
+    pub pricing: Option<ModelPricing>,
+    /// Whether Codex requests reasoning summaries from this model.
+    pub supports_reasoning: bool,
+}
+
+impl ModelInfo {
+    fn new(id: String, config: &Config) -> Self {
+        let is_current = id == config.model;
+        Self {
+            context_window: config
+                .model_context_window
+                .filter(|_| is_current)
+                .or_else(|| model_context_window(&id)),
+            pricing: config.model_pricing_for(&id),
+            supports_reasoning: config.model_provider.supports_reasoning_summaries(&id),
+            id,
+        }
+    }
+
+    /// `$` to `$$$` by output price, for a quick comparison in the picker.
+    pub fn pricing_tier(&self) -> Option<&'static str> {
+        self.pricing.map(|pricing| match pricing.output {
+            p if p < 1.0 => "$",
+            p if p < 5.0 => "$$",
+            _ => "$$$",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelCatalog {
+    /// Sorted by id; always contains the current model.
+    pub models: Vec<ModelInfo>,
+    /// Why the provider's listing is missing, if it could not be fetched.
+    pub list_error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ListModelsResponse {
+    data: Vec<ListedModel>,
+}
+
+#[derive(Deserialize)]
+struct ListedModel {
+    id: String,
+}
+
+/// Collect the models available with `config`'s provider. A provider that
+/// cannot list its models (offline, no API key, no `/models` endpoint) still
+/// yields the configured models, with the reason in `list_error`.
+pub async fn fetch_model_catalog(config: &Config) -> ModelCatalog {
+    let (mut ids, list_error) = match list_provider_models(&config.model_provider).await {
+        Ok(ids) => (ids, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    ids.extend(config.models.iter().cloned());
+    ids.push(config.model.clone());
+    ids.sort();
+    ids.dedup();
+    ModelCatalog {
+        models: ids
+            .into_iter()
+            .map(|id| ModelInfo::new(id, config))
+            .collect(),
+        list_error,
+    }
+}
+
+async fn list_provider_models(provider: &ModelProviderInfo) -> anyhow::Result<Vec<String>> {
+    let url = format!("{}/models", provider.base_url.trim_end_matches('/'));
+    let mut request = reqwest::Client::new()
+        .get(&url)
+        .timeout(LIST_MODELS_TIMEOUT);
+    if let Some(api_key) = provider.api_key()? {
+        request = match provider.wire_api {
+            WireApi::Anthropic => request
+                .header("x-api-key", api_key)
+                .header("anthropic-version", "2023-06-01"),
+            WireApi::Responses | WireApi::Chat => request.bearer_auth(api_key),
+        };
+    }
+    let response = request.send().await?.error_for_status()?;
+    let listing: ListModelsResponse = response.json().await?;
+    Ok(listing.data.into_iter().map(|model| model.id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::client_common::model_supports_reasoning_summaries;
+    use crate::model_pricing::builtin_model_pricing;
+
+    #[test]
+    fn parses_openai_style_listing() {
+        let listing: ListModelsResponse = serde_json::from_str(
+            r#"{"object":"list","data":[{"id":"o3","object":"model","owned_by":"openai"},{"id":"llama3:8b"}]}"#,
+        )
+        .unwrap();
+        let ids: Vec<String> = listing.data.into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec!["o3".to_string(), "llama3:8b".to_string()]);
+    }
+
+    #[test]
+    fn pricing_tier_follows_output_price() {
+        let model = |id: &str| ModelInfo {
+            id: id.to_string(),
+            context_window: model_context_window(id),
+            pricing: builtin_model_pricing(id),
+            supports_reasoning: model_supports_reasoning_summaries(id),
+        };
+        assert_eq!(model("gpt-4.1-nano").pricing_tier(), Some("$"));
+        assert_eq!(model("o4-mini").pricing_tier(), Some("$$"));
+        assert_eq!(model("o3").pricing_tier(), Some("$$$"));
+        assert_eq!(model("llama3").pricing_tier(), None);
+        assert!(model("o3").supports_reasoning);
+    }
+}
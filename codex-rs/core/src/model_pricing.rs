@@ -0,0 +1,103 @@
+//! Approximate API prices, used to show what a session has cost so far.
+
+use serde::Deserialize;
+
+use crate::protocol::TokenUsage;
+
+/// Prices in USD per million tokens.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    /// Price of input tokens served from the prompt cache. Defaults to the
+    /// regular input price.
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+    /// Price of output tokens, including reasoning tokens.
+    pub output: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, cached_input: f64, output: f64) -> Self {
+        Self {
+            input,
+            cached_input: Some(cached_input),
+            output,
+        }
+    }
+
+    /// Cost of `usage` in USD.
+    pub fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        let cached = usage
+            .cached_input_tokens
+            .unwrap_or(0)
+            .min(usage.input_tokens);
+        let uncached = usage.input_tokens - cached;
+        let per_token = |price: f64| price / 1_000_000.0;
+        uncached as f64 * per_token(self.input)
+            + cached as f64 * per_token(self.cached_input.unwrap_or(self.input))
+            + usage.output_tokens as f64 * per_token(self.output)
+    }
+}
+
+/// Published prices of the OpenAI models Codex is commonly used with, matched
+/// by prefix so dated snapshots (e.g. `o3-2025-04-16`) are covered. Returns
+/// `None` for unknown models; `[model_pricing]` in config.toml fills the gaps.
+pub(crate) fn builtin_model_pricing(model: &str) -> Option<ModelPricing> {
+    let model = model.to_ascii_lowercase();
+    let pricing = match model.as_str() {
+        m if m.starts_with("o3-mini") || m.starts_with("o4-mini") => {
+            ModelPricing::new(1.10, 0.275, 4.40)
+        }
+        m if m.starts_with("o3") => ModelPricing::new(2.00, 0.50, 8.00),
+        m if m.starts_with("codex-mini") => ModelPricing::new(1.50, 0.375, 6.00),
+        m if m.starts_with("gpt-4.1-nano") => ModelPricing::new(0.10, 0.025, 0.40),
+        m if m.starts_with("gpt-4.1-mini") => ModelPricing::new(0.40, 0.10, 1.60),
+        m if m.starts_with("gpt-4.1") => ModelPricing::new(2.00, 0.50, 8.00),
+        m if m.starts_with("gpt-4o-mini") => ModelPricing::new(0.15, 0.075, 0.60),
+        m if m.starts_with("gpt-4o") => ModelPricing::new(2.50, 1.25, 10.00),
+        m if m.starts_with("claude-opus-4") => ModelPricing::new(15.00, 1.50, 75.00),
+        m if m.starts_with("claude-sonnet-4") || m.starts_with("claude-3-7-sonnet") => {
+            ModelPricing::new(3.00, 0.30, 15.00)
+        }
+        m if m.starts_with("claude-3-5-haiku") => ModelPricing::new(0.80, 0.08, 4.00),
+        _ => return None,
+    };
+    Some(pricing)
+}
+
+/// Format a cost for display, e.g. `≈ $0.42`.
+pub fn format_cost_usd(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.01 {
+        "< $0.01".to_string()
+    } else {
+        format!("≈ ${cost:.2}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn prices_cached_input_separately() {
+        let pricing = builtin_model_pricing("o3-2025-04-16").unwrap();
+        let usage = TokenUsage {
+            input_tokens: 100_000,
+            cached_input_tokens: Some(40_000),
+            output_tokens: 10_000,
+            reasoning_output_tokens: Some(5_000),
+            total_tokens: 110_000,
+        };
+        // 60k * $2 + 40k * $0.50 + 10k * $8, per million.
+        assert!((pricing.cost_usd(&usage) - 0.22).abs() < 1e-9);
+        assert_eq!(format_cost_usd(pricing.cost_usd(&usage)), "≈ $0.22");
+        assert_eq!(format_cost_usd(0.001), "< $0.01");
+        assert_eq!(builtin_model_pricing("my-local-model"), None);
+        assert_eq!(
+            builtin_model_pricing("gpt-4.1-mini"),
+            Some(ModelPricing::new(0.40, 0.10, 1.60))
+        );
+    }
+}
@@ -10,6 +10,7 @@ use serde::Serialize;
 use std::collections::HashMap;
 use std::env::VarError;
 
+use crate::client_common::model_supports_reasoning_summaries;
 use crate::error::EnvVarError;
 use crate::openai_api_key::get_openai_api_key;
 
@@ -27,6 +28,8 @@ pub enum WireApi {
     Responses,
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     Chat,
+    /// Anthropic's Messages API at `/v1/messages`.
+    Anthropic,
 }
 
 /// Serializable representation of a provider definition.
@@ -45,9 +48,22 @@ pub struct ModelProviderInfo {
 
     /// Which wire protocol this provider expects.
     pub wire_api: WireApi,
+
+    /// Whether the provider's models emit reasoning summaries. When unset,
+    /// this is guessed from the model name; local OpenAI-compatible servers
+    /// such as Ollama set it to `false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_summaries: Option<bool>,
 }
 
 impl ModelProviderInfo {
+    /// Whether reasoning summaries should be requested (and reported) for
+    /// `model` when served by this provider.
+    pub fn supports_reasoning_summaries(&self, model: &str) -> bool {
+        self.reasoning_summaries
+            .unwrap_or_else(|| model_supports_reasoning_summaries(model))
+    }
+
     /// If `env_key` is Some, returns the API key for this provider if present
     /// (and non-empty) in the environment. If `env_key` is required but
     /// cannot be found, returns an error.
@@ -92,6 +108,7 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: Some("OPENAI_API_KEY".into()),
                 env_key_instructions: Some("Create an API key (https://platform.openai.com) and export it as an environment variable.".into()),
                 wire_api: WireApi::Responses,
+                reasoning_summaries: None,
             },
         ),
         (
@@ -102,6 +119,7 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: Some("OPENROUTER_API_KEY".into()),
                 env_key_instructions: None,
                 wire_api: WireApi::Chat,
+                reasoning_summaries: None,
             },
         ),
         (
@@ -112,6 +130,18 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: Some("GEMINI_API_KEY".into()),
                 env_key_instructions: None,
                 wire_api: WireApi::Chat,
+                reasoning_summaries: None,
+            },
+        ),
+        (
+            "anthropic",
+            P {
+                name: "Anthropic".into(),
+                base_url: "https://api.anthropic.com/v1".into(),
+                env_key: Some("ANTHROPIC_API_KEY".into()),
+                env_key_instructions: Some("Create an API key (https://console.anthropic.com) and export it as an environment variable.".into()),
+                wire_api: WireApi::Anthropic,
+                reasoning_summaries: Some(false),
             },
         ),
         (
@@ -122,6 +152,7 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: None,
                 env_key_instructions: None,
                 wire_api: WireApi::Chat,
+                reasoning_summaries: Some(false),
             },
         ),
         (
@@ -132,6 +163,7 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: Some("MISTRAL_API_KEY".into()),
                 env_key_instructions: None,
                 wire_api: WireApi::Chat,
+                reasoning_summaries: None,
             },
         ),
         (
@@ -142,6 +174,7 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: Some("DEEPSEEK_API_KEY".into()),
                 env_key_instructions: None,
                 wire_api: WireApi::Chat,
+                reasoning_summaries: None,
             },
         ),
         (
@@ -152,6 +185,7 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: Some("XAI_API_KEY".into()),
                 env_key_instructions: None,
                 wire_api: WireApi::Chat,
+                reasoning_summaries: None,
             },
         ),
         (
@@ -162,6 +196,7 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_key: Some("GROQ_API_KEY".into()),
                 env_key_instructions: None,
                 wire_api: WireApi::Chat,
+                reasoning_summaries: None,
             },
         ),
     ]
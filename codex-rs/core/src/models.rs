@@ -175,6 +175,10 @@ pub struct ShellToolCallParams {
     // The wire format uses `timeout`, which has ambiguous units, so we use
     // `timeout_ms` as the field name so it is clear in code.
     pub timeout_ms: Option<u64>,
+
+    /// Run the command on a pseudo-terminal, for programs that need a TTY.
+    #[serde(default)]
+    pub tty: bool,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -273,6 +277,7 @@ mod tests {
                 command: vec!["ls".to_string(), "-l".to_string()],
                 workdir: Some("/tmp".to_string()),
                 timeout_ms: Some(1000),
+                tty: false,
             },
             params
         );
@@ -2,8 +2,10 @@ use serde::Serialize;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::sync::LazyLock;
+use tracing::debug;
 
 use crate::client_common::Prompt;
+use crate::tool_output::strict_input_schema;
 
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct ResponsesApiTool {
@@ -24,12 +26,23 @@ pub(crate) enum OpenAiTool {
     LocalShell {},
 }
 
+impl OpenAiTool {
+    /// The name the tool is disabled by in the `[tools]` config.
+    fn name(&self) -> &'static str {
+        match self {
+            OpenAiTool::Function(tool) => tool.name,
+            OpenAiTool::LocalShell {} => "shell",
+        }
+    }
+}
+
 /// Generic JSON‑Schema subset needed for our tool definitions
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub(crate) enum JsonSchema {
     String,
     Number,
+    Boolean,
     Array {
         items: Box<JsonSchema>,
     },
@@ -52,21 +65,280 @@ static DEFAULT_TOOLS: LazyLock<Vec<OpenAiTool>> = LazyLock::new(|| {
     );
     properties.insert("workdir".to_string(), JsonSchema::String);
     properties.insert("timeout".to_string(), JsonSchema::Number);
+    properties.insert("tty".to_string(), JsonSchema::Boolean);
+
+    vec![
+        OpenAiTool::Function(ResponsesApiTool {
+            name: "shell",
+            description: "Runs a shell command, and returns its output. Set `tty` for commands \
+                          that need a terminal (pagers, progress bars, prompts).",
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties,
+                required: &["command"],
+                additional_properties: false,
+            },
+        }),
+        ATTACH_IMAGE_TOOL.clone(),
+        EDIT_FILE_TOOL.clone(),
+        SPAWN_AGENT_TOOL.clone(),
+        UPDATE_PLAN_TOOL.clone(),
+        WEB_SEARCH_TOOL.clone(),
+        FETCH_URL_TOOL.clone(),
+        LIST_MCP_RESOURCES_TOOL.clone(),
+        READ_MCP_RESOURCE_TOOL.clone(),
+    ]
+    .into_iter()
+    .chain(BACKGROUND_PROCESS_TOOLS.iter().cloned())
+    .collect()
+});
+
+/// Lets the model look at a PNG/JPEG file (screenshots, diagrams) instead of
+/// reading its bytes through the shell.
+static ATTACH_IMAGE_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let mut properties = BTreeMap::new();
+    properties.insert("path".to_string(), JsonSchema::String);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "attach_image",
+        description: "Attaches a local PNG or JPEG image to the conversation so you can see it. \
+                      Paths are resolved relative to the working directory.",
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: &["path"],
+            additional_properties: false,
+        },
+    })
+});
+
+/// Exact or regex find/replace in a single file, for tweaks too small to be
+/// worth a full `apply_patch`.
+static EDIT_FILE_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let properties = BTreeMap::from([
+        ("path".to_string(), JsonSchema::String),
+        ("old_string".to_string(), JsonSchema::String),
+        ("new_string".to_string(), JsonSchema::String),
+        ("regex".to_string(), JsonSchema::Boolean),
+        ("expected_occurrences".to_string(), JsonSchema::Number),
+    ]);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "edit_file",
+        description: "Replaces every occurrence of `old_string` in a file with `new_string` and \
+                      returns a unified diff of the change. Fails without changing anything \
+                      unless the file contains exactly `expected_occurrences` matches \
+                      (default 1). Set `regex` to treat `old_string` as a regular expression; \
+                      `new_string` may then use `$1` for capture groups.",
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: &["path", "old_string", "new_string"],
+            additional_properties: false,
+        },
+    })
+});
+
+/// `exec_background`, `process_output` and `process_kill`: start a command
+/// that keeps running across turns (dev servers, watchers) and manage it.
+static BACKGROUND_PROCESS_TOOLS: LazyLock<Vec<OpenAiTool>> = LazyLock::new(|| {
+    let mut exec_properties = BTreeMap::new();
+    exec_properties.insert(
+        "command".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String),
+        },
+    );
+    exec_properties.insert("workdir".to_string(), JsonSchema::String);
+
+    let id_properties = || BTreeMap::from([("id".to_string(), JsonSchema::Number)]);
+
+    vec![
+        OpenAiTool::Function(ResponsesApiTool {
+            name: "exec_background",
+            description: "Starts a long-running command (e.g. a dev server) in the background and \
+                          returns its process id without waiting for it to finish.",
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties: exec_properties,
+                required: &["command"],
+                additional_properties: false,
+            },
+        }),
+        OpenAiTool::Function(ResponsesApiTool {
+            name: "process_output",
+            description: "Returns the output a background process produced since the last call \
+                          and whether it is still running.",
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties: id_properties(),
+                required: &["id"],
+                additional_properties: false,
+            },
+        }),
+        OpenAiTool::Function(ResponsesApiTool {
+            name: "process_kill",
+            description: "Kills a background process started with exec_background.",
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties: id_properties(),
+                required: &["id"],
+                additional_properties: false,
+            },
+        }),
+    ]
+});
+
+/// Delegates a scoped sub-task to a child conversation, see `sub_agent.rs`.
+static SPAWN_AGENT_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let properties = BTreeMap::from([
+        ("prompt".to_string(), JsonSchema::String),
+        (
+            "tools".to_string(),
+            JsonSchema::Array {
+                items: Box::new(JsonSchema::String),
+            },
+        ),
+        ("max_turns".to_string(), JsonSchema::Number),
+    ]);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "spawn_agent",
+        description: "Delegates a self-contained sub-task to a sub-agent that starts with a fresh \
+                      conversation and returns its final message. `prompt` must include all the \
+                      context the sub-agent needs. `tools` limits it to the named tools (default: \
+                      all tools but spawn_agent); `max_turns` bounds its model turns (default 10, \
+                      at most 30).",
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: &["prompt"],
+            additional_properties: false,
+        },
+    })
+});
+
+/// Keeps the plan of a multi-step task up to date, see `plan_tool.rs`.
+static UPDATE_PLAN_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let step = JsonSchema::Object {
+        properties: BTreeMap::from([
+            ("step".to_string(), JsonSchema::String),
+            ("status".to_string(), JsonSchema::String),
+        ]),
+        required: &["step", "status"],
+        additional_properties: false,
+    };
+    let properties = BTreeMap::from([
+        ("explanation".to_string(), JsonSchema::String),
+        (
+            "plan".to_string(),
+            JsonSchema::Array {
+                items: Box::new(step),
+            },
+        ),
+    ]);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "update_plan",
+        description: "Replaces the plan of the current task, which the user sees next to the \
+                      conversation. Use it for tasks with several steps: list the steps with a \
+                      `status` of `pending`, `in_progress` or `completed` (at most one step in \
+                      progress) and update it as you go, with an optional `explanation` of \
+                      what changed.",
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: &["plan"],
+            additional_properties: false,
+        },
+    })
+});
 
-    vec![OpenAiTool::Function(ResponsesApiTool {
-        name: "shell",
-        description: "Runs a shell command, and returns its output.",
+static WEB_SEARCH_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let properties = BTreeMap::from([
+        ("query".to_string(), JsonSchema::String),
+        ("max_results".to_string(), JsonSchema::Number),
+    ]);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "web_search",
+        description: "Searches the web and returns the title, URL and a snippet of each result. \
+                      Use it for documentation, error messages or anything newer than your \
+                      training data.",
         strict: false,
         parameters: JsonSchema::Object {
             properties,
-            required: &["command"],
+            required: &["query"],
             additional_properties: false,
         },
-    })]
+    })
 });
 
-static DEFAULT_CODEX_MODEL_TOOLS: LazyLock<Vec<OpenAiTool>> =
-    LazyLock::new(|| vec![OpenAiTool::LocalShell {}]);
+static FETCH_URL_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let properties = BTreeMap::from([("url".to_string(), JsonSchema::String)]);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "fetch_url",
+        description: "Downloads an http(s) page and returns its readable text as markdown, \
+                      without navigation and other boilerplate, along with the final URL after \
+                      redirects. Use it to read linked documentation instead of running curl.",
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: &["url"],
+            additional_properties: false,
+        },
+    })
+});
+
+static LIST_MCP_RESOURCES_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let properties = BTreeMap::from([("server".to_string(), JsonSchema::String)]);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "list_mcp_resources",
+        description: "Lists the resources (files, documents, records...) that the MCP servers \
+                      expose, or only those of `server`, with the URI to read each one by.",
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: &[],
+            additional_properties: false,
+        },
+    })
+});
+
+static READ_MCP_RESOURCE_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let properties = BTreeMap::from([
+        ("server".to_string(), JsonSchema::String),
+        ("uri".to_string(), JsonSchema::String),
+    ]);
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "read_mcp_resource",
+        description: "Reads the resource at `uri` from the MCP server `server`, as listed by \
+                      `list_mcp_resources`.",
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: &["server", "uri"],
+            additional_properties: false,
+        },
+    })
+});
+
+static DEFAULT_CODEX_MODEL_TOOLS: LazyLock<Vec<OpenAiTool>> = LazyLock::new(|| {
+    [
+        OpenAiTool::LocalShell {},
+        ATTACH_IMAGE_TOOL.clone(),
+        EDIT_FILE_TOOL.clone(),
+        SPAWN_AGENT_TOOL.clone(),
+        UPDATE_PLAN_TOOL.clone(),
+        WEB_SEARCH_TOOL.clone(),
+        FETCH_URL_TOOL.clone(),
+        LIST_MCP_RESOURCES_TOOL.clone(),
+        READ_MCP_RESOURCE_TOOL.clone(),
+    ]
+    .into_iter()
+    .chain(BACKGROUND_PROCESS_TOOLS.iter().cloned())
+    .collect()
+});
+
+/// Names of the built-in tools, as listed in the `[tools]` config.
+pub(crate) fn builtin_tool_names() -> impl Iterator<Item = &'static str> {
+    DEFAULT_TOOLS.iter().map(OpenAiTool::name)
+}
 
 /// Returns JSON values that are compatible with Function Calling in the
 /// Responses API:
@@ -82,16 +354,19 @@ pub(crate) fn create_tools_json_for_responses_api(
         &DEFAULT_TOOLS
     };
     let mut tools_json = Vec::with_capacity(default_tools.len() + prompt.extra_tools.len());
-    for t in default_tools.iter() {
+    for t in default_tools
+        .iter()
+        .filter(|t| !prompt.disabled_tools.iter().any(|name| name == t.name()))
+    {
         tools_json.push(serde_json::to_value(t)?);
     }
-    tools_json.extend(
-        prompt
-            .extra_tools
-            .clone()
-            .into_iter()
-            .map(|(name, tool)| mcp_tool_to_openai_tool(name, tool)),
-    );
+    if prompt.hosted_web_search {
+        tools_json.push(json!({ "type": "web_search_preview" }));
+    }
+    tools_json.extend(prompt.extra_tools.clone().into_iter().map(|(name, tool)| {
+        let strict = prompt.strict_tools.contains(&name);
+        mcp_tool_to_openai_tool(name, tool, strict)
+    }));
 
     Ok(tools_json)
 }
@@ -131,6 +406,7 @@ pub(crate) fn create_tools_json_for_chat_completions_api(
 fn mcp_tool_to_openai_tool(
     fully_qualified_name: String,
     tool: mcp_types::Tool,
+    strict: bool,
 ) -> serde_json::Value {
     let mcp_types::Tool {
         description,
@@ -148,10 +424,22 @@ fn mcp_tool_to_openai_tool(
 
     // TODO(mbolin): Change the contract of this function to return
     // ResponsesApiTool.
-    json!({
+    let mut tool = json!({
         "name": fully_qualified_name,
         "description": description,
         "parameters": input_schema,
         "type": "function",
-    })
+    });
+    if strict {
+        match strict_input_schema(&tool["parameters"]) {
+            Some(parameters) => {
+                tool["parameters"] = parameters;
+                tool["strict"] = serde_json::Value::Bool(true);
+            }
+            None => debug!(
+                "not offering `{fully_qualified_name}` in strict mode: it has optional arguments"
+            ),
+        }
+    }
+    tool
 }
@@ -0,0 +1,113 @@
+//! The `update_plan` tool: the model keeps a short list of steps with their
+//! status up to date while working through a multi-step task. The plan lives
+//! in the session state and is shown by the clients; a resumed session
+//! restores it from the last `update_plan` call in its history.
+
+use crate::models::ResponseItem;
+use crate::protocol::PlanUpdateEvent;
+use crate::protocol::StepStatus;
+
+pub(crate) const UPDATE_PLAN_TOOL_NAME: &str = "update_plan";
+
+/// Parse and check the arguments of an `update_plan` call, which have the
+/// shape of the event that reports them.
+pub(crate) fn parse_plan_update(arguments: &str) -> Result<PlanUpdateEvent, String> {
+    let update: PlanUpdateEvent = serde_json::from_str(arguments)
+        .map_err(|e| format!("failed to parse function arguments: {e}"))?;
+    if update.plan.iter().any(|step| step.step.trim().is_empty()) {
+        return Err("plan steps must have a description".to_string());
+    }
+    let in_progress = update
+        .plan
+        .iter()
+        .filter(|step| step.status == StepStatus::InProgress)
+        .count();
+    if in_progress > 1 {
+        return Err(format!(
+            "{in_progress} steps are in_progress; at most one step can be in progress at a time"
+        ));
+    }
+    Ok(update)
+}
+
+/// The tool output for an accepted update.
+pub(crate) fn plan_summary(update: &PlanUpdateEvent) -> String {
+    let completed = update
+        .plan
+        .iter()
+        .filter(|step| step.status == StepStatus::Completed)
+        .count();
+    format!(
+        "Plan updated: {completed} of {} steps completed.",
+        update.plan.len()
+    )
+}
+
+/// The plan set by the last valid `update_plan` call in `items`.
+pub(crate) fn latest_plan(items: &[ResponseItem]) -> Option<PlanUpdateEvent> {
+    items.iter().rev().find_map(|item| match item {
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } if name == UPDATE_PLAN_TOOL_NAME => parse_plan_update(arguments).ok(),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::protocol::PlanStep;
+
+    #[test]
+    fn rejects_several_steps_in_progress() {
+        let update = parse_plan_update(
+            r#"{"plan": [
+                {"step": "write the parser", "status": "completed"},
+                {"step": "add tests", "status": "in_progress"},
+                {"step": "update docs", "status": "pending"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(update.plan.len(), 3);
+        assert_eq!(
+            plan_summary(&update),
+            "Plan updated: 1 of 3 steps completed."
+        );
+
+        let err = parse_plan_update(
+            r#"{"plan": [
+                {"step": "a", "status": "in_progress"},
+                {"step": "b", "status": "in_progress"}
+            ]}"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("at most one step"));
+        assert!(parse_plan_update(r#"{"plan": [{"step": "a", "status": "done"}]}"#).is_err());
+    }
+
+    #[test]
+    fn restores_the_last_valid_plan() {
+        let call = |arguments: &str| ResponseItem::FunctionCall {
+            name: UPDATE_PLAN_TOOL_NAME.to_string(),
+            arguments: arguments.to_string(),
+            call_id: "call".to_string(),
+        };
+        let items = vec![
+            call(r#"{"plan": [{"step": "a", "status": "in_progress"}]}"#),
+            call(r#"{"explanation": "a is done", "plan": [{"step": "a", "status": "completed"}]}"#),
+            call(r#"{"plan": "not a list"}"#),
+        ];
+        let plan = latest_plan(&items).unwrap();
+        assert_eq!(plan.explanation.as_deref(), Some("a is done"));
+        assert_eq!(
+            plan.plan,
+            vec![PlanStep {
+                step: "a".to_string(),
+                status: StepStatus::Completed,
+            }]
+        );
+        assert!(latest_plan(&[]).is_none());
+    }
+}
@@ -1,18 +1,25 @@
 //! Project-level documentation discovery.
 //!
-//! Project-level documentation can be stored in a file named `AGENTS.md`.
-//! Currently, we include only the contents of the first file found as follows:
+//! Project-level documentation is stored in files named `AGENTS.md`, which
+//! are layered as follows:
 //!
-//! 1.  Look for the doc file in the current working directory (as determined
-//!     by the `Config`).
-//! 2.  If not found, walk *upwards* until the Git repository root is reached
-//!     (detected by the presence of a `.git` directory/file), or failing that,
-//!     the filesystem root.
-//! 3.  If the Git root is encountered, look for the doc file there. If it
-//!     exists, the search stops – we do **not** walk past the Git root.
+//! 1.  Walk *upwards* from the current working directory (as determined by
+//!     the `Config`) until the Git repository root is reached (detected by
+//!     the presence of a `.git` directory/file). Outside a repository, the
+//!     working directory itself is the root.
+//! 2.  Load the doc file of every directory from the root down to the
+//!     working directory, least specific first. Where they conflict, the
+//!     instructions of deeper directories take precedence, and they are also
+//!     the first to be included when `project_doc_max_bytes` does not fit all.
+//! 3.  When the agent edits files in other directories under the root, the
+//!     doc files of those directories are loaded too, and returned to the
+//!     model with the result of the edit.
 
 use crate::config::Config;
+use crate::protocol::ProjectDoc;
+use std::collections::HashSet;
 use std::path::Path;
+use std::path::PathBuf;
 use tokio::io::AsyncReadExt;
 use tracing::error;
 
@@ -23,80 +30,244 @@ const CANDIDATE_FILENAMES: &[&str] = &["AGENTS.md"];
 /// be concatenated with the following separator.
 const PROJECT_DOC_SEPARATOR: &str = "\n\n--- project-doc ---\n\n";
 
-/// Combines `Config::instructions` and `AGENTS.md` (if present) into a single
-/// string of instructions.
+/// Precedes the docs when more than one was loaded.
+const LAYERED_DOCS_PREAMBLE: &str = "The following AGENTS.md files apply, from the repository root down to the working directory. Each applies to the files under its directory; where they conflict, the more specific (later) file takes precedence.";
+
+/// Precedes the docs loaded by editing files.
+const EDITED_DIRS_DOCS_PREAMBLE: &str = "The edited files are covered by these additional AGENTS.md files. Follow them for the files under their directories; they take precedence over the less specific instructions you were given.";
+
+/// Combines `Config::instructions` and the `AGENTS.md` files (if present)
+/// into a single string of instructions.
 pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
-    match find_project_doc(config).await {
-        Ok(Some(project_doc)) => match &config.instructions {
+    let docs = ProjectDocs::load(&config.cwd, config.project_doc_max_bytes).await;
+    combine_instructions(config.instructions.as_deref(), docs.docs())
+}
+
+/// `instructions` followed by the merged `docs`.
+pub(crate) fn combine_instructions(
+    instructions: Option<&str>,
+    docs: &[ProjectDoc],
+) -> Option<String> {
+    match merge_project_docs(docs) {
+        Some(project_doc) => match instructions {
             Some(original_instructions) => Some(format!(
                 "{original_instructions}{PROJECT_DOC_SEPARATOR}{project_doc}"
             )),
             None => Some(project_doc),
         },
-        Ok(None) => config.instructions.clone(),
-        Err(e) => {
-            error!("error trying to find project doc: {e:#}");
-            config.instructions.clone()
-        }
+        None => instructions.map(ToOwned::to_owned),
     }
 }
 
-/// Attempt to locate and load the project documentation. Currently, the search
-/// starts from `Config::cwd`, but if we may want to consider other directories
-/// in the future, e.g., additional writable directories in the `SandboxPolicy`.
-///
-/// On success returns `Ok(Some(contents))`. If no documentation file is found
-/// the function returns `Ok(None)`. Unexpected I/O failures bubble up as
-/// `Err` so callers can decide how to handle them.
-async fn find_project_doc(config: &Config) -> std::io::Result<Option<String>> {
-    let max_bytes = config.project_doc_max_bytes;
-
-    // Attempt to load from the working directory first.
-    if let Some(doc) = load_first_candidate(&config.cwd, CANDIDATE_FILENAMES, max_bytes).await? {
-        return Ok(Some(doc));
+/// The `AGENTS.md` files of a session: those of the working directory and its
+/// parents, and those of the directories the agent edited files in since.
+#[derive(Debug, Clone)]
+pub(crate) struct ProjectDocs {
+    /// Only directories under the root are searched for docs.
+    root: PathBuf,
+    max_bytes: usize,
+    /// Directories whose doc was already looked for: those from the root
+    /// down to `cwd`, then those of edited files.
+    seen_dirs: HashSet<PathBuf>,
+    docs: Vec<ProjectDoc>,
+}
+
+impl ProjectDocs {
+    /// Load the docs from the root down to `cwd`. I/O failures are logged,
+    /// and the docs found until then are kept.
+    pub(crate) async fn load(cwd: &Path, max_bytes: usize) -> Self {
+        // Canonicalize the path so that we do not end up in an infinite loop
+        // when `cwd` contains `..` components.
+        let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+        let root = match find_git_root(&cwd).await {
+            Ok(root) => root.unwrap_or_else(|| cwd.clone()),
+            Err(e) => {
+                error!(
+                    "error trying to find the git root of {}: {e:#}",
+                    cwd.display()
+                );
+                cwd.clone()
+            }
+        };
+        let mut project_docs = Self {
+            root,
+            max_bytes,
+            seen_dirs: HashSet::new(),
+            docs: Vec::new(),
+        };
+        if max_bytes == 0 {
+            return project_docs;
+        }
+
+        let dirs = dirs_between(&project_docs.root, &cwd);
+        // The deepest docs are the most specific, so they get the budget first.
+        let mut remaining = max_bytes;
+        for dir in dirs.iter().rev() {
+            if remaining == 0 {
+                break;
+            }
+            match load_first_candidate(dir, CANDIDATE_FILENAMES, remaining).await {
+                Ok(Some(doc)) => {
+                    remaining = remaining.saturating_sub(doc.contents.len());
+                    project_docs.docs.push(doc);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("error trying to find project doc: {e:#}");
+                    break;
+                }
+            }
+        }
+        project_docs.docs.reverse();
+        project_docs.seen_dirs.extend(dirs);
+        project_docs
+    }
+
+    /// The loaded docs, least specific first.
+    pub(crate) fn docs(&self) -> &[ProjectDoc] {
+        &self.docs
     }
 
-    // Walk up towards the filesystem root, stopping once we encounter the Git
-    // repository root. The presence of **either** a `.git` *file* or
-    // *directory* counts.
-    let mut dir = config.cwd.clone();
+    pub(crate) fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
 
-    // Canonicalize the path so that we do not end up in an infinite loop when
-    // `cwd` contains `..` components.
-    if let Ok(canon) = dir.canonicalize() {
-        dir = canon;
+    /// The directories from the root down to those of `paths` whose doc was
+    /// not looked for yet, parents first. They are marked as looked for.
+    pub(crate) fn take_unseen_dirs(&mut self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        if self.max_bytes == 0 {
+            return Vec::new();
+        }
+        let mut unseen = Vec::new();
+        for path in paths {
+            let Some(dir) = path.parent() else {
+                continue;
+            };
+            let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+            for dir in dirs_between(&self.root, &dir) {
+                if self.seen_dirs.insert(dir.clone()) {
+                    unseen.push(dir);
+                }
+            }
+        }
+        unseen.sort_by_key(|dir| dir.components().count());
+        unseen
     }
 
+    pub(crate) fn extend(&mut self, docs: impl IntoIterator<Item = ProjectDoc>) {
+        self.docs.extend(docs);
+    }
+
+    /// The doc files that were looked for, whether they exist or not, so
+    /// that creating one is noticed as well.
+    pub(crate) fn candidate_paths(&self) -> Vec<PathBuf> {
+        self.seen_dirs
+            .iter()
+            .flat_map(|dir| CANDIDATE_FILENAMES.iter().map(|name| dir.join(name)))
+            .collect()
+    }
+
+    /// Load the docs again after some changed: those from the root down to
+    /// `cwd`, then those of the directories of edited files.
+    pub(crate) async fn reload(&self, cwd: &Path) -> Self {
+        let mut reloaded = Self::load(cwd, self.max_bytes).await;
+        let mut edited_dirs: Vec<PathBuf> = self
+            .seen_dirs
+            .difference(&reloaded.seen_dirs)
+            .cloned()
+            .collect();
+        edited_dirs.sort_by_key(|dir| dir.components().count());
+        let docs = load_docs_in(&edited_dirs, self.max_bytes).await;
+        reloaded.seen_dirs.extend(edited_dirs);
+        reloaded.extend(docs);
+        reloaded
+    }
+}
+
+/// Load the docs of `dirs`, each truncated to `max_bytes`. Failures are
+/// logged and skipped.
+pub(crate) async fn load_docs_in(dirs: &[PathBuf], max_bytes: usize) -> Vec<ProjectDoc> {
+    let mut docs = Vec::new();
+    for dir in dirs {
+        match load_first_candidate(dir, CANDIDATE_FILENAMES, max_bytes).await {
+            Ok(Some(doc)) => docs.push(doc),
+            Ok(None) => {}
+            Err(e) => error!(
+                "error trying to load project doc in {}: {e:#}",
+                dir.display()
+            ),
+        }
+    }
+    docs
+}
+
+/// The instructions of `docs`: a single doc verbatim, several ones with the
+/// path of each and a note on their precedence.
+pub(crate) fn merge_project_docs(docs: &[ProjectDoc]) -> Option<String> {
+    match docs {
+        [] => None,
+        [doc] => Some(doc.contents.clone()),
+        docs => Some(format!("{LAYERED_DOCS_PREAMBLE}{}", format_docs(docs))),
+    }
+}
+
+/// The instructions of the docs loaded by editing files, to be appended to
+/// the result of the edit.
+pub(crate) fn format_edited_dirs_docs(docs: &[ProjectDoc]) -> String {
+    format!("\n\n{EDITED_DIRS_DOCS_PREAMBLE}{}", format_docs(docs))
+}
+
+fn format_docs(docs: &[ProjectDoc]) -> String {
+    docs.iter()
+        .map(|doc| {
+            format!(
+                "\n\n# AGENTS.md ({})\n\n{}",
+                doc.path.display(),
+                doc.contents.trim_end()
+            )
+        })
+        .collect()
+}
+
+/// The closest ancestor of `cwd` (inclusive) that contains a `.git` file or
+/// directory. We do **not** look past it.
+async fn find_git_root(cwd: &Path) -> std::io::Result<Option<PathBuf>> {
+    let mut dir = cwd.to_path_buf();
     while let Some(parent) = dir.parent() {
         // `.git` can be a *file* (for worktrees or submodules) or a *dir*.
         let git_marker = dir.join(".git");
-        let git_exists = match tokio::fs::metadata(&git_marker).await {
-            Ok(_) => true,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        match tokio::fs::metadata(&git_marker).await {
+            Ok(_) => return Ok(Some(dir)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
             Err(e) => return Err(e),
-        };
-
-        if git_exists {
-            // We are at the repo root – attempt one final load.
-            if let Some(doc) = load_first_candidate(&dir, CANDIDATE_FILENAMES, max_bytes).await? {
-                return Ok(Some(doc));
-            }
-            break;
         }
-
         dir = parent.to_path_buf();
     }
-
     Ok(None)
 }
 
+/// `root` and each directory below it down to `dir`, or nothing when `dir` is
+/// not under `root`.
+fn dirs_between(root: &Path, dir: &Path) -> Vec<PathBuf> {
+    let Ok(relative) = dir.strip_prefix(root) else {
+        return Vec::new();
+    };
+    let mut current = root.to_path_buf();
+    let mut dirs = vec![current.clone()];
+    for component in relative.components() {
+        current.push(component);
+        dirs.push(current.clone());
+    }
+    dirs
+}
+
 /// Attempt to load the first candidate file found in `dir`. Returns the file
 /// contents (truncated if it exceeds `max_bytes`) when successful.
 async fn load_first_candidate(
     dir: &Path,
     names: &[&str],
     max_bytes: usize,
-) -> std::io::Result<Option<String>> {
+) -> std::io::Result<Option<ProjectDoc>> {
     for name in names {
         let candidate = dir.join(name);
 
@@ -126,7 +297,10 @@ async fn load_first_candidate(
             continue;
         }
 
-        return Ok(Some(contents));
+        return Ok(Some(ProjectDoc {
+            path: candidate,
+            contents,
+        }));
     }
 
     Ok(None)
@@ -280,4 +454,70 @@ mod tests {
 
         assert_eq!(res, Some(INSTRUCTIONS.to_string()));
     }
+
+    /// The docs from the repo root down to `cwd` are all included, the most
+    /// specific last, and the deepest get the byte budget first.
+    #[tokio::test]
+    async fn merges_nested_docs_from_repo_root() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        fs::write(repo.path().join("AGENTS.md"), "root doc").unwrap();
+        let nested = repo.path().join("crates/tui");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(repo.path().join("crates/AGENTS.md"), "crates doc").unwrap();
+        fs::write(nested.join("AGENTS.md"), "tui doc").unwrap();
+
+        let docs = ProjectDocs::load(&nested, 4096).await;
+        let contents: Vec<&str> = docs.docs().iter().map(|d| d.contents.as_str()).collect();
+        assert_eq!(contents, vec!["root doc", "crates doc", "tui doc"]);
+
+        let merged = merge_project_docs(docs.docs()).unwrap();
+        assert!(merged.starts_with(LAYERED_DOCS_PREAMBLE));
+        let tui = nested.canonicalize().unwrap().join("AGENTS.md");
+        assert!(merged.ends_with(&format!("# AGENTS.md ({})\n\ntui doc", tui.display())));
+
+        let docs = ProjectDocs::load(&nested, 17).await;
+        let contents: Vec<&str> = docs.docs().iter().map(|d| d.contents.as_str()).collect();
+        assert_eq!(contents, vec!["crates doc", "tui doc"]);
+    }
+
+    /// Editing files loads the docs of their directories once.
+    #[tokio::test]
+    async fn edited_dirs_are_searched_once() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        let cwd = repo.path().join("cli");
+        let core = repo.path().join("core/src");
+        fs::create_dir_all(&cwd).unwrap();
+        fs::create_dir_all(&core).unwrap();
+        fs::write(repo.path().join("core/AGENTS.md"), "core doc").unwrap();
+
+        let mut docs = ProjectDocs::load(&cwd, 4096).await;
+        assert!(docs.docs().is_empty());
+
+        let edited = vec![core.join("lib.rs"), cwd.join("main.rs")];
+        let dirs = docs.take_unseen_dirs(&edited);
+        let root = repo.path().canonicalize().unwrap();
+        assert_eq!(dirs, vec![root.join("core"), root.join("core/src")]);
+        let loaded = load_docs_in(&dirs, docs.max_bytes()).await;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].contents, "core doc");
+        docs.extend(loaded);
+
+        assert!(docs.take_unseen_dirs(&edited).is_empty());
+        assert!(
+            docs.take_unseen_dirs(&[PathBuf::from("/elsewhere/a.rs")])
+                .is_empty()
+        );
+
+        // Reloading keeps the docs of the edited directories.
+        fs::write(repo.path().join("AGENTS.md"), "root doc").unwrap();
+        let reloaded = docs.reload(&cwd).await;
+        let contents: Vec<&str> = reloaded
+            .docs()
+            .iter()
+            .map(|d| d.contents.as_str())
+            .collect();
+        assert_eq!(contents, vec!["root doc", "core doc"]);
+    }
 }
@@ -0,0 +1,309 @@
+//! `/init` (`Op::InitProjectDoc`): the model drafts an AGENTS.md for the
+//! working directory from what simple heuristics find out about the project
+//! (languages, build tools, test commands, the top-level files and the
+//! README). The draft is only proposed; the client writes it once the user
+//! approves.
+
+use std::path::Path;
+
+/// The file drafted, in the working directory.
+pub(crate) const PROJECT_DOC_FILE: &str = "AGENTS.md";
+
+/// Top-level entries listed for the model.
+const MAX_LISTED_ENTRIES: usize = 100;
+
+/// Only the beginning of the README is passed on.
+const MAX_README_BYTES: usize = 8 * 1024;
+
+/// System prompt of the request that drafts the file.
+pub(crate) const INIT_INSTRUCTIONS: &str = r#"You write AGENTS.md files: instructions that coding agents read at the start of every session in a repository.
+
+You are given what is known about the repository in the current directory. Write a concise AGENTS.md in Markdown with these sections, leaving out any you have nothing concrete for:
+- Project overview: what the project is and how the code is organized
+- Build and test: the exact commands to build, test and lint
+- Code style: conventions to follow
+- Workflow: anything else an agent must know before changing code, e.g. generated files not to edit
+
+Only state what the information supports; do not invent commands or conventions. Reply with the contents of the file only, without a code fence or any other text."#;
+
+/// What the heuristics found out about the project in a directory.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ProjectFacts {
+    pub(crate) languages: Vec<&'static str>,
+    pub(crate) build_tools: Vec<&'static str>,
+    pub(crate) test_commands: Vec<String>,
+}
+
+impl ProjectFacts {
+    fn add(&mut self, language: &'static str, build_tool: &'static str, test: Option<String>) {
+        if !self.languages.contains(&language) {
+            self.languages.push(language);
+        }
+        if !self.build_tools.contains(&build_tool) {
+            self.build_tools.push(build_tool);
+        }
+        if let Some(test) = test.filter(|test| !self.test_commands.contains(test)) {
+            self.test_commands.push(test);
+        }
+    }
+}
+
+/// Recognize the project from the manifests and lock files in `cwd`.
+pub(crate) fn detect_project(cwd: &Path) -> ProjectFacts {
+    let has = |name: &str| cwd.join(name).exists();
+    let read = |name: &str| std::fs::read_to_string(cwd.join(name)).unwrap_or_default();
+    let mut facts = ProjectFacts::default();
+
+    if has("Cargo.toml") {
+        facts.add("Rust", "cargo", Some("cargo test".to_string()));
+    }
+    if has("package.json") {
+        let language = if has("tsconfig.json") {
+            "TypeScript"
+        } else {
+            "JavaScript"
+        };
+        let tool = if has("pnpm-lock.yaml") {
+            "pnpm"
+        } else if has("yarn.lock") {
+            "yarn"
+        } else if has("bun.lockb") || has("bun.lock") {
+            "bun"
+        } else {
+            "npm"
+        };
+        let has_test_script = serde_json::from_str::<serde_json::Value>(&read("package.json"))
+            .ok()
+            .is_some_and(|package| package.pointer("/scripts/test").is_some());
+        facts.add(
+            language,
+            tool,
+            has_test_script.then(|| format!("{tool} run test")),
+        );
+    }
+    if has("pyproject.toml") || has("setup.py") || has("requirements.txt") {
+        let pyproject = read("pyproject.toml");
+        let tool = if pyproject.contains("[tool.poetry]") {
+            "poetry"
+        } else if has("uv.lock") {
+            "uv"
+        } else {
+            "pip"
+        };
+        let uses_pytest = has("pytest.ini")
+            || has("conftest.py")
+            || pyproject.contains("pytest")
+            || read("requirements.txt").contains("pytest");
+        let test = uses_pytest.then(|| match tool {
+            "pip" => "pytest".to_string(),
+            tool => format!("{tool} run pytest"),
+        });
+        facts.add("Python", tool, test);
+    }
+    if has("go.mod") {
+        facts.add("Go", "go", Some("go test ./...".to_string()));
+    }
+    if has("pom.xml") {
+        facts.add("Java", "Maven", Some("mvn test".to_string()));
+    }
+    if has("build.gradle") || has("build.gradle.kts") {
+        let gradle = if has("gradlew") {
+            "./gradlew"
+        } else {
+            "gradle"
+        };
+        facts.add("Java/Kotlin", "Gradle", Some(format!("{gradle} test")));
+    }
+    if has("CMakeLists.txt") {
+        facts.add("C/C++", "CMake", Some("ctest --test-dir build".to_string()));
+    }
+    if has("Gemfile") {
+        let test = if has("spec") {
+            "bundle exec rspec"
+        } else {
+            "bundle exec rake test"
+        };
+        facts.add("Ruby", "Bundler", Some(test.to_string()));
+    }
+    if has("mix.exs") {
+        facts.add("Elixir", "Mix", Some("mix test".to_string()));
+    }
+    if has("Makefile") {
+        let makefile = read("Makefile");
+        if !facts.build_tools.contains(&"make") {
+            facts.build_tools.push("make");
+        }
+        if makefile.lines().any(|line| line.starts_with("test:")) {
+            facts.test_commands.push("make test".to_string());
+        }
+    }
+    facts
+}
+
+/// Everything the model is told about the project, gathered from `cwd`.
+pub(crate) struct InitContext {
+    pub(crate) facts: ProjectFacts,
+    /// Top-level files and directories (with a trailing `/`), sorted.
+    pub(crate) entries: Vec<String>,
+    pub(crate) readme: Option<String>,
+    /// The AGENTS.md already in `cwd`, which the draft is to improve.
+    pub(crate) existing: Option<String>,
+}
+
+impl InitContext {
+    pub(crate) fn gather(cwd: &Path) -> Self {
+        let mut entries: Vec<String> = std::fs::read_dir(cwd)
+            .map(|dir| {
+                dir.flatten()
+                    .filter_map(|entry| {
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        if name == ".git" {
+                            return None;
+                        }
+                        let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                        Some(if is_dir { format!("{name}/") } else { name })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        entries.sort();
+        let readme = ["README.md", "README", "README.rst", "README.txt"]
+            .iter()
+            .find_map(|name| std::fs::read_to_string(cwd.join(name)).ok())
+            .map(|mut readme| {
+                if readme.len() > MAX_README_BYTES {
+                    let mut end = MAX_README_BYTES;
+                    while !readme.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    readme.truncate(end);
+                }
+                readme
+            });
+        Self {
+            facts: detect_project(cwd),
+            entries,
+            readme,
+            existing: std::fs::read_to_string(cwd.join(PROJECT_DOC_FILE)).ok(),
+        }
+    }
+
+    /// The user message that asks for the draft.
+    pub(crate) fn request(&self) -> String {
+        let list = |items: &[&str]| {
+            if items.is_empty() {
+                "unknown".to_string()
+            } else {
+                items.join(", ")
+            }
+        };
+        let tests: Vec<&str> = self
+            .facts
+            .test_commands
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let mut request = format!(
+            "Draft an AGENTS.md for this repository.\n\n\
+             Detected from its manifests:\n\
+             - Languages: {}\n\
+             - Build tools: {}\n\
+             - Test commands: {}\n",
+            list(&self.facts.languages),
+            list(&self.facts.build_tools),
+            list(&tests),
+        );
+        let listed = self.entries.len().min(MAX_LISTED_ENTRIES);
+        request.push_str(&format!(
+            "\nTop-level entries:\n```\n{}\n```\n",
+            self.entries[..listed].join("\n")
+        ));
+        if self.entries.len() > listed {
+            request.push_str(&format!(
+                "({} more entries not listed)\n",
+                self.entries.len() - listed
+            ));
+        }
+        if let Some(readme) = &self.readme {
+            request.push_str(&format!("\nThe README:\n```\n{}\n```\n", readme.trim_end()));
+        }
+        if let Some(existing) = &self.existing {
+            request.push_str(&format!(
+                "\nThere already is an AGENTS.md; improve it and keep what is still accurate:\n```markdown\n{}\n```\n",
+                existing.trim_end()
+            ));
+        }
+        request
+    }
+}
+
+/// The file contents in the model's reply, without the code fence it may
+/// have put around them anyway.
+pub(crate) fn doc_from_reply(reply: &str) -> String {
+    let reply = reply.trim();
+    let unfenced = reply
+        .strip_prefix("```")
+        .and_then(|rest| rest.split_once('\n'))
+        .filter(|(info, _)| matches!(info.trim(), "" | "markdown" | "md"))
+        .and_then(|(_, body)| body.trim_end().strip_suffix("```"))
+        .unwrap_or(reply);
+    format!("{}\n", unfenced.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn detects_languages_tools_and_tests() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"test": "vitest"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        std::fs::write(dir.path().join("Makefile"), "all:\n\tcargo build\n").unwrap();
+
+        assert_eq!(
+            detect_project(dir.path()),
+            ProjectFacts {
+                languages: vec!["Rust", "TypeScript"],
+                build_tools: vec!["cargo", "pnpm", "make"],
+                test_commands: vec!["cargo test".to_string(), "pnpm run test".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn request_lists_entries_readme_and_existing_doc() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module example.com/x\n").unwrap();
+        std::fs::write(dir.path().join("README.md"), "# X\n\nDoes things.\n").unwrap();
+        std::fs::write(dir.path().join(PROJECT_DOC_FILE), "Run go vet.\n").unwrap();
+        std::fs::create_dir(dir.path().join("cmd")).unwrap();
+
+        let context = InitContext::gather(dir.path());
+        assert_eq!(context.existing.as_deref(), Some("Run go vet.\n"));
+        let request = context.request();
+        assert!(request.contains("- Languages: Go\n"));
+        assert!(request.contains("- Test commands: go test ./...\n"));
+        assert!(request.contains("```\nAGENTS.md\nREADME.md\ncmd/\ngo.mod\n```"));
+        assert!(request.contains("# X\n\nDoes things.\n```"));
+        assert!(request.contains("improve it and keep what is still accurate"));
+    }
+
+    #[test]
+    fn strips_a_fence_around_the_reply() {
+        assert_eq!(
+            doc_from_reply("```markdown\n# AGENTS.md\n\nRun `make`.\n```"),
+            "# AGENTS.md\n\nRun `make`.\n"
+        );
+        assert_eq!(doc_from_reply("# Guide\n"), "# Guide\n");
+    }
+}
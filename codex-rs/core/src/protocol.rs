@@ -8,10 +8,12 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use mcp_types::CallToolResult;
+use mcp_types::Prompt as McpPrompt;
 use serde::Deserialize;
 use serde::Serialize;
 use uuid::Uuid;
 
+use crate::config_types::PromptCaching;
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use crate::message_history::HistoryEntry;
@@ -43,6 +45,11 @@ pub enum Op {
         model_reasoning_effort: ReasoningEffortConfig,
         model_reasoning_summary: ReasoningSummaryConfig,
 
+        /// `cache_control` breakpoints for providers with explicit prompt
+        /// caching.
+        #[serde(default)]
+        prompt_caching: PromptCaching,
+
         /// Model instructions
         instructions: Option<String>,
         /// When to escalate for approval for execution
@@ -96,6 +103,10 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Report the width of the client's terminal. Commands the model runs
+    /// with `tty` get a pseudo-terminal this wide.
+    SetTerminalWidth { columns: u16 },
+
     /// Append an entry to the persistent cross-session message history.
     ///
     /// Note the entry is not guaranteed to be logged if the user has
@@ -105,8 +116,65 @@ pub enum Op {
         text: String,
     },
 
+    /// Deliver a short correction from the user to the running task. It is
+    /// sent to the model with the next request, i.e. as soon as the tool
+    /// calls in flight have returned, instead of after the task completes.
+    /// Without a running task it is handled like `UserInput`.
+    InjectUserGuidance {
+        /// The user's correction.
+        text: String,
+    },
+
     /// Request a single history entry identified by `log_id` + `offset`.
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
+
+    /// Find the newest history entry before offset `before` whose text
+    /// contains `query`, ignoring case.
+    SearchHistoryRequest {
+        query: String,
+        before: usize,
+        log_id: u64,
+    },
+
+    /// Summarize the conversation so far and continue with the summary in
+    /// place of the full history. Runs as a task: the server replies with
+    /// `TaskStarted`, `ContextCompacted` and `TaskComplete`.
+    Compact,
+
+    /// Restore the files changed by patches in the most recent turn that
+    /// changed any. The server replies with `TurnReverted`, or `Error` if
+    /// there is nothing to revert.
+    RevertTurn,
+
+    /// Review the changes in the working directory as a task, with a review
+    /// system prompt in place of the usual one. Each finding of the review
+    /// is reported as a `ReviewFinding` event before `TaskComplete`.
+    StartReview {
+        /// Git ref to review against: the diff covers the commits on `HEAD`
+        /// since it branched off this ref. Without one, the uncommitted
+        /// changes are reviewed.
+        base: Option<String>,
+    },
+
+    /// Ask the model for an AGENTS.md for the working directory, drafted
+    /// from its manifests, top-level files and README (and the AGENTS.md
+    /// already there, if any). Runs as a task; the draft is reported as
+    /// `ProjectDocProposal` before `TaskComplete` and nothing is written.
+    InitProjectDoc,
+
+    /// Render the prompt template `name` of the MCP server `server` with
+    /// `arguments`. The server replies with `McpPrompt`, or `Error`.
+    GetMcpPrompt {
+        server: String,
+        name: String,
+        arguments: HashMap<String, String>,
+    },
+
+    /// Run the custom prompt `name` (see
+    /// [`SessionConfiguredEvent::custom_prompts`]) with `arguments`. Its
+    /// `model` and `approval` apply to the task it starts; while a task is
+    /// running, the prompt is added to it as user input instead.
+    RunCustomPrompt { name: String, arguments: String },
 }
 
 /// Determines how liberally commands are auto‑approved by the system.
@@ -358,13 +426,30 @@ pub enum EventMsg {
     /// Ack the client's configure message.
     SessionConfigured(SessionConfiguredEvent),
 
+    /// Which MCP servers started, after `SessionConfigured`.
+    McpStartup(McpStartupEvent),
+
+    /// The custom prompts, sent again whenever a prompt file changes.
+    CustomPromptsUpdated(CustomPromptsUpdatedEvent),
+
+    /// The AGENTS.md files the instructions were built from: after
+    /// `SessionConfigured`, and again when editing files loads the
+    /// AGENTS.md of their directories.
+    ProjectDocsLoaded(ProjectDocsLoadedEvent),
+
     McpToolCallBegin(McpToolCallBeginEvent),
 
+    /// Progress reported by the MCP server for a running tool call.
+    McpToolCallProgress(McpToolCallProgressEvent),
+
     McpToolCallEnd(McpToolCallEndEvent),
 
     /// Notification that the server is about to execute a command.
     ExecCommandBegin(ExecCommandBeginEvent),
 
+    /// Incremental chunk of output from a running exec command.
+    ExecCommandOutputDelta(ExecCommandOutputDeltaEvent),
+
     ExecCommandEnd(ExecCommandEndEvent),
 
     ExecApprovalRequest(ExecApprovalRequestEvent),
@@ -380,8 +465,50 @@ pub enum EventMsg {
     /// Notification that a patch application has finished.
     PatchApplyEnd(PatchApplyEndEvent),
 
+    /// Guidance sent with `Op::InjectUserGuidance` was handed to the model.
+    UserGuidanceDelivered(UserGuidanceDeliveredEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
+
+    /// Response to SearchHistoryRequest.
+    SearchHistoryResponse(SearchHistoryResponseEvent),
+
+    /// Token usage reported by the model provider at the end of a turn.
+    TokenCount(TokenCountEvent),
+
+    /// Older turns were replaced by a summary to free up context.
+    ContextCompacted(ContextCompactedEvent),
+
+    /// The set of running `exec_background` processes changed.
+    BackgroundProcessesUpdated(BackgroundProcessesUpdatedEvent),
+
+    /// The file changes of a turn were rolled back (`Op::RevertTurn`).
+    TurnReverted(TurnRevertedEvent),
+
+    /// The model delegated a sub-task with `spawn_agent`. Tool call events
+    /// until the matching `SubAgentEnd` come from the sub-agent.
+    SubAgentBegin(SubAgentBeginEvent),
+
+    /// A message of a running sub-agent.
+    SubAgentMessage(SubAgentMessageEvent),
+
+    /// A sub-agent finished; its final message is the tool call's output.
+    SubAgentEnd(SubAgentEndEvent),
+
+    /// The model updated its plan with `update_plan` (or a resumed session
+    /// restored it).
+    PlanUpdate(PlanUpdateEvent),
+
+    /// A finding of a review started with `Op::StartReview`.
+    ReviewFinding(ReviewFinding),
+
+    /// An MCP prompt rendered for `Op::GetMcpPrompt`.
+    McpPrompt(McpPromptEvent),
+
+    /// The AGENTS.md drafted for `Op::InitProjectDoc`, for the client to
+    /// write once the user approves it.
+    ProjectDocProposal(ProjectDocProposalEvent),
 }
 
 // Individual event payload types matching each `EventMsg` variant.
@@ -391,6 +518,44 @@ pub struct ErrorEvent {
     pub message: String,
 }
 
+/// Token counts reported by the model provider for a single response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: Option<u64>,
+    pub output_tokens: u64,
+    pub reasoning_output_tokens: Option<u64>,
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Add `other` to `self`, e.g. to keep a running total for a session.
+    pub fn accumulate(&mut self, other: &TokenUsage) {
+        fn add(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or_default() + b.unwrap_or_default()),
+            }
+        }
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens = add(self.cached_input_tokens, other.cached_input_tokens);
+        self.output_tokens += other.output_tokens;
+        self.reasoning_output_tokens =
+            add(self.reasoning_output_tokens, other.reasoning_output_tokens);
+        self.total_tokens += other.total_tokens;
+    }
+
+    /// Share of the input tokens that were read from the provider's prompt
+    /// cache, or `None` if the provider does not report cache reads.
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let cached = self.cached_input_tokens?;
+        if self.input_tokens == 0 {
+            return None;
+        }
+        Some(cached as f64 / self.input_tokens as f64)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TaskCompleteEvent {
     pub last_agent_message: Option<String>,
@@ -406,6 +571,41 @@ pub struct AgentReasoningEvent {
     pub text: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpStartupEvent {
+    /// One entry per enabled server, sorted by name.
+    pub servers: Vec<McpServerStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CustomPromptsUpdatedEvent {
+    pub custom_prompts: Vec<CustomPrompt>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectDocsLoadedEvent {
+    /// Every AGENTS.md loaded so far, from the least to the most specific;
+    /// later docs take precedence over earlier ones.
+    pub docs: Vec<ProjectDoc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProjectDoc {
+    pub path: PathBuf,
+    /// Contents as given to the model, truncated to `project_doc_max_bytes`.
+    pub contents: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct McpServerStatus {
+    /// Name of the MCP server as defined in the config.
+    pub server: String,
+    /// Time until the server was ready, or until it failed.
+    pub startup_ms: u64,
+    /// Number of tools offered to the model, or why the server failed.
+    pub result: Result<usize, String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct McpToolCallBeginEvent {
     /// Identifier so this can be paired with the McpToolCallEnd event.
@@ -418,6 +618,28 @@ pub struct McpToolCallBeginEvent {
     pub arguments: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpToolCallProgressEvent {
+    /// Identifier of the McpToolCallBegin this progress belongs to.
+    pub call_id: String,
+    /// Progress so far; increases with every update.
+    pub progress: f64,
+    /// Value of `progress` at completion, if the server knows it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl McpToolCallProgressEvent {
+    /// Percentage done, when the total is known.
+    pub fn percent(&self) -> Option<u32> {
+        self.total
+            .filter(|total| *total > 0.0)
+            .map(|total| (self.progress / total * 100.0).clamp(0.0, 100.0) as u32)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct McpToolCallEndEvent {
     /// Identifier for the corresponding McpToolCallBegin that finished.
@@ -445,6 +667,37 @@ pub struct ExecCommandBeginEvent {
     pub cwd: PathBuf,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct BackgroundProcessInfo {
+    /// Handle the model uses with `process_output` and `process_kill`.
+    pub id: u32,
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BackgroundProcessesUpdatedEvent {
+    /// Background processes that have not been killed or reaped yet.
+    pub processes: Vec<BackgroundProcessInfo>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecOutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExecCommandOutputDeltaEvent {
+    /// Identifier for the ExecCommandBegin that produced this chunk.
+    pub call_id: String,
+    /// Which stream the chunk was read from.
+    pub stream: ExecOutputStream,
+    /// Raw output, decoded lossily as UTF-8. Chunks are not aligned to line
+    /// boundaries.
+    pub chunk: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ExecCommandEndEvent {
     /// Identifier for the ExecCommandBegin that finished.
@@ -466,6 +719,11 @@ pub struct ExecApprovalRequestEvent {
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// Word-level prefix (e.g. `["cargo"]`) the user may approve for the rest
+    /// of the session with [`ReviewDecision::ApprovedPrefixForSession`].
+    /// `None` when the command cannot be approved by prefix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_prefix: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -479,6 +737,167 @@ pub struct ApplyPatchApprovalRequestEvent {
     pub grant_root: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TokenCountEvent {
+    /// Usage for the response that just completed.
+    pub last: TokenUsage,
+    /// Cumulative usage for the session so far.
+    pub total: TokenUsage,
+    /// Configured `max_session_tokens`, if any.
+    pub max_session_tokens: Option<u64>,
+    /// Approximate cost of `total` in USD, if the model's price is known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
+    /// Context window of the model, if known, to tell how much of it the
+    /// prompt (`last.input_tokens`) fills.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_context_window: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ContextCompactedEvent {
+    /// Summary that now stands in for the compacted turns.
+    pub summary: String,
+    /// Prompt size (input tokens) of the last turn before compaction.
+    pub tokens_before: u64,
+    /// Approximate size of the summary that replaced it.
+    pub tokens_after: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TurnRevertedEvent {
+    /// Files restored to their contents from before the turn (or removed, if
+    /// the turn created them).
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlanStep {
+    pub step: String,
+    pub status: StepStatus,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlanUpdateEvent {
+    /// Why the plan changed, if the model said so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    pub plan: Vec<PlanStep>,
+}
+
+/// A custom prompt: a Markdown file in the `prompts` or `commands`
+/// directory of `$CODEX_HOME` or of the project's `.codex`, offered as the
+/// slash command `/<name>`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CustomPrompt {
+    pub name: String,
+    pub path: PathBuf,
+    /// Whether the prompt comes from the project or from `$CODEX_HOME`.
+    #[serde(default)]
+    pub scope: PromptScope,
+    /// The prompt without its frontmatter.
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// How to pass arguments, e.g. `<pr-number>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub argument_hint: Option<String>,
+    /// Model to run the prompt with instead of the session's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Approval policy to run the prompt with instead of the session's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<AskForApproval>,
+    /// Whether `` !`command` `` blocks are replaced with the output of the
+    /// command when the prompt is run.
+    #[serde(default)]
+    pub allow_shell: bool,
+}
+
+/// Where a [`CustomPrompt`] was found.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptScope {
+    /// The `.codex` directory of the project, which takes precedence.
+    Project,
+    /// `$CODEX_HOME`.
+    #[default]
+    User,
+}
+
+/// A prompt template offered by an MCP server.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct McpPromptInfo {
+    pub server: String,
+    pub prompt: McpPrompt,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct McpPromptEvent {
+    pub server: String,
+    pub name: String,
+    /// The text of the prompt's messages, to send as a user message.
+    pub text: String,
+}
+
+/// One review comment, anchored to lines of the new version of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ReviewFinding {
+    /// Path relative to the repository root.
+    pub file: String,
+    pub line_range: LineRange,
+    pub severity: ReviewSeverity,
+    /// What is wrong and how to fix it.
+    pub body: String,
+}
+
+/// Inclusive range of 1-based line numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LineRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SubAgentBeginEvent {
+    /// Identifier of the `spawn_agent` call.
+    pub call_id: String,
+    /// The task the sub-agent was given.
+    pub prompt: String,
+    pub max_turns: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SubAgentMessageEvent {
+    pub call_id: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SubAgentEndEvent {
+    pub call_id: String,
+    /// Model turns the sub-agent took.
+    pub turns: u32,
+    /// The sub-agent's final message, or why it did not finish.
+    pub result: Result<String, String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BackgroundEventEvent {
     pub message: String,
@@ -515,6 +934,32 @@ pub struct GetHistoryEntryResponseEvent {
     pub entry: Option<HistoryEntry>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectDocProposalEvent {
+    /// Where the file goes: `AGENTS.md` in the working directory.
+    pub path: PathBuf,
+    /// The file's current contents, if it exists.
+    pub existing: Option<String>,
+    /// The proposed contents.
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserGuidanceDeliveredEvent {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchHistoryResponseEvent {
+    pub query: String,
+    pub log_id: u64,
+    /// Offset of the matching entry, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<HistoryEntry>,
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct SessionConfiguredEvent {
     /// Unique id for this session.
@@ -528,6 +973,15 @@ pub struct SessionConfiguredEvent {
 
     /// Current number of entries in the history log.
     pub history_entry_count: usize,
+
+    /// Prompt templates of the MCP servers, offered as slash commands.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mcp_prompts: Vec<McpPromptInfo>,
+
+    /// Custom prompts of the project and `$CODEX_HOME`, offered as slash
+    /// commands.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_prompts: Vec<CustomPrompt>,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
@@ -542,6 +996,12 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this command and wants to automatically approve any
+    /// future command that starts with the same words (see
+    /// [`ExecApprovalRequestEvent::session_prefix`]) for the remainder of the
+    /// session.
+    ApprovedPrefixForSession,
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -590,6 +1050,8 @@ mod tests {
                 model: "codex-mini-latest".to_string(),
                 history_log_id: 0,
                 history_entry_count: 0,
+                mcp_prompts: Vec::new(),
+                custom_prompts: Vec::new(),
             }),
         };
         let serialized = serde_json::to_string(&event).unwrap();
@@ -598,4 +1060,37 @@ mod tests {
             r#"{"id":"1234","msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"codex-mini-latest","history_log_id":0,"history_entry_count":0}}"#
         );
     }
+
+    #[test]
+    fn cache_hit_ratio_is_share_of_input() {
+        let usage = TokenUsage {
+            input_tokens: 1_000,
+            cached_input_tokens: Some(750),
+            output_tokens: 10,
+            reasoning_output_tokens: None,
+            total_tokens: 1_010,
+        };
+        assert_eq!(usage.cache_hit_ratio(), Some(0.75));
+        let uncached = TokenUsage {
+            cached_input_tokens: None,
+            ..usage
+        };
+        assert_eq!(uncached.cache_hit_ratio(), None);
+    }
+
+    #[test]
+    fn progress_percent_needs_a_total() {
+        let progress = McpToolCallProgressEvent {
+            call_id: "call-1".to_string(),
+            progress: 30.0,
+            total: Some(120.0),
+            message: None,
+        };
+        assert_eq!(progress.percent(), Some(25));
+        let unbounded = McpToolCallProgressEvent {
+            total: None,
+            ..progress
+        };
+        assert_eq!(unbounded.percent(), None);
+    }
 }
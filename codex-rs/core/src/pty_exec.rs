@@ -0,0 +1,118 @@
+//! Runs shell tool commands attached to a pseudo-terminal instead of pipes,
+//! for programs that behave differently (or hang) when their output is not a
+//! terminal: pagers, progress bars, interactive prompts.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Read;
+use std::path::PathBuf;
+
+use portable_pty::Child;
+use portable_pty::CommandBuilder;
+use portable_pty::MasterPty;
+use portable_pty::PtySize;
+use portable_pty::native_pty_system;
+use tokio::io::AsyncWriteExt;
+use tokio::io::DuplexStream;
+
+/// Most programs only look at the width; the height just has to be sane.
+const PTY_ROWS: u16 = 24;
+
+pub(crate) struct PtyChild {
+    pub child: Box<dyn Child + Send + Sync>,
+    /// Everything the program writes to the terminal, stdout and stderr
+    /// alike, including ANSI escape sequences.
+    pub output: DuplexStream,
+    /// Dropping the master hangs up the terminal, so it is kept until the
+    /// output has been consumed.
+    pub master: Box<dyn MasterPty + Send>,
+}
+
+/// Spawns `program` on a new pseudo-terminal that is `columns` wide.
+pub(crate) fn spawn_pty_child(
+    program: PathBuf,
+    args: Vec<String>,
+    cwd: PathBuf,
+    env: HashMap<String, String>,
+    columns: u16,
+) -> io::Result<PtyChild> {
+    let pair = native_pty_system()
+        .openpty(PtySize {
+            rows: PTY_ROWS,
+            cols: columns,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(pty_error)?;
+
+    let mut cmd = CommandBuilder::new(program);
+    cmd.args(args);
+    cmd.cwd(cwd);
+    cmd.env_clear();
+    if !env.contains_key("TERM") {
+        cmd.env("TERM", "xterm-256color");
+    }
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+    let child = pair.slave.spawn_command(cmd).map_err(pty_error)?;
+    // The child must hold the only handle to the slave side, otherwise
+    // reading from the master never reaches EOF.
+    drop(pair.slave);
+
+    // Nobody is there to type, so close the terminal's input: programs that
+    // prompt read EOF instead of waiting for the timeout.
+    drop(pair.master.take_writer().map_err(pty_error)?);
+
+    let mut reader = pair.master.try_clone_reader().map_err(pty_error)?;
+    let (mut writer, output) = tokio::io::duplex(8192);
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let mut buf = [0u8; 8192];
+        loop {
+            let n = match reader.read(&mut buf) {
+                // Linux reports EIO once every process has closed the slave.
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            if handle.block_on(writer.write_all(&buf[..n])).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(PtyChild {
+        child,
+        output,
+        master: pair.master,
+    })
+}
+
+fn pty_error(err: anyhow::Error) -> io::Error {
+    io::Error::other(format!("failed to set up pseudo-terminal: {err}"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn child_sees_a_terminal_of_the_requested_width() {
+        let env = HashMap::from([("PATH".to_string(), std::env::var("PATH").unwrap())]);
+        let mut pty = spawn_pty_child(
+            PathBuf::from("stty"),
+            vec!["size".to_string()],
+            std::env::current_dir().unwrap(),
+            env,
+            123,
+        )
+        .unwrap();
+
+        let mut output = String::new();
+        pty.output.read_to_string(&mut output).await.unwrap();
+        assert_eq!(output.trim(), format!("{PTY_ROWS} 123"));
+    }
+}
@@ -0,0 +1,288 @@
+//! Review mode (`Op::StartReview`): the model reviews the diff of the
+//! working directory under a review system prompt and replies with its
+//! findings as JSON, which the session reports as `ReviewFinding` events.
+
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::protocol::ReviewFinding;
+
+/// Diffs larger than this are cut off so the review fits the context window.
+const MAX_DIFF_BYTES: usize = 200 * 1024;
+
+/// System prompt of review turns, in place of the built-in instructions.
+pub(crate) const REVIEW_INSTRUCTIONS: &str = r#"You are a meticulous code reviewer. You are given the diff of a change to the repository in the current directory.
+
+Look for bugs, security issues, missing error handling and confusing code in the changed lines. You may run read-only commands and read other files for context, but do not modify anything.
+
+Finish with a reply that contains only a JSON array of findings, without any other text. Each finding is an object with these keys:
+- "file": the path of the changed file, relative to the repository root
+- "line_range": {"start": first line, "end": last line}, 1-based line numbers in the new version of the file
+- "severity": "error", "warning" or "info"
+- "body": what is wrong and how to fix it
+Reply with [] if there is nothing worth commenting on."#;
+
+/// `git diff --no-index` reads this as an empty file.
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+#[cfg(not(windows))]
+const NULL_DEVICE: &str = "/dev/null";
+
+/// The diff to review: the commits on `HEAD` since it branched off `base`,
+/// or the uncommitted changes (staged or not, and new files that are not
+/// ignored) without a base.
+pub async fn git_diff(cwd: &Path, base: Option<&str>) -> Result<String, String> {
+    let Some(base) = base else {
+        return uncommitted_diff(cwd).await;
+    };
+    // `--end-of-options` keeps a base such as `--output=x` from being read as
+    // an option.
+    let range = format!("{base}...HEAD");
+    git(
+        cwd,
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--end-of-options",
+            &range,
+        ],
+        &[0],
+    )
+    .await
+}
+
+async fn uncommitted_diff(cwd: &Path) -> Result<String, String> {
+    let mut diff = git(cwd, &["diff", "--no-color", "--no-ext-diff", "HEAD"], &[0]).await?;
+    let untracked = git(
+        cwd,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+        &[0],
+    )
+    .await?;
+    for file in untracked.split('\0').filter(|file| !file.is_empty()) {
+        // Exits with 1 because the files differ.
+        diff.push_str(
+            &git(
+                cwd,
+                &[
+                    "diff",
+                    "--no-color",
+                    "--no-ext-diff",
+                    "--no-index",
+                    "--",
+                    NULL_DEVICE,
+                    file,
+                ],
+                &[0, 1],
+            )
+            .await?,
+        );
+    }
+    Ok(diff)
+}
+
+/// Run git in `cwd` and return its output if it exits with one of
+/// `success_codes`.
+async fn git(cwd: &Path, args: &[&str], success_codes: &[i32]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .await
+        .map_err(|e| format!("failed to run git {}: {e}", args[0]))?;
+    if !output
+        .status
+        .code()
+        .is_some_and(|code| success_codes.contains(&code))
+    {
+        return Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The user message that starts a review of `diff`.
+pub(crate) fn review_request(diff: &str, base: Option<&str>) -> String {
+    let what = match base {
+        Some(base) => format!("the commits on HEAD since it branched off `{base}`"),
+        None => "the uncommitted changes".to_string(),
+    };
+    let (diff, note) = if diff.len() > MAX_DIFF_BYTES {
+        let mut end = MAX_DIFF_BYTES;
+        while !diff.is_char_boundary(end) {
+            end -= 1;
+        }
+        (
+            &diff[..end],
+            "\n[The diff was truncated; review the rest by reading the files.]",
+        )
+    } else {
+        (diff, "")
+    };
+    format!("Review {what}.\n\n```diff\n{diff}\n```{note}")
+}
+
+/// Parse the findings from the final reply of a review, tolerating text or a
+/// code fence around the JSON array.
+pub(crate) fn parse_findings(reply: &str) -> Result<Vec<ReviewFinding>, String> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Err("the review reply does not contain a JSON array".to_string()),
+    };
+    let mut findings: Vec<ReviewFinding> = serde_json::from_str(json)
+        .map_err(|e| format!("the review reply is not a valid list of findings: {e}"))?;
+    for finding in &mut findings {
+        if finding.line_range.end < finding.line_range.start {
+            finding.line_range.end = finding.line_range.start;
+        }
+    }
+    Ok(findings)
+}
+
+/// The hunks of a unified `diff` of `file` that overlap `start..=end` in
+/// the new version of the file, or all of the file's hunks if none does.
+pub fn hunks_for_lines(diff: &str, file: &str, start: u32, end: u32) -> Vec<String> {
+    // Each hunk with whether it overlaps the lines.
+    let mut hunks: Vec<(String, bool)> = Vec::new();
+    let mut in_file = false;
+    let mut current: Option<(String, bool)> = None;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            hunks.extend(current.take());
+            in_file = line.ends_with(&format!(" b/{file}"));
+            continue;
+        }
+        if !in_file {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("@@ ") {
+            hunks.extend(current.take());
+            let overlaps = new_range(header)
+                .is_some_and(|(first, count)| first <= end && start < first + count.max(1));
+            current = Some((line.to_string(), overlaps));
+        } else if let Some((hunk, _)) = current.as_mut() {
+            hunk.push('\n');
+            hunk.push_str(line);
+        }
+    }
+    hunks.extend(current.take());
+
+    let any_overlap = hunks.iter().any(|(_, overlaps)| *overlaps);
+    hunks
+        .into_iter()
+        .filter(|(_, overlaps)| *overlaps || !any_overlap)
+        .map(|(hunk, _)| hunk)
+        .collect()
+}
+
+/// First line and line count of the new side of a hunk header
+/// (`-a,b +c,d @@`).
+fn new_range(header: &str) -> Option<(u32, u32)> {
+    let new = header
+        .split_whitespace()
+        .find(|part| part.starts_with('+'))?;
+    let mut parts = new[1..].splitn(2, ',');
+    let first = parts.next()?.parse().ok()?;
+    let count = match parts.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+    Some((first, count))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::protocol::LineRange;
+    use crate::protocol::ReviewSeverity;
+
+    #[tokio::test]
+    async fn uncommitted_diff_includes_new_files() {
+        let repo = tempfile::tempdir().unwrap();
+        let run = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=Codex",
+                    "-c",
+                    "user.email=codex@example.com",
+                ])
+                .args(args)
+                .current_dir(repo.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?} failed");
+        };
+        run(&["init", "-q"]);
+        std::fs::write(repo.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        run(&["add", "lib.rs"]);
+        run(&["commit", "-q", "-m", "initial"]);
+        std::fs::write(repo.path().join("lib.rs"), "fn b() {}\n").unwrap();
+        std::fs::write(repo.path().join("new.rs"), "fn c() {}\n").unwrap();
+        std::fs::write(repo.path().join(".gitignore"), "target/\n").unwrap();
+        std::fs::create_dir(repo.path().join("target")).unwrap();
+        std::fs::write(repo.path().join("target/out.rs"), "").unwrap();
+
+        let diff = git_diff(repo.path(), None).await.unwrap();
+        assert!(diff.contains("+fn b() {}"));
+        assert!(diff.contains("+++ b/new.rs\n@@ -0,0 +1 @@\n+fn c() {}"));
+        assert!(!diff.contains("out.rs"));
+
+        assert!(git_diff(repo.path(), Some("--output=x")).await.is_err());
+        assert!(!repo.path().join("x").exists());
+    }
+
+    #[test]
+    fn parses_findings_inside_code_fence() {
+        let reply = "```json\n[{\"file\": \"src/lib.rs\", \"line_range\": {\"start\": 7, \"end\": 3}, \"severity\": \"warning\", \"body\": \"Unchecked [index].\"}]\n```";
+        assert_eq!(
+            parse_findings(reply).unwrap(),
+            vec![ReviewFinding {
+                file: "src/lib.rs".to_string(),
+                line_range: LineRange { start: 7, end: 7 },
+                severity: ReviewSeverity::Warning,
+                body: "Unchecked [index].".to_string(),
+            }]
+        );
+        assert_eq!(parse_findings("[]").unwrap(), Vec::new());
+        assert!(parse_findings("Looks good to me!").is_err());
+    }
+
+    #[test]
+    fn picks_the_hunks_around_a_finding() {
+        let diff = "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,3 @@
+ fn a() {}
++fn b() {}
+@@ -40,3 +41,4 @@ impl A {
+     x
++    y
+diff --git a/src/b.rs b/src/b.rs
+@@ -1 +1 @@
+-old
++new
+";
+        assert_eq!(
+            hunks_for_lines(diff, "src/a.rs", 43, 43),
+            vec!["@@ -40,3 +41,4 @@ impl A {\n     x\n+    y".to_string()]
+        );
+        // A finding outside every hunk shows the whole diff of the file.
+        assert_eq!(hunks_for_lines(diff, "src/a.rs", 20, 20).len(), 2);
+        assert_eq!(
+            hunks_for_lines(diff, "src/b.rs", 1, 1),
+            vec!["@@ -1 +1 @@\n-old\n+new".to_string()]
+        );
+        assert!(hunks_for_lines(diff, "src/c.rs", 1, 1).is_empty());
+    }
+}
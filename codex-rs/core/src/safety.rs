@@ -6,11 +6,16 @@ use std::path::PathBuf;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 
+use crate::command_wrapper::normalize_wrapped_command;
 use crate::config::AutoAllowPredicate;
 use crate::exec::SandboxType;
+use crate::is_safe_command::WordOnlyCommand;
 use crate::is_safe_command::is_known_safe_command;
+use crate::is_safe_command::parse_bash_word_only_commands_sequence;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::windows_shell::parse_windows_word_only_commands_sequence;
+use crate::windows_shell::windows_shell_script;
 
 #[derive(Debug)]
 pub enum SafetyCheck {
@@ -69,6 +74,7 @@ pub fn assess_command_safety(
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
+    approved_prefixes: &HashSet<Vec<String>>,
 ) -> SafetyCheck {
     let approve_without_sandbox = || SafetyCheck::AutoApprove {
         sandbox_type: SandboxType::None,
@@ -76,7 +82,10 @@ pub fn assess_command_safety(
 
     // Previously approved or allow-listed commands
     // All approval modes allow these commands to continue without sandboxing
-    if is_known_safe_command(command) || approved.contains(command) {
+    if is_known_safe_command(command)
+        || approved.contains(command)
+        || is_approved_by_prefix(command, approved_prefixes)
+    {
         // TODO(ragona): I think we should consider running even these inside the sandbox, but it's
         // a change in behavior so I'm keeping it at parity with upstream for now.
         return approve_without_sandbox();
@@ -104,6 +113,84 @@ pub fn assess_command_safety(
     }
 }
 
+/// Programs that run another, arbitrary command. Approving them by prefix
+/// would approve everything.
+const NON_PREFIXABLE_PROGRAMS: &[&str] = &[
+    "bash",
+    "sh",
+    "zsh",
+    "env",
+    "sudo",
+    "xargs",
+    "eval",
+    "exec",
+    "nohup",
+    "time",
+    "timeout",
+    "command",
+    "powershell",
+    "pwsh",
+    "cmd",
+    "start",
+];
+
+/// The individual plain-word commands that make up `command`, looking inside
+/// `bash -lc`, `powershell -Command` and `cmd /c` scripts.
+fn word_only_commands(command: &[String]) -> Option<Vec<WordOnlyCommand>> {
+    if let Some((shell, script)) = windows_shell_script(command) {
+        let commands = parse_windows_word_only_commands_sequence(shell, script)?;
+        return Some(
+            commands
+                .into_iter()
+                .map(WordOnlyCommand::from_words)
+                .collect(),
+        );
+    }
+    match command {
+        // Only `bash -lc` is parsed with the bash grammar; `sh` and `zsh`
+        // scripts can mean something else and are left as one opaque command.
+        [bash, flag, script] if bash == "bash" && flag == "-lc" => {
+            // Changing directory first does not change what runs, so a
+            // leading `cd <dir> &&` is not counted as one of the commands.
+            match normalize_wrapped_command(command) {
+                Some(normalized) => parse_bash_word_only_commands_sequence(&normalized.script),
+                None => parse_bash_word_only_commands_sequence(script),
+            }
+        }
+        [] => None,
+        _ => Some(vec![WordOnlyCommand::from_words(command.to_vec())]),
+    }
+}
+
+/// The prefix the user may approve for the rest of the session after being
+/// asked about `command`: its program name, provided every command in the
+/// script runs that same program (e.g. `cargo build && cargo test`).
+/// Environment variable assignments in front of the program are ignored.
+pub fn session_prefix_for_command(command: &[String]) -> Option<Vec<String>> {
+    let commands = word_only_commands(command)?;
+    let program = commands.first()?.words.first()?;
+    if NON_PREFIXABLE_PROGRAMS.contains(&program.as_str())
+        || !commands.iter().all(|c| c.words.first() == Some(program))
+    {
+        return None;
+    }
+    Some(vec![program.clone()])
+}
+
+/// True if every command in `command` starts with one of `prefixes` and sets
+/// no environment variables that could make it run other code.
+fn is_approved_by_prefix(command: &[String], prefixes: &HashSet<Vec<String>>) -> bool {
+    if prefixes.is_empty() {
+        return false;
+    }
+    word_only_commands(command).is_some_and(|commands| {
+        commands.iter().all(|c| {
+            c.has_only_harmless_assignments()
+                && prefixes.iter().any(|prefix| c.words.starts_with(prefix))
+        })
+    })
+}
+
 pub fn get_platform_sandbox() -> Option<SandboxType> {
     if cfg!(target_os = "macos") {
         Some(SandboxType::MacosSeatbelt)
@@ -386,4 +473,74 @@ mod tests {
         let vote = evaluate_auto_allow_predicates(&["cmd".to_string()], &preds);
         assert_eq!(vote, AutoAllowVote::Allow);
     }
+
+    #[test]
+    fn session_prefix_is_the_shared_program() {
+        let bash = |script: &str| vec!["bash".to_string(), "-lc".to_string(), script.to_string()];
+
+        assert_eq!(
+            session_prefix_for_command(&bash("cargo build && cargo test")),
+            Some(vec!["cargo".to_string()])
+        );
+        assert_eq!(
+            session_prefix_for_command(&bash("cargo test | tee log")),
+            None
+        );
+        assert_eq!(session_prefix_for_command(&bash("sudo cargo test")), None);
+        assert_eq!(session_prefix_for_command(&bash("cargo test > log")), None);
+        assert_eq!(
+            session_prefix_for_command(&bash("cd core && cargo test")),
+            Some(vec!["cargo".to_string()])
+        );
+    }
+
+    #[test]
+    fn approved_prefix_covers_every_command_in_script() {
+        let bash = |script: &str| vec!["bash".to_string(), "-lc".to_string(), script.to_string()];
+        let prefixes = HashSet::from([vec!["cargo".to_string()]]);
+
+        assert!(is_approved_by_prefix(
+            &bash("cargo fmt && cargo clippy"),
+            &prefixes
+        ));
+        assert!(is_approved_by_prefix(
+            &["cargo".to_string(), "test".to_string()],
+            &prefixes
+        ));
+        assert!(is_approved_by_prefix(
+            &bash("RUST_BACKTRACE=1 cargo test"),
+            &prefixes
+        ));
+        assert!(!is_approved_by_prefix(
+            &bash("RUSTC_WRAPPER=./evil cargo build"),
+            &prefixes
+        ));
+        assert!(!is_approved_by_prefix(
+            &bash("cargo test; rm -rf ~"),
+            &prefixes
+        ));
+        assert!(!is_approved_by_prefix(&bash("cargo test $(id)"), &prefixes));
+    }
+
+    #[test]
+    fn only_bash_lc_scripts_are_looked_into() {
+        let prefixes = HashSet::from([vec!["cargo".to_string()]]);
+        for shell in ["sh", "zsh"] {
+            let command = vec![
+                shell.to_string(),
+                "-c".to_string(),
+                "cd core && cargo test".to_string(),
+            ];
+            assert!(!is_approved_by_prefix(&command, &prefixes));
+            assert_eq!(session_prefix_for_command(&command), None);
+        }
+        assert!(is_approved_by_prefix(
+            &[
+                "bash".to_string(),
+                "-lc".to_string(),
+                "cd core && cargo test".to_string()
+            ],
+            &prefixes
+        ));
+    }
 }
@@ -0,0 +1,237 @@
+//! `sampling/createMessage` for `codex mcp serve`: MCP clients ask Codex's
+//! configured model for a completion. Requests are refused unless
+//! `[mcp_sampling]` enables them for the client, and their token usage is
+//! tracked like a session's: it counts against `max_session_tokens` and is
+//! reported as [`TokenCountEvent`]s.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use futures::StreamExt;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::Role;
+use mcp_types::SamplingMessage;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use tokio::sync::Mutex;
+
+use crate::client::ModelClient;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::config::Config;
+use crate::config_types::McpSamplingConfig;
+use crate::model_pricing::ModelPricing;
+use crate::models::ContentItem;
+use crate::models::ResponseItem;
+use crate::openai_tools::builtin_tool_names;
+use crate::protocol::TokenCountEvent;
+use crate::token_budget::TokenBudget;
+
+/// System prompt when the request does not bring one.
+const DEFAULT_INSTRUCTIONS: &str = "You are a helpful assistant.";
+
+/// Answers the sampling requests of one MCP server process.
+pub struct SamplingSession {
+    client: ModelClient,
+    model: String,
+    config: McpSamplingConfig,
+    model_pricing: Option<ModelPricing>,
+    budget: Mutex<TokenBudget>,
+}
+
+impl SamplingSession {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            client: ModelClient::new(
+                config.model.clone(),
+                config.model_provider.clone(),
+                config.model_reasoning_effort,
+                config.model_reasoning_summary,
+                config.prompt_caching,
+            )
+            .with_organization(config.org_id.as_deref(), config.workspace_id.as_deref()),
+            model: config.model.clone(),
+            config: config.mcp_sampling.clone(),
+            model_pricing: config.model_pricing_for(&config.model),
+            budget: Mutex::new(TokenBudget::new(
+                config.max_session_tokens,
+                Default::default(),
+            )),
+        }
+    }
+
+    /// Whether `client_name` may sample, or why not.
+    pub fn check_allowed(&self, client_name: &str) -> Result<(), String> {
+        if !self.config.enabled {
+            Err("sampling is not enabled in [mcp_sampling]".to_string())
+        } else if !self.config.clients.is_empty()
+            && !self.config.clients.iter().any(|name| name == client_name)
+        {
+            Err(format!(
+                "client '{client_name}' is not allowed to sample; add it to [mcp_sampling] clients"
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Runs the conversation of `params` through the model and returns its
+    /// reply with the token usage of the request. `maxTokens`,
+    /// `temperature`, `stopSequences` and `includeContext` are not
+    /// forwarded: the model runs with its configured settings.
+    pub async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+    ) -> Result<(CreateMessageResult, Option<TokenCountEvent>), String> {
+        if self.budget.lock().await.is_exhausted() {
+            return Err("the token budget (`max_session_tokens`) is used up".to_string());
+        }
+        let input = params
+            .messages
+            .into_iter()
+            .map(sampling_message_to_item)
+            .collect::<Result<Vec<_>, _>>()?;
+        let prompt = Prompt {
+            input,
+            prev_id: None,
+            base_instructions_override: Some(
+                params
+                    .system_prompt
+                    .unwrap_or_else(|| DEFAULT_INSTRUCTIONS.to_string()),
+            ),
+            user_instructions: None,
+            store: false,
+            extra_tools: HashMap::new(),
+            strict_tools: HashSet::new(),
+            disabled_tools: builtin_tool_names().map(str::to_string).collect(),
+            hosted_web_search: false,
+        };
+
+        let mut stream = self
+            .client
+            .stream(&prompt)
+            .await
+            .map_err(|e| format!("model request failed: {e}"))?;
+        let mut reply = String::new();
+        let mut token_count = None;
+        while let Some(event) = stream.next().await {
+            match event.map_err(|e| format!("model request failed: {e}"))? {
+                ResponseEvent::OutputItemDone(ResponseItem::Message { role, content })
+                    if role == "assistant" =>
+                {
+                    for item in content {
+                        if let ContentItem::OutputText { text } = item {
+                            reply.push_str(&text);
+                        }
+                    }
+                }
+                ResponseEvent::OutputItemDone(_) => {}
+                ResponseEvent::Completed { token_usage, .. } => {
+                    if let Some(usage) = token_usage {
+                        let mut budget = self.budget.lock().await;
+                        budget.record(&usage);
+                        let total = budget.total();
+                        token_count = Some(TokenCountEvent {
+                            last: usage,
+                            total,
+                            max_session_tokens: budget.max_tokens(),
+                            estimated_cost_usd: self
+                                .model_pricing
+                                .map(|pricing| pricing.cost_usd(&total)),
+                            model_context_window: None,
+                        });
+                    }
+                    break;
+                }
+            }
+        }
+
+        let result = CreateMessageResult {
+            content: CreateMessageResultContent::TextContent(TextContent {
+                annotations: None,
+                text: reply,
+                r#type: "text".to_string(),
+            }),
+            model: self.model.clone(),
+            role: Role::Assistant,
+            stop_reason: Some("endTurn".to_string()),
+        };
+        Ok((result, token_count))
+    }
+}
+
+/// The model input for one message of the sampled conversation. Images are
+/// passed as data URLs; audio is not supported.
+fn sampling_message_to_item(message: SamplingMessage) -> Result<ResponseItem, String> {
+    let (role, content) = match (message.role, message.content) {
+        (Role::User, SamplingMessageContent::TextContent(content)) => {
+            ("user", ContentItem::InputText { text: content.text })
+        }
+        (Role::Assistant, SamplingMessageContent::TextContent(content)) => {
+            ("assistant", ContentItem::OutputText { text: content.text })
+        }
+        (Role::User, SamplingMessageContent::ImageContent(content)) => (
+            "user",
+            ContentItem::InputImage {
+                image_url: format!("data:{};base64,{}", content.mime_type, content.data),
+            },
+        ),
+        (Role::Assistant, SamplingMessageContent::ImageContent(_)) => {
+            return Err("assistant messages with images are not supported".to_string());
+        }
+        (_, SamplingMessageContent::AudioContent(_)) => {
+            return Err("audio content is not supported".to_string());
+        }
+    };
+    Ok(ResponseItem::Message {
+        role: role.to_string(),
+        content: vec![content],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use mcp_types::ImageContent;
+
+    #[test]
+    fn converts_sampling_messages() {
+        let message = |role, content| SamplingMessage { content, role };
+        let item = sampling_message_to_item(message(
+            Role::User,
+            SamplingMessageContent::ImageContent(ImageContent {
+                annotations: None,
+                data: "iVBORw0K".to_string(),
+                mime_type: "image/png".to_string(),
+                r#type: "image".to_string(),
+            }),
+        ))
+        .unwrap();
+        let ResponseItem::Message { role, content } = item else {
+            panic!("expected a message");
+        };
+        assert_eq!(role, "user");
+        assert!(matches!(
+            &content[..],
+            [ContentItem::InputImage { image_url }] if image_url == "data:image/png;base64,iVBORw0K"
+        ));
+
+        let item = sampling_message_to_item(message(
+            Role::Assistant,
+            SamplingMessageContent::TextContent(TextContent {
+                annotations: None,
+                text: "Hi".to_string(),
+                r#type: "text".to_string(),
+            }),
+        ))
+        .unwrap();
+        assert!(matches!(
+            item,
+            ResponseItem::Message { role, .. } if role == "assistant"
+        ));
+    }
+}
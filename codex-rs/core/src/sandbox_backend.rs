@@ -0,0 +1,203 @@
+//! Backends that isolate sandboxed shell commands.
+//!
+//! A [`SandboxBackend`] turns the command the model asked for into the
+//! program invocation that runs it under the backend's isolation mechanism.
+//! The process is then spawned and its output consumed the same way for every
+//! backend (see [`crate::exec::process_exec_tool_call`]).
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::config_types::ContainerSandboxConfig;
+use crate::exec::MACOS_PATH_TO_SEATBELT_EXECUTABLE;
+use crate::exec::create_linux_sandbox_command_args;
+use crate::exec::create_seatbelt_command_args;
+use crate::protocol::SandboxPolicy;
+
+/// Program invocation that runs a command under a sandbox backend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxedCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Value for `argv[0]`, if it should differ from `program`.
+    pub arg0: Option<&'static str>,
+}
+
+pub trait SandboxBackend: Send + Sync {
+    /// Wrap `command` so that it runs under this backend with the
+    /// restrictions described by `sandbox_policy`.
+    fn wrap_command(
+        &self,
+        command: Vec<String>,
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> SandboxedCommand;
+}
+
+/// macOS Seatbelt (`sandbox-exec`).
+pub struct SeatbeltBackend;
+
+impl SandboxBackend for SeatbeltBackend {
+    fn wrap_command(
+        &self,
+        command: Vec<String>,
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> SandboxedCommand {
+        SandboxedCommand {
+            program: PathBuf::from(MACOS_PATH_TO_SEATBELT_EXECUTABLE),
+            args: create_seatbelt_command_args(command, sandbox_policy, cwd),
+            arg0: None,
+        }
+    }
+}
+
+/// Linux Landlock+seccomp via the `codex-linux-sandbox` helper.
+pub struct LinuxSandboxBackend<'a> {
+    pub codex_linux_sandbox_exe: &'a Path,
+}
+
+impl SandboxBackend for LinuxSandboxBackend<'_> {
+    fn wrap_command(
+        &self,
+        command: Vec<String>,
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> SandboxedCommand {
+        SandboxedCommand {
+            program: self.codex_linux_sandbox_exe.to_path_buf(),
+            args: create_linux_sandbox_command_args(command, sandbox_policy, cwd),
+            arg0: Some("codex-linux-sandbox"),
+        }
+    }
+}
+
+/// A throwaway Docker/Podman container. The working directory and the
+/// writable roots are bind-mounted at the same paths as on the host; the cwd
+/// is mounted read-only unless the policy allows writing to it. Environment
+/// variables are forwarded by name, so their values come from the
+/// environment the engine itself is spawned with.
+pub struct ContainerBackend<'a> {
+    pub config: &'a ContainerSandboxConfig,
+    pub env_keys: Vec<String>,
+    /// Name for the container, so that it can be removed if the command is
+    /// killed.
+    pub name: Option<String>,
+}
+
+impl SandboxBackend for ContainerBackend<'_> {
+    fn wrap_command(
+        &self,
+        command: Vec<String>,
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> SandboxedCommand {
+        let cwd_str = cwd.to_string_lossy().to_string();
+        let mut args: Vec<String> = ["run", "--rm", "-i", "--init"].map(String::from).into();
+        if let Some(name) = &self.name {
+            args.extend(["--name".to_string(), name.clone()]);
+        }
+
+        if !sandbox_policy.has_full_network_access() {
+            args.extend(["--network", "none"].map(String::from));
+        }
+
+        let writable_roots = if sandbox_policy.has_full_disk_write_access() {
+            vec![cwd.to_path_buf()]
+        } else {
+            sandbox_policy.get_writable_roots_with_cwd(cwd)
+        };
+        if !writable_roots.iter().any(|root| root == cwd) {
+            args.extend(["-v".to_string(), format!("{cwd_str}:{cwd_str}:ro")]);
+        }
+        for root in writable_roots {
+            let root = root.to_string_lossy();
+            args.extend(["-v".to_string(), format!("{root}:{root}")]);
+        }
+
+        args.extend(["-w".to_string(), cwd_str]);
+        for key in &self.env_keys {
+            args.extend(["-e".to_string(), key.clone()]);
+        }
+
+        args.push(self.config.image.clone());
+        args.extend(command);
+
+        SandboxedCommand {
+            program: PathBuf::from(&self.config.engine),
+            args,
+            arg0: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::SandboxPermission;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn container_mounts_cwd_writable_and_disables_network() {
+        let config = ContainerSandboxConfig::default();
+        let backend = ContainerBackend {
+            config: &config,
+            env_keys: vec!["PATH".to_string()],
+            name: None,
+        };
+        let policy = SandboxPolicy::from(vec![
+            SandboxPermission::DiskFullReadAccess,
+            SandboxPermission::DiskWriteCwd,
+        ]);
+        let wrapped = backend.wrap_command(
+            vec!["ls".to_string(), "-l".to_string()],
+            &policy,
+            Path::new("/work"),
+        );
+        assert_eq!(wrapped.program, PathBuf::from("docker"));
+        assert_eq!(
+            wrapped.args,
+            [
+                "run",
+                "--rm",
+                "-i",
+                "--init",
+                "--network",
+                "none",
+                "-v",
+                "/work:/work",
+                "-w",
+                "/work",
+                "-e",
+                "PATH",
+                "ubuntu:24.04",
+                "ls",
+                "-l",
+            ]
+        );
+    }
+
+    #[test]
+    fn container_mounts_cwd_read_only_for_read_only_policy() {
+        let config = ContainerSandboxConfig {
+            engine: "podman".to_string(),
+            image: "alpine".to_string(),
+        };
+        let backend = ContainerBackend {
+            config: &config,
+            env_keys: Vec::new(),
+            name: Some("codex-exec-1".to_string()),
+        };
+        let wrapped = backend.wrap_command(
+            vec!["true".to_string()],
+            &SandboxPolicy::new_read_only_policy(),
+            Path::new("/work"),
+        );
+        assert_eq!(wrapped.program, PathBuf::from("podman"));
+        assert_eq!(
+            wrapped.args[..6],
+            ["run", "--rm", "-i", "--init", "--name", "codex-exec-1"]
+        );
+        assert!(wrapped.args.contains(&"/work:/work:ro".to_string()));
+    }
+}
@@ -0,0 +1,624 @@
+//! Session store – persists a Codex conversation to disk as it progresses so
+//! that it can be resumed later with `codex resume`.
+//!
+//! Every session is written to `~/.codex/sessions/<id>.jsonl`. Each line is a
+//! [`SessionRecord`]: a leading `meta` record, a `turn_context` record each
+//! time the session is (re)configured, one `item` record per [`ResponseItem`]
+//! exchanged with the model, and a `turn_completed` record carrying the
+//! response id and token usage reported at the end of each model turn.
+//!
+//! Older `rollout-<timestamp>-<id>.jsonl` files, which contain bare
+//! [`ResponseItem`]s, can still be loaded.
+
+use std::fs::File;
+use std::fs::{self};
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Error as IoError;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::{self};
+use uuid::Uuid;
+
+use crate::models::ContentItem;
+use crate::models::ResponseItem;
+use crate::protocol::AskForApproval;
+use crate::protocol::SandboxPolicy;
+use crate::protocol::TokenUsage;
+
+/// Folder inside `~/.codex` that holds saved sessions.
+const SESSIONS_SUBDIR: &str = "sessions";
+
+/// Filename prefix used by the previous rollout format.
+const LEGACY_ROLLOUT_PREFIX: &str = "rollout-";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionMeta {
+    pub id: Uuid,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+/// Settings in effect for the turns that follow this record.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TurnContext {
+    pub cwd: PathBuf,
+    pub model: String,
+    /// Key of the session's provider in `model_providers`; absent in older
+    /// session files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_provider: Option<String>,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+}
+
+/// A single line of a session file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum SessionRecord {
+    Meta(SessionMeta),
+    TurnContext(TurnContext),
+    Item {
+        item: ResponseItem,
+    },
+    TurnCompleted {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        response_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token_usage: Option<TokenUsage>,
+    },
+}
+
+/// Appends [`SessionRecord`]s for a session to its file, flushing after every
+/// update.
+///
+/// Sessions are recorded as JSONL and can be inspected with tools such as:
+///
+/// ```ignore
+/// $ jq -C . ~/.codex/sessions/5973b6c0-94b8-487b-a530-2aeb6098ae0e.jsonl
+/// $ fx ~/.codex/sessions/5973b6c0-94b8-487b-a530-2aeb6098ae0e.jsonl
+/// ```
+#[derive(Clone)]
+pub(crate) struct SessionStore {
+    tx: Sender<String>,
+}
+
+impl SessionStore {
+    /// Open (or create) the session file for `session_id`. When the file is
+    /// new a `meta` record is written first; when resuming, records are
+    /// appended to the existing file, even a legacy rollout file.
+    pub async fn new(
+        codex_home: &Path,
+        session_id: Uuid,
+        instructions: Option<String>,
+    ) -> std::io::Result<Self> {
+        let dir = codex_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&dir)?;
+        let path = find_session_file(codex_home, session_id)
+            .unwrap_or_else(|| session_path(codex_home, session_id));
+        let is_new = !path.exists();
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)?;
+
+        // A reasonably-sized bounded channel. If the buffer fills up the send
+        // future will yield, which is fine – we only need to ensure we do not
+        // perform *blocking* I/O on the caller’s thread.
+        let (tx, mut rx) = mpsc::channel::<String>(256);
+
+        // Spawn a Tokio task that owns the file handle and performs async
+        // writes. Using `tokio::fs::File` keeps everything on the async I/O
+        // driver instead of blocking the runtime.
+        tokio::task::spawn(async move {
+            let mut file = tokio::fs::File::from_std(file);
+
+            while let Some(line) = rx.recv().await {
+                // Write line + newline, then flush to disk.
+                if let Err(e) = file.write_all(line.as_bytes()).await {
+                    tracing::warn!("session writer: failed to write line: {e}");
+                    break;
+                }
+                if let Err(e) = file.write_all(b"\n").await {
+                    tracing::warn!("session writer: failed to write newline: {e}");
+                    break;
+                }
+                if let Err(e) = file.flush().await {
+                    tracing::warn!("session writer: failed to flush: {e}");
+                    break;
+                }
+            }
+        });
+
+        let store = Self { tx };
+        if is_new {
+            store
+                .record(&SessionRecord::Meta(SessionMeta {
+                    id: session_id,
+                    timestamp: now_timestamp()?,
+                    instructions,
+                }))
+                .await?;
+        }
+        Ok(store)
+    }
+
+    /// Append `items` to the session file.
+    pub(crate) async fn record_items(&self, items: &[ResponseItem]) -> std::io::Result<()> {
+        for item in items {
+            match item {
+                // Note that function calls may look a bit strange if they are
+                // "fully qualified MCP tool calls," so we could consider
+                // reformatting them in that case.
+                ResponseItem::Message { .. }
+                | ResponseItem::LocalShellCall { .. }
+                | ResponseItem::FunctionCall { .. }
+                | ResponseItem::FunctionCallOutput { .. } => {}
+                ResponseItem::Reasoning { .. } | ResponseItem::Other => {
+                    // These should never be serialized.
+                    continue;
+                }
+            }
+            self.record(&SessionRecord::Item { item: item.clone() })
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn record_turn_context(&self, context: TurnContext) -> std::io::Result<()> {
+        self.record(&SessionRecord::TurnContext(context)).await
+    }
+
+    pub(crate) async fn record_turn_completed(
+        &self,
+        response_id: Option<String>,
+        token_usage: Option<TokenUsage>,
+    ) -> std::io::Result<()> {
+        self.record(&SessionRecord::TurnCompleted {
+            response_id,
+            token_usage,
+        })
+        .await
+    }
+
+    async fn record(&self, record: &SessionRecord) -> std::io::Result<()> {
+        // Serialize the record to JSON first so that the writer task only has
+        // to perform the actual write.
+        let json = serde_json::to_string(record)
+            .map_err(|e| IoError::other(format!("failed to serialize session record: {e}")))?;
+
+        self.tx
+            .send(json)
+            .await
+            .map_err(|e| IoError::other(format!("failed to queue session record: {e}")))
+    }
+}
+
+fn now_timestamp() -> std::io::Result<String> {
+    let timestamp_format: &[FormatItem] =
+        format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+    OffsetDateTime::now_utc()
+        .format(timestamp_format)
+        .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))
+}
+
+/// Everything needed to continue a previously persisted session.
+#[derive(Debug, Clone, Default)]
+pub struct SavedSession {
+    pub items: Vec<ResponseItem>,
+    /// Total token usage across all recorded turns.
+    pub token_usage: TokenUsage,
+    /// The most recent turn context, if any was recorded. Resuming continues
+    /// with its model, working directory and approval policy.
+    pub turn_context: Option<TurnContext>,
+    /// Response id of the last completed turn (Responses API only).
+    pub previous_response_id: Option<String>,
+}
+
+impl SavedSession {
+    /// Text of each user message, in order. The index of a message here is
+    /// the `before_user_message` to pass to [`fork_session`].
+    pub fn user_messages(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                ResponseItem::Message { role, content } if role == "user" => Some(
+                    content
+                        .iter()
+                        .filter_map(|c| match c {
+                            ContentItem::InputText { text } => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join(""),
+                ),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Short description of a saved session, used by `codex resume`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    /// First user message of the session, if any.
+    pub preview: Option<String>,
+}
+
+fn session_path(codex_home: &Path, session_id: Uuid) -> PathBuf {
+    codex_home
+        .join(SESSIONS_SUBDIR)
+        .join(format!("{session_id}.jsonl"))
+}
+
+/// Extract the session id from a `<id>.jsonl` or legacy
+/// `rollout-<timestamp>-<id>.jsonl` filename.
+fn session_id_from_filename(name: &str) -> Option<Uuid> {
+    let stem = name.strip_suffix(".jsonl")?;
+    if stem.starts_with(LEGACY_ROLLOUT_PREFIX) {
+        // A UUID is always 36 characters long.
+        let id = stem.get(stem.len().checked_sub(36)?..)?;
+        Uuid::parse_str(id).ok()
+    } else {
+        Uuid::parse_str(stem).ok()
+    }
+}
+
+fn find_session_file(codex_home: &Path, session_id: Uuid) -> Option<PathBuf> {
+    let path = session_path(codex_home, session_id);
+    if path.exists() {
+        return Some(path);
+    }
+    fs::read_dir(codex_home.join(SESSIONS_SUBDIR))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(LEGACY_ROLLOUT_PREFIX)
+                        && session_id_from_filename(name) == Some(session_id)
+                })
+        })
+}
+
+/// Parse one line of a session file. Legacy rollout lines are bare
+/// [`ResponseItem`]s; the leading legacy metadata line is skipped.
+fn parse_line(line: &str) -> Option<SessionRecord> {
+    if let Ok(record) = serde_json::from_str::<SessionRecord>(line) {
+        return Some(record);
+    }
+    serde_json::from_str::<ResponseItem>(line)
+        .ok()
+        .map(|item| SessionRecord::Item { item })
+}
+
+/// Load the session with the given id from `~/.codex/sessions`.
+pub fn load_session(codex_home: &Path, session_id: Uuid) -> std::io::Result<SavedSession> {
+    let path = find_session_file(codex_home, session_id).ok_or_else(|| {
+        IoError::new(
+            std::io::ErrorKind::NotFound,
+            format!("no saved session with id {session_id}"),
+        )
+    })?;
+
+    let mut saved = SavedSession::default();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        match parse_line(&line) {
+            Some(SessionRecord::Item { item }) => saved.items.push(item),
+            Some(SessionRecord::TurnContext(context)) => saved.turn_context = Some(context),
+            Some(SessionRecord::TurnCompleted {
+                response_id,
+                token_usage,
+            }) => {
+                if response_id.is_some() {
+                    saved.previous_response_id = response_id;
+                }
+                if let Some(usage) = token_usage {
+                    saved.token_usage.accumulate(&usage);
+                }
+            }
+            Some(SessionRecord::Meta(_)) | None => {}
+        }
+    }
+    Ok(saved)
+}
+
+/// Copy the session with the given id into a new session that ends just
+/// before its `before_user_message`-th (0-based) user message, and return the
+/// new session's id. The original session is left untouched; the fork keeps
+/// the response id of the last turn it contains, so it can also continue from
+/// server-side storage.
+pub fn fork_session(
+    codex_home: &Path,
+    session_id: Uuid,
+    before_user_message: usize,
+) -> std::io::Result<Uuid> {
+    let path = find_session_file(codex_home, session_id).ok_or_else(|| {
+        IoError::new(
+            std::io::ErrorKind::NotFound,
+            format!("no saved session with id {session_id}"),
+        )
+    })?;
+
+    let fork_id = Uuid::new_v4();
+    let mut instructions = None;
+    let mut records = Vec::new();
+    let mut user_messages = 0;
+    for line in BufReader::new(File::open(path)?).lines() {
+        match parse_line(&line?) {
+            Some(SessionRecord::Meta(meta)) => instructions = meta.instructions,
+            Some(SessionRecord::Item {
+                item: ResponseItem::Message { role, .. },
+            }) if role == "user" && user_messages == before_user_message => break,
+            Some(record) => {
+                if let SessionRecord::Item {
+                    item: ResponseItem::Message { role, .. },
+                } = &record
+                {
+                    if role == "user" {
+                        user_messages += 1;
+                    }
+                }
+                records.push(record);
+            }
+            None => {}
+        }
+    }
+
+    let meta = SessionRecord::Meta(SessionMeta {
+        id: fork_id,
+        timestamp: now_timestamp()?,
+        instructions,
+    });
+    let mut contents = String::new();
+    for record in std::iter::once(&meta).chain(&records) {
+        let json = serde_json::to_string(record)
+            .map_err(|e| IoError::other(format!("failed to serialize session record: {e}")))?;
+        contents.push_str(&json);
+        contents.push('\n');
+    }
+    fs::create_dir_all(codex_home.join(SESSIONS_SUBDIR))?;
+    fs::write(session_path(codex_home, fork_id), contents)?;
+    Ok(fork_id)
+}
+
+/// List saved sessions, most recently modified first.
+pub fn list_sessions(codex_home: &Path) -> std::io::Result<Vec<SessionSummary>> {
+    let dir = codex_home.join(SESSIONS_SUBDIR);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(id) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(session_id_from_filename)
+        else {
+            continue;
+        };
+        let modified = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let preview = first_user_message(&path);
+        sessions.push(SessionSummary {
+            id,
+            path,
+            modified,
+            preview,
+        });
+    }
+    sessions.sort_by(|a, b| b.modified.cmp(&a.modified));
+    Ok(sessions)
+}
+
+/// Id of the most recently modified saved session, if any.
+pub fn latest_session_id(codex_home: &Path) -> std::io::Result<Option<Uuid>> {
+    Ok(list_sessions(codex_home)?.first().map(|s| s.id))
+}
+
+fn first_user_message(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .find_map(|line| match parse_line(&line)? {
+            SessionRecord::Item {
+                item: ResponseItem::Message { role, content },
+            } if role == "user" => content.into_iter().find_map(|c| match c {
+                ContentItem::InputText { text } => Some(text),
+                _ => None,
+            }),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn user_message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn write_lines(path: &Path, lines: &[String]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut file = File::create(path).unwrap();
+        for line in lines {
+            writeln!(file, "{line}").unwrap();
+        }
+    }
+
+    #[test]
+    fn load_session_restores_items_usage_and_context() {
+        let codex_home = TempDir::new().unwrap();
+        let id = Uuid::new_v4();
+        let usage = TokenUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            ..Default::default()
+        };
+        let records = [
+            SessionRecord::Meta(SessionMeta {
+                id,
+                timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+                instructions: None,
+            }),
+            SessionRecord::TurnContext(TurnContext {
+                cwd: PathBuf::from("/workspace"),
+                model: "o3".to_string(),
+                model_provider: Some("openai".to_string()),
+                approval_policy: AskForApproval::OnFailure,
+                sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            }),
+            SessionRecord::Item {
+                item: user_message("hello"),
+            },
+            SessionRecord::TurnCompleted {
+                response_id: Some("resp_1".to_string()),
+                token_usage: Some(usage),
+            },
+            SessionRecord::TurnCompleted {
+                response_id: Some("resp_2".to_string()),
+                token_usage: Some(usage),
+            },
+        ];
+        let lines: Vec<String> = records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect();
+        write_lines(&session_path(codex_home.path(), id), &lines);
+
+        let saved = load_session(codex_home.path(), id).unwrap();
+        assert_eq!(saved.items.len(), 1);
+        assert_eq!(saved.token_usage.total_tokens, 30);
+        assert_eq!(saved.previous_response_id.as_deref(), Some("resp_2"));
+        assert_eq!(saved.turn_context.unwrap().model, "o3");
+    }
+
+    #[test]
+    fn legacy_rollout_files_are_listed_and_loaded() {
+        let codex_home = TempDir::new().unwrap();
+        let id = Uuid::new_v4();
+        let path = codex_home
+            .path()
+            .join(SESSIONS_SUBDIR)
+            .join(format!("rollout-2025-05-07T17-24-21-{id}.jsonl"));
+        write_lines(
+            &path,
+            &[
+                format!(r#"{{"id":"{id}","timestamp":"2025-05-07T17:24:21.000Z"}}"#),
+                serde_json::to_string(&user_message("fix the bug")).unwrap(),
+            ],
+        );
+
+        let sessions = list_sessions(codex_home.path()).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, id);
+        assert_eq!(sessions[0].preview.as_deref(), Some("fix the bug"));
+        assert_eq!(latest_session_id(codex_home.path()).unwrap(), Some(id));
+
+        let saved = load_session(codex_home.path(), id).unwrap();
+        assert_eq!(saved.items.len(), 1);
+        // Resuming appends to the legacy file rather than starting a new one
+        // that would hide it.
+        assert_eq!(find_session_file(codex_home.path(), id), Some(path));
+    }
+
+    #[test]
+    fn fork_session_stops_before_the_chosen_user_message() {
+        let codex_home = TempDir::new().unwrap();
+        let id = Uuid::new_v4();
+        let agent_message = ResponseItem::Message {
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: "done".to_string(),
+            }],
+        };
+        let records = [
+            SessionRecord::Meta(SessionMeta {
+                id,
+                timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+                instructions: Some("be brief".to_string()),
+            }),
+            SessionRecord::Item {
+                item: user_message("first"),
+            },
+            SessionRecord::Item {
+                item: agent_message.clone(),
+            },
+            SessionRecord::TurnCompleted {
+                response_id: Some("resp_1".to_string()),
+                token_usage: None,
+            },
+            SessionRecord::Item {
+                item: user_message("second"),
+            },
+            SessionRecord::Item {
+                item: agent_message,
+            },
+            SessionRecord::TurnCompleted {
+                response_id: Some("resp_2".to_string()),
+                token_usage: None,
+            },
+        ];
+        let lines: Vec<String> = records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect();
+        write_lines(&session_path(codex_home.path(), id), &lines);
+
+        let original = load_session(codex_home.path(), id).unwrap();
+        assert_eq!(original.user_messages(), vec!["first", "second"]);
+
+        let fork_id = fork_session(codex_home.path(), id, 1).unwrap();
+        assert_ne!(fork_id, id);
+        let fork = load_session(codex_home.path(), fork_id).unwrap();
+        assert_eq!(fork.user_messages(), vec!["first"]);
+        assert_eq!(fork.items.len(), 2);
+        assert_eq!(fork.previous_response_id.as_deref(), Some("resp_1"));
+        assert_eq!(
+            load_session(codex_home.path(), id).unwrap().items.len(),
+            original.items.len()
+        );
+    }
+
+    #[test]
+    fn missing_session_is_not_found() {
+        let codex_home = TempDir::new().unwrap();
+        let err = load_session(codex_home.path(), Uuid::new_v4()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(list_sessions(codex_home.path()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! The `spawn_agent` tool: the model hands a scoped sub-task to a child
+//! conversation with its own prompt, a restricted set of tools and a bounded
+//! number of turns. The child runs inside the parent's session (same sandbox,
+//! approvals and token budget) but sees none of the parent's history, and its
+//! final message becomes the output of the tool call.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::openai_tools::builtin_tool_names;
+use crate::plan_tool::UPDATE_PLAN_TOOL_NAME;
+
+pub(crate) const SPAWN_AGENT_TOOL_NAME: &str = "spawn_agent";
+
+/// Turns a sub-agent gets when the model does not ask for a number.
+const DEFAULT_MAX_TURNS: u32 = 10;
+
+/// Upper bound on `max_turns`, so a runaway sub-agent cannot stall the turn
+/// that spawned it for long.
+const MAX_TURNS_LIMIT: u32 = 30;
+
+/// Appended to the session's instructions for the child conversation.
+pub(crate) const SUB_AGENT_INSTRUCTIONS: &str = "You are a sub-agent working on a task delegated \
+    by another agent, which only sees your final message. Work on the task without asking \
+    questions, then end with a concise, self-contained report of what you found or changed.";
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SpawnAgentArgs {
+    pub prompt: String,
+    /// Tools the sub-agent may use; all but `spawn_agent` if omitted.
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+}
+
+impl SpawnAgentArgs {
+    pub(crate) fn max_turns(&self) -> u32 {
+        self.max_turns
+            .unwrap_or(DEFAULT_MAX_TURNS)
+            .clamp(1, MAX_TURNS_LIMIT)
+    }
+
+    /// Whether the sub-agent may call `name`. Sub-agents never spawn agents
+    /// of their own, and the plan belongs to the parent.
+    pub(crate) fn allows(&self, name: &str) -> bool {
+        if name == SPAWN_AGENT_TOOL_NAME || name == UPDATE_PLAN_TOOL_NAME {
+            return false;
+        }
+        // Codex models call the shell through the built-in `local_shell`.
+        let name = if name == "local_shell" || name == "container.exec" {
+            "shell"
+        } else {
+            name
+        };
+        self.tools
+            .as_ref()
+            .is_none_or(|tools| tools.iter().any(|tool| tool == name))
+    }
+
+    /// Built-in tools to hide from the sub-agent, on top of those disabled
+    /// for the session.
+    pub(crate) fn disabled_tools(&self, session_disabled: &[String]) -> Vec<String> {
+        let mut disabled = session_disabled.to_vec();
+        for name in builtin_tool_names() {
+            if !self.allows(name) && !disabled.iter().any(|d| d == name) {
+                disabled.push(name.to_string());
+            }
+        }
+        disabled
+    }
+
+    /// The MCP tools (by fully qualified name) offered to the sub-agent.
+    pub(crate) fn mcp_tools(
+        &self,
+        mut tools: HashMap<String, mcp_types::Tool>,
+    ) -> HashMap<String, mcp_types::Tool> {
+        tools.retain(|name, _| self.allows(name));
+        tools
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn max_turns_is_bounded() {
+        let args: SpawnAgentArgs = serde_json::from_str(r#"{"prompt": "find the bug"}"#).unwrap();
+        assert_eq!(args.max_turns(), DEFAULT_MAX_TURNS);
+
+        let args: SpawnAgentArgs =
+            serde_json::from_str(r#"{"prompt": "find the bug", "max_turns": 500}"#).unwrap();
+        assert_eq!(args.max_turns(), MAX_TURNS_LIMIT);
+    }
+
+    #[test]
+    fn restricts_tools_to_the_requested_ones() {
+        let args: SpawnAgentArgs =
+            serde_json::from_str(r#"{"prompt": "read the docs", "tools": ["shell"]}"#).unwrap();
+        assert!(args.allows("shell"));
+        assert!(args.allows("local_shell"));
+        assert!(!args.allows("edit_file"));
+
+        let disabled = args.disabled_tools(&["attach_image".to_string()]);
+        assert!(disabled.contains(&"edit_file".to_string()));
+        assert!(disabled.contains(&SPAWN_AGENT_TOOL_NAME.to_string()));
+        assert!(!disabled.contains(&"shell".to_string()));
+        assert_eq!(
+            disabled
+                .iter()
+                .filter(|name| *name == "attach_image")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn never_allows_nested_sub_agents() {
+        let args: SpawnAgentArgs =
+            serde_json::from_str(r#"{"prompt": "x", "tools": ["spawn_agent"]}"#).unwrap();
+        assert!(!args.allows(SPAWN_AGENT_TOOL_NAME));
+        let args: SpawnAgentArgs = serde_json::from_str(r#"{"prompt": "x"}"#).unwrap();
+        assert!(!args.allows(SPAWN_AGENT_TOOL_NAME));
+        assert!(!args.allows(UPDATE_PLAN_TOOL_NAME));
+        assert!(args.allows("edit_file"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Observability for fleets of Codex agents: spans for turns, model requests
+//! and tool calls, and counters for tokens and approval decisions.
+//!
+//! The spans are regular `tracing` spans, so they also show up in the logs.
+//! With the `otel` cargo feature and an `[otel]` table in config.toml,
+//! [`otel_layer`] additionally exports them, together with the counters, to
+//! an OTLP/HTTP collector. Without the feature the counters are no-ops.
+
+use tracing::Span;
+use tracing::field::Empty;
+use tracing::info_span;
+use tracing_subscriber::Layer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config_types::OtelConfig;
+use crate::models::ResponseInputItem;
+use crate::protocol::ReviewDecision;
+use crate::protocol::TokenUsage;
+
+/// One turn of a task: a model request and the tool calls it makes.
+pub(crate) fn turn_span(sub_id: &str) -> Span {
+    info_span!("turn", sub_id, otel.status_code = Empty)
+}
+
+/// A streamed model request, until its last event has arrived.
+pub(crate) fn model_request_span(model: &str, provider: &str) -> Span {
+    info_span!("model_request", model, provider, otel.status_code = Empty)
+}
+
+pub(crate) fn tool_call_span(tool: &str, call_id: &str) -> Span {
+    info_span!(
+        "tool_call",
+        tool,
+        call_id,
+        success = Empty,
+        otel.status_code = Empty
+    )
+}
+
+/// Mark `span` as failed for the trace backend.
+pub(crate) fn record_failure(span: &Span) {
+    span.record("otel.status_code", "ERROR");
+}
+
+/// Record on a [`tool_call_span`] whether the call succeeded.
+pub(crate) fn record_tool_call_output(span: &Span, output: &ResponseInputItem) {
+    let success = match output {
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.success != Some(false),
+        ResponseInputItem::McpToolCallOutput { result, .. } => result
+            .as_ref()
+            .is_ok_and(|result| result.is_error != Some(true)),
+        ResponseInputItem::Message { .. } => true,
+    };
+    span.record("success", success);
+    if !success {
+        record_failure(span);
+    }
+}
+
+/// Count the tokens of a model request by `model`.
+pub(crate) fn record_token_usage(model: &str, usage: &TokenUsage) {
+    #[cfg(feature = "otel")]
+    otel::record_token_usage(model, usage);
+    #[cfg(not(feature = "otel"))]
+    let _ = (model, usage);
+}
+
+/// Count the user's answer to an approval request of `kind` (`exec` or
+/// `patch`).
+pub(crate) fn record_approval(kind: &'static str, decision: ReviewDecision) {
+    let decision = match decision {
+        ReviewDecision::Approved => "approved",
+        ReviewDecision::ApprovedForSession => "approved_for_session",
+        ReviewDecision::ApprovedPrefixForSession => "approved_prefix_for_session",
+        ReviewDecision::Denied => "denied",
+        ReviewDecision::Abort => "abort",
+    };
+    #[cfg(feature = "otel")]
+    otel::record_approval(kind, decision);
+    #[cfg(not(feature = "otel"))]
+    let _ = (kind, decision);
+}
+
+/// Flushes and shuts down the exporters when dropped, so keep it alive until
+/// the process exits.
+pub struct OtelGuard {
+    #[cfg(feature = "otel")]
+    tracer_provider: opentelemetry_sdk::trace::SdkTracerProvider,
+    #[cfg(feature = "otel")]
+    meter_provider: opentelemetry_sdk::metrics::SdkMeterProvider,
+}
+
+#[cfg(feature = "otel")]
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        let _ = self.tracer_provider.shutdown();
+        let _ = self.meter_provider.shutdown();
+    }
+}
+
+/// A layer exporting the spans of `codex_core` to the collector of `config`.
+/// Installs the metric exporter as well.
+#[cfg(feature = "otel")]
+pub fn otel_layer<S>(
+    config: &OtelConfig,
+) -> Result<(Box<dyn Layer<S> + Send + Sync>, OtelGuard), String>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    otel::layer(config)
+}
+
+/// Without the `otel` feature there is nothing to export to.
+#[cfg(not(feature = "otel"))]
+pub fn otel_layer<S>(
+    _config: &OtelConfig,
+) -> Result<(Box<dyn Layer<S> + Send + Sync>, OtelGuard), String>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    Err("this build does not include the `otel` feature; [otel] is ignored".to_string())
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use std::sync::LazyLock;
+
+    use opentelemetry::KeyValue;
+    use opentelemetry::global;
+    use opentelemetry::metrics::Counter;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::MetricExporter;
+    use opentelemetry_otlp::SpanExporter;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_otlp::WithHttpConfig;
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing::Level;
+    use tracing_subscriber::Layer;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::registry::LookupSpan;
+
+    use super::OtelGuard;
+    use crate::config_types::OtelConfig;
+    use crate::protocol::TokenUsage;
+
+    struct Instruments {
+        tokens: Counter<u64>,
+        approvals: Counter<u64>,
+    }
+
+    /// Created on first use, once [`layer`] has installed the meter provider.
+    static INSTRUMENTS: LazyLock<Instruments> = LazyLock::new(|| {
+        let meter = global::meter("codex");
+        Instruments {
+            tokens: meter
+                .u64_counter("codex.tokens")
+                .with_description("Tokens used by model requests")
+                .with_unit("{token}")
+                .build(),
+            approvals: meter
+                .u64_counter("codex.approvals")
+                .with_description("Answers to approval requests")
+                .build(),
+        }
+    });
+
+    pub(super) fn record_token_usage(model: &str, usage: &TokenUsage) {
+        let counts = [
+            ("input", Some(usage.input_tokens)),
+            ("cached_input", usage.cached_input_tokens),
+            ("output", Some(usage.output_tokens)),
+            ("reasoning_output", usage.reasoning_output_tokens),
+        ];
+        for (kind, count) in counts {
+            if let Some(count) = count {
+                INSTRUMENTS.tokens.add(
+                    count,
+                    &[
+                        KeyValue::new("model", model.to_string()),
+                        KeyValue::new("type", kind),
+                    ],
+                );
+            }
+        }
+    }
+
+    pub(super) fn record_approval(kind: &'static str, decision: &'static str) {
+        INSTRUMENTS.approvals.add(
+            1,
+            &[
+                KeyValue::new("kind", kind),
+                KeyValue::new("decision", decision),
+            ],
+        );
+    }
+
+    pub(super) fn layer<S>(
+        config: &OtelConfig,
+    ) -> Result<(Box<dyn Layer<S> + Send + Sync>, OtelGuard), String>
+    where
+        S: tracing::Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+    {
+        let endpoint = config.endpoint.trim_end_matches('/');
+        let resource = Resource::builder()
+            .with_service_name(config.service_name.clone())
+            .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+            .build();
+
+        let span_exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{endpoint}/v1/traces"))
+            .with_headers(config.headers.clone())
+            .build()
+            .map_err(|e| format!("failed to create the span exporter: {e}"))?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_resource(resource.clone())
+            .build();
+
+        let metric_exporter = MetricExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{endpoint}/v1/metrics"))
+            .with_headers(config.headers.clone())
+            .build()
+            .map_err(|e| format!("failed to create the metric exporter: {e}"))?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metric_exporter)
+            .with_resource(resource)
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+
+        // Only Codex's own spans: the exporter's HTTP client must not trace
+        // itself.
+        let layer = tracing_opentelemetry::layer()
+            .with_tracer(tracer_provider.tracer("codex"))
+            .with_filter(Targets::new().with_target("codex_core", Level::INFO));
+        Ok((
+            Box::new(layer),
+            OtelGuard {
+                tracer_provider,
+                meter_provider,
+            },
+        ))
+    }
+}
@@ -0,0 +1,108 @@
+//! Per-session token budget enforcement (`max_session_tokens`).
+
+use crate::protocol::TokenUsage;
+
+/// Fraction of the budget at which a warning is emitted.
+const WARN_THRESHOLD: f64 = 0.8;
+
+/// Outcome of recording usage against the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BudgetStatus {
+    /// Below the warning threshold (or no budget configured).
+    Ok,
+    /// Crossed the warning threshold with this update. Reported only once.
+    Warn,
+    /// The budget is used up; further turns must be refused.
+    Exhausted,
+}
+
+/// Tracks cumulative token usage for a session against an optional limit.
+#[derive(Debug, Default)]
+pub(crate) struct TokenBudget {
+    max_tokens: Option<u64>,
+    total: TokenUsage,
+    warned: bool,
+}
+
+impl TokenBudget {
+    pub(crate) fn new(max_tokens: Option<u64>, initial: TokenUsage) -> Self {
+        let mut budget = Self {
+            max_tokens,
+            total: initial,
+            warned: false,
+        };
+        // A resumed session that is already past the threshold should not
+        // warn again on its first turn.
+        budget.warned = budget.fraction_used() >= WARN_THRESHOLD;
+        budget
+    }
+
+    pub(crate) fn total(&self) -> TokenUsage {
+        self.total
+    }
+
+    pub(crate) fn max_tokens(&self) -> Option<u64> {
+        self.max_tokens
+    }
+
+    pub(crate) fn record(&mut self, usage: &TokenUsage) -> BudgetStatus {
+        self.total.accumulate(usage);
+        if self.is_exhausted() {
+            return BudgetStatus::Exhausted;
+        }
+        if !self.warned && self.fraction_used() >= WARN_THRESHOLD {
+            self.warned = true;
+            return BudgetStatus::Warn;
+        }
+        BudgetStatus::Ok
+    }
+
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.max_tokens
+            .is_some_and(|max| self.total.total_tokens >= max)
+    }
+
+    fn fraction_used(&self) -> f64 {
+        match self.max_tokens {
+            Some(max) if max > 0 => self.total.total_tokens as f64 / max as f64,
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(total_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            total_tokens,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn warns_once_then_exhausts() {
+        let mut budget = TokenBudget::new(Some(100), TokenUsage::default());
+        assert_eq!(budget.record(&usage(50)), BudgetStatus::Ok);
+        assert_eq!(budget.record(&usage(30)), BudgetStatus::Warn);
+        assert_eq!(budget.record(&usage(5)), BudgetStatus::Ok);
+        assert_eq!(budget.record(&usage(15)), BudgetStatus::Exhausted);
+        assert!(budget.is_exhausted());
+        assert_eq!(budget.total().total_tokens, 100);
+    }
+
+    #[test]
+    fn unlimited_budget_never_warns() {
+        let mut budget = TokenBudget::new(None, TokenUsage::default());
+        assert_eq!(budget.record(&usage(u64::MAX / 2)), BudgetStatus::Ok);
+        assert!(!budget.is_exhausted());
+    }
+
+    #[test]
+    fn resumed_session_past_threshold_does_not_rewarn() {
+        let mut budget = TokenBudget::new(Some(100), usage(90));
+        assert_eq!(budget.record(&usage(1)), BudgetStatus::Ok);
+    }
+}
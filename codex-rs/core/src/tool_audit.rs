@@ -0,0 +1,176 @@
+//! Append-only audit log of the tool calls the agent makes, for teams that
+//! need to review exactly what was executed.
+//!
+//! Calls are appended to `~/.codex/logs/tool_audit.<YYYY-MM-DD>.jsonl`, the
+//! file of the day (UTC) the call finished, one JSON object per line:
+//!
+//! ````text
+//! {"ts":"2025-06-01T12:00:00Z","session_id":"<uuid>","call_id":"call_1","tool":"shell",
+//!  "arguments":{"command":["ls"]},"duration_ms":12,"success":true,"output_bytes":42,
+//!  "output_sha256":"<hex>","output_preview":"<first 200 chars>"}
+//! ````
+//!
+//! Naming the files by day rather than renaming a single file keeps
+//! concurrent sessions from racing each other at midnight.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use time::Date;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use uuid::Uuid;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Characters of the output kept in `output_preview`; the rest is only
+/// represented by its digest.
+const OUTPUT_PREVIEW_CHARS: usize = 200;
+
+/// A finished tool call, as handed to [`ToolAuditLog::record`].
+pub(crate) struct AuditedToolCall<'a> {
+    pub call_id: &'a str,
+    pub tool: &'a str,
+    /// Arguments as sent by the model, usually a JSON object.
+    pub arguments: &'a str,
+    pub duration: Duration,
+    pub success: bool,
+    pub output: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolAuditEntry {
+    ts: String,
+    session_id: String,
+    call_id: String,
+    tool: String,
+    arguments: serde_json::Value,
+    duration_ms: u64,
+    success: bool,
+    output_bytes: usize,
+    output_sha256: String,
+    output_preview: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ToolAuditLog {
+    dir: PathBuf,
+    session_id: Uuid,
+}
+
+impl ToolAuditLog {
+    pub(crate) fn new(codex_home: &Path, session_id: Uuid) -> Self {
+        Self {
+            dir: codex_home.join("logs"),
+            session_id,
+        }
+    }
+
+    pub(crate) async fn record(&self, call: AuditedToolCall<'_>) -> std::io::Result<()> {
+        let now = OffsetDateTime::now_utc();
+        let entry = ToolAuditEntry {
+            ts: now.format(&Rfc3339).map_err(std::io::Error::other)?,
+            session_id: self.session_id.to_string(),
+            call_id: call.call_id.to_string(),
+            tool: call.tool.to_string(),
+            arguments: serde_json::from_str(call.arguments)
+                .unwrap_or_else(|_| serde_json::Value::String(call.arguments.to_string())),
+            duration_ms: u64::try_from(call.duration.as_millis()).unwrap_or(u64::MAX),
+            success: call.success,
+            output_bytes: call.output.len(),
+            output_sha256: format!("{:x}", Sha256::digest(call.output.as_bytes())),
+            output_preview: call.output.chars().take(OUTPUT_PREVIEW_CHARS).collect(),
+        };
+        let mut line = serde_json::to_string(&entry).map_err(std::io::Error::other)?;
+        line.push('\n');
+
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || append_line(&dir, &line, now.date())).await?
+    }
+}
+
+/// The audit file of `day` in `dir`.
+fn audit_file(dir: &Path, day: Date) -> PathBuf {
+    dir.join(format!("tool_audit.{day}.jsonl"))
+}
+
+/// Append `line` to the audit file of `today` in `dir` with a single write.
+fn append_line(dir: &Path, line: &str, today: Date) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let path = audit_file(dir, today);
+
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    file.write_all(line.as_bytes())?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn records_calls_with_digest() {
+        let home = tempdir().unwrap();
+        let log = ToolAuditLog::new(home.path(), Uuid::nil());
+        let output = "x".repeat(500);
+        log.record(AuditedToolCall {
+            call_id: "call_1",
+            tool: "shell",
+            arguments: r#"{"command":["ls"]}"#,
+            duration: Duration::from_millis(12),
+            success: true,
+            output: &output,
+        })
+        .await
+        .unwrap();
+
+        let today = OffsetDateTime::now_utc().date();
+        let contents =
+            std::fs::read_to_string(audit_file(&home.path().join("logs"), today)).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(contents.trim_end()).unwrap();
+        assert_eq!(entry["tool"], "shell");
+        assert_eq!(entry["arguments"]["command"][0], "ls");
+        assert_eq!(entry["duration_ms"], 12);
+        assert_eq!(entry["output_bytes"], 500);
+        assert_eq!(
+            entry["output_preview"].as_str().unwrap().len(),
+            OUTPUT_PREVIEW_CHARS
+        );
+        assert_eq!(entry["output_sha256"].as_str().unwrap().len(), 64);
+    }
+
+    #[test]
+    fn calls_go_to_the_file_of_their_day() {
+        let dir = tempdir().unwrap();
+        let today = OffsetDateTime::now_utc().date();
+        let yesterday = today.previous_day().unwrap();
+        append_line(dir.path(), "old\n", yesterday).unwrap();
+        append_line(dir.path(), "new\n", today).unwrap();
+        append_line(dir.path(), "newer\n", today).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(audit_file(dir.path(), yesterday)).unwrap(),
+            "old\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(audit_file(dir.path(), today)).unwrap(),
+            "new\nnewer\n"
+        );
+    }
+}
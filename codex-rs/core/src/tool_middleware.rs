@@ -0,0 +1,359 @@
+//! Hooks around every tool call the model makes, so embedders can add
+//! logging, metrics, argument rewriting or redaction without touching the
+//! handlers of the individual tools. Middlewares are passed to
+//! [`crate::Codex::spawn_with_middleware`] and run in order; the built-in
+//! ones are enabled by the config:
+//!
+//! * `mask_env_secrets`: [`EnvSecretMaskingMiddleware`] runs first, so the
+//!   others never see the secrets it masks.
+//! * `tool_audit_log`: the audit log runs last and records what the model
+//!   was finally given.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use mcp_types::CallToolResultContent;
+use tracing::warn;
+
+use crate::models::FunctionCallOutputPayload;
+use crate::models::ResponseInputItem;
+use crate::tool_audit::AuditedToolCall;
+use crate::tool_audit::ToolAuditLog;
+
+/// Environment variables whose values are masked by
+/// [`EnvSecretMaskingMiddleware`] contain one of these in their name.
+const SECRET_ENV_VAR_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD"];
+
+/// Shorter values are too likely to occur in unrelated output to be masked.
+const MIN_SECRET_LEN: usize = 8;
+
+/// A tool call as seen by the middlewares.
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub call_id: String,
+    /// The name the model called the tool by; `local_shell` for the shell of
+    /// the codex models.
+    pub tool: String,
+    /// Arguments as sent by the model, usually a JSON object.
+    pub arguments: String,
+    pub started: Instant,
+}
+
+/// Hooks around tool calls. All hooks default to doing nothing.
+pub trait ToolMiddleware: Send + Sync {
+    /// Runs before the call. May rewrite `call.arguments` (not applied to
+    /// `local_shell` calls), or refuse the call with a reason that is
+    /// returned to the model instead of running the tool.
+    fn pre_call(&self, _call: &mut ToolCall) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Runs after a successful call, and may rewrite its output.
+    fn post_call(&self, _call: &ToolCall, _output: &mut ResponseInputItem) {}
+
+    /// Runs after a call that failed or was refused, and may rewrite its
+    /// output.
+    fn on_error(&self, _call: &ToolCall, _output: &mut ResponseInputItem) {}
+}
+
+/// The middlewares of a session, in the order they run.
+#[derive(Clone, Default)]
+pub(crate) struct ToolMiddlewareStack {
+    middlewares: Vec<Arc<dyn ToolMiddleware>>,
+}
+
+impl ToolMiddlewareStack {
+    pub(crate) fn new(middlewares: Vec<Arc<dyn ToolMiddleware>>) -> Self {
+        Self { middlewares }
+    }
+
+    /// Run the `pre_call` hooks until one refuses the call. A refusal is
+    /// passed through the `on_error` hooks and returned as the output of the
+    /// call.
+    pub(crate) fn pre_call(&self, call: &mut ToolCall) -> Result<(), ResponseInputItem> {
+        for middleware in &self.middlewares {
+            if let Err(reason) = middleware.pre_call(call) {
+                let mut output = ResponseInputItem::FunctionCallOutput {
+                    call_id: call.call_id.clone(),
+                    output: FunctionCallOutputPayload {
+                        content: format!("tool call refused: {reason}"),
+                        success: Some(false),
+                    },
+                };
+                self.post_call(call, &mut output);
+                return Err(output);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the `post_call` or `on_error` hooks, depending on `output`.
+    pub(crate) fn post_call(&self, call: &ToolCall, output: &mut ResponseInputItem) {
+        let succeeded = call_succeeded(output);
+        for middleware in &self.middlewares {
+            if succeeded {
+                middleware.post_call(call, output);
+            } else {
+                middleware.on_error(call, output);
+            }
+        }
+    }
+}
+
+/// Whether the output reports a successful call.
+pub(crate) fn call_succeeded(output: &ResponseInputItem) -> bool {
+    match output {
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.success != Some(false),
+        ResponseInputItem::McpToolCallOutput { result, .. } => result
+            .as_ref()
+            .is_ok_and(|result| result.is_error != Some(true)),
+        ResponseInputItem::Message { .. } => true,
+    }
+}
+
+/// Rewrite the text of a tool call output, including the text contents of
+/// MCP results.
+pub(crate) fn map_output_text(output: &mut ResponseInputItem, f: impl Fn(&str) -> String) {
+    match output {
+        ResponseInputItem::FunctionCallOutput { output, .. } => {
+            output.content = f(&output.content);
+        }
+        ResponseInputItem::McpToolCallOutput { result, .. } => match result {
+            Ok(result) => {
+                for content in &mut result.content {
+                    if let CallToolResultContent::TextContent(text) = content {
+                        text.text = f(&text.text);
+                    }
+                }
+                if let Some(structured) = &mut result.structured_content {
+                    map_json_strings(structured, &f);
+                }
+            }
+            Err(e) => *e = f(e),
+        },
+        ResponseInputItem::Message { .. } => {}
+    }
+}
+
+fn map_json_strings(value: &mut serde_json::Value, f: &impl Fn(&str) -> String) {
+    match value {
+        serde_json::Value::String(text) => *text = f(text),
+        serde_json::Value::Array(items) => {
+            for item in items {
+                map_json_strings(item, f);
+            }
+        }
+        serde_json::Value::Object(object) => {
+            for item in object.values_mut() {
+                map_json_strings(item, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Appends each call to the tool audit log (`tool_audit_log`).
+pub(crate) struct AuditLogMiddleware {
+    log: ToolAuditLog,
+}
+
+impl AuditLogMiddleware {
+    pub(crate) fn new(log: ToolAuditLog) -> Self {
+        Self { log }
+    }
+
+    /// Failing to write the log does not fail the call.
+    fn record(&self, call: &ToolCall, output: &ResponseInputItem) {
+        let (success, output) = match output {
+            ResponseInputItem::FunctionCallOutput {
+                output: payload, ..
+            } => (call_succeeded(output), payload.content.clone()),
+            ResponseInputItem::McpToolCallOutput { result, .. } => (
+                call_succeeded(output),
+                match result {
+                    Ok(result) => serde_json::to_string(result).unwrap_or_default(),
+                    Err(e) => e.clone(),
+                },
+            ),
+            ResponseInputItem::Message { .. } => return,
+        };
+        let log = self.log.clone();
+        let call = call.clone();
+        let duration = call.started.elapsed();
+        tokio::spawn(async move {
+            let audited = AuditedToolCall {
+                call_id: &call.call_id,
+                tool: &call.tool,
+                arguments: &call.arguments,
+                duration,
+                success,
+                output: &output,
+            };
+            if let Err(e) = log.record(audited).await {
+                warn!("failed to write tool audit log: {e}");
+            }
+        });
+    }
+}
+
+impl ToolMiddleware for AuditLogMiddleware {
+    fn post_call(&self, call: &ToolCall, output: &mut ResponseInputItem) {
+        self.record(call, output);
+    }
+
+    fn on_error(&self, call: &ToolCall, output: &mut ResponseInputItem) {
+        self.record(call, output);
+    }
+}
+
+/// Masks the values of secret-looking environment variables (API keys,
+/// tokens, passwords) in tool output before the model sees it
+/// (`mask_env_secrets`).
+pub(crate) struct EnvSecretMaskingMiddleware {
+    /// (name, value), longest values first so that a secret containing
+    /// another is masked as a whole.
+    secrets: Vec<(String, String)>,
+}
+
+impl EnvSecretMaskingMiddleware {
+    pub(crate) fn from_env() -> Self {
+        Self::new(std::env::vars())
+    }
+
+    fn new(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut secrets: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, value)| {
+                let name = name.to_ascii_uppercase();
+                value.len() >= MIN_SECRET_LEN
+                    && SECRET_ENV_VAR_MARKERS
+                        .iter()
+                        .any(|marker| name.contains(marker))
+            })
+            .collect();
+        secrets.sort_by(|(_, a), (_, b)| b.len().cmp(&a.len()));
+        Self { secrets }
+    }
+
+    fn mask(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (name, value) in &self.secrets {
+            if text.contains(value.as_str()) {
+                text = text.replace(value.as_str(), &format!("[REDACTED:{name}]"));
+            }
+        }
+        text
+    }
+}
+
+impl ToolMiddleware for EnvSecretMaskingMiddleware {
+    fn post_call(&self, _call: &ToolCall, output: &mut ResponseInputItem) {
+        map_output_text(output, |text| self.mask(text));
+    }
+
+    fn on_error(&self, _call: &ToolCall, output: &mut ResponseInputItem) {
+        map_output_text(output, |text| self.mask(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::sync::Mutex;
+
+    fn output(content: &str) -> ResponseInputItem {
+        ResponseInputItem::FunctionCallOutput {
+            call_id: "call_1".to_string(),
+            output: FunctionCallOutputPayload {
+                content: content.to_string(),
+                success: Some(true),
+            },
+        }
+    }
+
+    fn content(output: &ResponseInputItem) -> &str {
+        match output {
+            ResponseInputItem::FunctionCallOutput { output, .. } => &output.content,
+            _ => "",
+        }
+    }
+
+    /// Refuses `rm`, and records the hooks it sees.
+    #[derive(Default)]
+    struct Recorder {
+        hooks: Mutex<Vec<String>>,
+    }
+
+    impl ToolMiddleware for Recorder {
+        fn pre_call(&self, call: &mut ToolCall) -> Result<(), String> {
+            self.hooks.lock().unwrap().push("pre".to_string());
+            if call.arguments.contains("rm") {
+                return Err("rm is not allowed".to_string());
+            }
+            call.arguments = call.arguments.replace("ls", "ls -la");
+            Ok(())
+        }
+
+        fn post_call(&self, _call: &ToolCall, _output: &mut ResponseInputItem) {
+            self.hooks.lock().unwrap().push("post".to_string());
+        }
+
+        fn on_error(&self, _call: &ToolCall, output: &mut ResponseInputItem) {
+            self.hooks.lock().unwrap().push("error".to_string());
+            map_output_text(output, |text| format!("{text} (see policy)"));
+        }
+    }
+
+    #[test]
+    fn runs_the_hooks_around_calls() {
+        let recorder = Arc::new(Recorder::default());
+        let stack = ToolMiddlewareStack::new(vec![recorder.clone()]);
+        let call = |arguments: &str| ToolCall {
+            call_id: "call_1".to_string(),
+            tool: "shell".to_string(),
+            arguments: arguments.to_string(),
+            started: Instant::now(),
+        };
+
+        let mut ls = call("ls");
+        assert!(stack.pre_call(&mut ls).is_ok());
+        assert_eq!(ls.arguments, "ls -la");
+        stack.post_call(&ls, &mut output("a.txt"));
+
+        let refused = stack.pre_call(&mut call("rm -rf /")).unwrap_err();
+        assert!(!call_succeeded(&refused));
+        assert_eq!(
+            content(&refused),
+            "tool call refused: rm is not allowed (see policy)"
+        );
+
+        let hooks = recorder.hooks.lock().unwrap();
+        assert_eq!(*hooks, vec!["pre", "post", "pre", "error"]);
+    }
+
+    #[test]
+    fn masks_secret_env_values() {
+        let masking = EnvSecretMaskingMiddleware::new([
+            (
+                "OPENAI_API_KEY".to_string(),
+                "sk-abcdefgh12345678".to_string(),
+            ),
+            ("GITHUB_TOKEN".to_string(), "short".to_string()),
+            ("HOME".to_string(), "/home/someone".to_string()),
+        ]);
+        let call = ToolCall {
+            call_id: "call_1".to_string(),
+            tool: "shell".to_string(),
+            arguments: "{}".to_string(),
+            started: Instant::now(),
+        };
+        let mut out = output("key=sk-abcdefgh12345678 token=short home=/home/someone");
+        masking.post_call(&call, &mut out);
+        assert_eq!(
+            content(&out),
+            "key=[REDACTED:OPENAI_API_KEY] token=short home=/home/someone"
+        );
+    }
+}
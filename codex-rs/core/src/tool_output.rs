@@ -0,0 +1,277 @@
+//! Structured tool output: tools that declare an `outputSchema` have their
+//! results checked against it before the model sees them, and servers
+//! configured with `strict_tools` have their tools offered in strict mode, so
+//! the model's arguments match the input schemas exactly.
+
+use mcp_types::CallToolResult;
+use mcp_types::CallToolResultContent;
+use serde_json::Value;
+use thiserror::Error;
+
+/// Why the result of a tool call was not passed on to the model.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub(crate) enum FunctionCallError {
+    /// The tool declares an output schema but returned no JSON.
+    #[error("tool `{tool}` returned malformed JSON: {message}")]
+    MalformedOutput { tool: String, message: String },
+
+    /// The JSON the tool returned does not match its output schema.
+    #[error("output of tool `{tool}` does not match its output schema at {path}: {message}")]
+    SchemaMismatch {
+        tool: String,
+        path: String,
+        message: String,
+    },
+}
+
+/// Check the result of a call to `tool` against its `output_schema`. The
+/// structured content is taken from `structuredContent` or, for servers that
+/// only return text, parsed from the single text content. Error results are
+/// passed through unchecked.
+pub(crate) fn validate_tool_output(
+    tool: &str,
+    output_schema: &Value,
+    result: &CallToolResult,
+) -> Result<(), FunctionCallError> {
+    if result.is_error == Some(true) {
+        return Ok(());
+    }
+    let parsed;
+    let output = match (&result.structured_content, &result.content[..]) {
+        (Some(output), _) => output,
+        (None, [CallToolResultContent::TextContent(text)]) => {
+            parsed = serde_json::from_str::<Value>(&text.text).map_err(|e| {
+                FunctionCallError::MalformedOutput {
+                    tool: tool.to_string(),
+                    message: e.to_string(),
+                }
+            })?;
+            &parsed
+        }
+        (None, _) => {
+            return Err(FunctionCallError::MalformedOutput {
+                tool: tool.to_string(),
+                message: "expected `structuredContent` or a single JSON text content".to_string(),
+            });
+        }
+    };
+    check(output_schema, output, "$").map_err(|(path, message)| FunctionCallError::SchemaMismatch {
+        tool: tool.to_string(),
+        path,
+        message,
+    })
+}
+
+/// Validate `value` against the subset of JSON Schema tools use in practice:
+/// `type`, `enum`, `properties`, `required`, `additionalProperties: false` and
+/// `items`. Other keywords are ignored. Errors carry the JSON path of the
+/// offending value.
+fn check(schema: &Value, value: &Value, path: &str) -> Result<(), (String, String)> {
+    let mismatch = |message: String| Err((path.to_string(), message));
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return mismatch(format!(
+                "expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return mismatch(format!("{value} is not one of the allowed values"));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        return mismatch(format!("missing required property `{name}`"));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+            for (name, property) in object {
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(property_schema) => {
+                        check(property_schema, property, &format!("{path}.{name}"))?
+                    }
+                    None if closed => {
+                        return mismatch(format!("unexpected property `{name}`"));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (idx, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{path}[{idx}]"))?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, r#type: &str) -> bool {
+    match r#type {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+/// The input schema of a tool made acceptable for strict mode: every object
+/// closed with `additionalProperties: false`. `None` if the schema has
+/// optional properties, which strict mode does not allow.
+pub(crate) fn strict_input_schema(schema: &Value) -> Option<Value> {
+    let mut schema = schema.clone();
+    make_strict(&mut schema).then_some(schema)
+}
+
+fn make_strict(schema: &mut Value) -> bool {
+    let Some(object) = schema.as_object_mut() else {
+        return true;
+    };
+    if let Some(items) = object.get_mut("items") {
+        if !make_strict(items) {
+            return false;
+        }
+    }
+    let Some(Value::Object(properties)) = object.get_mut("properties") else {
+        return true;
+    };
+    for property in properties.values_mut() {
+        if !make_strict(property) {
+            return false;
+        }
+    }
+    let names: Vec<String> = properties.keys().cloned().collect();
+    let required: Vec<&str> = object
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    if !names.iter().all(|name| required.contains(&name.as_str())) {
+        return false;
+    }
+    object.insert("additionalProperties".to_string(), Value::Bool(false));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::TextContent;
+    use serde_json::json;
+
+    fn text_result(text: &str) -> CallToolResult {
+        CallToolResult {
+            content: vec![CallToolResultContent::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            })],
+            is_error: None,
+            structured_content: None,
+        }
+    }
+
+    #[test]
+    fn validates_structured_output() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "issues": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "number": { "type": "integer" } },
+                        "required": ["number"],
+                    },
+                },
+            },
+            "required": ["issues"],
+        });
+
+        let ok = text_result(r#"{"issues": [{"number": 12}]}"#);
+        assert_eq!(validate_tool_output("search", &schema, &ok), Ok(()));
+
+        let wrong_type = CallToolResult {
+            structured_content: Some(json!({"issues": [{"number": "12"}]})),
+            ..text_result("")
+        };
+        assert_eq!(
+            validate_tool_output("search", &schema, &wrong_type),
+            Err(FunctionCallError::SchemaMismatch {
+                tool: "search".to_string(),
+                path: "$.issues[0].number".to_string(),
+                message: "expected integer, got string".to_string(),
+            })
+        );
+
+        let malformed = text_result("12 issues");
+        assert!(matches!(
+            validate_tool_output("search", &schema, &malformed),
+            Err(FunctionCallError::MalformedOutput { .. })
+        ));
+
+        let error = CallToolResult {
+            is_error: Some(true),
+            ..text_result("rate limited")
+        };
+        assert_eq!(validate_tool_output("search", &schema, &error), Ok(()));
+    }
+
+    #[test]
+    fn strict_schemas_close_objects_with_all_properties_required() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "query": { "type": "string" } },
+            "required": ["query"],
+        });
+        assert_eq!(
+            strict_input_schema(&schema),
+            Some(json!({
+                "type": "object",
+                "properties": { "query": { "type": "string" } },
+                "required": ["query"],
+                "additionalProperties": false,
+            }))
+        );
+
+        let optional = json!({
+            "type": "object",
+            "properties": { "query": { "type": "string" }, "limit": { "type": "integer" } },
+            "required": ["query"],
+        });
+        assert_eq!(strict_input_schema(&optional), None);
+    }
+}
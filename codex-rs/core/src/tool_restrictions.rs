@@ -0,0 +1,182 @@
+//! Enforcement of the `[tools]` config table, which lets the user or a
+//! project hide built-in tools and limit MCP servers to some of their tools.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use toml::Value as TomlValue;
+
+use crate::config_types::ToolsConfig;
+
+impl ToolsConfig {
+    /// Add the restrictions of `other`: tools disabled by either stay
+    /// disabled, and a server limited by both only offers the tools both
+    /// allow.
+    pub fn restrict(&mut self, other: ToolsConfig) {
+        for name in other.disabled {
+            if !self.disabled.contains(&name) {
+                self.disabled.push(name);
+            }
+        }
+        for (server, allowed) in other.mcp_servers {
+            match self.mcp_servers.get_mut(&server) {
+                Some(current) => current.retain(|tool| allowed.contains(tool)),
+                None => {
+                    self.mcp_servers.insert(server, allowed);
+                }
+            }
+        }
+    }
+
+    pub fn is_builtin_tool_enabled(&self, name: &str) -> bool {
+        !self.disabled.iter().any(|disabled| disabled == name)
+    }
+
+    pub fn is_mcp_tool_allowed(&self, server: &str, tool: &str) -> bool {
+        self.mcp_servers
+            .get(server)
+            .is_none_or(|allowed| allowed.iter().any(|allowed| allowed == tool))
+    }
+
+    /// One human-readable line per restriction, for status displays.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.disabled.is_empty() {
+            lines.push(format!("disabled: {}", self.disabled.join(", ")));
+        }
+        let mut servers: Vec<_> = self.mcp_servers.iter().collect();
+        servers.sort();
+        for (server, allowed) in servers {
+            let allowed = if allowed.is_empty() {
+                "no tools".to_string()
+            } else {
+                allowed.join(", ")
+            };
+            lines.push(format!("{server}: only {allowed}"));
+        }
+        lines
+    }
+}
+
+/// The `[tools]` table of the nearest `.codex/config.toml` between `cwd` and
+/// the root of its Git repository. Only this table of a project config is
+/// read, so a checked-out repository can restrict the agent but not loosen
+/// the sandbox or approval settings. The user config in `codex_home` is
+/// skipped if the search runs into it.
+pub(crate) fn load_project_tools_config(
+    cwd: &Path,
+    codex_home: &Path,
+) -> std::io::Result<Option<ToolsConfig>> {
+    match find_project_config(cwd, codex_home) {
+        Some(path) => parse_tools_table(&path),
+        None => Ok(None),
+    }
+}
+
+/// The nearest `.codex/config.toml` between `cwd` and the root of its Git
+/// repository, other than the user config in `codex_home`.
+pub fn find_project_config(cwd: &Path, codex_home: &Path) -> Option<PathBuf> {
+    let user_config = codex_home.join("config.toml");
+    let mut dir = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    loop {
+        let candidate = dir.join(".codex").join("config.toml");
+        if candidate.is_file() && !same_file(&candidate, &user_config) {
+            return Some(candidate);
+        }
+        if dir.join(".git").exists() {
+            return None;
+        }
+        dir = dir.parent()?.to_path_buf();
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| PathBuf::from(path));
+    canonical(a) == canonical(b)
+}
+
+fn parse_tools_table(path: &Path) -> std::io::Result<Option<ToolsConfig>> {
+    let contents = std::fs::read_to_string(path)?;
+    let invalid = |e: toml::de::Error| {
+        tracing::error!("Failed to parse {}: {e}", path.display());
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    };
+    let mut value = toml::from_str::<TomlValue>(&contents).map_err(invalid)?;
+    let Some(tools) = value.as_table_mut().and_then(|table| table.remove("tools")) else {
+        return Ok(None);
+    };
+    tools.try_into().map(Some).map_err(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    #[test]
+    fn project_config_only_adds_restrictions() {
+        let mut tools = ToolsConfig {
+            disabled: vec!["edit_file".to_string()],
+            mcp_servers: HashMap::from([(
+                "github".to_string(),
+                vec!["get_issue".to_string(), "create_issue".to_string()],
+            )]),
+        };
+        tools.restrict(ToolsConfig {
+            disabled: vec!["exec_background".to_string()],
+            mcp_servers: HashMap::from([
+                ("github".to_string(), vec!["get_issue".to_string()]),
+                ("docs".to_string(), Vec::new()),
+            ]),
+        });
+
+        assert!(!tools.is_builtin_tool_enabled("edit_file"));
+        assert!(!tools.is_builtin_tool_enabled("exec_background"));
+        assert!(tools.is_builtin_tool_enabled("shell"));
+        assert!(tools.is_mcp_tool_allowed("github", "get_issue"));
+        assert!(!tools.is_mcp_tool_allowed("github", "create_issue"));
+        assert!(!tools.is_mcp_tool_allowed("docs", "search"));
+        assert!(tools.is_mcp_tool_allowed("other", "anything"));
+        assert_eq!(
+            tools.describe(),
+            vec![
+                "disabled: edit_file, exec_background".to_string(),
+                "docs: only no tools".to_string(),
+                "github: only get_issue".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_project_config_up_to_repo_root() {
+        let home = tempdir().unwrap();
+        let repo = tempdir().unwrap();
+        std::fs::create_dir(repo.path().join(".git")).unwrap();
+        std::fs::create_dir_all(repo.path().join(".codex")).unwrap();
+        std::fs::create_dir_all(repo.path().join("src/nested")).unwrap();
+        std::fs::write(
+            repo.path().join(".codex/config.toml"),
+            "model = \"ignored\"\n\n[tools]\ndisabled = [\"attach_image\"]\n\n[tools.mcp_servers]\ngithub = [\"get_issue\"]\n",
+        )
+        .unwrap();
+
+        let tools = load_project_tools_config(&repo.path().join("src/nested"), home.path())
+            .unwrap()
+            .unwrap();
+        assert_eq!(tools.disabled, vec!["attach_image".to_string()]);
+        assert_eq!(
+            tools.mcp_servers,
+            HashMap::from([("github".to_string(), vec!["get_issue".to_string()])])
+        );
+
+        let elsewhere = tempdir().unwrap();
+        std::fs::create_dir(elsewhere.path().join(".git")).unwrap();
+        assert_eq!(
+            load_project_tools_config(elsewhere.path(), home.path()).unwrap(),
+            None
+        );
+    }
+}
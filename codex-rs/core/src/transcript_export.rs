@@ -0,0 +1,752 @@
+//! Render a saved session as a self-contained Markdown or HTML document for
+//! sharing, used by `codex export` and the TUI's `/export` command.
+//!
+//! The transcript contains the user and agent messages, reasoning summaries,
+//! tool calls with their output, and patches as `diff` blocks. HTML exports
+//! inline their stylesheet and highlight code blocks without any scripts, so
+//! the file can be opened or attached anywhere.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use uuid::Uuid;
+
+use crate::models::ContentItem;
+use crate::models::LocalShellAction;
+use crate::models::ReasoningItemReasoningSummary;
+use crate::models::ResponseItem;
+use crate::models::ShellToolCallParams;
+use crate::session_store::SavedSession;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// [`ExportFormat::Html`] for `.html`/`.htm` paths, Markdown otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                ExportFormat::Html
+            }
+            _ => ExportFormat::Markdown,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// File name used when no export path is given.
+pub fn default_export_file_name(session_id: Uuid, format: ExportFormat) -> String {
+    format!("codex-session-{session_id}.{}", format.extension())
+}
+
+/// Render `session` as a standalone document in `format`.
+pub fn render_transcript(session_id: Uuid, session: &SavedSession, format: ExportFormat) -> String {
+    let title = format!("Codex session {session_id}");
+    let meta = session_meta(session);
+    let entries = transcript_entries(&session.items);
+    match format {
+        ExportFormat::Markdown => render_markdown(&title, &meta, &entries),
+        ExportFormat::Html => render_html(&title, &meta, &entries),
+    }
+}
+
+/// One rendered unit of the transcript.
+#[derive(Debug)]
+enum Entry {
+    User(String),
+    Agent(String),
+    Reasoning(String),
+    ToolCall {
+        name: String,
+        language: &'static str,
+        code: String,
+    },
+    ToolOutput {
+        exit_code: Option<i64>,
+        success: Option<bool>,
+        output: String,
+    },
+}
+
+fn session_meta(session: &SavedSession) -> Vec<(&'static str, String)> {
+    let mut meta = Vec::new();
+    if let Some(context) = &session.turn_context {
+        meta.push(("Model", context.model.clone()));
+        meta.push(("Working directory", context.cwd.display().to_string()));
+    }
+    let usage = &session.token_usage;
+    if usage.total_tokens > 0 {
+        meta.push((
+            "Tokens",
+            format!(
+                "{} total ({} input, {} output)",
+                usage.total_tokens, usage.input_tokens, usage.output_tokens
+            ),
+        ));
+    }
+    meta
+}
+
+fn transcript_entries(items: &[ResponseItem]) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for item in items {
+        match item {
+            ResponseItem::Message { role, content } => {
+                let text = content
+                    .iter()
+                    .map(|c| match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            text.as_str()
+                        }
+                        ContentItem::InputImage { .. } => "[image]",
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                match role.as_str() {
+                    "user" => entries.push(Entry::User(text)),
+                    "assistant" => entries.push(Entry::Agent(text)),
+                    _ => {}
+                }
+            }
+            ResponseItem::Reasoning { summary, .. } => {
+                let text = summary
+                    .iter()
+                    .map(|s| match s {
+                        ReasoningItemReasoningSummary::SummaryText { text } => text.as_str(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                if !text.trim().is_empty() {
+                    entries.push(Entry::Reasoning(text));
+                }
+            }
+            ResponseItem::LocalShellCall { action, .. } => match action {
+                LocalShellAction::Exec(exec) => entries.push(command_entry(&exec.command)),
+            },
+            ResponseItem::FunctionCall {
+                name, arguments, ..
+            } => {
+                let params = (name == "shell" || name == "container.exec")
+                    .then(|| serde_json::from_str::<ShellToolCallParams>(arguments).ok())
+                    .flatten();
+                entries.push(match params {
+                    Some(params) => command_entry(&params.command),
+                    None => Entry::ToolCall {
+                        name: name.clone(),
+                        language: "json",
+                        code: serde_json::from_str::<serde_json::Value>(arguments)
+                            .ok()
+                            .and_then(|value| serde_json::to_string_pretty(&value).ok())
+                            .unwrap_or_else(|| arguments.clone()),
+                    },
+                });
+            }
+            ResponseItem::FunctionCallOutput { output, .. } => {
+                // Shell output is a JSON object with the output and its exit
+                // code; other tools return plain text.
+                let parsed = serde_json::from_str::<serde_json::Value>(&output.content).ok();
+                let shell_output = parsed
+                    .as_ref()
+                    .and_then(|value| value.get("output"))
+                    .and_then(|value| value.as_str());
+                entries.push(Entry::ToolOutput {
+                    exit_code: parsed
+                        .as_ref()
+                        .and_then(|value| value.pointer("/metadata/exit_code"))
+                        .and_then(|value| value.as_i64()),
+                    success: output.success,
+                    output: shell_output.unwrap_or(&output.content).to_string(),
+                });
+            }
+            ResponseItem::Other => {}
+        }
+    }
+    entries
+}
+
+/// Patches become `diff` blocks, `bash -lc` scripts are shown unwrapped.
+fn command_entry(command: &[String]) -> Entry {
+    match command {
+        [program, patch] if program == "apply_patch" => Entry::ToolCall {
+            name: "apply_patch".to_string(),
+            language: "diff",
+            code: patch.clone(),
+        },
+        [bash, flag, script] if bash == "bash" && flag == "-lc" => Entry::ToolCall {
+            name: "shell".to_string(),
+            language: "bash",
+            code: script.clone(),
+        },
+        _ => Entry::ToolCall {
+            name: "shell".to_string(),
+            language: "bash",
+            code: command
+                .iter()
+                .map(|arg| {
+                    if !arg.is_empty()
+                        && arg
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c))
+                    {
+                        arg.clone()
+                    } else {
+                        format!("'{}'", arg.replace('\'', r"'\''"))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        },
+    }
+}
+
+fn output_label(exit_code: Option<i64>, success: Option<bool>) -> String {
+    match (exit_code, success) {
+        (Some(code), _) => format!("Output (exit code {code})"),
+        (None, Some(false)) => "Output (failed)".to_string(),
+        _ => "Output".to_string(),
+    }
+}
+
+/// A backtick fence longer than any run of backticks inside `code`.
+fn fence_for(code: &str) -> String {
+    let longest = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn render_markdown(title: &str, meta: &[(&str, String)], entries: &[Entry]) -> String {
+    let mut out = format!("# {title}\n\n");
+    for (key, value) in meta {
+        let _ = writeln!(out, "- **{key}:** {value}");
+    }
+    if !meta.is_empty() {
+        out.push('\n');
+    }
+    for entry in entries {
+        match entry {
+            Entry::User(text) => {
+                let _ = write!(out, "## User\n\n{}\n\n", text.trim_end());
+            }
+            Entry::Agent(text) => {
+                let _ = write!(out, "## Codex\n\n{}\n\n", text.trim_end());
+            }
+            Entry::Reasoning(text) => {
+                out.push_str("> **Reasoning**\n>\n");
+                for line in text.trim_end().lines() {
+                    let _ = writeln!(out, "> {line}");
+                }
+                out.push('\n');
+            }
+            Entry::ToolCall {
+                name,
+                language,
+                code,
+            } => {
+                let fence = fence_for(code);
+                let _ = write!(
+                    out,
+                    "### Tool call: `{name}`\n\n{fence}{language}\n{}\n{fence}\n\n",
+                    code.trim_end()
+                );
+            }
+            Entry::ToolOutput {
+                exit_code,
+                success,
+                output,
+            } => {
+                let fence = fence_for(output);
+                let _ = write!(
+                    out,
+                    "**{}:**\n\n{fence}text\n{}\n{fence}\n\n",
+                    output_label(*exit_code, *success),
+                    output.trim_end()
+                );
+            }
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "\
+body { margin: 0; background: #f6f8fa; color: #1f2328; font: 15px/1.5 -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; }
+main { max-width: 900px; margin: 0 auto; padding: 24px; }
+h1 { font-size: 22px; }
+.meta { color: #59636e; padding-left: 20px; }
+section { background: #fff; border: 1px solid #d1d9e0; border-radius: 6px; margin: 12px 0; padding: 8px 16px; }
+section.user { border-left: 4px solid #0969da; }
+section.agent { border-left: 4px solid #8250df; }
+section.reasoning { color: #59636e; font-style: italic; }
+h2, h3 { font-size: 14px; margin: 8px 0; color: #59636e; }
+.prose { white-space: pre-wrap; }
+pre { background: #f6f8fa; border-radius: 6px; padding: 12px; overflow-x: auto; font: 13px/1.45 ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }
+.kw { color: #cf222e; }
+.str { color: #0a3069; }
+.com { color: #6e7781; font-style: italic; }
+.num { color: #0550ae; }
+.add { color: #116329; background: #dafbe1; }
+.del { color: #82071e; background: #ffebe9; }
+.hunk { color: #8250df; }
+";
+
+fn render_html(title: &str, meta: &[(&str, String)], entries: &[Entry]) -> String {
+    let title = escape_html(title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n<main>\n\
+         <h1>{title}</h1>\n"
+    );
+    if !meta.is_empty() {
+        out.push_str("<ul class=\"meta\">\n");
+        for (key, value) in meta {
+            let _ = writeln!(
+                out,
+                "<li><strong>{key}:</strong> {}</li>",
+                escape_html(value)
+            );
+        }
+        out.push_str("</ul>\n");
+    }
+    for entry in entries {
+        match entry {
+            Entry::User(text) => {
+                let _ = writeln!(
+                    out,
+                    "<section class=\"user\"><h2>User</h2>{}</section>",
+                    html_message(text)
+                );
+            }
+            Entry::Agent(text) => {
+                let _ = writeln!(
+                    out,
+                    "<section class=\"agent\"><h2>Codex</h2>{}</section>",
+                    html_message(text)
+                );
+            }
+            Entry::Reasoning(text) => {
+                let _ = writeln!(
+                    out,
+                    "<section class=\"reasoning\"><h2>Reasoning</h2><div class=\"prose\">{}</div></section>",
+                    escape_html(text.trim())
+                );
+            }
+            Entry::ToolCall {
+                name,
+                language,
+                code,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "<section class=\"tool\"><h3>Tool call: {}</h3>{}</section>",
+                    escape_html(name),
+                    html_code_block(language, code)
+                );
+            }
+            Entry::ToolOutput {
+                exit_code,
+                success,
+                output,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "<section class=\"tool-output\"><h3>{}</h3>{}</section>",
+                    output_label(*exit_code, *success),
+                    html_code_block("text", output)
+                );
+            }
+        }
+    }
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+/// Message text with its fenced code blocks highlighted; the prose around
+/// them is kept as written.
+fn html_message(text: &str) -> String {
+    let mut out = String::new();
+    let mut prose: Vec<&str> = Vec::new();
+    // (fence, language, lines) of the code block being collected.
+    let mut code: Option<(String, String, Vec<&str>)> = None;
+    let flush_prose = |prose: &mut Vec<&str>, out: &mut String| {
+        let joined = prose.join("\n");
+        if !joined.trim().is_empty() {
+            let _ = write!(
+                out,
+                "<div class=\"prose\">{}</div>",
+                escape_html(joined.trim())
+            );
+        }
+        prose.clear();
+    };
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match code.as_mut() {
+            Some((fence, _, lines)) => {
+                let closes = fence.chars().next().is_some_and(|fence_char| {
+                    trimmed.starts_with(fence.as_str())
+                        && trimmed.trim_start_matches(fence_char).trim().is_empty()
+                });
+                if closes {
+                    if let Some((_, language, lines)) = code.take() {
+                        out.push_str(&html_code_block(&language, &lines.join("\n")));
+                    }
+                } else {
+                    lines.push(line);
+                }
+            }
+            None => {
+                let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+                let fence_len = fence_char
+                    .map(|fc| trimmed.chars().take_while(|c| *c == fc).count())
+                    .unwrap_or_default();
+                if fence_len >= 3 {
+                    flush_prose(&mut prose, &mut out);
+                    let language = trimmed[fence_len..]
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string();
+                    code = Some((trimmed[..fence_len].to_string(), language, Vec::new()));
+                } else {
+                    prose.push(line);
+                }
+            }
+        }
+    }
+    if let Some((_, language, lines)) = code {
+        out.push_str(&html_code_block(&language, &lines.join("\n")));
+    }
+    flush_prose(&mut prose, &mut out);
+    out
+}
+
+fn html_code_block(language: &str, code: &str) -> String {
+    let class = if language.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", escape_html(language))
+    };
+    format!(
+        "<pre><code{class}>{}</code></pre>",
+        highlight(language, code.trim_end())
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Keywords and comment syntax for the languages we highlight.
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comment: Option<&'static str>,
+    /// Whether `'` starts a string (it does not in Rust, where it also marks
+    /// lifetimes).
+    single_quote_strings: bool,
+}
+
+fn syntax_for(language: &str) -> Option<Syntax> {
+    let syntax = match language.to_ascii_lowercase().as_str() {
+        "rust" | "rs" => Syntax {
+            keywords: &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+                "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+                "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            line_comment: Some("//"),
+            single_quote_strings: false,
+        },
+        "python" | "py" => Syntax {
+            keywords: &[
+                "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+                "del", "elif", "else", "except", "False", "finally", "for", "from", "if", "import",
+                "in", "is", "lambda", "None", "not", "or", "pass", "raise", "return", "True",
+                "try", "while", "with", "yield",
+            ],
+            line_comment: Some("#"),
+            single_quote_strings: true,
+        },
+        "bash" | "sh" | "shell" | "zsh" | "console" => Syntax {
+            keywords: &[
+                "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function",
+                "if", "in", "local", "return", "then", "until", "while",
+            ],
+            line_comment: Some("#"),
+            single_quote_strings: true,
+        },
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => Syntax {
+            keywords: &[
+                "async",
+                "await",
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "default",
+                "else",
+                "export",
+                "extends",
+                "false",
+                "for",
+                "from",
+                "function",
+                "if",
+                "import",
+                "interface",
+                "let",
+                "new",
+                "null",
+                "return",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "type",
+                "undefined",
+                "var",
+                "while",
+            ],
+            line_comment: Some("//"),
+            single_quote_strings: true,
+        },
+        "go" => Syntax {
+            keywords: &[
+                "break",
+                "case",
+                "chan",
+                "const",
+                "continue",
+                "default",
+                "defer",
+                "else",
+                "false",
+                "for",
+                "func",
+                "go",
+                "if",
+                "import",
+                "interface",
+                "map",
+                "nil",
+                "package",
+                "range",
+                "return",
+                "select",
+                "struct",
+                "switch",
+                "true",
+                "type",
+                "var",
+            ],
+            line_comment: Some("//"),
+            single_quote_strings: false,
+        },
+        "json" => Syntax {
+            keywords: &["false", "null", "true"],
+            line_comment: None,
+            single_quote_strings: false,
+        },
+        "toml" | "yaml" | "yml" => Syntax {
+            keywords: &["false", "true"],
+            line_comment: Some("#"),
+            single_quote_strings: true,
+        },
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+/// Escaped `code` with `<span>`s around tokens for the stylesheet to color.
+/// This is a lightweight tokenizer, not a parser: it is meant to make
+/// exported code easier to read, not to be exact.
+fn highlight(language: &str, code: &str) -> String {
+    if matches!(language, "diff" | "patch") {
+        return code
+            .lines()
+            .map(|line| {
+                let class = if line.starts_with("@@") || line.starts_with("***") {
+                    Some("hunk")
+                } else if line.starts_with('+') {
+                    Some("add")
+                } else if line.starts_with('-') {
+                    Some("del")
+                } else {
+                    None
+                };
+                match class {
+                    Some(class) => format!("<span class=\"{class}\">{}</span>", escape_html(line)),
+                    None => escape_html(line),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    let Some(syntax) = syntax_for(language) else {
+        return escape_html(code);
+    };
+
+    let span =
+        |class: &str, token: &str| format!("<span class=\"{class}\">{}</span>", escape_html(token));
+    let mut out = String::with_capacity(code.len());
+    let mut rest = code;
+    while let Some(c) = rest.chars().next() {
+        let token_len = if syntax
+            .line_comment
+            .is_some_and(|marker| rest.starts_with(marker))
+        {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            out.push_str(&span("com", &rest[..len]));
+            len
+        } else if c == '"' || (c == '\'' && syntax.single_quote_strings) {
+            let mut escaped = false;
+            let len = rest[1..]
+                .char_indices()
+                .find(|&(_, ch)| {
+                    let end = (ch == c && !escaped) || ch == '\n';
+                    escaped = ch == '\\' && !escaped;
+                    end
+                })
+                .map(|(i, ch)| 1 + i + ch.len_utf8())
+                .unwrap_or(rest.len());
+            out.push_str(&span("str", &rest[..len]));
+            len
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'))
+                .unwrap_or(rest.len());
+            out.push_str(&span("num", &rest[..len]));
+            len
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            if syntax.keywords.contains(&word) {
+                out.push_str(&span("kw", word));
+            } else {
+                out.push_str(word);
+            }
+            len
+        } else {
+            out.push_str(&escape_html(&rest[..c.len_utf8()]));
+            c.len_utf8()
+        };
+        rest = &rest[token_len..];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::models::FunctionCallOutputPayload;
+    use crate::protocol::TokenUsage;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn session() -> SavedSession {
+        SavedSession {
+            items: vec![
+                message("user", "Fix <the> bug"),
+                ResponseItem::Reasoning {
+                    id: "r1".to_string(),
+                    summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                        text: "Looking at main.rs".to_string(),
+                    }],
+                },
+                ResponseItem::FunctionCall {
+                    name: "shell".to_string(),
+                    arguments: r#"{"command":["apply_patch","*** Begin Patch\n-old\n+new\n*** End Patch"]}"#
+                        .to_string(),
+                    call_id: "c1".to_string(),
+                },
+                ResponseItem::FunctionCallOutput {
+                    call_id: "c1".to_string(),
+                    output: FunctionCallOutputPayload {
+                        content: r#"{"output":"Done!","metadata":{"exit_code":0,"duration_seconds":0.1}}"#
+                            .to_string(),
+                        success: Some(true),
+                    },
+                },
+                message("assistant", "Fixed:\n\n```rust\nfn main() {} // ok\n```"),
+            ],
+            token_usage: TokenUsage::default(),
+            turn_context: None,
+            previous_response_id: None,
+        }
+    }
+
+    #[test]
+    fn renders_markdown_with_tool_calls_and_reasoning() {
+        let id = Uuid::nil();
+        let markdown = render_transcript(id, &session(), ExportFormat::Markdown);
+        assert!(markdown.starts_with(&format!("# Codex session {id}\n")));
+        assert!(markdown.contains("## User\n\nFix <the> bug\n"));
+        assert!(markdown.contains("> **Reasoning**\n>\n> Looking at main.rs\n"));
+        assert!(markdown.contains(
+            "### Tool call: `apply_patch`\n\n```diff\n*** Begin Patch\n-old\n+new\n*** End Patch\n```"
+        ));
+        assert!(markdown.contains("**Output (exit code 0):**\n\n```text\nDone!\n```"));
+        assert!(markdown.contains("## Codex\n\nFixed:\n\n```rust\nfn main() {} // ok\n```"));
+    }
+
+    #[test]
+    fn renders_escaped_highlighted_html() {
+        let html = render_transcript(Uuid::nil(), &session(), ExportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Fix &lt;the&gt; bug"));
+        assert!(html.contains("<span class=\"add\">+new</span>"));
+        assert!(html.contains(
+            "<pre><code class=\"language-rust\"><span class=\"kw\">fn</span> main() {} <span class=\"com\">// ok</span></code></pre>"
+        ));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.HTML")),
+            ExportFormat::Html
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.md")),
+            ExportFormat::Markdown
+        );
+        assert_eq!(fence_for("a ``` b"), "````");
+    }
+}
@@ -0,0 +1,283 @@
+//! The `web_search` tool: the model searches the web and gets back the title,
+//! URL and a snippet of each result. Depending on the `[web_search]` config
+//! the query goes to the provider's own search (the Responses API's hosted
+//! `web_search_preview` tool) or to a SearxNG or Bing endpoint. The tool is
+//! only offered when enabled and the sandbox policy allows network access.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::WireApi;
+use crate::config_types::WebSearchBackend;
+use crate::config_types::WebSearchConfig;
+use crate::protocol::SandboxPolicy;
+
+pub(crate) const WEB_SEARCH_TOOL_NAME: &str = "web_search";
+
+const DEFAULT_MAX_RESULTS: usize = 5;
+
+/// Upper bound on `max_results`, whoever asks for it.
+const MAX_RESULTS_LIMIT: usize = 20;
+
+const BING_ENDPOINT: &str = "https://api.bing.microsoft.com/v7.0/search";
+
+const BING_API_KEY_ENV: &str = "BING_SEARCH_API_KEY";
+
+/// Time allowed for a search endpoint to answer.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// System prompt of the one-off request that runs a native search.
+pub(crate) const NATIVE_SEARCH_INSTRUCTIONS: &str = "Search the web for the user's query. \
+    Reply with only a JSON array of the results, best first, without any other text. Each \
+    result is an object with the keys \"title\", \"url\" and \"snippet\" (one or two sentences \
+    from the page). Reply with [] if nothing relevant turns up.";
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WebSearchArgs {
+    pub query: String,
+    #[serde(default)]
+    pub max_results: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SearchResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub snippet: String,
+}
+
+/// Why the tool is not offered in a session, or `None` if it is.
+pub(crate) fn web_search_unavailable(
+    config: &WebSearchConfig,
+    sandbox_policy: &SandboxPolicy,
+    wire_api: WireApi,
+) -> Option<&'static str> {
+    if !config.enabled {
+        Some("not enabled in [web_search]")
+    } else if !sandbox_policy.has_full_network_access() {
+        Some("the sandbox policy does not allow network access")
+    } else if config.backend == WebSearchBackend::Native && wire_api != WireApi::Responses {
+        Some("native search needs a provider with the Responses API")
+    } else if config.backend == WebSearchBackend::Searxng && config.url.is_none() {
+        Some("the searxng backend needs a url")
+    } else {
+        None
+    }
+}
+
+/// Results to return for `args`, bounded by the config.
+pub(crate) fn max_results(config: &WebSearchConfig, args: &WebSearchArgs) -> usize {
+    args.max_results
+        .or(config.max_results)
+        .unwrap_or(DEFAULT_MAX_RESULTS)
+        .clamp(1, MAX_RESULTS_LIMIT)
+}
+
+/// Query the SearxNG or Bing endpoint of `config`.
+pub(crate) async fn search_endpoint(
+    config: &WebSearchConfig,
+    query: &str,
+    max_results: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("failed to create HTTP client: {e}"))?;
+    let request = match config.backend {
+        WebSearchBackend::Searxng => {
+            let base = config.url.as_deref().unwrap_or_default();
+            client
+                .get(format!("{}/search", base.trim_end_matches('/')))
+                .query(&[("q", query), ("format", "json")])
+        }
+        WebSearchBackend::Bing => {
+            let key_env = config.api_key_env.as_deref().unwrap_or(BING_API_KEY_ENV);
+            let key = std::env::var(key_env)
+                .map_err(|_| format!("set {key_env} to the Bing subscription key"))?;
+            let count = max_results.to_string();
+            client
+                .get(config.url.as_deref().unwrap_or(BING_ENDPOINT))
+                .query(&[("q", query), ("count", count.as_str())])
+                .header("Ocp-Apim-Subscription-Key", key)
+        }
+        WebSearchBackend::Native => {
+            return Err("native search does not use an endpoint".to_string());
+        }
+    };
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("search request failed: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("search endpoint returned {status}"));
+    }
+    let json: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("search endpoint returned invalid JSON: {e}"))?;
+    Ok(match config.backend {
+        WebSearchBackend::Bing => parse_bing_results(&json, max_results),
+        _ => parse_searxng_results(&json, max_results),
+    })
+}
+
+/// `results[]` of SearxNG's JSON output.
+fn parse_searxng_results(json: &Value, max_results: usize) -> Vec<SearchResult> {
+    parse_results(json.get("results"), "title", "content", max_results)
+}
+
+/// `webPages.value[]` of a Bing Web Search response.
+fn parse_bing_results(json: &Value, max_results: usize) -> Vec<SearchResult> {
+    let pages = json.get("webPages").and_then(|pages| pages.get("value"));
+    parse_results(pages, "name", "snippet", max_results)
+}
+
+fn parse_results(
+    results: Option<&Value>,
+    title_key: &str,
+    snippet_key: &str,
+    max_results: usize,
+) -> Vec<SearchResult> {
+    let field = |result: &Value, key: &str| {
+        result
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+    results
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|result| SearchResult {
+            title: field(result, title_key),
+            url: field(result, "url"),
+            snippet: field(result, snippet_key),
+        })
+        .filter(|result| !result.url.is_empty())
+        .take(max_results)
+        .collect()
+}
+
+/// Parse the results from the reply of a native search, tolerating text or a
+/// code fence around the JSON array.
+pub(crate) fn parse_native_results(
+    reply: &str,
+    max_results: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Err(format!("the search reply has no results: {reply}")),
+    };
+    let mut results: Vec<SearchResult> = serde_json::from_str(json)
+        .map_err(|e| format!("the search reply is not a valid list of results: {e}"))?;
+    results.truncate(max_results);
+    Ok(results)
+}
+
+/// The tool output: one numbered entry per result.
+pub(crate) fn format_results(query: &str, results: &[SearchResult]) -> String {
+    if results.is_empty() {
+        return format!("No results for \"{query}\".");
+    }
+    results
+        .iter()
+        .enumerate()
+        .map(|(idx, result)| {
+            format!(
+                "{}. {}\n   {}\n   {}",
+                idx + 1,
+                result.title,
+                result.url,
+                result.snippet
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::protocol::SandboxPermission;
+    use serde_json::json;
+
+    #[test]
+    fn parses_searxng_and_bing_results() {
+        let searxng = json!({"results": [
+            {"title": "Rust", "url": "https://rust-lang.org", "content": "A language."},
+            {"title": "No url", "content": "skipped"},
+            {"title": "Tokio", "url": "https://tokio.rs", "content": "Async runtime."},
+        ]});
+        assert_eq!(
+            parse_searxng_results(&searxng, 5),
+            vec![
+                SearchResult {
+                    title: "Rust".to_string(),
+                    url: "https://rust-lang.org".to_string(),
+                    snippet: "A language.".to_string(),
+                },
+                SearchResult {
+                    title: "Tokio".to_string(),
+                    url: "https://tokio.rs".to_string(),
+                    snippet: "Async runtime.".to_string(),
+                },
+            ]
+        );
+        assert_eq!(parse_searxng_results(&searxng, 1).len(), 1);
+
+        let bing = json!({"webPages": {"value": [
+            {"name": "Serde", "url": "https://serde.rs", "snippet": "Serialization."},
+        ]}});
+        assert_eq!(parse_bing_results(&bing, 5)[0].title, "Serde");
+        assert!(parse_bing_results(&json!({}), 5).is_empty());
+    }
+
+    #[test]
+    fn parses_native_results_inside_code_fence() {
+        let reply = "```json\n[{\"title\": \"Rust\", \"url\": \"https://rust-lang.org\", \"snippet\": \"A language.\"}]\n```";
+        let results = parse_native_results(reply, 5).unwrap();
+        assert_eq!(results[0].url, "https://rust-lang.org");
+        assert!(parse_native_results("I could not search.", 5).is_err());
+        assert_eq!(
+            format_results("rust", &results),
+            "1. Rust\n   https://rust-lang.org\n   A language."
+        );
+    }
+
+    #[test]
+    fn only_offered_when_enabled_with_network_access() {
+        let mut config = WebSearchConfig::default();
+        let network = SandboxPolicy::from(vec![
+            SandboxPermission::DiskFullReadAccess,
+            SandboxPermission::NetworkFullAccess,
+        ]);
+        assert!(web_search_unavailable(&config, &network, WireApi::Responses).is_some());
+
+        config.enabled = true;
+        assert!(
+            web_search_unavailable(
+                &config,
+                &SandboxPolicy::new_read_only_policy(),
+                WireApi::Responses
+            )
+            .is_some()
+        );
+        assert!(web_search_unavailable(&config, &network, WireApi::Chat).is_some());
+
+        config.backend = WebSearchBackend::Searxng;
+        config.url = Some("https://searx.example.org".to_string());
+        assert_eq!(
+            web_search_unavailable(&config, &network, WireApi::Chat),
+            None
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Word-only parsing of `powershell -Command` and `cmd /c` scripts, the
+//! Windows counterpart of the tree-sitter based parsing of `bash -lc` scripts
+//! in [`crate::is_safe_command`].
+//!
+//! Only the plain subset of each syntax is accepted: bare words, quoted
+//! strings without expansion, and commands joined by `&&`, `||`, `|`, `;` (for
+//! PowerShell) or newlines. Anything else (variables, subexpressions,
+//! redirections, escapes, ...) makes the whole script unparseable so that it
+//! goes through the regular approval flow.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowsShell {
+    PowerShell,
+    Cmd,
+}
+
+/// If `command` runs a script with `powershell`/`pwsh -Command` or `cmd /c`,
+/// return the shell and the script.
+pub(crate) fn windows_shell_script(command: &[String]) -> Option<(WindowsShell, &str)> {
+    let (program, args) = command.split_first()?;
+    let name = program.rsplit(['\\', '/']).next()?.to_ascii_lowercase();
+    match name.strip_suffix(".exe").unwrap_or(&name) {
+        "powershell" | "pwsh" => {
+            // Switches that do not change how the script is interpreted.
+            let mut args = args.iter().skip_while(|arg| {
+                matches!(
+                    arg.to_ascii_lowercase().as_str(),
+                    "-noprofile" | "-nologo" | "-noninteractive"
+                )
+            });
+            let flag = args.next()?.to_ascii_lowercase();
+            let script = args.next()?;
+            (matches!(flag.as_str(), "-command" | "-c") && args.next().is_none())
+                .then_some((WindowsShell::PowerShell, script.as_str()))
+        }
+        "cmd" => match args {
+            [flag, script] if flag.eq_ignore_ascii_case("/c") => {
+                Some((WindowsShell::Cmd, script.as_str()))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Split `script` into the words of each of its commands if it only uses the
+/// plain subset of `shell`'s syntax; otherwise, return `None`.
+pub(crate) fn parse_windows_word_only_commands_sequence(
+    shell: WindowsShell,
+    script: &str,
+) -> Option<Vec<Vec<String>>> {
+    let mut commands = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    // Set after `&&`, `||`, `|` or `;`, which must be followed by a command.
+    let mut expect_command = false;
+    let mut chars = script.chars().peekable();
+
+    loop {
+        while matches!(chars.peek(), Some(' ' | '\t')) {
+            chars.next();
+        }
+        let Some(&c) = chars.peek() else {
+            break;
+        };
+        match c {
+            '\n' | '\r' => {
+                chars.next();
+                if !words.is_empty() {
+                    commands.push(std::mem::take(&mut words));
+                } else if expect_command {
+                    return None;
+                }
+            }
+            ';' | '|' | '&' => {
+                chars.next();
+                match c {
+                    '|' | '&' if chars.peek() == Some(&c) => {
+                        chars.next();
+                    }
+                    // `cmd` runs the next command after a single `&`.
+                    '&' if shell == WindowsShell::Cmd => {}
+                    // A single `&` is PowerShell's call operator.
+                    '&' => return None,
+                    ';' if shell == WindowsShell::Cmd => return None,
+                    _ => {}
+                }
+                if words.is_empty() {
+                    return None;
+                }
+                commands.push(std::mem::take(&mut words));
+                expect_command = true;
+                continue;
+            }
+            _ => words.push(read_word(shell, &mut chars)?),
+        }
+        expect_command = false;
+    }
+
+    if expect_command && words.is_empty() {
+        return None;
+    }
+    if !words.is_empty() {
+        commands.push(words);
+    }
+    if commands.is_empty() {
+        return None;
+    }
+    Some(commands)
+}
+
+fn read_word(shell: WindowsShell, chars: &mut Peekable<Chars<'_>>) -> Option<String> {
+    let mut word = String::new();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' | '\r' | ';' | '|' | '&' => break,
+            '"' => {
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        c if is_expansion_char(shell, c) => return None,
+                        c => word.push(c),
+                    }
+                }
+            }
+            // PowerShell single-quoted strings are literal; `''` is a quote.
+            '\'' if shell == WindowsShell::PowerShell => {
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        '\'' if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            word.push('\'');
+                        }
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            c if c.is_alphanumeric() || "-_./\\:=+~*?".contains(c) => {
+                chars.next();
+                word.push(c);
+            }
+            _ => return None,
+        }
+    }
+    Some(word)
+}
+
+/// Characters that expand to something else even inside double quotes.
+fn is_expansion_char(shell: WindowsShell, c: char) -> bool {
+    match shell {
+        WindowsShell::PowerShell => matches!(c, '$' | '`'),
+        WindowsShell::Cmd => matches!(c, '%' | '!'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn parse(shell: WindowsShell, script: &str) -> Option<Vec<Vec<String>>> {
+        parse_windows_word_only_commands_sequence(shell, script)
+    }
+
+    #[test]
+    fn recognizes_windows_shell_invocations() {
+        let command = vec_str(&["powershell.exe", "-NoProfile", "-Command", "Get-ChildItem"]);
+        assert_eq!(
+            windows_shell_script(&command),
+            Some((WindowsShell::PowerShell, "Get-ChildItem"))
+        );
+        let command = vec_str(&["C:\\Windows\\System32\\cmd.exe", "/C", "dir"]);
+        assert_eq!(
+            windows_shell_script(&command),
+            Some((WindowsShell::Cmd, "dir"))
+        );
+        assert_eq!(
+            windows_shell_script(&vec_str(&["pwsh", "-File", "script.ps1"])),
+            None
+        );
+        assert_eq!(windows_shell_script(&vec_str(&["bash", "-lc", "ls"])), None);
+    }
+
+    #[test]
+    fn parses_plain_powershell() {
+        assert_eq!(
+            parse(
+                WindowsShell::PowerShell,
+                "Get-Content -Path 'C:\\src\\it''s.txt'; git status && rg \"fn main\""
+            ),
+            Some(vec![
+                vec_str(&["Get-Content", "-Path", "C:\\src\\it's.txt"]),
+                vec_str(&["git", "status"]),
+                vec_str(&["rg", "fn main"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_powershell_expansion() {
+        for script in [
+            "Get-Content $env:USERPROFILE",
+            "echo \"$(Remove-Item x)\"",
+            "& rm x",
+            "ls > out.txt",
+            "Get-ChildItem | % { rm $_ }",
+            "ls a,b",
+            "git status &&",
+            "echo `n",
+        ] {
+            assert_eq!(parse(WindowsShell::PowerShell, script), None, "{script}");
+        }
+    }
+
+    #[test]
+    fn parses_plain_cmd() {
+        assert_eq!(
+            parse(
+                WindowsShell::Cmd,
+                "dir /b \"C:\\Program Files\" & type a.txt"
+            ),
+            Some(vec![
+                vec_str(&["dir", "/b", "C:\\Program Files"]),
+                vec_str(&["type", "a.txt"]),
+            ])
+        );
+        for script in [
+            "echo %PATH%",
+            "type a.txt > b.txt",
+            "dir ^& del x",
+            "echo \"!x!\"",
+        ] {
+            assert_eq!(parse(WindowsShell::Cmd, script), None, "{script}");
+        }
+    }
+}
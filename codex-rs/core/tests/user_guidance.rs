@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use codex_core::Codex;
+use codex_core::ModelProviderInfo;
+use codex_core::exec::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_core::protocol::ErrorEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::UserGuidanceDeliveredEvent;
+mod test_support;
+use tempfile::TempDir;
+use test_support::load_default_config_for_test;
+use tokio::time::timeout;
+use wiremock::Match;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::Request;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const GUIDANCE: &str = "use the other directory";
+
+/// Matcher for requests whose body carries the guidance (or not).
+struct CarriesGuidance(bool);
+
+impl Match for CarriesGuidance {
+    fn matches(&self, req: &Request) -> bool {
+        String::from_utf8_lossy(&req.body).contains(GUIDANCE) == self.0
+    }
+}
+
+/// SSE stream of a response that calls a tool the session does not have.
+fn sse_function_call(id: &str) -> String {
+    format!(
+        "event: response.output_item.done\n\
+data: {{\"type\":\"response.output_item.done\",\"item\":{{\"type\":\"function_call\",\"name\":\"noop\",\"arguments\":\"{{}}\",\"call_id\":\"call1\"}}}}\n\n\
+event: response.completed\n\
+data: {{\"type\":\"response.completed\",\"response\":{{\"id\":\"{id}\",\"output\":[]}}}}\n\n\n"
+    )
+}
+
+fn sse_completed(id: &str) -> String {
+    format!(
+        "event: response.completed\n\
+data: {{\"type\":\"response.completed\",\"response\":{{\"id\":\"{id}\",\"output\":[]}}}}\n\n\n"
+    )
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn guidance_is_sent_with_the_next_request_after_a_tool_call() {
+    #![allow(clippy::unwrap_used)]
+
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+
+    // The first request is slow enough for the guidance to arrive while it
+    // is in flight, and ends in a tool call.
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(CarriesGuidance(false))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse_function_call("resp1"), "text/event-stream")
+                .set_delay(Duration::from_millis(500)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // The follow-up with the tool output carries the guidance.
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(CarriesGuidance(true))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse_completed("resp2"), "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    unsafe {
+        std::env::set_var("OPENAI_REQUEST_MAX_RETRIES", "0");
+        std::env::set_var("OPENAI_STREAM_MAX_RETRIES", "0");
+    }
+    let model_provider = ModelProviderInfo {
+        name: "openai".into(),
+        base_url: format!("{}/v1", server.uri()),
+        // Environment variable that should exist in the test environment.
+        env_key: Some("PATH".into()),
+        env_key_instructions: None,
+        wire_api: codex_core::WireApi::Responses,
+        reasoning_summaries: None,
+    };
+
+    let codex_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model_provider = model_provider;
+    let ctrl_c = std::sync::Arc::new(tokio::sync::Notify::new());
+    let (codex, _init_id) = Codex::spawn(config, ctrl_c.clone()).await.unwrap();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let mut delivered = None;
+    loop {
+        let ev = timeout(Duration::from_secs(5), codex.next_event())
+            .await
+            .unwrap()
+            .unwrap();
+        match ev.msg {
+            EventMsg::TaskStarted => {
+                codex
+                    .submit(Op::InjectUserGuidance {
+                        text: GUIDANCE.into(),
+                    })
+                    .await
+                    .unwrap();
+            }
+            EventMsg::UserGuidanceDelivered(UserGuidanceDeliveredEvent { text }) => {
+                delivered = Some(text);
+            }
+            EventMsg::TaskComplete(_) => break,
+            EventMsg::Error(ErrorEvent { message }) => {
+                panic!("unexpected error: {message}")
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(delivered.as_deref(), Some(GUIDANCE));
+}
@@ -0,0 +1,700 @@
+use codex_common::elapsed::format_elapsed;
+use codex_core::config::Config;
+use codex_core::model_pricing::format_cost_usd;
+use codex_core::protocol::AgentMessageEvent;
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::ContextCompactedEvent;
+use codex_core::protocol::ErrorEvent;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
+use codex_core::protocol::ExecCommandBeginEvent;
+use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::McpStartupEvent;
+use codex_core::protocol::McpToolCallBeginEvent;
+use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::PlanUpdateEvent;
+use codex_core::protocol::ProjectDocsLoadedEvent;
+use codex_core::protocol::ReviewFinding;
+use codex_core::protocol::ReviewSeverity;
+use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::StepStatus;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentMessageEvent;
+use codex_core::protocol::TokenCountEvent;
+use codex_core::protocol::TurnRevertedEvent;
+use codex_core::protocol::UserGuidanceDeliveredEvent;
+use owo_colors::OwoColorize;
+use owo_colors::Style;
+use shlex::try_join;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::event_processor::EventProcessor;
+use crate::event_processor::create_config_summary_entries;
+
+/// This should be configurable. When used in CI, users may not want to impose
+/// a limit so they can see the full transcript.
+const MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL: usize = 20;
+
+/// Prints events as a human-readable, timestamped transcript.
+pub(crate) struct EventProcessorWithHumanOutput {
+    call_id_to_command: HashMap<String, ExecCommandBegin>,
+    call_id_to_patch: HashMap<String, PatchApplyBegin>,
+
+    /// Tracks in-flight MCP tool calls so we can calculate duration and print
+    /// a concise summary when the corresponding `McpToolCallEnd` event is
+    /// received.
+    call_id_to_tool_call: HashMap<String, McpToolCallBegin>,
+
+    // To ensure that --color=never is respected, ANSI escapes _must_ be added
+    // using .style() with one of these fields. If you need a new style, add a
+    // new field here.
+    bold: Style,
+    italic: Style,
+    dimmed: Style,
+
+    magenta: Style,
+    red: Style,
+    green: Style,
+    cyan: Style,
+
+    /// Whether to include `AgentReasoning` events in the output.
+    show_agent_reasoning: bool,
+}
+
+impl EventProcessorWithHumanOutput {
+    pub(crate) fn create_with_ansi(with_ansi: bool, show_agent_reasoning: bool) -> Self {
+        let call_id_to_command = HashMap::new();
+        let call_id_to_patch = HashMap::new();
+        let call_id_to_tool_call = HashMap::new();
+
+        if with_ansi {
+            Self {
+                call_id_to_command,
+                call_id_to_patch,
+                bold: Style::new().bold(),
+                italic: Style::new().italic(),
+                dimmed: Style::new().dimmed(),
+                magenta: Style::new().magenta(),
+                red: Style::new().red(),
+                green: Style::new().green(),
+                cyan: Style::new().cyan(),
+                call_id_to_tool_call,
+                show_agent_reasoning,
+            }
+        } else {
+            Self {
+                call_id_to_command,
+                call_id_to_patch,
+                bold: Style::new(),
+                italic: Style::new(),
+                dimmed: Style::new(),
+                magenta: Style::new(),
+                red: Style::new(),
+                green: Style::new(),
+                cyan: Style::new(),
+                call_id_to_tool_call,
+                show_agent_reasoning,
+            }
+        }
+    }
+}
+
+struct ExecCommandBegin {
+    command: Vec<String>,
+    start_time: Instant,
+}
+
+/// Metadata captured when an `McpToolCallBegin` event is received.
+struct McpToolCallBegin {
+    /// Formatted invocation string, e.g. `server.tool({"city":"sf"})`.
+    invocation: String,
+    /// Timestamp when the call started so we can compute duration later.
+    start_time: Instant,
+}
+
+struct PatchApplyBegin {
+    start_time: Instant,
+    auto_approved: bool,
+}
+
+// Timestamped println helper. The timestamp is styled with self.dimmed.
+#[macro_export]
+macro_rules! ts_println {
+    ($self:ident, $($arg:tt)*) => {{
+        let now = chrono::Utc::now();
+        let formatted = now.format("[%Y-%m-%dT%H:%M:%S]");
+        print!("{} ", formatted.style($self.dimmed));
+        println!($($arg)*);
+    }};
+}
+
+impl EventProcessor for EventProcessorWithHumanOutput {
+    /// Print a concise summary of the effective configuration that will be used
+    /// for the session. This mirrors the information shown in the TUI welcome
+    /// screen.
+    fn print_config_summary(&mut self, config: &Config, prompt: &str) {
+        const VERSION: &str = env!("CARGO_PKG_VERSION");
+        ts_println!(
+            self,
+            "OpenAI Codex v{} (research preview)\n--------",
+            VERSION
+        );
+
+        for (key, value) in create_config_summary_entries(config) {
+            println!("{} {}", format!("{key}:").style(self.bold), value);
+        }
+
+        println!("--------");
+
+        // Echo the prompt that will be sent to the agent so it is visible in the
+        // transcript/logs before any events come in. Note the prompt may have been
+        // read from stdin, so it may not be visible in the terminal otherwise.
+        ts_println!(
+            self,
+            "{}\n{}",
+            "User instructions:".style(self.bold).style(self.cyan),
+            prompt
+        );
+    }
+
+    fn process_event(&mut self, event: Event) {
+        let Event { id: _, msg } = event;
+        match msg {
+            EventMsg::Error(ErrorEvent { message }) => {
+                let prefix = "ERROR:".style(self.red);
+                ts_println!(self, "{prefix} {message}");
+            }
+            EventMsg::McpStartup(McpStartupEvent { servers }) => {
+                for status in servers {
+                    match status.result {
+                        Ok(tools) => ts_println!(
+                            self,
+                            "{}",
+                            format!(
+                                "MCP server {} ready ({tools} tools, {}ms)",
+                                status.server, status.startup_ms
+                            )
+                            .style(self.dimmed)
+                        ),
+                        Err(err) => {
+                            let prefix = "ERROR:".style(self.red);
+                            ts_println!(
+                                self,
+                                "{prefix} MCP server {} failed to start: {err}",
+                                status.server
+                            );
+                        }
+                    }
+                }
+            }
+            EventMsg::ProjectDocsLoaded(ProjectDocsLoadedEvent { docs }) => {
+                let paths: Vec<String> = docs
+                    .iter()
+                    .map(|doc| doc.path.display().to_string())
+                    .collect();
+                ts_println!(
+                    self,
+                    "{}",
+                    format!("AGENTS.md loaded: {}", paths.join(", ")).style(self.dimmed)
+                );
+            }
+            EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
+                // Collapse verbose sandbox-denied and retry logs into exec flow; skip them here.
+                if message.contains("sandbox denied") || message.contains("retrying") {
+                    // drop verbose background messages for exec errors/retries
+                } else {
+                    ts_println!(self, "{}", message.style(self.dimmed));
+                }
+            }
+            EventMsg::TaskStarted | EventMsg::TaskComplete(_) => {
+                // Ignore.
+            }
+            EventMsg::AgentMessage(AgentMessageEvent { message }) => {
+                ts_println!(
+                    self,
+                    "{}\n{message}",
+                    "codex".style(self.bold).style(self.magenta)
+                );
+            }
+            EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+                call_id,
+                command,
+                cwd,
+            }) => {
+                self.call_id_to_command.insert(
+                    call_id.clone(),
+                    ExecCommandBegin {
+                        command: command.clone(),
+                        start_time: Instant::now(),
+                    },
+                );
+                ts_println!(
+                    self,
+                    "{} {} in {}",
+                    "exec".style(self.magenta),
+                    escape_command(&command).style(self.bold),
+                    cwd.to_string_lossy(),
+                );
+            }
+            EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+                call_id,
+                stdout,
+                stderr,
+                exit_code,
+            }) => {
+                let exec_command = self.call_id_to_command.remove(&call_id);
+                let (duration, call) = if let Some(ExecCommandBegin {
+                    command,
+                    start_time,
+                }) = exec_command
+                {
+                    (
+                        format!(" in {}", format_elapsed(start_time)),
+                        format!("{}", escape_command(&command).style(self.bold)),
+                    )
+                } else {
+                    ("".to_string(), format!("exec('{call_id}')"))
+                };
+
+                let output = if exit_code == 0 { stdout } else { stderr };
+                let truncated_output = output
+                    .lines()
+                    .take(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                match exit_code {
+                    0 => {
+                        let title = format!("{call} succeeded{duration}:");
+                        ts_println!(self, "{}", title.style(self.green));
+                    }
+                    _ => {
+                        let title = format!("{call} exited {exit_code}{duration}:");
+                        ts_println!(self, "{}", title.style(self.red));
+                    }
+                }
+                println!("{}", truncated_output.style(self.dimmed));
+            }
+            EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
+                call_id,
+                server,
+                tool,
+                arguments,
+            }) => {
+                // Build fully-qualified tool name: server.tool
+                let fq_tool_name = format!("{server}.{tool}");
+
+                // Format arguments as compact JSON so they fit on one line.
+                let args_str = arguments
+                    .as_ref()
+                    .map(|v: &serde_json::Value| {
+                        serde_json::to_string(v).unwrap_or_else(|_| v.to_string())
+                    })
+                    .unwrap_or_default();
+
+                let invocation = if args_str.is_empty() {
+                    format!("{fq_tool_name}()")
+                } else {
+                    format!("{fq_tool_name}({args_str})")
+                };
+
+                self.call_id_to_tool_call.insert(
+                    call_id.clone(),
+                    McpToolCallBegin {
+                        invocation: invocation.clone(),
+                        start_time: Instant::now(),
+                    },
+                );
+
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "tool".style(self.magenta),
+                    invocation.style(self.bold),
+                );
+            }
+            EventMsg::McpToolCallProgress(progress) => {
+                let mut line = match progress.percent() {
+                    Some(percent) => format!("{percent}%"),
+                    None => format!("{}", progress.progress),
+                };
+                if let Some(message) = &progress.message {
+                    line.push_str(&format!(" {message}"));
+                }
+                let invocation = self
+                    .call_id_to_tool_call
+                    .get(&progress.call_id)
+                    .map(|call| call.invocation.as_str())
+                    .unwrap_or("tool");
+                println!("{}", format!("{invocation} {line}").style(self.dimmed));
+            }
+            EventMsg::McpToolCallEnd(tool_call_end_event) => {
+                let is_success = tool_call_end_event.is_success();
+                let McpToolCallEndEvent { call_id, result } = tool_call_end_event;
+                // Retrieve start time and invocation for duration calculation and labeling.
+                let info = self.call_id_to_tool_call.remove(&call_id);
+
+                let (duration, invocation) = if let Some(McpToolCallBegin {
+                    invocation,
+                    start_time,
+                    ..
+                }) = info
+                {
+                    (format!(" in {}", format_elapsed(start_time)), invocation)
+                } else {
+                    (String::new(), format!("tool('{call_id}')"))
+                };
+
+                let status_str = if is_success { "success" } else { "failed" };
+                let title_style = if is_success { self.green } else { self.red };
+                let title = format!("{invocation} {status_str}{duration}:");
+
+                ts_println!(self, "{}", title.style(title_style));
+
+                if let Ok(res) = result {
+                    let val: serde_json::Value = res.into();
+                    let pretty =
+                        serde_json::to_string_pretty(&val).unwrap_or_else(|_| val.to_string());
+
+                    for line in pretty.lines().take(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL) {
+                        println!("{}", line.style(self.dimmed));
+                    }
+                }
+            }
+            EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+                call_id,
+                auto_approved,
+                changes,
+            }) => {
+                // Store metadata so we can calculate duration later when we
+                // receive the corresponding PatchApplyEnd event.
+                self.call_id_to_patch.insert(
+                    call_id.clone(),
+                    PatchApplyBegin {
+                        start_time: Instant::now(),
+                        auto_approved,
+                    },
+                );
+
+                ts_println!(
+                    self,
+                    "{} auto_approved={}:",
+                    "apply_patch".style(self.magenta),
+                    auto_approved,
+                );
+
+                // Pretty-print the patch summary with colored diff markers so
+                // it’s easy to scan in the terminal output.
+                for (path, change) in changes.iter() {
+                    match change {
+                        FileChange::Add { content } => {
+                            let header = format!(
+                                "{} {}",
+                                format_file_change(change),
+                                path.to_string_lossy()
+                            );
+                            println!("{}", header.style(self.magenta));
+                            for line in content.lines() {
+                                println!("{}", line.style(self.green));
+                            }
+                        }
+                        FileChange::Delete => {
+                            let header = format!(
+                                "{} {}",
+                                format_file_change(change),
+                                path.to_string_lossy()
+                            );
+                            println!("{}", header.style(self.magenta));
+                        }
+                        FileChange::Update {
+                            unified_diff,
+                            move_path,
+                        } => {
+                            let header = if let Some(dest) = move_path {
+                                format!(
+                                    "{} {} -> {}",
+                                    format_file_change(change),
+                                    path.to_string_lossy(),
+                                    dest.to_string_lossy()
+                                )
+                            } else {
+                                format!("{} {}", format_file_change(change), path.to_string_lossy())
+                            };
+                            println!("{}", header.style(self.magenta));
+
+                            // Colorize diff lines. We keep file header lines
+                            // (--- / +++) without extra coloring so they are
+                            // still readable.
+                            for diff_line in unified_diff.lines() {
+                                if diff_line.starts_with('+') && !diff_line.starts_with("+++") {
+                                    println!("{}", diff_line.style(self.green));
+                                } else if diff_line.starts_with('-')
+                                    && !diff_line.starts_with("---")
+                                {
+                                    println!("{}", diff_line.style(self.red));
+                                } else {
+                                    println!("{diff_line}");
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+                call_id,
+                stdout,
+                stderr,
+                success,
+            }) => {
+                let patch_begin = self.call_id_to_patch.remove(&call_id);
+
+                // Compute duration and summary label similar to exec commands.
+                let (duration, label) = if let Some(PatchApplyBegin {
+                    start_time,
+                    auto_approved,
+                }) = patch_begin
+                {
+                    (
+                        format!(" in {}", format_elapsed(start_time)),
+                        format!("apply_patch(auto_approved={})", auto_approved),
+                    )
+                } else {
+                    (String::new(), format!("apply_patch('{call_id}')"))
+                };
+
+                let (exit_code, output, title_style) = if success {
+                    (0, stdout, self.green)
+                } else {
+                    (1, stderr, self.red)
+                };
+
+                let title = format!("{label} exited {exit_code}{duration}:");
+                ts_println!(self, "{}", title.style(title_style));
+                for line in output.lines() {
+                    println!("{}", line.style(self.dimmed));
+                }
+            }
+            EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent { command, .. }) => {
+                // There is nobody to ask, so the request is always denied.
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "approval denied:".style(self.red),
+                    escape_command(&command).style(self.bold)
+                );
+            }
+            EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+                changes, ..
+            }) => {
+                let paths = changes
+                    .keys()
+                    .map(|path| path.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ts_println!(
+                    self,
+                    "{} apply_patch {}",
+                    "approval denied:".style(self.red),
+                    paths.style(self.bold)
+                );
+            }
+            EventMsg::AgentReasoning(agent_reasoning_event) => {
+                if self.show_agent_reasoning {
+                    ts_println!(
+                        self,
+                        "{}\n{}",
+                        "thinking".style(self.italic).style(self.magenta),
+                        agent_reasoning_event.text
+                    );
+                }
+            }
+            EventMsg::SessionConfigured(session_configured_event) => {
+                let SessionConfiguredEvent {
+                    session_id,
+                    model,
+                    history_log_id: _,
+                    history_entry_count: _,
+                    mcp_prompts: _,
+                    custom_prompts: _,
+                } = session_configured_event;
+
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "codex resume".style(self.magenta).style(self.bold),
+                    session_id.to_string().style(self.dimmed)
+                );
+
+                ts_println!(self, "model: {}", model);
+                println!();
+            }
+            EventMsg::GetHistoryEntryResponse(_) | EventMsg::SearchHistoryResponse(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::McpPrompt(_) | EventMsg::ProjectDocProposal(_) => {
+                // Only requested by interactive clients.
+            }
+            EventMsg::CustomPromptsUpdated(_) => {
+                // Custom prompts are only offered by interactive clients.
+            }
+            EventMsg::BackgroundProcessesUpdated(_) => {
+                // The model reports on background processes itself.
+            }
+            EventMsg::ExecCommandOutputDelta(_) => {
+                // The full output is printed once the command ends.
+            }
+            EventMsg::ContextCompacted(ContextCompactedEvent {
+                tokens_before,
+                tokens_after,
+                ..
+            }) => {
+                ts_println!(
+                    self,
+                    "{} {tokens_before} → {tokens_after} tokens",
+                    "context compacted:".style(self.magenta).style(self.bold)
+                );
+            }
+            EventMsg::SubAgentBegin(SubAgentBeginEvent {
+                prompt, max_turns, ..
+            }) => {
+                ts_println!(
+                    self,
+                    "{} (up to {max_turns} turns)",
+                    "sub-agent started".style(self.magenta).style(self.bold)
+                );
+                for line in prompt.lines().take(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL) {
+                    println!("{}", line.style(self.dimmed));
+                }
+            }
+            EventMsg::SubAgentMessage(SubAgentMessageEvent { message, .. }) => {
+                ts_println!(
+                    self,
+                    "{}\n{message}",
+                    "sub-agent".style(self.italic).style(self.magenta)
+                );
+            }
+            EventMsg::SubAgentEnd(SubAgentEndEvent { turns, result, .. }) => match result {
+                Ok(_) => ts_println!(
+                    self,
+                    "{}",
+                    format!("sub-agent finished after {turns} turns").style(self.green)
+                ),
+                Err(message) => ts_println!(
+                    self,
+                    "{} {message}",
+                    format!("sub-agent failed after {turns} turns:").style(self.red)
+                ),
+            },
+            EventMsg::PlanUpdate(PlanUpdateEvent { explanation, plan }) => {
+                ts_println!(self, "{}", "plan:".style(self.magenta).style(self.bold));
+                if let Some(explanation) = explanation {
+                    println!("{}", explanation.style(self.italic));
+                }
+                for step in plan {
+                    match step.status {
+                        StepStatus::Completed => {
+                            println!("{}", format!("[x] {}", step.step).style(self.dimmed))
+                        }
+                        StepStatus::InProgress => {
+                            println!("{}", format!("[>] {}", step.step).style(self.bold))
+                        }
+                        StepStatus::Pending => println!("[ ] {}", step.step),
+                    }
+                }
+            }
+            EventMsg::ReviewFinding(ReviewFinding {
+                file,
+                line_range,
+                severity,
+                body,
+            }) => {
+                let severity = match severity {
+                    ReviewSeverity::Error => "error".style(self.red),
+                    ReviewSeverity::Warning => "warning".style(self.magenta),
+                    ReviewSeverity::Info => "info".style(self.dimmed),
+                };
+                let lines = if line_range.start == line_range.end {
+                    line_range.start.to_string()
+                } else {
+                    format!("{}-{}", line_range.start, line_range.end)
+                };
+                ts_println!(
+                    self,
+                    "{} {}",
+                    severity.style(self.bold),
+                    format!("{file}:{lines}").style(self.cyan)
+                );
+                println!("{body}");
+            }
+            EventMsg::UserGuidanceDelivered(UserGuidanceDeliveredEvent { text }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "guidance delivered:".style(self.magenta).style(self.bold),
+                    text
+                );
+            }
+            EventMsg::TurnReverted(TurnRevertedEvent { files }) => {
+                ts_println!(
+                    self,
+                    "{}",
+                    "reverted files:".style(self.magenta).style(self.bold)
+                );
+                for path in files {
+                    println!("{}", path.to_string_lossy().style(self.dimmed));
+                }
+            }
+            EventMsg::TokenCount(TokenCountEvent {
+                last,
+                total,
+                max_session_tokens,
+                estimated_cost_usd,
+                ..
+            }) => {
+                let budget = max_session_tokens
+                    .map(|max| format!(" / {max}"))
+                    .unwrap_or_default();
+                let cost = estimated_cost_usd
+                    .map(|cost| format!(" ({})", format_cost_usd(cost)))
+                    .unwrap_or_default();
+                let cache = last
+                    .cache_hit_ratio()
+                    .map(|ratio| format!(", {:.0}% of this turn's input cached", ratio * 100.0))
+                    .unwrap_or_default();
+                ts_println!(
+                    self,
+                    "{} {}{budget}{cost}{cache}",
+                    "tokens used:".style(self.dimmed),
+                    total.total_tokens
+                );
+            }
+        }
+    }
+}
+
+fn escape_command(command: &[String]) -> String {
+    // Strip any `bash -lc` wrapper and return the inner command directly.
+    if command.len() == 3 && command[0] == "bash" && command[1] == "-lc" {
+        return command[2].clone();
+    }
+    try_join(command.iter().map(|s| s.as_str())).unwrap_or_else(|_| command.join(" "))
+}
+
+fn format_file_change(change: &FileChange) -> &'static str {
+    match change {
+        FileChange::Add { .. } => "A",
+        FileChange::Delete => "D",
+        FileChange::Update {
+            move_path: Some(_), ..
+        } => "R",
+        FileChange::Update {
+            move_path: None, ..
+        } => "M",
+    }
+}
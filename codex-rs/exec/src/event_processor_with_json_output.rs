@@ -0,0 +1,37 @@
+use codex_core::config::Config;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use serde_json::Map;
+use serde_json::Value;
+use tracing::error;
+
+use crate::event_processor::EventProcessor;
+use crate::event_processor::create_config_summary_entries;
+
+/// Prints one JSON object per line: first the configuration summary, then
+/// every [`Event`] in its serialized protocol form.
+pub(crate) struct EventProcessorWithJsonOutput;
+
+impl EventProcessor for EventProcessorWithJsonOutput {
+    fn print_config_summary(&mut self, config: &Config, prompt: &str) {
+        let mut summary: Map<String, Value> = create_config_summary_entries(config)
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), Value::String(value)))
+            .collect();
+        summary.insert("prompt".to_string(), Value::String(prompt.to_string()));
+        println!("{}", Value::Object(summary));
+    }
+
+    fn process_event(&mut self, event: Event) {
+        match &event.msg {
+            // The complete output is included in `ExecCommandEnd`.
+            EventMsg::ExecCommandOutputDelta(_)
+            | EventMsg::GetHistoryEntryResponse(_)
+            | EventMsg::SearchHistoryResponse(_) => {}
+            _ => match serde_json::to_string(&event) {
+                Ok(line) => println!("{line}"),
+                Err(e) => error!("Failed to serialize event: {e:?}"),
+            },
+        }
+    }
+}
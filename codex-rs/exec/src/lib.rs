@@ -1,5 +1,7 @@
 mod cli;
 mod event_processor;
+mod event_processor_with_human_output;
+mod event_processor_with_json_output;
 
 use std::io::IsTerminal;
 use std::io::Read;
@@ -16,25 +18,61 @@ use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::telemetry::otel_layer;
 use codex_core::util::is_inside_git_repo;
 use event_processor::EventProcessor;
+use event_processor_with_human_output::EventProcessorWithHumanOutput;
+use event_processor_with_json_output::EventProcessorWithJsonOutput;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
 
-pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
+/// How a `codex exec` run ended. Scripts can branch on it through
+/// [`ExecOutcome::exit_code`] without parsing the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecOutcome {
+    /// The task ran to completion.
+    Completed,
+    /// The task ran to completion, but at least one command or patch needed
+    /// approval and was denied.
+    NeededApproval,
+    /// The task ended with an error, was interrupted, or ran out of its token
+    /// budget.
+    Failed,
+}
+
+impl ExecOutcome {
+    /// The process exit code for this outcome. `2` is left to clap for
+    /// invalid command-line usage.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ExecOutcome::Completed => 0,
+            ExecOutcome::Failed => 1,
+            ExecOutcome::NeededApproval => 3,
+        }
+    }
+}
+
+pub async fn run_main(
+    cli: Cli,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<ExecOutcome> {
     let Cli {
         images,
         model,
         config_profile,
+        approval_policy,
         full_auto,
         sandbox,
         cwd,
         skip_git_repo_check,
         color,
+        json,
         last_message_file,
         prompt,
         config_overrides,
@@ -95,8 +133,9 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         model,
         config_profile,
         // This CLI is intended to be headless and has no affordances for asking
-        // the user for approval.
-        approval_policy: Some(AskForApproval::Never),
+        // the user for approval: unless another policy is requested, never
+        // ask, and deny any request that is made anyway.
+        approval_policy: Some(approval_policy.map_or(AskForApproval::Never, Into::into)),
         sandbox_policy,
         cwd: cwd.map(|p| p.canonicalize().unwrap_or(p)),
         model_provider: None,
@@ -112,8 +151,14 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
 
     let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
-    let mut event_processor =
-        EventProcessor::create_with_ansi(stdout_with_ansi, !config.hide_agent_reasoning);
+    let mut event_processor: Box<dyn EventProcessor> = if json {
+        Box::new(EventProcessorWithJsonOutput)
+    } else {
+        Box::new(EventProcessorWithHumanOutput::create_with_ansi(
+            stdout_with_ansi,
+            !config.hide_agent_reasoning,
+        ))
+    };
     // Print the effective configuration and prompt so users can see what Codex
     // is using.
     event_processor.print_config_summary(&config, &prompt);
@@ -125,16 +170,29 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
 
     // TODO(mbolin): Take a more thoughtful approach to logging.
     let default_level = "error";
-    let _ = tracing_subscriber::fmt()
+    let stderr_layer = tracing_subscriber::fmt::layer()
+        .with_ansi(stderr_with_ansi)
+        .with_writer(std::io::stderr)
         // Fallback to the `default_level` log filter if the environment
         // variable is not set _or_ contains an invalid value
-        .with_env_filter(
+        .with_filter(
             EnvFilter::try_from_default_env()
                 .or_else(|_| EnvFilter::try_new(default_level))
                 .unwrap_or_else(|_| EnvFilter::new(default_level)),
-        )
-        .with_ansi(stderr_with_ansi)
-        .with_writer(std::io::stderr)
+        );
+    // Export spans and metrics to the `[otel]` collector, if configured. The
+    // guard flushes them when the run ends.
+    let (otel_layer, _otel_guard) = match config.otel.as_ref().map(otel_layer) {
+        Some(Ok((layer, guard))) => (Some(layer), Some(guard)),
+        Some(Err(e)) => {
+            eprintln!("Not exporting to OpenTelemetry: {e}");
+            (None, None)
+        }
+        None => (None, None),
+    };
+    let _ = tracing_subscriber::registry()
+        .with(stderr_layer)
+        .with(otel_layer)
         .try_init();
 
     let (codex_wrapper, event, ctrl_c) = codex_wrapper::init_codex(config).await?;
@@ -205,22 +263,50 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     let initial_prompt_task_id = codex.submit(Op::UserInput { items }).await?;
     info!("Sent prompt with event ID: {initial_prompt_task_id}");
 
-    // Run the loop until the task is complete.
+    // Run the loop until the task is complete or fails.
+    let mut needed_approval = false;
+    let mut outcome = ExecOutcome::Failed;
     while let Some(event) = rx.recv().await {
-        let (is_last_event, last_assistant_message) = match &event.msg {
+        let task_complete = match &event.msg {
             EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
-                (true, last_agent_message.clone())
+                Some(last_agent_message.clone())
             }
-            _ => (false, None),
+            _ => None,
+        };
+        // A turn that errors out never sends `TaskComplete`.
+        let failed = event.id == initial_prompt_task_id && matches!(event.msg, EventMsg::Error(_));
+        let denial = match &event.msg {
+            EventMsg::ExecApprovalRequest(_) => Some(Op::ExecApproval {
+                id: event.id.clone(),
+                decision: ReviewDecision::Denied,
+            }),
+            EventMsg::ApplyPatchApprovalRequest(_) => Some(Op::PatchApproval {
+                id: event.id.clone(),
+                decision: ReviewDecision::Denied,
+            }),
+            _ => None,
         };
         event_processor.process_event(event);
-        if is_last_event {
+        if let Some(denial) = denial {
+            needed_approval = true;
+            codex.submit(denial).await?;
+        }
+        if let Some(last_assistant_message) = task_complete {
             handle_last_message(last_assistant_message, last_message_file.as_deref())?;
+            outcome = if needed_approval {
+                ExecOutcome::NeededApproval
+            } else {
+                ExecOutcome::Completed
+            };
+            break;
+        }
+        if failed {
+            handle_last_message(None, last_message_file.as_deref())?;
             break;
         }
     }
 
-    Ok(())
+    Ok(outcome)
 }
 
 fn handle_last_message(
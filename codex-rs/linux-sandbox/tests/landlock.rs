@@ -1,6 +1,7 @@
 #![cfg(target_os = "linux")]
 #![expect(clippy::unwrap_used, clippy::expect_used)]
 
+use codex_core::config_types::ContainerSandboxConfig;
 use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
@@ -44,6 +45,8 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         cwd: std::env::current_dir().expect("cwd should exist"),
         timeout_ms: Some(timeout_ms),
         env: create_env_from_core_vars(),
+        tty_columns: None,
+        max_output_bytes: None,
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy_with_writable_roots(writable_roots);
@@ -56,6 +59,8 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         ctrl_c,
         &sandbox_policy,
         &codex_linux_sandbox_exe,
+        &ContainerSandboxConfig::default(),
+        None,
     )
     .await
     .unwrap();
@@ -116,7 +121,8 @@ async fn test_writable_root() {
 }
 
 #[tokio::test]
-#[should_panic(expected = "Sandbox(Timeout)")]
+// Timed-out commands are killed and reported with exit code 124.
+#[should_panic(expected = "exit code: 124")]
 async fn test_timeout() {
     run_cmd(&["sleep", "2"], &[], 50).await;
 }
@@ -134,6 +140,8 @@ async fn assert_network_blocked(cmd: &[&str]) {
         // do not stall the suite.
         timeout_ms: Some(NETWORK_TIMEOUT_MS),
         env: create_env_from_core_vars(),
+        tty_columns: None,
+        max_output_bytes: None,
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -146,6 +154,8 @@ async fn assert_network_blocked(cmd: &[&str]) {
         ctrl_c,
         &sandbox_policy,
         &codex_linux_sandbox_exe,
+        &ContainerSandboxConfig::default(),
+        None,
     )
     .await;
 
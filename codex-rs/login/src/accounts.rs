@@ -0,0 +1,250 @@
+//! Named accounts: besides the default `CODEX_HOME/auth.json`, logins can be
+//! stored as `CODEX_HOME/auth/<account>.json` (`codex login --profile
+//! <account>`) and picked with `account = "<account>"` in config.toml.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::Deserialize;
+
+use crate::AuthDotJson;
+
+/// Directory in CODEX_HOME holding the auth files of named accounts.
+const ACCOUNTS_DIR: &str = "auth";
+
+/// Name that refers to the default `auth.json` rather than a named account.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// The auth file of `account`, or the default `auth.json` when `None`.
+/// Fails for a name that is not a plain account name (see
+/// [`is_valid_account_name`]), which could otherwise point outside
+/// `CODEX_HOME/auth`.
+pub fn auth_file_path(codex_home: &Path, account: Option<&str>) -> std::io::Result<PathBuf> {
+    match account {
+        Some(account) if !is_valid_account_name(account) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("`{account}` is not a valid account name"),
+        )),
+        Some(account) => Ok(codex_home
+            .join(ACCOUNTS_DIR)
+            .join(format!("{account}.json"))),
+        None => Ok(codex_home.join("auth.json")),
+    }
+}
+
+/// Account names become file names, so they are limited to letters, digits,
+/// `-`, `_` and `.` (but not a leading `.`). [`DEFAULT_ACCOUNT`] is taken.
+pub fn is_valid_account_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != DEFAULT_ACCOUNT
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Names of the stored named accounts, sorted.
+pub fn list_accounts(codex_home: &Path) -> Vec<String> {
+    let mut accounts: Vec<String> = std::fs::read_dir(codex_home.join(ACCOUNTS_DIR))
+        .map(|dir| {
+            dir.flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    name.strip_suffix(".json")
+                        .filter(|account| is_valid_account_name(account))
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    accounts.sort();
+    accounts
+}
+
+/// Who an auth file logs in as, from the claims of its tokens.
+#[derive(Debug, Default, PartialEq)]
+pub struct AccountInfo {
+    pub email: Option<String>,
+    /// The ChatGPT plan, e.g. `plus` or `pro`.
+    pub plan: Option<String>,
+    /// The organization the login bills to unless `org_id` picks another.
+    pub org_id: Option<String>,
+    /// Every organization the login belongs to, when the token lists them.
+    pub organizations: Vec<String>,
+}
+
+impl AccountInfo {
+    /// Whether requests of this login may be billed to `org_id`. A login
+    /// whose token does not list its organizations is given the benefit of
+    /// the doubt.
+    pub fn check_org(&self, org_id: &str) -> Result<(), String> {
+        let mut known: Vec<&str> = self.organizations.iter().map(String::as_str).collect();
+        if let Some(default_org) = self.org_id.as_deref().filter(|org| !known.contains(org)) {
+            known.push(default_org);
+        }
+        if known.is_empty() || known.contains(&org_id) {
+            return Ok(());
+        }
+        Err(format!(
+            "`{org_id}` is not an organization of this login (it belongs to {})",
+            known.join(", ")
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct IdClaims {
+    email: Option<String>,
+    #[serde(rename = "https://api.openai.com/auth")]
+    auth: Option<IdAuthClaims>,
+}
+
+#[derive(Deserialize)]
+struct IdAuthClaims {
+    organization_id: Option<String>,
+    #[serde(default)]
+    organizations: Vec<Organization>,
+}
+
+#[derive(Deserialize)]
+struct Organization {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct AccessClaims {
+    #[serde(rename = "https://api.openai.com/auth")]
+    auth: Option<AuthClaims>,
+}
+
+#[derive(Deserialize)]
+struct AuthClaims {
+    chatgpt_plan_type: Option<String>,
+}
+
+/// Read the email, plan and organizations of the login stored for `account`.
+/// The tokens are not verified; this is only for display and sanity checks.
+pub fn read_account_info(codex_home: &Path, account: Option<&str>) -> std::io::Result<AccountInfo> {
+    let contents = std::fs::read_to_string(auth_file_path(codex_home, account)?)?;
+    let auth_dot_json: AuthDotJson = serde_json::from_str(&contents)?;
+    let (email, id_auth) = match jwt_claims::<IdClaims>(&auth_dot_json.tokens.id_token) {
+        Some(claims) => (claims.email, claims.auth),
+        None => (None, None),
+    };
+    let (org_id, organizations) = match id_auth {
+        Some(auth) => (
+            auth.organization_id,
+            auth.organizations.into_iter().map(|org| org.id).collect(),
+        ),
+        None => (None, Vec::new()),
+    };
+    let plan = jwt_claims::<AccessClaims>(&auth_dot_json.tokens.access_token)
+        .and_then(|c| c.auth)
+        .and_then(|auth| auth.chatgpt_plan_type);
+    Ok(AccountInfo {
+        email,
+        plan,
+        org_id,
+        organizations,
+    })
+}
+
+/// The payload of a JWT, without checking its signature.
+fn jwt_claims<T: for<'de> Deserialize<'de>>(jwt: &str) -> Option<T> {
+    let payload = jwt.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn jwt(claims: serde_json::Value) -> String {
+        format!("e30.{}.sig", URL_SAFE_NO_PAD.encode(claims.to_string()))
+    }
+
+    #[test]
+    fn reads_named_accounts_and_their_claims() {
+        let codex_home = tempfile::tempdir().unwrap();
+        let auth_dir = codex_home.path().join(ACCOUNTS_DIR);
+        std::fs::create_dir(&auth_dir).unwrap();
+        let auth_json = serde_json::json!({
+            "OPENAI_API_KEY": "sk-work",
+            "tokens": {
+                "id_token": jwt(serde_json::json!({
+                    "email": "me@work.example",
+                    "https://api.openai.com/auth": {
+                        "organization_id": "org-work",
+                        "organizations": [{"id": "org-work"}, {"id": "org-team"}],
+                    },
+                })),
+                "access_token": jwt(serde_json::json!({
+                    "https://api.openai.com/auth": {"chatgpt_plan_type": "pro"}
+                })),
+                "refresh_token": "r",
+            },
+            "last_refresh": "2025-01-01T00:00:00Z",
+        });
+        std::fs::write(auth_dir.join("work.json"), auth_json.to_string()).unwrap();
+        std::fs::write(auth_dir.join("personal.json"), "{}").unwrap();
+        std::fs::write(auth_dir.join("notes.txt"), "").unwrap();
+
+        assert_eq!(list_accounts(codex_home.path()), vec!["personal", "work"]);
+        assert_eq!(
+            read_account_info(codex_home.path(), Some("work")).unwrap(),
+            AccountInfo {
+                email: Some("me@work.example".to_string()),
+                plan: Some("pro".to_string()),
+                org_id: Some("org-work".to_string()),
+                organizations: vec!["org-work".to_string(), "org-team".to_string()],
+            }
+        );
+        assert!(read_account_info(codex_home.path(), None).is_err());
+    }
+
+    #[test]
+    fn org_must_be_one_of_the_logins() {
+        let info = AccountInfo {
+            org_id: Some("org-personal".to_string()),
+            organizations: vec!["org-personal".to_string(), "org-team".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(info.check_org("org-team"), Ok(()));
+        assert_eq!(
+            info.check_org("org-other"),
+            Err(
+                "`org-other` is not an organization of this login (it belongs to org-personal, org-team)"
+                    .to_string()
+            )
+        );
+        assert_eq!(AccountInfo::default().check_org("org-other"), Ok(()));
+    }
+
+    #[test]
+    fn account_names_are_plain_file_names() {
+        assert!(is_valid_account_name("work-2"));
+        assert!(!is_valid_account_name("../auth"));
+        assert!(!is_valid_account_name(".hidden"));
+        assert!(!is_valid_account_name(""));
+        assert!(!is_valid_account_name(DEFAULT_ACCOUNT));
+
+        let codex_home = Path::new("/home/me/.codex");
+        assert_eq!(
+            auth_file_path(codex_home, Some("work")).unwrap(),
+            codex_home.join("auth/work.json")
+        );
+        assert_eq!(
+            auth_file_path(codex_home, None).unwrap(),
+            codex_home.join("auth.json")
+        );
+        for name in ["/repo/creds", "../auth", "a/b", DEFAULT_ACCOUNT] {
+            let err = auth_file_path(codex_home, Some(name)).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+}
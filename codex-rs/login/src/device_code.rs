@@ -0,0 +1,231 @@
+//! Device authorization grant (RFC 8628) for machines without a browser:
+//! the user opens a URL on any other device and enters a short code while
+//! this process polls the token endpoint.
+
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::AuthDotJson;
+use crate::CLIENT_ID;
+use crate::ISSUER;
+use crate::TokenData;
+use crate::auth_file_path;
+use crate::write_auth_json;
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Added to the polling interval each time the server answers `slow_down`.
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+/// A pending device authorization, to be shown to the user.
+#[derive(Debug, Deserialize)]
+pub struct DeviceCode {
+    device_code: String,
+    /// The code the user enters at `verification_uri`.
+    pub user_code: String,
+    pub verification_uri: String,
+    /// `verification_uri` with the code filled in, if the server offers one.
+    pub verification_uri_complete: Option<String>,
+    /// Seconds until the codes expire.
+    pub expires_in: u64,
+    /// Seconds to wait between polls.
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// Start a device authorization with the auth server.
+pub async fn request_device_code() -> std::io::Result<DeviceCode> {
+    let response = reqwest::Client::new()
+        .post(format!("{ISSUER}/oauth/device/code"))
+        .form(&[
+            ("client_id", CLIENT_ID),
+            ("scope", "openid profile email offline_access"),
+        ])
+        .send()
+        .await
+        .map_err(std::io::Error::other)?;
+    if !response.status().is_success() {
+        return Err(std::io::Error::other(format!(
+            "Failed to request a device code: {}",
+            response.status()
+        )));
+    }
+    response.json().await.map_err(std::io::Error::other)
+}
+
+/// Poll until the user has approved `device_code`, then exchange the tokens
+/// for an API key, write the auth file of `account` and return the key.
+pub async fn complete_device_code_login(
+    codex_home: &Path,
+    account: Option<&str>,
+    device_code: &DeviceCode,
+) -> std::io::Result<String> {
+    let auth_path = auth_file_path(codex_home, account)?;
+    let tokens = poll_for_tokens(device_code).await?;
+    let api_key = exchange_for_api_key(&tokens.id_token).await?;
+
+    if let Some(dir) = auth_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let auth_dot_json = AuthDotJson {
+        openai_api_key: api_key,
+        tokens,
+        last_refresh: Utc::now(),
+    };
+    write_auth_json(&auth_path, &auth_dot_json)?;
+    Ok(auth_dot_json.openai_api_key)
+}
+
+/// What a poll of the token endpoint tells us to do next.
+#[derive(Debug, PartialEq)]
+enum PollOutcome {
+    Approved(TokenData),
+    /// The user has not approved the request yet.
+    Pending,
+    /// Polling too fast; wait longer between polls.
+    SlowDown,
+    Failed(String),
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+fn parse_poll_response(success: bool, body: &str) -> PollOutcome {
+    if success {
+        return match serde_json::from_str::<TokenData>(body) {
+            Ok(tokens) => PollOutcome::Approved(tokens),
+            Err(err) => PollOutcome::Failed(format!("unexpected token response: {err}")),
+        };
+    }
+    match serde_json::from_str::<ErrorResponse>(body) {
+        Ok(response) => match response.error.as_str() {
+            "authorization_pending" => PollOutcome::Pending,
+            "slow_down" => PollOutcome::SlowDown,
+            "access_denied" => PollOutcome::Failed("the login was denied".to_string()),
+            "expired_token" => {
+                PollOutcome::Failed("the code expired before it was entered".to_string())
+            }
+            error => PollOutcome::Failed(
+                response
+                    .error_description
+                    .unwrap_or_else(|| error.to_string()),
+            ),
+        },
+        Err(_) => PollOutcome::Failed(format!("unexpected response: {body}")),
+    }
+}
+
+async fn poll_for_tokens(device_code: &DeviceCode) -> std::io::Result<TokenData> {
+    let client = reqwest::Client::new();
+    let deadline = Instant::now() + Duration::from_secs(device_code.expires_in);
+    let mut interval = Duration::from_secs(device_code.interval);
+    loop {
+        tokio::time::sleep(interval).await;
+        if Instant::now() > deadline {
+            return Err(std::io::Error::other(
+                "the code expired before it was entered",
+            ));
+        }
+        let response = client
+            .post(format!("{ISSUER}/oauth/token"))
+            .form(&[
+                ("grant_type", DEVICE_CODE_GRANT_TYPE),
+                ("device_code", device_code.device_code.as_str()),
+                ("client_id", CLIENT_ID),
+            ])
+            .send()
+            .await
+            .map_err(std::io::Error::other)?;
+        let success = response.status().is_success();
+        let body = response.text().await.map_err(std::io::Error::other)?;
+        match parse_poll_response(success, &body) {
+            PollOutcome::Approved(tokens) => return Ok(tokens),
+            PollOutcome::Pending => {}
+            PollOutcome::SlowDown => interval += SLOW_DOWN_STEP,
+            PollOutcome::Failed(message) => return Err(std::io::Error::other(message)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ExchangeResponse {
+    access_token: String,
+}
+
+/// Trade the ID token for an API key, as the browser login does.
+async fn exchange_for_api_key(id_token: &str) -> std::io::Result<String> {
+    let name = format!(
+        "Codex CLI [auto-generated] ({}) [device]",
+        Utc::now().format("%Y-%m-%d")
+    );
+    let response = reqwest::Client::new()
+        .post(format!("{ISSUER}/oauth/token"))
+        .form(&[
+            (
+                "grant_type",
+                "urn:ietf:params:oauth:grant-type:token-exchange",
+            ),
+            ("client_id", CLIENT_ID),
+            ("requested_token", "openai-api-key"),
+            ("subject_token", id_token),
+            (
+                "subject_token_type",
+                "urn:ietf:params:oauth:token-type:id_token",
+            ),
+            ("name", name.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(std::io::Error::other)?;
+    if !response.status().is_success() {
+        return Err(std::io::Error::other(format!(
+            "Failed to obtain an API key: {}",
+            response.status()
+        )));
+    }
+    let exchange: ExchangeResponse = response.json().await.map_err(std::io::Error::other)?;
+    Ok(exchange.access_token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interprets_poll_responses() {
+        assert_eq!(
+            parse_poll_response(false, r#"{"error": "authorization_pending"}"#),
+            PollOutcome::Pending
+        );
+        assert_eq!(
+            parse_poll_response(false, r#"{"error": "slow_down"}"#),
+            PollOutcome::SlowDown
+        );
+        assert_eq!(
+            parse_poll_response(false, r#"{"error": "expired_token"}"#),
+            PollOutcome::Failed("the code expired before it was entered".to_string())
+        );
+        assert_eq!(
+            parse_poll_response(
+                true,
+                r#"{"id_token": "a.b.c", "access_token": "d.e.f", "refresh_token": "r", "token_type": "Bearer"}"#
+            ),
+            PollOutcome::Approved(TokenData {
+                id_token: "a.b.c".to_string(),
+                access_token: "d.e.f".to_string(),
+                refresh_token: "r".to_string(),
+            })
+        );
+    }
+}
@@ -11,27 +11,44 @@ use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
+mod accounts;
+mod device_code;
+
+pub use accounts::AccountInfo;
+pub use accounts::DEFAULT_ACCOUNT;
+pub use accounts::auth_file_path;
+pub use accounts::is_valid_account_name;
+pub use accounts::list_accounts;
+pub use accounts::read_account_info;
+pub use device_code::DeviceCode;
+pub use device_code::complete_device_code_login;
+pub use device_code::request_device_code;
+
 const SOURCE_FOR_PYTHON_SERVER: &str = include_str!("./login_with_chatgpt.py");
 
+const ISSUER: &str = "https://auth.openai.com";
+
 const CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
 
 /// Run `python3 -c {{SOURCE_FOR_PYTHON_SERVER}}` with the CODEX_HOME
 /// environment variable set to the provided `codex_home` path. If the
-/// subprocess exits 0, read the OPENAI_API_KEY property out of
-/// CODEX_HOME/auth.json and return Ok(OPENAI_API_KEY). Otherwise, return Err
-/// with any information from the subprocess.
+/// subprocess exits 0, read the OPENAI_API_KEY property out of the auth file
+/// of `account` (see [`auth_file_path`]) and return Ok(OPENAI_API_KEY).
+/// Otherwise, return Err with any information from the subprocess.
 ///
 /// If `capture_output` is true, the subprocess's output will be captured and
 /// recorded in memory. Otherwise, the subprocess's output will be sent to the
 /// current process's stdout/stderr.
 pub async fn login_with_chatgpt(
     codex_home: &Path,
+    account: Option<&str>,
     capture_output: bool,
 ) -> std::io::Result<String> {
     let child = Command::new("python3")
         .arg("-c")
         .arg(SOURCE_FOR_PYTHON_SERVER)
         .env("CODEX_HOME", codex_home)
+        .env("CODEX_AUTH_FILE", auth_file_path(codex_home, account)?)
         .stdin(Stdio::null())
         .stdout(if capture_output {
             Stdio::piped()
@@ -47,7 +64,7 @@ pub async fn login_with_chatgpt(
 
     let output = child.wait_with_output().await?;
     if output.status.success() {
-        try_read_openai_api_key(codex_home).await
+        try_read_openai_api_key(codex_home, account).await
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         Err(std::io::Error::other(format!(
@@ -56,10 +73,14 @@ pub async fn login_with_chatgpt(
     }
 }
 
-/// Attempt to read the `OPENAI_API_KEY` from the `auth.json` file in the given
-/// `CODEX_HOME` directory, refreshing it, if necessary.
-pub async fn try_read_openai_api_key(codex_home: &Path) -> std::io::Result<String> {
-    let auth_path = codex_home.join("auth.json");
+/// Attempt to read the `OPENAI_API_KEY` from the auth file of `account` (the
+/// default `auth.json` when `None`) in the given `CODEX_HOME` directory,
+/// refreshing it, if necessary.
+pub async fn try_read_openai_api_key(
+    codex_home: &Path,
+    account: Option<&str>,
+) -> std::io::Result<String> {
+    let auth_path = auth_file_path(codex_home, account)?;
     let mut file = std::fs::File::open(&auth_path)?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
@@ -73,27 +94,28 @@ pub async fn try_read_openai_api_key(codex_home: &Path) -> std::io::Result<Strin
             auth_dot_json.tokens.refresh_token = refresh_token;
         }
         auth_dot_json.last_refresh = Utc::now();
-
-        let mut options = OpenOptions::new();
-        options.truncate(true).write(true).create(true);
-        #[cfg(unix)]
-        {
-            options.mode(0o600);
-        }
-
-        let json_data = serde_json::to_string(&auth_dot_json)?;
-        {
-            let mut file = options.open(&auth_path)?;
-            file.write_all(json_data.as_bytes())?;
-            file.flush()?;
-        }
-
+        write_auth_json(&auth_path, &auth_dot_json)?;
         Ok(auth_dot_json.openai_api_key)
     } else {
         Ok(auth_dot_json.openai_api_key)
     }
 }
 
+/// Write `auth.json`, readable only by the user.
+fn write_auth_json(auth_path: &Path, auth_dot_json: &AuthDotJson) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.truncate(true).write(true).create(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+    }
+
+    let json_data = serde_json::to_string(auth_dot_json)?;
+    let mut file = options.open(auth_path)?;
+    file.write_all(json_data.as_bytes())?;
+    file.flush()
+}
+
 fn is_expired(auth_dot_json: &AuthDotJson) -> bool {
     let last_refresh = auth_dot_json.last_refresh;
     last_refresh < Utc::now() - chrono::Duration::days(28)
@@ -109,7 +131,7 @@ async fn try_refresh_token(auth_dot_json: &AuthDotJson) -> std::io::Result<Refre
 
     let client = reqwest::Client::new();
     let response = client
-        .post("https://auth.openai.com/oauth/token")
+        .post(format!("{ISSUER}/oauth/token"))
         .header("Content-Type", "application/json")
         .json(&refresh_request)
         .send()
@@ -155,13 +177,12 @@ struct AuthDotJson {
     last_refresh: DateTime<Utc>,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 struct TokenData {
     /// This is a JWT.
     id_token: String,
 
     /// This is a JWT.
-    #[allow(dead_code)]
     access_token: String,
 
     refresh_token: String,
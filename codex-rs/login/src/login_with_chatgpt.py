@@ -3,7 +3,8 @@
 - Listens on 127.0.0.1:1455
 - Opens http://localhost:1455/auth/callback in the browser
 - If the user successfully navigates the auth flow,
-  $CODEX_HOME/auth.json will be written with the API key.
+  $CODEX_HOME/auth.json (or $CODEX_AUTH_FILE, when set) will be written with
+  the API key.
 - User will be redirected to http://localhost:1455/success upon success.
 
 The script should exit with a non-zero code if the user fails to navigate the
@@ -355,20 +356,27 @@ class _ApiKeyHTTPHandler(http.server.BaseHTTPRequestHandler):
         threading.Thread(target=self.server.shutdown, daemon=True).start()
 
 
+def _auth_file_path(codex_home: str) -> str:
+    """$CODEX_AUTH_FILE, set when logging in to a named account, or else
+    $CODEX_HOME/auth.json."""
+    return os.environ.get("CODEX_AUTH_FILE") or os.path.join(codex_home, "auth.json")
+
+
 def _write_auth_file(*, auth: AuthBundle, codex_home: str) -> bool:
-    """Persist *api_key* to $CODEX_HOME/auth.json.
+    """Persist *api_key* to the auth file (see `_auth_file_path`).
 
     Returns True on success, False otherwise.  Any error is printed to
     *stderr* so that the Rust layer can surface the problem.
     """
-    if not os.path.isdir(codex_home):
+    auth_path = _auth_file_path(codex_home)
+    auth_dir = os.path.dirname(auth_path)
+    if not os.path.isdir(auth_dir):
         try:
-            os.makedirs(codex_home, exist_ok=True)
+            os.makedirs(auth_dir, exist_ok=True)
         except Exception as exc:  # pragma: no cover – unlikely
-            eprint(f"ERROR: unable to create CODEX_HOME directory: {exc}")
+            eprint(f"ERROR: unable to create the auth directory: {exc}")
             return False
 
-    auth_path = os.path.join(codex_home, "auth.json")
     auth_json_contents = {
         "OPENAI_API_KEY": auth.api_key,
         "tokens": {
@@ -491,8 +499,7 @@ def maybe_redeem_credits(
 
         # Update auth.json with new tokens.
         try:
-            auth_dir = codex_home
-            auth_path = os.path.join(auth_dir, "auth.json")
+            auth_path = _auth_file_path(codex_home)
             with open(auth_path, "r", encoding="utf-8") as fp:
                 existing = json.load(fp)
 
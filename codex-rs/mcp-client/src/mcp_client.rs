@@ -22,6 +22,9 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
@@ -30,11 +33,23 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressNotification;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::ProgressToken;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
@@ -45,6 +60,7 @@ use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tokio::time;
 use tracing::debug;
 use tracing::error;
@@ -58,6 +74,10 @@ const CHANNEL_CAPACITY: usize = 128;
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// `progressToken -> sender` of the requests that asked for progress
+/// notifications. The token is the ID of the request.
+type ProgressSenders = Arc<Mutex<HashMap<i64, mpsc::Sender<ProgressNotificationParams>>>>;
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
@@ -73,6 +93,13 @@ pub struct McpClient {
     /// to the originating caller.
     pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
 
+    /// Where to forward `notifications/progress` for in-flight requests.
+    progress: ProgressSenders,
+
+    /// Becomes `true` once the server's stdout is closed, i.e. the process
+    /// exited (or stopped talking to us).
+    exited: watch::Receiver<bool>,
+
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
 }
@@ -110,6 +137,8 @@ impl McpClient {
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
         let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let progress: ProgressSenders = Arc::new(Mutex::new(HashMap::new()));
+        let (exited_tx, exited) = watch::channel(false);
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -143,6 +172,7 @@ impl McpClient {
         // STDOUT and dispatches responses to the pending map.
         let reader_handle = {
             let pending = pending.clone();
+            let progress = progress.clone();
             let mut lines = BufReader::new(stdout).lines();
 
             tokio::spawn(async move {
@@ -155,8 +185,15 @@ impl McpClient {
                         Ok(JSONRPCMessage::Error(err)) => {
                             Self::dispatch_error(err, &pending).await;
                         }
+                        Ok(JSONRPCMessage::Notification(JSONRPCNotification {
+                            method,
+                            params,
+                            ..
+                        })) if method == ProgressNotification::METHOD => {
+                            Self::dispatch_progress(params, &progress).await;
+                        }
                         Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
+                            // Other server-initiated notifications are only logged.
                             info!("<- notification: {}", line);
                         }
                         Ok(other) => {
@@ -169,6 +206,11 @@ impl McpClient {
                         }
                     }
                 }
+
+                // The server is gone: fail the requests still waiting for a
+                // reply instead of letting them run into their timeouts.
+                pending.lock().await.clear();
+                let _ = exited_tx.send(true);
             })
         };
 
@@ -182,6 +224,8 @@ impl McpClient {
             child,
             outgoing_tx,
             pending,
+            progress,
+            exited,
             id_counter: AtomicI64::new(1),
         })
     }
@@ -196,6 +240,24 @@ impl McpClient {
         params: R::Params,
         timeout: Option<Duration>,
     ) -> Result<R::Result>
+    where
+        R: ModelContextProtocolRequest,
+        R::Params: Serialize,
+        R::Result: DeserializeOwned,
+    {
+        self.send_request_with_progress::<R>(params, timeout, None)
+            .await
+    }
+
+    /// Like [`send_request`](Self::send_request), but asks the server for
+    /// progress notifications (`_meta.progressToken`) and forwards them to
+    /// `progress_tx` until the request completes.
+    pub async fn send_request_with_progress<R>(
+        &self,
+        params: R::Params,
+        timeout: Option<Duration>,
+        progress_tx: Option<mpsc::Sender<ProgressNotificationParams>>,
+    ) -> Result<R::Result>
     where
         R: ModelContextProtocolRequest,
         R::Params: Serialize,
@@ -203,19 +265,47 @@ impl McpClient {
     {
         // Create a new unique ID.
         let id = self.id_counter.fetch_add(1, Ordering::SeqCst);
-        let request_id = RequestId::Integer(id);
 
         // Serialize params -> JSON. For many request types `Params` is
         // `Option<T>` and `None` should be encoded as *absence* of the field.
-        let params_json = serde_json::to_value(&params)?;
+        let mut params_json = serde_json::to_value(&params)?;
+        if let Some(progress_tx) = progress_tx {
+            if params_json.is_null() {
+                params_json = serde_json::json!({});
+            }
+            if let Some(object) = params_json.as_object_mut() {
+                object.insert(
+                    "_meta".to_string(),
+                    serde_json::json!({ "progressToken": id }),
+                );
+                self.progress.lock().await.insert(id, progress_tx);
+            }
+        }
         let params_field = if params_json.is_null() {
             None
         } else {
             Some(params_json)
         };
 
+        let result = self
+            .send_request_with_id::<R>(id, params_field, timeout)
+            .await;
+        self.progress.lock().await.remove(&id);
+        result
+    }
+
+    async fn send_request_with_id<R>(
+        &self,
+        id: i64,
+        params_field: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<R::Result>
+    where
+        R: ModelContextProtocolRequest,
+        R::Result: DeserializeOwned,
+    {
         let jsonrpc_request = JSONRPCRequest {
-            id: request_id.clone(),
+            id: RequestId::Integer(id),
             jsonrpc: JSONRPC_VERSION.to_string(),
             method: R::METHOD.to_string(),
             params: params_field,
@@ -343,10 +433,104 @@ impl McpClient {
         name: String,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+    ) -> Result<mcp_types::CallToolResult> {
+        self.call_tool_with_progress(name, arguments, timeout, None)
+            .await
+    }
+
+    /// `tools/call` that forwards the server's progress notifications for
+    /// the call to `progress_tx`.
+    pub async fn call_tool_with_progress(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+        progress_tx: Option<mpsc::Sender<ProgressNotificationParams>>,
     ) -> Result<mcp_types::CallToolResult> {
         let params = CallToolRequestParams { name, arguments };
         debug!("MCP tool call: {params:?}");
-        self.send_request::<CallToolRequest>(params, timeout).await
+        self.send_request_with_progress::<CallToolRequest>(params, timeout, progress_tx)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        debug!("MCP resource read: {params:?}");
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        debug!("MCP prompt get: {params:?}");
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
+    /// Resolves once the server process has exited. Returns immediately if it
+    /// already has.
+    pub fn wait_for_exit(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut exited = self.exited.clone();
+        async move {
+            // An error means the reader task is gone, which also means the
+            // server is.
+            let _ = exited.wait_for(|exited| *exited).await;
+        }
+    }
+
+    /// Internal helper: route a `notifications/progress` to the sender of the
+    /// request it belongs to.
+    async fn dispatch_progress(params: Option<serde_json::Value>, progress: &ProgressSenders) {
+        let params = match params.map(serde_json::from_value::<ProgressNotificationParams>) {
+            Some(Ok(params)) => params,
+            Some(Err(e)) => {
+                warn!("invalid progress notification: {e}");
+                return;
+            }
+            None => {
+                warn!("progress notification without params");
+                return;
+            }
+        };
+        let ProgressToken::Integer(id) = params.progress_token else {
+            warn!("progress notification with unknown token");
+            return;
+        };
+        if let Some(tx) = progress.lock().await.get(&id) {
+            // Progress is best-effort: drop updates the consumer is not
+            // keeping up with rather than stall the reader.
+            let _ = tx.try_send(params);
+        }
     }
 
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
@@ -137,6 +137,7 @@ pub(crate) fn create_tool_for_codex_tool_call_param() -> Tool {
                 .to_string(),
         ),
         annotations: None,
+        output_schema: None,
     }
 }
 
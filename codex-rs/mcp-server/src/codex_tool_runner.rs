@@ -20,7 +20,7 @@ use mcp_types::TextContent;
 use tokio::sync::mpsc::Sender;
 
 /// Convert a Codex [`Event`] to an MCP notification.
-fn codex_event_to_notification(event: &Event) -> JSONRPCMessage {
+pub(crate) fn codex_event_to_notification(event: &Event) -> JSONRPCMessage {
     #[expect(clippy::expect_used)]
     JSONRPCMessage::Notification(mcp_types::JSONRPCNotification {
         jsonrpc: JSONRPC_VERSION.into(),
@@ -49,6 +49,7 @@ pub async fn run_codex_tool_session(
                     annotations: None,
                 })],
                 is_error: Some(true),
+                structured_content: None,
             };
             let _ = outgoing
                 .send(JSONRPCMessage::Response(JSONRPCResponse {
@@ -98,6 +99,7 @@ pub async fn run_codex_tool_session(
                                 annotations: None,
                             })],
                             is_error: None,
+                            structured_content: None,
                         };
                         let _ = outgoing
                             .send(JSONRPCMessage::Response(JSONRPCResponse {
@@ -116,6 +118,7 @@ pub async fn run_codex_tool_session(
                                 annotations: None,
                             })],
                             is_error: None,
+                            structured_content: None,
                         };
                         let _ = outgoing
                             .send(JSONRPCMessage::Response(JSONRPCResponse {
@@ -137,6 +140,7 @@ pub async fn run_codex_tool_session(
                                     annotations: None,
                                 })],
                                 is_error: None,
+                                structured_content: None,
                             }
                         } else {
                             CallToolResult {
@@ -146,6 +150,7 @@ pub async fn run_codex_tool_session(
                                     annotations: None,
                                 })],
                                 is_error: None,
+                                structured_content: None,
                             }
                         };
                         let _ = outgoing
@@ -163,14 +168,32 @@ pub async fn run_codex_tool_session(
                     EventMsg::Error(_)
                     | EventMsg::TaskStarted
                     | EventMsg::AgentReasoning(_)
+                    | EventMsg::McpStartup(_)
+                    | EventMsg::CustomPromptsUpdated(_)
+                    | EventMsg::ProjectDocsLoaded(_)
                     | EventMsg::McpToolCallBegin(_)
+                    | EventMsg::McpToolCallProgress(_)
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::ExecCommandBegin(_)
+                    | EventMsg::ExecCommandOutputDelta(_)
                     | EventMsg::ExecCommandEnd(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
-                    | EventMsg::GetHistoryEntryResponse(_) => {
+                    | EventMsg::GetHistoryEntryResponse(_)
+                    | EventMsg::SearchHistoryResponse(_)
+                    | EventMsg::TokenCount(_)
+                    | EventMsg::ContextCompacted(_)
+                    | EventMsg::BackgroundProcessesUpdated(_)
+                    | EventMsg::TurnReverted(_)
+                    | EventMsg::UserGuidanceDelivered(_)
+                    | EventMsg::SubAgentBegin(_)
+                    | EventMsg::SubAgentMessage(_)
+                    | EventMsg::SubAgentEnd(_)
+                    | EventMsg::PlanUpdate(_)
+                    | EventMsg::ReviewFinding(_)
+                    | EventMsg::McpPrompt(_)
+                    | EventMsg::ProjectDocProposal(_) => {
                         // For now, we do not do anything extra for these
                         // events. Note that
                         // send(codex_event_to_notification(&event)) above has
@@ -188,6 +211,7 @@ pub async fn run_codex_tool_session(
                         annotations: None,
                     })],
                     is_error: Some(true),
+                    structured_content: None,
                 };
                 let _ = outgoing
                     .send(JSONRPCMessage::Response(JSONRPCResponse {
@@ -1,13 +1,27 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use crate::codex_tool_config::CodexToolCallParam;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
+use crate::codex_tool_runner::codex_event_to_notification;
 
 use codex_core::config::Config as CodexConfig;
+use codex_core::config::ConfigOverrides;
+use codex_core::custom_prompts::discover_prompts;
+use codex_core::custom_prompts::expand_prompt_with_values;
+use codex_core::custom_prompts::prompt_parameters;
+use codex_core::protocol::CustomPrompt;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::sampling::SamplingSession;
 use mcp_types::CallToolRequestParams;
 use mcp_types::CallToolResult;
 use mcp_types::CallToolResultContent;
 use mcp_types::ClientRequest;
+use mcp_types::CreateMessageRequest;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::JSONRPC_VERSION;
 use mcp_types::JSONRPCBatchRequest;
 use mcp_types::JSONRPCBatchResponse;
@@ -17,9 +31,16 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsResult;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::Prompt;
+use mcp_types::PromptArgument;
+use mcp_types::PromptMessage;
+use mcp_types::PromptMessageContent;
 use mcp_types::RequestId;
+use mcp_types::Role;
+use mcp_types::ServerCapabilitiesPrompts;
 use mcp_types::ServerCapabilitiesTools;
 use mcp_types::ServerNotification;
 use mcp_types::TextContent;
@@ -31,6 +52,13 @@ pub(crate) struct MessageProcessor {
     outgoing: mpsc::Sender<JSONRPCMessage>,
     initialized: bool,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    /// `clientInfo.name` from `initialize`.
+    client_name: String,
+    /// Answers `sampling/createMessage`; `None` if the config failed to load.
+    sampling: Option<Arc<SamplingSession>>,
+    /// `(codex_home, cwd)` of the config, where the custom prompts offered
+    /// by `prompts/list` are discovered; `None` if the config failed to load.
+    prompt_roots: Option<(PathBuf, PathBuf)>,
 }
 
 impl MessageProcessor {
@@ -44,6 +72,9 @@ impl MessageProcessor {
             outgoing,
             initialized: false,
             codex_linux_sandbox_exe,
+            client_name: String::new(),
+            sampling: None,
+            prompt_roots: None,
         }
     }
 
@@ -51,6 +82,13 @@ impl MessageProcessor {
         // Hold on to the ID so we can respond.
         let request_id = request.id.clone();
 
+        // Sampling is normally requested by servers, so `ClientRequest` has
+        // no variant for it.
+        if request.method == CreateMessageRequest::METHOD {
+            self.handle_create_message(request_id, request.params);
+            return;
+        }
+
         let client_request = match ClientRequest::try_from(request) {
             Ok(client_request) => client_request,
             Err(e) => {
@@ -83,10 +121,10 @@ impl MessageProcessor {
                 self.handle_unsubscribe(params);
             }
             ClientRequest::ListPromptsRequest(params) => {
-                self.handle_list_prompts(params);
+                self.handle_list_prompts(request_id, params);
             }
             ClientRequest::GetPromptRequest(params) => {
-                self.handle_get_prompt(params);
+                self.handle_get_prompt(request_id, params);
             }
             ClientRequest::ListToolsRequest(params) => {
                 self.handle_list_tools(request_id, params);
@@ -206,14 +244,32 @@ impl MessageProcessor {
         }
 
         self.initialized = true;
+        self.client_name = params.client_info.name.clone();
+
+        let overrides = ConfigOverrides {
+            codex_linux_sandbox_exe: self.codex_linux_sandbox_exe.clone(),
+            ..Default::default()
+        };
+        match CodexConfig::load_with_cli_overrides(Vec::new(), overrides) {
+            Ok(config) => {
+                if config.mcp_sampling.enabled {
+                    self.sampling = Some(Arc::new(SamplingSession::new(&config)));
+                }
+                self.prompt_roots = Some((config.codex_home.clone(), config.cwd.clone()));
+            }
+            Err(e) => tracing::warn!("Failed to load config for sampling and prompts: {e}"),
+        }
 
         // Build a minimal InitializeResult. Fill with placeholders.
         let result = mcp_types::InitializeResult {
             capabilities: mcp_types::ServerCapabilities {
                 completions: None,
-                experimental: None,
+                experimental: self.sampling.as_ref().map(|_| json!({ "sampling": {} })),
                 logging: None,
-                prompts: None,
+                prompts: self
+                    .prompt_roots
+                    .as_ref()
+                    .map(|_| ServerCapabilitiesPrompts { list_changed: None }),
                 resources: None,
                 tools: Some(ServerCapabilitiesTools {
                     list_changed: Some(true),
@@ -247,6 +303,83 @@ impl MessageProcessor {
         }
     }
 
+    fn send_error(&self, id: RequestId, code: i64, message: String) {
+        let error = JSONRPCMessage::Error(JSONRPCError {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id,
+            error: JSONRPCErrorError {
+                code,
+                message,
+                data: None,
+            },
+        });
+        if let Err(e) = self.outgoing.try_send(error) {
+            tracing::error!("Failed to send error: {e}");
+        }
+    }
+
+    /// Answers with a completion of Codex's configured model. The token usage
+    /// is reported as a `codex/event` `token_count` notification.
+    fn handle_create_message(&self, id: RequestId, params: Option<serde_json::Value>) {
+        tracing::info!("sampling/createMessage -> params: {:?}", params);
+        let params = match params.map(serde_json::from_value::<CreateMessageRequestParams>) {
+            Some(Ok(params)) => params,
+            Some(Err(e)) => {
+                self.send_error(id, -32602, format!("Invalid params: {e}"));
+                return;
+            }
+            None => {
+                self.send_error(id, -32602, "Missing params".to_string());
+                return;
+            }
+        };
+        let Some(sampling) = self.sampling.clone() else {
+            self.send_error(
+                id,
+                -32601,
+                "sampling is not enabled in [mcp_sampling]".to_string(),
+            );
+            return;
+        };
+        if let Err(message) = sampling.check_allowed(&self.client_name) {
+            self.send_error(id, -32600, message);
+            return;
+        }
+
+        let outgoing = self.outgoing.clone();
+        task::spawn(async move {
+            let message = match sampling.create_message(params).await {
+                Ok((result, token_count)) => {
+                    if let Some(token_count) = token_count {
+                        let event = Event {
+                            id: match &id {
+                                RequestId::String(id) => id.clone(),
+                                RequestId::Integer(id) => id.to_string(),
+                            },
+                            msg: EventMsg::TokenCount(token_count),
+                        };
+                        let _ = outgoing.send(codex_event_to_notification(&event)).await;
+                    }
+                    JSONRPCMessage::Response(JSONRPCResponse {
+                        jsonrpc: JSONRPC_VERSION.into(),
+                        id,
+                        result: result.into(),
+                    })
+                }
+                Err(message) => JSONRPCMessage::Error(JSONRPCError {
+                    jsonrpc: JSONRPC_VERSION.into(),
+                    id,
+                    error: JSONRPCErrorError {
+                        code: -32603,
+                        message,
+                        data: None,
+                    },
+                }),
+            };
+            let _ = outgoing.send(message).await;
+        });
+    }
+
     fn handle_ping(
         &self,
         id: RequestId,
@@ -293,18 +426,88 @@ impl MessageProcessor {
         tracing::info!("resources/unsubscribe -> params: {:?}", params);
     }
 
+    fn custom_prompts(&self) -> Vec<CustomPrompt> {
+        self.prompt_roots
+            .as_ref()
+            .map(|(codex_home, cwd)| discover_prompts(codex_home, cwd))
+            .unwrap_or_default()
+    }
+
+    /// Lists the custom prompts, the same ones the TUI offers as slash
+    /// commands. Their arguments are named as in
+    /// [`prompt_parameters`]: `1` for `$1`, `base` for `${base}`.
     fn handle_list_prompts(
         &self,
+        id: RequestId,
         params: <mcp_types::ListPromptsRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("prompts/list -> params: {:?}", params);
+        let prompts = self
+            .custom_prompts()
+            .iter()
+            .map(|prompt| Prompt {
+                arguments: Some(
+                    prompt_parameters(prompt)
+                        .into_iter()
+                        .map(|(name, required)| PromptArgument {
+                            description: prompt.argument_hint.clone(),
+                            name,
+                            required: Some(required),
+                        })
+                        .collect(),
+                ),
+                description: prompt.description.clone(),
+                name: prompt.name.clone(),
+            })
+            .collect();
+        let result = ListPromptsResult {
+            next_cursor: None,
+            prompts,
+        };
+        self.send_response::<mcp_types::ListPromptsRequest>(id, result);
     }
 
+    /// Expands a custom prompt into a user message. The prompt's model and
+    /// approval overrides, its `` !`command` `` blocks and its `@path`
+    /// references only take effect when it is run in a Codex session.
     fn handle_get_prompt(
         &self,
+        id: RequestId,
         params: <mcp_types::GetPromptRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("prompts/get -> params: {:?}", params);
+        let prompts = self.custom_prompts();
+        let Some(prompt) = prompts.iter().find(|prompt| prompt.name == params.name) else {
+            self.send_error(id, -32602, format!("Unknown prompt '{}'", params.name));
+            return;
+        };
+        let values: HashMap<String, String> = match params.arguments {
+            Some(serde_json::Value::Object(arguments)) => arguments
+                .into_iter()
+                .map(|(name, value)| match value {
+                    serde_json::Value::String(value) => (name, value),
+                    value => (name, value.to_string()),
+                })
+                .collect(),
+            _ => HashMap::new(),
+        };
+        match expand_prompt_with_values(prompt, &values) {
+            Ok(text) => {
+                let result = GetPromptResult {
+                    description: prompt.description.clone(),
+                    messages: vec![PromptMessage {
+                        content: PromptMessageContent::TextContent(TextContent {
+                            r#type: "text".to_string(),
+                            text,
+                            annotations: None,
+                        }),
+                        role: Role::User,
+                    }],
+                };
+                self.send_response::<mcp_types::GetPromptRequest>(id, result);
+            }
+            Err(message) => self.send_error(id, -32602, message),
+        }
     }
 
     fn handle_list_tools(
@@ -339,6 +542,7 @@ impl MessageProcessor {
                     annotations: None,
                 })],
                 is_error: Some(true),
+                structured_content: None,
             };
             self.send_response::<mcp_types::CallToolRequest>(id, result);
             return;
@@ -358,6 +562,7 @@ impl MessageProcessor {
                                 annotations: None,
                             })],
                             is_error: Some(true),
+                            structured_content: None,
                         };
                         self.send_response::<mcp_types::CallToolRequest>(id, result);
                         return;
@@ -371,6 +576,7 @@ impl MessageProcessor {
                             annotations: None,
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     };
                     self.send_response::<mcp_types::CallToolRequest>(id, result);
                     return;
@@ -386,6 +592,7 @@ impl MessageProcessor {
                         annotations: None,
                     })],
                     is_error: Some(true),
+                    structured_content: None,
                 };
                 self.send_response::<mcp_types::CallToolRequest>(id, result);
                 return;
@@ -2,23 +2,38 @@ use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::chatwidget::ChatWidget;
 use crate::confirm_ctrl_d::ConfirmCtrlD;
+use crate::external_editor::edit_in_external_editor;
 use crate::git_warning_screen::GitWarningOutcome;
 use crate::git_warning_screen::GitWarningScreen;
+use crate::input_pause::InputPause;
+use crate::keymap::KeyAction;
+use crate::keymap::Keymap;
 use crate::login_screen::LoginScreen;
 use crate::mouse_capture::MouseCapture;
 use crate::scroll_event_helper::ScrollEventHelper;
 use crate::slash_command::SlashCommand;
+use crate::tabs::TabId;
+use crate::tabs::Tabs;
 use crate::tui;
 use codex_core::config::{Config, ConfigOverrides};
-use codex_core::protocol::{Event, EventMsg, Op, SessionConfiguredEvent};
+use codex_core::openai_api_key::set_openai_api_key;
+use codex_core::protocol::{Event, EventMsg, Op, SessionConfiguredEvent, TokenCountEvent};
+use codex_core::session_store::fork_session;
+use codex_core::session_store::load_session;
+use codex_login::DEFAULT_ACCOUNT;
+use codex_login::is_valid_account_name;
+use codex_login::try_read_openai_api_key;
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
+use crossterm::event::MouseButton;
 use crossterm::event::MouseEvent;
 use crossterm::event::MouseEventKind;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::channel;
+use std::time::Duration;
 use std::time::Instant;
 
 use codex_core::ResponseItem;
@@ -28,6 +43,10 @@ use std::io::{BufRead, BufReader};
 use std::process::{Command, Stdio};
 use std::thread;
 
+/// How long the input thread waits for input before checking whether an
+/// external editor wants the terminal.
+const INPUT_PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Top-level application state: which full-screen view is currently active.
 #[allow(clippy::large_enum_variant)]
 enum AppState<'a> {
@@ -54,9 +73,20 @@ pub(crate) struct App<'a> {
     /// Stored parameters needed to instantiate the ChatWidget later, e.g.,
     /// after dismissing the Git-repo warning.
     chat_args: Option<ChatWidgetArgs>,
-    session_id: Option<Uuid>,
+    /// The open conversations; the shown one's widget is in `app_state`.
+    tabs: Tabs<ChatWidget<'a>>,
     /// Tracks Ctrl+D confirmation state when enabled in config.
     confirm_ctrl_d: ConfirmCtrlD,
+
+    /// Bindings of the app-wide keys (interrupt, exit).
+    keymap: Keymap,
+
+    /// Paused while an external editor owns the terminal, to keep the input
+    /// thread from reading its keys.
+    input_pause: Arc<InputPause>,
+
+    /// This session's debug log, bundled by `/debug export`.
+    log_path: PathBuf,
 }
 
 /// Aggregate parameters needed to create a `ChatWidget`, as creation may be
@@ -111,6 +141,22 @@ fn parse_mount_remove_args(raw: &str) -> Result<std::path::PathBuf, String> {
     container.ok_or_else(|| "missing 'container' argument".to_string())
 }
 
+/// Parse raw argument string for `/debug export [path]`: the path to write
+/// the bundle to, if given.
+fn parse_debug_args(raw: &str) -> Result<Option<PathBuf>, String> {
+    let raw = raw.trim();
+    let (subcommand, path) = raw.split_once(char::is_whitespace).unwrap_or((raw, ""));
+    match subcommand {
+        "export" => {
+            let path = path.trim();
+            Ok((!path.is_empty()).then(|| PathBuf::from(path)))
+        }
+        other => Err(format!(
+            "unknown /debug command '{other}', expected /debug export [path]"
+        )),
+    }
+}
+
 /// Handle inline mount-add DSL event.
 fn handle_inline_mount_add(config: &mut Config, raw: &str) -> Result<(), String> {
     let (host, container, mode) = parse_mount_add_args(raw)?;
@@ -170,22 +216,37 @@ impl<'a> App<'a> {
         show_login_screen: bool,
         show_git_warning: bool,
         initial_images: Vec<std::path::PathBuf>,
+        log_path: PathBuf,
     ) -> Self {
         let (app_event_tx, app_event_rx) = channel();
         let app_event_tx = AppEventSender::new(app_event_tx);
         let scroll_event_helper = ScrollEventHelper::new(app_event_tx.clone());
 
         // Spawn a dedicated thread for reading the crossterm event loop and
-        // re-publishing the events as AppEvents, as appropriate.
+        // re-publishing the events as AppEvents, as appropriate. It stops
+        // reading while `input_pause` is paused so that an external editor
+        // gets the keys.
+        let input_pause = Arc::new(InputPause::default());
         {
             let app_event_tx = app_event_tx.clone();
+            let input_pause = Arc::clone(&input_pause);
             std::thread::spawn(move || {
-                while let Ok(event) = crossterm::event::read() {
+                loop {
+                    input_pause.wait_while_paused();
+                    match crossterm::event::poll(INPUT_PAUSE_POLL_INTERVAL) {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(_) => break,
+                    }
+                    let Ok(event) = crossterm::event::read() else {
+                        break;
+                    };
                     match event {
                         crossterm::event::Event::Key(key_event) => {
                             app_event_tx.send(AppEvent::KeyEvent(key_event));
                         }
-                        crossterm::event::Event::Resize(_, _) => {
+                        crossterm::event::Event::Resize(columns, _) => {
+                            app_event_tx.send(AppEvent::CodexOp(Op::SetTerminalWidth { columns }));
                             app_event_tx.send(AppEvent::Redraw);
                         }
                         crossterm::event::Event::Mouse(MouseEvent {
@@ -200,6 +261,25 @@ impl<'a> App<'a> {
                         }) => {
                             scroll_event_helper.scroll_down();
                         }
+                        crossterm::event::Event::Mouse(MouseEvent {
+                            kind: MouseEventKind::Down(MouseButton::Left),
+                            column,
+                            row,
+                            ..
+                        }) => {
+                            app_event_tx.send(AppEvent::MouseClick { column, row });
+                        }
+                        crossterm::event::Event::FocusGained => {
+                            app_event_tx.send(AppEvent::FocusChanged(true));
+                        }
+                        crossterm::event::Event::FocusLost => {
+                            app_event_tx.send(AppEvent::FocusChanged(false));
+                        }
+                        // Terminals paste nothing when the clipboard holds
+                        // an image instead of text.
+                        crossterm::event::Event::Paste(pasted) if pasted.is_empty() => {
+                            app_event_tx.send(AppEvent::PasteImage);
+                        }
                         crossterm::event::Event::Paste(pasted) => {
                             use crossterm::event::KeyModifiers;
 
@@ -222,9 +302,11 @@ impl<'a> App<'a> {
                         }
                     }
                 }
+                input_pause.stop_reading();
             });
         }
 
+        let tabs = Tabs::new();
         let (app_state, chat_args) = if show_login_screen {
             (
                 AppState::Login {
@@ -251,6 +333,7 @@ impl<'a> App<'a> {
             let chat_widget = ChatWidget::new(
                 config.clone(),
                 app_event_tx.clone(),
+                tabs.active_id(),
                 initial_prompt,
                 initial_images,
             );
@@ -262,17 +345,25 @@ impl<'a> App<'a> {
             )
         };
 
+        let keymap = Keymap::new(&config.keys);
+        for error in keymap.errors() {
+            tracing::warn!("{error}");
+        }
+
         Self {
             app_event_tx,
             app_event_rx,
             app_state,
             config: config.clone(),
             chat_args,
-            session_id: None,
+            tabs,
             confirm_ctrl_d: ConfirmCtrlD::new(
                 config.tui.require_double_ctrl_d,
                 config.tui.double_ctrl_d_timeout_secs,
             ),
+            keymap,
+            input_pause,
+            log_path,
         }
     }
 
@@ -284,7 +375,7 @@ impl<'a> App<'a> {
 
     /// Override the session ID for this UI instance (useful for session-resume).
     pub fn set_session_id(&mut self, id: Uuid) {
-        self.session_id = Some(id);
+        self.tabs.active_mut().session_id = Some(id);
     }
 
     /// Replay a previous session transcript into the chat widget.
@@ -298,7 +389,25 @@ impl<'a> App<'a> {
 
     /// Returns the session ID assigned by the backend for this session, if available.
     pub fn session_id(&self) -> Option<Uuid> {
-        self.session_id
+        self.tabs.active().session_id
+    }
+
+    /// Sessions of all open tabs, for the resume hints printed on exit.
+    pub fn session_ids(&self) -> Vec<Uuid> {
+        self.tabs
+            .all()
+            .iter()
+            .filter_map(|tab| tab.session_id)
+            .collect()
+    }
+
+    /// Estimated cost of the sessions of all open tabs.
+    pub fn session_cost_usd(&self) -> Option<f64> {
+        self.tabs
+            .all()
+            .iter()
+            .filter_map(|tab| tab.session_cost_usd)
+            .reduce(|a, b| a + b)
     }
 
     pub(crate) fn run(
@@ -335,6 +444,69 @@ impl<'a> App<'a> {
                     }
                     self.app_event_tx.send(AppEvent::Redraw);
                 }
+                AppEvent::InlineProfile(name) => {
+                    self.switch_profile(name);
+                    self.app_event_tx.send(AppEvent::Redraw);
+                }
+                AppEvent::InlineAccount(name) => self.switch_account(name),
+                AppEvent::AccountSwitched { account, result } => {
+                    if result.is_ok() {
+                        self.config.account = account.clone();
+                        // The API key is process-wide, so every tab switched.
+                        for widget in self.tabs.hidden_widgets_mut() {
+                            widget.account_switched_in_other_tab(account.clone());
+                        }
+                    }
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.account_switched(account, result);
+                    }
+                }
+                AppEvent::OpenMcpPrompt(info) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.open_mcp_prompt(info);
+                    }
+                }
+                AppEvent::RunCustomPrompt { name, arguments } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.run_custom_prompt(name, arguments);
+                    }
+                }
+                AppEvent::InlineReasoning(mode) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.set_reasoning_display(&mode);
+                    }
+                }
+                AppEvent::InlineReview(base) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.start_review(Some(base));
+                    }
+                }
+                AppEvent::ShowFindingDiff(finding) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.show_finding_diff(finding);
+                    }
+                }
+                AppEvent::FindingDiffLoaded { finding, hunks } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.add_finding_diff(finding, hunks);
+                    }
+                }
+                AppEvent::InlineExport(path) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.export_transcript(self.session_id(), Some(PathBuf::from(path)));
+                    }
+                }
+                AppEvent::InlineDebug(args) => {
+                    let session_id = self.session_id();
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        match parse_debug_args(&args) {
+                            Ok(path) => {
+                                widget.export_debug_bundle(session_id, &self.log_path, path)
+                            }
+                            Err(e) => widget.add_error(e),
+                        }
+                    }
+                }
                 AppEvent::InlineInspectEnv(_raw) => {
                     let tx = self.app_event_tx.clone();
                     thread::spawn(move || {
@@ -386,6 +558,7 @@ impl<'a> App<'a> {
                 AppEvent::ConfigReloadApply => {
                     match Config::load_with_cli_overrides(Vec::new(), ConfigOverrides::default()) {
                         Ok(new_cfg) => {
+                            self.keymap = Keymap::new(&new_cfg.keys);
                             self.config = new_cfg.clone();
                             if let AppState::Chat { widget } = &mut self.app_state {
                                 widget.update_config(new_cfg);
@@ -395,16 +568,21 @@ impl<'a> App<'a> {
                     }
                     self.app_event_tx.send(AppEvent::Redraw);
                 }
+                AppEvent::WriteProjectDoc { path, content } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.write_project_doc(&path, &content);
+                    }
+                }
                 AppEvent::ConfigReloadIgnore => {
                     self.app_event_tx.send(AppEvent::Redraw);
                 }
                 AppEvent::KeyEvent(key_event) => {
-                    match key_event {
-                        KeyEvent {
-                            code: KeyCode::Char('c'),
-                            modifiers: crossterm::event::KeyModifiers::CONTROL,
-                            ..
-                        } => {
+                    let history_focused = matches!(
+                        &self.app_state,
+                        AppState::Chat { widget } if widget.history_has_focus()
+                    );
+                    match self.keymap.action_for(&key_event, history_focused) {
+                        Some(KeyAction::Interrupt) => {
                             // Forward interrupt to ChatWidget when active.
                             match &mut self.app_state {
                                 AppState::Chat { widget } => {
@@ -415,20 +593,25 @@ impl<'a> App<'a> {
                                 }
                             }
                         }
-                        KeyEvent {
-                            code: KeyCode::Char('d'),
-                            modifiers: crossterm::event::KeyModifiers::CONTROL,
-                            ..
-                        } => {
-                            // Handle Ctrl+D exit confirmation when enabled.
+                        Some(KeyAction::NextTab) => {
+                            self.switch_tab(1);
+                            self.app_event_tx.send(AppEvent::Redraw);
+                        }
+                        Some(KeyAction::PrevTab) => {
+                            self.switch_tab(-1);
+                            self.app_event_tx.send(AppEvent::Redraw);
+                        }
+                        Some(KeyAction::Exit) => {
+                            // Exit (Ctrl+D by default), confirming first when enabled.
                             let now = Instant::now();
                             if self.confirm_ctrl_d.handle(now) {
                                 break;
                             }
                             if let AppState::Chat { widget } = &mut self.app_state {
-                                widget.show_exit_confirmation_prompt(
-                                    "Press Ctrl+D again to confirm exit".to_string(),
-                                );
+                                widget.show_exit_confirmation_prompt(format!(
+                                    "Press {} again to confirm exit",
+                                    self.keymap.label(KeyAction::Exit)
+                                ));
                             }
                         }
                         _ => {
@@ -439,14 +622,56 @@ impl<'a> App<'a> {
                 AppEvent::Scroll(scroll_delta) => {
                     self.dispatch_scroll_event(scroll_delta);
                 }
-                AppEvent::CodexEvent(event) => {
-                    self.dispatch_codex_event(event);
+                AppEvent::CodexEvent { tab, event } => {
+                    self.dispatch_codex_event(tab, event);
+                }
+                AppEvent::CopyToClipboard(text) => match &mut self.app_state {
+                    AppState::Chat { widget } => widget.copy_to_clipboard(&text),
+                    AppState::Login { .. } | AppState::GitWarning { .. } => {}
+                },
+                AppEvent::OpenExternalEditor(draft) => {
+                    // Wait for the input thread to leave the terminal alone
+                    // before the editor starts reading it.
+                    self.input_pause.pause();
+                    let edited = tui::suspend()
+                        .map_err(|e| format!("cannot release the terminal: {e}"))
+                        .and_then(|()| edit_in_external_editor(&draft));
+                    let resumed = tui::resume(terminal).and_then(|()| mouse_capture.reapply());
+                    self.input_pause.resume();
+                    resumed?;
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        match edited {
+                            Ok(text) => widget.set_composer_text(&text),
+                            Err(e) => widget.add_error(format!("Failed to edit the prompt: {e}")),
+                        }
+                    }
+                    self.app_event_tx.send(AppEvent::Redraw);
                 }
+                AppEvent::PasteImage => match &mut self.app_state {
+                    AppState::Chat { widget } => widget.paste_image(),
+                    AppState::Login { .. } | AppState::GitWarning { .. } => {}
+                },
+                AppEvent::MouseClick { column, row } => match &mut self.app_state {
+                    AppState::Chat { widget } => widget.handle_mouse_click(column, row),
+                    AppState::Login { .. } | AppState::GitWarning { .. } => {}
+                },
+                AppEvent::FocusChanged(focused) => match &mut self.app_state {
+                    AppState::Chat { widget } => widget.set_terminal_focused(focused),
+                    AppState::Login { .. } | AppState::GitWarning { .. } => {}
+                },
                 AppEvent::ExitRequest => {
                     break;
                 }
                 AppEvent::CodexOp(op) => match &mut self.app_state {
-                    AppState::Chat { widget } => widget.submit_op(op),
+                    AppState::Chat { widget } => {
+                        // Every tab's agent wraps its output to the terminal.
+                        if let Op::SetTerminalWidth { .. } = op {
+                            for hidden in self.tabs.hidden_widgets_mut() {
+                                hidden.submit_op(op.clone());
+                            }
+                        }
+                        widget.submit_op(op)
+                    }
                     AppState::Login { .. } | AppState::GitWarning { .. } => {}
                 },
                 AppEvent::LatestLog(line) => match &mut self.app_state {
@@ -455,13 +680,24 @@ impl<'a> App<'a> {
                 },
                 AppEvent::DispatchCommand(command) => match command {
                     SlashCommand::New => {
+                        let tab = self.tabs.renew_active();
                         let new_widget = Box::new(ChatWidget::new(
                             self.config.clone(),
                             self.app_event_tx.clone(),
+                            tab,
                             None,
                             Vec::new(),
                         ));
                         self.app_state = AppState::Chat { widget: new_widget };
+                        self.refresh_tab_bar();
+                        self.app_event_tx.send(AppEvent::Redraw);
+                    }
+                    SlashCommand::NewTab => {
+                        self.open_tab(self.config.clone());
+                        self.app_event_tx.send(AppEvent::Redraw);
+                    }
+                    SlashCommand::CloseTab => {
+                        self.close_tab();
                         self.app_event_tx.send(AppEvent::Redraw);
                     }
                     SlashCommand::ToggleMouseMode => {
@@ -470,7 +706,7 @@ impl<'a> App<'a> {
                         }
                     }
                     SlashCommand::EditPrompt => {
-                        // External-editor prompt handled inline by the composer; no-op here.
+                        // The composer sends `OpenExternalEditor` with the draft instead.
                     }
                     SlashCommand::Quit => {
                         break;
@@ -502,7 +738,106 @@ impl<'a> App<'a> {
                             self.app_event_tx.send(AppEvent::Redraw);
                         }
                     }
+                    SlashCommand::Compact => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.submit_op(Op::Compact);
+                        }
+                    }
+                    SlashCommand::Undo => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.submit_op(Op::RevertTurn);
+                        }
+                    }
+                    SlashCommand::Review => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.start_review(None);
+                        }
+                    }
+                    SlashCommand::Keys => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.show_key_bindings();
+                        }
+                    }
+                    SlashCommand::Agents => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.show_project_docs();
+                        }
+                    }
+                    SlashCommand::Init => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.init_project_doc();
+                        }
+                    }
+                    SlashCommand::Model => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.fetch_model_catalog();
+                        }
+                    }
+                    SlashCommand::Profile => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.show_profiles();
+                        }
+                    }
+                    SlashCommand::Account => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.show_accounts();
+                        }
+                    }
+                    SlashCommand::Export => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.export_transcript(self.session_id(), None);
+                        }
+                    }
+                    SlashCommand::Debug => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.add_background_event(format!(
+                                "Debug log: {}. /debug export [path] zips it with the redacted config and version info for a bug report.",
+                                self.log_path.display()
+                            ));
+                        }
+                    }
+                    SlashCommand::Fork => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.show_fork_picker(self.session_id());
+                        }
+                    }
+                    SlashCommand::Reasoning => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.show_reasoning_display();
+                        }
+                    }
+                    SlashCommand::Search => {
+                        if let AppState::Chat { widget } = &mut self.app_state {
+                            widget.push_search();
+                            self.app_event_tx.send(AppEvent::Redraw);
+                        }
+                    }
                 },
+                AppEvent::ModelCatalogLoaded(catalog) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.show_model_picker(catalog);
+                    }
+                }
+                AppEvent::SelectModel(model) => {
+                    // New chats (`/new`) keep using the chosen model.
+                    self.config.model = model.clone();
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.switch_model(model);
+                    }
+                }
+                AppEvent::ForkSession {
+                    before_user_message,
+                    message,
+                } => {
+                    self.fork_current_session(before_user_message, message);
+                    self.app_event_tx.send(AppEvent::Redraw);
+                }
+                AppEvent::SearchJump { entry, line } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.scroll_to_search_hit(entry, line);
+                        self.app_event_tx.send(AppEvent::Redraw);
+                    }
+                }
                 AppEvent::ShellCommand(cmd) => {
                     if let AppState::Chat { widget } = &mut self.app_state {
                         widget.handle_shell_command(cmd);
@@ -561,6 +896,7 @@ impl<'a> App<'a> {
                     let widget = Box::new(ChatWidget::new(
                         args.config,
                         self.app_event_tx.clone(),
+                        self.tabs.active_id(),
                         args.initial_prompt,
                         args.initial_images,
                     ));
@@ -584,13 +920,205 @@ impl<'a> App<'a> {
         }
     }
 
-    fn dispatch_codex_event(&mut self, event: Event) {
-        // Capture session ID when the session is initially configured
-        if let EventMsg::SessionConfigured(SessionConfiguredEvent { session_id, .. }) = &event.msg {
-            self.session_id = Some(*session_id);
+    /// Load the API key of the login `name` in the background and use it for
+    /// the following requests of every tab; [`DEFAULT_ACCOUNT`] is the login
+    /// in `auth.json`.
+    fn switch_account(&mut self, name: String) {
+        if name != DEFAULT_ACCOUNT && !is_valid_account_name(&name) {
+            if let AppState::Chat { widget } = &mut self.app_state {
+                widget.add_error(format!("`{name}` is not a valid account name."));
+            }
+            return;
+        }
+        let account = (name != DEFAULT_ACCOUNT).then_some(name);
+        let codex_home = self.config.codex_home.clone();
+        let app_event_tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let result = try_read_openai_api_key(&codex_home, account.as_deref())
+                .await
+                .map(set_openai_api_key)
+                .map_err(|e| e.to_string());
+            app_event_tx.send(AppEvent::AccountSwitched { account, result });
+        });
+    }
+
+    /// Reload the config with the `[profiles]` entry `name` and continue the
+    /// current conversation in a fresh chat that uses its provider and model.
+    /// The new chat resumes the current session, so the agent keeps the
+    /// context and new turns are appended to the same session file.
+    fn switch_profile(&mut self, name: String) {
+        let overrides = ConfigOverrides {
+            cwd: Some(self.config.cwd.clone()),
+            sandbox_policy: Some(self.config.sandbox_policy.clone()),
+            config_profile: Some(name.clone()),
+            codex_linux_sandbox_exe: self.config.codex_linux_sandbox_exe.clone(),
+            ..Default::default()
+        };
+        let mut new_cfg = match Config::load_with_cli_overrides(Vec::new(), overrides) {
+            Ok(new_cfg) => new_cfg,
+            Err(e) => {
+                if let AppState::Chat { widget } = &mut self.app_state {
+                    widget.add_error(format!("Failed to switch to profile `{name}`: {e}"));
+                }
+                return;
+            }
+        };
+
+        let session_id = self.session_id();
+        let saved = session_id.and_then(|id| match load_session(&new_cfg.codex_home, id) {
+            Ok(saved) => Some(saved),
+            Err(e) => {
+                tracing::warn!("failed to load session {id} to carry it over: {e}");
+                None
+            }
+        });
+        if saved.is_some() {
+            new_cfg.resume_session_id = session_id;
+        }
+
+        let account_changed = new_cfg.account != self.config.account;
+        self.keymap = Keymap::new(&new_cfg.keys);
+        self.config = new_cfg.clone();
+        let tab = self.tabs.renew_active();
+        let mut widget = Box::new(ChatWidget::new(
+            new_cfg.clone(),
+            self.app_event_tx.clone(),
+            tab,
+            None,
+            Vec::new(),
+        ));
+        if let Some(saved) = saved {
+            widget.replay_items(saved.items);
+        }
+        widget.add_background_event(format!(
+            "Switched to profile `{name}`: model {}, provider {}.",
+            new_cfg.model, new_cfg.model_provider_id
+        ));
+        self.app_state = AppState::Chat { widget };
+        self.refresh_tab_bar();
+        if account_changed {
+            let account = new_cfg
+                .account
+                .unwrap_or_else(|| DEFAULT_ACCOUNT.to_string());
+            self.switch_account(account);
+        }
+    }
+
+    /// Continue the conversation in a new tab with a new session that ends
+    /// just before the chosen user message, whose text is put back in the
+    /// composer. The original tab and session are left as they are.
+    fn fork_current_session(&mut self, before_user_message: usize, message: String) {
+        let Some(session_id) = self.session_id() else {
+            return;
+        };
+        let codex_home = &self.config.codex_home;
+        let forked = fork_session(codex_home, session_id, before_user_message)
+            .and_then(|fork_id| Ok((fork_id, load_session(codex_home, fork_id)?)));
+        let (fork_id, saved) = match forked {
+            Ok(forked) => forked,
+            Err(e) => {
+                if let AppState::Chat { widget } = &mut self.app_state {
+                    widget.add_error(format!("Failed to fork the session: {e}"));
+                }
+                return;
+            }
+        };
+
+        let mut new_cfg = self.config.clone();
+        new_cfg.resume_session_id = Some(fork_id);
+        if let Some(widget) = self.open_tab(new_cfg) {
+            widget.replay_items(saved.items);
+            widget.add_background_event(format!(
+                "Forked session {session_id} into {fork_id}; the original stays in the previous tab."
+            ));
+            widget.set_composer_text(&message);
+        }
+    }
+
+    /// Open a tab with a new chat for `config` and show it.
+    fn open_tab(&mut self, config: Config) -> Option<&mut ChatWidget<'a>> {
+        let AppState::Chat { widget } = &mut self.app_state else {
+            return None;
+        };
+        let tab = self.tabs.allocate_id();
+        let new_widget = Box::new(ChatWidget::new(
+            config,
+            self.app_event_tx.clone(),
+            tab,
+            None,
+            Vec::new(),
+        ));
+        self.tabs.open(widget, tab, new_widget);
+        widget.set_tab_bar(self.tabs.tab_bar());
+        Some(&mut **widget)
+    }
+
+    /// Close the shown tab and its conversation; the last tab stays open.
+    fn close_tab(&mut self) {
+        if let AppState::Chat { widget } = &mut self.app_state {
+            if self.tabs.close_active(widget) {
+                widget.set_tab_bar(self.tabs.tab_bar());
+            } else {
+                widget.add_error("This is the only tab; use /new for a fresh chat.".to_string());
+            }
+        }
+    }
+
+    fn switch_tab(&mut self, offset: isize) {
+        if let AppState::Chat { widget } = &mut self.app_state {
+            self.tabs.switch(offset, widget);
+            widget.set_tab_bar(self.tabs.tab_bar());
+        }
+    }
+
+    fn refresh_tab_bar(&mut self) {
+        if let AppState::Chat { widget } = &mut self.app_state {
+            widget.set_tab_bar(self.tabs.tab_bar());
+        }
+    }
+
+    fn dispatch_codex_event(&mut self, tab: TabId, event: Event) {
+        // Events of a chat that was replaced (`/new`, `/profile`) or closed.
+        let Some(info) = self.tabs.info_mut(tab) else {
+            return;
+        };
+        let mut tab_bar_changed = false;
+        match &event.msg {
+            // Capture session ID when the session is initially configured
+            EventMsg::SessionConfigured(SessionConfiguredEvent { session_id, .. }) => {
+                info.session_id = Some(*session_id);
+                tab_bar_changed = true;
+            }
+            EventMsg::TokenCount(TokenCountEvent {
+                total,
+                estimated_cost_usd,
+                ..
+            }) => {
+                info.token_usage = *total;
+                if estimated_cost_usd.is_some() {
+                    info.session_cost_usd = *estimated_cost_usd;
+                }
+                tab_bar_changed = true;
+            }
+            _ => {}
+        }
+
+        if tab != self.tabs.active_id() {
+            if let Some(widget) = self.tabs.hidden_widget_mut(tab) {
+                widget.handle_codex_event(event);
+            }
+            if tab_bar_changed {
+                self.refresh_tab_bar();
+            }
+            return;
         }
         match &mut self.app_state {
-            AppState::Chat { widget } => widget.handle_codex_event(event),
+            AppState::Chat { widget } => {
+                widget.handle_codex_event(event);
+                if tab_bar_changed {
+                    widget.set_tab_bar(self.tabs.tab_bar());
+                }
+            }
             AppState::Login { .. } | AppState::GitWarning { .. } => {}
         }
     }
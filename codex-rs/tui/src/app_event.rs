@@ -1,12 +1,20 @@
+use codex_core::model_catalog::ModelCatalog;
 use codex_core::protocol::Event;
+use codex_core::protocol::McpPromptInfo;
+use codex_core::protocol::ReviewFinding;
 use crossterm::event::KeyEvent;
 
 use crate::slash_command::SlashCommand;
+use crate::tabs::TabId;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub(crate) enum AppEvent {
-    CodexEvent(Event),
+    /// Event from the agent of a tab.
+    CodexEvent {
+        tab: TabId,
+        event: Event,
+    },
 
     Redraw,
 
@@ -16,6 +24,25 @@ pub(crate) enum AppEvent {
     /// scroll up/down events within a short time window.
     Scroll(i32),
 
+    /// Copy text to the system clipboard and report it in the history.
+    CopyToClipboard(String),
+
+    /// Attach the image on the system clipboard to the composer.
+    PasteImage,
+
+    /// Edit the given draft in the external editor and put the result back
+    /// into the composer.
+    OpenExternalEditor(String),
+
+    /// Left mouse button pressed at the given terminal cell.
+    MouseClick {
+        column: u16,
+        row: u16,
+    },
+
+    /// The terminal window gained (`true`) or lost (`false`) focus.
+    FocusChanged(bool),
+
     /// Request to exit the application gracefully.
     ExitRequest,
 
@@ -37,6 +64,28 @@ pub(crate) enum AppEvent {
     InlineMountRemove(String),
     /// Inline inspect-env DSL: raw argument string (unused).
     InlineInspectEnv(String),
+    /// Inline export DSL: path to write the transcript to.
+    InlineExport(String),
+    /// Inline debug DSL: `export [path]`.
+    InlineDebug(String),
+    /// Inline profile DSL: name of the profile to switch to.
+    InlineProfile(String),
+    /// Switch to the stored login `/account <name>`.
+    InlineAccount(String),
+    /// The API key of the login `account` (`None` for the default one) was
+    /// loaded and is now used, or why it could not be.
+    AccountSwitched {
+        account: Option<String>,
+        result: Result<(), String>,
+    },
+    /// Run a prompt of an MCP server (`/mcp:<server>:<prompt>`), asking for
+    /// its arguments first.
+    OpenMcpPrompt(McpPromptInfo),
+    /// Run the custom prompt `/<name>` with the text typed after it.
+    RunCustomPrompt {
+        name: String,
+        arguments: String,
+    },
     /// Perform mount-add: create symlink and update sandbox policy.
     MountAdd {
         host: std::path::PathBuf,
@@ -53,6 +102,38 @@ pub(crate) enum AppEvent {
     ConfigReloadApply,
     /// Ignore on-disk config.toml changes and continue with old config.
     ConfigReloadIgnore,
+    /// Models fetched for the `/model` picker.
+    ModelCatalogLoaded(ModelCatalog),
+    /// Switch the session to this model.
+    SelectModel(String),
+    /// Fork the session just before its `before_user_message`-th user
+    /// message, whose text is `message`.
+    ForkSession {
+        before_user_message: usize,
+        message: String,
+    },
+    /// Start a review of the changes against a git ref (`/review <base>`).
+    InlineReview(String),
+    /// Set how reasoning is shown (`/reasoning <mode>`).
+    InlineReasoning(String),
+    /// Write the AGENTS.md drafted by `/init`, which the user accepted.
+    WriteProjectDoc {
+        path: std::path::PathBuf,
+        content: String,
+    },
+    /// Show the diff around a finding of the latest review.
+    ShowFindingDiff(ReviewFinding),
+    /// The diff hunks around a review finding, or why they could not be
+    /// loaded.
+    FindingDiffLoaded {
+        finding: ReviewFinding,
+        hunks: Result<Vec<String>, String>,
+    },
+    /// Scroll the conversation history to a `/search` hit.
+    SearchJump {
+        entry: usize,
+        line: usize,
+    },
     /// Run an arbitrary shell command in the agent's container (from hotkey prompt).
     ShellCommand(String),
     /// Result of a previously-invoked shell command: call ID, stdout, stderr, and exit code.
@@ -68,10 +149,24 @@ impl PartialEq for AppEvent {
     fn eq(&self, other: &Self) -> bool {
         use AppEvent::*;
         match (self, other) {
-            (CodexEvent(_), CodexEvent(_)) => true,
+            (CodexEvent { tab: a, .. }, CodexEvent { tab: b, .. }) => a == b,
             (Redraw, Redraw) => true,
             (KeyEvent(a), KeyEvent(b)) => a == b,
             (Scroll(a), Scroll(b)) => a == b,
+            (
+                MouseClick {
+                    column: c1,
+                    row: r1,
+                },
+                MouseClick {
+                    column: c2,
+                    row: r2,
+                },
+            ) => c1 == c2 && r1 == r2,
+            (CopyToClipboard(a), CopyToClipboard(b)) => a == b,
+            (PasteImage, PasteImage) => true,
+            (OpenExternalEditor(a), OpenExternalEditor(b)) => a == b,
+            (FocusChanged(a), FocusChanged(b)) => a == b,
             (ExitRequest, ExitRequest) => true,
             (CodexOp(a), CodexOp(b)) => a == b,
             (LatestLog(a), LatestLog(b)) => a == b,
@@ -79,6 +174,44 @@ impl PartialEq for AppEvent {
             (InlineMountAdd(a), InlineMountAdd(b)) => a == b,
             (InlineMountRemove(a), InlineMountRemove(b)) => a == b,
             (InlineInspectEnv(a), InlineInspectEnv(b)) => a == b,
+            (InlineExport(a), InlineExport(b)) => a == b,
+            (InlineDebug(a), InlineDebug(b)) => a == b,
+            (InlineProfile(a), InlineProfile(b)) => a == b,
+            (InlineAccount(a), InlineAccount(b)) => a == b,
+            (
+                AccountSwitched {
+                    account: a1,
+                    result: r1,
+                },
+                AccountSwitched {
+                    account: a2,
+                    result: r2,
+                },
+            ) => a1 == a2 && r1 == r2,
+            (OpenMcpPrompt(a), OpenMcpPrompt(b)) => a == b,
+            (
+                RunCustomPrompt {
+                    name: a,
+                    arguments: x,
+                },
+                RunCustomPrompt {
+                    name: b,
+                    arguments: y,
+                },
+            ) => a == b && x == y,
+            (InlineReview(a), InlineReview(b)) => a == b,
+            (InlineReasoning(a), InlineReasoning(b)) => a == b,
+            (ShowFindingDiff(a), ShowFindingDiff(b)) => a == b,
+            (
+                FindingDiffLoaded {
+                    finding: f1,
+                    hunks: h1,
+                },
+                FindingDiffLoaded {
+                    finding: f2,
+                    hunks: h2,
+                },
+            ) => f1 == f2 && h1 == h2,
             (
                 MountAdd {
                     host: h1,
@@ -94,7 +227,39 @@ impl PartialEq for AppEvent {
             (MountRemove { container: c1 }, MountRemove { container: c2 }) => c1 == c2,
             (ConfigReloadRequest(a), ConfigReloadRequest(b)) => a == b,
             (ConfigReloadApply, ConfigReloadApply) => true,
+            (ModelCatalogLoaded(a), ModelCatalogLoaded(b)) => a == b,
+            (SelectModel(a), SelectModel(b)) => a == b,
+            (
+                ForkSession {
+                    before_user_message: i1,
+                    message: m1,
+                },
+                ForkSession {
+                    before_user_message: i2,
+                    message: m2,
+                },
+            ) => i1 == i2 && m1 == m2,
             (ConfigReloadIgnore, ConfigReloadIgnore) => true,
+            (
+                WriteProjectDoc {
+                    path: p1,
+                    content: c1,
+                },
+                WriteProjectDoc {
+                    path: p2,
+                    content: c2,
+                },
+            ) => p1 == p2 && c1 == c2,
+            (
+                SearchJump {
+                    entry: e1,
+                    line: l1,
+                },
+                SearchJump {
+                    entry: e2,
+                    line: l2,
+                },
+            ) => e1 == e2 && l1 == l2,
             (ShellCommand(a), ShellCommand(b)) => a == b,
             (
                 ShellCommandResult {
@@ -46,6 +46,17 @@ impl<'a> BottomPaneView<'a> for ApprovalModalView<'a> {
         self.maybe_advance();
     }
 
+    fn handle_mouse_click(
+        &mut self,
+        _pane: &mut BottomPane<'a>,
+        area: Rect,
+        column: u16,
+        row: u16,
+    ) {
+        self.current.handle_mouse_click(area, column, row);
+        self.maybe_advance();
+    }
+
     fn is_complete(&self) -> bool {
         self.current.is_complete() && self.queue.is_empty()
     }
@@ -17,6 +17,18 @@ pub(crate) trait BottomPaneView<'a> {
     /// scheduled after this call.
     fn handle_key_event(&mut self, _pane: &mut BottomPane<'a>, _key_event: KeyEvent) {}
 
+    /// Handle a left click at (`column`, `row`) while the view is active.
+    /// `area` is where the view is rendered. A redraw is always scheduled
+    /// after this call.
+    fn handle_mouse_click(
+        &mut self,
+        _pane: &mut BottomPane<'a>,
+        _area: Rect,
+        _column: u16,
+        _row: u16,
+    ) {
+    }
+
     /// Return `true` if the view has finished and should be removed.
     fn is_complete(&self) -> bool {
         false
@@ -1,3 +1,9 @@
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use codex_core::model_pricing::format_cost_usd;
+use codex_core::protocol::CustomPrompt;
+use codex_core::protocol::McpPromptInfo;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Alignment;
@@ -8,11 +14,13 @@ use ratatui::widgets::BorderType;
 use ratatui::widgets::Borders;
 use ratatui::widgets::Widget;
 use ratatui::widgets::WidgetRef;
+use regex_lite::Regex;
 use tui_textarea::Input;
 use tui_textarea::Key;
 use tui_textarea::TextArea;
 
 use super::chat_composer_history::ChatComposerHistory;
+use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
 
 use crate::app_event::AppEvent;
@@ -24,6 +32,11 @@ const MIN_TEXTAREA_ROWS: usize = 1;
 /// Rows consumed by the border.
 const BORDER_LINES: u16 = 2;
 
+static IMAGE_PLACEHOLDER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::expect_used)]
+    Regex::new(r"\[image #\d+ \d+x\d+\]").expect("invalid image placeholder regex")
+});
+
 /// Result returned when the user interacts with the text area.
 #[derive(Debug, PartialEq)]
 pub enum InputResult {
@@ -40,8 +53,30 @@ pub(crate) struct ChatComposer<'a> {
     max_rows: usize,
     /// Last computed context-left percentage
     context_left_percent: f64,
+    /// Whether the footer starts with the context left; off while the
+    /// status line shows it.
+    show_context_left: bool,
     /// Whether the composer is in shell-command mode (Ctrl+M toggles).
     shell_mode: bool,
+    /// Session tokens used and the configured `max_session_tokens`, if any.
+    token_budget: Option<(u64, u64)>,
+    /// Estimated cost of the session so far, in USD.
+    session_cost_usd: Option<f64>,
+    /// Commands of the live `exec_background` processes.
+    background_processes: Vec<String>,
+    /// Prompt templates of the MCP servers, offered in the command popup.
+    mcp_prompts: Vec<McpPromptInfo>,
+    custom_prompts: Vec<CustomPrompt>,
+    /// Images pasted into the message being composed.
+    attached_images: Vec<AttachedImage>,
+    /// Images of the last submitted message, for [`Self::take_submitted_images`].
+    submitted_images: Vec<PathBuf>,
+}
+
+/// An image attached to the message, shown in the text as `placeholder`.
+struct AttachedImage {
+    placeholder: String,
+    path: PathBuf,
 }
 
 #[cfg(test)]
@@ -73,6 +108,70 @@ mod tests {
             other => panic!("Expected DispatchCommand(Shell), got {:?}", other),
         }
     }
+
+    #[test]
+    fn reasoning_command_passes_its_mode() {
+        let (tx, rx) = mpsc::channel();
+        let mut composer = ChatComposer::new(true, AppEventSender::new(tx), 1);
+        for c in "/reasoning summary".chars() {
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        let (res, _) = composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(res, InputResult::None);
+        match rx.try_recv().unwrap() {
+            AppEvent::InlineReasoning(mode) => assert_eq!(
+                mode.parse::<codex_core::config_types::ReasoningDisplay>(),
+                Ok(codex_core::config_types::ReasoningDisplay::Summary)
+            ),
+            other => panic!("Expected InlineReasoning, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn submits_images_whose_placeholders_remain() {
+        let (tx, _rx) = mpsc::channel();
+        let mut composer = ChatComposer::new(true, AppEventSender::new(tx), 1);
+        composer.attach_image(PathBuf::from("/tmp/a.png"), 800, 600);
+        composer.attach_image(PathBuf::from("/tmp/b.png"), 32, 32);
+        // Delete the second placeholder, `[image #2 32x32]`.
+        for _ in 0.."[image #2 32x32]".len() {
+            composer.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        }
+        for c in " what is this?".chars() {
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+
+        let (res, _) = composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            res,
+            InputResult::Submitted("[image #1 800x600] what is this?".to_string())
+        );
+        assert_eq!(
+            composer.take_submitted_images(),
+            vec![PathBuf::from("/tmp/a.png")]
+        );
+        assert!(composer.take_submitted_images().is_empty());
+    }
+
+    #[test]
+    fn restored_message_keeps_its_images() {
+        let (tx, _rx) = mpsc::channel();
+        let mut composer = ChatComposer::new(true, AppEventSender::new(tx), 1);
+        composer.restore_message(
+            "compare [image #1 800x600] with [image #3 32x32]",
+            vec![PathBuf::from("/tmp/a.png"), PathBuf::from("/tmp/c.png")],
+        );
+
+        let (res, _) = composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            res,
+            InputResult::Submitted("compare [image #1 800x600] with [image #3 32x32]".to_string())
+        );
+        assert_eq!(
+            composer.take_submitted_images(),
+            vec![PathBuf::from("/tmp/a.png"), PathBuf::from("/tmp/c.png")]
+        );
+    }
 }
 
 impl ChatComposer<'_> {
@@ -88,7 +187,15 @@ impl ChatComposer<'_> {
             history: ChatComposerHistory::new(),
             max_rows,
             context_left_percent: 100.0,
+            show_context_left: true,
             shell_mode: false,
+            token_budget: None,
+            session_cost_usd: None,
+            background_processes: Vec::new(),
+            mcp_prompts: Vec::new(),
+            custom_prompts: Vec::new(),
+            attached_images: Vec::new(),
+            submitted_images: Vec::new(),
         };
         this.update_border(has_input_focus);
         this
@@ -113,6 +220,20 @@ impl ChatComposer<'_> {
             .on_entry_response(log_id, offset, entry, &mut self.textarea)
     }
 
+    /// Integrate the response to a Ctrl-R search of the persistent history.
+    pub(crate) fn on_history_search_response(
+        &mut self,
+        log_id: u64,
+        query: &str,
+        found: Option<(usize, String)>,
+    ) -> bool {
+        let updated = self
+            .history
+            .on_search_response(log_id, query, found, &mut self.textarea);
+        self.update_border(true);
+        updated
+    }
+
     pub fn set_input_focus(&mut self, has_focus: bool) {
         self.update_border(has_focus);
     }
@@ -122,8 +243,89 @@ impl ChatComposer<'_> {
         self.context_left_percent = pct;
     }
 
+    pub fn set_show_context_left(&mut self, show: bool) {
+        self.show_context_left = show;
+    }
+
+    /// Update the session token budget shown next to the context indicator.
+    pub fn set_token_budget(&mut self, used: u64, max: u64) {
+        self.token_budget = Some((used, max));
+    }
+
+    pub fn set_session_cost(&mut self, cost_usd: f64) {
+        self.session_cost_usd = Some(cost_usd);
+    }
+
+    /// Update the background processes shown after the context indicator.
+    pub fn set_background_processes(&mut self, commands: Vec<String>) {
+        self.background_processes = commands;
+    }
+
+    pub fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptInfo>) {
+        self.mcp_prompts = prompts;
+        self.rebuild_command_popup();
+    }
+
+    /// Insert a placeholder such as `[image #1 800x600]` for the image at
+    /// `path` at the cursor.
+    pub fn attach_image(&mut self, path: PathBuf, width: u32, height: u32) {
+        // Restored messages may skip numbers, so continue after the highest.
+        let number = self
+            .attached_images
+            .iter()
+            .filter_map(|image| {
+                image
+                    .placeholder
+                    .strip_prefix("[image #")?
+                    .split(' ')
+                    .next()?
+                    .parse::<usize>()
+                    .ok()
+            })
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let placeholder = format!("[image #{number} {width}x{height}]");
+        self.textarea.insert_str(&placeholder);
+        self.attached_images
+            .push(AttachedImage { placeholder, path });
+    }
+
+    /// Replace the draft with a message submitted earlier. Its images are
+    /// matched to the `[image #N WxH]` placeholders in `text` in order.
+    pub fn restore_message(&mut self, text: &str, image_paths: Vec<PathBuf>) {
+        self.set_input_text(text);
+        self.attached_images = IMAGE_PLACEHOLDER_REGEX
+            .find_iter(text)
+            .map(|m| m.as_str().to_string())
+            .zip(image_paths)
+            .map(|(placeholder, path)| AttachedImage { placeholder, path })
+            .collect();
+    }
+
+    /// The attached images whose placeholders were still in the submitted
+    /// message.
+    pub fn take_submitted_images(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.submitted_images)
+    }
+
+    pub fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
+        self.custom_prompts = prompts;
+        self.rebuild_command_popup();
+    }
+
+    /// Make an open command popup list the current prompts.
+    fn rebuild_command_popup(&mut self) {
+        if self.command_popup.take().is_some() {
+            self.sync_command_popup();
+        }
+    }
+
     /// Handle a key event coming from the main UI.
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        if self.history.is_searching() {
+            return self.handle_key_event_in_search(key_event);
+        }
         let result = match self.command_popup {
             Some(_) => self.handle_key_event_with_popup(key_event),
             None => self.handle_key_event_without_popup(key_event),
@@ -178,7 +380,32 @@ impl ChatComposer<'_> {
                 alt: false,
                 ctrl: false,
             } => {
-                if let Some(cmd) = popup.selected_command() {
+                if let Some(CommandItem::CustomPrompt(prompt)) = popup.selected_command() {
+                    // Everything after the command token is passed as arguments.
+                    let text = self.textarea.lines().join("\n");
+                    let arguments = text
+                        .trim_start()
+                        .split_once(char::is_whitespace)
+                        .map(|(_, arguments)| arguments.trim().to_string())
+                        .unwrap_or_default();
+                    self.app_event_tx.send(AppEvent::RunCustomPrompt {
+                        name: prompt.name,
+                        arguments,
+                    });
+                    self.textarea.select_all();
+                    self.textarea.cut();
+                    self.command_popup = None;
+                    return (InputResult::None, true);
+                }
+                if let Some(CommandItem::McpPrompt(info)) = popup.selected_command() {
+                    // Arguments are asked for in a form.
+                    self.app_event_tx.send(AppEvent::OpenMcpPrompt(info));
+                    self.textarea.select_all();
+                    self.textarea.cut();
+                    self.command_popup = None;
+                    return (InputResult::None, true);
+                }
+                if let Some(CommandItem::Builtin(cmd)) = popup.selected_command() {
                     // Inline DSL for mount-add/remove with args or dispatch other commands.
                     let first_line = self
                         .textarea
@@ -193,23 +420,56 @@ impl ChatComposer<'_> {
                     let mut parts = stripped.splitn(2, char::is_whitespace);
                     let _cmd_token = parts.next().unwrap_or("");
                     let args = parts.next().unwrap_or("").trim_start();
-                    // Launch external editor for prompt drafting when slash command is /edit-prompt
-                    if *cmd == SlashCommand::EditPrompt {
-                        self.open_external_editor();
+                    // Draft the prompt in the external editor, starting from
+                    // whatever follows `/edit-prompt`.
+                    if cmd == SlashCommand::EditPrompt {
+                        let draft = self
+                            .textarea
+                            .lines()
+                            .join("\n")
+                            .trim_start()
+                            .strip_prefix('/')
+                            .and_then(|text| text.split_once(char::is_whitespace))
+                            .map(|(_, draft)| draft.trim_start().to_string())
+                            .unwrap_or_default();
+                        self.app_event_tx.send(AppEvent::OpenExternalEditor(draft));
                         self.command_popup = None;
                         return (InputResult::None, true);
                     }
                     if !args.is_empty()
-                        && (*cmd == SlashCommand::MountAdd || *cmd == SlashCommand::MountRemove)
+                        && matches!(
+                            cmd,
+                            SlashCommand::MountAdd
+                                | SlashCommand::MountRemove
+                                | SlashCommand::Export
+                                | SlashCommand::Debug
+                                | SlashCommand::Profile
+                                | SlashCommand::Account
+                                | SlashCommand::Review
+                                | SlashCommand::Reasoning
+                        )
                     {
-                        let ev = if *cmd == SlashCommand::MountAdd {
-                            AppEvent::InlineMountAdd(args.to_string())
-                        } else {
-                            AppEvent::InlineMountRemove(args.to_string())
+                        let ev = match cmd {
+                            SlashCommand::MountAdd => AppEvent::InlineMountAdd(args.to_string()),
+                            SlashCommand::MountRemove => {
+                                AppEvent::InlineMountRemove(args.to_string())
+                            }
+                            SlashCommand::Profile => {
+                                AppEvent::InlineProfile(args.trim().to_string())
+                            }
+                            SlashCommand::Account => {
+                                AppEvent::InlineAccount(args.trim().to_string())
+                            }
+                            SlashCommand::Debug => AppEvent::InlineDebug(args.to_string()),
+                            SlashCommand::Review => AppEvent::InlineReview(args.trim().to_string()),
+                            SlashCommand::Reasoning => {
+                                AppEvent::InlineReasoning(args.trim().to_string())
+                            }
+                            _ => AppEvent::InlineExport(args.trim_end().to_string()),
                         };
                         self.app_event_tx.send(ev);
                     } else {
-                        self.app_event_tx.send(AppEvent::DispatchCommand(*cmd));
+                        self.app_event_tx.send(AppEvent::DispatchCommand(cmd));
                     }
                     self.textarea.select_all();
                     self.textarea.cut();
@@ -223,6 +483,47 @@ impl ChatComposer<'_> {
         }
     }
 
+    /// Handle key event during a Ctrl-R history search. Keys that do not edit
+    /// the query end the search, keeping the match, and then act as usual.
+    fn handle_key_event_in_search(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        match key_event.into() {
+            Input {
+                key: Key::Char('r'),
+                ctrl: true,
+                alt: false,
+                ..
+            } => self
+                .history
+                .search_older(&mut self.textarea, &self.app_event_tx),
+            Input {
+                key: Key::Char(c),
+                ctrl: false,
+                alt: false,
+                ..
+            } => self
+                .history
+                .search_push(c, &mut self.textarea, &self.app_event_tx),
+            Input {
+                key: Key::Backspace,
+                ..
+            } => self
+                .history
+                .search_pop(&mut self.textarea, &self.app_event_tx),
+            Input { key: Key::Esc, .. } => self.history.cancel_search(&mut self.textarea),
+            Input {
+                key: Key::Enter, ..
+            } => self.history.accept_search(),
+            _ => {
+                self.history.accept_search();
+                self.update_border(true);
+                return self.handle_key_event(key_event);
+            }
+        }
+        self.update_border(true);
+        self.sync_command_popup();
+        (InputResult::None, true)
+    }
+
     /// Handle key event when no popup is visible.
     fn handle_key_event_without_popup(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
         let input: Input = key_event.into();
@@ -263,6 +564,12 @@ impl ChatComposer<'_> {
                 let text = self.textarea.lines().join("\n");
                 self.textarea.select_all();
                 self.textarea.cut();
+                self.submitted_images = self
+                    .attached_images
+                    .drain(..)
+                    .filter(|image| text.contains(&image.placeholder))
+                    .map(|image| image.path)
+                    .collect();
 
                 if text.is_empty() {
                     (InputResult::None, true)
@@ -283,6 +590,17 @@ impl ChatComposer<'_> {
                 self.textarea.insert_newline();
                 (InputResult::None, true)
             }
+            Input {
+                key: Key::Char('r'),
+                ctrl: true,
+                alt: false,
+                shift: false,
+            } => {
+                self.history
+                    .search_older(&mut self.textarea, &self.app_event_tx);
+                self.update_border(true);
+                (InputResult::None, true)
+            }
             Input {
                 key: Key::Char('m'),
                 ctrl: true,
@@ -305,48 +623,19 @@ impl ChatComposer<'_> {
         (InputResult::None, true)
     }
 
-    /// Launch an external editor on a temporary file pre-populated with the current draft,
-    /// then reload the edited contents back into the textarea on exit.
-    pub fn open_external_editor(&mut self) {
-        use std::io::Write;
-        use std::process::Command;
-        // Dump current draft to a temp file
-        let content = self.textarea.lines().join("\n");
-        let mut tmp = match tempfile::NamedTempFile::new() {
-            Ok(f) => f,
-            Err(e) => {
-                tracing::error!("failed to create temp file for editor: {e}");
-                return;
-            }
-        };
-        if let Err(e) = write!(tmp, "{}", content) {
-            tracing::error!("failed to write to temp file for editor: {e}");
-            return;
-        }
-        let path = tmp.path();
-        // Determine editor: VISUAL > EDITOR > nvim
-        let editor = std::env::var("VISUAL")
-            .or_else(|_| std::env::var("EDITOR"))
-            .unwrap_or_else(|_| "nvim".into());
-        // Launch editor and wait for exit
-        if let Err(e) = Command::new(editor).arg(path).status() {
-            tracing::error!("failed to launch editor: {e}");
-            return;
-        }
-        // Read back edited contents (fall back to original on error)
-        let new_text = std::fs::read_to_string(path).unwrap_or(content);
-        // Replace textarea contents
-        self.textarea.select_all();
-        self.textarea.cut();
-        let _ = self.textarea.insert_str(new_text);
-    }
-
     /// Return the current text in the composer input.
-    #[allow(dead_code)]
     pub fn get_input_text(&self) -> String {
         self.textarea.lines().join("\n")
     }
 
+    /// Replace the current text in the composer input.
+    pub fn set_input_text(&mut self, text: &str) {
+        self.textarea.select_all();
+        self.textarea.cut();
+        let _ = self.textarea.insert_str(text);
+        self.sync_command_popup();
+    }
+
     /// Returns true if the composer is in shell-command mode.
     #[allow(dead_code)]
     pub fn is_shell_mode(&self) -> bool {
@@ -369,7 +658,11 @@ impl ChatComposer<'_> {
 
         if first_line.starts_with('/') {
             // Create popup lazily when the user starts a slash command.
-            let popup = self.command_popup.get_or_insert_with(CommandPopup::new);
+            let mcp_prompts = &self.mcp_prompts;
+            let custom_prompts = &self.custom_prompts;
+            let popup = self.command_popup.get_or_insert_with(|| {
+                CommandPopup::new(mcp_prompts.clone(), custom_prompts.clone())
+            });
 
             // Forward *only* the first line since `CommandPopup` only needs
             // the command token.
@@ -404,7 +697,15 @@ impl ChatComposer<'_> {
             border_style: Style,
         }
 
-        let bs = if self.shell_mode {
+        let bs = if let Some(prompt) = self.history.search_prompt() {
+            BlockState {
+                right_title: Line::from(format!(
+                    "{prompt} – Ctrl+R older | Enter to accept | Esc to cancel"
+                ))
+                .alignment(Alignment::Right),
+                border_style: Style::default().fg(Color::Cyan),
+            }
+        } else if self.shell_mode {
             BlockState {
                 right_title: Line::from("Shell mode – Enter to run | Ctrl+M to exit shell mode")
                     .alignment(Alignment::Right),
@@ -435,6 +736,10 @@ impl ChatComposer<'_> {
     pub(crate) fn is_command_popup_visible(&self) -> bool {
         self.command_popup.is_some()
     }
+
+    pub(crate) fn is_history_search_active(&self) -> bool {
+        self.history.is_searching()
+    }
 }
 
 impl WidgetRef for &ChatComposer<'_> {
@@ -465,21 +770,58 @@ impl WidgetRef for &ChatComposer<'_> {
         }
         // Render context-left indicator when not displaying a popup
         if self.command_popup.is_none() {
-            let pct = self.context_left_percent.round();
-            let text = format!("{:.0}% context left", pct);
-            let color = if pct > 40.0 {
-                Color::Green
-            } else if pct > 25.0 {
-                Color::Yellow
-            } else {
-                Color::Red
-            };
-            buf.set_string(
-                area.x + 1,
-                area.y + area.height - 1,
-                text,
-                Style::default().fg(color),
-            );
+            let start = area.x + 1;
+            let y = area.y + area.height - 1;
+            let mut x = start;
+            if self.show_context_left {
+                let pct = self.context_left_percent.round();
+                let text = format!("{:.0}% context left", pct);
+                let color = if pct > 40.0 {
+                    Color::Green
+                } else if pct > 25.0 {
+                    Color::Yellow
+                } else {
+                    Color::Red
+                };
+                (x, _) = buf.set_stringn(x, y, &text, usize::MAX, Style::default().fg(color));
+            }
+            // Items after the first are separated by a dot.
+            let sep = |x: u16| if x > start { " · " } else { "" };
+            if let Some((used, max)) = self.token_budget {
+                let pct = used as f64 / max.max(1) as f64 * 100.0;
+                // Only draw attention to the budget once 80% is used.
+                let color = if pct >= 100.0 {
+                    Color::Red
+                } else if pct >= 80.0 {
+                    Color::Yellow
+                } else {
+                    Color::DarkGray
+                };
+                (x, _) = buf.set_string(
+                    x,
+                    y,
+                    format!("{}{used}/{max} session tokens ({pct:.0}%)", sep(x)),
+                    Style::default().fg(color),
+                );
+            }
+            if let Some(cost) = self.session_cost_usd {
+                (x, _) = buf.set_string(
+                    x,
+                    y,
+                    format!("{}{} this session", sep(x), format_cost_usd(cost)),
+                    Style::default().fg(Color::DarkGray),
+                );
+            }
+            if !self.background_processes.is_empty() {
+                let text = format!(
+                    "{}{} background: {}",
+                    sep(x),
+                    self.background_processes.len(),
+                    self.background_processes.join(", ")
+                );
+                let max_width = usize::from((area.x + area.width).saturating_sub(x + 1));
+                buf.set_stringn(x, y, text, max_width, Style::default().fg(Color::Cyan));
+            }
         }
     }
 }
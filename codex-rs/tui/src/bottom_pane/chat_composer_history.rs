@@ -31,6 +31,23 @@ pub(crate) struct ChatComposerHistory {
     /// history navigation. Used to decide if further Up/Down presses should be
     /// treated as navigation versus normal cursor movement.
     last_history_text: Option<String>,
+
+    /// Incremental reverse search (Ctrl-R) in progress, if any.
+    search: Option<HistorySearch>,
+}
+
+/// State of a Ctrl-R search. The composer shows the current match while the
+/// user types the query.
+struct HistorySearch {
+    query: String,
+    /// Composer text when the search started, restored on cancel.
+    original: String,
+    /// Index of the shown match in the combined history.
+    matched: Option<usize>,
+    /// A search of the persistent history for `query` is in flight.
+    pending: bool,
+    /// The last search found nothing.
+    failed: bool,
 }
 
 impl ChatComposerHistory {
@@ -42,6 +59,7 @@ impl ChatComposerHistory {
             fetched_history: HashMap::new(),
             history_cursor: None,
             last_history_text: None,
+            search: None,
         }
     }
 
@@ -53,6 +71,7 @@ impl ChatComposerHistory {
         self.local_history.clear();
         self.history_cursor = None;
         self.last_history_text = None;
+        self.search = None;
     }
 
     /// Record a message submitted by the user in the current session so it can
@@ -160,10 +179,158 @@ impl ChatComposerHistory {
         false
     }
 
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// The search prompt to show while a Ctrl-R search is in progress, in the
+    /// style of readline.
+    pub fn search_prompt(&self) -> Option<String> {
+        self.search.as_ref().map(|search| {
+            let failing = if search.failed { "failing " } else { "" };
+            format!("({failing}reverse-i-search)`{}'", search.query)
+        })
+    }
+
+    /// Handle Ctrl-R: start a search, or look for an older match of the
+    /// current query.
+    pub fn search_older(&mut self, textarea: &mut TextArea, app_event_tx: &AppEventSender) {
+        match &self.search {
+            Some(search) => {
+                if let Some(matched) = search.matched {
+                    self.run_search(matched, textarea, app_event_tx);
+                }
+            }
+            None => {
+                self.search = Some(HistorySearch {
+                    query: String::new(),
+                    original: textarea.lines().join("\n"),
+                    matched: None,
+                    pending: false,
+                    failed: false,
+                });
+            }
+        }
+    }
+
+    /// Extend the query by `c`. The current match is kept if it still
+    /// matches.
+    pub fn search_push(&mut self, c: char, textarea: &mut TextArea, app_event_tx: &AppEventSender) {
+        let total = self.total_entries();
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.query.push(c);
+        let before = search.matched.map_or(total, |matched| matched + 1);
+        self.run_search(before, textarea, app_event_tx);
+    }
+
+    /// Shorten the query by one character and search again from the newest
+    /// entry.
+    pub fn search_pop(&mut self, textarea: &mut TextArea, app_event_tx: &AppEventSender) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.query.pop();
+        self.run_search(self.total_entries(), textarea, app_event_tx);
+    }
+
+    /// End the search, keeping the match in the composer. Up/Down continue
+    /// from the match.
+    pub fn accept_search(&mut self) {
+        if let Some(matched) = self.search.take().and_then(|search| search.matched) {
+            self.history_cursor = Some(matched as isize);
+        }
+    }
+
+    /// End the search and restore the text it started from.
+    pub fn cancel_search(&mut self, textarea: &mut TextArea) {
+        if let Some(search) = self.search.take() {
+            self.replace_textarea_content(textarea, &search.original);
+            self.history_cursor = None;
+            self.last_history_text = None;
+        }
+    }
+
+    /// Integrate a SearchHistoryResponse event.
+    pub fn on_search_response(
+        &mut self,
+        log_id: u64,
+        query: &str,
+        found: Option<(usize, String)>,
+        textarea: &mut TextArea,
+    ) -> bool {
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+        if self.history_log_id != Some(log_id) || !search.pending || search.query != query {
+            return false;
+        }
+        search.pending = false;
+        match found {
+            Some((offset, text)) => {
+                search.matched = Some(offset);
+                search.failed = false;
+                self.fetched_history.insert(offset, text.clone());
+                self.replace_textarea_content(textarea, &text);
+            }
+            None => search.failed = true,
+        }
+        true
+    }
+
     // ---------------------------------------------------------------------
     // Internal helpers
     // ---------------------------------------------------------------------
 
+    fn total_entries(&self) -> usize {
+        self.history_entry_count + self.local_history.len()
+    }
+
+    /// Show the newest entry before `before` that contains the query. Local
+    /// entries are searched here; older ones are asked of the agent, which
+    /// answers with a SearchHistoryResponse event.
+    fn run_search(
+        &mut self,
+        before: usize,
+        textarea: &mut TextArea,
+        app_event_tx: &AppEventSender,
+    ) {
+        let before = before.min(self.total_entries());
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.pending = false;
+        search.failed = false;
+        if search.query.is_empty() {
+            search.matched = None;
+            return;
+        }
+        let query = search.query.to_lowercase();
+        let local_match = (self.history_entry_count..before).rev().find(|&idx| {
+            self.local_history[idx - self.history_entry_count]
+                .to_lowercase()
+                .contains(&query)
+        });
+        if let Some(idx) = local_match {
+            search.matched = Some(idx);
+            let text = self.local_history[idx - self.history_entry_count].clone();
+            self.replace_textarea_content(textarea, &text);
+            return;
+        }
+        match self.history_log_id {
+            Some(log_id) if before.min(self.history_entry_count) > 0 => {
+                search.pending = true;
+                app_event_tx.send(AppEvent::CodexOp(Op::SearchHistoryRequest {
+                    query: search.query.clone(),
+                    before: before.min(self.history_entry_count),
+                    log_id,
+                }));
+            }
+            _ => search.failed = true,
+        }
+    }
+
     fn populate_history_at_index(
         &mut self,
         global_idx: usize,
@@ -260,4 +427,62 @@ mod tests {
         history.on_entry_response(1, 1, Some("older".into()), &mut textarea);
         assert_eq!(textarea.lines().join("\n"), "older");
     }
+
+    #[test]
+    fn reverse_search_asks_for_older_matches() {
+        let (tx, rx) = channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+
+        let mut history = ChatComposerHistory::new();
+        history.set_metadata(1, 5);
+        history.record_local_submission("cargo build");
+        history.record_local_submission("git status");
+
+        let mut textarea = TextArea::default();
+        let _ = textarea.insert_str("draft");
+        history.search_older(&mut textarea, &tx);
+        for c in "Cargo".chars() {
+            history.search_push(c, &mut textarea, &tx);
+        }
+        // Entries of this session are searched without asking the agent.
+        assert_eq!(textarea.lines().join("\n"), "cargo build");
+        assert!(rx.try_recv().is_err());
+        assert_eq!(
+            history.search_prompt().as_deref(),
+            Some("(reverse-i-search)`Cargo'")
+        );
+
+        history.search_older(&mut textarea, &tx);
+        let AppEvent::CodexOp(request) = rx.try_recv().expect("expected a search request") else {
+            panic!("unexpected event variant");
+        };
+        assert_eq!(
+            Op::SearchHistoryRequest {
+                query: "Cargo".into(),
+                before: 5,
+                log_id: 1
+            },
+            request
+        );
+        assert!(history.on_search_response(
+            1,
+            "Cargo",
+            Some((3, "cargo test".into())),
+            &mut textarea
+        ));
+        assert_eq!(textarea.lines().join("\n"), "cargo test");
+
+        history.search_older(&mut textarea, &tx);
+        rx.try_recv().expect("expected a second search request");
+        assert!(history.on_search_response(1, "Cargo", None, &mut textarea));
+        assert_eq!(
+            history.search_prompt().as_deref(),
+            Some("(failing reverse-i-search)`Cargo'")
+        );
+        assert_eq!(textarea.lines().join("\n"), "cargo test");
+
+        history.cancel_search(&mut textarea);
+        assert!(!history.is_searching());
+        assert_eq!(textarea.lines().join("\n"), "draft");
+    }
 }
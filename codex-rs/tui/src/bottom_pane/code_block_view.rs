@@ -0,0 +1,161 @@
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+
+use super::BottomPane;
+use super::BottomPaneView;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::markdown_code_blocks::CodeBlock;
+
+/// Blocks listed at once; also the highest number that can be typed.
+const MAX_VISIBLE_BLOCKS: usize = 9;
+
+/// Lists the code blocks of the latest agent message and copies the chosen
+/// one to the clipboard.
+pub(crate) struct CodeBlockView {
+    blocks: Vec<CodeBlock>,
+    selected: usize,
+    app_event_tx: AppEventSender,
+    done: bool,
+}
+
+impl CodeBlockView {
+    pub fn new(blocks: Vec<CodeBlock>, app_event_tx: AppEventSender) -> Self {
+        Self {
+            blocks,
+            selected: 0,
+            app_event_tx,
+            done: false,
+        }
+    }
+
+    fn copy(&mut self, idx: usize) {
+        if let Some(block) = self.blocks.get(idx) {
+            self.app_event_tx
+                .send(AppEvent::CopyToClipboard(block.code.clone()));
+            self.done = true;
+        }
+    }
+}
+
+impl<'a> BottomPaneView<'a> for CodeBlockView {
+    fn handle_key_event(&mut self, pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Char(c @ '1'..='9') => self.copy(c as usize - '1' as usize),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1) % self.blocks.len().max(1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self
+                    .selected
+                    .checked_sub(1)
+                    .unwrap_or(self.blocks.len().saturating_sub(1));
+            }
+            KeyCode::Enter => self.copy(self.selected),
+            KeyCode::Esc => self.done = true,
+            _ => {}
+        }
+        pane.request_redraw();
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    fn calculate_required_height(&self, _area: &Rect) -> u16 {
+        self.blocks.len().clamp(1, MAX_VISIBLE_BLOCKS) as u16 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let first = (self.selected + 1).saturating_sub(MAX_VISIBLE_BLOCKS);
+        let lines: Vec<Line> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(MAX_VISIBLE_BLOCKS)
+            .map(|(idx, block)| {
+                let first_line = block.code.lines().next().unwrap_or_default().trim();
+                let line_count = block.code.lines().count();
+                let mut spans = vec![format!("{}. ", idx + 1).dim()];
+                if let Some(language) = &block.language {
+                    spans.push(format!("[{language}] ").cyan());
+                }
+                spans.push(first_line.to_string().into());
+                spans.push(
+                    format!(
+                        " ({line_count} line{})",
+                        if line_count == 1 { "" } else { "s" }
+                    )
+                    .dim(),
+                );
+                let line = Line::from(spans);
+                if idx == self.selected {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title("Copy code block (1-9 or Enter to copy, Esc to cancel)")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bottom_pane::BottomPaneParams;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn number_keys_copy_the_block() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            composer_max_rows: 3,
+            show_status_line: false,
+        });
+        let block = |code: &str| CodeBlock {
+            language: None,
+            code: code.to_string(),
+        };
+        let mut view = CodeBlockView::new(vec![block("one"), block("two")], app_event_tx);
+
+        view.handle_key_event(
+            &mut pane,
+            KeyEvent::new(KeyCode::Char('3'), KeyModifiers::NONE),
+        );
+        assert!(!view.is_complete());
+
+        view.handle_key_event(
+            &mut pane,
+            KeyEvent::new(KeyCode::Char('2'), KeyModifiers::NONE),
+        );
+        assert!(view.is_complete());
+        assert!(
+            rx.try_iter()
+                .any(|event| event == AppEvent::CopyToClipboard("two".to_string()))
+        );
+    }
+}
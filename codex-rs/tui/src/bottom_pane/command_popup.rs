@@ -1,10 +1,15 @@
 use std::collections::HashMap;
 
+use codex_core::protocol::CustomPrompt;
+use codex_core::protocol::McpPromptInfo;
+use codex_core::protocol::PromptScope;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
 use ratatui::style::Style;
 use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
 use ratatui::widgets::Block;
 use ratatui::widgets::BorderType;
 use ratatui::widgets::Borders;
@@ -16,6 +21,7 @@ use ratatui::widgets::WidgetRef;
 
 use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
+use crate::slash_command::mcp_prompt_command;
 
 const MAX_POPUP_ROWS: usize = 5;
 /// Ideally this is enough to show the longest command name.
@@ -23,9 +29,64 @@ const FIRST_COLUMN_WIDTH: u16 = 20;
 
 use ratatui::style::Modifier;
 
+/// An entry of the popup: a built-in command, a prompt template of an MCP
+/// server (`/mcp:<server>:<prompt>`) or a custom prompt (`/<name>`).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CommandItem {
+    Builtin(SlashCommand),
+    McpPrompt(McpPromptInfo),
+    CustomPrompt(CustomPrompt),
+}
+
+impl CommandItem {
+    /// Command string without the leading '/'.
+    pub(crate) fn command(&self) -> String {
+        match self {
+            CommandItem::Builtin(cmd) => cmd.command().to_string(),
+            CommandItem::McpPrompt(info) => mcp_prompt_command(info),
+            CommandItem::CustomPrompt(prompt) => prompt.name.clone(),
+        }
+    }
+
+    /// Where a prompt comes from, shown before its description.
+    fn badge(&self) -> Option<&'static str> {
+        match self {
+            CommandItem::Builtin(_) => None,
+            CommandItem::McpPrompt(_) => Some("mcp"),
+            CommandItem::CustomPrompt(prompt) => Some(match prompt.scope {
+                PromptScope::Project => "project",
+                PromptScope::User => "user",
+            }),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            CommandItem::Builtin(cmd) => cmd.description().to_string(),
+            CommandItem::McpPrompt(info) => info
+                .prompt
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Prompt of the MCP server `{}`.", info.server)),
+            CommandItem::CustomPrompt(prompt) => {
+                let description = prompt
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("Custom prompt {}.", prompt.path.display()));
+                match &prompt.argument_hint {
+                    Some(hint) => format!("{description} Arguments: {hint}"),
+                    None => description,
+                }
+            }
+        }
+    }
+}
+
 pub(crate) struct CommandPopup {
     command_filter: String,
     all_commands: HashMap<&'static str, SlashCommand>,
+    mcp_prompts: Vec<McpPromptInfo>,
+    custom_prompts: Vec<CustomPrompt>,
     selected_idx: Option<usize>,
 }
 
@@ -36,27 +97,120 @@ mod tests {
 
     #[test]
     fn filter_inspect_env_in_command_popup() {
-        let mut popup = CommandPopup::new();
+        let mut popup = CommandPopup::new(Vec::new(), Vec::new());
         popup.on_composer_text_change("/inspect-env".to_string());
-        let filtered: Vec<&SlashCommand> = popup.filtered_commands();
+        let filtered: Vec<CommandItem> = popup.filtered_commands();
         // Ensure InspectEnv command is among filtered results
-        assert!(filtered.contains(&&SlashCommand::InspectEnv));
+        assert!(filtered.contains(&CommandItem::Builtin(SlashCommand::InspectEnv)));
     }
 
     #[test]
     fn select_inspect_env_as_selected_command() {
-        let mut popup = CommandPopup::new();
+        let mut popup = CommandPopup::new(Vec::new(), Vec::new());
         popup.on_composer_text_change("/inspect-env".to_string());
         popup.selected_idx = Some(0);
-        assert_eq!(popup.selected_command(), Some(&SlashCommand::InspectEnv));
+        assert_eq!(
+            popup.selected_command(),
+            Some(CommandItem::Builtin(SlashCommand::InspectEnv))
+        );
+    }
+
+    #[test]
+    fn filter_mcp_prompts_by_server_and_name() {
+        let info = McpPromptInfo {
+            server: "github".to_string(),
+            prompt: mcp_types::Prompt {
+                arguments: None,
+                description: None,
+                name: "triage".to_string(),
+            },
+        };
+        let mut popup = CommandPopup::new(vec![info.clone()], Vec::new());
+        popup.on_composer_text_change("/mcp:git".to_string());
+        assert_eq!(
+            popup.filtered_commands(),
+            vec![CommandItem::McpPrompt(info)]
+        );
+        assert_eq!(popup.filtered_commands()[0].command(), "mcp:github:triage");
+    }
+
+    #[test]
+    fn fuzzy_matches_rank_prefixes_first() {
+        let prompt = |name: &str, scope: PromptScope| CustomPrompt {
+            name: name.to_string(),
+            path: format!("/repo/.codex/prompts/{name}.md").into(),
+            scope,
+            content: "Fix $ARGUMENTS".to_string(),
+            description: None,
+            argument_hint: None,
+            model: None,
+            approval: None,
+            allow_shell: false,
+        };
+        let mut popup = CommandPopup::new(
+            Vec::new(),
+            vec![
+                prompt("fix-flaky-test", PromptScope::Project),
+                prompt("flaky", PromptScope::User),
+            ],
+        );
+        popup.on_composer_text_change("/flaky".to_string());
+        assert_eq!(
+            popup.filtered_commands(),
+            vec![
+                CommandItem::CustomPrompt(prompt("flaky", PromptScope::User)),
+                CommandItem::CustomPrompt(prompt("fix-flaky-test", PromptScope::Project)),
+            ]
+        );
+        popup.on_composer_text_change("/fft".to_string());
+        let filtered = popup.filtered_commands();
+        assert_eq!(filtered[0].command(), "fix-flaky-test");
+        assert_eq!(filtered[0].badge(), Some("project"));
+    }
+
+    #[test]
+    fn custom_prompts_do_not_shadow_builtins() {
+        let prompt = |name: &str| CustomPrompt {
+            name: name.to_string(),
+            path: format!("/home/user/.codex/prompts/{name}.md").into(),
+            scope: PromptScope::User,
+            content: "Review $ARGUMENTS".to_string(),
+            description: Some("Review a pull request".to_string()),
+            argument_hint: Some("<pr-number>".to_string()),
+            model: None,
+            approval: None,
+            allow_shell: false,
+        };
+        let mut popup = CommandPopup::new(Vec::new(), vec![prompt("review"), prompt("review-pr")]);
+        popup.on_composer_text_change("/review".to_string());
+        let filtered = popup.filtered_commands();
+        assert_eq!(
+            filtered,
+            vec![
+                CommandItem::Builtin(SlashCommand::Review),
+                CommandItem::CustomPrompt(prompt("review-pr")),
+            ]
+        );
+        assert_eq!(
+            filtered[1].description(),
+            "Review a pull request Arguments: <pr-number>"
+        );
     }
 }
 
 impl CommandPopup {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(mcp_prompts: Vec<McpPromptInfo>, custom_prompts: Vec<CustomPrompt>) -> Self {
+        let all_commands = built_in_slash_commands();
+        // The built-in commands take precedence over custom prompts.
+        let custom_prompts = custom_prompts
+            .into_iter()
+            .filter(|prompt| !all_commands.contains_key(prompt.name.as_str()))
+            .collect();
         Self {
             command_filter: String::new(),
-            all_commands: built_in_slash_commands(),
+            all_commands,
+            mcp_prompts,
+            custom_prompts,
             selected_idx: None,
         }
     }
@@ -104,26 +258,32 @@ impl CommandPopup {
         row_count + 2
     }
 
-    /// Return the list of commands that match the current filter. Matching is
-    /// performed using a *prefix* comparison on the command name.
-    fn filtered_commands(&self) -> Vec<&SlashCommand> {
-        let mut cmds: Vec<&SlashCommand> = self
+    /// Return the list of commands that match the current filter, best
+    /// match first; see [`fuzzy_score`]. Commands that match equally well
+    /// are sorted alphabetically so the order is stable and predictable.
+    fn filtered_commands(&self) -> Vec<CommandItem> {
+        let filter = self.command_filter.to_ascii_lowercase();
+        let mut cmds: Vec<(i32, String, CommandItem)> = self
             .all_commands
             .values()
-            .filter(|cmd| {
-                if self.command_filter.is_empty() {
-                    true
-                } else {
-                    cmd.command()
-                        .starts_with(&self.command_filter.to_ascii_lowercase())
-                }
+            .map(|cmd| CommandItem::Builtin(*cmd))
+            .chain(self.mcp_prompts.iter().cloned().map(CommandItem::McpPrompt))
+            .chain(
+                self.custom_prompts
+                    .iter()
+                    .cloned()
+                    .map(CommandItem::CustomPrompt),
+            )
+            .filter_map(|cmd| {
+                let command = cmd.command();
+                let score = fuzzy_score(&command.to_ascii_lowercase(), &filter)?;
+                Some((score, command, cmd))
             })
             .collect();
-
-        // Sort the commands alphabetically so the order is stable and
-        // predictable.
-        cmds.sort_by(|a, b| a.command().cmp(b.command()));
-        cmds
+        cmds.sort_by(|(a_score, a, _), (b_score, b, _)| {
+            b_score.cmp(a_score).then_with(|| a.cmp(b))
+        });
+        cmds.into_iter().map(|(_, _, cmd)| cmd).collect()
     }
 
     /// Move the selection cursor one step up.
@@ -163,19 +323,66 @@ impl CommandPopup {
     }
 
     /// Return currently selected command, if any.
-    pub(crate) fn selected_command(&self) -> Option<&SlashCommand> {
-        let matches = self.filtered_commands();
-        self.selected_idx.and_then(|idx| matches.get(idx).copied())
+    pub(crate) fn selected_command(&self) -> Option<CommandItem> {
+        let mut matches = self.filtered_commands();
+        self.selected_idx
+            .filter(|&idx| idx < matches.len())
+            .map(|idx| matches.swap_remove(idx))
     }
 }
 
+/// How well `command` matches `filter`, both lower-case, or `None` if the
+/// characters of `filter` do not appear in `command` in order. Prefix
+/// matches rank first, the closer to the whole command the better, then
+/// other substrings, then scattered characters; characters that follow a
+/// `-`, `:` or `_` or the previous match score higher.
+fn fuzzy_score(command: &str, filter: &str) -> Option<i32> {
+    if filter.is_empty() {
+        return Some(0);
+    }
+    if command.starts_with(filter) {
+        return Some(2000 - (command.len() - filter.len()) as i32);
+    }
+    let is_boundary = |i: usize| command[..i].ends_with(['-', ':', '_']);
+    if let Some(start) = command.find(filter) {
+        let boundary_bonus = if is_boundary(start) { 100 } else { 0 };
+        return Some(1000 + boundary_bonus - start as i32);
+    }
+    let mut score = 0;
+    let mut filter_chars = filter.chars().peekable();
+    let mut previous_match: Option<usize> = None;
+    for (i, c) in command.char_indices() {
+        let Some(&wanted) = filter_chars.peek() else {
+            break;
+        };
+        if c != wanted {
+            continue;
+        }
+        filter_chars.next();
+        score += match previous_match {
+            Some(previous) if previous + 1 == i => 10,
+            _ if is_boundary(i) => 5,
+            Some(previous) => -((i - previous) as i32),
+            None => -(i as i32),
+        };
+        previous_match = Some(i);
+    }
+    filter_chars.peek().is_none().then_some(score)
+}
+
 impl WidgetRef for CommandPopup {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let matches = self.filtered_commands();
 
         let mut rows: Vec<Row> = Vec::new();
-        let visible_matches: Vec<&SlashCommand> =
-            matches.into_iter().take(MAX_POPUP_ROWS).collect();
+        let visible_matches: Vec<CommandItem> = matches.into_iter().take(MAX_POPUP_ROWS).collect();
+        // MCP prompts can have longer names than the built-in commands.
+        let first_column_width = visible_matches
+            .iter()
+            .map(|cmd| cmd.command().len() as u16 + 2)
+            .max()
+            .unwrap_or_default()
+            .max(FIRST_COLUMN_WIDTH);
 
         if visible_matches.is_empty() {
             rows.push(Row::new(vec![
@@ -195,9 +402,14 @@ impl WidgetRef for CommandPopup {
                     (command_style, default_style)
                 };
 
+                let mut description = Vec::new();
+                if let Some(badge) = cmd.badge() {
+                    description.push(Span::from(format!("[{badge}] ")).dim());
+                }
+                description.push(Span::from(cmd.description()));
                 rows.push(Row::new(vec![
                     Cell::from(format!("/{}", cmd.command())).style(cmd_style),
-                    Cell::from(cmd.description().to_string()).style(desc_style),
+                    Cell::from(Line::from(description)).style(desc_style),
                 ]));
             }
         }
@@ -206,7 +418,7 @@ impl WidgetRef for CommandPopup {
 
         let table = Table::new(
             rows,
-            [Constraint::Length(FIRST_COLUMN_WIDTH), Constraint::Min(10)],
+            [Constraint::Length(first_column_width), Constraint::Min(10)],
         )
         .column_spacing(0)
         .block(
@@ -0,0 +1,134 @@
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+
+use super::BottomPane;
+use super::BottomPaneView;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+/// Messages listed at once; the list scrolls to keep the selection visible.
+const MAX_VISIBLE_MESSAGES: usize = 10;
+
+/// Lists the user messages of the session for `/fork`; the fork starts just
+/// before the chosen one.
+pub(crate) struct ForkPickerView {
+    messages: Vec<String>,
+    selected: usize,
+    app_event_tx: AppEventSender,
+    done: bool,
+}
+
+impl ForkPickerView {
+    pub fn new(messages: Vec<String>, app_event_tx: AppEventSender) -> Self {
+        Self {
+            // Forking usually means redoing the latest request differently.
+            selected: messages.len().saturating_sub(1),
+            messages,
+            app_event_tx,
+            done: false,
+        }
+    }
+}
+
+impl<'a> BottomPaneView<'a> for ForkPickerView {
+    fn handle_key_event(&mut self, pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        let len = self.messages.len().max(1);
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1) % len,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.checked_sub(1).unwrap_or(len - 1);
+            }
+            KeyCode::Enter => {
+                if let Some(message) = self.messages.get(self.selected) {
+                    self.app_event_tx.send(AppEvent::ForkSession {
+                        before_user_message: self.selected,
+                        message: message.clone(),
+                    });
+                }
+                self.done = true;
+            }
+            KeyCode::Esc => self.done = true,
+            _ => {}
+        }
+        pane.request_redraw();
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    fn calculate_required_height(&self, _area: &Rect) -> u16 {
+        self.messages.len().clamp(1, MAX_VISIBLE_MESSAGES) as u16 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let first = (self.selected + 1).saturating_sub(MAX_VISIBLE_MESSAGES);
+        let lines: Vec<Line> = self
+            .messages
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(MAX_VISIBLE_MESSAGES)
+            .map(|(idx, message)| {
+                let preview = message.lines().next().unwrap_or_default();
+                let line = Line::from(vec![format!("{:>3}. ", idx + 1).dim(), preview.into()]);
+                if idx == self.selected {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title("Fork before message (Enter to fork, Esc to cancel)")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bottom_pane::BottomPaneParams;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn enter_forks_before_the_highlighted_message() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            composer_max_rows: 3,
+            show_status_line: false,
+        });
+        let messages = vec!["fix the bug".to_string(), "now add tests".to_string()];
+        let mut view = ForkPickerView::new(messages, app_event_tx);
+        assert_eq!(view.selected, 1);
+
+        view.handle_key_event(&mut pane, KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        view.handle_key_event(&mut pane, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(view.is_complete());
+        assert!(rx.try_iter().any(|event| event
+            == AppEvent::ForkSession {
+                before_user_message: 0,
+                message: "fix the bug".to_string(),
+            }));
+    }
+}
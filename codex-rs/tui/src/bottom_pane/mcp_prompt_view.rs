@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use codex_core::protocol::McpPromptInfo;
+use codex_core::protocol::Op;
+use crossterm::event::Event as CrosstermEvent;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use mcp_types::PromptArgument;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+use super::BottomPane;
+use super::BottomPaneView;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::slash_command::mcp_prompt_command;
+
+/// Asks for the arguments of an MCP prompt, one per line (Enter moves to
+/// the next), then has the server render the prompt.
+pub(crate) struct McpPromptView {
+    info: McpPromptInfo,
+    arguments: Vec<PromptArgument>,
+    inputs: Vec<Input>,
+    current: usize,
+    /// Set when Enter is pressed on an empty required argument.
+    missing_required: bool,
+    app_event_tx: AppEventSender,
+    done: bool,
+}
+
+impl McpPromptView {
+    pub fn new(info: McpPromptInfo, app_event_tx: AppEventSender) -> Self {
+        let arguments = info.prompt.arguments.clone().unwrap_or_default();
+        Self {
+            inputs: vec![Input::default(); arguments.len()],
+            arguments,
+            info,
+            current: 0,
+            missing_required: false,
+            app_event_tx,
+            done: false,
+        }
+    }
+
+    fn submit(&mut self) {
+        let arguments: HashMap<String, String> = self
+            .arguments
+            .iter()
+            .zip(&self.inputs)
+            .filter(|(_, input)| !input.value().is_empty())
+            .map(|(argument, input)| (argument.name.clone(), input.value().to_string()))
+            .collect();
+        self.app_event_tx.send(AppEvent::CodexOp(Op::GetMcpPrompt {
+            server: self.info.server.clone(),
+            name: self.info.prompt.name.clone(),
+            arguments,
+        }));
+        self.done = true;
+    }
+}
+
+impl<'a> BottomPaneView<'a> for McpPromptView {
+    fn handle_key_event(&mut self, pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => self.done = true,
+            KeyCode::Enter => {
+                let required = self.arguments[self.current].required == Some(true);
+                if required && self.inputs[self.current].value().is_empty() {
+                    self.missing_required = true;
+                } else if self.current + 1 < self.inputs.len() {
+                    self.missing_required = false;
+                    self.current += 1;
+                } else {
+                    self.submit();
+                }
+            }
+            KeyCode::Up | KeyCode::BackTab => {
+                self.missing_required = false;
+                self.current = self.current.saturating_sub(1);
+            }
+            _ => {
+                self.inputs[self.current].handle_event(&CrosstermEvent::Key(key_event));
+            }
+        }
+        pane.request_redraw();
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    fn calculate_required_height(&self, _area: &Rect) -> u16 {
+        // One line per argument, the description of the current one and the
+        // border.
+        self.arguments.len() as u16 + 1 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = self
+            .arguments
+            .iter()
+            .zip(&self.inputs)
+            .enumerate()
+            .map(|(idx, (argument, input))| {
+                let marker = if idx == self.current { "> " } else { "  " };
+                let required = if argument.required == Some(true) {
+                    "*"
+                } else {
+                    ""
+                };
+                Line::from(vec![
+                    format!("{marker}{}{required}: ", argument.name).bold(),
+                    input.value().to_string().into(),
+                ])
+            })
+            .collect();
+        let hint = if self.missing_required {
+            format!("{} is required", self.arguments[self.current].name).red()
+        } else {
+            self.arguments[self.current]
+                .description
+                .clone()
+                .unwrap_or_default()
+                .dim()
+        };
+        lines.push(Line::from(hint));
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "/{} (Enter for the next argument, Esc to cancel)",
+                        mcp_prompt_command(&self.info)
+                    ))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bottom_pane::BottomPaneParams;
+    use crossterm::event::KeyModifiers;
+
+    fn argument(name: &str, required: bool) -> PromptArgument {
+        PromptArgument {
+            description: None,
+            name: name.to_string(),
+            required: Some(required),
+        }
+    }
+
+    #[test]
+    fn submits_the_arguments_after_the_last_one() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            composer_max_rows: 3,
+            show_status_line: false,
+        });
+        let info = McpPromptInfo {
+            server: "github".to_string(),
+            prompt: mcp_types::Prompt {
+                arguments: Some(vec![argument("issue", true), argument("tone", false)]),
+                description: None,
+                name: "triage".to_string(),
+            },
+        };
+        let mut view = McpPromptView::new(info, app_event_tx);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        // The required argument cannot be skipped.
+        view.handle_key_event(&mut pane, key(KeyCode::Enter));
+        assert!(view.missing_required);
+        view.handle_key_event(&mut pane, key(KeyCode::Char('7')));
+        view.handle_key_event(&mut pane, key(KeyCode::Enter));
+        view.handle_key_event(&mut pane, key(KeyCode::Enter));
+        assert!(view.is_complete());
+
+        let expected = AppEvent::CodexOp(Op::GetMcpPrompt {
+            server: "github".to_string(),
+            name: "triage".to_string(),
+            arguments: HashMap::from([("issue".to_string(), "7".to_string())]),
+        });
+        assert!(rx.try_iter().any(|event| event == expected));
+    }
+}
@@ -1,7 +1,17 @@
 //! Bottom pane: shows the ChatComposer or a BottomPaneView, if one is active.
 
+use std::path::Path;
+use std::path::PathBuf;
+
 use bottom_pane_view::BottomPaneView;
 use bottom_pane_view::ConditionalUpdate;
+use codex_core::model_catalog::ModelCatalog;
+use codex_core::protocol::CustomPrompt;
+use codex_core::protocol::McpPromptInfo;
+use codex_core::protocol::PlanUpdateEvent;
+use codex_core::protocol::ProjectDocProposalEvent;
+use codex_core::protocol::ReviewFinding;
+use codex_core::protocol::SandboxPolicy;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -9,28 +19,50 @@ use ratatui::widgets::WidgetRef;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::conversation_history_widget::TranscriptLine;
+use crate::markdown_code_blocks::CodeBlock;
 use crate::user_approval_widget::ApprovalRequest;
 
 mod approval_modal_view;
 mod bottom_pane_view;
 mod chat_composer;
 mod chat_composer_history;
+mod code_block_view;
 mod command_popup;
 mod config_reload_view;
+mod fork_picker_view;
 mod inspect_env_view;
+mod mcp_prompt_view;
+mod model_picker_view;
 mod mount_view;
+mod plan_widget;
+mod project_doc_view;
+mod queued_messages;
+mod review_findings_view;
+mod search_view;
 mod shell_command_view;
 mod status_indicator_view;
+mod status_line;
 
 pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
 
 use approval_modal_view::ApprovalModalView;
+use code_block_view::CodeBlockView;
 use config_reload_view::ConfigReloadView;
+use fork_picker_view::ForkPickerView;
 use inspect_env_view::InspectEnvView;
+use mcp_prompt_view::McpPromptView;
+use model_picker_view::ModelPickerView;
 use mount_view::{MountAddView, MountRemoveView};
+use plan_widget::PlanWidget;
+use project_doc_view::ProjectDocView;
+use queued_messages::QueuedMessages;
+use review_findings_view::ReviewFindingsView;
+use search_view::SearchView;
 use shell_command_view::ShellCommandView;
 use status_indicator_view::StatusIndicatorView;
+use status_line::StatusLine;
 
 /// Pane displayed in the lower half of the chat UI.
 pub(crate) struct BottomPane<'a> {
@@ -41,6 +73,17 @@ pub(crate) struct BottomPane<'a> {
     /// If present, this is displayed instead of the `composer`.
     active_view: Option<Box<dyn BottomPaneView<'a> + 'a>>,
 
+    /// The model's `update_plan` steps, shown below the composer or view.
+    plan: PlanWidget,
+
+    /// Messages waiting for the running turn to complete, shown above the
+    /// composer or view.
+    queued: QueuedMessages,
+
+    /// Model, sandbox, turn time and context left, shown on the last row
+    /// unless `tui.status_line` is off.
+    status_line: Option<StatusLine>,
+
     app_event_tx: AppEventSender,
     has_input_focus: bool,
     is_task_running: bool,
@@ -51,23 +94,55 @@ pub(crate) struct BottomPaneParams {
     pub(crate) has_input_focus: bool,
     /// Maximum number of visible lines in the chat input composer.
     pub(crate) composer_max_rows: usize,
+    /// Whether to show the status line below the composer.
+    pub(crate) show_status_line: bool,
 }
 
-impl BottomPane<'_> {
+impl<'a> BottomPane<'a> {
     pub fn new(params: BottomPaneParams) -> Self {
+        let mut composer = ChatComposer::new(
+            params.has_input_focus,
+            params.app_event_tx.clone(),
+            params.composer_max_rows,
+        );
+        // The status line shows the context left instead.
+        composer.set_show_context_left(!params.show_status_line);
         Self {
-            composer: ChatComposer::new(
-                params.has_input_focus,
-                params.app_event_tx.clone(),
-                params.composer_max_rows,
-            ),
+            composer,
             active_view: None,
+            plan: PlanWidget::default(),
+            queued: QueuedMessages::default(),
+            status_line: params.show_status_line.then(StatusLine::default),
             app_event_tx: params.app_event_tx,
             has_input_focus: params.has_input_focus,
             is_task_running: false,
         }
     }
 
+    /// Forward a left click at (`column`, `row`) to the active view, which is
+    /// rendered in `area`. The composer does not handle clicks.
+    pub(crate) fn handle_mouse_click(&mut self, area: Rect, column: u16, row: u16) {
+        if let Some(mut view) = self.active_view.take() {
+            view.handle_mouse_click(self, area, column, row);
+            self.restore_active_view(view);
+        }
+    }
+
+    /// Put `view` back after it handled input, or replace it with the status
+    /// indicator once it is complete.
+    fn restore_active_view(&mut self, view: Box<dyn BottomPaneView<'a> + 'a>) {
+        if !view.is_complete() {
+            self.active_view = Some(view);
+        } else if self.is_task_running {
+            let height = self.composer.calculate_required_height(&Rect::default());
+            self.active_view = Some(Box::new(StatusIndicatorView::new(
+                self.app_event_tx.clone(),
+                height,
+            )));
+        }
+        self.request_redraw();
+    }
+
     /// Forward a key event to the active view or the composer.
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> InputResult {
         if let Some(mut view) = self.active_view.take() {
@@ -82,16 +157,7 @@ impl BottomPane<'_> {
                 return input_result;
             }
             view.handle_key_event(self, key_event);
-            if !view.is_complete() {
-                self.active_view = Some(view);
-            } else if self.is_task_running {
-                let height = self.composer.calculate_required_height(&Rect::default());
-                self.active_view = Some(Box::new(StatusIndicatorView::new(
-                    self.app_event_tx.clone(),
-                    height,
-                )));
-            }
-            self.request_redraw();
+            self.restore_active_view(view);
             InputResult::None
         } else {
             let (input_result, needs_redraw) = self.composer.handle_key_event(key_event);
@@ -125,6 +191,9 @@ impl BottomPane<'_> {
 
     pub fn set_task_running(&mut self, running: bool) {
         self.is_task_running = running;
+        if let Some(status_line) = &mut self.status_line {
+            status_line.set_turn_running(running);
+        }
 
         match (running, self.active_view.is_some()) {
             (true, false) => {
@@ -153,9 +222,59 @@ impl BottomPane<'_> {
         }
     }
 
-    /// Update the context-left percentage displayed in the composer.
+    /// Update the context-left percentage displayed in the status line, or
+    /// in the composer without one.
     pub fn set_context_percent(&mut self, pct: f64) {
         self.composer.set_context_left(pct);
+        if let Some(status_line) = &mut self.status_line {
+            status_line.set_context_left(pct);
+        }
+        self.request_redraw();
+    }
+
+    /// Show the session's model and sandbox in the status line.
+    pub(crate) fn set_status_session(&mut self, model: &str, sandbox: &SandboxPolicy, cwd: &Path) {
+        if let Some(status_line) = &mut self.status_line {
+            status_line.set_session(model, sandbox, cwd);
+        }
+    }
+
+    /// Update the session token budget shown in the composer footer.
+    pub fn set_token_budget(&mut self, used: u64, max: u64) {
+        self.composer.set_token_budget(used, max);
+        self.request_redraw();
+    }
+
+    pub fn set_session_cost(&mut self, cost_usd: f64) {
+        self.composer.set_session_cost(cost_usd);
+        self.request_redraw();
+    }
+
+    /// Update the background processes listed in the composer footer.
+    pub fn set_background_processes(&mut self, commands: Vec<String>) {
+        self.composer.set_background_processes(commands);
+        self.request_redraw();
+    }
+
+    pub fn set_plan(&mut self, update: PlanUpdateEvent) {
+        self.plan.set_plan(update);
+        self.request_redraw();
+    }
+
+    pub fn set_queued_messages(&mut self, texts: Vec<String>) {
+        self.queued.set_messages(texts);
+        self.request_redraw();
+    }
+
+    /// Whether Esc should take back a queued message: the composer is empty
+    /// and has the keyboard, with no dialog or popup in front of it.
+    pub(crate) fn can_edit_queued_message(&self) -> bool {
+        self.active_view
+            .as_ref()
+            .is_none_or(|view| view.should_hide_when_task_is_done())
+            && !self.composer.is_command_popup_visible()
+            && !self.composer.is_history_search_active()
+            && self.composer.get_input_text().is_empty()
     }
 
     /// Launch interactive mount-add dialog (host, container, [mode]).
@@ -193,6 +312,110 @@ impl BottomPane<'_> {
         self.request_redraw();
     }
 
+    /// Launch the `/search` view over the given transcript lines.
+    pub fn push_search(&mut self, lines: Vec<TranscriptLine>) {
+        let view = SearchView::new(lines, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+    }
+
+    /// Open the picker for copying one of `blocks`. Returns `false` without
+    /// opening it while another dialog (e.g. an approval) is shown.
+    pub(crate) fn push_code_block_picker(&mut self, blocks: Vec<CodeBlock>) -> bool {
+        if let Some(view) = self.active_view.as_mut() {
+            if !view.should_hide_when_task_is_done() {
+                return false;
+            }
+        }
+        let view = CodeBlockView::new(blocks, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+        true
+    }
+
+    /// Open the `/model` picker. Returns `false` without opening it while
+    /// another dialog is shown.
+    pub(crate) fn push_model_picker(
+        &mut self,
+        catalog: ModelCatalog,
+        current_model: String,
+    ) -> bool {
+        if let Some(view) = self.active_view.as_mut() {
+            if !view.should_hide_when_task_is_done() {
+                return false;
+            }
+        }
+        let view = ModelPickerView::new(catalog, current_model, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+        true
+    }
+
+    /// Open the `/fork` picker over the session's user messages. Returns
+    /// `false` without opening it while another dialog is shown.
+    pub(crate) fn push_fork_picker(&mut self, messages: Vec<String>) -> bool {
+        if let Some(view) = self.active_view.as_mut() {
+            if !view.should_hide_when_task_is_done() {
+                return false;
+            }
+        }
+        let view = ForkPickerView::new(messages, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+        true
+    }
+
+    /// Open the form for the arguments of an MCP prompt. Returns `false`
+    /// without opening it while another dialog is shown.
+    pub(crate) fn push_mcp_prompt_form(&mut self, info: McpPromptInfo) -> bool {
+        if let Some(view) = self.active_view.as_mut() {
+            if !view.should_hide_when_task_is_done() {
+                return false;
+            }
+        }
+        let view = McpPromptView::new(info, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+        true
+    }
+
+    pub(crate) fn push_review_findings(&mut self, findings: Vec<ReviewFinding>) -> bool {
+        if let Some(view) = self.active_view.as_mut() {
+            if !view.should_hide_when_task_is_done() {
+                return false;
+            }
+        }
+        let view = ReviewFindingsView::new(findings, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+        true
+    }
+
+    /// Show the AGENTS.md drafted by `/init` for the user to accept.
+    pub(crate) fn push_project_doc_proposal(&mut self, proposal: ProjectDocProposalEvent) {
+        let view = ProjectDocView::new(proposal, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+    }
+
+    /// The composer's draft.
+    pub(crate) fn composer_text(&self) -> String {
+        self.composer.get_input_text()
+    }
+
+    /// Replace the composer's draft with `text`.
+    pub(crate) fn set_composer_text(&mut self, text: &str) {
+        self.composer.set_input_text(text);
+        self.request_redraw();
+    }
+
+    /// Put a message that was submitted back into the composer, with the
+    /// images whose placeholders it contains.
+    pub(crate) fn restore_composer_message(&mut self, text: &str, image_paths: Vec<PathBuf>) {
+        self.composer.restore_message(text, image_paths);
+        self.request_redraw();
+    }
+
     /// Called when the agent requests user approval.
     pub fn push_approval_request(&mut self, request: ApprovalRequest) {
         let request = if let Some(view) = self.active_view.as_mut() {
@@ -215,11 +438,16 @@ impl BottomPane<'_> {
 
     /// Height (terminal rows) required by the current bottom pane.
     pub fn calculate_required_height(&self, area: &Rect) -> u16 {
-        if let Some(view) = &self.active_view {
+        let height = if let Some(view) = &self.active_view {
             view.calculate_required_height(area)
         } else {
             self.composer.calculate_required_height(area)
-        }
+        };
+        height + self.queued.height() + self.plan.height() + self.status_line_height()
+    }
+
+    fn status_line_height(&self) -> u16 {
+        u16::from(self.status_line.is_some())
     }
 
     pub(crate) fn request_redraw(&self) {
@@ -237,6 +465,28 @@ impl BottomPane<'_> {
         self.composer.set_history_metadata(log_id, entry_count);
     }
 
+    /// Offer the prompt templates of the MCP servers as slash commands.
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptInfo>) {
+        self.composer.set_mcp_prompts(prompts);
+    }
+
+    /// Insert a placeholder for the image at `path` into the composer and
+    /// send the image along with the message while the placeholder is in it.
+    pub(crate) fn attach_image(&mut self, path: PathBuf, width: u32, height: u32) {
+        self.composer.attach_image(path, width, height);
+        self.request_redraw();
+    }
+
+    /// The images attached to the message that was just submitted.
+    pub(crate) fn take_submitted_images(&mut self) -> Vec<PathBuf> {
+        self.composer.take_submitted_images()
+    }
+
+    /// Offer the custom prompts as slash commands.
+    pub(crate) fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
+        self.composer.set_custom_prompts(prompts);
+    }
+
     pub(crate) fn on_history_entry_response(
         &mut self,
         log_id: u64,
@@ -251,15 +501,71 @@ impl BottomPane<'_> {
             self.request_redraw();
         }
     }
+
+    pub(crate) fn on_history_search_response(
+        &mut self,
+        log_id: u64,
+        query: &str,
+        found: Option<(usize, String)>,
+    ) {
+        if self
+            .composer
+            .on_history_search_response(log_id, query, found)
+        {
+            self.request_redraw();
+        }
+    }
 }
 
 impl WidgetRef for &BottomPane<'_> {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        // The status line takes the last row, below everything else.
+        let status_height = self.status_line_height().min(area.height);
+        if let Some(status_line) = &self.status_line {
+            status_line.render_ref(
+                Rect {
+                    y: area.bottom() - status_height,
+                    height: status_height,
+                    ..area
+                },
+                buf,
+            );
+        }
+        let area = Rect {
+            height: area.height - status_height,
+            ..area
+        };
+        // The plan takes the bottom rows so views keep their position.
+        let plan_height = self.plan.height().min(area.height);
+        let plan_area = Rect {
+            y: area.bottom() - plan_height,
+            height: plan_height,
+            ..area
+        };
+        let area = Rect {
+            height: area.height - plan_height,
+            ..area
+        };
+        // Queued messages take the top rows, above the composer.
+        let queued_height = self.queued.height().min(area.height);
+        (&self.queued).render_ref(
+            Rect {
+                height: queued_height,
+                ..area
+            },
+            buf,
+        );
+        let area = Rect {
+            y: area.y + queued_height,
+            height: area.height - queued_height,
+            ..area
+        };
         // Always render composer, then overlay any active view (e.g., status indicator or modal)
         (&self.composer).render_ref(area, buf);
         if let Some(ov) = &self.active_view {
             ov.render(area, buf);
         }
+        (&self.plan).render_ref(plan_area, buf);
     }
 }
 
@@ -278,6 +584,7 @@ mod tests {
             app_event_tx,
             has_input_focus: true,
             composer_max_rows: 3,
+            show_status_line: false,
         })
     }
 
@@ -289,6 +596,7 @@ mod tests {
             app_event_tx,
             has_input_focus: true,
             composer_max_rows: 3,
+            show_status_line: false,
         });
         (pane, rx)
     }
@@ -376,4 +684,32 @@ mod tests {
         let content = pane.composer.get_input_text();
         assert_eq!(content, "");
     }
+
+    #[test]
+    fn search_enter_twice_jumps_to_hit() {
+        let (mut pane, rx) = make_pane_and_rx();
+        pane.push_search(vec![
+            TranscriptLine {
+                entry: 0,
+                line: 0,
+                text: "first".to_string(),
+            },
+            TranscriptLine {
+                entry: 3,
+                line: 2,
+                text: "needle here".to_string(),
+            },
+        ]);
+        for c in "needle".chars() {
+            pane.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        pane.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        pane.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let jumps: Vec<AppEvent> = rx
+            .try_iter()
+            .filter(|ev| matches!(ev, AppEvent::SearchJump { .. }))
+            .collect();
+        assert_eq!(jumps, vec![AppEvent::SearchJump { entry: 3, line: 2 }]);
+        assert!(pane.active_view.is_none());
+    }
 }
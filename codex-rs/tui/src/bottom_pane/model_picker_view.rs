@@ -0,0 +1,186 @@
+use codex_core::model_catalog::ModelCatalog;
+use codex_core::model_catalog::ModelInfo;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+
+use super::BottomPane;
+use super::BottomPaneView;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+/// Models listed at once; the list scrolls to keep the selection visible.
+const MAX_VISIBLE_MODELS: usize = 10;
+
+/// Lists the models of a [`ModelCatalog`] with their context window, pricing
+/// tier and reasoning support, and switches to the chosen one.
+pub(crate) struct ModelPickerView {
+    catalog: ModelCatalog,
+    current_model: String,
+    selected: usize,
+    app_event_tx: AppEventSender,
+    done: bool,
+}
+
+impl ModelPickerView {
+    pub fn new(catalog: ModelCatalog, current_model: String, app_event_tx: AppEventSender) -> Self {
+        let selected = catalog
+            .models
+            .iter()
+            .position(|model| model.id == current_model)
+            .unwrap_or_default();
+        Self {
+            catalog,
+            current_model,
+            selected,
+            app_event_tx,
+            done: false,
+        }
+    }
+
+    fn model_line(&self, model: &ModelInfo) -> Line<'static> {
+        let marker = if model.id == self.current_model {
+            "● "
+        } else {
+            "  "
+        };
+        let mut details = Vec::new();
+        if let Some(window) = model.context_window {
+            details.push(format!("{}k context", window / 1000));
+        }
+        if let (Some(tier), Some(pricing)) = (model.pricing_tier(), model.pricing) {
+            details.push(format!(
+                "{tier} (${:.2}/${:.2} per 1M)",
+                pricing.input, pricing.output
+            ));
+        }
+        if model.supports_reasoning {
+            details.push("reasoning".to_string());
+        }
+        let mut spans: Vec<Span<'static>> = vec![marker.into(), model.id.clone().into()];
+        if !details.is_empty() {
+            spans.push(format!("  {}", details.join(" · ")).dim());
+        }
+        Line::from(spans)
+    }
+}
+
+impl<'a> BottomPaneView<'a> for ModelPickerView {
+    fn handle_key_event(&mut self, pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        let len = self.catalog.models.len().max(1);
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1) % len,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.checked_sub(1).unwrap_or(len - 1);
+            }
+            KeyCode::Enter => {
+                if let Some(model) = self.catalog.models.get(self.selected) {
+                    if model.id != self.current_model {
+                        self.app_event_tx
+                            .send(AppEvent::SelectModel(model.id.clone()));
+                    }
+                }
+                self.done = true;
+            }
+            KeyCode::Esc => self.done = true,
+            _ => {}
+        }
+        pane.request_redraw();
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    fn calculate_required_height(&self, _area: &Rect) -> u16 {
+        let error_line = usize::from(self.catalog.list_error.is_some());
+        (self.catalog.models.len().clamp(1, MAX_VISIBLE_MODELS) + error_line) as u16 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let first = (self.selected + 1).saturating_sub(MAX_VISIBLE_MODELS);
+        let mut lines: Vec<Line> = self
+            .catalog
+            .models
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(MAX_VISIBLE_MODELS)
+            .map(|(idx, model)| {
+                let line = self.model_line(model);
+                if idx == self.selected {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+        if let Some(error) = &self.catalog.list_error {
+            lines.push(Line::from(
+                format!("Could not list the provider's models: {error}").red(),
+            ));
+        }
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title("Switch model (Enter to select, Esc to cancel)")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bottom_pane::BottomPaneParams;
+    use crossterm::event::KeyModifiers;
+
+    fn model(id: &str) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            context_window: None,
+            pricing: None,
+            supports_reasoning: false,
+        }
+    }
+
+    #[test]
+    fn enter_selects_the_highlighted_model() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            composer_max_rows: 3,
+            show_status_line: false,
+        });
+        let catalog = ModelCatalog {
+            models: vec![model("gpt-4.1"), model("o3"), model("o4-mini")],
+            list_error: None,
+        };
+        let mut view = ModelPickerView::new(catalog, "o3".to_string(), app_event_tx);
+        assert_eq!(view.selected, 1);
+
+        view.handle_key_event(&mut pane, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        view.handle_key_event(&mut pane, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(view.is_complete());
+        assert!(
+            rx.try_iter()
+                .any(|event| event == AppEvent::SelectModel("o4-mini".to_string()))
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use codex_core::protocol::PlanUpdateEvent;
+use codex_core::protocol::StepStatus;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+
+/// Steps listed at once; the list starts just before the first unfinished
+/// step so the one in progress stays visible.
+const MAX_VISIBLE_STEPS: usize = 6;
+
+/// The plan the model keeps with `update_plan`, shown below the composer
+/// until the conversation ends.
+#[derive(Default)]
+pub(crate) struct PlanWidget {
+    update: PlanUpdateEvent,
+}
+
+impl PlanWidget {
+    pub(crate) fn set_plan(&mut self, update: PlanUpdateEvent) {
+        self.update = update;
+    }
+
+    pub(crate) fn height(&self) -> u16 {
+        self.lines().len() as u16
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let steps = &self.update.plan;
+        if steps.is_empty() {
+            return Vec::new();
+        }
+        let completed = steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Completed)
+            .count();
+        let mut header = vec![
+            "plan".magenta().bold(),
+            format!(" {completed}/{} done", steps.len()).dim(),
+        ];
+        if let Some(explanation) = self
+            .update
+            .explanation
+            .as_deref()
+            .and_then(|explanation| explanation.lines().next())
+        {
+            header.push(format!(" · {explanation}").dim().italic());
+        }
+        let mut lines = vec![Line::from(header)];
+
+        let first_unfinished = steps
+            .iter()
+            .position(|step| step.status != StepStatus::Completed)
+            .unwrap_or(steps.len());
+        let start = first_unfinished
+            .saturating_sub(1)
+            .min(steps.len().saturating_sub(MAX_VISIBLE_STEPS));
+        if start > 0 {
+            lines.push(format!("  … {start} earlier steps").dim().into());
+        }
+        for step in steps.iter().skip(start).take(MAX_VISIBLE_STEPS) {
+            lines.push(match step.status {
+                StepStatus::Completed => format!("  ✔ {}", step.step).dim().crossed_out().into(),
+                StepStatus::InProgress => format!("  ▶ {}", step.step).cyan().bold().into(),
+                StepStatus::Pending => format!("  □ {}", step.step).into(),
+            });
+        }
+        let after = steps.len().saturating_sub(start + MAX_VISIBLE_STEPS);
+        if after > 0 {
+            lines.push(format!("  … {after} more steps").dim().into());
+        }
+        lines
+    }
+}
+
+impl WidgetRef for &PlanWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::PlanStep;
+
+    fn step(text: &str, status: StepStatus) -> PlanStep {
+        PlanStep {
+            step: text.to_string(),
+            status,
+        }
+    }
+
+    fn rendered(widget: &PlanWidget) -> Vec<String> {
+        widget
+            .lines()
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_step_in_progress_visible() {
+        let mut widget = PlanWidget::default();
+        assert_eq!(widget.height(), 0);
+
+        let mut plan: Vec<PlanStep> = (1..=8)
+            .map(|n| step(&format!("step {n}"), StepStatus::Completed))
+            .collect();
+        plan.push(step("step 9", StepStatus::InProgress));
+        plan.push(step("step 10", StepStatus::Pending));
+        widget.set_plan(PlanUpdateEvent {
+            explanation: Some("tests pass now".to_string()),
+            plan,
+        });
+
+        let lines = rendered(&widget);
+        assert_eq!(lines[0], "plan 8/10 done · tests pass now");
+        assert_eq!(lines[1], "  … 4 earlier steps");
+        assert_eq!(lines[2], "  ✔ step 5");
+        assert!(lines.contains(&"  ▶ step 9".to_string()));
+        assert_eq!(lines.last().map(String::as_str), Some("  □ step 10"));
+        assert_eq!(widget.height() as usize, lines.len());
+    }
+}
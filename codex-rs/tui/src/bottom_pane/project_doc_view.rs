@@ -0,0 +1,169 @@
+use std::path::PathBuf;
+
+use codex_core::protocol::ProjectDocProposalEvent;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+
+use super::BottomPane;
+use super::BottomPaneView;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+/// Diff lines shown at once; the rest is reached by scrolling.
+const MAX_VISIBLE_LINES: usize = 20;
+
+/// Shows the AGENTS.md drafted by `/init` as a diff against the current file
+/// and writes it once the user accepts.
+pub(crate) struct ProjectDocView {
+    path: PathBuf,
+    content: String,
+    diff: Vec<Line<'static>>,
+    scroll: usize,
+    app_event_tx: AppEventSender,
+    done: bool,
+}
+
+impl ProjectDocView {
+    pub fn new(proposal: ProjectDocProposalEvent, app_event_tx: AppEventSender) -> Self {
+        let ProjectDocProposalEvent {
+            path,
+            existing,
+            content,
+        } = proposal;
+        let diff = diff_lines(existing.as_deref().unwrap_or_default(), &content);
+        Self {
+            path,
+            content,
+            diff,
+            scroll: 0,
+            app_event_tx,
+            done: false,
+        }
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.diff.len().saturating_sub(MAX_VISIBLE_LINES)
+    }
+}
+
+/// The unified diff from `old` to `new`, colored like `git diff`.
+fn diff_lines(old: &str, new: &str) -> Vec<Line<'static>> {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .to_string()
+        .lines()
+        .map(|line| -> Line<'static> {
+            let line = line.to_string();
+            if line.starts_with("@@") {
+                line.cyan().into()
+            } else if line.starts_with('+') {
+                line.green().into()
+            } else if line.starts_with('-') {
+                line.red().into()
+            } else {
+                line.dim().into()
+            }
+        })
+        .collect()
+}
+
+impl<'a> BottomPaneView<'a> for ProjectDocView {
+    fn handle_key_event(&mut self, pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = (self.scroll + 1).min(self.max_scroll());
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => {
+                self.scroll = (self.scroll + MAX_VISIBLE_LINES).min(self.max_scroll());
+            }
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(MAX_VISIBLE_LINES),
+            KeyCode::Enter | KeyCode::Char('y') => {
+                self.app_event_tx.send(AppEvent::WriteProjectDoc {
+                    path: self.path.clone(),
+                    content: self.content.clone(),
+                });
+                self.done = true;
+            }
+            KeyCode::Esc | KeyCode::Char('n') => self.done = true,
+            _ => {}
+        }
+        pane.request_redraw();
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    fn calculate_required_height(&self, _area: &Rect) -> u16 {
+        self.diff.len().clamp(1, MAX_VISIBLE_LINES) as u16 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let lines: Vec<Line> = self
+            .diff
+            .iter()
+            .skip(self.scroll)
+            .take(MAX_VISIBLE_LINES)
+            .cloned()
+            .collect();
+        let title = format!(
+            "Write {}? (Enter/y to write, Esc/n to discard, j/k to scroll)",
+            self.path.display()
+        );
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bottom_pane::BottomPaneParams;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn enter_writes_the_proposed_file() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            composer_max_rows: 3,
+            show_status_line: false,
+        });
+        let proposal = ProjectDocProposalEvent {
+            path: PathBuf::from("/repo/AGENTS.md"),
+            existing: Some("# Guide\n\nRun make.\n".to_string()),
+            content: "# Guide\n\nRun make test.\n".to_string(),
+        };
+        let mut view = ProjectDocView::new(proposal, app_event_tx);
+        let diff: Vec<String> = view.diff.iter().map(|line| line.to_string()).collect();
+        assert!(diff.contains(&"-Run make.".to_string()));
+        assert!(diff.contains(&"+Run make test.".to_string()));
+
+        view.handle_key_event(&mut pane, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(view.is_complete());
+        assert!(rx.try_iter().any(|event| event
+            == AppEvent::WriteProjectDoc {
+                path: PathBuf::from("/repo/AGENTS.md"),
+                content: "# Guide\n\nRun make test.\n".to_string(),
+            }));
+    }
+}
@@ -0,0 +1,105 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+
+/// Messages listed at once; the oldest are sent first, so they are the ones
+/// shown.
+const MAX_VISIBLE_MESSAGES: usize = 3;
+
+/// Messages submitted while a turn is running, shown as chips above the
+/// composer until they are sent.
+#[derive(Default)]
+pub(crate) struct QueuedMessages {
+    texts: Vec<String>,
+}
+
+impl QueuedMessages {
+    pub(crate) fn set_messages(&mut self, texts: Vec<String>) {
+        self.texts = texts;
+    }
+
+    pub(crate) fn height(&self) -> u16 {
+        self.lines().len() as u16
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        if self.texts.is_empty() {
+            return Vec::new();
+        }
+        let mut lines: Vec<Line<'static>> = self
+            .texts
+            .iter()
+            .take(MAX_VISIBLE_MESSAGES)
+            .enumerate()
+            .map(|(i, text)| {
+                let first_line = text.lines().next().unwrap_or_default();
+                let ellipsis = if text.lines().nth(1).is_some() {
+                    " …"
+                } else {
+                    ""
+                };
+                Line::from(vec![
+                    format!(" queued #{} ", i + 1).black().on_yellow(),
+                    format!(" {first_line}{ellipsis}").dim(),
+                ])
+            })
+            .collect();
+        let more = self.texts.len().saturating_sub(MAX_VISIBLE_MESSAGES);
+        if more > 0 {
+            lines.push(format!("  … {more} more queued").dim().into());
+        }
+        lines.push(
+            "  sent when the turn completes · Esc on an empty composer edits the last one"
+                .dim()
+                .italic()
+                .into(),
+        );
+        lines
+    }
+}
+
+impl WidgetRef for &QueuedMessages {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered(widget: &QueuedMessages) -> Vec<String> {
+        widget
+            .lines()
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lists_the_first_messages_as_chips() {
+        let mut widget = QueuedMessages::default();
+        assert_eq!(widget.height(), 0);
+
+        widget.set_messages(vec![
+            "run the tests".to_string(),
+            "then fix\nthe failures".to_string(),
+            "commit".to_string(),
+            "push".to_string(),
+        ]);
+        assert_eq!(
+            rendered(&widget),
+            vec![
+                " queued #1  run the tests",
+                " queued #2  then fix …",
+                " queued #3  commit",
+                "  … 1 more queued",
+                "  sent when the turn completes · Esc on an empty composer edits the last one",
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use codex_core::protocol::ReviewFinding;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+
+use super::BottomPane;
+use super::BottomPaneView;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::review_cell::line_label;
+use crate::review_cell::severity_span;
+
+/// Findings listed at once; the list scrolls to keep the selection visible.
+const MAX_VISIBLE_FINDINGS: usize = 10;
+
+/// Lists the findings of the latest review; the chosen one's diff is shown
+/// in the history.
+pub(crate) struct ReviewFindingsView {
+    findings: Vec<ReviewFinding>,
+    selected: usize,
+    app_event_tx: AppEventSender,
+    done: bool,
+}
+
+impl ReviewFindingsView {
+    pub fn new(findings: Vec<ReviewFinding>, app_event_tx: AppEventSender) -> Self {
+        Self {
+            findings,
+            selected: 0,
+            app_event_tx,
+            done: false,
+        }
+    }
+}
+
+impl<'a> BottomPaneView<'a> for ReviewFindingsView {
+    fn handle_key_event(&mut self, pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        let len = self.findings.len().max(1);
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1) % len,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.checked_sub(1).unwrap_or(len - 1);
+            }
+            KeyCode::Enter => {
+                if let Some(finding) = self.findings.get(self.selected) {
+                    self.app_event_tx
+                        .send(AppEvent::ShowFindingDiff(finding.clone()));
+                }
+                self.done = true;
+            }
+            KeyCode::Esc => self.done = true,
+            _ => {}
+        }
+        pane.request_redraw();
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    fn calculate_required_height(&self, _area: &Rect) -> u16 {
+        self.findings.len().clamp(1, MAX_VISIBLE_FINDINGS) as u16 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let first = (self.selected + 1).saturating_sub(MAX_VISIBLE_FINDINGS);
+        let lines: Vec<Line> = self
+            .findings
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(MAX_VISIBLE_FINDINGS)
+            .map(|(idx, finding)| {
+                let body = finding.body.lines().next().unwrap_or_default();
+                let line = Line::from(vec![
+                    format!("{}:{} ", finding.file, line_label(finding.line_range)).cyan(),
+                    severity_span(finding.severity),
+                    format!(" {body}").into(),
+                ]);
+                if idx == self.selected {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title("Review findings (Enter to show the diff, Esc to cancel)")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bottom_pane::BottomPaneParams;
+    use codex_core::protocol::LineRange;
+    use codex_core::protocol::ReviewSeverity;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn enter_shows_the_diff_of_the_highlighted_finding() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            composer_max_rows: 3,
+            show_status_line: false,
+        });
+        let findings: Vec<ReviewFinding> = ["src/a.rs", "src/b.rs"]
+            .into_iter()
+            .map(|file| ReviewFinding {
+                file: file.to_string(),
+                line_range: LineRange { start: 4, end: 6 },
+                severity: ReviewSeverity::Warning,
+                body: "Unchecked index.".to_string(),
+            })
+            .collect();
+        let mut view = ReviewFindingsView::new(findings.clone(), app_event_tx);
+
+        view.handle_key_event(&mut pane, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        view.handle_key_event(&mut pane, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(view.is_complete());
+        assert!(
+            rx.try_iter()
+                .any(|event| event == AppEvent::ShowFindingDiff(findings[1].clone()))
+        );
+    }
+}
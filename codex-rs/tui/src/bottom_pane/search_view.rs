@@ -0,0 +1,211 @@
+use crossterm::event::{Event as CrosstermEvent, KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use super::BottomPane;
+use super::BottomPaneView;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::conversation_history_widget::TranscriptLine;
+
+/// Maximum number of matching lines shown below the query.
+const MAX_VISIBLE_MATCHES: usize = 5;
+
+enum SearchStage {
+    /// Typing the query; matches are filtered on every keystroke.
+    Query,
+    /// Query confirmed; `n`/`N` move between matches.
+    Navigate,
+}
+
+/// Interactive `/search` view: filters the transcript as the user types and
+/// jumps the conversation history to the selected hit on Enter.
+pub(crate) struct SearchView {
+    stage: SearchStage,
+    input: Input,
+    lines: Vec<TranscriptLine>,
+    /// Indices into `lines` that contain the current query.
+    matches: Vec<usize>,
+    selected: usize,
+    app_event_tx: AppEventSender,
+    done: bool,
+}
+
+impl SearchView {
+    pub fn new(lines: Vec<TranscriptLine>, app_event_tx: AppEventSender) -> Self {
+        Self {
+            stage: SearchStage::Query,
+            input: Input::default(),
+            lines,
+            matches: Vec::new(),
+            selected: 0,
+            app_event_tx,
+            done: false,
+        }
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = matching_lines(&self.lines, self.input.value());
+        self.selected = 0;
+    }
+
+    fn select_next(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = (self.selected + 1) % self.matches.len();
+        }
+    }
+
+    fn select_prev(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = (self.selected + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+
+    fn jump_to_selected(&mut self) {
+        if let Some(hit) = self.matches.get(self.selected).map(|&i| &self.lines[i]) {
+            self.app_event_tx.send(AppEvent::SearchJump {
+                entry: hit.entry,
+                line: hit.line,
+            });
+        }
+        self.done = true;
+    }
+}
+
+/// Case-insensitive substring match; an empty query matches nothing.
+fn matching_lines(lines: &[TranscriptLine], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let query = query.to_lowercase();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.text.to_lowercase().contains(&query))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+impl<'a> BottomPaneView<'a> for SearchView {
+    fn handle_key_event(&mut self, pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        if self.done {
+            return;
+        }
+        match self.stage {
+            SearchStage::Query => match key_event.code {
+                KeyCode::Esc => self.done = true,
+                KeyCode::Enter if self.matches.is_empty() => self.done = true,
+                KeyCode::Enter => self.stage = SearchStage::Navigate,
+                KeyCode::Down => self.select_next(),
+                KeyCode::Up => self.select_prev(),
+                _ => {
+                    self.input.handle_event(&CrosstermEvent::Key(key_event));
+                    self.update_matches();
+                }
+            },
+            SearchStage::Navigate => match key_event.code {
+                KeyCode::Char('n') | KeyCode::Down => self.select_next(),
+                KeyCode::Char('N') | KeyCode::Up => self.select_prev(),
+                KeyCode::Char('/') => self.stage = SearchStage::Query,
+                KeyCode::Enter => self.jump_to_selected(),
+                KeyCode::Esc => self.done = true,
+                _ => {}
+            },
+        }
+        pane.request_redraw();
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+
+    fn calculate_required_height(&self, _area: &Rect) -> u16 {
+        // Query + matches + border
+        1 + self.matches.len().clamp(1, MAX_VISIBLE_MATCHES) as u16 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let title = match self.stage {
+            SearchStage::Query => "Search (Enter to confirm, Esc to cancel)".to_string(),
+            SearchStage::Navigate => format!(
+                "Match {}/{} (n/N next/prev, Enter to jump, / to edit, Esc to cancel)",
+                self.selected + 1,
+                self.matches.len()
+            ),
+        };
+
+        let mut lines = vec![Line::from(vec!["/ ".dim(), self.input.value().into()])];
+        if self.matches.is_empty() {
+            if !self.input.value().is_empty() {
+                lines.push(Line::from("no matches".dim().italic()));
+            }
+        } else {
+            // Keep the selected match inside the visible window.
+            let first = (self.selected + 1).saturating_sub(MAX_VISIBLE_MATCHES);
+            for (idx, &line_idx) in self
+                .matches
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(MAX_VISIBLE_MATCHES)
+            {
+                let line = Line::from(self.lines[line_idx].text.trim().to_string());
+                lines.push(if idx == self.selected {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                });
+            }
+        }
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(entry: usize, text: &str) -> TranscriptLine {
+        TranscriptLine {
+            entry,
+            line: 0,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn matching_is_case_insensitive() {
+        let lines = vec![line(0, "Hello world"), line(1, "goodbye"), line(2, "WORLD")];
+        assert_eq!(matching_lines(&lines, "world"), vec![0, 2]);
+        assert!(matching_lines(&lines, "").is_empty());
+    }
+
+    #[test]
+    fn navigation_wraps_around() {
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let mut view = SearchView::new(
+            vec![line(0, "foo"), line(1, "bar"), line(2, "foo bar")],
+            AppEventSender::new(tx),
+        );
+        view.input = Input::new("foo".to_string());
+        view.update_matches();
+        assert_eq!(view.matches, vec![0, 2]);
+        view.select_prev();
+        assert_eq!(view.selected, 1);
+        view.select_next();
+        assert_eq!(view.selected, 0);
+    }
+}
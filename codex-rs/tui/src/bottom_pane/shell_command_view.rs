@@ -94,6 +94,7 @@ mod tests {
             app_event_tx: evt_tx.clone(),
             has_input_focus: true,
             composer_max_rows: 1,
+            show_status_line: false,
         });
         // Enter command 'a'
         view.handle_key_event(
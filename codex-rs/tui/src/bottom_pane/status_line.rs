@@ -0,0 +1,140 @@
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_common::elapsed::format_duration;
+use codex_core::protocol::SandboxPolicy;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+
+/// One line below the composer with the model, the sandbox, the time spent
+/// on the running (or last) turn and how much of the context window is left.
+#[derive(Default)]
+pub(crate) struct StatusLine {
+    model: String,
+    sandbox: &'static str,
+    turn: Option<Turn>,
+    context_left_percent: Option<f64>,
+}
+
+enum Turn {
+    Running(Instant),
+    Done(Duration),
+}
+
+impl StatusLine {
+    pub(crate) fn set_session(&mut self, model: &str, sandbox: &SandboxPolicy, cwd: &Path) {
+        self.model = model.to_string();
+        self.sandbox = sandbox_label(sandbox, cwd);
+    }
+
+    pub(crate) fn set_turn_running(&mut self, running: bool) {
+        self.turn = match (running, self.turn.take()) {
+            (true, Some(Turn::Running(start))) => Some(Turn::Running(start)),
+            (true, _) => Some(Turn::Running(Instant::now())),
+            (false, Some(Turn::Running(start))) => Some(Turn::Done(start.elapsed())),
+            (false, turn) => turn,
+        };
+    }
+
+    pub(crate) fn set_context_left(&mut self, pct: f64) {
+        self.context_left_percent = Some(pct);
+    }
+
+    fn line(&self) -> Line<'static> {
+        let mut spans: Vec<Span<'static>> = vec![" ".into(), self.model.clone().cyan()];
+        if !self.sandbox.is_empty() {
+            spans.push(" · ".dim());
+            spans.push(self.sandbox.dim());
+        }
+        match self.turn {
+            Some(Turn::Running(start)) => {
+                spans.push(" · ".dim());
+                spans.push(format!("turn {}", format_duration(start.elapsed())).into());
+            }
+            Some(Turn::Done(elapsed)) => {
+                spans.push(" · ".dim());
+                spans.push(format!("last turn {}", format_duration(elapsed)).dim());
+            }
+            None => {}
+        }
+        if let Some(pct) = self.context_left_percent {
+            let text = format!("{pct:.0}% context left");
+            spans.push(" · ".dim());
+            spans.push(if pct > 40.0 {
+                text.green()
+            } else if pct > 25.0 {
+                text.yellow()
+            } else {
+                text.red()
+            });
+        }
+        Line::from(spans)
+    }
+}
+
+/// Short description of what commands may do under `policy`.
+fn sandbox_label(policy: &SandboxPolicy, cwd: &Path) -> &'static str {
+    if policy.is_unrestricted() {
+        "no sandbox"
+    } else if policy.has_full_disk_write_access() {
+        "full disk write"
+    } else if policy.get_writable_roots_with_cwd(cwd).is_empty() {
+        "read-only"
+    } else {
+        "workspace write"
+    }
+}
+
+impl WidgetRef for &StatusLine {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.line()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered(status: &StatusLine) -> String {
+        status
+            .line()
+            .spans
+            .iter()
+            .map(|s| s.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn shows_model_sandbox_and_context() {
+        let mut status = StatusLine::default();
+        status.set_session(
+            "o3",
+            &SandboxPolicy::new_read_only_policy(),
+            Path::new("/repo"),
+        );
+        assert_eq!(rendered(&status), " o3 · read-only");
+
+        status.set_session(
+            "o3",
+            &SandboxPolicy::new_full_auto_policy(),
+            Path::new("/repo"),
+        );
+        status.set_context_left(72.4);
+        assert_eq!(
+            rendered(&status),
+            " o3 · workspace write · 72% context left"
+        );
+
+        status.set_turn_running(true);
+        assert!(rendered(&status).contains(" · turn "));
+        status.set_turn_running(false);
+        assert!(rendered(&status).contains(" · last turn "));
+    }
+}
@@ -1,45 +1,95 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
 use codex_core::ContentItem;
 use codex_core::ReasoningItemReasoningSummary;
 use codex_core::ResponseItem;
+use codex_core::codex::configure_session_op;
 use codex_core::codex_wrapper::init_codex;
 use codex_core::config::Config;
+use codex_core::config_types::ReasoningDisplay;
+use codex_core::debug_bundle::default_bundle_file_name;
+use codex_core::debug_bundle::write_debug_bundle;
+use codex_core::model_catalog::ModelCatalog;
+use codex_core::model_catalog::fetch_model_catalog;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::BackgroundProcessesUpdatedEvent;
+use codex_core::protocol::ContextCompactedEvent;
+use codex_core::protocol::CustomPromptsUpdatedEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandOutputDeltaEvent;
 use codex_core::protocol::InputItem;
+use codex_core::protocol::McpPromptEvent;
+use codex_core::protocol::McpPromptInfo;
+use codex_core::protocol::McpStartupEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::ProjectDoc;
+use codex_core::protocol::ProjectDocsLoadedEvent;
+use codex_core::protocol::ReviewFinding;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentMessageEvent;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TokenCountEvent;
+use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TurnRevertedEvent;
+use codex_core::review::git_diff;
+use codex_core::review::hunks_for_lines;
+use codex_core::session_store::load_session;
+use codex_core::transcript_export::ExportFormat;
+use codex_core::transcript_export::default_export_file_name;
+use codex_core::transcript_export::render_transcript;
+use codex_login::DEFAULT_ACCOUNT;
+use codex_login::list_accounts;
+use codex_login::read_account_info;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
 use ratatui::layout::Direction;
 use ratatui::layout::Layout;
+use ratatui::layout::Position;
 use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
 use ratatui::widgets::Widget;
 use ratatui::widgets::WidgetRef;
+use tempfile::TempPath;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::unbounded_channel;
+use uuid::Uuid;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::InputResult;
+use crate::clipboard;
 use crate::context::calculate_context_percent_remaining;
 use crate::conversation_history_widget::ConversationHistoryWidget;
+use crate::exec_command::strip_bash_lc_and_escape;
 use crate::history_cell::PatchEventType;
+use crate::history_cell::compose_account_display;
+use crate::keymap::KeyAction;
+use crate::keymap::Keymap;
+use crate::markdown_code_blocks::extract_code_blocks;
+use crate::notifications::Notification;
+use crate::notifications::Notifier;
+use crate::tabs::TabId;
 use crate::user_approval_widget::ApprovalRequest;
 use shlex;
 
@@ -55,6 +105,40 @@ pub(crate) struct ChatWidget<'a> {
     history_items: Vec<ResponseItem>,
     /// Counter to generate unique call IDs for shell commands.
     next_shell_call_id: usize,
+    /// Start time and token usage so far of the running turn, for
+    /// `tui.show_turn_stats`.
+    turn_stats: Option<(Instant, TokenUsage)>,
+    notifier: Notifier,
+    keymap: Keymap,
+    /// Areas of the history and bottom panes in the last frame, to route
+    /// mouse clicks.
+    last_layout: Cell<(Rect, Rect)>,
+    /// Text of the latest agent message, for copying its code blocks.
+    last_agent_message: Option<String>,
+    /// Shown above the bottom pane while several tabs are open.
+    tab_bar: Option<Line<'static>>,
+    /// Git ref of the latest `/review`, which its findings' diffs are taken
+    /// against.
+    review_base: Option<String>,
+    /// AGENTS.md files of the session, for `/agents`.
+    project_docs: Vec<ProjectDoc>,
+    /// Whether a turn is in progress, from the submission of a message until
+    /// the task completes or fails.
+    task_running: bool,
+    /// Submission id of the running turn, from its `TaskStarted`. Errors with
+    /// another id are refusals of other ops and leave the turn running.
+    running_turn: Option<String>,
+    /// Messages submitted while a turn was running, sent one per turn as
+    /// turns complete.
+    queued_messages: VecDeque<UserMessage>,
+    /// How reasoning is shown, changed with `/reasoning`.
+    reasoning_display: ReasoningDisplay,
+    /// Whether a token count reported the model's context window. The
+    /// context left is then taken from token counts only.
+    context_window_reported: bool,
+    /// Images pasted from the clipboard. Messages, queued or submitted,
+    /// refer to them by path until the session ends, when they are deleted.
+    pasted_images: Vec<TempPath>,
 }
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -89,11 +173,18 @@ impl ChatWidget<'_> {
     pub(crate) fn new(
         config: Config,
         app_event_tx: AppEventSender,
+        tab: TabId,
         initial_prompt: Option<String>,
         initial_images: Vec<PathBuf>,
     ) -> Self {
         let (codex_op_tx, mut codex_op_rx) = unbounded_channel::<Op>();
 
+        let reasoning_display = if config.hide_agent_reasoning {
+            ReasoningDisplay::Hide
+        } else {
+            config.tui.reasoning_display
+        };
+
         let app_event_tx_clone = app_event_tx.clone();
         // Create the Codex asynchronously so the UI loads as quickly as possible.
         let config_for_agent_loop = config.clone();
@@ -109,7 +200,10 @@ impl ChatWidget<'_> {
 
             // Forward the captured `SessionInitialized` event that was consumed
             // inside `init_codex()` so it can be rendered in the UI.
-            app_event_tx_clone.send(AppEvent::CodexEvent(session_event.clone()));
+            app_event_tx_clone.send(AppEvent::CodexEvent {
+                tab,
+                event: session_event.clone(),
+            });
             let codex = Arc::new(codex);
             let codex_clone = codex.clone();
             tokio::spawn(async move {
@@ -122,7 +216,7 @@ impl ChatWidget<'_> {
             });
 
             while let Ok(event) = codex.next_event().await {
-                app_event_tx_clone.send(AppEvent::CodexEvent(event));
+                app_event_tx_clone.send(AppEvent::CodexEvent { tab, event });
             }
         });
 
@@ -134,8 +228,13 @@ impl ChatWidget<'_> {
                 app_event_tx,
                 has_input_focus: true,
                 composer_max_rows: config.tui.composer_max_rows,
+                show_status_line: config.tui.status_line,
             }),
             input_focus: InputFocus::BottomPane,
+            notifier: Notifier::new(&config.tui),
+            keymap: Keymap::new(&config.keys),
+            last_layout: Cell::new((Rect::default(), Rect::default())),
+            last_agent_message: None,
             config,
             initial_user_message: create_initial_user_message(
                 initial_prompt.unwrap_or_default(),
@@ -143,44 +242,239 @@ impl ChatWidget<'_> {
             ),
             history_items: Vec::new(),
             next_shell_call_id: 0,
+            turn_stats: None,
+            tab_bar: None,
+            review_base: None,
+            project_docs: Vec::new(),
+            task_running: false,
+            running_turn: None,
+            queued_messages: VecDeque::new(),
+            reasoning_display,
+            context_window_reported: false,
+            pasted_images: Vec::new(),
         }
     }
 
     pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) {
-        // Special-case <Tab>: normally toggles focus between history and bottom panes.
-        // However, when the slash-command popup is visible we forward the key
-        // to the bottom pane so it can handle auto-completion.
-        if matches!(key_event.code, crossterm::event::KeyCode::Tab)
-            && !self.bottom_pane.is_command_popup_visible()
-        {
-            self.input_focus = match self.input_focus {
+        // Special-case the focus key (<Tab> by default): normally toggles focus
+        // between history and bottom panes. However, when the slash-command
+        // popup is visible we forward the key to the bottom pane so it can
+        // handle auto-completion.
+        let action = self.keymap.action_for(&key_event, self.history_has_focus());
+        if action == Some(KeyAction::CopyCodeBlock) {
+            self.show_code_block_picker();
+            return;
+        }
+        if action == Some(KeyAction::PasteImage) {
+            self.paste_image();
+            return;
+        }
+        if action == Some(KeyAction::ExpandReasoning) {
+            if self
+                .conversation_history
+                .toggle_reasoning(&self.config, self.keymap.label(KeyAction::ExpandReasoning))
+            {
+                self.request_redraw();
+            }
+            return;
+        }
+        if action == Some(KeyAction::Steer) {
+            self.steer_running_turn();
+            return;
+        }
+        if action == Some(KeyAction::ExternalEditor) {
+            self.app_event_tx.send(AppEvent::OpenExternalEditor(
+                self.bottom_pane.composer_text(),
+            ));
+            return;
+        }
+
+        if action == Some(KeyAction::ToggleFocus) && !self.bottom_pane.is_command_popup_visible() {
+            self.set_input_focus(match self.input_focus {
                 InputFocus::HistoryPane => InputFocus::BottomPane,
                 InputFocus::BottomPane => InputFocus::HistoryPane,
-            };
-            self.conversation_history
-                .set_input_focus(self.input_focus == InputFocus::HistoryPane);
-            self.bottom_pane
-                .set_input_focus(self.input_focus == InputFocus::BottomPane);
-            self.request_redraw();
+            });
+            return;
+        }
+
+        if action == Some(KeyAction::EditQueued)
+            && self.input_focus == InputFocus::BottomPane
+            && !self.queued_messages.is_empty()
+            && self.bottom_pane.can_edit_queued_message()
+        {
+            self.edit_last_queued_message();
             return;
         }
 
         match self.input_focus {
-            InputFocus::HistoryPane => {
-                let needs_redraw = self.conversation_history.handle_key_event(key_event);
-                if needs_redraw {
-                    self.request_redraw();
+            InputFocus::HistoryPane => match action {
+                Some(KeyAction::Search) => {
+                    self.set_input_focus(InputFocus::BottomPane);
+                    self.push_search();
                 }
-            }
+                Some(KeyAction::ReviewFindings) => self.show_review_findings(),
+                Some(KeyAction::Yank) => {
+                    if let Some(text) = self.conversation_history.take_selected_text() {
+                        self.copy_to_clipboard(&text);
+                    }
+                }
+                Some(action) => {
+                    if self.conversation_history.handle_key_action(action) {
+                        self.request_redraw();
+                    }
+                }
+                None => {}
+            },
             InputFocus::BottomPane => match self.bottom_pane.handle_key_event(key_event) {
                 InputResult::Submitted(text) => {
-                    self.submit_user_message(text.into());
+                    let image_paths = self.bottom_pane.take_submitted_images();
+                    let message = UserMessage { text, image_paths };
+                    if self.task_running {
+                        self.queued_messages.push_back(message);
+                        self.sync_queued_messages();
+                    } else {
+                        self.submit_user_message(message);
+                    }
                 }
                 InputResult::None => {}
             },
         }
     }
 
+    /// A left click focuses the pane under the cursor; in the bottom pane it
+    /// may also choose an option of the approval modal.
+    pub(crate) fn handle_mouse_click(&mut self, column: u16, row: u16) {
+        let (history_area, bottom_area) = self.last_layout.get();
+        let position = Position::new(column, row);
+        if history_area.contains(position) {
+            self.set_input_focus(InputFocus::HistoryPane);
+        } else if bottom_area.contains(position) {
+            self.set_input_focus(InputFocus::BottomPane);
+            self.bottom_pane
+                .handle_mouse_click(bottom_area, column, row);
+        }
+    }
+
+    /// Let the user pick a code block of the latest agent message to copy.
+    fn show_code_block_picker(&mut self) {
+        let blocks = self
+            .last_agent_message
+            .as_deref()
+            .map(extract_code_blocks)
+            .unwrap_or_default();
+        if blocks.is_empty() {
+            self.conversation_history
+                .add_background_event("No code blocks in the latest agent message.".to_string());
+            self.request_redraw();
+        } else if self.bottom_pane.push_code_block_picker(blocks) {
+            self.set_input_focus(InputFocus::BottomPane);
+        }
+    }
+
+    /// Attach the image on the clipboard to the message being composed.
+    pub(crate) fn paste_image(&mut self) {
+        match clipboard::paste_image_to_temp_png() {
+            Ok(image) => {
+                self.set_input_focus(InputFocus::BottomPane);
+                self.bottom_pane
+                    .attach_image(image.file.to_path_buf(), image.width, image.height);
+                self.pasted_images.push(image.file);
+            }
+            Err(e) => self
+                .conversation_history
+                .add_error(format!("Failed to paste an image: {e}")),
+        }
+        self.request_redraw();
+    }
+
+    /// Copy `text` to the clipboard. In terminals without clipboard support
+    /// the text is shown in the history instead, to be selected by hand.
+    pub(crate) fn copy_to_clipboard(&mut self, text: &str) {
+        let lines = text.lines().count();
+        let plural = if lines == 1 { "" } else { "s" };
+        if !clipboard::terminal_supports_osc52() {
+            self.conversation_history.add_background_event(format!(
+                "This terminal cannot set the clipboard; select the text below instead \
+                 (/toggle-mouse-mode lets the terminal handle the mouse).\n\n{text}"
+            ));
+        } else if let Err(e) = clipboard::copy_to_clipboard(text) {
+            self.conversation_history
+                .add_error(format!("Failed to copy to the clipboard: {e}"));
+        } else {
+            self.conversation_history
+                .add_background_event(format!("Copied {lines} line{plural} to the clipboard."));
+        }
+        self.request_redraw();
+    }
+
+    pub(crate) fn history_has_focus(&self) -> bool {
+        self.input_focus == InputFocus::HistoryPane
+    }
+
+    fn set_input_focus(&mut self, input_focus: InputFocus) {
+        self.input_focus = input_focus;
+        self.conversation_history
+            .set_input_focus(input_focus == InputFocus::HistoryPane);
+        self.bottom_pane
+            .set_input_focus(input_focus == InputFocus::BottomPane);
+        self.request_redraw();
+    }
+
+    /// Send the draft to the running turn as guidance, or as a new message
+    /// when no turn is running.
+    fn steer_running_turn(&mut self) {
+        let text = self.bottom_pane.composer_text();
+        if text.trim().is_empty() {
+            return;
+        }
+        self.bottom_pane.set_composer_text("");
+        if !self.task_running {
+            self.submit_user_message(text.into());
+            return;
+        }
+
+        self.codex_op_tx
+            .send(Op::InjectUserGuidance { text: text.clone() })
+            .unwrap_or_else(|e| {
+                tracing::error!("failed to send guidance: {e}");
+            });
+        self.codex_op_tx
+            .send(Op::AddToHistory { text: text.clone() })
+            .unwrap_or_else(|e| {
+                tracing::error!("failed to send AddHistory op: {e}");
+            });
+        self.conversation_history
+            .add_user_message(&self.config, text);
+        self.conversation_history.scroll_to_bottom();
+    }
+
+    /// Send the oldest queued message, if any, now that the turn is over.
+    fn submit_queued_message(&mut self) {
+        if let Some(message) = self.queued_messages.pop_front() {
+            self.sync_queued_messages();
+            self.submit_user_message(message);
+        }
+    }
+
+    /// Take the newest queued message back into the composer, to edit it or
+    /// to drop it by clearing the composer.
+    fn edit_last_queued_message(&mut self) {
+        if let Some(UserMessage { text, image_paths }) = self.queued_messages.pop_back() {
+            self.sync_queued_messages();
+            self.bottom_pane
+                .restore_composer_message(&text, image_paths);
+        }
+    }
+
+    fn sync_queued_messages(&mut self) {
+        self.bottom_pane.set_queued_messages(
+            self.queued_messages
+                .iter()
+                .map(|message| message.text.clone())
+                .collect(),
+        );
+    }
+
     fn submit_user_message(&mut self, user_message: UserMessage) {
         let UserMessage { text, image_paths } = user_message;
         let mut items: Vec<InputItem> = Vec::new();
@@ -197,6 +491,7 @@ impl ChatWidget<'_> {
             return;
         }
 
+        self.task_running = true;
         self.codex_op_tx
             .send(Op::UserInput { items })
             .unwrap_or_else(|e| {
@@ -260,7 +555,16 @@ impl ChatWidget<'_> {
             }
         }
         self.conversation_history.scroll_to_bottom();
-        // update context-left after replay
+        self.estimate_context_percent();
+    }
+
+    /// Estimate the context left from the text of the conversation, unless
+    /// the model reported its context window with a token count, whose
+    /// figure is kept.
+    fn estimate_context_percent(&mut self) {
+        if self.context_window_reported {
+            return;
+        }
         let pct = calculate_context_percent_remaining(&self.history_items, &self.config.model);
         self.bottom_pane.set_context_percent(pct);
     }
@@ -277,6 +581,19 @@ impl ChatWidget<'_> {
                 // composer can navigate through past messages.
                 self.bottom_pane
                     .set_history_metadata(event.history_log_id, event.history_entry_count);
+                self.bottom_pane.set_status_session(
+                    &event.model,
+                    &self.config.sandbox_policy,
+                    &self.config.cwd,
+                );
+                self.bottom_pane.set_mcp_prompts(event.mcp_prompts.clone());
+                self.bottom_pane
+                    .set_custom_prompts(event.custom_prompts.clone());
+
+                // Commands run on a PTY are as wide as this terminal.
+                if let Ok((columns, _)) = crossterm::terminal::size() {
+                    self.submit_op(Op::SetTerminalWidth { columns });
+                }
 
                 if let Some(user_message) = self.initial_user_message.take() {
                     // If the user provided an initial message, add it to the
@@ -287,6 +604,7 @@ impl ChatWidget<'_> {
                 self.request_redraw();
             }
             EventMsg::AgentMessage(AgentMessageEvent { message }) => {
+                self.last_agent_message = Some(message.clone());
                 self.conversation_history
                     .add_agent_message(&self.config, message.clone());
                 // record raw item for context-left calculation
@@ -299,44 +617,77 @@ impl ChatWidget<'_> {
                 self.request_redraw();
             }
             EventMsg::AgentReasoning(AgentReasoningEvent { text }) => {
-                if !self.config.hide_agent_reasoning {
-                    self.conversation_history
-                        .add_agent_reasoning(&self.config, text);
-                    self.request_redraw();
+                match self.reasoning_display {
+                    ReasoningDisplay::Show => self
+                        .conversation_history
+                        .add_agent_reasoning(&self.config, text),
+                    ReasoningDisplay::Summary => {
+                        self.conversation_history.add_agent_reasoning_summary(
+                            &self.config,
+                            text,
+                            self.keymap.label(KeyAction::ExpandReasoning),
+                        )
+                    }
+                    ReasoningDisplay::Hide => return,
                 }
+                self.request_redraw();
             }
             EventMsg::TaskStarted => {
+                self.task_running = true;
+                self.running_turn = Some(id);
+                self.turn_stats = Some((Instant::now(), TokenUsage::default()));
                 self.bottom_pane.set_task_running(true);
                 self.request_redraw();
             }
-            EventMsg::TaskComplete(TaskCompleteEvent {
-                last_agent_message: _,
-            }) => {
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
                 self.bottom_pane.set_task_running(false);
-                // update context-left after turn completes
-                let pct =
-                    calculate_context_percent_remaining(&self.history_items, &self.config.model);
-                self.bottom_pane.set_context_percent(pct);
+                self.notifier.notify(Notification::TaskComplete {
+                    last_agent_message: last_agent_message.as_deref(),
+                });
+                if let Some((started, usage)) = self.turn_stats.take() {
+                    if self.config.tui.show_turn_stats {
+                        self.conversation_history
+                            .add_turn_stats(&usage, started.elapsed());
+                    }
+                }
+                self.estimate_context_percent();
+                self.task_running = false;
+                self.running_turn = None;
+                self.submit_queued_message();
                 self.request_redraw();
             }
             EventMsg::Error(ErrorEvent { message }) => {
                 self.conversation_history.add_error(message);
-                self.bottom_pane.set_task_running(false);
-                // update context-left after error
-                let pct =
-                    calculate_context_percent_remaining(&self.history_items, &self.config.model);
-                self.bottom_pane.set_context_percent(pct);
+                // Only the running turn's own error ends it; a submitted
+                // message refused before it started has no turn id yet.
+                let ends_turn = match &self.running_turn {
+                    Some(turn) => *turn == id,
+                    None => self.task_running,
+                };
+                if ends_turn {
+                    self.task_running = false;
+                    self.running_turn = None;
+                    self.bottom_pane.set_task_running(false);
+                    self.estimate_context_percent();
+                    self.submit_queued_message();
+                }
+                self.request_redraw();
             }
             EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
                 command,
                 cwd,
                 reason,
+                session_prefix,
             }) => {
+                self.notifier.notify(Notification::ApprovalRequired {
+                    detail: &strip_bash_lc_and_escape(&command),
+                });
                 let request = ApprovalRequest::Exec {
                     id,
                     command,
                     cwd,
                     reason,
+                    session_prefix,
                 };
                 self.bottom_pane.push_approval_request(request);
             }
@@ -356,6 +707,14 @@ impl ChatWidget<'_> {
                 // prompt before they have seen *what* is being requested.
                 // ------------------------------------------------------------------
 
+                let files = changes.len();
+                self.notifier.notify(Notification::ApprovalRequired {
+                    detail: &format!(
+                        "apply patch to {files} file{}",
+                        if files == 1 { "" } else { "s" }
+                    ),
+                });
+
                 self.conversation_history.add_patch_event(
                     &self.config,
                     PatchEventType::ApprovalRequest,
@@ -373,6 +732,82 @@ impl ChatWidget<'_> {
                 self.bottom_pane.push_approval_request(request);
                 self.request_redraw();
             }
+            EventMsg::SubAgentBegin(SubAgentBeginEvent {
+                call_id,
+                prompt,
+                max_turns,
+            }) => {
+                self.conversation_history
+                    .add_sub_agent(call_id, prompt, max_turns);
+                self.request_redraw();
+            }
+            EventMsg::SubAgentMessage(SubAgentMessageEvent { message, .. }) => {
+                let first_line = message.lines().next().unwrap_or_default().to_string();
+                self.conversation_history
+                    .add_sub_agent_step(Line::from(first_line));
+                self.request_redraw();
+            }
+            EventMsg::SubAgentEnd(SubAgentEndEvent {
+                call_id,
+                turns,
+                result,
+            }) => {
+                self.conversation_history
+                    .record_completed_sub_agent(&call_id, turns, result);
+                self.request_redraw();
+            }
+            // Tool calls of a running sub-agent are shown as steps of its cell.
+            EventMsg::ExecCommandBegin(ExecCommandBeginEvent { command, .. })
+                if self.conversation_history.has_running_sub_agent() =>
+            {
+                let command = strip_bash_lc_and_escape(&command);
+                self.conversation_history
+                    .add_sub_agent_step(Line::from(format!("$ {command}")).dim());
+                self.request_redraw();
+            }
+            EventMsg::ExecCommandEnd(ExecCommandEndEvent { exit_code, .. })
+                if self.conversation_history.has_running_sub_agent() =>
+            {
+                if exit_code != 0 {
+                    self.conversation_history
+                        .add_sub_agent_step(Line::from(format!("  ✗ exit {exit_code}")).red());
+                    self.request_redraw();
+                }
+            }
+            EventMsg::ExecCommandOutputDelta(_)
+                if self.conversation_history.has_running_sub_agent() => {}
+            EventMsg::ProjectDocsLoaded(_) if self.conversation_history.has_running_sub_agent() => {
+            }
+            EventMsg::McpToolCallBegin(McpToolCallBeginEvent { server, tool, .. })
+                if self.conversation_history.has_running_sub_agent() =>
+            {
+                self.conversation_history
+                    .add_sub_agent_step(Line::from(format!("tool {server}.{tool}")).dim());
+                self.request_redraw();
+            }
+            EventMsg::McpToolCallProgress(_)
+                if self.conversation_history.has_running_sub_agent() => {}
+            EventMsg::McpToolCallEnd(event)
+                if self.conversation_history.has_running_sub_agent() =>
+            {
+                if !event.is_success() {
+                    self.conversation_history
+                        .add_sub_agent_step(Line::from("  ✗ tool call failed").red());
+                    self.request_redraw();
+                }
+            }
+            EventMsg::PatchApplyBegin(PatchApplyBeginEvent { changes, .. })
+                if self.conversation_history.has_running_sub_agent() =>
+            {
+                let mut files: Vec<String> = changes
+                    .keys()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                files.sort();
+                self.conversation_history
+                    .add_sub_agent_step(Line::from(format!("patch {}", files.join(", "))).dim());
+                self.request_redraw();
+            }
             EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
                 call_id,
                 command,
@@ -382,6 +817,15 @@ impl ChatWidget<'_> {
                     .add_active_exec_command(call_id, command);
                 self.request_redraw();
             }
+            EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                call_id,
+                chunk,
+                ..
+            }) => {
+                self.conversation_history
+                    .append_exec_output(&call_id, &chunk);
+                self.request_redraw();
+            }
             EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                 call_id: _,
                 auto_approved,
@@ -409,6 +853,15 @@ impl ChatWidget<'_> {
                     .record_completed_exec_command(call_id, stdout, stderr, exit_code);
                 self.request_redraw();
             }
+            EventMsg::McpStartup(McpStartupEvent { servers }) => {
+                self.conversation_history.add_mcp_startup(&servers);
+                self.request_redraw();
+            }
+            EventMsg::ProjectDocsLoaded(ProjectDocsLoadedEvent { docs }) => {
+                self.conversation_history.add_project_docs_loaded(&docs);
+                self.project_docs = docs;
+                self.request_redraw();
+            }
             EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
                 call_id,
                 server,
@@ -419,6 +872,11 @@ impl ChatWidget<'_> {
                     .add_active_mcp_tool_call(call_id, server, tool, arguments);
                 self.request_redraw();
             }
+            EventMsg::McpToolCallProgress(progress) => {
+                self.conversation_history
+                    .update_mcp_tool_call_progress(&progress);
+                self.request_redraw();
+            }
             EventMsg::McpToolCallEnd(mcp_tool_call_end_event) => {
                 let success = mcp_tool_call_end_event.is_success();
                 let McpToolCallEndEvent { call_id, result } = mcp_tool_call_end_event;
@@ -426,6 +884,75 @@ impl ChatWidget<'_> {
                     .record_completed_mcp_tool_call(call_id, success, result);
                 self.request_redraw();
             }
+            EventMsg::TokenCount(TokenCountEvent {
+                last,
+                total,
+                max_session_tokens,
+                estimated_cost_usd,
+                model_context_window,
+            }) => {
+                if let Some((_, usage)) = &mut self.turn_stats {
+                    usage.accumulate(&last);
+                }
+                if let Some(window) = model_context_window {
+                    self.context_window_reported = true;
+                    let used = last.input_tokens as f64 / window.max(1) as f64;
+                    self.bottom_pane
+                        .set_context_percent((100.0 - used * 100.0).clamp(0.0, 100.0));
+                }
+                if let Some(max) = max_session_tokens {
+                    self.bottom_pane.set_token_budget(total.total_tokens, max);
+                }
+                if let Some(cost) = estimated_cost_usd {
+                    self.bottom_pane.set_session_cost(cost);
+                }
+            }
+            EventMsg::ContextCompacted(ContextCompactedEvent {
+                tokens_before,
+                tokens_after,
+                ..
+            }) => {
+                self.conversation_history
+                    .add_context_compacted(tokens_before, tokens_after);
+                self.request_redraw();
+            }
+            EventMsg::PlanUpdate(update) => {
+                self.bottom_pane.set_plan(update);
+            }
+            EventMsg::McpPrompt(McpPromptEvent { text, .. }) => {
+                self.submit_user_message(text.into());
+                self.request_redraw();
+            }
+            EventMsg::CustomPromptsUpdated(CustomPromptsUpdatedEvent { custom_prompts }) => {
+                self.bottom_pane.set_custom_prompts(custom_prompts);
+                self.request_redraw();
+            }
+            EventMsg::ProjectDocProposal(proposal) => {
+                self.bottom_pane.push_project_doc_proposal(proposal);
+                self.set_input_focus(InputFocus::BottomPane);
+                self.request_redraw();
+            }
+            EventMsg::ReviewFinding(finding) => {
+                self.conversation_history.add_review_finding(finding);
+                self.request_redraw();
+            }
+            EventMsg::UserGuidanceDelivered(_) => {
+                self.conversation_history
+                    .add_background_event("Guidance delivered to the model.".to_string());
+                self.request_redraw();
+            }
+            EventMsg::TurnReverted(TurnRevertedEvent { files }) => {
+                self.conversation_history.add_turn_reverted(&files);
+                self.request_redraw();
+            }
+            EventMsg::BackgroundProcessesUpdated(BackgroundProcessesUpdatedEvent { processes }) => {
+                self.bottom_pane.set_background_processes(
+                    processes
+                        .iter()
+                        .map(|process| strip_bash_lc_and_escape(&process.command))
+                        .collect(),
+                );
+            }
             EventMsg::GetHistoryEntryResponse(event) => {
                 let codex_core::protocol::GetHistoryEntryResponseEvent {
                     offset,
@@ -437,6 +964,19 @@ impl ChatWidget<'_> {
                 self.bottom_pane
                     .on_history_entry_response(log_id, offset, entry.map(|e| e.text));
             }
+            EventMsg::SearchHistoryResponse(event) => {
+                let codex_core::protocol::SearchHistoryResponseEvent {
+                    query,
+                    log_id,
+                    offset,
+                    entry,
+                } = event;
+                self.bottom_pane.on_history_search_response(
+                    log_id,
+                    &query,
+                    offset.zip(entry.map(|e| e.text)),
+                );
+            }
             event => {
                 self.conversation_history
                     .add_background_event(format!("{event:?}"));
@@ -445,6 +985,378 @@ impl ChatWidget<'_> {
         }
     }
 
+    /// Set how reasoning is shown from the argument of `/reasoning`.
+    pub(crate) fn set_reasoning_display(&mut self, mode: &str) {
+        match mode.parse::<ReasoningDisplay>() {
+            Ok(mode) => {
+                self.reasoning_display = mode;
+                self.show_reasoning_display();
+            }
+            Err(_) => {
+                self.conversation_history.add_error(format!(
+                    "Unknown reasoning display `{mode}`: use show, hide or summary."
+                ));
+                self.request_redraw();
+            }
+        }
+    }
+
+    /// Tell how reasoning is shown, for `/reasoning` without an argument.
+    pub(crate) fn show_reasoning_display(&mut self) {
+        self.conversation_history.add_background_event(format!(
+            "Reasoning display: {} (/reasoning show|hide|summary to change it).",
+            self.reasoning_display
+        ));
+        self.request_redraw();
+    }
+
+    /// Print the current key bindings for `/keys`.
+    pub(crate) fn show_key_bindings(&mut self) {
+        self.conversation_history
+            .add_background_event(self.keymap.describe().join("\n"));
+        self.request_redraw();
+    }
+
+    /// Show the AGENTS.md files of the session for `/agents`.
+    pub(crate) fn show_project_docs(&mut self) {
+        self.conversation_history
+            .add_project_docs(&self.config, &self.project_docs);
+        self.request_redraw();
+    }
+
+    /// List the `[profiles]` of config.toml for `/profile`.
+    pub(crate) fn show_profiles(&mut self) {
+        let mut profiles: Vec<_> = self.config.profiles.iter().collect();
+        profiles.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let message = if profiles.is_empty() {
+            "No profiles are defined under [profiles] in config.toml.".to_string()
+        } else {
+            let mut lines = vec!["Profiles (switch with /profile <name>):".to_string()];
+            for (name, profile) in profiles {
+                let marker = if self.config.active_profile.as_ref() == Some(name) {
+                    "*"
+                } else {
+                    " "
+                };
+                let mut details = Vec::new();
+                if let Some(model) = &profile.model {
+                    details.push(format!("model {model}"));
+                }
+                if let Some(provider) = &profile.model_provider {
+                    details.push(format!("provider {provider}"));
+                }
+                lines.push(format!("{marker} {name}  {}", details.join(", ")));
+            }
+            lines.join("\n")
+        };
+        self.conversation_history.add_background_event(message);
+        self.request_redraw();
+    }
+
+    /// List the default login and those stored with `codex login --profile`
+    /// for `/account`.
+    pub(crate) fn show_accounts(&mut self) {
+        let codex_home = &self.config.codex_home;
+        let active = self.config.account.as_deref().unwrap_or(DEFAULT_ACCOUNT);
+        let mut lines = vec!["Accounts (switch with /account <name>):".to_string()];
+        let accounts = std::iter::once(None).chain(list_accounts(codex_home).into_iter().map(Some));
+        for account in accounts {
+            let name = account.as_deref().unwrap_or(DEFAULT_ACCOUNT);
+            let marker = if name == active { "*" } else { " " };
+            let details = match read_account_info(codex_home, account.as_deref()) {
+                Ok(info) => [info.email, info.plan]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Err(_) => "not logged in".to_string(),
+            };
+            lines.push(format!("{marker} {name}  {details}"));
+        }
+        lines.push("Add one with `codex login --profile <name>`.".to_string());
+        self.conversation_history
+            .add_background_event(lines.join("\n"));
+        self.request_redraw();
+    }
+
+    /// Report the outcome of `/account <name>`.
+    pub(crate) fn account_switched(&mut self, account: Option<String>, result: Result<(), String>) {
+        let name = account.as_deref().unwrap_or(DEFAULT_ACCOUNT).to_string();
+        match result {
+            Ok(()) => {
+                self.config.account = account;
+                let display = compose_account_display(&self.config).unwrap_or(name);
+                self.conversation_history
+                    .add_background_event(format!("Switched to account {display}."));
+            }
+            Err(e) => self
+                .conversation_history
+                .add_error(format!("Failed to switch to account `{name}`: {e}")),
+        }
+        self.request_redraw();
+    }
+
+    /// Another tab switched to `account`. The API key is shared by all tabs,
+    /// so this tab's requests use the account from now on as well.
+    pub(crate) fn account_switched_in_other_tab(&mut self, account: Option<String>) {
+        let name = account.as_deref().unwrap_or(DEFAULT_ACCOUNT).to_string();
+        self.config.account = account;
+        let display = compose_account_display(&self.config).unwrap_or(name);
+        self.conversation_history.add_background_event(format!(
+            "Another tab switched to account {display}, which this tab uses too."
+        ));
+    }
+
+    pub(crate) fn add_background_event(&mut self, message: String) {
+        self.conversation_history.add_background_event(message);
+        self.request_redraw();
+    }
+
+    pub(crate) fn add_error(&mut self, message: String) {
+        self.conversation_history.add_error(message);
+        self.request_redraw();
+    }
+
+    /// Fetch the models for `/model` in the background; the picker opens
+    /// when they arrive as [`AppEvent::ModelCatalogLoaded`].
+    pub(crate) fn fetch_model_catalog(&mut self) {
+        let config = self.config.clone();
+        let app_event_tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let catalog = fetch_model_catalog(&config).await;
+            app_event_tx.send(AppEvent::ModelCatalogLoaded(catalog));
+        });
+    }
+
+    pub(crate) fn show_model_picker(&mut self, catalog: ModelCatalog) {
+        if self
+            .bottom_pane
+            .push_model_picker(catalog, self.config.model.clone())
+        {
+            self.set_input_focus(InputFocus::BottomPane);
+        }
+    }
+
+    /// Reconfigure the session to use `model`. The conversation so far is
+    /// kept; a running task is aborted.
+    pub(crate) fn switch_model(&mut self, model: String) {
+        self.config.model = model;
+        self.conversation_history
+            .add_background_event(format!("Switched to model {}.", self.config.model));
+        self.request_redraw();
+        let config = self.config.clone();
+        // Submitted directly: by the time the op is ready another tab may be
+        // shown.
+        let codex_op_tx = self.codex_op_tx.clone();
+        tokio::spawn(async move {
+            let op = configure_session_op(&config).await;
+            if let Err(e) = codex_op_tx.send(op) {
+                tracing::error!("failed to submit op: {e}");
+            }
+        });
+    }
+
+    /// Open the `/fork` picker over the user messages of the saved session.
+    pub(crate) fn show_fork_picker(&mut self, session_id: Option<Uuid>) {
+        let messages = match session_id.map(|id| load_session(&self.config.codex_home, id)) {
+            Some(Ok(session)) => session.user_messages(),
+            Some(Err(e)) => {
+                self.add_error(format!("Failed to load the session to fork: {e}"));
+                return;
+            }
+            None => Vec::new(),
+        };
+        if messages.is_empty() {
+            self.add_error("There are no messages to fork from yet.".to_string());
+            return;
+        }
+        if self.bottom_pane.push_fork_picker(messages) {
+            self.set_input_focus(InputFocus::BottomPane);
+        }
+    }
+
+    /// Run the custom prompt `name`. Core expands it and applies its model
+    /// and approval overrides; the command as typed is shown and kept in the
+    /// message history.
+    pub(crate) fn run_custom_prompt(&mut self, name: String, arguments: String) {
+        let command = format!("/{name} {arguments}").trim_end().to_string();
+        self.submit_op(Op::RunCustomPrompt { name, arguments });
+        self.submit_op(Op::AddToHistory {
+            text: command.clone(),
+        });
+        self.conversation_history
+            .add_user_message(&self.config, command);
+        self.conversation_history.scroll_to_bottom();
+        self.request_redraw();
+    }
+
+    /// Run the MCP prompt of `info`, with a form for its arguments if it
+    /// declares any. The rendered prompt is sent as a user message.
+    pub(crate) fn open_mcp_prompt(&mut self, info: McpPromptInfo) {
+        let has_arguments = info
+            .prompt
+            .arguments
+            .as_ref()
+            .is_some_and(|arguments| !arguments.is_empty());
+        if !has_arguments {
+            self.submit_op(Op::GetMcpPrompt {
+                server: info.server,
+                name: info.prompt.name,
+                arguments: HashMap::new(),
+            });
+        } else if self.bottom_pane.push_mcp_prompt_form(info) {
+            self.set_input_focus(InputFocus::BottomPane);
+        }
+    }
+
+    /// Ask the model for an AGENTS.md for `/init`; it is proposed with
+    /// `ProjectDocProposal`.
+    pub(crate) fn init_project_doc(&mut self) {
+        self.conversation_history.add_background_event(
+            "Drafting an AGENTS.md from the project's manifests and README...".to_string(),
+        );
+        self.submit_op(Op::InitProjectDoc);
+        self.request_redraw();
+    }
+
+    /// Write the AGENTS.md drafted by `/init` once the user accepted it.
+    pub(crate) fn write_project_doc(&mut self, path: &Path, content: &str) {
+        match std::fs::write(path, content) {
+            Ok(()) => self
+                .conversation_history
+                .add_background_event(format!("Wrote {}", path.display())),
+            Err(e) => self
+                .conversation_history
+                .add_error(format!("Failed to write {}: {e}", path.display())),
+        }
+        self.request_redraw();
+    }
+
+    /// Review the changes against `base`, or the uncommitted ones.
+    pub(crate) fn start_review(&mut self, base: Option<String>) {
+        self.review_base = base.clone();
+        self.submit_op(Op::StartReview { base });
+    }
+
+    fn show_review_findings(&mut self) {
+        let findings = self.conversation_history.latest_review_findings();
+        if findings.is_empty() {
+            self.conversation_history.add_background_event(
+                "No review findings yet; start a review with /review.".to_string(),
+            );
+            self.request_redraw();
+        } else if self.bottom_pane.push_review_findings(findings) {
+            self.set_input_focus(InputFocus::BottomPane);
+        }
+    }
+
+    /// Load the diff hunks around `finding` in the background.
+    pub(crate) fn show_finding_diff(&mut self, finding: ReviewFinding) {
+        let cwd = self.config.cwd.clone();
+        let base = self.review_base.clone();
+        let app_event_tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let hunks = git_diff(&cwd, base.as_deref()).await.map(|diff| {
+                hunks_for_lines(
+                    &diff,
+                    &finding.file,
+                    finding.line_range.start,
+                    finding.line_range.end,
+                )
+            });
+            app_event_tx.send(AppEvent::FindingDiffLoaded { finding, hunks });
+        });
+    }
+
+    pub(crate) fn add_finding_diff(
+        &mut self,
+        finding: ReviewFinding,
+        hunks: Result<Vec<String>, String>,
+    ) {
+        match hunks {
+            Ok(hunks) if hunks.is_empty() => self.add_error(format!(
+                "{} has no changes in the reviewed diff.",
+                finding.file
+            )),
+            Ok(hunks) => {
+                self.conversation_history.add_finding_diff(&finding, &hunks);
+                self.conversation_history.scroll_to_bottom();
+                self.request_redraw();
+            }
+            Err(e) => self.add_error(format!("Failed to load the diff: {e}")),
+        }
+    }
+
+    pub(crate) fn set_tab_bar(&mut self, tab_bar: Option<Line<'static>>) {
+        self.tab_bar = tab_bar;
+    }
+
+    /// Replace the composer's draft, e.g. with the message a fork starts from.
+    pub(crate) fn set_composer_text(&mut self, text: &str) {
+        self.bottom_pane.set_composer_text(text);
+    }
+
+    /// Write the saved session to `path`, relative to the working directory,
+    /// for `/export`. `.html` paths get HTML, anything else Markdown.
+    pub(crate) fn export_transcript(&mut self, session_id: Option<Uuid>, path: Option<PathBuf>) {
+        let Some(session_id) = session_id else {
+            self.conversation_history
+                .add_error("There is no session to export yet.".to_string());
+            self.request_redraw();
+            return;
+        };
+        let path = self.config.cwd.join(path.unwrap_or_else(|| {
+            PathBuf::from(default_export_file_name(session_id, ExportFormat::Markdown))
+        }));
+        let format = ExportFormat::from_path(&path);
+        let result = load_session(&self.config.codex_home, session_id).and_then(|session| {
+            std::fs::write(&path, render_transcript(session_id, &session, format))
+        });
+        match result {
+            Ok(()) => self
+                .conversation_history
+                .add_background_event(format!("Exported the transcript to {}", path.display())),
+            Err(e) => self
+                .conversation_history
+                .add_error(format!("Failed to export the transcript: {e}")),
+        }
+        self.request_redraw();
+    }
+
+    /// Zip the debug log at `log_path` with the redacted config and version
+    /// info for `/debug export`, to `path` relative to the working directory.
+    pub(crate) fn export_debug_bundle(
+        &mut self,
+        session_id: Option<Uuid>,
+        log_path: &Path,
+        path: Option<PathBuf>,
+    ) {
+        let path = self
+            .config
+            .cwd
+            .join(path.unwrap_or_else(|| PathBuf::from(default_bundle_file_name())));
+        match write_debug_bundle(
+            &self.config,
+            env!("CARGO_PKG_VERSION"),
+            session_id,
+            log_path,
+            &path,
+        ) {
+            Ok(()) => self.conversation_history.add_background_event(format!(
+                "Wrote the debug bundle to {}; check it before attaching it to a bug report.",
+                path.display()
+            )),
+            Err(e) => self
+                .conversation_history
+                .add_error(format!("Failed to write the debug bundle: {e}")),
+        }
+        self.request_redraw();
+    }
+
+    pub(crate) fn set_terminal_focused(&mut self, focused: bool) {
+        self.notifier.set_terminal_focused(focused);
+    }
+
     /// Update the live log preview while a task is running.
     pub(crate) fn update_latest_log(&mut self, line: String) {
         // Forward only if we are currently showing the status indicator.
@@ -487,12 +1399,26 @@ impl ChatWidget<'_> {
 
     /// Update the running config and reconstruct bottom pane settings.
     pub fn update_config(&mut self, config: Config) {
+        self.keymap = Keymap::new(&config.keys);
         self.config = config.clone();
         self.bottom_pane = BottomPane::new(BottomPaneParams {
             app_event_tx: self.app_event_tx.clone(),
             has_input_focus: true,
             composer_max_rows: config.tui.composer_max_rows,
+            show_status_line: config.tui.status_line,
         });
+        self.bottom_pane
+            .set_status_session(&config.model, &config.sandbox_policy, &config.cwd);
+    }
+
+    /// Open the `/search` view over the current transcript.
+    pub fn push_search(&mut self) {
+        let lines = self.conversation_history.transcript_lines();
+        self.bottom_pane.push_search(lines);
+    }
+
+    pub(crate) fn scroll_to_search_hit(&mut self, entry: usize, line: usize) {
+        self.conversation_history.scroll_to_line(entry, line);
     }
 
     /// Launch interactive shell-command dialog.
@@ -578,13 +1504,22 @@ impl ChatWidget<'_> {
 impl WidgetRef for &ChatWidget<'_> {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let bottom_height = self.bottom_pane.calculate_required_height(&area);
+        let tab_bar_height = u16::from(self.tab_bar.is_some());
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(0), Constraint::Length(bottom_height)])
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(tab_bar_height),
+                Constraint::Length(bottom_height),
+            ])
             .split(area);
 
+        self.last_layout.set((chunks[0], chunks[2]));
         self.conversation_history.render(chunks[0], buf);
-        (&self.bottom_pane).render(chunks[1], buf);
+        if let Some(tab_bar) = &self.tab_bar {
+            tab_bar.clone().render(chunks[1], buf);
+        }
+        (&self.bottom_pane).render(chunks[2], buf);
     }
 }
@@ -7,7 +7,8 @@ use uuid::Uuid;
 
 /// Interactive TUI for chatting with Codex.
 ///
-/// If a `--session` UUID is provided, resumes the existing session.
+/// If a `--session` UUID is provided (e.g. via `codex resume`), resumes the
+/// existing session.
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
@@ -53,6 +54,14 @@ pub struct Cli {
     #[arg(long = "debug-log", value_name = "FILE")]
     pub debug_log: Option<PathBuf>,
 
+    /// Set by `codex resume --last`: continue the most recent session.
+    #[clap(skip)]
+    pub resume_last: bool,
+
+    /// Set by `codex resume` without an id: choose a session from a picker.
+    #[clap(skip)]
+    pub resume_picker: bool,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 }
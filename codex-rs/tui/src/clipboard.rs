@@ -0,0 +1,62 @@
+//! Copy text to the system clipboard with the OSC 52 escape sequence. This
+//! needs no platform clipboard library and also works over SSH; most terminals
+//! support it, some (e.g. tmux) only when configured to.
+//!
+//! OSC 52 only carries text, so pasting an image reads the platform
+//! clipboard instead, which is not reachable over SSH.
+
+use std::io::Write;
+use std::io::stdout;
+
+use base64::Engine;
+use tempfile::TempPath;
+
+pub(crate) fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{encoded}\x07")?;
+    out.flush()
+}
+
+/// `false` for terminals known to ignore OSC 52, where callers should show
+/// the text for manual copying instead.
+pub(crate) fn terminal_supports_osc52() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    !matches!(term.as_str(), "linux" | "dumb") && term_program != "Apple_Terminal"
+}
+
+/// An image from the clipboard, saved as a PNG file. The file is deleted
+/// when `file` is dropped.
+pub(crate) struct PastedImage {
+    pub file: TempPath,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Save the image on the clipboard to a temporary PNG file, to be attached
+/// to a message. Errors are messages for the user.
+pub(crate) fn paste_image_to_temp_png() -> Result<PastedImage, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("cannot access the clipboard: {e}"))?;
+    let image = clipboard.get_image().map_err(|e| match e {
+        arboard::Error::ContentNotAvailable => "the clipboard holds no image".to_string(),
+        e => format!("cannot read the clipboard: {e}"),
+    })?;
+    let (width, height) = (image.width as u32, image.height as u32);
+    let rgba = image::RgbaImage::from_raw(width, height, image.bytes.into_owned())
+        .ok_or_else(|| "the clipboard image has an unexpected size".to_string())?;
+    let file = tempfile::Builder::new()
+        .prefix("codex-clipboard-")
+        .suffix(".png")
+        .tempfile()
+        .map_err(|e| format!("cannot create a file for the image: {e}"))?
+        .into_temp_path();
+    rgba.save_with_format(&file, image::ImageFormat::Png)
+        .map_err(|e| format!("cannot write {}: {e}", file.display()))?;
+    Ok(PastedImage {
+        file,
+        width,
+        height,
+    })
+}
@@ -2,11 +2,18 @@ use crate::cell_widget::CellWidget;
 use crate::history_cell::CommandOutput;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::PatchEventType;
+use crate::keymap::KeyAction;
+use crate::markdown_image::local_image_paths;
+use crate::sub_agent_cell::SubAgentCell;
+use crate::wrapping::wrap_lines;
 use codex_core::config::Config;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::McpServerStatus;
+use codex_core::protocol::McpToolCallProgressEvent;
+use codex_core::protocol::ProjectDoc;
+use codex_core::protocol::ReviewFinding;
 use codex_core::protocol::SessionConfiguredEvent;
-use crossterm::event::KeyCode;
-use crossterm::event::KeyEvent;
+use codex_core::protocol::TokenUsage;
 use ratatui::prelude::*;
 use ratatui::style::Style;
 use ratatui::widgets::*;
@@ -15,6 +22,17 @@ use std::cell::Cell as StdCell;
 use std::cell::Cell;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// One logical (unwrapped) line of the transcript, used by `/search`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TranscriptLine {
+    /// Index of the history entry the line belongs to.
+    pub entry: usize,
+    /// Index of the line within that entry.
+    pub line: usize,
+    pub text: String,
+}
 
 /// A single history entry plus its cached wrapped-line count.
 struct Entry {
@@ -33,6 +51,14 @@ pub struct ConversationHistoryWidget {
     /// The height of the viewport last time render_ref() was called
     last_viewport_height: StdCell<usize>,
     has_input_focus: bool,
+    /// Lines selected with the `select` key, as (anchor, cursor) indices into
+    /// the wrapped lines. Movement keys move the cursor while it is set.
+    selection: Option<(usize, usize)>,
+    /// Whether sub-agent cells list all of their steps.
+    sub_agents_expanded: bool,
+    /// The latest reasoning shown as a summary: its entry, full text and
+    /// whether it is currently expanded.
+    collapsed_reasoning: Option<(usize, String, bool)>,
 }
 
 impl ConversationHistoryWidget {
@@ -44,6 +70,9 @@ impl ConversationHistoryWidget {
             num_rendered_lines: StdCell::new(0),
             last_viewport_height: StdCell::new(0),
             has_input_focus: false,
+            selection: None,
+            sub_agents_expanded: false,
+            collapsed_reasoning: None,
         }
     }
 
@@ -52,25 +81,161 @@ impl ConversationHistoryWidget {
     }
 
     /// Returns true if it needs a redraw.
-    pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
-        match key_event.code {
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.scroll_up(1);
-                true
+    pub(crate) fn handle_key_action(&mut self, action: KeyAction) -> bool {
+        let viewport_height = self.last_viewport_height.get().max(1);
+        let half_page = (viewport_height / 2).max(1);
+
+        if action == KeyAction::ToggleOutput {
+            return self.toggle_focused_output();
+        }
+
+        if let Some((anchor, cursor)) = self.selection {
+            let last_line = self.num_rendered_lines.get().saturating_sub(1);
+            let cursor = match action {
+                KeyAction::ScrollUp => cursor.saturating_sub(1),
+                KeyAction::ScrollDown => cursor + 1,
+                KeyAction::PageUp => cursor.saturating_sub(viewport_height),
+                KeyAction::PageDown => cursor + viewport_height,
+                KeyAction::HalfPageUp => cursor.saturating_sub(half_page),
+                KeyAction::HalfPageDown => cursor + half_page,
+                KeyAction::ScrollTop => 0,
+                KeyAction::ScrollBottom => last_line,
+                KeyAction::Select => {
+                    self.selection = None;
+                    return true;
+                }
+                _ => return false,
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.scroll_down(1);
-                true
+            .min(last_line);
+            self.selection = Some((anchor, cursor));
+            self.scroll_to_show(cursor);
+            return true;
+        }
+
+        match action {
+            KeyAction::ScrollUp => self.scroll_up(1),
+            KeyAction::ScrollDown => self.scroll_down(1),
+            KeyAction::PageUp => self.scroll_page_up(),
+            KeyAction::PageDown => self.scroll_page_down(),
+            KeyAction::HalfPageUp => self.scroll_up(half_page as u32),
+            KeyAction::HalfPageDown => self.scroll_down(half_page as u32),
+            KeyAction::ScrollTop => self.scroll_position = 0,
+            KeyAction::ScrollBottom => self.scroll_to_bottom(),
+            KeyAction::Select => {
+                let top = self.top_line();
+                self.selection = Some((top, top));
             }
-            KeyCode::PageUp | KeyCode::Char('b') => {
-                self.scroll_page_up();
-                true
+            KeyAction::ToggleDetails => {
+                self.sub_agents_expanded = !self.sub_agents_expanded;
+                let expanded = self.sub_agents_expanded;
+                let width = self.cached_width.get();
+                for entry in &mut self.entries {
+                    if let HistoryCell::SubAgent { .. } = entry.cell {
+                        entry
+                            .cell
+                            .update_sub_agent(|cell| cell.set_expanded(expanded));
+                        if width > 0 {
+                            entry.line_count.set(entry.cell.height(width));
+                        }
+                    }
+                }
+            }
+            KeyAction::Interrupt
+            | KeyAction::Exit
+            | KeyAction::ToggleFocus
+            | KeyAction::ToggleOutput
+            | KeyAction::Search
+            | KeyAction::Yank
+            | KeyAction::CopyCodeBlock
+            | KeyAction::ReviewFindings
+            | KeyAction::PasteImage
+            | KeyAction::ExternalEditor
+            | KeyAction::Steer
+            | KeyAction::ExpandReasoning
+            | KeyAction::NextTab
+            | KeyAction::PrevTab => return false,
+        }
+        true
+    }
+
+    /// Expand the folded output of the command under the selection cursor,
+    /// or of the last one in view, or fold it again.
+    fn toggle_focused_output(&mut self) -> bool {
+        let (first, last) = match self.selection {
+            Some((_, cursor)) => (cursor, cursor),
+            None => {
+                let top = self.top_line();
+                (top, top + self.last_viewport_height.get().max(1) - 1)
             }
-            KeyCode::PageDown | KeyCode::Char(' ') => {
-                self.scroll_page_down();
-                true
+        };
+        let mut start = 0;
+        let mut focused = None;
+        for (i, entry) in self.entries.iter().enumerate() {
+            let end = start + entry.line_count.get();
+            if start <= last && end > first && entry.cell.is_foldable() {
+                focused = Some(i);
             }
-            _ => false,
+            start = end;
+        }
+        let Some(entry) = focused.and_then(|i| self.entries.get_mut(i)) else {
+            return false;
+        };
+        entry.cell.toggle_fold();
+        let width = self.cached_width.get();
+        if width > 0 {
+            entry.line_count.set(entry.cell.height(width));
+        }
+        true
+    }
+
+    /// End the selection and return the text of the selected lines.
+    pub(crate) fn take_selected_text(&mut self) -> Option<String> {
+        let (anchor, cursor) = self.selection.take()?;
+        let lines = self.wrapped_text_lines();
+        let end = anchor.max(cursor).min(lines.len().checked_sub(1)?);
+        let selected = lines.get(anchor.min(cursor)..=end)?;
+        Some(selected.join("\n"))
+    }
+
+    /// Plain text of every wrapped line at the last rendered width, so that
+    /// indices match what is on screen. Non-text cells become blank lines.
+    fn wrapped_text_lines(&self) -> Vec<String> {
+        let width = self.cached_width.get();
+        let mut out = Vec::new();
+        for entry in &self.entries {
+            let mut lines: Vec<String> = match entry.cell.text_block() {
+                Some(view) if width > 0 => wrap_lines(&view.lines, width)
+                    .iter()
+                    .map(|line| {
+                        let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+                        text.trim_end().to_string()
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            lines.resize(entry.line_count.get(), String::new());
+            out.extend(lines);
+        }
+        out
+    }
+
+    /// Index of the wrapped line at the top of the viewport.
+    fn top_line(&self) -> usize {
+        let max_scroll = self
+            .num_rendered_lines
+            .get()
+            .saturating_sub(self.last_viewport_height.get());
+        self.scroll_position.min(max_scroll)
+    }
+
+    /// Scroll as little as possible to bring wrapped line `line` into view.
+    fn scroll_to_show(&mut self, line: usize) {
+        let viewport_height = self.last_viewport_height.get().max(1);
+        let top = self.top_line();
+        if line < top {
+            self.scroll_position = line;
+        } else if line >= top + viewport_height {
+            self.scroll_position = line + 1 - viewport_height;
         }
     }
 
@@ -173,6 +338,43 @@ impl ConversationHistoryWidget {
         self.scroll_position = usize::MAX;
     }
 
+    /// Plain text of every line in the transcript, in display order.
+    pub(crate) fn transcript_lines(&self) -> Vec<TranscriptLine> {
+        let mut out = Vec::new();
+        for (entry_idx, entry) in self.entries.iter().enumerate() {
+            let Some(view) = entry.cell.text_block() else {
+                continue;
+            };
+            for (line_idx, line) in view.lines.iter().enumerate() {
+                out.push(TranscriptLine {
+                    entry: entry_idx,
+                    line: line_idx,
+                    text: line.spans.iter().map(|s| s.content.as_ref()).collect(),
+                });
+            }
+        }
+        out
+    }
+
+    /// Scroll so that `line` of history entry `entry` is at the top of the
+    /// viewport (or as close as the end of the transcript allows).
+    pub(crate) fn scroll_to_line(&mut self, entry: usize, line: usize) {
+        let width = self.cached_width.get();
+        let mut offset: usize = self
+            .entries
+            .iter()
+            .take(entry)
+            .map(|e| e.line_count.get())
+            .sum();
+        if width > 0 {
+            if let Some(view) = self.entries.get(entry).and_then(|e| e.cell.text_block()) {
+                let before = &view.lines[..line.min(view.lines.len())];
+                offset += wrap_lines(before, width).len();
+            }
+        }
+        self.scroll_position = offset;
+    }
+
     /// Note `model` could differ from `config.model` if the agent decided to
     /// use a different model than the one requested by the user.
     pub fn add_session_info(&mut self, config: &Config, event: SessionConfiguredEvent) {
@@ -195,17 +397,85 @@ impl ConversationHistoryWidget {
     }
 
     pub fn add_agent_message(&mut self, config: &Config, message: String) {
+        let images = if config.tui.inline_images {
+            local_image_paths(&message, &config.cwd)
+        } else {
+            Vec::new()
+        };
         self.add_to_history(HistoryCell::new_agent_message(config, message));
+        for path in images {
+            if let Some(cell) = HistoryCell::new_inline_image(&path) {
+                self.add_to_history(cell);
+            }
+        }
     }
 
     pub fn add_agent_reasoning(&mut self, config: &Config, text: String) {
         self.add_to_history(HistoryCell::new_agent_reasoning(config, text));
     }
 
+    /// Add reasoning as a one-line summary that
+    /// [`Self::toggle_reasoning`] expands.
+    pub fn add_agent_reasoning_summary(&mut self, config: &Config, text: String, expand_key: &str) {
+        self.add_to_history(HistoryCell::new_agent_reasoning_summary(
+            config, &text, expand_key,
+        ));
+        self.collapsed_reasoning = Some((self.entries.len() - 1, text, false));
+    }
+
+    /// Expand the latest summarized reasoning, or collapse it again. Returns
+    /// false when there is none.
+    pub fn toggle_reasoning(&mut self, config: &Config, expand_key: &str) -> bool {
+        let Some((index, text, expanded)) = self.collapsed_reasoning.as_mut() else {
+            return false;
+        };
+        *expanded = !*expanded;
+        let cell = if *expanded {
+            HistoryCell::new_agent_reasoning(config, text.clone())
+        } else {
+            HistoryCell::new_agent_reasoning_summary(config, text, expand_key)
+        };
+        let width = self.cached_width.get();
+        if let Some(entry) = self.entries.get_mut(*index) {
+            entry
+                .line_count
+                .set(if width > 0 { cell.height(width) } else { 0 });
+            entry.cell = cell;
+        }
+        true
+    }
+
     pub fn add_background_event(&mut self, message: String) {
         self.add_to_history(HistoryCell::new_background_event(message));
     }
 
+    pub fn add_mcp_startup(&mut self, servers: &[McpServerStatus]) {
+        self.add_to_history(HistoryCell::new_mcp_startup(servers));
+    }
+
+    pub fn add_project_docs_loaded(&mut self, docs: &[ProjectDoc]) {
+        self.add_to_history(HistoryCell::new_project_docs_loaded(docs));
+    }
+
+    pub fn add_project_docs(&mut self, config: &Config, docs: &[ProjectDoc]) {
+        self.add_to_history(HistoryCell::new_project_docs(config, docs));
+    }
+
+    pub fn add_context_compacted(&mut self, tokens_before: u64, tokens_after: u64) {
+        self.add_to_history(HistoryCell::new_context_compacted(
+            tokens_before,
+            tokens_after,
+        ));
+    }
+
+    pub fn add_turn_stats(&mut self, usage: &TokenUsage, elapsed: Duration) {
+        self.add_to_history(HistoryCell::new_turn_stats(usage, elapsed));
+    }
+
+    pub fn add_turn_reverted(&mut self, files: &[PathBuf]) {
+        self.add_to_history(HistoryCell::new_turn_reverted(files));
+    }
+
     pub fn add_error(&mut self, message: String) {
         self.add_to_history(HistoryCell::new_error_event(message));
     }
@@ -236,6 +506,90 @@ impl ConversationHistoryWidget {
         ));
     }
 
+    pub fn add_sub_agent(&mut self, call_id: String, prompt: String, max_turns: u32) {
+        self.add_to_history(HistoryCell::new_sub_agent(SubAgentCell::new(
+            call_id,
+            prompt,
+            max_turns,
+            self.sub_agents_expanded,
+        )));
+    }
+
+    /// Whether a sub-agent is running; its tool calls go into its cell.
+    pub fn has_running_sub_agent(&self) -> bool {
+        self.entries.iter().rev().any(
+            |entry| matches!(&entry.cell, HistoryCell::SubAgent { cell, .. } if cell.is_running()),
+        )
+    }
+
+    /// Add a line to the running sub-agent's cell.
+    pub fn add_sub_agent_step(&mut self, step: Line<'static>) {
+        self.update_sub_agent(SubAgentCell::is_running, |cell| cell.push_step(step));
+    }
+
+    pub fn record_completed_sub_agent(
+        &mut self,
+        call_id: &str,
+        turns: u32,
+        result: Result<String, String>,
+    ) {
+        self.update_sub_agent(
+            |cell| cell.call_id == call_id,
+            |cell| cell.finish(turns, result),
+        );
+    }
+
+    /// Add a review finding to the review cell at the end of the history,
+    /// or start one. Findings of a review arrive back to back.
+    pub fn add_review_finding(&mut self, finding: ReviewFinding) {
+        let width = self.cached_width.get();
+        match self.entries.last_mut() {
+            Some(entry) if matches!(entry.cell, HistoryCell::Review { .. }) => {
+                entry.cell.push_review_finding(finding);
+                if width > 0 {
+                    entry.line_count.set(entry.cell.height(width));
+                }
+            }
+            _ => self.add_to_history(HistoryCell::new_review(finding)),
+        }
+    }
+
+    /// The findings of the latest review, in the order they are shown.
+    pub fn latest_review_findings(&self) -> Vec<ReviewFinding> {
+        self.entries
+            .iter()
+            .rev()
+            .find_map(|entry| match &entry.cell {
+                HistoryCell::Review { cell, .. } => Some(cell.findings()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn add_finding_diff(&mut self, finding: &ReviewFinding, hunks: &[String]) {
+        self.add_to_history(HistoryCell::new_finding_diff(finding, hunks));
+    }
+
+    /// Apply `update` to the newest sub-agent cell that satisfies `pred`.
+    fn update_sub_agent(
+        &mut self,
+        pred: impl Fn(&SubAgentCell) -> bool,
+        update: impl FnOnce(&mut SubAgentCell),
+    ) {
+        let width = self.cached_width.get();
+        let Some(entry) =
+            self.entries.iter_mut().rev().find(
+                |entry| matches!(&entry.cell, HistoryCell::SubAgent { cell, .. } if pred(cell)),
+            )
+        else {
+            return;
+        };
+        entry.cell.update_sub_agent(update);
+        if width > 0 {
+            entry.line_count.set(entry.cell.height(width));
+        }
+    }
+
     fn add_to_history(&mut self, cell: HistoryCell) {
         let width = self.cached_width.get();
         let count = if width > 0 { cell.height(width) } else { 0 };
@@ -246,6 +600,22 @@ impl ConversationHistoryWidget {
         });
     }
 
+    pub fn append_exec_output(&mut self, call_id: &str, chunk: &str) {
+        let width = self.cached_width.get();
+        for entry in self.entries.iter_mut() {
+            if matches!(
+                &entry.cell,
+                HistoryCell::ActiveExecCommand { call_id: id, .. } if id == call_id
+            ) {
+                entry.cell.append_exec_output(chunk);
+                if width > 0 {
+                    entry.line_count.set(entry.cell.height(width));
+                }
+                break;
+            }
+        }
+    }
+
     pub fn record_completed_exec_command(
         &mut self,
         call_id: String,
@@ -284,6 +654,22 @@ impl ConversationHistoryWidget {
         }
     }
 
+    pub fn update_mcp_tool_call_progress(&mut self, progress: &McpToolCallProgressEvent) {
+        let width = self.cached_width.get();
+        for entry in self.entries.iter_mut() {
+            if matches!(
+                &entry.cell,
+                HistoryCell::ActiveMcpToolCall { call_id, .. } if *call_id == progress.call_id
+            ) {
+                entry.cell.set_mcp_tool_call_progress(progress);
+                if width > 0 {
+                    entry.line_count.set(entry.cell.height(width));
+                }
+                break;
+            }
+        }
+    }
+
     pub fn record_completed_mcp_tool_call(
         &mut self,
         call_id: String,
@@ -322,9 +708,14 @@ impl ConversationHistoryWidget {
 
 impl WidgetRef for ConversationHistoryWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let (title, border_style) = if self.has_input_focus {
+        let (title, border_style) = if self.selection.is_some() {
+            (
+                "Select (j/k, ctrl-d/ctrl-u, g/G = extend,  y = copy,  v = cancel)",
+                Style::default().fg(Color::LightYellow),
+            )
+        } else if self.has_input_focus {
             (
-                "Messages (↑/↓ or j/k = line,  b/space = page)",
+                "Messages (j/k, ctrl-d/ctrl-u, g/G = scroll,  / = search,  v = select,  z = expand output)",
                 Style::default().fg(Color::LightYellow),
             )
         } else {
@@ -439,6 +830,19 @@ impl WidgetRef for ConversationHistoryWidget {
             }
         }
 
+        // Highlight the selected lines.
+        if let Some((anchor, cursor)) = self.selection {
+            let selected = anchor.min(cursor)..=anchor.max(cursor);
+            for row in 0..inner.height {
+                if selected.contains(&(scroll_pos + row as usize)) {
+                    buf.set_style(
+                        Rect::new(inner.x, inner.y + row, effective_width, 1),
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    );
+                }
+            }
+        }
+
         // Always render a scrollbar *track* so the reserved column is filled.
         let overflow = num_lines.saturating_sub(viewport_height);
 
@@ -488,9 +892,48 @@ impl WidgetRef for ConversationHistoryWidget {
     }
 }
 
-/// Common [`Wrap`] configuration used for both measurement and rendering so
-/// they stay in sync.
-#[inline]
-pub(crate) const fn wrap_cfg() -> ratatui::widgets::Wrap {
-    ratatui::widgets::Wrap { trim: false }
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use codex_core::config::ConfigOverrides;
+    use codex_core::config::ConfigToml;
+
+    fn texts(cell: &HistoryCell) -> Vec<String> {
+        let Some(view) = cell.text_block() else {
+            panic!("reasoning cells are text");
+        };
+        view.lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn summarized_reasoning_expands_and_collapses() {
+        let codex_home = tempfile::tempdir().unwrap();
+        let mut config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .unwrap();
+        config.tui.sender_break_line = false;
+        config.tui.message_spacing = false;
+        let mut history = ConversationHistoryWidget::new();
+        assert!(!history.toggle_reasoning(&config, "ctrl-o"));
+
+        let text = "**Planning the fix**\nstep one\n\nstep two".to_string();
+        history.add_agent_reasoning_summary(&config, text, "ctrl-o");
+        let summary = vec!["thinking Planning the fix · 4 lines (ctrl-o to expand)".to_string()];
+        assert_eq!(texts(&history.entries[0].cell), summary);
+
+        assert!(history.toggle_reasoning(&config, "ctrl-o"));
+        let expanded = texts(&history.entries[0].cell);
+        assert_eq!(expanded[0], "thinking Planning the fix");
+        assert!(expanded.iter().any(|line| line.contains("step two")));
+
+        assert!(history.toggle_reasoning(&config, "ctrl-o"));
+        assert_eq!(texts(&history.entries[0].cell), summary);
+    }
 }
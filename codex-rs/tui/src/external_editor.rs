@@ -0,0 +1,75 @@
+//! Edit the composer's draft in the user's editor: `$VISUAL`, then
+//! `$EDITOR`, then `nvim`. The caller hands the terminal over for the
+//! duration, see [`crate::tui::suspend`].
+
+use std::io::Write;
+use std::process::Command;
+
+const DEFAULT_EDITOR: &str = "nvim";
+
+/// Run the editor on a temporary Markdown file holding `draft` and return the
+/// text it was saved with. Errors are messages for the user.
+pub(crate) fn edit_in_external_editor(draft: &str) -> Result<String, String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("codex-prompt-")
+        .suffix(".md")
+        .tempfile()
+        .map_err(|e| format!("cannot create a file for the draft: {e}"))?;
+    file.write_all(draft.as_bytes())
+        .and_then(|()| file.flush())
+        .map_err(|e| format!("cannot write the draft: {e}"))?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    // `$EDITOR` may carry arguments, as in `code --wait`.
+    let mut argv = shlex::split(&editor)
+        .filter(|argv| !argv.is_empty())
+        .ok_or_else(|| format!("cannot parse the editor command `{editor}`"))?;
+    let program = argv.remove(0);
+    let status = Command::new(&program)
+        .args(argv)
+        .arg(file.path())
+        .status()
+        .map_err(|e| format!("cannot run `{program}`: {e}"))?;
+    if !status.success() {
+        return Err(format!("`{editor}` exited with {status}"));
+    }
+
+    let edited = std::fs::read_to_string(file.path())
+        .map_err(|e| format!("cannot read the edited draft: {e}"))?;
+    // Editors end the file with a newline the draft did not have.
+    Ok(edited
+        .strip_suffix('\n')
+        .map(|text| text.strip_suffix('\r').unwrap_or(text))
+        .unwrap_or(&edited)
+        .to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_what_the_editor_saved() {
+        // The editor gets the file as `$0`; it checks that the draft is there
+        // and replaces it.
+        let editor = r#"sh -c 'grep -qx "the draft" "$0" && printf "edited\n" > "$0"'"#;
+        unsafe {
+            std::env::remove_var("VISUAL");
+            std::env::set_var("EDITOR", editor);
+        }
+        assert_eq!(
+            edit_in_external_editor("the draft"),
+            Ok("edited".to_string())
+        );
+
+        unsafe {
+            std::env::set_var("EDITOR", "false");
+        }
+        assert_eq!(
+            edit_in_external_editor("the draft"),
+            Err("`false` exited with exit status: 1".to_string())
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use crate::cell_widget::CellWidget;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::markdown::append_markdown;
+use crate::review_cell::ReviewCell;
+use crate::review_cell::diff_lines;
+use crate::sub_agent_cell::SubAgentCell;
 use crate::text_block::TextBlock;
 use crate::text_formatting::format_and_truncate_tool_result;
 use base64::Engine;
@@ -8,9 +11,16 @@ use codex_ansi_escape::ansi_escape_line;
 use codex_common::elapsed::format_duration;
 use codex_core::WireApi;
 use codex_core::config::Config;
-use codex_core::model_supports_reasoning_summaries;
+use codex_core::openai_api_key::OPENAI_API_KEY_ENV_VAR;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::McpServerStatus;
+use codex_core::protocol::McpToolCallProgressEvent;
+use codex_core::protocol::ProjectDoc;
+use codex_core::protocol::ReviewFinding;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::TokenUsage;
+use codex_login::DEFAULT_ACCOUNT;
+use codex_login::read_account_info;
 use image::DynamicImage;
 use image::GenericImageView;
 use image::ImageReader;
@@ -27,6 +37,7 @@ use ratatui_image::Resize as ImgResize;
 use ratatui_image::picker::ProtocolType;
 use std::collections::HashMap;
 use std::io::Cursor;
+use std::path::Path;
 use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
@@ -96,11 +107,20 @@ pub enum HistoryCell {
         /// The shell command, escaped and formatted.
         command: String,
         start: Instant,
+        /// Most recent output, at most [`LIVE_OUTPUT_TAIL_LINES`] lines plus
+        /// the line currently being written, and no more than
+        /// [`LIVE_OUTPUT_TAIL_MAX_BYTES`].
+        output_tail: String,
         view: TextBlock,
     },
 
-    /// Completed exec tool call.
-    CompletedExecCommand { view: TextBlock },
+    /// Completed exec tool call. Long output is shown folded to its first
+    /// and last lines; `folded` holds the other rendering, which
+    /// [`HistoryCell::toggle_fold`] swaps in.
+    CompletedExecCommand {
+        view: TextBlock,
+        folded: Option<TextBlock>,
+    },
 
     /// An MCP tool call that has not finished yet.
     ActiveMcpToolCall {
@@ -130,6 +150,13 @@ pub enum HistoryCell {
         render_cache: std::cell::RefCell<Option<ImageRenderCache>>,
     },
 
+    /// Local image referenced by a markdown image in an agent message, shown
+    /// below the message when `tui.inline_images` is enabled.
+    InlineImage {
+        image: DynamicImage,
+        render_cache: std::cell::RefCell<Option<ImageRenderCache>>,
+    },
+
     /// Background event.
     BackgroundEvent { view: TextBlock },
 
@@ -143,10 +170,57 @@ pub enum HistoryCell {
     /// behaviour of `ActiveExecCommand` so the user sees *what* patch the
     /// model wants to apply before being prompted to approve or deny it.
     PendingPatch { view: TextBlock },
+
+    /// A `spawn_agent` sub-task with the steps of the sub-agent.
+    SubAgent { cell: SubAgentCell, view: TextBlock },
+
+    /// The findings of a `/review`, grouped by file.
+    Review { cell: ReviewCell, view: TextBlock },
 }
 
 const TOOL_CALL_MAX_LINES: usize = 5;
 
+/// Lines of exec output shown above and below the fold marker.
+const EXEC_OUTPUT_HEAD_LINES: usize = 3;
+const EXEC_OUTPUT_TAIL_LINES: usize = 2;
+
+/// Number of output lines shown while an exec command is still running.
+const LIVE_OUTPUT_TAIL_LINES: usize = 20;
+
+/// Most output kept for a running exec command, for output that does not
+/// come in lines.
+const LIVE_OUTPUT_TAIL_MAX_BYTES: usize = 16 * 1024;
+
+/// The login the session uses, e.g. `work (me@example.com, pro, org org-1)`,
+/// or `None` if the provider does not use ChatGPT logins or there is no login.
+pub(crate) fn compose_account_display(config: &Config) -> Option<String> {
+    let uses_openai_key = config.model_provider.env_key.as_deref() == Some(OPENAI_API_KEY_ENV_VAR);
+    if !uses_openai_key {
+        return None;
+    }
+    let account = config.account.as_deref();
+    let info = read_account_info(&config.codex_home, account).ok()?;
+    let name = account.unwrap_or(DEFAULT_ACCOUNT);
+    let org = config.org_id.clone().or(info.org_id);
+    let details: Vec<String> = [
+        info.email,
+        info.plan,
+        org.map(|org| format!("org {org}")),
+        config
+            .workspace_id
+            .as_ref()
+            .map(|workspace| format!("workspace {workspace}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    Some(if details.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name} ({})", details.join(", "))
+    })
+}
+
 impl HistoryCell {
     pub(crate) fn new_session_info(
         config: &Config,
@@ -158,6 +232,8 @@ impl HistoryCell {
             session_id,
             history_log_id: _,
             history_entry_count: _,
+            mcp_prompts: _,
+            custom_prompts: _,
         } = event;
         if is_first_event {
             const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -171,7 +247,7 @@ impl HistoryCell {
                 ]),
                 Line::from(""),
                 Line::from(vec![
-                    "codex session".magenta().bold(),
+                    "codex resume".magenta().bold(),
                     " ".into(),
                     session_id.to_string().dim(),
                 ]),
@@ -185,7 +261,9 @@ impl HistoryCell {
                 ("sandbox", format!("{:?}", config.sandbox_policy)),
             ];
             if config.model_provider.wire_api == WireApi::Responses
-                && model_supports_reasoning_summaries(&config.model)
+                && config
+                    .model_provider
+                    .supports_reasoning_summaries(&config.model)
             {
                 entries.push((
                     "reasoning effort",
@@ -196,6 +274,22 @@ impl HistoryCell {
                     config.model_reasoning_summary.to_string(),
                 ));
             }
+            if config.model_provider.wire_api == WireApi::Anthropic {
+                let caching = &config.prompt_caching;
+                let value = match (caching.enabled, caching.conversation) {
+                    (false, _) => "off".to_string(),
+                    (true, true) => format!("prompt, tools, conversation ({})", caching.ttl),
+                    (true, false) => format!("prompt, tools ({})", caching.ttl),
+                };
+                entries.push(("prompt caching", value));
+            }
+            if let Some(account) = compose_account_display(config) {
+                entries.push(("account", account));
+            }
+            let restrictions = config.tools.describe();
+            if !restrictions.is_empty() {
+                entries.push(("tools", restrictions.join("; ")));
+            }
             for (key, value) in entries {
                 lines.push(Line::from(vec![format!("{key}: ").bold(), value.into()]));
             }
@@ -345,21 +439,80 @@ impl HistoryCell {
         }
     }
 
+    /// Agent reasoning collapsed to one line: its heading, or first line,
+    /// and how long it is.
+    pub(crate) fn new_agent_reasoning_summary(
+        config: &Config,
+        text: &str,
+        expand_key: &str,
+    ) -> Self {
+        let headline = text
+            .lines()
+            .map(|line| line.trim().trim_matches(|c| c == '#' || c == '*').trim())
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+        let line_count = text.lines().count();
+        let mut lines = vec![Line::from(vec![
+            "thinking".magenta().italic(),
+            " ".into(),
+            headline.to_string().italic(),
+            format!(" · {line_count} lines ({expand_key} to expand)").dim(),
+        ])];
+        if config.tui.message_spacing {
+            lines.push(Line::from(""));
+        }
+        HistoryCell::AgentReasoning {
+            view: TextBlock::new(lines),
+        }
+    }
+
     pub(crate) fn new_active_exec_command(call_id: String, command: Vec<String>) -> Self {
         let command_escaped = strip_bash_lc_and_escape(&command);
         let start = Instant::now();
 
-        let lines: Vec<Line<'static>> = vec![
-            Line::from(vec!["command".magenta(), " running...".dim()]),
-            Line::from(format!("$ {command_escaped}")),
-            Line::from(""),
-        ];
+        let view = TextBlock::new(active_exec_command_lines(&command_escaped, ""));
 
         HistoryCell::ActiveExecCommand {
             call_id,
             command: command_escaped,
             start,
-            view: TextBlock::new(lines),
+            output_tail: String::new(),
+            view,
+        }
+    }
+
+    /// Append streamed output to a running exec command, keeping only the
+    /// last [`LIVE_OUTPUT_TAIL_LINES`] lines on screen. Text a carriage return
+    /// overwrites is dropped, so progress bars do not pile up.
+    pub(crate) fn append_exec_output(&mut self, chunk: &str) {
+        if let HistoryCell::ActiveExecCommand {
+            command,
+            output_tail,
+            view,
+            ..
+        } = self
+        {
+            output_tail.push_str(chunk);
+            if output_tail.contains('\r') {
+                *output_tail = collapse_carriage_returns(output_tail);
+            }
+            // Drop whole lines from the front once there are more than we show.
+            let newlines = output_tail.matches('\n').count();
+            if let Some(idx) = newlines
+                .checked_sub(LIVE_OUTPUT_TAIL_LINES + 1)
+                .and_then(|n| output_tail.match_indices('\n').nth(n))
+                .map(|(idx, _)| idx)
+            {
+                output_tail.drain(..=idx);
+            }
+            if output_tail.len() > LIVE_OUTPUT_TAIL_MAX_BYTES {
+                let mut start = output_tail.len() - LIVE_OUTPUT_TAIL_MAX_BYTES;
+                while !output_tail.is_char_boundary(start) {
+                    start += 1;
+                }
+                output_tail.drain(..start);
+            }
+            *view = TextBlock::new(active_exec_command_lines(command, output_tail));
         }
     }
 
@@ -403,22 +556,32 @@ impl HistoryCell {
                 lines.push(Line::from(indent + cmd_line));
             }
         }
-        let mut lines_iter = if exit_code == 0 {
+        // Commands run on a PTY report all of their output as stdout.
+        let output: Vec<Line<'static>> = if exit_code == 0 || stderr.is_empty() {
             stdout.lines()
         } else {
             stderr.lines()
-        };
-        for raw in lines_iter.by_ref().take(TOOL_CALL_MAX_LINES) {
-            lines.push(ansi_escape_line(raw).dim());
-        }
-        let remaining = lines_iter.count();
-        if remaining > 0 {
-            lines.push(Line::from(format!("... {} additional lines", remaining)).dim());
         }
-        lines.push(Line::from(""));
-
-        HistoryCell::CompletedExecCommand {
-            view: TextBlock::new(lines),
+        .map(exec_output_line)
+        .collect();
+        let folded_output = fold_output_lines(&output);
+        let mut full = lines.clone();
+        full.extend(output);
+        full.push(Line::from(""));
+
+        match folded_output {
+            Some(folded_output) => {
+                lines.extend(folded_output);
+                lines.push(Line::from(""));
+                HistoryCell::CompletedExecCommand {
+                    view: TextBlock::new(lines),
+                    folded: Some(TextBlock::new(full)),
+                }
+            }
+            None => HistoryCell::CompletedExecCommand {
+                view: TextBlock::new(full),
+                folded: None,
+            },
         }
     }
 
@@ -450,8 +613,7 @@ impl HistoryCell {
         let invocation = Line::from(invocation_spans);
 
         let start = Instant::now();
-        let title_line = Line::from(vec!["tool".magenta(), " running...".dim()]);
-        let lines: Vec<Line<'static>> = vec![title_line, invocation.clone(), Line::from("")];
+        let lines = active_mcp_tool_call_lines(&invocation, None);
 
         HistoryCell::ActiveMcpToolCall {
             call_id,
@@ -461,6 +623,16 @@ impl HistoryCell {
         }
     }
 
+    /// Shows the latest progress reported for a running MCP tool call.
+    pub(crate) fn set_mcp_tool_call_progress(&mut self, progress: &McpToolCallProgressEvent) {
+        if let HistoryCell::ActiveMcpToolCall {
+            invocation, view, ..
+        } = self
+        {
+            *view = TextBlock::new(active_mcp_tool_call_lines(invocation, Some(progress)));
+        }
+    }
+
     /// If the first content is an image, return a new cell with the image.
     /// TODO(rgwood-dd): Handle images properly even if they're not the first result.
     fn try_new_completed_mcp_tool_call_with_image_output(
@@ -507,6 +679,26 @@ impl HistoryCell {
         }
     }
 
+    pub(crate) fn new_inline_image(path: &Path) -> Option<Self> {
+        let reader = match ImageReader::open(path).and_then(|r| r.with_guessed_format()) {
+            Ok(reader) => reader,
+            Err(e) => {
+                error!("Failed to open image {}: {e}", path.display());
+                return None;
+            }
+        };
+        match reader.decode() {
+            Ok(image) => Some(HistoryCell::InlineImage {
+                image,
+                render_cache: std::cell::RefCell::new(None),
+            }),
+            Err(e) => {
+                error!("Image decoding failed for {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
     pub(crate) fn new_completed_mcp_tool_call(
         num_cols: u16,
         invocation: Line<'static>,
@@ -600,6 +792,117 @@ impl HistoryCell {
         }
     }
 
+    pub(crate) fn new_context_compacted(tokens_before: u64, tokens_after: u64) -> Self {
+        let lines: Vec<Line<'static>> = vec![
+            Line::from("context compacted".magenta().bold()),
+            Line::from(format!(
+                "Earlier turns were replaced by a summary: {tokens_before} → {tokens_after} tokens"
+            ))
+            .dim(),
+            Line::from(""),
+        ];
+        HistoryCell::BackgroundEvent {
+            view: TextBlock::new(lines),
+        }
+    }
+
+    /// One line per MCP server: ready with its tool count, or why it failed.
+    pub(crate) fn new_mcp_startup(servers: &[McpServerStatus]) -> Self {
+        let mut lines: Vec<Line<'static>> = vec![Line::from("MCP servers".magenta().bold())];
+        for status in servers {
+            let elapsed = format_duration(Duration::from_millis(status.startup_ms));
+            let line = match &status.result {
+                Ok(tools) => Line::from(vec![
+                    "✓ ".green(),
+                    status.server.clone().bold(),
+                    format!(" ready ({tools} tools, {elapsed})").dim(),
+                ]),
+                Err(err) => Line::from(vec![
+                    "✗ ".red(),
+                    status.server.clone().bold(),
+                    format!(" failed after {elapsed}: {err}").red(),
+                ]),
+            };
+            lines.push(line);
+        }
+        lines.push(Line::from(""));
+        HistoryCell::BackgroundEvent {
+            view: TextBlock::new(lines),
+        }
+    }
+
+    /// The paths of the AGENTS.md files loaded so far.
+    pub(crate) fn new_project_docs_loaded(docs: &[ProjectDoc]) -> Self {
+        let mut lines: Vec<Line<'static>> = vec![Line::from("AGENTS.md".magenta().bold())];
+        lines.extend(
+            docs.iter()
+                .map(|doc| Line::from(format!("  {}", doc.path.display())).dim()),
+        );
+        lines.push(Line::from(""));
+        HistoryCell::BackgroundEvent {
+            view: TextBlock::new(lines),
+        }
+    }
+
+    /// The contents of the AGENTS.md files, in the order of their precedence,
+    /// for `/agents`.
+    pub(crate) fn new_project_docs(config: &Config, docs: &[ProjectDoc]) -> Self {
+        let mut lines: Vec<Line<'static>> = vec![Line::from("AGENTS.md".magenta().bold())];
+        if docs.is_empty() {
+            lines.push(Line::from("No AGENTS.md files were loaded.").dim());
+        } else if docs.len() > 1 {
+            lines.push(
+                Line::from("Later files are more specific and take precedence over earlier ones.")
+                    .dim(),
+            );
+        }
+        for doc in docs {
+            lines.push(Line::from(""));
+            lines.push(Line::from(doc.path.display().to_string().bold()));
+            append_markdown(&doc.contents, &mut lines, config);
+        }
+        lines.push(Line::from(""));
+        HistoryCell::BackgroundEvent {
+            view: TextBlock::new(lines),
+        }
+    }
+
+    /// One dim line with the token usage and wall time of a finished turn.
+    pub(crate) fn new_turn_stats(usage: &TokenUsage, elapsed: Duration) -> Self {
+        let mut input = format!("{} input", usage.input_tokens);
+        if let (Some(cached), Some(ratio)) = (
+            usage.cached_input_tokens.filter(|cached| *cached > 0),
+            usage.cache_hit_ratio(),
+        ) {
+            input.push_str(&format!(" ({cached} cached, {:.0}% hit)", ratio * 100.0));
+        }
+        let mut output = format!("{} output", usage.output_tokens);
+        if let Some(reasoning) = usage.reasoning_output_tokens.filter(|r| *r > 0) {
+            output.push_str(&format!(" ({reasoning} reasoning)"));
+        }
+        let line = format!(
+            "turn: {input} · {output} tokens · {:.1}s",
+            elapsed.as_secs_f64()
+        );
+        HistoryCell::BackgroundEvent {
+            view: TextBlock::new(vec![Line::from(line).dim(), Line::from("")]),
+        }
+    }
+
+    pub(crate) fn new_turn_reverted(files: &[PathBuf]) -> Self {
+        let mut lines: Vec<Line<'static>> = vec![Line::from("undo".magenta().bold())];
+        lines.push(Line::from("Reverted the file changes of the last turn:").dim());
+        lines.extend(
+            files
+                .iter()
+                .map(|path| Line::from(format!("  {}", path.display())).dim()),
+        );
+        lines.push(Line::from(""));
+        HistoryCell::BackgroundEvent {
+            view: TextBlock::new(lines),
+        }
+    }
+
     pub(crate) fn new_error_event(message: String) -> Self {
         let lines: Vec<Line<'static>> = vec![
             vec!["ERROR: ".red().bold(), message.into()].into(),
@@ -672,6 +975,163 @@ impl HistoryCell {
             view: TextBlock::new(lines),
         }
     }
+
+    pub(crate) fn new_sub_agent(cell: SubAgentCell) -> Self {
+        let view = TextBlock::new(cell.lines());
+        HistoryCell::SubAgent { cell, view }
+    }
+
+    /// Update a sub-agent cell and re-render it.
+    pub(crate) fn update_sub_agent(&mut self, update: impl FnOnce(&mut SubAgentCell)) {
+        if let HistoryCell::SubAgent { cell, view } = self {
+            update(cell);
+            *view = TextBlock::new(cell.lines());
+        }
+    }
+
+    pub(crate) fn new_review(finding: ReviewFinding) -> Self {
+        let mut cell = ReviewCell::default();
+        cell.push(finding);
+        let view = TextBlock::new(cell.lines());
+        HistoryCell::Review { cell, view }
+    }
+
+    /// Add a finding to a review cell and re-render it.
+    pub(crate) fn push_review_finding(&mut self, finding: ReviewFinding) {
+        if let HistoryCell::Review { cell, view } = self {
+            cell.push(finding);
+            *view = TextBlock::new(cell.lines());
+        }
+    }
+
+    /// The hunks of the diff around a review finding.
+    pub(crate) fn new_finding_diff(finding: &ReviewFinding, hunks: &[String]) -> Self {
+        HistoryCell::BackgroundEvent {
+            view: TextBlock::new(diff_lines(finding, hunks)),
+        }
+    }
+
+    /// Whether the cell has long output that [`Self::toggle_fold`] expands.
+    pub(crate) fn is_foldable(&self) -> bool {
+        matches!(
+            self,
+            HistoryCell::CompletedExecCommand {
+                folded: Some(_),
+                ..
+            }
+        )
+    }
+
+    /// Show the full output of a folded cell, or fold it again.
+    pub(crate) fn toggle_fold(&mut self) {
+        if let HistoryCell::CompletedExecCommand {
+            view,
+            folded: Some(folded),
+        } = self
+        {
+            std::mem::swap(view, folded);
+        }
+    }
+
+    /// Text-backed cells expose their lines so the transcript can be
+    /// searched; image output has no text and returns `None`.
+    pub(crate) fn text_block(&self) -> Option<&TextBlock> {
+        match self {
+            HistoryCell::WelcomeMessage { view }
+            | HistoryCell::UserPrompt { view }
+            | HistoryCell::AgentMessage { view }
+            | HistoryCell::AgentReasoning { view }
+            | HistoryCell::BackgroundEvent { view }
+            | HistoryCell::ErrorEvent { view }
+            | HistoryCell::SessionInfo { view }
+            | HistoryCell::CompletedExecCommand { view, .. }
+            | HistoryCell::CompletedMcpToolCall { view }
+            | HistoryCell::PendingPatch { view }
+            | HistoryCell::ActiveExecCommand { view, .. }
+            | HistoryCell::ActiveMcpToolCall { view, .. }
+            | HistoryCell::SubAgent { view, .. }
+            | HistoryCell::Review { view, .. } => Some(view),
+            HistoryCell::CompletedMcpToolCallWithImageOutput { .. }
+            | HistoryCell::InlineImage { .. } => None,
+        }
+    }
+}
+
+fn active_mcp_tool_call_lines(
+    invocation: &Line<'static>,
+    progress: Option<&McpToolCallProgressEvent>,
+) -> Vec<Line<'static>> {
+    let mut title_line = Line::from(vec!["tool".magenta(), " running...".dim()]);
+    if let Some(progress) = progress {
+        if let Some(percent) = progress.percent() {
+            title_line.spans.push(format!(" {percent}%").cyan());
+        }
+        if let Some(message) = &progress.message {
+            title_line.spans.push(format!(" {message}").dim());
+        }
+    }
+    vec![title_line, invocation.clone(), Line::from("")]
+}
+
+fn active_exec_command_lines(command: &str, output_tail: &str) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = vec![
+        Line::from(vec!["command".magenta(), " running...".dim()]),
+        Line::from(format!("$ {command}")),
+    ];
+    let output_lines: Vec<&str> = output_tail.lines().collect();
+    let skip = output_lines.len().saturating_sub(LIVE_OUTPUT_TAIL_LINES);
+    for raw in &output_lines[skip..] {
+        lines.push(exec_output_line(raw));
+    }
+    lines.push(Line::from(""));
+    lines
+}
+
+/// `text` with each line cut to what follows its last carriage return. A
+/// carriage return at the end of a line is kept: it may be the start of a
+/// `\r\n` whose `\n` has not arrived yet.
+fn collapse_carriage_returns(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let body = line.strip_suffix('\r').unwrap_or(line);
+            match body.rfind('\r') {
+                Some(idx) => &line[idx + 1..],
+                None => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The first and last lines of `output` around a "… +N lines" marker, or
+/// `None` when it is short enough to show in full.
+fn fold_output_lines(output: &[Line<'static>]) -> Option<Vec<Line<'static>>> {
+    // Folding a single line would not save any space.
+    let hidden = output
+        .len()
+        .checked_sub(EXEC_OUTPUT_HEAD_LINES + EXEC_OUTPUT_TAIL_LINES)
+        .filter(|hidden| *hidden > 1)?;
+    let mut lines = output[..EXEC_OUTPUT_HEAD_LINES].to_vec();
+    lines.push(Line::from(format!("… +{hidden} lines")).dim());
+    lines.extend_from_slice(&output[output.len() - EXEC_OUTPUT_TAIL_LINES..]);
+    Some(lines)
+}
+
+/// One line of command output. Plain output is dimmed; output that carries
+/// its own ANSI styling (e.g. from a command run on a PTY) keeps its colors.
+fn exec_output_line(raw: &str) -> Line<'static> {
+    // Progress bars redraw the current line with `\r`; show the latest.
+    let raw = raw
+        .trim_end_matches('\r')
+        .rsplit('\r')
+        .next()
+        .unwrap_or_default();
+    let line = ansi_escape_line(raw);
+    if line.spans.iter().all(|span| span.style == Style::default()) {
+        line.dim()
+    } else {
+        line
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -690,14 +1150,20 @@ impl CellWidget for HistoryCell {
             | HistoryCell::BackgroundEvent { view }
             | HistoryCell::ErrorEvent { view }
             | HistoryCell::SessionInfo { view }
-            | HistoryCell::CompletedExecCommand { view }
+            | HistoryCell::CompletedExecCommand { view, .. }
             | HistoryCell::CompletedMcpToolCall { view }
             | HistoryCell::PendingPatch { view }
             | HistoryCell::ActiveExecCommand { view, .. }
-            | HistoryCell::ActiveMcpToolCall { view, .. } => view.height(width),
+            | HistoryCell::ActiveMcpToolCall { view, .. }
+            | HistoryCell::SubAgent { view, .. }
+            | HistoryCell::Review { view, .. } => view.height(width),
             HistoryCell::CompletedMcpToolCallWithImageOutput {
                 image,
                 render_cache,
+            }
+            | HistoryCell::InlineImage {
+                image,
+                render_cache,
             } => ensure_image_cache(image, width, render_cache),
         }
     }
@@ -711,16 +1177,20 @@ impl CellWidget for HistoryCell {
             | HistoryCell::BackgroundEvent { view }
             | HistoryCell::ErrorEvent { view }
             | HistoryCell::SessionInfo { view }
-            | HistoryCell::CompletedExecCommand { view }
+            | HistoryCell::CompletedExecCommand { view, .. }
             | HistoryCell::CompletedMcpToolCall { view }
             | HistoryCell::PendingPatch { view }
             | HistoryCell::ActiveExecCommand { view, .. }
-            | HistoryCell::ActiveMcpToolCall { view, .. } => {
-                view.render_window(first_visible_line, area, buf)
-            }
+            | HistoryCell::ActiveMcpToolCall { view, .. }
+            | HistoryCell::SubAgent { view, .. }
+            | HistoryCell::Review { view, .. } => view.render_window(first_visible_line, area, buf),
             HistoryCell::CompletedMcpToolCallWithImageOutput {
                 image,
                 render_cache,
+            }
+            | HistoryCell::InlineImage {
+                image,
+                render_cache,
             } => {
                 // Ensure we have a cached, resized copy that matches the current width.
                 // `height()` should have prepared the cache, but if something invalidated it
@@ -899,3 +1369,105 @@ fn ensure_image_cache(
 
     height_rows
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(view: &TextBlock) -> Vec<String> {
+        view.lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    fn completed(stdout: String) -> HistoryCell {
+        HistoryCell::new_completed_exec_command(
+            "seq 20".to_string(),
+            CommandOutput {
+                exit_code: 0,
+                stdout,
+                stderr: String::new(),
+                duration: Duration::from_millis(3),
+            },
+        )
+    }
+
+    #[test]
+    fn long_exec_output_is_folded_until_toggled() {
+        let stdout = (1..=20).map(|i| format!("{i}\n")).collect::<String>();
+        let mut cell = completed(stdout);
+        assert!(cell.is_foldable());
+        let Some(view) = cell.text_block() else {
+            panic!("exec cells are text");
+        };
+        assert_eq!(
+            texts(view)[1..],
+            ["1", "2", "3", "… +15 lines", "19", "20", ""]
+        );
+
+        cell.toggle_fold();
+        let Some(view) = cell.text_block() else {
+            panic!("exec cells are text");
+        };
+        assert_eq!(view.lines.len(), 22);
+
+        cell.toggle_fold();
+        assert_eq!(cell.text_block().map(|view| view.lines.len()), Some(8));
+    }
+
+    fn output_tail(cell: &HistoryCell) -> &str {
+        match cell {
+            HistoryCell::ActiveExecCommand { output_tail, .. } => output_tail,
+            _ => panic!("not a running exec cell"),
+        }
+    }
+
+    #[test]
+    fn live_output_keeps_the_last_lines() {
+        let mut cell = HistoryCell::new_active_exec_command("1".to_string(), vec!["seq".into()]);
+        for i in 1..=30 {
+            cell.append_exec_output(&format!("{i}\n"));
+        }
+        cell.append_exec_output("3");
+        let expected: String = (11..=30).map(|i| format!("{i}\n")).collect();
+        assert_eq!(output_tail(&cell), format!("{expected}3"));
+    }
+
+    #[test]
+    fn carriage_returns_overwrite_the_line() {
+        let mut cell = HistoryCell::new_active_exec_command("1".to_string(), vec!["dl".into()]);
+        cell.append_exec_output("start\n");
+        for percent in 0..=100 {
+            cell.append_exec_output(&format!("\r{percent}%"));
+        }
+        assert_eq!(output_tail(&cell), "start\n100%");
+
+        // A `\r\n` split across chunks is still a line ending.
+        cell.append_exec_output("\r");
+        cell.append_exec_output("\ndone\r\n");
+        assert_eq!(output_tail(&cell), "start\n100%\r\ndone\r\n");
+        let Some(view) = cell.text_block() else {
+            panic!("exec cells are text");
+        };
+        assert_eq!(texts(view)[2..], ["start", "100%", "done", ""]);
+    }
+
+    #[test]
+    fn output_without_line_breaks_is_capped() {
+        let mut cell = HistoryCell::new_active_exec_command("1".to_string(), vec!["yes".into()]);
+        for _ in 0..10_000 {
+            cell.append_exec_output("é…");
+        }
+        let tail = output_tail(&cell);
+        assert!(tail.len() <= LIVE_OUTPUT_TAIL_MAX_BYTES);
+        assert!(tail.len() > LIVE_OUTPUT_TAIL_MAX_BYTES - 4);
+    }
+
+    #[test]
+    fn short_exec_output_is_shown_in_full() {
+        let cell = completed("1\n2\n3\n4\n5\n6\n".to_string());
+        assert!(!cell.is_foldable());
+        assert_eq!(cell.text_block().map(|view| view.lines.len()), Some(8));
+    }
+}
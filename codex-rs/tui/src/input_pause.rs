@@ -0,0 +1,112 @@
+//! Hands the terminal's input over from the input thread to an external
+//! program (the editor) and back. Polling the terminal already consumes the
+//! bytes typed, so the input thread has to be out of `poll`/`read` before
+//! the program starts, not merely told to stop.
+
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+#[derive(Default)]
+pub(crate) struct InputPause {
+    state: Mutex<PauseState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct PauseState {
+    /// The main thread wants the terminal's input.
+    requested: bool,
+    /// The input thread has stopped reading, for the pause or for good.
+    parked: bool,
+}
+
+impl InputPause {
+    /// Ask the input thread to stop reading and wait until it has.
+    pub(crate) fn pause(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.requested = true;
+        while !state.parked {
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Let the input thread read again.
+    pub(crate) fn resume(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.requested = false;
+        self.changed.notify_all();
+    }
+
+    /// Called by the input thread between reads: if a pause was asked for,
+    /// acknowledge it and block until it is over.
+    pub(crate) fn wait_while_paused(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !state.requested {
+            return;
+        }
+        state.parked = true;
+        self.changed.notify_all();
+        while state.requested {
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        state.parked = false;
+    }
+
+    /// Called when the input thread exits, so that pausing does not wait for
+    /// it.
+    pub(crate) fn stop_reading(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.parked = true;
+        self.changed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn pause_returns_once_the_reader_is_parked() {
+        let pause = Arc::new(InputPause::default());
+        let reads = Arc::new(AtomicUsize::new(0));
+        let reader = {
+            let pause = Arc::clone(&pause);
+            let reads = Arc::clone(&reads);
+            thread::spawn(move || {
+                while reads.load(Ordering::SeqCst) < 1000 {
+                    pause.wait_while_paused();
+                    reads.fetch_add(1, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(1));
+                }
+                pause.stop_reading();
+            })
+        };
+
+        pause.pause();
+        let paused_at = reads.load(Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(reads.load(Ordering::SeqCst), paused_at);
+
+        pause.resume();
+        thread::sleep(Duration::from_millis(50));
+        assert!(reads.load(Ordering::SeqCst) > paused_at);
+
+        reads.store(1000, Ordering::SeqCst);
+        #[expect(clippy::unwrap_used)]
+        reader.join().unwrap();
+        // With the reader gone, pausing does not block.
+        pause.pause();
+    }
+}
@@ -0,0 +1,416 @@
+//! Key bindings for the TUI actions that can be remapped through the `[keys]`
+//! table in config.toml, e.g.
+//!
+//! ```toml
+//! [keys]
+//! interrupt = "ctrl-x"
+//! scroll_up = ["up", "k", "ctrl-p"]
+//! ```
+//!
+//! A key is written as optional `ctrl-`, `alt-` and `shift-` prefixes followed
+//! by a character or a key name (`esc`, `enter`, `tab`, `up`, `pageup`,
+//! `f1`, ...). Listing an action replaces all of its default keys.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use codex_core::config_types::KeyBinding;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use strum::IntoEnumIterator;
+use strum_macros::AsRefStr;
+use strum_macros::EnumIter;
+use strum_macros::EnumString;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum KeyAction {
+    Interrupt,
+    Exit,
+    ToggleFocus,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    ScrollTop,
+    ScrollBottom,
+    Search,
+    Select,
+    Yank,
+    ToggleDetails,
+    ToggleOutput,
+    ReviewFindings,
+    CopyCodeBlock,
+    PasteImage,
+    ExternalEditor,
+    Steer,
+    EditQueued,
+    ExpandReasoning,
+    NextTab,
+    PrevTab,
+}
+
+impl KeyAction {
+    fn description(self) -> &'static str {
+        match self {
+            KeyAction::Interrupt => "interrupt the running task",
+            KeyAction::Exit => "exit Codex",
+            KeyAction::ToggleFocus => "switch focus between history and composer",
+            KeyAction::ScrollUp => "scroll history up one line",
+            KeyAction::ScrollDown => "scroll history down one line",
+            KeyAction::PageUp => "scroll history up one page",
+            KeyAction::PageDown => "scroll history down one page",
+            KeyAction::HalfPageUp => "scroll history up half a page",
+            KeyAction::HalfPageDown => "scroll history down half a page",
+            KeyAction::ScrollTop => "jump to the start of the history",
+            KeyAction::ScrollBottom => "jump to the end of the history",
+            KeyAction::Search => "search the history",
+            KeyAction::Select => "start/stop selecting history lines",
+            KeyAction::Yank => "copy the selected lines to the clipboard",
+            KeyAction::ToggleDetails => "expand/collapse the steps of sub-agents",
+            KeyAction::ToggleOutput => "expand/collapse the output of the command in view",
+            KeyAction::ReviewFindings => "jump to the diff of a finding of the latest review",
+            KeyAction::CopyCodeBlock => "copy a code block of the latest agent message",
+            KeyAction::PasteImage => "attach the image on the clipboard to the message",
+            KeyAction::ExternalEditor => "edit the message in $VISUAL or $EDITOR",
+            KeyAction::Steer => "send the message to the running turn as guidance",
+            KeyAction::EditQueued => "take the newest queued message back into the composer",
+            KeyAction::ExpandReasoning => "expand/collapse the latest summarized reasoning",
+            KeyAction::NextTab => "show the next tab",
+            KeyAction::PrevTab => "show the previous tab",
+        }
+    }
+
+    /// Whether the action only applies while the history pane has focus.
+    /// These keys are free to type text into the composer otherwise.
+    fn is_history_action(self) -> bool {
+        !matches!(
+            self,
+            KeyAction::Interrupt
+                | KeyAction::Exit
+                | KeyAction::ToggleFocus
+                | KeyAction::CopyCodeBlock
+                | KeyAction::PasteImage
+                | KeyAction::ExternalEditor
+                | KeyAction::Steer
+                | KeyAction::EditQueued
+                | KeyAction::ExpandReasoning
+                | KeyAction::NextTab
+                | KeyAction::PrevTab
+        )
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            KeyAction::Interrupt => &["ctrl-c"],
+            KeyAction::Exit => &["ctrl-d"],
+            KeyAction::ToggleFocus => &["tab"],
+            KeyAction::ScrollUp => &["up", "k"],
+            KeyAction::ScrollDown => &["down", "j"],
+            KeyAction::PageUp => &["pageup", "b"],
+            KeyAction::PageDown => &["pagedown", "space"],
+            KeyAction::HalfPageUp => &["ctrl-u"],
+            KeyAction::HalfPageDown => &["ctrl-d"],
+            KeyAction::ScrollTop => &["g", "home"],
+            KeyAction::ScrollBottom => &["G", "end"],
+            KeyAction::Search => &["/"],
+            KeyAction::Select => &["v"],
+            KeyAction::Yank => &["y"],
+            KeyAction::ToggleDetails => &["e"],
+            KeyAction::ToggleOutput => &["z"],
+            KeyAction::ReviewFindings => &["f"],
+            KeyAction::CopyCodeBlock => &["ctrl-y"],
+            KeyAction::PasteImage => &["ctrl-v"],
+            KeyAction::ExternalEditor => &["ctrl-g"],
+            KeyAction::Steer => &["alt-enter"],
+            KeyAction::EditQueued => &["esc"],
+            KeyAction::ExpandReasoning => &["ctrl-o"],
+            KeyAction::NextTab => &["ctrl-pagedown"],
+            KeyAction::PrevTab => &["ctrl-pageup"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn matches(&self, event: &KeyEvent) -> bool {
+        match (self.code, event.code) {
+            // Terminals differ in whether they report SHIFT for characters
+            // that already carry their case, so only the character counts.
+            (KeyCode::Char(a), KeyCode::Char(b)) => {
+                a == b
+                    && self.modifiers.difference(KeyModifiers::SHIFT)
+                        == event.modifiers.difference(KeyModifiers::SHIFT)
+            }
+            (a, b) => a == b && self.modifiers == event.modifiers,
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec.trim();
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl-") {
+                (KeyModifiers::CONTROL, "ctrl-".len())
+            } else if lower.starts_with("alt-") {
+                (KeyModifiers::ALT, "alt-".len())
+            } else if lower.starts_with("shift-") {
+                (KeyModifiers::SHIFT, "shift-".len())
+            } else {
+                break;
+            };
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').map(str::parse::<u8>) {
+                    Some(Ok(n @ 1..=12)) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{spec}`")),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+/// Current bindings of every [`KeyAction`].
+#[derive(Debug, Clone)]
+pub(crate) struct Keymap {
+    bindings: Vec<(KeyAction, Vec<(String, KeyChord)>)>,
+    /// Problems with the `[keys]` table; the affected actions keep their
+    /// default keys.
+    errors: Vec<String>,
+}
+
+impl Keymap {
+    pub(crate) fn new(keys: &HashMap<String, KeyBinding>) -> Self {
+        let mut errors = Vec::new();
+        let mut overrides = HashMap::new();
+        for (name, binding) in keys {
+            match KeyAction::from_str(name) {
+                Ok(action) => {
+                    overrides.insert(action, binding.keys());
+                }
+                Err(_) => errors.push(format!("[keys]: unknown action `{name}`")),
+            }
+        }
+
+        let bindings = KeyAction::iter()
+            .map(|action| {
+                let parsed = overrides.get(&action).and_then(|specs| {
+                    specs
+                        .iter()
+                        .map(|spec| spec.parse::<KeyChord>().map(|chord| (spec.clone(), chord)))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| errors.push(format!("[keys] {}: {e}", action.as_ref())))
+                        .ok()
+                });
+                let chords = parsed.unwrap_or_else(|| {
+                    action
+                        .default_keys()
+                        .iter()
+                        .filter_map(|spec| {
+                            Some(((*spec).to_string(), spec.parse::<KeyChord>().ok()?))
+                        })
+                        .collect()
+                });
+                (action, chords)
+            })
+            .collect();
+        errors.sort();
+        Self { bindings, errors }
+    }
+
+    /// The action bound to `event`. History actions are only considered
+    /// while the history pane has focus, and then take precedence, so e.g.
+    /// ctrl-d scrolls the history rather than exiting.
+    pub(crate) fn action_for(&self, event: &KeyEvent, history_focused: bool) -> Option<KeyAction> {
+        let find = |history: bool| {
+            self.bindings.iter().find_map(|(action, chords)| {
+                (action.is_history_action() == history
+                    && chords.iter().any(|(_, chord)| chord.matches(event)))
+                .then_some(*action)
+            })
+        };
+        if history_focused {
+            find(true).or_else(|| find(false))
+        } else {
+            find(false)
+        }
+    }
+
+    /// The first key bound to `action`, as written in the config, for hints
+    /// such as "press ctrl-d again to exit".
+    pub(crate) fn label(&self, action: KeyAction) -> &str {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, chords)| chords.first())
+            .map_or("(unbound)", |(spec, _)| spec.as_str())
+    }
+
+    pub(crate) fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// One line per action for `/keys`, followed by any config errors.
+    pub(crate) fn describe(&self) -> Vec<String> {
+        let mut lines = vec!["Key bindings (remap under [keys] in config.toml):".to_string()];
+        for (action, chords) in &self.bindings {
+            let keys = chords
+                .iter()
+                .map(|(spec, _)| spec.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!(
+                "  {:<15} {:<20} {}",
+                action.as_ref(),
+                keys,
+                action.description()
+            ));
+        }
+        lines.extend(self.errors.iter().cloned());
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_key_specs() {
+        assert_eq!(
+            "Ctrl-Alt-x".parse::<KeyChord>(),
+            Ok(KeyChord {
+                code: KeyCode::Char('x'),
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+            })
+        );
+        assert_eq!("f5".parse::<KeyChord>().map(|c| c.code), Ok(KeyCode::F(5)));
+        assert_eq!(
+            "-".parse::<KeyChord>().map(|c| c.code),
+            Ok(KeyCode::Char('-'))
+        );
+        assert!("ctrl-nope".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn config_replaces_default_keys() {
+        let keymap = Keymap::new(&HashMap::from([
+            (
+                "interrupt".to_string(),
+                KeyBinding::One("ctrl-x".to_string()),
+            ),
+            (
+                "scroll_up".to_string(),
+                KeyBinding::Many(vec!["ctrl-p".to_string(), "bogus-key".to_string()]),
+            ),
+            ("backtrack".to_string(), KeyBinding::One("esc".to_string())),
+        ]));
+
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('x'), KeyModifiers::CONTROL), false),
+            Some(KeyAction::Interrupt)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('c'), KeyModifiers::CONTROL), false),
+            None
+        );
+        // The invalid override is ignored as a whole.
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('k'), KeyModifiers::NONE), true),
+            Some(KeyAction::ScrollUp)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('p'), KeyModifiers::CONTROL), true),
+            None
+        );
+        assert_eq!(keymap.label(KeyAction::Interrupt), "ctrl-x");
+        assert_eq!(
+            keymap.errors(),
+            &[
+                "[keys] scroll_up: unknown key `bogus-key`".to_string(),
+                "[keys]: unknown action `backtrack`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn history_actions_need_history_focus() {
+        let keymap = Keymap::new(&HashMap::new());
+        let ctrl_d = key(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action_for(&ctrl_d, false), Some(KeyAction::Exit));
+        assert_eq!(
+            keymap.action_for(&ctrl_d, true),
+            Some(KeyAction::HalfPageDown)
+        );
+        let k = key(KeyCode::Char('k'), KeyModifiers::NONE);
+        assert_eq!(keymap.action_for(&k, false), None);
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('G'), KeyModifiers::SHIFT), true),
+            Some(KeyAction::ScrollBottom)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Tab, KeyModifiers::NONE), true),
+            Some(KeyAction::ToggleFocus)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::PageDown, KeyModifiers::CONTROL), false),
+            Some(KeyAction::NextTab)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Enter, KeyModifiers::ALT), false),
+            Some(KeyAction::Steer)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Esc, KeyModifiers::NONE), false),
+            Some(KeyAction::EditQueued)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('o'), KeyModifiers::CONTROL), false),
+            Some(KeyAction::ExpandReasoning)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('z'), KeyModifiers::NONE), true),
+            Some(KeyAction::ToggleOutput)
+        );
+    }
+}
@@ -3,25 +3,27 @@
 // alternate‑screen mode starts; that file opts‑out locally via `allow`.
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 use app::App;
-use codex_core::ResponseItem;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::debug_bundle::new_log_path;
+use codex_core::file_watcher::FileWatcher;
 use codex_core::openai_api_key::OPENAI_API_KEY_ENV_VAR;
 use codex_core::openai_api_key::get_openai_api_key;
 use codex_core::openai_api_key::set_openai_api_key;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::session_store;
+use codex_core::telemetry::otel_layer;
 use codex_core::util::is_inside_git_repo;
 use codex_login::try_read_openai_api_key;
 use log_layer::TuiLogLayer;
-use serde_json;
-use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader};
+use resume_picker::ResumePicker;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
 use std::path::PathBuf;
 use tracing_appender::non_blocking;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
-use uuid::Uuid;
 
 mod app;
 mod app_event;
@@ -31,24 +33,39 @@ mod cell_widget;
 mod chatwidget;
 mod citation_regex;
 mod cli;
+mod clipboard;
 mod config_reload;
 mod confirm_ctrl_d;
 pub mod context;
 mod conversation_history_widget;
 mod exec_command;
+mod external_editor;
 mod git_warning_screen;
 pub mod history_cell;
+mod input_pause;
+mod keymap;
 mod log_layer;
 mod login_screen;
 mod markdown;
+mod markdown_code_blocks;
+mod markdown_footnote;
+mod markdown_image;
+mod markdown_table;
+mod markdown_task_list;
 mod mouse_capture;
+mod notifications;
+mod resume_picker;
+mod review_cell;
 mod scroll_event_helper;
 mod slash_command;
 mod status_indicator_widget;
+mod sub_agent_cell;
+mod tabs;
 pub mod text_block;
 mod text_formatting;
 mod tui;
 mod user_approval_widget;
+mod wrapping;
 
 pub use cli::Cli;
 
@@ -94,13 +111,13 @@ pub fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> std::io::
         }
     };
 
-    // Determine log file path: use --debug-log if set, otherwise default under config log_dir
+    // Determine log file path: use --debug-log if set, otherwise a fresh log
+    // of this session under config log_dir.
     let log_path = if let Some(path) = &cli.debug_log {
         path.clone()
     } else {
         let log_dir = codex_core::config::log_dir(&config)?;
-        std::fs::create_dir_all(&log_dir)?;
-        log_dir.join("codex-tui.log")
+        new_log_path(&log_dir)?
     };
     // Open (or create) your log file, appending to it.
     let mut log_file_opts = OpenOptions::new();
@@ -113,7 +130,7 @@ pub fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> std::io::
         log_file_opts.mode(0o600);
     }
 
-    let log_file = log_file_opts.open(log_path)?;
+    let log_file = log_file_opts.open(&log_path)?;
 
     // Wrap file in non‑blocking writer.
     let (non_blocking, _guard) = non_blocking(log_file);
@@ -124,20 +141,38 @@ pub fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> std::io::
             .unwrap_or_else(|_| EnvFilter::new("codex_core=info,codex_tui=info"))
     };
 
-    // Build layered subscriber:
+    // Build layered subscriber; the file gets one JSON object per event so
+    // that `/debug export` bundles can be filtered and searched.
     let file_layer = tracing_subscriber::fmt::layer()
+        .json()
+        .with_current_span(true)
         .with_writer(non_blocking)
-        .with_target(false)
         .with_filter(env_filter());
 
     // Channel that carries formatted log lines to the UI.
     let (log_tx, log_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
     let tui_layer = TuiLogLayer::new(log_tx.clone(), 120).with_filter(env_filter());
 
+    // Export spans and metrics to the `[otel]` collector, if configured. The
+    // guard flushes them when `run_main` returns.
+    let mut otel_error = None;
+    let (otel_layer, _otel_guard) = match config.otel.as_ref().map(otel_layer) {
+        Some(Ok((layer, guard))) => (Some(layer), Some(guard)),
+        Some(Err(e)) => {
+            otel_error = Some(e);
+            (None, None)
+        }
+        None => (None, None),
+    };
+
     let _ = tracing_subscriber::registry()
         .with(file_layer)
         .with(tui_layer)
+        .with(otel_layer)
         .try_init();
+    if let Some(e) = otel_error {
+        tracing::warn!("not exporting to OpenTelemetry: {e}");
+    }
 
     let show_login_screen = should_show_login_screen(&config);
 
@@ -147,7 +182,14 @@ pub fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> std::io::
     // `--allow-no-git-exec` flag.
     let show_git_warning = !cli.skip_git_repo_check && !is_inside_git_repo(&config);
 
-    try_run_ratatui_app(cli, config, show_login_screen, show_git_warning, log_rx);
+    try_run_ratatui_app(
+        cli,
+        config,
+        show_login_screen,
+        show_git_warning,
+        log_path,
+        log_rx,
+    );
     Ok(())
 }
 
@@ -160,28 +202,31 @@ fn try_run_ratatui_app(
     config: Config,
     show_login_screen: bool,
     show_git_warning: bool,
+    log_path: PathBuf,
     log_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
 ) {
-    if let Err(report) = run_ratatui_app(cli, config, show_login_screen, show_git_warning, log_rx) {
+    if let Err(report) = run_ratatui_app(
+        cli,
+        config,
+        show_login_screen,
+        show_git_warning,
+        log_path,
+        log_rx,
+    ) {
         eprintln!("Error: {report:?}");
     }
 }
 
 fn run_ratatui_app(
     cli: Cli,
-    config: Config,
+    mut config: Config,
     show_login_screen: bool,
     show_git_warning: bool,
+    log_path: PathBuf,
     mut log_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
 ) -> color_eyre::Result<()> {
     color_eyre::install()?;
-
-    // Forward panic reports through the tracing stack so that they appear in
-    // the status indicator instead of breaking the alternate screen – the
-    // normal colour‑eyre hook writes to stderr which would corrupt the UI.
-    std::panic::set_hook(Box::new(|info| {
-        tracing::error!("panic: {info}");
-    }));
+    tui::set_panic_hook(config.codex_home.clone());
     let (mut terminal, mut mouse_capture) = tui::init(&config)?;
     terminal.clear()?;
 
@@ -189,22 +234,70 @@ fn run_ratatui_app(
         prompt,
         images,
         session,
+        resume_last,
+        resume_picker,
+        cwd: cwd_override,
         ..
     } = cli;
+    let session = match session {
+        Some(id) => Some(id),
+        None if resume_last => session_store::latest_session_id(&config.codex_home)?,
+        None if resume_picker => {
+            let sessions = session_store::list_sessions(&config.codex_home)?;
+            ResumePicker::new(sessions).run(&mut terminal)?
+        }
+        None => None,
+    };
+    // Seed the agent with the saved transcript and reuse the session id so
+    // new turns are appended to the same session file.
+    let saved = match session {
+        Some(id) => match session_store::load_session(&config.codex_home, id) {
+            Ok(saved) => {
+                config.resume_session_id = Some(id);
+                // Continue with the model, provider and directory the session
+                // last used where nothing else chose them. The approval and
+                // sandbox policies are always the current ones, so a saved
+                // session never loosens them.
+                if let Some(context) = &saved.turn_context {
+                    if config.defaulted.model {
+                        config.model = context.model.clone();
+                    }
+                    let saved_provider = context
+                        .model_provider
+                        .as_ref()
+                        .filter(|_| config.defaulted.model_provider)
+                        .and_then(|id| Some((id.clone(), config.model_providers.get(id)?.clone())));
+                    if let Some((id, provider)) = saved_provider {
+                        config.model_provider_id = id;
+                        config.model_provider = provider;
+                    }
+                    if cwd_override.is_none() && context.cwd.is_dir() {
+                        config.cwd = context.cwd.clone();
+                    }
+                }
+                Some(saved)
+            }
+            Err(e) => {
+                tracing::error!("failed to load session {id}: {e}");
+                None
+            }
+        },
+        None => None,
+    };
+    terminal.clear()?;
+
     let mut app = App::new(
         config.clone(),
         prompt,
         show_login_screen,
         show_git_warning,
         images,
+        log_path,
     );
     // If resuming, override the generated session ID so UI hint logic and history use it
-    if let Some(id) = session {
+    if let (Some(id), Some(saved)) = (session, saved) {
         app.set_session_id(id);
-        // Attempt to replay past conversation transcript
-        if let Some(items) = load_rollout_for_session(&config, id) {
-            app.replay_items(items);
-        }
+        app.replay_items(saved.items);
     }
 
     // Bridge log receiver into the AppEvent channel so latest log lines update the UI.
@@ -217,78 +310,66 @@ fn run_ratatui_app(
         });
     }
 
-    // Watch config.toml for changes and prompt reload.
+    // Watch config.toml, and the project's `.codex/config.toml`, for changes
+    // and prompt reload.
     {
         let app_event_tx = app.event_sender();
-        let config_path = config.codex_home.join("config.toml");
-        std::thread::spawn(move || {
-            use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-            use std::sync::mpsc::channel;
-            use std::time::Duration;
-            let (tx, rx) = channel();
-            let mut watcher: RecommendedWatcher = Watcher::new(tx, notify::Config::default())
-                .unwrap_or_else(|e| {
-                    tracing::error!("config watcher failed: {e}");
-                    std::process::exit(1);
-                });
-            if watcher
-                .watch(&config_path, RecursiveMode::NonRecursive)
-                .is_err()
-            {
-                tracing::error!("Failed to watch config.toml");
-                return;
-            }
-            let mut last = std::fs::read_to_string(&config_path).unwrap_or_default();
-            for res in rx {
-                if let Ok(event) = res {
-                    if matches!(event.kind, EventKind::Modify(_)) {
-                        std::thread::sleep(Duration::from_millis(100));
-                        let new = std::fs::read_to_string(&config_path).unwrap_or_default();
-                        if new != last {
-                            let diff = crate::config_reload::generate_diff(&last, &new);
-                            last = new.clone();
+        let mut config_paths = vec![config.codex_home.join("config.toml")];
+        config_paths.extend(codex_core::find_project_config(
+            &config.cwd,
+            &config.codex_home,
+        ));
+        match FileWatcher::new(config_paths.clone()) {
+            Ok((watcher, mut changes)) => {
+                tokio::spawn(async move {
+                    // Dropping the watcher would stop it.
+                    let _watcher = watcher;
+                    let mut contents: HashMap<PathBuf, String> = HashMap::new();
+                    for path in config_paths {
+                        let current = std::fs::read_to_string(&path).unwrap_or_default();
+                        if let Ok(path) = path.canonicalize() {
+                            contents.insert(path, current);
+                        }
+                    }
+                    while let Some(changed) = changes.recv().await {
+                        let mut diff = String::new();
+                        for path in changed {
+                            let new = std::fs::read_to_string(&path).unwrap_or_default();
+                            let old = contents.insert(path.clone(), new.clone());
+                            let old = old.unwrap_or_default();
+                            if new != old {
+                                diff.push_str(&format!("{}\n", path.display()));
+                                diff.push_str(&crate::config_reload::generate_diff(&old, &new));
+                            }
+                        }
+                        if !diff.is_empty() {
                             app_event_tx
                                 .send(crate::app_event::AppEvent::ConfigReloadRequest(diff));
                         }
                     }
-                }
+                });
             }
-        });
+            Err(e) => tracing::error!("Failed to watch config.toml: {e}"),
+        }
     }
 
     let app_result = app.run(&mut terminal, &mut mouse_capture);
 
     restore();
-    // On exit, display a command that can be used to resume this session
+    // On exit, display what the sessions cost and the commands that can be
+    // used to resume them.
     #[allow(clippy::print_stderr)]
-    if let Some(session_id) = app.session_id() {
-        eprintln!("Resume this session with: codex session {session_id}");
+    if let Some(cost) = app.session_cost_usd() {
+        eprintln!(
+            "Estimated cost: {} this session",
+            codex_core::model_pricing::format_cost_usd(cost)
+        );
     }
-    app_result
-}
-
-/// Load and parse a previous session's rollout JSONL file.
-fn load_rollout_for_session(config: &Config, session_id: Uuid) -> Option<Vec<ResponseItem>> {
-    let dir = config.codex_home.join("sessions");
-    let target = session_id.to_string();
-    for entry in fs::read_dir(&dir).ok()? {
-        let path = entry.ok()?.path();
-        if let Some(fname) = path.file_name().and_then(|s| s.to_str()) {
-            if fname.starts_with("rollout-") && fname.contains(&target) && fname.ends_with(".jsonl")
-            {
-                let file = File::open(path).ok()?;
-                let reader = BufReader::new(file);
-                let mut items = Vec::new();
-                for line in reader.lines().flatten() {
-                    if let Ok(item) = serde_json::from_str::<ResponseItem>(&line) {
-                        items.push(item);
-                    }
-                }
-                return Some(items);
-            }
-        }
+    #[allow(clippy::print_stderr)]
+    for session_id in app.session_ids() {
+        eprintln!("Resume this session with: codex resume {session_id}");
     }
-    None
+    app_result
 }
 
 #[expect(
@@ -310,9 +391,10 @@ fn should_show_login_screen(config: &Config) -> bool {
         // Reading the OpenAI API key is an async operation because it may need
         // to refresh the token. Block on it.
         let codex_home = config.codex_home.clone();
+        let account = config.account.clone();
         let (tx, rx) = tokio::sync::oneshot::channel();
         tokio::spawn(async move {
-            match try_read_openai_api_key(&codex_home).await {
+            match try_read_openai_api_key(&codex_home, account.as_deref()).await {
                 Ok(openai_api_key) => {
                     set_openai_api_key(openai_api_key);
                     tx.send(false).unwrap();
@@ -336,5 +418,6 @@ fn is_in_need_of_openai_api_key(config: &Config) -> bool {
         .as_ref()
         .map(|s| s == OPENAI_API_KEY_ENV_VAR)
         .unwrap_or(false);
-    is_using_openai_key && get_openai_api_key().is_none()
+    // A configured account takes precedence over $OPENAI_API_KEY.
+    is_using_openai_key && (get_openai_api_key().is_none() || config.account.is_some())
 }
@@ -39,7 +39,7 @@ impl LoginScreen {
 impl WidgetRef for &LoginScreen {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let text = Paragraph::new(
-            "Login using `codex login` and then run this command again. 'q' to quit.",
+            "Login using `codex login` (`codex login --device` without a browser, e.g. over SSH) and then run this command again. 'q' to quit.",
         );
         text.render(area, buf);
     }
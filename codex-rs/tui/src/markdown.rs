@@ -6,6 +6,12 @@ use std::borrow::Cow;
 use std::path::Path;
 
 use crate::citation_regex::CITATION_REGEX;
+use crate::markdown_footnote::rewrite_footnotes;
+use crate::markdown_image::rewrite_images;
+use crate::markdown_table::MarkdownSegment;
+use crate::markdown_table::split_tables;
+use crate::markdown_task_list::rewrite_task_list_markers;
+use crate::markdown_task_list::style_completed_tasks;
 
 pub(crate) fn append_markdown(
     markdown_source: &str,
@@ -38,8 +44,21 @@ fn append_markdown_with_opener_and_cwd(
     // renderer. When `file_opener` is absent we bypass the transformation to
     // avoid unnecessary allocations.
     let processed_markdown = rewrite_file_citations(markdown_source, file_opener, cwd);
+    let processed_markdown = rewrite_footnotes(&processed_markdown);
+    let processed_markdown = rewrite_images(&processed_markdown);
 
-    let markdown = tui_markdown::from_str(&processed_markdown);
+    // Tables are laid out by us; everything else goes through `tui_markdown`.
+    for segment in split_tables(&processed_markdown) {
+        match segment {
+            MarkdownSegment::Markdown(src) => append_rendered_markdown(src, lines),
+            MarkdownSegment::Table(table) => lines.extend(table.render()),
+        }
+    }
+}
+
+fn append_rendered_markdown(markdown_source: &str, lines: &mut Vec<Line<'static>>) {
+    let markdown_source = rewrite_task_list_markers(markdown_source);
+    let markdown = tui_markdown::from_str(&markdown_source);
 
     // `tui_markdown` returns a `ratatui::text::Text` where every `Line` borrows
     // from the input `message` string. Since the `HistoryCell` stores its lines
@@ -62,7 +81,7 @@ fn append_markdown_with_opener_and_cwd(
             None => owned_line,
         };
 
-        lines.push(owned_line);
+        lines.push(style_completed_tasks(owned_line));
     }
 }
 
@@ -0,0 +1,104 @@
+//! Fenced code blocks of a Markdown message, so they can be copied out of the
+//! history without the surrounding prose.
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CodeBlock {
+    /// First word of the info string after the opening fence, e.g. `rust`.
+    pub language: Option<String>,
+    pub code: String,
+}
+
+struct OpenFence<'a> {
+    fence_char: char,
+    fence_len: usize,
+    language: Option<String>,
+    lines: Vec<&'a str>,
+}
+
+impl OpenFence<'_> {
+    fn finish(self) -> CodeBlock {
+        CodeBlock {
+            language: self.language,
+            code: self.lines.join("\n"),
+        }
+    }
+}
+
+/// Code blocks fenced with ```` ``` ```` or `~~~`, in order. A block that is
+/// not closed runs to the end of the message, as in CommonMark.
+pub(crate) fn extract_code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<OpenFence> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let fence = if line.len() - trimmed.len() <= 3 {
+            fence_of(trimmed)
+        } else {
+            None
+        };
+        match open.as_mut() {
+            None => {
+                if let Some((fence_char, fence_len)) = fence {
+                    open = Some(OpenFence {
+                        fence_char,
+                        fence_len,
+                        language: trimmed[fence_len..]
+                            .split_whitespace()
+                            .next()
+                            .map(str::to_string),
+                        lines: Vec::new(),
+                    });
+                }
+            }
+            Some(current) => {
+                let closes = fence.is_some_and(|(fence_char, fence_len)| {
+                    fence_char == current.fence_char
+                        && fence_len >= current.fence_len
+                        && trimmed[fence_len..].trim().is_empty()
+                });
+                if !closes {
+                    current.lines.push(line);
+                } else if let Some(done) = open.take() {
+                    blocks.push(done.finish());
+                }
+            }
+        }
+    }
+    blocks.extend(open.map(OpenFence::finish));
+    blocks
+}
+
+/// The fence character and length if `line` starts with a code fence.
+fn fence_of(line: &str) -> Option<(char, usize)> {
+    let fence_char = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|c| *c == fence_char).count();
+    (len >= 3).then_some((fence_char, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_fenced_blocks() {
+        let markdown = "Run this:\n\n```bash\ncargo test\n```\n\nThen:\n\n~~~~\nfn main() {\n    // ```\n}\n~~~~\n\n```python\nunterminated";
+        assert_eq!(
+            extract_code_blocks(markdown),
+            vec![
+                CodeBlock {
+                    language: Some("bash".to_string()),
+                    code: "cargo test".to_string(),
+                },
+                CodeBlock {
+                    language: None,
+                    code: "fn main() {\n    // ```\n}".to_string(),
+                },
+                CodeBlock {
+                    language: Some("python".to_string()),
+                    code: "unterminated".to_string(),
+                },
+            ]
+        );
+        assert!(extract_code_blocks("no code here").is_empty());
+    }
+}
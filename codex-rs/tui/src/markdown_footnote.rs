@@ -0,0 +1,146 @@
+//! GFM footnote support for rendered markdown.
+//!
+//! `tui_markdown` does not understand footnotes, so `[^note]` references and
+//! `[^note]: …` definitions would otherwise show up as raw text in the middle
+//! of the message. We number references in order of first use, replace them
+//! with `[n]` markers and move the definitions to a footnote section at the
+//! end of the message.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex_lite::Captures;
+use regex_lite::Regex;
+
+static DEFINITION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::expect_used)]
+    Regex::new(r"^\[\^([^\]\s]+)\]:[ \t]*(.*)$").expect("invalid footnote definition regex")
+});
+
+static REFERENCE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::expect_used)]
+    Regex::new(r"\[\^([^\]\s]+)\]").expect("invalid footnote reference regex")
+});
+
+/// Rewrite footnotes in `src` into inline `[n]` markers plus a trailing
+/// footnote section. Sources without footnotes are returned unchanged.
+pub(crate) fn rewrite_footnotes(src: &str) -> Cow<'_, str> {
+    if !src.contains("[^") {
+        return Cow::Borrowed(src);
+    }
+
+    // First pass: pull definitions (and their indented continuation lines)
+    // out of the body.
+    let mut body = Vec::new();
+    let mut definitions: Vec<(String, String)> = Vec::new();
+    let mut in_fence = false;
+    let mut in_definition = false;
+    for line in src.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            body.push(line);
+            continue;
+        }
+        if let Some(caps) = DEFINITION_REGEX.captures(line) {
+            definitions.push((caps[1].to_string(), caps[2].trim().to_string()));
+            in_definition = true;
+            continue;
+        }
+        match definitions.last_mut() {
+            Some((_, text))
+                if in_definition && (line.starts_with("    ") || line.starts_with('\t')) =>
+            {
+                text.push(' ');
+                text.push_str(line.trim());
+            }
+            _ => {
+                in_definition = false;
+                body.push(line);
+            }
+        }
+    }
+    if definitions.is_empty() {
+        return Cow::Borrowed(src);
+    }
+
+    // Second pass: number references in order of first use. References to
+    // undefined labels are left as written.
+    let defined: HashMap<&str, &str> = definitions
+        .iter()
+        .map(|(label, text)| (label.as_str(), text.as_str()))
+        .collect();
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+    let mut out = String::with_capacity(src.len());
+    let mut in_fence = false;
+    for line in body {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            out.push_str(line);
+        } else {
+            let replaced = REFERENCE_REGEX.replace_all(line, |caps: &Captures<'_>| {
+                let label = &caps[1];
+                if !defined.contains_key(label) {
+                    return caps[0].to_string();
+                }
+                let n = *numbers.entry(label.to_string()).or_insert_with(|| {
+                    order.push(label.to_string());
+                    order.len()
+                });
+                format!("\\[{n}\\]")
+            });
+            out.push_str(&replaced);
+        }
+        out.push('\n');
+    }
+
+    // Definitions that are never referenced are still shown, after the
+    // referenced ones.
+    for (label, _) in &definitions {
+        if !numbers.contains_key(label) {
+            order.push(label.clone());
+            numbers.insert(label.clone(), order.len());
+        }
+    }
+
+    out.push_str("\n---\n");
+    for (n, label) in order.iter().enumerate() {
+        let text = defined.get(label.as_str()).copied().unwrap_or_default();
+        out.push_str(&format!("\n\\[{}\\] {text}\n", n + 1));
+    }
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn references_are_numbered_and_definitions_moved_to_the_end() {
+        let src = "Claim[^b] and another[^a], again[^b].\n\n[^a]: First source.\n[^b]: Second\n    source.\n\nMore text.\n";
+        assert_eq!(
+            rewrite_footnotes(src),
+            "Claim\\[1\\] and another\\[2\\], again\\[1\\].\n\n\nMore text.\n\n---\n\n\\[1\\] Second source.\n\n\\[2\\] First source.\n"
+        );
+    }
+
+    #[test]
+    fn undefined_references_and_code_are_left_alone() {
+        let src = "Array[^x] access\n```\n[^a]: not a footnote\n```\n";
+        assert_eq!(rewrite_footnotes(src), src);
+    }
+
+    #[test]
+    fn unreferenced_definitions_are_listed() {
+        let src = "Text.\n[^note]: Orphan.\n";
+        assert_eq!(rewrite_footnotes(src), "Text.\n\n---\n\n\\[1\\] Orphan.\n");
+    }
+}
@@ -0,0 +1,114 @@
+//! Markdown image support.
+//!
+//! `tui_markdown` drops `![alt](url)` images, so image links emitted by the
+//! model vanish from the transcript. We rewrite them into an
+//! `[image: alt] (url)` placeholder before rendering. When `tui.inline_images`
+//! is enabled, images that refer to local files are additionally shown below
+//! the message using the terminal graphics protocol (Kitty, iTerm2, Sixel).
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use regex_lite::Captures;
+use regex_lite::Regex;
+
+static IMAGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::expect_used)]
+    Regex::new(r#"!\[([^\]]*)\]\(\s*<?([^)\s>]+)>?(?:\s+"[^"]*")?\s*\)"#)
+        .expect("invalid markdown image regex")
+});
+
+/// Replace markdown images outside fenced code blocks with a textual
+/// placeholder.
+pub(crate) fn rewrite_images(src: &str) -> Cow<'_, str> {
+    if !src.contains("![") {
+        return Cow::Borrowed(src);
+    }
+    let mut out = String::with_capacity(src.len());
+    for (line, in_fence) in lines_with_fence_state(src) {
+        if in_fence {
+            out.push_str(line);
+        } else {
+            let replaced = IMAGE_REGEX.replace_all(line, |caps: &Captures<'_>| {
+                let alt = caps[1].trim();
+                let alt = if alt.is_empty() { "image" } else { alt };
+                format!("\\[image: {alt}\\] ({})", &caps[2])
+            });
+            out.push_str(&replaced);
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Local files referenced by markdown images in `src`, resolved against `cwd`.
+/// Remote URLs and paths that do not exist are skipped.
+pub(crate) fn local_image_paths(src: &str, cwd: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for (line, in_fence) in lines_with_fence_state(src) {
+        if in_fence {
+            continue;
+        }
+        for caps in IMAGE_REGEX.captures_iter(line) {
+            let url = &caps[2];
+            if url.contains("://") && !url.starts_with("file://") {
+                continue;
+            }
+            let path = Path::new(url.strip_prefix("file://").unwrap_or(url));
+            let path = cwd.join(path);
+            if path.is_file() && !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+/// Yields each line of `src` (including its terminator) together with
+/// whether it is part of a fenced code block.
+fn lines_with_fence_state(src: &str) -> impl Iterator<Item = (&str, bool)> {
+    let mut in_fence = false;
+    src.split_inclusive('\n').map(move |line| {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if is_fence {
+            in_fence = !in_fence;
+        }
+        (line, in_fence || is_fence)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn images_become_placeholders() {
+        let src = "See ![the chart](out/chart.png \"Chart\") and ![](https://x.dev/a.jpg).\n";
+        assert_eq!(
+            rewrite_images(src),
+            "See \\[image: the chart\\] (out/chart.png) and \\[image: image\\] (https://x.dev/a.jpg).\n"
+        );
+    }
+
+    #[test]
+    fn images_in_code_fences_are_left_alone() {
+        let src = "```md\n![alt](a.png)\n```\n";
+        assert_eq!(rewrite_images(src), src);
+    }
+
+    #[test]
+    fn only_existing_local_images_are_returned() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("shot.png"), b"png").unwrap();
+        let src = "![a](shot.png) ![b](missing.png) ![c](https://x.dev/shot.png) ![d](shot.png)";
+        assert_eq!(
+            local_image_paths(src, dir.path()),
+            vec![dir.path().join("shot.png")]
+        );
+    }
+}
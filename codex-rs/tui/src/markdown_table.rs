@@ -0,0 +1,261 @@
+//! GFM table support for rendered markdown.
+//!
+//! `tui_markdown` does not lay out tables, so pipe tables emitted by the model
+//! would otherwise render as run-together text. Before handing the source to
+//! the markdown renderer we split out table blocks and draw them ourselves as
+//! aligned box-drawing rows.
+
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use unicode_width::UnicodeWidthStr;
+
+/// A piece of markdown source: either regular markdown for the renderer or a
+/// table we lay out ourselves.
+#[derive(Debug, PartialEq)]
+pub(crate) enum MarkdownSegment<'a> {
+    Markdown(&'a str),
+    Table(Table),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Table {
+    alignments: Vec<Alignment>,
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// Split `src` into markdown and table segments. Tables inside fenced code
+/// blocks are left alone.
+pub(crate) fn split_tables(src: &str) -> Vec<MarkdownSegment<'_>> {
+    let lines: Vec<(usize, &str)> = line_offsets(src).collect();
+    let mut segments = Vec::new();
+    let mut markdown_start = 0;
+    let mut in_fence = false;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let (offset, line) = lines[i];
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let alignments = match lines.get(i + 1) {
+            Some((_, next)) if !in_fence => parse_delimiter_row(next),
+            _ => None,
+        };
+        let header = split_row(line);
+        match alignments {
+            Some(alignments) if line.contains('|') && header.len() == alignments.len() => {
+                let mut end = i + 2;
+                let mut rows = Vec::new();
+                while let Some((_, row)) = lines.get(end) {
+                    if row.trim().is_empty() || !row.contains('|') {
+                        break;
+                    }
+                    rows.push(normalize_row(split_row(row), alignments.len()));
+                    end += 1;
+                }
+                if offset > markdown_start {
+                    segments.push(MarkdownSegment::Markdown(&src[markdown_start..offset]));
+                }
+                segments.push(MarkdownSegment::Table(Table {
+                    alignments,
+                    header,
+                    rows,
+                }));
+                markdown_start = lines.get(end).map_or(src.len(), |(o, _)| *o);
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    if markdown_start < src.len() {
+        segments.push(MarkdownSegment::Markdown(&src[markdown_start..]));
+    }
+    segments
+}
+
+/// Yields `(byte_offset, line)` pairs, with line endings stripped.
+fn line_offsets(src: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    src.split_inclusive('\n').map(move |raw| {
+        let start = offset;
+        offset += raw.len();
+        (start, raw.trim_end_matches(['\n', '\r']))
+    })
+}
+
+/// Parse a row such as `| :--- | :-: | --: |` into column alignments.
+fn parse_delimiter_row(line: &str) -> Option<Vec<Alignment>> {
+    if !line.contains('-') {
+        return None;
+    }
+    split_row(line)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (false, true) => Alignment::Right,
+                _ => Alignment::Left,
+            })
+        })
+        .collect()
+}
+
+/// Split a table row into trimmed cells, honouring `\|` escapes.
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => line,
+    };
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// GFM pads short rows with empty cells and drops extra ones.
+fn normalize_row(mut row: Vec<String>, columns: usize) -> Vec<String> {
+    row.resize(columns, String::new());
+    row
+}
+
+impl Table {
+    pub(crate) fn render(&self) -> Vec<Line<'static>> {
+        let widths: Vec<usize> = (0..self.alignments.len())
+            .map(|col| {
+                std::iter::once(&self.header)
+                    .chain(&self.rows)
+                    .map(|row| row[col].width())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let border = |left: &str, mid: &str, right: &str| {
+            let body = widths
+                .iter()
+                .map(|w| "─".repeat(w + 2))
+                .collect::<Vec<_>>()
+                .join(mid);
+            Line::from(format!("{left}{body}{right}")).style(Style::default().dim())
+        };
+
+        let mut lines = vec![border("┌", "┬", "┐")];
+        lines.push(self.render_row(
+            &self.header,
+            &widths,
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        lines.push(border("├", "┼", "┤"));
+        for row in &self.rows {
+            lines.push(self.render_row(row, &widths, Style::default()));
+        }
+        lines.push(border("└", "┴", "┘"));
+        lines
+    }
+
+    fn render_row(&self, cells: &[String], widths: &[usize], style: Style) -> Line<'static> {
+        let separator = || Span::styled("│", Style::default().dim());
+        let mut spans = vec![separator()];
+        for ((cell, width), alignment) in cells.iter().zip(widths).zip(&self.alignments) {
+            let pad = width - cell.width();
+            let (before, after) = match alignment {
+                Alignment::Left => (0, pad),
+                Alignment::Right => (pad, 0),
+                Alignment::Center => (pad / 2, pad - pad / 2),
+            };
+            spans.push(Span::raw(" ".repeat(before + 1)));
+            spans.push(Span::styled(cell.clone(), style));
+            spans.push(Span::raw(" ".repeat(after + 1)));
+            spans.push(separator());
+        }
+        Line::from(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rendered(table: &Table) -> Vec<String> {
+        table
+            .render()
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn splits_table_out_of_surrounding_markdown() {
+        let src = "Intro\n\n| a | b |\n|---|--:|\n| 1 | 22 |\n\nOutro\n";
+        let segments = split_tables(src);
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0], MarkdownSegment::Markdown("Intro\n\n"));
+        assert_eq!(segments[2], MarkdownSegment::Markdown("\nOutro\n"));
+        let MarkdownSegment::Table(table) = &segments[1] else {
+            panic!("expected a table, got {:?}", segments[1]);
+        };
+        assert_eq!(
+            rendered(table),
+            vec![
+                "┌───┬────┐",
+                "│ a │  b │",
+                "├───┼────┤",
+                "│ 1 │ 22 │",
+                "└───┴────┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn tables_in_code_fences_are_left_alone() {
+        let src = "```\n| a | b |\n|---|---|\n```\n";
+        assert_eq!(split_tables(src), vec![MarkdownSegment::Markdown(src)]);
+    }
+
+    #[test]
+    fn short_rows_are_padded_and_escaped_pipes_kept() {
+        let src = "| x | y |\n| :-: | - |\n| a\\|b |\n";
+        let segments = split_tables(src);
+        let [MarkdownSegment::Table(table)] = segments.as_slice() else {
+            panic!("expected a single table, got {segments:?}");
+        };
+        assert_eq!(table.rows, vec![vec!["a|b".to_string(), String::new()]]);
+        assert_eq!(table.alignments, vec![Alignment::Center, Alignment::Left]);
+    }
+
+    #[test]
+    fn mismatched_delimiter_row_is_not_a_table() {
+        let src = "| a | b |\n|---|\n";
+        assert_eq!(split_tables(src), vec![MarkdownSegment::Markdown(src)]);
+    }
+}
@@ -0,0 +1,135 @@
+//! GFM task list (`- [ ]` / `- [x]`) support for rendered markdown.
+//!
+//! `tui_markdown` renders the checkbox as literal `[ ]` text. We replace the
+//! markers with ballot box glyphs before rendering and then dim and strike
+//! through the text of completed items in the rendered lines.
+
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+use ratatui::style::Modifier;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use regex_lite::Regex;
+
+const UNCHECKED: &str = "☐";
+const CHECKED: &str = "☑";
+
+static TASK_ITEM_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::expect_used)]
+    Regex::new(r"^(\s*(?:[-*+]|\d+[.)])\s+)\[([ xX])\](\s)").expect("invalid task list regex")
+});
+
+/// Replace task list checkboxes in `src` with ☐/☑, leaving fenced code
+/// blocks untouched.
+pub(crate) fn rewrite_task_list_markers(src: &str) -> Cow<'_, str> {
+    if !src.contains("[ ]") && !src.contains("[x]") && !src.contains("[X]") {
+        return Cow::Borrowed(src);
+    }
+    let mut out = String::with_capacity(src.len());
+    let mut in_fence = false;
+    for line in src.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        match TASK_ITEM_REGEX.captures(line) {
+            Some(caps) if !in_fence => {
+                let marker = if &caps[2] == " " { UNCHECKED } else { CHECKED };
+                out.push_str(&caps[1]);
+                out.push_str(marker);
+                if let Some(rest) = caps.get(3) {
+                    out.push_str(&line[rest.start()..]);
+                }
+            }
+            _ => out.push_str(line),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Dim and strike through the text following a ☑ marker at the start of a
+/// rendered list item.
+pub(crate) fn style_completed_tasks(line: Line<'static>) -> Line<'static> {
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    let Some(marker_at) = text.find(CHECKED) else {
+        return line;
+    };
+    let is_list_prefix = text[..marker_at]
+        .chars()
+        .all(|c| c.is_whitespace() || c.is_ascii_digit() || "-*+•.)".contains(c));
+    if !is_list_prefix {
+        return line;
+    }
+
+    let body_start = marker_at + CHECKED.len();
+    let mut offset = 0;
+    let mut spans = Vec::with_capacity(line.spans.len() + 1);
+    for span in line.spans {
+        let start = offset;
+        offset += span.content.len();
+        if offset <= body_start {
+            spans.push(span);
+        } else if start >= body_start {
+            spans.push(completed(span));
+        } else {
+            let (head, tail) = span.content.split_at(body_start - start);
+            spans.push(Span::styled(head.to_string(), span.style));
+            spans.push(completed(Span::styled(tail.to_string(), span.style)));
+        }
+    }
+    Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+fn completed(span: Span<'static>) -> Span<'static> {
+    let style = span
+        .style
+        .add_modifier(Modifier::DIM | Modifier::CROSSED_OUT);
+    span.style(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn markers_are_replaced_outside_code_fences() {
+        let src = "- [ ] todo\n- [x] done\n1. [X] numbered\n```\n- [ ] code\n```\n";
+        assert_eq!(
+            rewrite_task_list_markers(src),
+            "- ☐ todo\n- ☑ done\n1. ☑ numbered\n```\n- [ ] code\n```\n"
+        );
+    }
+
+    #[test]
+    fn brackets_outside_list_items_are_kept() {
+        let src = "see [x] in the middle\n- [link](url)\n";
+        assert_eq!(rewrite_task_list_markers(src), src);
+    }
+
+    #[test]
+    fn completed_item_text_is_dimmed_and_struck_through() {
+        let line = Line::from(vec![Span::raw("- "), Span::raw("☑ done")]);
+        let styled = style_completed_tasks(line);
+        let contents: Vec<&str> = styled.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(contents, vec!["- ", "☑", " done"]);
+        assert!(
+            styled.spans[2]
+                .style
+                .add_modifier
+                .contains(Modifier::CROSSED_OUT)
+        );
+        assert!(!styled.spans[1].style.add_modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn checked_glyph_mid_sentence_is_not_styled() {
+        let line = Line::from("I marked it ☑ already");
+        assert_eq!(style_completed_tasks(line.clone()), line);
+    }
+}
@@ -38,6 +38,15 @@ impl MouseCapture {
         Ok(())
     }
 
+    /// Enable the capture again if it is active, after the terminal was
+    /// reset, e.g. by [`crate::tui::suspend`].
+    pub(crate) fn reapply(&self) -> Result<()> {
+        if self.mouse_capture_is_active {
+            enable_capture()?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn toggle(&mut self) -> Result<()> {
         self.set_active(!self.mouse_capture_is_active)
     }
@@ -0,0 +1,143 @@
+//! Alerts for events that need the user's attention (approval requests, the
+//! end of a turn) while the terminal window is in the background, so users
+//! can switch away during long turns. Focus is tracked with the terminal's
+//! focus reporting; terminals that do not report focus are treated as always
+//! focused and never notify.
+
+use std::io::Write;
+use std::io::stdout;
+use std::process::Command;
+use std::process::Stdio;
+
+use codex_core::config_types::NotificationMethod;
+use codex_core::config_types::Tui;
+
+/// Characters of the event detail (command, agent message) included in a
+/// notification.
+const MAX_DETAIL_CHARS: usize = 80;
+
+pub(crate) enum Notification<'a> {
+    ApprovalRequired { detail: &'a str },
+    TaskComplete { last_agent_message: Option<&'a str> },
+}
+
+impl Notification<'_> {
+    fn text(&self) -> String {
+        let (title, detail) = match self {
+            Notification::ApprovalRequired { detail } => ("approval required", Some(*detail)),
+            Notification::TaskComplete { last_agent_message } => {
+                ("task complete", *last_agent_message)
+            }
+        };
+        let detail: String = detail
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect();
+        match detail.chars().count() {
+            0 => format!("Codex: {title}"),
+            n if n > MAX_DETAIL_CHARS => {
+                let truncated: String = detail.chars().take(MAX_DETAIL_CHARS - 1).collect();
+                format!("Codex: {title}: {truncated}…")
+            }
+            _ => format!("Codex: {title}: {detail}"),
+        }
+    }
+}
+
+pub(crate) struct Notifier {
+    method: NotificationMethod,
+    command: Option<Vec<String>>,
+    terminal_focused: bool,
+}
+
+impl Notifier {
+    pub(crate) fn new(config: &Tui) -> Self {
+        Self {
+            method: config.notifications,
+            command: config.notify_command.clone(),
+            terminal_focused: true,
+        }
+    }
+
+    pub(crate) fn set_terminal_focused(&mut self, focused: bool) {
+        self.terminal_focused = focused;
+    }
+
+    /// Emit `notification` unless the terminal is focused.
+    pub(crate) fn notify(&self, notification: Notification) {
+        if self.terminal_focused {
+            return;
+        }
+        let text = notification.text();
+        let result = match self.method {
+            NotificationMethod::Off => Ok(()),
+            NotificationMethod::Osc9 => write_escape(&format!("\x1b]9;{text}\x07")),
+            NotificationMethod::Bell => write_escape("\x07"),
+            NotificationMethod::Command => self.spawn_command(text),
+        };
+        if let Err(e) = result {
+            tracing::warn!("failed to send notification: {e}");
+        }
+    }
+
+    fn spawn_command(&self, text: String) -> std::io::Result<()> {
+        let Some((program, args)) = self.command.as_deref().and_then(|argv| argv.split_first())
+        else {
+            tracing::warn!("tui.notifications = \"command\" requires tui.notify_command");
+            return Ok(());
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .arg(text)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // Reap the child without blocking the UI.
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+fn write_escape(sequence: &str) -> std::io::Result<()> {
+    let mut out = stdout();
+    out.write_all(sequence.as_bytes())?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notification_text_is_single_line_and_truncated() {
+        assert_eq!(
+            Notification::TaskComplete {
+                last_agent_message: None
+            }
+            .text(),
+            "Codex: task complete"
+        );
+        assert_eq!(
+            Notification::ApprovalRequired {
+                detail: "rm -rf\n  build\x07"
+            }
+            .text(),
+            "Codex: approval required: rm -rf build"
+        );
+        let long = "word ".repeat(40);
+        let text = Notification::TaskComplete {
+            last_agent_message: Some(&long),
+        }
+        .text();
+        assert!(text.ends_with('…'));
+        assert_eq!(
+            text.chars().count(),
+            "Codex: task complete: ".len() + MAX_DETAIL_CHARS
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Full‑screen picker shown by `codex resume` (without an id) that lists
+//! saved sessions, most recent first, and lets the user choose one to
+//! continue.
+
+use codex_common::elapsed::format_duration;
+use codex_core::session_store::SessionSummary;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use ratatui::widgets::WidgetRef;
+use uuid::Uuid;
+
+use crate::tui;
+
+/// Result of handling a key event while the picker is active.
+#[derive(Debug, PartialEq)]
+pub(crate) enum ResumePickerOutcome {
+    /// User selected a session to resume.
+    Selected(Uuid),
+    /// User dismissed the picker – start a fresh session instead.
+    Cancelled,
+    /// Keep showing the picker.
+    None,
+}
+
+pub(crate) struct ResumePicker {
+    sessions: Vec<SessionSummary>,
+    selected: usize,
+}
+
+impl ResumePicker {
+    pub(crate) fn new(sessions: Vec<SessionSummary>) -> Self {
+        Self {
+            sessions,
+            selected: 0,
+        }
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) -> ResumePickerOutcome {
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                ResumePickerOutcome::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.sessions.len() {
+                    self.selected += 1;
+                }
+                ResumePickerOutcome::None
+            }
+            KeyCode::Enter => match self.sessions.get(self.selected) {
+                Some(session) => ResumePickerOutcome::Selected(session.id),
+                None => ResumePickerOutcome::Cancelled,
+            },
+            KeyCode::Esc | KeyCode::Char('q') => ResumePickerOutcome::Cancelled,
+            _ => ResumePickerOutcome::None,
+        }
+    }
+
+    /// Run the picker on `terminal` until the user makes a choice. Must be
+    /// called before the `App` starts reading crossterm events.
+    pub(crate) fn run(mut self, terminal: &mut tui::Tui) -> std::io::Result<Option<Uuid>> {
+        if self.sessions.is_empty() {
+            return Ok(None);
+        }
+        loop {
+            terminal.draw(|frame| frame.render_widget_ref(&self, frame.area()))?;
+            if let Event::Key(key_event) = crossterm::event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match self.handle_key_event(key_event) {
+                    ResumePickerOutcome::Selected(id) => return Ok(Some(id)),
+                    ResumePickerOutcome::Cancelled => return Ok(None),
+                    ResumePickerOutcome::None => {}
+                }
+            }
+        }
+    }
+}
+
+impl WidgetRef for &ResumePicker {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title("Resume a previous session (↑/↓ select, Enter resume, Esc new session)")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::LightYellow));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // Keep the selected row visible when the list is taller than the view.
+        let height = inner.height as usize;
+        let first = (self.selected + 1).saturating_sub(height);
+        let lines: Vec<Line<'static>> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .map(|(idx, session)| {
+                let age = session
+                    .modified
+                    .elapsed()
+                    .map(|d| format!("{} ago", format_duration(d)))
+                    .unwrap_or_default();
+                let preview = session
+                    .preview
+                    .as_deref()
+                    .and_then(|p| p.lines().next())
+                    .unwrap_or("(no messages)")
+                    .to_string();
+                let line = Line::from(vec![
+                    format!("{age:>12}  ").dim(),
+                    session.id.to_string()[..8].to_string().magenta(),
+                    "  ".into(),
+                    preview.into(),
+                ]);
+                if idx == self.selected {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+        Paragraph::new(lines).render(inner, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    fn summary(id: Uuid) -> SessionSummary {
+        SessionSummary {
+            id,
+            path: PathBuf::from(format!("{id}.jsonl")),
+            modified: SystemTime::now(),
+            preview: None,
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn navigate_and_select() {
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+        let mut picker = ResumePicker::new(ids.iter().copied().map(summary).collect());
+        assert_eq!(
+            picker.handle_key_event(key(KeyCode::Down)),
+            ResumePickerOutcome::None
+        );
+        // Moving past the end keeps the last entry selected.
+        picker.handle_key_event(key(KeyCode::Down));
+        assert_eq!(
+            picker.handle_key_event(key(KeyCode::Enter)),
+            ResumePickerOutcome::Selected(ids[1])
+        );
+        picker.handle_key_event(key(KeyCode::Up));
+        assert_eq!(
+            picker.handle_key_event(key(KeyCode::Enter)),
+            ResumePickerOutcome::Selected(ids[0])
+        );
+        assert_eq!(
+            picker.handle_key_event(key(KeyCode::Esc)),
+            ResumePickerOutcome::Cancelled
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! History cell with the findings of a `/review`, grouped by file in the
+//! order the files first come up. The findings arrive one event at a time,
+//! so the cell is re-rendered as it grows.
+
+use codex_core::protocol::LineRange;
+use codex_core::protocol::ReviewFinding;
+use codex_core::protocol::ReviewSeverity;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+#[derive(Default)]
+pub(crate) struct ReviewCell {
+    findings: Vec<ReviewFinding>,
+}
+
+impl ReviewCell {
+    pub(crate) fn push(&mut self, finding: ReviewFinding) {
+        self.findings.push(finding);
+    }
+
+    /// The findings in display order, i.e. grouped by file.
+    pub(crate) fn findings(&self) -> Vec<ReviewFinding> {
+        self.by_file()
+            .into_iter()
+            .flat_map(|(_, findings)| findings.into_iter().cloned())
+            .collect()
+    }
+
+    fn by_file(&self) -> Vec<(&str, Vec<&ReviewFinding>)> {
+        let mut files: Vec<(&str, Vec<&ReviewFinding>)> = Vec::new();
+        for finding in &self.findings {
+            match files.iter_mut().find(|(file, _)| *file == finding.file) {
+                Some((_, findings)) => findings.push(finding),
+                None => files.push((finding.file.as_str(), vec![finding])),
+            }
+        }
+        files
+    }
+
+    pub(crate) fn lines(&self) -> Vec<Line<'static>> {
+        let files = self.by_file();
+        let count = |severity| {
+            self.findings
+                .iter()
+                .filter(|finding| finding.severity == severity)
+                .count()
+        };
+        let mut lines = vec![Line::from(vec![
+            "review".magenta().bold(),
+            format!(
+                " {} findings in {} files ({} errors, {} warnings, {} info)",
+                self.findings.len(),
+                files.len(),
+                count(ReviewSeverity::Error),
+                count(ReviewSeverity::Warning),
+                count(ReviewSeverity::Info),
+            )
+            .dim(),
+        ])];
+        for (file, findings) in files {
+            lines.push(Line::from(file.to_string()).cyan().bold());
+            for finding in findings {
+                let mut body = finding.body.lines();
+                lines.push(Line::from(vec![
+                    format!("  {} ", line_label(finding.line_range)).dim(),
+                    severity_span(finding.severity),
+                    format!(" {}", body.next().unwrap_or_default()).into(),
+                ]));
+                lines.extend(body.map(|line| Line::from(format!("      {line}"))));
+            }
+        }
+        lines.push(Line::from("(press f in the history to jump to the diff of a finding)").dim());
+        lines.push(Line::from(""));
+        lines
+    }
+}
+
+pub(crate) fn line_label(range: LineRange) -> String {
+    if range.start == range.end {
+        format!("L{}", range.start)
+    } else {
+        format!("L{}-{}", range.start, range.end)
+    }
+}
+
+pub(crate) fn severity_span(severity: ReviewSeverity) -> Span<'static> {
+    match severity {
+        ReviewSeverity::Error => "error".red().bold(),
+        ReviewSeverity::Warning => "warning".yellow().bold(),
+        ReviewSeverity::Info => "info".dim(),
+    }
+}
+
+/// The hunks around a finding, with added and removed lines coloured.
+pub(crate) fn diff_lines(finding: &ReviewFinding, hunks: &[String]) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(vec![
+        "diff".magenta().bold(),
+        format!(" {}:{}", finding.file, line_label(finding.line_range)).dim(),
+    ])];
+    for text in hunks.iter().flat_map(|hunk| hunk.lines()) {
+        let line = Line::from(text.to_string());
+        lines.push(if text.starts_with("@@") {
+            line.cyan()
+        } else if text.starts_with('+') {
+            line.green()
+        } else if text.starts_with('-') {
+            line.red()
+        } else {
+            line
+        });
+    }
+    lines.push(Line::from(""));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(file: &str, start: u32, severity: ReviewSeverity, body: &str) -> ReviewFinding {
+        ReviewFinding {
+            file: file.to_string(),
+            line_range: LineRange { start, end: start },
+            severity,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn groups_findings_by_file() {
+        let mut cell = ReviewCell::default();
+        cell.push(finding("src/a.rs", 3, ReviewSeverity::Error, "Off by one."));
+        cell.push(finding("src/b.rs", 9, ReviewSeverity::Info, "Typo."));
+        cell.push(finding(
+            "src/a.rs",
+            12,
+            ReviewSeverity::Warning,
+            "Unchecked index.\nUse get().",
+        ));
+
+        let lines: Vec<String> = cell
+            .lines()
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            lines[0],
+            "review 3 findings in 2 files (1 errors, 1 warnings, 1 info)"
+        );
+        assert_eq!(lines[1], "src/a.rs");
+        assert_eq!(lines[2], "  L3 error Off by one.");
+        assert_eq!(lines[3], "  L12 warning Unchecked index.");
+        assert_eq!(lines[4], "      Use get().");
+        assert_eq!(lines[5], "src/b.rs");
+
+        let files: Vec<&str> = cell.findings().iter().map(|f| f.file.as_str()).collect();
+        assert_eq!(files, vec!["src/a.rs", "src/a.rs", "src/b.rs"]);
+    }
+}
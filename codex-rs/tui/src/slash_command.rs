@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use codex_core::protocol::McpPromptInfo;
 use strum::IntoEnumIterator;
 use strum_macros::AsRefStr; // derive macro
 use strum_macros::EnumIter;
@@ -25,6 +26,38 @@ pub enum SlashCommand {
     InspectEnv,
     /// Prompt to run a shell command in the container.
     Shell,
+    /// Search the conversation transcript.
+    Search,
+    /// Summarize the conversation to free up context.
+    Compact,
+    /// Revert the file changes made in the last turn.
+    Undo,
+    /// Review the uncommitted changes, or those since a git ref.
+    Review,
+    /// List the current key bindings.
+    Keys,
+    /// Show the AGENTS.md files loaded for the session.
+    Agents,
+    /// Draft an AGENTS.md for the working directory.
+    Init,
+    /// Export the transcript to a Markdown or HTML file.
+    Export,
+    /// Show the debug log, or bundle it for a bug report.
+    Debug,
+    /// Switch to another model of the provider.
+    Model,
+    /// List the `[profiles]` of config.toml or switch to one.
+    Profile,
+    /// List the stored logins or switch to one.
+    Account,
+    /// Continue from an earlier user message in a new session.
+    Fork,
+    /// Show, hide or summarize the model's reasoning.
+    Reasoning,
+    /// Open another conversation in a new tab.
+    NewTab,
+    /// Close the shown tab.
+    CloseTab,
 }
 
 impl SlashCommand {
@@ -42,6 +75,46 @@ impl SlashCommand {
                 "Inspect sandbox and container environment (mounts, permissions, network)"
             }
             SlashCommand::Shell => "Run a shell command in the container.",
+            SlashCommand::Search => {
+                "Search the conversation transcript (n/N to jump between hits)."
+            }
+            SlashCommand::Compact => {
+                "Summarize the conversation so far to free up context (shows token counts)."
+            }
+            SlashCommand::Undo => "Revert the file changes made by the agent in its last turn.",
+            SlashCommand::Review => {
+                "Review the uncommitted changes, or the commits since a ref: /review [base]."
+            }
+            SlashCommand::Keys => "List the current key bindings (remap them under [keys]).",
+            SlashCommand::Agents => {
+                "Show the AGENTS.md files in effect and their instructions, by precedence."
+            }
+            SlashCommand::Init => {
+                "Draft an AGENTS.md for this project with the model; review the diff, then write it."
+            }
+            SlashCommand::Model => {
+                "Switch the model (lists context window, pricing and reasoning support)."
+            }
+            SlashCommand::Profile => {
+                "List config profiles, or switch provider and model: /profile <name>."
+            }
+            SlashCommand::Account => {
+                "List the logins from `codex login --profile`, or switch: /account <name>."
+            }
+            SlashCommand::Fork => {
+                "Fork the conversation before an earlier message into a new session."
+            }
+            SlashCommand::Reasoning => {
+                "Show, hide or summarize the model's reasoning: /reasoning [show|hide|summary]."
+            }
+            SlashCommand::NewTab => "Open a new chat in another tab (Ctrl-PgUp/PgDn to switch).",
+            SlashCommand::CloseTab => "Close the current tab and its conversation.",
+            SlashCommand::Export => {
+                "Export the transcript to a Markdown or HTML file: /export [path]."
+            }
+            SlashCommand::Debug => {
+                "Show the debug log path; /debug export [path] zips it for a bug report."
+            }
             SlashCommand::Quit => "Exit the application.",
         }
     }
@@ -53,6 +126,12 @@ impl SlashCommand {
     }
 }
 
+/// Command string (without the leading '/') of a prompt template of an MCP
+/// server: `mcp:<server>:<prompt>`.
+pub fn mcp_prompt_command(info: &McpPromptInfo) -> String {
+    format!("mcp:{}:{}", info.server, info.prompt.name)
+}
+
 /// Return all built-in commands in a HashMap keyed by their command string.
 pub fn built_in_slash_commands() -> HashMap<&'static str, SlashCommand> {
     SlashCommand::iter().map(|c| (c.command(), c)).collect()
@@ -73,4 +152,16 @@ mod tests {
         let desc = SlashCommand::InspectEnv.description();
         assert!(desc.contains("sandbox"), "description was: {}", desc);
     }
+
+    #[test]
+    fn built_in_includes_compact() {
+        let commands = built_in_slash_commands();
+        assert_eq!(commands.get("compact"), Some(&SlashCommand::Compact));
+    }
+
+    #[test]
+    fn built_in_includes_undo() {
+        let commands = built_in_slash_commands();
+        assert_eq!(commands.get("undo"), Some(&SlashCommand::Undo));
+    }
 }
@@ -0,0 +1,144 @@
+//! History cell of a `spawn_agent` sub-task. The sub-agent's messages and
+//! tool calls are folded into the cell as one line each; collapsed (the
+//! default) it only shows the task, the latest step and the outcome.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+
+/// Lines of the task and of the final message shown when expanded.
+const MAX_TEXT_LINES: usize = 5;
+
+pub(crate) struct SubAgentCell {
+    pub(crate) call_id: String,
+    prompt: String,
+    max_turns: u32,
+    steps: Vec<Line<'static>>,
+    start: Instant,
+    /// Turns taken, final message or error, and wall time once finished.
+    outcome: Option<(u32, Result<String, String>, Duration)>,
+    expanded: bool,
+}
+
+impl SubAgentCell {
+    pub(crate) fn new(call_id: String, prompt: String, max_turns: u32, expanded: bool) -> Self {
+        Self {
+            call_id,
+            prompt,
+            max_turns,
+            steps: Vec::new(),
+            start: Instant::now(),
+            outcome: None,
+            expanded,
+        }
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.outcome.is_none()
+    }
+
+    pub(crate) fn push_step(&mut self, step: Line<'static>) {
+        self.steps.push(step);
+    }
+
+    pub(crate) fn finish(&mut self, turns: u32, result: Result<String, String>) {
+        self.outcome = Some((turns, result, self.start.elapsed()));
+    }
+
+    pub(crate) fn set_expanded(&mut self, expanded: bool) {
+        self.expanded = expanded;
+    }
+
+    pub(crate) fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        let steps = match self.steps.len() {
+            1 => "1 step".to_string(),
+            n => format!("{n} steps"),
+        };
+        lines.push(match &self.outcome {
+            None => Line::from(vec![
+                "sub-agent".magenta().bold(),
+                format!(" running… (up to {} turns, {steps})", self.max_turns).dim(),
+            ]),
+            Some((turns, Ok(_), elapsed)) => Line::from(vec![
+                "✓ sub-agent".green().bold(),
+                format!(" {turns} turns · {steps} · {:.1}s", elapsed.as_secs_f64()).dim(),
+            ]),
+            Some((turns, Err(_), elapsed)) => Line::from(vec![
+                "✗ sub-agent".red().bold(),
+                format!(" {turns} turns · {steps} · {:.1}s", elapsed.as_secs_f64()).dim(),
+            ]),
+        });
+
+        if self.expanded {
+            lines.extend(text_lines(&self.prompt).map(|line| Line::from(line).italic()));
+            lines.extend(self.steps.iter().cloned());
+        } else {
+            if let Some(first) = self.prompt.lines().next() {
+                lines.push(Line::from(first.to_string()).italic());
+            }
+            // While running, the latest step shows what the sub-agent is up to.
+            if let (None, Some(step)) = (&self.outcome, self.steps.last()) {
+                lines.push(step.clone());
+            }
+        }
+
+        match &self.outcome {
+            Some((_, Ok(message), _)) if self.expanded => {
+                lines.extend(text_lines(message).map(Line::from));
+            }
+            Some((_, Err(error), _)) => {
+                lines.push(Line::from(error.lines().next().unwrap_or_default().to_string()).red());
+            }
+            _ => {}
+        }
+        if !self.expanded && self.steps.len() > 1 {
+            lines.push(Line::from("(press e in the history to expand)").dim());
+        }
+        lines.push(Line::from(""));
+        lines
+    }
+}
+
+fn text_lines(text: &str) -> impl Iterator<Item = String> + '_ {
+    let hidden = text.lines().count().saturating_sub(MAX_TEXT_LINES);
+    text.lines()
+        .take(MAX_TEXT_LINES)
+        .map(str::to_string)
+        .chain((hidden > 0).then(|| format!("… {hidden} more lines")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered(cell: &SubAgentCell) -> Vec<String> {
+        cell.lines()
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn collapsed_cell_shows_the_latest_step_while_running() {
+        let mut cell =
+            SubAgentCell::new("call".into(), "find flaky tests\nin ci".into(), 10, false);
+        cell.push_step(Line::from("$ cargo test"));
+        cell.push_step(Line::from("$ grep -r flaky"));
+        let lines = rendered(&cell);
+        assert_eq!(lines[0], "sub-agent running… (up to 10 turns, 2 steps)");
+        assert_eq!(lines[1], "find flaky tests");
+        assert_eq!(lines[2], "$ grep -r flaky");
+        assert!(!lines.iter().any(|line| line == "$ cargo test"));
+
+        cell.set_expanded(true);
+        cell.finish(3, Ok("two tests are flaky".into()));
+        let lines = rendered(&cell);
+        assert!(lines[0].starts_with("✓ sub-agent 3 turns · 2 steps"));
+        assert!(lines.iter().any(|line| line == "$ cargo test"));
+        assert!(lines.iter().any(|line| line == "in ci"));
+        assert!(lines.iter().any(|line| line == "two tests are flaky"));
+    }
+}
@@ -0,0 +1,255 @@
+//! Several conversations in one TUI instance. Each tab owns a `ChatWidget`
+//! (and through it an agent); the tab being shown lives in `AppState::Chat`,
+//! the others are parked here until they are switched to.
+
+use std::collections::HashMap;
+
+use codex_core::protocol::TokenUsage;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use uuid::Uuid;
+
+/// Identifies the tab whose agent sent an event. A tab gets a new id whenever
+/// its chat is replaced (`/new`, `/profile`), so late events of the old agent
+/// are dropped instead of showing up in the new chat.
+pub(crate) type TabId = u64;
+
+pub(crate) struct TabInfo {
+    pub(crate) id: TabId,
+    pub(crate) session_id: Option<Uuid>,
+    /// Tokens used by the tab's session so far.
+    pub(crate) token_usage: TokenUsage,
+    /// Latest estimated cost of the tab's session.
+    pub(crate) session_cost_usd: Option<f64>,
+}
+
+impl TabInfo {
+    fn new(id: TabId) -> Self {
+        Self {
+            id,
+            session_id: None,
+            token_usage: TokenUsage::default(),
+            session_cost_usd: None,
+        }
+    }
+}
+
+/// The tabs and the parked widgets. `W` is the app's `ChatWidget`; it is a
+/// parameter so that the bookkeeping can be tested without agents.
+pub(crate) struct Tabs<W> {
+    tabs: Vec<TabInfo>,
+    /// Widgets of the tabs that are not shown, by tab id.
+    hidden: HashMap<TabId, Box<W>>,
+    active: usize,
+    next_id: TabId,
+}
+
+impl<W> Tabs<W> {
+    /// A single tab, whose widget is created with [`Tabs::active_id`].
+    pub(crate) fn new() -> Self {
+        Self {
+            tabs: vec![TabInfo::new(0)],
+            hidden: HashMap::new(),
+            active: 0,
+            next_id: 1,
+        }
+    }
+
+    pub(crate) fn active(&self) -> &TabInfo {
+        &self.tabs[self.active]
+    }
+
+    pub(crate) fn active_mut(&mut self) -> &mut TabInfo {
+        &mut self.tabs[self.active]
+    }
+
+    pub(crate) fn active_id(&self) -> TabId {
+        self.active().id
+    }
+
+    pub(crate) fn all(&self) -> &[TabInfo] {
+        &self.tabs
+    }
+
+    pub(crate) fn info_mut(&mut self, id: TabId) -> Option<&mut TabInfo> {
+        self.tabs.iter_mut().find(|tab| tab.id == id)
+    }
+
+    /// The widget of a tab that is not shown, or `None` for the shown tab and
+    /// for chats that were replaced.
+    pub(crate) fn hidden_widget_mut(&mut self, id: TabId) -> Option<&mut W> {
+        self.hidden.get_mut(&id).map(|widget| &mut **widget)
+    }
+
+    pub(crate) fn hidden_widgets_mut(&mut self) -> impl Iterator<Item = &mut W> {
+        self.hidden.values_mut().map(|widget| &mut **widget)
+    }
+
+    /// Id for a new chat, to pass to [`Tabs::open`] or
+    /// [`Tabs::renew_active`].
+    pub(crate) fn allocate_id(&mut self) -> TabId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Give the shown tab a new id for the chat that replaces its widget, and
+    /// forget the old chat's session.
+    pub(crate) fn renew_active(&mut self) -> TabId {
+        let id = self.allocate_id();
+        self.tabs[self.active] = TabInfo::new(id);
+        id
+    }
+
+    /// Add a tab for `widget` (created with `id`) after the shown one and
+    /// show it in place of `shown`, which is parked.
+    pub(crate) fn open(&mut self, shown: &mut Box<W>, id: TabId, widget: Box<W>) {
+        let previous = std::mem::replace(shown, widget);
+        self.hidden.insert(self.active_id(), previous);
+        self.active += 1;
+        self.tabs.insert(self.active, TabInfo::new(id));
+    }
+
+    /// Show the tab `offset` tabs away, wrapping around, in place of `shown`,
+    /// which is parked.
+    pub(crate) fn switch(&mut self, offset: isize, shown: &mut Box<W>) {
+        let len = self.tabs.len() as isize;
+        let target = (self.active as isize + offset).rem_euclid(len) as usize;
+        let Some(widget) = self.hidden.remove(&self.tabs[target].id) else {
+            return;
+        };
+        let previous = std::mem::replace(shown, widget);
+        self.hidden.insert(self.active_id(), previous);
+        self.active = target;
+    }
+
+    /// Close the shown tab, replacing `shown` with the tab that takes its
+    /// place: the next one, or the previous one for the last tab. Returns
+    /// `false`, leaving the tabs as they were, if it is the only tab.
+    pub(crate) fn close_active(&mut self, shown: &mut Box<W>) -> bool {
+        if self.tabs.len() == 1 {
+            return false;
+        }
+        let replacement = if self.active + 1 < self.tabs.len() {
+            self.active + 1
+        } else {
+            self.active - 1
+        };
+        let Some(widget) = self.hidden.remove(&self.tabs[replacement].id) else {
+            return false;
+        };
+        *shown = widget;
+        self.tabs.remove(self.active);
+        // The next tab moves into the closed tab's place.
+        self.active = self.active.min(self.tabs.len() - 1);
+        true
+    }
+
+    /// The tab bar shown above the bottom pane, or `None` with a single tab.
+    pub(crate) fn tab_bar(&self) -> Option<Line<'static>> {
+        if self.tabs.len() < 2 {
+            return None;
+        }
+        let mut spans: Vec<Span<'static>> = Vec::new();
+        for (idx, tab) in self.tabs.iter().enumerate() {
+            if idx > 0 {
+                spans.push(" │ ".dim());
+            }
+            let session = tab
+                .session_id
+                .map(|id| id.to_string().chars().take(8).collect::<String>())
+                .unwrap_or_else(|| "new".to_string());
+            let label = format!(
+                " {}: {session} · {} ",
+                idx + 1,
+                format_tokens(tab.token_usage.total_tokens)
+            );
+            spans.push(if idx == self.active {
+                label.reversed()
+            } else {
+                label.dim()
+            });
+        }
+        Some(Line::from(spans))
+    }
+}
+
+/// Token count for the tab bar, e.g. `950 tok` or `12.3k tok`.
+fn format_tokens(tokens: u64) -> String {
+    if tokens < 1_000 {
+        format!("{tokens} tok")
+    } else {
+        format!("{:.1}k tok", tokens as f64 / 1_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_token_counts() {
+        assert_eq!(format_tokens(950), "950 tok");
+        assert_eq!(format_tokens(12_345), "12.3k tok");
+    }
+
+    #[test]
+    fn single_tab_has_no_tab_bar() {
+        let mut tabs = Tabs::<Widget>::new();
+        assert!(tabs.tab_bar().is_none());
+        let old_id = tabs.active_id();
+        assert_ne!(tabs.renew_active(), old_id);
+        assert_eq!(tabs.all().len(), 1);
+    }
+
+    /// Stands in for a `ChatWidget`.
+    #[derive(Debug, PartialEq)]
+    struct Widget(&'static str);
+
+    /// Three tabs showing "b", the middle one.
+    fn three_tabs() -> (Tabs<Widget>, Box<Widget>) {
+        let mut tabs = Tabs::new();
+        let mut shown = Box::new(Widget("a"));
+        for name in ["b", "c"] {
+            let id = tabs.allocate_id();
+            tabs.open(&mut shown, id, Box::new(Widget(name)));
+        }
+        tabs.switch(-1, &mut shown);
+        assert_eq!(*shown, Widget("b"));
+        (tabs, shown)
+    }
+
+    #[test]
+    fn closing_a_tab_shows_the_next_one() {
+        let (mut tabs, mut shown) = three_tabs();
+        assert!(tabs.close_active(&mut shown));
+        assert_eq!(*shown, Widget("c"));
+        assert_eq!(tabs.all().len(), 2);
+        assert_eq!(tabs.active_id(), tabs.all()[1].id);
+
+        // The last tab is replaced by the one before it.
+        assert!(tabs.close_active(&mut shown));
+        assert_eq!(*shown, Widget("a"));
+        assert_eq!(tabs.all().len(), 1);
+
+        // The only tab stays open.
+        assert!(!tabs.close_active(&mut shown));
+        assert_eq!(*shown, Widget("a"));
+        assert_eq!(tabs.all().len(), 1);
+    }
+
+    #[test]
+    fn closing_keeps_the_tabs_when_the_replacement_is_missing() {
+        let (mut tabs, mut shown) = three_tabs();
+        let next = tabs.all()[2].id;
+        tabs.hidden.remove(&next);
+        let ids: Vec<TabId> = tabs.all().iter().map(|tab| tab.id).collect();
+        let active = tabs.active_id();
+
+        assert!(!tabs.close_active(&mut shown));
+        assert_eq!(*shown, Widget("b"));
+        assert_eq!(tabs.all().iter().map(|tab| tab.id).collect::<Vec<_>>(), ids);
+        assert_eq!(tabs.active_id(), active);
+    }
+}
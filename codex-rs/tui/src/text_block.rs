@@ -1,4 +1,5 @@
 use crate::cell_widget::CellWidget;
+use crate::wrapping::wrap_lines;
 use ratatui::prelude::*;
 
 /// A simple widget that just displays a list of `Line`s via a `Paragraph`.
@@ -19,16 +20,11 @@ impl TextBlock {
 
 impl CellWidget for TextBlock {
     fn height(&self, width: u16) -> usize {
-        // Use the same wrapping configuration as ConversationHistoryWidget so
-        // measurement stays in sync with rendering.
-        ratatui::widgets::Paragraph::new(self.lines.clone())
-            .wrap(crate::conversation_history_widget::wrap_cfg())
-            .line_count(width)
+        wrap_lines(&self.lines, width).len()
     }
 
     fn render_window(&self, first_visible_line: usize, area: Rect, buf: &mut Buffer) {
-        ratatui::widgets::Paragraph::new(self.lines.clone())
-            .wrap(crate::conversation_history_widget::wrap_cfg())
+        ratatui::widgets::Paragraph::new(wrap_lines(&self.lines, area.width))
             .scroll((first_visible_line as u16, 0))
             .render(area, buf);
     }
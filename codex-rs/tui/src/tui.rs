@@ -1,11 +1,16 @@
+use std::backtrace::Backtrace;
 use std::io::Result;
 use std::io::Stdout;
 use std::io::stdout;
+use std::path::PathBuf;
 
 use codex_core::config::Config;
+use codex_core::crash_report::write_crash_report;
 use crossterm::event::DisableBracketedPaste;
+use crossterm::event::DisableFocusChange;
 use crossterm::event::DisableMouseCapture;
 use crossterm::event::EnableBracketedPaste;
+use crossterm::event::EnableFocusChange;
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 use ratatui::crossterm::execute;
@@ -23,22 +28,55 @@ pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 pub fn init(config: &Config) -> Result<(Tui, MouseCapture)> {
     execute!(stdout(), EnterAlternateScreen)?;
     execute!(stdout(), EnableBracketedPaste)?;
+    execute!(stdout(), EnableFocusChange)?;
     let mouse_capture = MouseCapture::new_with_capture(!config.tui.disable_mouse_capture)?;
 
     enable_raw_mode()?;
-    set_panic_hook();
     let tui = Terminal::new(CrosstermBackend::new(stdout()))?;
     Ok((tui, mouse_capture))
 }
 
-fn set_panic_hook() {
-    let hook = std::panic::take_hook();
-    std::panic::set_hook(Box::new(move |panic_info| {
+/// Replace the panic hook (including colour-eyre's, whose report would be
+/// drawn over the UI) with one that restores the terminal, writes a crash
+/// report with a backtrace to `codex_home/crash/` and points to it.
+#[expect(
+    clippy::print_stderr,
+    reason = "The terminal has been restored, so we can write to stderr."
+)]
+pub fn set_panic_hook(codex_home: PathBuf) {
+    std::panic::set_hook(Box::new(move |info| {
         let _ = restore(); // ignore any errors as we are already failing
-        hook(panic_info);
+        tracing::error!("panic: {info}");
+        let thread = std::thread::current();
+        match write_crash_report(
+            &codex_home,
+            env!("CARGO_PKG_VERSION"),
+            thread.name(),
+            &info.to_string(),
+            &Backtrace::force_capture(),
+        ) {
+            Ok(path) => eprintln!("Codex crashed. Crash report: {}", path.display()),
+            Err(err) => eprintln!("Codex crashed ({info}); failed to write a crash report: {err}"),
+        }
     }));
 }
 
+/// Hand the terminal to another program, such as an editor, until
+/// [`resume`] takes it back.
+pub fn suspend() -> Result<()> {
+    restore()
+}
+
+/// Take the terminal back after [`suspend`] and repaint it from scratch on
+/// the next draw. Mouse capture is re-enabled by the caller.
+pub fn resume(terminal: &mut Tui) -> Result<()> {
+    execute!(stdout(), EnterAlternateScreen)?;
+    execute!(stdout(), EnableBracketedPaste)?;
+    execute!(stdout(), EnableFocusChange)?;
+    enable_raw_mode()?;
+    terminal.clear()
+}
+
 /// Restore the terminal to its original state
 pub fn restore() -> Result<()> {
     // We are shutting down, and we cannot reference the `MouseCapture`, so we
@@ -47,6 +85,7 @@ pub fn restore() -> Result<()> {
         // It is possible that `DisableMouseCapture` is written more than once
         // on shutdown, so ignore the error in this case.
     }
+    execute!(stdout(), DisableFocusChange)?;
     execute!(stdout(), DisableBracketedPaste)?;
     execute!(stdout(), LeaveAlternateScreen)?;
     disable_raw_mode()?;
@@ -8,11 +8,13 @@
 
 use std::path::PathBuf;
 
+use codex_core::command_wrapper::normalize_wrapped_command;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
+use ratatui::layout::Position;
 use ratatui::layout::Rect;
 use ratatui::prelude::*;
 use ratatui::text::Line;
@@ -39,6 +41,8 @@ pub(crate) enum ApprovalRequest {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        /// Prefix that may be approved for the rest of the session.
+        session_prefix: Option<Vec<String>>,
     },
     ApplyPatch {
         id: String,
@@ -49,45 +53,64 @@ pub(crate) enum ApprovalRequest {
 
 /// Options displayed in the *select* mode.
 struct SelectOption {
-    label: &'static str,
+    label: String,
     decision: Option<ReviewDecision>,
     /// `true` when this option switches the widget to *input* mode.
     enters_input_mode: bool,
 }
 
-// keep in same order as in the TS implementation
-const SELECT_OPTIONS: &[SelectOption] = &[
-    SelectOption {
-        label: "Yes, run this command (y)",
-        decision: Some(ReviewDecision::Approved),
-
-        enters_input_mode: false,
-    },
-    SelectOption {
-        label: "Always allow this command for the remainder of the session (a)",
-        decision: Some(ReviewDecision::ApprovedForSession),
-
-        enters_input_mode: false,
-    },
-    SelectOption {
-        label: "Edit or give feedback (e)",
-        decision: None,
-
-        enters_input_mode: true,
-    },
-    SelectOption {
-        label: "No, and keep going (n)",
-        decision: Some(ReviewDecision::Denied),
-
-        enters_input_mode: false,
-    },
-    SelectOption {
-        label: "No, and stop for now (esc)",
-        decision: Some(ReviewDecision::Abort),
+impl SelectOption {
+    fn new(label: impl Into<String>, decision: Option<ReviewDecision>) -> Self {
+        Self {
+            label: label.into(),
+            decision,
+            enters_input_mode: false,
+        }
+    }
+}
 
-        enters_input_mode: false,
-    },
-];
+// keep in same order as in the TS implementation
+fn select_options(approval_request: &ApprovalRequest) -> Vec<SelectOption> {
+    let mut options = vec![SelectOption::new(
+        "Yes, run this command (y)",
+        Some(ReviewDecision::Approved),
+    )];
+    match approval_request {
+        ApprovalRequest::Exec {
+            command,
+            session_prefix,
+            ..
+        } => {
+            let cmd = strip_bash_lc_and_escape(command);
+            options.push(SelectOption::new(
+                session_scoped_label(&cmd, MAX_SNIPPET_LEN),
+                Some(ReviewDecision::ApprovedForSession),
+            ));
+            if let Some(prefix) = session_prefix {
+                options.push(SelectOption::new(
+                    prefix_scoped_label(&prefix.join(" "), MAX_SNIPPET_LEN),
+                    Some(ReviewDecision::ApprovedPrefixForSession),
+                ));
+            }
+        }
+        ApprovalRequest::ApplyPatch { .. } => {
+            options.push(SelectOption::new(
+                "Always allow this command for the remainder of the session (a)",
+                Some(ReviewDecision::ApprovedForSession),
+            ));
+        }
+    }
+    options.extend([
+        SelectOption {
+            label: "Edit or give feedback (e)".to_string(),
+            decision: None,
+            enters_input_mode: true,
+        },
+        SelectOption::new("No, and keep going (n)", Some(ReviewDecision::Denied)),
+        SelectOption::new("No, and stop for now (esc)", Some(ReviewDecision::Abort)),
+    ]);
+    options
+}
 
 /// Maximum length of the command snippet to display in the session-scoped approval label.
 const MAX_SNIPPET_LEN: usize = 30;
@@ -116,6 +139,12 @@ fn session_scoped_label(cmd: &str, max_len: usize) -> String {
     )
 }
 
+/// Build the label for approving every command that starts with `prefix`.
+fn prefix_scoped_label(prefix: &str, max_len: usize) -> String {
+    let snippet = truncate_middle(prefix, max_len);
+    format!("Yes, always allow `{snippet} ...` for this session (p)")
+}
+
 /// Internal mode the widget is in – mirrors the TypeScript component.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Mode {
@@ -129,6 +158,9 @@ pub(crate) struct UserApprovalWidget<'a> {
     app_event_tx: AppEventSender,
     confirmation_prompt: Paragraph<'a>,
 
+    /// Options shown in *select* mode.
+    select_options: Vec<SelectOption>,
+
     /// Currently selected index in *select* mode.
     selected_option: usize,
 
@@ -157,10 +189,14 @@ impl UserApprovalWidget<'_> {
                 reason,
                 ..
             } => {
-                let cmd = strip_bash_lc_and_escape(command);
+                // Show `bash -lc 'cd dir && make'` as `make` run in `dir`.
+                let (cwd, cmd) = match normalize_wrapped_command(command) {
+                    Some(normalized) => (cwd.join(&normalized.cd), normalized.script),
+                    None => (cwd.clone(), strip_bash_lc_and_escape(command)),
+                };
                 // Maybe try to relativize to the cwd of this process first?
                 // Will make cwd_str shorter in the common case.
-                let cwd_str = match relativize_to_home(cwd) {
+                let cwd_str = match relativize_to_home(&cwd) {
                     Some(rel) => format!("~/{}", rel.display()),
                     None => cwd.display().to_string(),
                 };
@@ -207,6 +243,7 @@ impl UserApprovalWidget<'_> {
         };
 
         Self {
+            select_options: select_options(&approval_request),
             approval_request,
             app_event_tx,
             confirmation_prompt,
@@ -223,7 +260,7 @@ impl UserApprovalWidget<'_> {
 
         match self.mode {
             Mode::Select => {
-                let num_option_lines = SELECT_OPTIONS.len() as u16;
+                let num_option_lines = self.select_options.len() as u16;
                 confirmation_prompt_height + num_option_lines + BORDER_LINES
             }
             Mode::Input => {
@@ -260,13 +297,13 @@ impl UserApprovalWidget<'_> {
         match key_event.code {
             KeyCode::Up => {
                 if self.selected_option == 0 {
-                    self.selected_option = SELECT_OPTIONS.len() - 1;
+                    self.selected_option = self.select_options.len() - 1;
                 } else {
                     self.selected_option -= 1;
                 }
             }
             KeyCode::Down => {
-                self.selected_option = (self.selected_option + 1) % SELECT_OPTIONS.len();
+                self.selected_option = (self.selected_option + 1) % self.select_options.len();
             }
             KeyCode::Char('y') => {
                 self.send_decision(ReviewDecision::Approved);
@@ -274,20 +311,22 @@ impl UserApprovalWidget<'_> {
             KeyCode::Char('a') => {
                 self.send_decision(ReviewDecision::ApprovedForSession);
             }
+            KeyCode::Char('p') => {
+                if self
+                    .select_options
+                    .iter()
+                    .any(|opt| opt.decision == Some(ReviewDecision::ApprovedPrefixForSession))
+                {
+                    self.send_decision(ReviewDecision::ApprovedPrefixForSession);
+                }
+            }
             KeyCode::Char('n') => {
                 self.send_decision(ReviewDecision::Denied);
             }
             KeyCode::Char('e') => {
                 self.mode = Mode::Input;
             }
-            KeyCode::Enter => {
-                let opt = &SELECT_OPTIONS[self.selected_option];
-                if opt.enters_input_mode {
-                    self.mode = Mode::Input;
-                } else if let Some(decision) = opt.decision {
-                    self.send_decision(decision);
-                }
-            }
+            KeyCode::Enter => self.choose_selected_option(),
             KeyCode::Esc => {
                 self.send_decision(ReviewDecision::Abort);
             }
@@ -295,6 +334,33 @@ impl UserApprovalWidget<'_> {
         }
     }
 
+    /// Handle a left click at (`column`, `row`) on the widget rendered in
+    /// `area`. Clicking an option chooses it, as if it had been selected and
+    /// confirmed with Enter.
+    pub(crate) fn handle_mouse_click(&mut self, area: Rect, column: u16, row: u16) {
+        if self.mode != Mode::Select || !area.contains(Position::new(column, row)) {
+            return;
+        }
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let options_top = inner.y + self.get_confirmation_prompt_height(inner.width);
+        let Some(idx) = row.checked_sub(options_top).map(usize::from) else {
+            return;
+        };
+        if idx < self.select_options.len() {
+            self.selected_option = idx;
+            self.choose_selected_option();
+        }
+    }
+
+    fn choose_selected_option(&mut self) {
+        let opt = &self.select_options[self.selected_option];
+        if opt.enters_input_mode {
+            self.mode = Mode::Input;
+        } else if let Some(decision) = opt.decision {
+            self.send_decision(decision);
+        }
+    }
+
     fn handle_input_key(&mut self, key_event: KeyEvent) {
         // Handle special keys first.
         match key_event.code {
@@ -304,7 +370,11 @@ impl UserApprovalWidget<'_> {
             }
             KeyCode::Esc => {
                 self.mode = Mode::Select;
-                if let Some(idx) = SELECT_OPTIONS.iter().position(|opt| opt.enters_input_mode) {
+                if let Some(idx) = self
+                    .select_options
+                    .iter()
+                    .position(|opt| opt.enters_input_mode)
+                {
                     self.selected_option = idx;
                 }
             }
@@ -374,35 +444,19 @@ impl WidgetRef for &UserApprovalWidget<'_> {
         // non-wrapping lines rather than a Paragraph because get_height(Rect)
         // depends on this behavior for its calculation.
         let lines = match self.mode {
-            Mode::Select => {
-                // Generate a dynamic label for session-scoped approval when executing a shell command.
-                let dynamic_label = match &self.approval_request {
-                    ApprovalRequest::Exec { command, .. } => {
-                        let cmd_str = strip_bash_lc_and_escape(command);
-                        Some(session_scoped_label(&cmd_str, MAX_SNIPPET_LEN))
-                    }
-                    _ => None,
-                };
-                SELECT_OPTIONS
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, opt)| {
-                        let label = if idx == 1 {
-                            dynamic_label
-                                .clone()
-                                .unwrap_or_else(|| opt.label.to_string())
-                        } else {
-                            opt.label.to_string()
-                        };
-                        let (prefix, style) = if idx == self.selected_option {
-                            ("▶", BLUE_FG)
-                        } else {
-                            (" ", PLAIN)
-                        };
-                        Line::styled(format!("  {prefix} {}", label), style)
-                    })
-                    .collect()
-            }
+            Mode::Select => self
+                .select_options
+                .iter()
+                .enumerate()
+                .map(|(idx, opt)| {
+                    let (prefix, style) = if idx == self.selected_option {
+                        ("▶", BLUE_FG)
+                    } else {
+                        (" ", PLAIN)
+                    };
+                    Line::styled(format!("  {prefix} {}", opt.label), style)
+                })
+                .collect(),
             Mode::Input => {
                 vec![
                     Line::from("Give the model feedback on this command:"),
@@ -443,6 +497,7 @@ mod tests {
                 command: Vec::new(),
                 cwd: std::env::current_dir().unwrap(),
                 reason: None,
+                session_prefix: None,
             },
             app_event_tx.clone(),
         );
@@ -453,7 +508,8 @@ mod tests {
         }
         widget.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
         assert_eq!(widget.mode, Mode::Select);
-        let expected_idx = SELECT_OPTIONS
+        let expected_idx = widget
+            .select_options
             .iter()
             .position(|opt| opt.enters_input_mode)
             .unwrap();
@@ -463,6 +519,75 @@ mod tests {
         assert!(!widget.done);
     }
 
+    #[test]
+    fn prefix_option_is_offered_only_with_a_session_prefix() {
+        let (tx, rx) = mpsc::channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let request = |session_prefix: Option<Vec<String>>| ApprovalRequest::Exec {
+            id: "id".into(),
+            command: vec!["cargo".into(), "test".into()],
+            cwd: std::env::current_dir().unwrap(),
+            reason: None,
+            session_prefix,
+        };
+
+        let mut widget = UserApprovalWidget::new(request(None), app_event_tx.clone());
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        assert!(!widget.is_complete());
+
+        let mut widget = UserApprovalWidget::new(request(Some(vec!["cargo".into()])), app_event_tx);
+        assert!(
+            widget
+                .select_options
+                .iter()
+                .any(|opt| opt.label == "Yes, always allow `cargo ...` for this session (p)")
+        );
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        assert!(widget.is_complete());
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            AppEvent::CodexOp(Op::ExecApproval {
+                decision: ReviewDecision::ApprovedPrefixForSession,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn clicking_an_option_chooses_it() {
+        let (tx, rx) = mpsc::channel();
+        let mut widget = UserApprovalWidget::new(
+            ApprovalRequest::ApplyPatch {
+                id: "id".into(),
+                reason: None,
+                grant_root: None,
+            },
+            AppEventSender::new(tx),
+        );
+        let area = Rect::new(0, 10, 60, 0);
+        let area = Rect {
+            height: widget.get_height(&area),
+            ..area
+        };
+        let options_top = area.bottom() - 1 - widget.select_options.len() as u16;
+
+        // Clicks on the prompt or outside the widget are ignored.
+        widget.handle_mouse_click(area, 5, options_top - 1);
+        widget.handle_mouse_click(area, 5, area.bottom());
+        assert!(!widget.is_complete());
+
+        // Second option: "Always allow ... (a)".
+        widget.handle_mouse_click(area, 5, options_top + 1);
+        assert!(widget.is_complete());
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            AppEvent::CodexOp(Op::PatchApproval {
+                decision: ReviewDecision::ApprovedForSession,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_truncate_middle_shorter_or_equal() {
         assert_eq!(truncate_middle("short", 10), "short");
@@ -499,6 +624,7 @@ mod tests {
                 command: vec!["cmd".into()],
                 cwd: std::env::current_dir().unwrap(),
                 reason: None,
+                session_prefix: None,
             },
             app_event_tx,
         );
@@ -0,0 +1,283 @@
+//! Word wrapping for history cells.
+//!
+//! ratatui's `Wrap` breaks long lines but starts every continuation line at
+//! column 0, so wrapped list items and blockquotes lose their shape. This
+//! module reflows lines at word boundaries and repeats the indentation (and
+//! any blockquote bar) of the first line on each continuation line.
+
+use ratatui::layout::Alignment;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+
+/// Reflow `lines` so that none is wider than `width` columns.
+pub(crate) fn wrap_lines(lines: &[Line<'static>], width: u16) -> Vec<Line<'static>> {
+    let width = usize::from(width);
+    let mut out = Vec::with_capacity(lines.len());
+    for line in lines {
+        if width == 0 || line.width() <= width {
+            out.push(line.clone());
+        } else {
+            wrap_line(line, width, &mut out);
+        }
+    }
+    out
+}
+
+/// A run of whitespace or a word. Words may consist of several differently
+/// styled pieces (e.g. `foo**bar**`) but are never broken at a style change.
+enum Token {
+    Space(Vec<(String, Style)>),
+    Word(Vec<(String, Style)>),
+}
+
+fn wrap_line(line: &Line<'static>, width: usize, out: &mut Vec<Line<'static>>) {
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    let mut indent = continuation_indent(&text);
+    // A hanging indent that leaves almost no room for text is worse than none.
+    if indent.width() * 2 > width {
+        indent.clear();
+    }
+
+    let mut builder = LineBuilder::new(line, width, indent);
+    for token in tokenize(line) {
+        match token {
+            Token::Space(pieces) => builder.pending.extend(pieces),
+            Token::Word(pieces) => {
+                let word_width: usize = pieces.iter().map(|(s, _)| s.width()).sum();
+                if !builder.fits(word_width) && builder.has_content {
+                    builder.break_line(out);
+                }
+                if builder.fits(word_width) {
+                    builder.push(pieces);
+                    continue;
+                }
+                // The word is wider than a whole line: break it anywhere.
+                for (piece, style) in pieces {
+                    for ch in piece.chars() {
+                        if !builder.fits(ch.width().unwrap_or(0)) && builder.has_content {
+                            builder.break_line(out);
+                        }
+                        builder.push(vec![(ch.to_string(), style)]);
+                    }
+                }
+            }
+        }
+    }
+    builder.finish(out);
+}
+
+fn tokenize(line: &Line<'static>) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    for span in &line.spans {
+        for ch in span.content.chars() {
+            let is_space = ch.is_whitespace();
+            let continues_token = matches!(
+                (tokens.last(), is_space),
+                (Some(Token::Space(_)), true) | (Some(Token::Word(_)), false)
+            );
+            if !continues_token {
+                tokens.push(if is_space {
+                    Token::Space(Vec::new())
+                } else {
+                    Token::Word(Vec::new())
+                });
+            }
+            let Some(Token::Space(pieces) | Token::Word(pieces)) = tokens.last_mut() else {
+                continue;
+            };
+            match pieces.last_mut() {
+                Some((piece, style)) if *style == span.style => piece.push(ch),
+                _ => pieces.push((ch.to_string(), span.style)),
+            }
+        }
+    }
+    tokens
+}
+
+/// Prefix for continuation lines: leading whitespace, blockquote bars and
+/// the width of a list marker (`- `, `1. `, `☐ `) turned into spaces.
+fn continuation_indent(text: &str) -> String {
+    let mut indent = String::new();
+    let mut rest = text;
+    loop {
+        let trimmed = rest.trim_start_matches(' ');
+        indent.push_str(&rest[..rest.len() - trimmed.len()]);
+        rest = trimmed;
+        match rest.chars().next() {
+            Some(c @ ('>' | '│' | '▌')) => {
+                indent.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+            _ => break,
+        }
+    }
+    let marker_len = list_marker_len(rest);
+    indent.push_str(&" ".repeat(rest[..marker_len].width()));
+    indent
+}
+
+/// Byte length of a list marker (including the trailing space) at the start
+/// of `text`, or 0 if there is none.
+fn list_marker_len(text: &str) -> usize {
+    let bullet = match text.chars().next() {
+        Some(c @ ('-' | '*' | '+' | '•')) => c.len_utf8(),
+        Some(c) if c.is_ascii_digit() => {
+            let digits = text.chars().take_while(char::is_ascii_digit).count();
+            match text[digits..].chars().next() {
+                Some('.' | ')') => digits + 1,
+                _ => return 0,
+            }
+        }
+        _ => return 0,
+    };
+    if !text[bullet..].starts_with(' ') {
+        return 0;
+    }
+    let mut len = bullet + 1;
+    for checkbox in ["☐ ", "☑ "] {
+        if text[len..].starts_with(checkbox) {
+            len += checkbox.len();
+        }
+    }
+    len
+}
+
+struct LineBuilder {
+    width: usize,
+    style: Style,
+    alignment: Option<Alignment>,
+    indent: String,
+    spans: Vec<Span<'static>>,
+    used: usize,
+    /// Whether the current output line has any words on it yet.
+    has_content: bool,
+    /// Still on the first output line, whose leading whitespace is kept.
+    first_line: bool,
+    /// Whitespace seen since the last word; emitted only if another word
+    /// follows on the same line.
+    pending: Vec<(String, Style)>,
+}
+
+impl LineBuilder {
+    fn new(line: &Line<'static>, width: usize, indent: String) -> Self {
+        Self {
+            width,
+            style: line.style,
+            alignment: line.alignment,
+            indent,
+            spans: Vec::new(),
+            used: 0,
+            has_content: false,
+            first_line: true,
+            pending: Vec::new(),
+        }
+    }
+
+    fn keeps_pending(&self) -> bool {
+        self.has_content || self.first_line
+    }
+
+    fn fits(&self, word_width: usize) -> bool {
+        let pending_width: usize = if self.keeps_pending() {
+            self.pending.iter().map(|(s, _)| s.width()).sum()
+        } else {
+            0
+        };
+        self.used + pending_width + word_width <= self.width
+    }
+
+    fn push(&mut self, pieces: Vec<(String, Style)>) {
+        let pending = std::mem::take(&mut self.pending);
+        if self.keeps_pending() {
+            for (space, style) in pending {
+                self.used += space.width();
+                self.spans.push(Span::styled(space, style));
+            }
+        }
+        for (piece, style) in pieces {
+            self.used += piece.width();
+            self.spans.push(Span::styled(piece, style));
+        }
+        self.has_content = true;
+    }
+
+    fn break_line(&mut self, out: &mut Vec<Line<'static>>) {
+        self.finish(out);
+        // Whitespace at a break point is dropped.
+        self.pending.clear();
+        self.first_line = false;
+        self.has_content = false;
+        self.used = self.indent.width();
+        if !self.indent.is_empty() {
+            self.spans.push(Span::raw(self.indent.clone()));
+        }
+    }
+
+    fn finish(&mut self, out: &mut Vec<Line<'static>>) {
+        let mut line = Line::from(std::mem::take(&mut self.spans)).style(self.style);
+        line.alignment = self.alignment;
+        out.push(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use ratatui::style::Modifier;
+
+    fn texts(lines: &[Line<'static>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        let lines = vec![Line::from("the quick brown fox jumps")];
+        assert_eq!(
+            texts(&wrap_lines(&lines, 10)),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+    }
+
+    #[test]
+    fn list_items_keep_hanging_indent() {
+        let lines = vec![Line::from("  - alpha beta gamma delta")];
+        assert_eq!(
+            texts(&wrap_lines(&lines, 14)),
+            vec!["  - alpha beta", "    gamma", "    delta"]
+        );
+    }
+
+    #[test]
+    fn blockquote_prefix_is_repeated() {
+        let lines = vec![Line::from("> one two three four")];
+        assert_eq!(
+            texts(&wrap_lines(&lines, 10)),
+            vec!["> one two", "> three", "> four"]
+        );
+    }
+
+    #[test]
+    fn long_words_are_broken_and_styles_kept() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let lines = vec![Line::from(vec![
+            Span::raw("ab "),
+            Span::styled("cdefghij", bold),
+        ])];
+        let wrapped = wrap_lines(&lines, 4);
+        assert_eq!(texts(&wrapped), vec!["ab", "cdef", "ghij"]);
+        assert!(wrapped[1].spans.iter().all(|s| s.style == bold));
+    }
+
+    #[test]
+    fn short_lines_are_untouched() {
+        let lines = vec![Line::from("  fits")];
+        assert_eq!(wrap_lines(&lines, 80), lines);
+    }
+}